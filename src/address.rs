@@ -0,0 +1,237 @@
+//! Decoding Monero addresses offline, so a mistyped destination is caught
+//! before the wallet RPC sees it.
+//!
+//! An address is a network tag, the public spend and view keys, an 8-byte
+//! payment ID for integrated addresses, and the first 4 bytes of the
+//! Keccak-256 hash of all that. It is written in Monero's own base58, which
+//! encodes 8-byte blocks as 11 characters each, not the whole number at once
+//! as Bitcoin's does.
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Network;
+use crate::error::TransactionError;
+
+/// What an address pays to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressKind {
+    /// An account's primary address.
+    Standard,
+    /// A standard address with a payment ID folded in.
+    Integrated,
+    Subaddress,
+}
+
+impl std::fmt::Display for AddressKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddressKind::Standard => write!(f, "standard address"),
+            AddressKind::Integrated => write!(f, "integrated address"),
+            AddressKind::Subaddress => write!(f, "subaddress"),
+        }
+    }
+}
+
+/// Network tags, as in monero's `cryptonote_config.h`.
+const TAGS: [(u8, Network, AddressKind); 9] = [
+    (18, Network::Mainnet, AddressKind::Standard),
+    (19, Network::Mainnet, AddressKind::Integrated),
+    (42, Network::Mainnet, AddressKind::Subaddress),
+    (53, Network::Testnet, AddressKind::Standard),
+    (54, Network::Testnet, AddressKind::Integrated),
+    (63, Network::Testnet, AddressKind::Subaddress),
+    (24, Network::Stagenet, AddressKind::Standard),
+    (25, Network::Stagenet, AddressKind::Integrated),
+    (36, Network::Stagenet, AddressKind::Subaddress),
+];
+
+/// Tag, two 32-byte keys and the checksum.
+const STANDARD_LEN: usize = 1 + 64 + 4;
+/// The same plus the payment ID.
+const INTEGRATED_LEN: usize = STANDARD_LEN + 8;
+
+/// Decode `address` and check its checksum, returning the network and kind
+/// its tag names. Fails with [`TransactionError::InvalidAddress`] saying
+/// what is wrong.
+pub fn decode(address: &str) -> Result<(Network, AddressKind), TransactionError> {
+    let invalid = |why: &str| TransactionError::InvalidAddress(format!("{address}: {why}"));
+    let bytes = base58_decode(address).ok_or_else(|| invalid("not valid base58"))?;
+    let (&tag, _) = bytes.split_first().ok_or_else(|| invalid("empty"))?;
+    let &(_, network, kind) = TAGS
+        .iter()
+        .find(|(t, _, _)| *t == tag)
+        .ok_or_else(|| invalid("unknown network tag"))?;
+    let expected = match kind {
+        AddressKind::Integrated => INTEGRATED_LEN,
+        _ => STANDARD_LEN,
+    };
+    if bytes.len() != expected {
+        return Err(invalid(&format!("wrong length for a {network} {kind}")));
+    }
+    let (body, checksum) = bytes.split_at(bytes.len() - 4);
+    if keccak256(body)[..4] != *checksum {
+        return Err(invalid("checksum mismatch, probably a typo"));
+    }
+    Ok((network, kind))
+}
+
+const ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Characters used to encode a block of 0 to 8 bytes.
+const ENCODED_BLOCK_LEN: [usize; 9] = [0, 2, 3, 5, 6, 7, 9, 10, 11];
+
+fn base58_decode(text: &str) -> Option<Vec<u8>> {
+    let text = text.as_bytes();
+    let mut out = Vec::with_capacity(text.len() * 8 / 11);
+    for block in text.chunks(11) {
+        let size = ENCODED_BLOCK_LEN.iter().position(|&n| n == block.len())?;
+        let mut value: u64 = 0;
+        for &c in block {
+            let digit = ALPHABET.iter().position(|&a| a == c)? as u64;
+            value = value.checked_mul(58)?.checked_add(digit)?;
+        }
+        if size < 8 && value >> (8 * size) != 0 {
+            return None;
+        }
+        out.extend_from_slice(&value.to_be_bytes()[8 - size..]);
+    }
+    Some(out)
+}
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+const ROTATIONS: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+const LANES: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+fn keccak_f(state: &mut [u64; 25]) {
+    for round_constant in ROUND_CONSTANTS {
+        let mut columns = [0u64; 5];
+        for (x, column) in columns.iter_mut().enumerate() {
+            *column = (0..25).step_by(5).fold(0, |acc, y| acc ^ state[x + y]);
+        }
+        for x in 0..5 {
+            let t = columns[(x + 4) % 5] ^ columns[(x + 1) % 5].rotate_left(1);
+            for y in (0..25).step_by(5) {
+                state[x + y] ^= t;
+            }
+        }
+        let mut carried = state[1];
+        for (&lane, &rotation) in LANES.iter().zip(&ROTATIONS) {
+            let next = state[lane];
+            state[lane] = carried.rotate_left(rotation);
+            carried = next;
+        }
+        for y in (0..25).step_by(5) {
+            let row: [u64; 5] = state[y..y + 5].try_into().unwrap();
+            for x in 0..5 {
+                state[y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+        state[0] ^= round_constant;
+    }
+}
+
+/// Keccak-256 with the original padding Monero uses, not SHA3-256's.
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    const RATE: usize = 136;
+    let mut state = [0u64; 25];
+    let mut padded = data.to_vec();
+    padded.push(0x01);
+    padded.resize(padded.len().div_ceil(RATE) * RATE, 0);
+    *padded.last_mut().unwrap() |= 0x80;
+    for block in padded.chunks(RATE) {
+        for (lane, word) in state.iter_mut().zip(block.chunks(8)) {
+            *lane ^= u64::from_le_bytes(word.try_into().unwrap());
+        }
+        keccak_f(&mut state);
+    }
+    let mut out = [0u8; 32];
+    for (chunk, lane) in out.chunks_mut(8).zip(state) {
+        chunk.copy_from_slice(&lane.to_le_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keccak256() {
+        assert_eq!(
+            hex::encode(keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hex::encode(keccak256(b"The quick brown fox jumps over the lazy dog")),
+            "4d741b6f1eb29cb2a9b9911c82f56fa8d73b04959d3d9d222895df6c0b28aa15"
+        );
+    }
+
+    #[test]
+    fn test_decode_every_kind() {
+        for (address, network, kind) in [
+            // The donation subaddress and the example address from the docs.
+            ("888tNkZrPN6JsEgekjMnABU4TBzc2Dt29EPAvkRxbANsAnjyPbb3iQ1YBRk1UXcdRsiKc9dhwMVgN5S9cQUiyoogDavup3H", Network::Mainnet, AddressKind::Subaddress),
+            ("4AdUndXHHZ6cfufTMvppY6JwXNouMBzSkbLYfpAV5Usx3skxNgYeYTRj5UzqtReoS44qo9mtmXCqY45DJ852K5Jv2684Rge", Network::Mainnet, AddressKind::Standard),
+            ("4FVpMEAzeUPUT1aYwq1vBmaoLa4BbiykWQuaezmn1PXPHGktAeoR1vwgMQzE7aWffp8TEVCCyAY7QT4FJSUQUGSmZAToASqHxJVPosKbDS", Network::Mainnet, AddressKind::Integrated),
+            ("53QjBsa9cjxesZJQJfXBV5K2KvBmtgw3WXPzqsENUZzVdKR2wY96911DGendcjpn4dXeYYrf3inwHJ7XhKQNpc8jGPHc2EK", Network::Stagenet, AddressKind::Standard),
+            ("7AD81zednq2W2s76XGVAH5dsLRy1V6RFZYSbkaiqXdYFd8A32T93XRJ8cnZxiVhKY85stEkq8ReFU9BUMWjDMGX29SisBKm", Network::Stagenet, AddressKind::Subaddress),
+            ("9tAopxsiizcVcwF5fLgSsHMkgjv7Uf1yyZgbLzombxwrBQ3eKrBbbFAQJ9SNnrcqKn4rADMYR8D2S24VM1vWTQyeKa2cLfX", Network::Testnet, AddressKind::Standard),
+            ("A5cekvf144BckN25LTia1aF2yVboJMQiQhJpuN6TnVy21Phk9qvqPCoLLXvaZP9LJ6WAxSfTciNaZetvpQLCuKeiVz91u7GvQDfPooDmVJ", Network::Testnet, AddressKind::Integrated),
+        ] {
+            assert_eq!(decode(address).unwrap(), (network, kind), "{address}");
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_typos() {
+        let good = "4AdUndXHHZ6cfufTMvppY6JwXNouMBzSkbLYfpAV5Usx3skxNgYeYTRj5UzqtReoS44qo9mtmXCqY45DJ852K5Jv2684Rge";
+        let message = |address: &str| decode(address).unwrap_err().to_string();
+
+        let typo = good.replacen("Und", "Unb", 1);
+        assert!(
+            message(&typo).contains("checksum mismatch"),
+            "{}",
+            message(&typo)
+        );
+        // 'l' isn't in the alphabet.
+        assert!(message(&good.replacen('U', "l", 1)).contains("not valid base58"));
+        assert!(message(&good[..94]).contains("wrong length"));
+        // An integrated address short of its payment ID.
+        let integrated = "4FVpMEAzeUPUT1aYwq1vBmaoLa4BbiykWQuaezmn1PXPHGktAeoR1vwgMQzE7aWffp8TEVCCyAY7QT4FJSUQUGSmZAToASqHxJVPosKbDS";
+        assert!(
+            message(&integrated[..99]).contains("wrong length for a mainnet integrated address")
+        );
+        assert!(decode(&"4".repeat(95)).is_err());
+    }
+}
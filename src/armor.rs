@@ -0,0 +1,342 @@
+//! ASCII armor for multisig blobs.
+//!
+//! Armored blobs are easier to move between machines by hand (USB stick, QR,
+//! email) and carry their kind in the header so the wrong blob can't be fed
+//! to the wrong command:
+//!
+//! ```text
+//! -----BEGIN MONERO MULTISIG TX SET-----
+//! Comment: built by alice
+//!
+//! 4d6f6e65726f206d756c74697369672074782073657420646174612e2e2e
+//! -----END MONERO MULTISIG TX SET-----
+//! ```
+//!
+//! Header lines are optional; the payload is wrapped at [`LINE_WIDTH`].
+//! `export-info` writes an [`ExportHeader`] into its armor, saying when, at
+//! what wallet height and by whom the multisig info was exported.
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+/// Payload characters per armored line.
+pub const LINE_WIDTH: usize = 64;
+
+const DASHES: &str = "-----";
+
+/// Hyphen and the dashes word processors and mail clients turn hyphens into.
+const DASH_LIKE: &[char] = &[
+    '-', '\u{2010}', '\u{2011}', '\u{2012}', '\u{2013}', '\u{2014}', '\u{2015}', '\u{2212}',
+];
+
+/// The kind of blob carried inside an armor block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlobKind {
+    /// A multisig transaction set (unsigned or partially signed).
+    TxSet,
+    /// Partial key images from `export_multisig_info`.
+    MultisigInfo,
+}
+
+impl BlobKind {
+    fn label(self) -> &'static str {
+        match self {
+            BlobKind::TxSet => "MONERO MULTISIG TX SET",
+            BlobKind::MultisigInfo => "MONERO MULTISIG INFO",
+        }
+    }
+
+    fn from_label(label: &str) -> Option<Self> {
+        [BlobKind::TxSet, BlobKind::MultisigInfo]
+            .into_iter()
+            .find(|k| k.label() == label)
+    }
+}
+
+impl fmt::Display for BlobKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlobKind::TxSet => write!(f, "tx set"),
+            BlobKind::MultisigInfo => write!(f, "multisig info"),
+        }
+    }
+}
+
+/// A parsed armor block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Armored {
+    pub kind: BlobKind,
+    pub headers: Vec<(String, String)>,
+    pub payload: String,
+}
+
+/// Errors from parsing an armor block.
+#[derive(Debug, thiserror::Error)]
+pub enum ArmorError {
+    #[error("unknown armor type \"{0}\"")]
+    UnknownKind(String),
+
+    #[error("armor block is missing its END line")]
+    MissingEnd,
+
+    #[error("armor END line does not match BEGIN line")]
+    MismatchedEnd,
+
+    #[error("expected a {expected} but got a {found}")]
+    WrongKind { expected: BlobKind, found: BlobKind },
+}
+
+/// What `export-info` says about a multisig info blob in its armor headers.
+/// The blob itself is encrypted, so this is the only way the receiver can
+/// tell how fresh it is.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportHeader {
+    pub created: Option<DateTime<Utc>>,
+    /// Wallet height at export.
+    pub height: Option<u64>,
+    /// Who exported it.
+    pub from: Option<String>,
+}
+
+impl ExportHeader {
+    const CREATED: &'static str = "Created";
+    const HEIGHT: &'static str = "Height";
+    const FROM: &'static str = "From";
+
+    /// The header lines to armor the blob with.
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        let mut headers = Vec::new();
+        if let Some(created) = self.created {
+            headers.push((
+                Self::CREATED,
+                created.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            ));
+        }
+        if let Some(height) = self.height {
+            headers.push((Self::HEIGHT, height.to_string()));
+        }
+        if let Some(from) = &self.from {
+            headers.push((Self::FROM, from.clone()));
+        }
+        headers
+    }
+
+    /// Read the header back from parsed armor headers. Other headers, and
+    /// values that don't parse, are ignored.
+    pub fn parse(headers: &[(String, String)]) -> Self {
+        let get = |key: &str| {
+            headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v.trim())
+        };
+        ExportHeader {
+            created: get(Self::CREATED)
+                .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+                .map(|t| t.with_timezone(&Utc)),
+            height: get(Self::HEIGHT).and_then(|v| v.parse().ok()),
+            from: get(Self::FROM).filter(|v| !v.is_empty()).map(String::from),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == ExportHeader::default()
+    }
+}
+
+/// Wrap `payload` in an armor block with optional `Key: Value` headers.
+pub fn armor(kind: BlobKind, headers: &[(&str, &str)], payload: &str) -> String {
+    let mut out = format!("{DASHES}BEGIN {}{DASHES}\n", kind.label());
+    for (key, value) in headers {
+        out.push_str(&format!("{key}: {value}\n"));
+    }
+    if !headers.is_empty() {
+        out.push('\n');
+    }
+    let payload = payload.trim();
+    for chunk in payload.as_bytes().chunks(LINE_WIDTH) {
+        // Payloads are hex, so chunking on bytes never splits a character.
+        out.push_str(&String::from_utf8_lossy(chunk));
+        out.push('\n');
+    }
+    out.push_str(&format!("{DASHES}END {}{DASHES}", kind.label()));
+    out
+}
+
+/// Parse the first armor block in `text`, ignoring any surrounding lines (so
+/// a whole saved command output or email body can be passed in). Returns
+/// `Ok(None)` when `text` contains no armor at all.
+pub fn dearmor(text: &str) -> Result<Option<Armored>, ArmorError> {
+    let mut lines = text.lines().map(str::trim);
+    let Some(label) = lines.by_ref().find_map(|l| {
+        l.strip_prefix(DASHES)?
+            .strip_prefix("BEGIN ")?
+            .strip_suffix(DASHES)
+    }) else {
+        return Ok(None);
+    };
+    let kind = BlobKind::from_label(label).ok_or_else(|| ArmorError::UnknownKind(label.into()))?;
+
+    let mut headers = Vec::new();
+    let mut payload = String::new();
+    let mut in_headers = true;
+    for line in lines {
+        if let Some(end) = line
+            .strip_prefix(DASHES)
+            .and_then(|l| l.strip_prefix("END "))
+        {
+            if end.strip_suffix(DASHES) != Some(label) {
+                return Err(ArmorError::MismatchedEnd);
+            }
+            return Ok(Some(Armored {
+                kind,
+                headers,
+                payload,
+            }));
+        }
+        if in_headers {
+            if line.is_empty() {
+                in_headers = false;
+                continue;
+            }
+            if let Some((key, value)) = line.split_once(": ") {
+                headers.push((key.to_string(), value.to_string()));
+                continue;
+            }
+            in_headers = false;
+        }
+        payload.push_str(line);
+    }
+    Err(ArmorError::MissingEnd)
+}
+
+/// `line` rewritten as a proper BEGIN or END line, if it is one whose dashes
+/// or spaces were mangled in transit (`—–BEGIN MONERO MULTISIG TX SET—–`).
+/// Returns `None` for other lines and for boundary lines already intact.
+pub fn repair_boundary(line: &str) -> Option<String> {
+    let trimmed = line.trim();
+    let inner = trimmed.trim_start_matches(DASH_LIKE);
+    if inner.len() == trimmed.len() {
+        return None;
+    }
+    let core = inner.trim_end_matches(DASH_LIKE);
+    if core.len() == inner.len() {
+        return None;
+    }
+    let core = core.replace('\u{a0}', " ");
+    if !core.starts_with("BEGIN ") && !core.starts_with("END ") {
+        return None;
+    }
+    let repaired = format!("{DASHES}{core}{DASHES}");
+    (repaired != trimmed).then_some(repaired)
+}
+
+/// Return the payload of `text`, unwrapping armor of the expected kind if
+/// present. Unarmored input is returned trimmed, unchanged.
+pub fn unwrap(text: &str, expected: BlobKind) -> Result<String, ArmorError> {
+    match dearmor(text)? {
+        Some(a) if a.kind == expected => Ok(a.payload),
+        Some(a) => Err(ArmorError::WrongKind {
+            expected,
+            found: a.kind,
+        }),
+        None => Ok(text.trim().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_with_headers() {
+        let payload = "ab".repeat(100);
+        let text = armor(BlobKind::TxSet, &[("Comment", "from alice")], &payload);
+        assert!(text
+            .lines()
+            .all(|l| l.len() <= LINE_WIDTH || l.starts_with(DASHES)));
+
+        let parsed = dearmor(&text).unwrap().unwrap();
+        assert_eq!(parsed.kind, BlobKind::TxSet);
+        assert_eq!(
+            parsed.headers,
+            vec![("Comment".into(), "from alice".into())]
+        );
+        assert_eq!(parsed.payload, payload);
+    }
+
+    #[test]
+    fn test_armor_inside_surrounding_text() {
+        let text = format!(
+            "Transaction built successfully:\n  Hash: ff\n\n{}\n\nTx set fingerprint: x",
+            armor(BlobKind::TxSet, &[], "abcd")
+        );
+        assert_eq!(unwrap(&text, BlobKind::TxSet).unwrap(), "abcd");
+    }
+
+    #[test]
+    fn test_unarmored_passthrough() {
+        assert!(dearmor("deadbeef").unwrap().is_none());
+        assert_eq!(unwrap("  deadbeef\n", BlobKind::TxSet).unwrap(), "deadbeef");
+    }
+
+    #[test]
+    fn test_wrong_kind_rejected() {
+        let text = armor(BlobKind::MultisigInfo, &[], "abcd");
+        assert!(matches!(
+            unwrap(&text, BlobKind::TxSet),
+            Err(ArmorError::WrongKind { .. })
+        ));
+    }
+
+    #[test]
+    fn test_repair_boundary() {
+        assert_eq!(
+            repair_boundary("\u{2014}\u{2013}BEGIN MONERO MULTISIG TX SET\u{2014}\u{2013}")
+                .as_deref(),
+            Some("-----BEGIN MONERO MULTISIG TX SET-----")
+        );
+        assert_eq!(
+            repair_boundary("---END\u{a0}MONERO MULTISIG INFO---").as_deref(),
+            Some("-----END MONERO MULTISIG INFO-----")
+        );
+        assert_eq!(
+            repair_boundary("-----BEGIN MONERO MULTISIG TX SET-----"),
+            None
+        );
+        assert_eq!(repair_boundary("-----"), None);
+        assert_eq!(repair_boundary("abcd"), None);
+    }
+
+    #[test]
+    fn test_export_header_round_trip() {
+        let header = ExportHeader {
+            created: Some("2026-10-15T09:30:00Z".parse().unwrap()),
+            height: Some(3_100_200),
+            from: Some("alice".into()),
+        };
+        let headers = header.headers();
+        let borrowed: Vec<(&str, &str)> = headers.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        let text = armor(BlobKind::MultisigInfo, &borrowed, "abcd");
+        assert!(text.contains("Created: 2026-10-15T09:30:00Z\nHeight: 3100200\nFrom: alice\n"));
+
+        let parsed = dearmor(&text).unwrap().unwrap();
+        assert_eq!(ExportHeader::parse(&parsed.headers), header);
+        assert_eq!(parsed.payload, "abcd");
+
+        // Headers from elsewhere, or mangled ones, are left out.
+        let other = vec![
+            ("Comment".to_string(), "hi".to_string()),
+            ("Height".to_string(), "tall".to_string()),
+        ];
+        assert!(ExportHeader::parse(&other).is_empty());
+    }
+
+    #[test]
+    fn test_truncated_armor_rejected() {
+        let text = armor(BlobKind::TxSet, &[], "abcd");
+        let truncated = text.lines().take(2).collect::<Vec<_>>().join("\n");
+        assert!(matches!(dearmor(&truncated), Err(ArmorError::MissingEnd)));
+    }
+}
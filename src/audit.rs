@@ -0,0 +1,304 @@
+//! Hash-chained audit log of state-changing commands.
+//!
+//! Every command that takes the exclusive data directory lock appends one
+//! JSON line to `data_dir/audit.log`: when it ran, who ran it, its arguments
+//! with secrets redacted, how it ended, the wallet state transition, the tx
+//! hashes of journal entries it touched, and anything it asked the operator
+//! to confirm. Each line's `hash` is the SHA-256 of the previous line
+//! followed by this line without its `hash`, so editing, removing or
+//! reordering lines breaks the chain from that point on, and
+//! [`verify`] finds the first broken link.
+//!
+//! Lines are written with a single append and fsync'd while the command still
+//! holds the exclusive lock, so two invocations never interleave and no line
+//! is lost once the command has exited.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::DataDir;
+use crate::error::Result;
+use crate::journal::Journal;
+
+const AUDIT_FILE: &str = "audit.log";
+
+/// One line of the audit log, without its chain hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub at: DateTime<Utc>,
+    pub command: String,
+    /// Command line arguments, redacted by [`crate::utils::redact_args`].
+    pub args: Vec<String>,
+    pub user: String,
+    pub host: String,
+    /// `ok`, or the error the command failed with.
+    pub outcome: String,
+    /// Wallet stage before and after, when the command changed it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transition: Option<Transition>,
+    /// Latest tx hash of each journal entry the command added or changed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tx_hashes: Vec<String>,
+    /// What the command showed the operator before acting, such as the
+    /// transaction `submit-tx` asked them to confirm.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<serde_json::Value>,
+}
+
+/// A change of wallet stage; `None` means there was no wallet state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transition {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// What the audit log compares before and after a command.
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    stage: Option<String>,
+    /// Journal entries by id, serialized.
+    entries: BTreeMap<u64, String>,
+    hashes: BTreeMap<u64, Option<String>>,
+}
+
+impl Snapshot {
+    /// Read the wallet stage and journal. Unreadable files count as absent:
+    /// the audit log records what happened, not whether the files are sound.
+    pub fn capture(data_dir: &DataDir) -> Self {
+        let stage = crate::wallet::load_state_unverified(data_dir)
+            .ok()
+            .map(|s| s.wallet.stage());
+        let mut snapshot = Snapshot {
+            stage,
+            ..Snapshot::default()
+        };
+        if let Ok(journal) = Journal::load(data_dir) {
+            for entry in &journal.entries {
+                let text = serde_json::to_string(entry).unwrap_or_default();
+                snapshot.entries.insert(entry.id, text);
+                snapshot
+                    .hashes
+                    .insert(entry.id, entry.tx_hashes.last().cloned());
+            }
+        }
+        snapshot
+    }
+}
+
+impl AuditRecord {
+    /// Describe a finished command from the snapshots taken around it.
+    pub fn new(
+        command: &str,
+        args: Vec<String>,
+        outcome: std::result::Result<(), String>,
+        before: &Snapshot,
+        after: &Snapshot,
+    ) -> Self {
+        let transition = (before.stage != after.stage).then(|| Transition {
+            from: before.stage.clone(),
+            to: after.stage.clone(),
+        });
+        let tx_hashes = after
+            .entries
+            .iter()
+            .filter(|(id, text)| before.entries.get(id) != Some(text))
+            .filter_map(|(id, _)| after.hashes.get(id).cloned().flatten())
+            .collect();
+        let host = gethostname::gethostname().to_string_lossy().into_owned();
+        let identity = crate::utils::identity();
+        let user = identity
+            .strip_suffix(&format!("@{host}"))
+            .unwrap_or(&identity)
+            .to_string();
+        AuditRecord {
+            at: Utc::now(),
+            command: command.to_string(),
+            args,
+            user,
+            host,
+            outcome: match outcome {
+                Ok(()) => "ok".to_string(),
+                Err(e) => e,
+            },
+            transition,
+            tx_hashes,
+            summary: None,
+        }
+    }
+}
+
+/// Chain hash of a line whose content (without `hash`) is `content`.
+fn link_hash(previous: &str, content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(previous.as_bytes());
+    hasher.update(content.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// `record` as a log line chained to `previous`, without the newline.
+fn chain(previous: &str, record: &AuditRecord) -> Result<String> {
+    let mut value = serde_json::to_value(record)?;
+    let content = serde_json::to_string(&value)?;
+    value["hash"] = link_hash(previous, &content).into();
+    Ok(serde_json::to_string(&value)?)
+}
+
+/// Append `record` to the audit log in `data_dir`, fsync'd. Call with the
+/// exclusive lock held.
+pub fn append(data_dir: &DataDir, record: &AuditRecord) -> Result<()> {
+    let path = data_dir.file(AUDIT_FILE);
+    let previous = match std::fs::read_to_string(&path) {
+        Ok(text) => text.lines().last().unwrap_or_default().to_string(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let mut line = chain(&previous, record)?;
+    line.push('\n');
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    file.write_all(line.as_bytes())?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Result of walking the audit log's hash chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Verification {
+    Intact {
+        entries: usize,
+    },
+    /// `line` counts from 1; every line before it checked out.
+    Broken {
+        line: usize,
+        reason: String,
+    },
+}
+
+/// Check every line of the audit log in `data_dir` against the one before
+/// it. A missing log is intact with no entries.
+pub fn verify(data_dir: &DataDir) -> Result<Verification> {
+    let text = match std::fs::read_to_string(data_dir.file(AUDIT_FILE)) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(verify_lines(&text))
+}
+
+fn verify_lines(text: &str) -> Verification {
+    let mut previous = "";
+    let mut entries = 0;
+    for (i, line) in text.lines().enumerate() {
+        let broken = |reason: &str| Verification::Broken {
+            line: i + 1,
+            reason: reason.to_string(),
+        };
+        let Ok(serde_json::Value::Object(mut map)) = serde_json::from_str(line) else {
+            return broken("not a JSON object");
+        };
+        let Some(serde_json::Value::String(hash)) = map.remove("hash") else {
+            return broken("no hash");
+        };
+        let content = serde_json::Value::Object(map).to_string();
+        if link_hash(previous, &content) != hash {
+            return broken(
+                "hash does not match: this line was edited, or one before it was removed or edited",
+            );
+        }
+        previous = line;
+        entries += 1;
+    }
+    Verification::Intact { entries }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(command: &str) -> AuditRecord {
+        let before = Snapshot::default();
+        let after = Snapshot {
+            stage: Some("setup finished".into()),
+            ..Snapshot::default()
+        };
+        AuditRecord::new(command, vec![command.into()], Ok(()), &before, &after)
+    }
+
+    #[test]
+    fn test_chain_verifies_and_detects_tampering() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = DataDir::new(tmp.path());
+        for command in ["create-wallet", "exchange-keys", "build-tx"] {
+            append(&dir, &record(command)).unwrap();
+        }
+        assert_eq!(verify(&dir).unwrap(), Verification::Intact { entries: 3 });
+
+        let text = std::fs::read_to_string(dir.file(AUDIT_FILE)).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        let edited = text.replace("exchange-keys", "export-info");
+        assert!(matches!(
+            verify_lines(&edited),
+            Verification::Broken { line: 2, .. }
+        ));
+
+        let removed = [lines[0], lines[2]].join("\n");
+        assert!(matches!(
+            verify_lines(&removed),
+            Verification::Broken { line: 2, .. }
+        ));
+
+        let garbage = format!("{}\nnot json\n", lines[0]);
+        assert_eq!(
+            verify_lines(&garbage),
+            Verification::Broken {
+                line: 2,
+                reason: "not a JSON object".into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_record_reports_changes_only() {
+        let mut before = Snapshot {
+            stage: Some("setup finished".into()),
+            ..Snapshot::default()
+        };
+        before.entries.insert(1, "old".into());
+        before.hashes.insert(1, Some("aa".into()));
+        before.entries.insert(2, "same".into());
+        before.hashes.insert(2, Some("bb".into()));
+        let mut after = before.clone();
+        after.entries.insert(1, "new".into());
+        after.entries.insert(3, "added".into());
+        after.hashes.insert(3, Some("cc".into()));
+
+        let record = AuditRecord::new("sign-tx", vec![], Err("boom".into()), &before, &after);
+        assert_eq!(record.tx_hashes, vec!["aa", "cc"]);
+        assert!(record.transition.is_none());
+        assert_eq!(record.outcome, "boom");
+        assert!(!record.user.contains('@'));
+
+        let created = AuditRecord::new(
+            "create-wallet",
+            vec![],
+            Ok(()),
+            &Snapshot::default(),
+            &before,
+        );
+        assert_eq!(
+            created.transition,
+            Some(Transition {
+                from: None,
+                to: Some("setup finished".into())
+            })
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! Synchronous wrappers around the core wallet operations, for callers
+//! without an async runtime. Needs the `blocking` cargo feature.
+//!
+//! A [`Client`] owns a small single-threaded tokio runtime and runs each
+//! call to completion on it. The methods take the same arguments as the
+//! async functions in [`crate::transaction`], minus the [`RpcClient`], and
+//! return the same errors. Async code must use those functions directly:
+//! [`Client::new`] refuses to run inside a tokio runtime, where blocking on
+//! one would panic or stall it.
+
+use thiserror::Error;
+
+use crate::config::RpcClient;
+use crate::error::Result;
+use crate::transaction::{
+    self, Amount, Balance, Destination, ImportReport, PartiallySignedTx, Priority, Source,
+    SubmitResult, UnsignedMultisigTx,
+};
+
+#[derive(Error, Debug)]
+pub enum BlockingError {
+    #[error(
+        "the blocking client can't be used inside a tokio runtime — call the async functions \
+         in `transaction` instead"
+    )]
+    InsideRuntime,
+
+    #[error("could not start the blocking client's runtime: {0}")]
+    Runtime(#[from] std::io::Error),
+}
+
+/// An [`RpcClient`] with a runtime of its own to block on.
+#[derive(Debug)]
+pub struct Client {
+    rpc: RpcClient,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl Client {
+    /// Wrap `rpc`. Fails with [`BlockingError::InsideRuntime`] when called
+    /// from within a tokio runtime.
+    pub fn new(rpc: RpcClient) -> std::result::Result<Self, BlockingError> {
+        if tokio::runtime::Handle::try_current().is_ok() {
+            return Err(BlockingError::InsideRuntime);
+        }
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Self { rpc, runtime })
+    }
+
+    /// The wrapped async client.
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    /// See [`transaction::get_balance`].
+    pub fn get_balance(&self, account: u32) -> Result<Balance> {
+        self.runtime
+            .block_on(transaction::get_balance(&self.rpc, account))
+    }
+
+    /// See [`transaction::build_unsigned_tx`].
+    pub fn build_unsigned_tx(
+        &self,
+        destinations: &[Destination],
+        priority: Priority,
+        allow_split: bool,
+        source: &Source,
+        fee_estimate: Option<Amount>,
+    ) -> Result<UnsignedMultisigTx> {
+        self.runtime.block_on(transaction::build_unsigned_tx(
+            &self.rpc,
+            destinations,
+            priority,
+            allow_split,
+            source,
+            fee_estimate,
+        ))
+    }
+
+    /// See [`transaction::sign_multisig_tx`].
+    pub fn sign_multisig_tx(&self, tx_data_hex: &str) -> Result<PartiallySignedTx> {
+        self.runtime
+            .block_on(transaction::sign_multisig_tx(&self.rpc, tx_data_hex))
+    }
+
+    /// See [`transaction::submit_multisig_tx`].
+    pub fn submit_multisig_tx(&self, tx_data_hex: &str) -> Result<SubmitResult> {
+        self.runtime
+            .block_on(transaction::submit_multisig_tx(&self.rpc, tx_data_hex))
+    }
+
+    /// See [`transaction::export_multisig_info`].
+    pub fn export_multisig_info(&self) -> Result<String> {
+        self.runtime
+            .block_on(transaction::export_multisig_info(&self.rpc))
+    }
+
+    /// See [`transaction::import_multisig_info`].
+    pub fn import_multisig_info(&self, info: &[String]) -> Result<ImportReport> {
+        self.runtime
+            .block_on(transaction::import_multisig_info(&self.rpc, info))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::method_daemon;
+
+    /// A mock wallet RPC served from a runtime of its own, so the test
+    /// thread itself stays outside any runtime.
+    fn mock(answers: &'static [(&'static str, &'static str)]) -> (tokio::runtime::Runtime, Client) {
+        let server = tokio::runtime::Runtime::new().unwrap();
+        let daemon = server.block_on(method_daemon(answers));
+        let client = Client::new(RpcClient::new(&daemon)).unwrap();
+        (server, client)
+    }
+
+    #[test]
+    fn test_blocking_calls_reach_the_wallet_rpc() {
+        let (_server, client) = mock(&[
+            (
+                "get_balance",
+                r#"{"balance":7,"unlocked_balance":5,"blocks_to_unlock":0}"#,
+            ),
+            ("export_multisig_info", r#"{"info":"4d6f6e65726f"}"#),
+            (
+                "sign_multisig",
+                r#"{"tx_data_hex":"signed","tx_hash_list":["h"]}"#,
+            ),
+        ]);
+        let balance = client.get_balance(0).unwrap();
+        assert_eq!((balance.balance.0, balance.unlocked_balance.0), (7, 5));
+        assert_eq!(client.export_multisig_info().unwrap(), "4d6f6e65726f");
+        assert_eq!(client.sign_multisig_tx("set").unwrap().tx_hash, "h");
+
+        // Errors come back exactly as from the async API.
+        let err = client.submit_multisig_tx("set").unwrap_err();
+        assert!(err.to_string().contains("submit_multisig"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_refuses_to_run_inside_a_runtime() {
+        let rpc = RpcClient::new(&crate::config::DaemonRpc::default());
+        assert!(matches!(
+            Client::new(rpc),
+            Err(BlockingError::InsideRuntime)
+        ));
+    }
+}
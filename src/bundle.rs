@@ -0,0 +1,699 @@
+//! Group bundles: what a co-signer's new machine needs from the group, in
+//! one signed file.
+//!
+//! `bundle export` packages the wallet's address book, the roster, the
+//! destination allowlist and the group's config settings as JSON, with an
+//! HMAC-SHA256 over them keyed by `manifest_secret`, as tx set manifests
+//! are ([`crate::manifest`]):
+//!
+//! ```json
+//! { "version": 1, "created_at": "…", "participants": ["alice", "bob", "carol"],
+//!   "address_book": [{ "name": "rent", "address": "4…" }],
+//!   "allowed_destinations": ["4…"], "profile": { "network": "mainnet", … }, "hmac": "…" }
+//! ```
+//!
+//! `bundle import` checks the HMAC before looking at anything else, then
+//! lists how the bundle differs from this machine as [`Change`]s. Nothing
+//! local is ever deleted: an address book entry or an allowlist only this
+//! machine has stays. Replacing something set here, a name's address, the
+//! roster, the allowlist or a `policy` limit, is a conflict the operator
+//! settles.
+//!
+//! The roster is relative: each machine names the *other* participants and
+//! calls itself [`ME`](crate::wallet::ME). A bundle therefore lists the
+//! whole group, its exporter included, and the importer's roster is
+//! everyone but them.
+//!
+//! The profile leaves out what belongs to one machine ([`MACHINE_KEYS`]),
+//! every secret, and the webhook URL, which often carries a token.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::wallet::AddressBookEntry;
+
+/// Bundles are a few hundred bytes per address; anything this big is not
+/// one.
+const MAX_BUNDLE_BYTES: usize = 4 * 1024 * 1024;
+
+/// The only bundle format so far.
+const BUNDLE_VERSION: u32 = 1;
+
+/// Config keys that describe one machine rather than the group, left out
+/// of a bundle's profile.
+pub const MACHINE_KEYS: &[&str] = &[
+    "daemon",
+    "wallet_rpc",
+    "data_dir",
+    "log_file",
+    "log_retention",
+    "use_keyring",
+];
+
+#[derive(Error, Debug)]
+pub enum BundleError {
+    #[error("unsupported bundle version {0}; this build reads version {BUNDLE_VERSION}")]
+    UnsupportedVersion(u32),
+
+    #[error(
+        "the bundle's HMAC doesn't match: it was changed after it was written, or written \
+         with a different manifest_secret"
+    )]
+    BadHmac,
+
+    #[error("\"{name}\" is not in the bundle's roster ({})", .participants.join(", "))]
+    NotAParticipant {
+        name: String,
+        participants: Vec<String>,
+    },
+}
+
+/// One address book entry in a bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookEntry {
+    pub name: String,
+    pub address: String,
+}
+
+/// A group bundle as written to and read from a bundle file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bundle {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    /// Everyone in the group, the exporter first; empty when the exporter
+    /// has no roster.
+    pub participants: Vec<String>,
+    pub address_book: Vec<BookEntry>,
+    /// The exporter's `policy.allowed_destinations`.
+    pub allowed_destinations: Option<Vec<String>>,
+    /// The group's config settings, see [`profile`].
+    pub profile: Map<String, Value>,
+    /// Hex HMAC-SHA256 of every other field; see [`Bundle::verify`].
+    pub hmac: String,
+}
+
+/// The fields the HMAC covers, in the order they are serialized for it.
+#[derive(Serialize)]
+struct Signed<'a> {
+    version: u32,
+    created_at: &'a DateTime<Utc>,
+    participants: &'a [String],
+    address_book: &'a [BookEntry],
+    allowed_destinations: Option<&'a [String]>,
+    profile: &'a Map<String, Value>,
+}
+
+fn mac(secret: &str, signed: &Signed<'_>) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(&serde_json::to_vec(signed).expect("bundle fields serialize"));
+    mac
+}
+
+/// What this machine has of what a bundle carries.
+#[derive(Debug, Clone, Default)]
+pub struct Local {
+    pub address_book: Vec<AddressBookEntry>,
+    /// The other participants, as the state file names them.
+    pub roster: Vec<String>,
+    pub allowed_destinations: Option<Vec<String>>,
+    pub profile: Map<String, Value>,
+}
+
+/// One way a bundle differs from this machine.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// A name only the bundle has.
+    AddEntry(BookEntry),
+    /// A name saved here with a different address.
+    EntryConflict {
+        name: String,
+        /// The local entry's position in the address book.
+        index: u64,
+        local: String,
+        remote: String,
+    },
+    /// This machine has no roster yet.
+    SetRoster(Vec<String>),
+    /// The roster here names other people.
+    RosterConflict {
+        local: Vec<String>,
+        remote: Vec<String>,
+    },
+    /// The bundle's allowlist; one that is only set here is kept.
+    Allowlist {
+        local: Option<Vec<String>>,
+        remote: Vec<String>,
+    },
+    /// A config setting, as `key` or `section.key`.
+    Setting {
+        key: String,
+        local: Option<Value>,
+        remote: Value,
+    },
+}
+
+impl Change {
+    /// Whether taking the bundle's side replaces something set here, so
+    /// the operator has to choose. Other settings are group defaults, but
+    /// the allowlist and `policy` limits guard what this machine signs, so
+    /// replacing them, even with a looser value, is a choice.
+    pub fn is_conflict(&self) -> bool {
+        match self {
+            Change::EntryConflict { .. } | Change::RosterConflict { .. } => true,
+            Change::Allowlist { local, .. } => local.is_some(),
+            Change::Setting { key, local, .. } => {
+                key.starts_with("policy.") && local.as_ref().is_some_and(|v| !v.is_null())
+            }
+            Change::AddEntry(_) | Change::SetRoster(_) => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Change {
+    /// One `-`/`+` line per side, like a diff of this machine against the
+    /// bundle.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::AddEntry(entry) => {
+                write!(f, "+ address book: {} = {}", entry.name, entry.address)
+            }
+            Change::EntryConflict {
+                name,
+                local,
+                remote,
+                ..
+            } => write!(
+                f,
+                "- address book: {name} = {local}\n+ address book: {name} = {remote}"
+            ),
+            Change::SetRoster(remote) => write!(f, "+ roster: {}", remote.join(", ")),
+            Change::RosterConflict { local, remote } => write!(
+                f,
+                "- roster: {}\n+ roster: {}",
+                local.join(", "),
+                remote.join(", ")
+            ),
+            Change::Allowlist { local, remote } => {
+                let list = |addresses: &[String]| match addresses {
+                    [] => "(nothing allowed)".to_string(),
+                    addresses => addresses.join(", "),
+                };
+                write!(
+                    f,
+                    "- allowed destinations: {}\n+ allowed destinations: {}",
+                    local.as_deref().map_or("(not set)".to_string(), list),
+                    list(remote)
+                )
+            }
+            Change::Setting { key, local, remote } => {
+                if let Some(local) = local {
+                    writeln!(f, "- {key}: {local}")?;
+                }
+                write!(f, "+ {key}: {remote}")
+            }
+        }
+    }
+}
+
+/// The group's settings from `config`: everything but [`MACHINE_KEYS`],
+/// the allowlist, which a bundle carries on its own, secrets and the
+/// webhook URL. Passwords and `manifest_secret` are never serialized in the
+/// first place.
+pub fn profile(config: &Config) -> Map<String, Value> {
+    let Value::Object(mut profile) = serde_json::to_value(config).expect("config serializes")
+    else {
+        unreachable!("config serializes to an object");
+    };
+    for key in MACHINE_KEYS {
+        profile.remove(*key);
+    }
+    if let Some(Value::Object(policy)) = profile.get_mut("policy") {
+        policy.remove("allowed_destinations");
+    }
+    if let Some(Value::Object(notifications)) = profile.get_mut("notifications") {
+        notifications.remove("secret");
+        notifications.remove("webhook_url");
+    }
+    profile
+}
+
+impl Bundle {
+    /// A bundle of `local`, signed with `secret`. `participants` is the
+    /// whole group, the exporter first.
+    pub fn new(participants: Vec<String>, local: &Local, secret: &str) -> Self {
+        let mut bundle = Bundle {
+            version: BUNDLE_VERSION,
+            created_at: Utc::now(),
+            participants,
+            address_book: local
+                .address_book
+                .iter()
+                .map(|e| BookEntry {
+                    name: e.description.clone(),
+                    address: e.address.clone(),
+                })
+                .collect(),
+            allowed_destinations: local.allowed_destinations.clone(),
+            profile: local.profile.clone(),
+            hmac: String::new(),
+        };
+        bundle.hmac = hex::encode(mac(secret, &bundle.signed()).finalize().into_bytes());
+        bundle
+    }
+
+    fn signed(&self) -> Signed<'_> {
+        Signed {
+            version: self.version,
+            created_at: &self.created_at,
+            participants: &self.participants,
+            address_book: &self.address_book,
+            allowed_destinations: self.allowed_destinations.as_deref(),
+            profile: &self.profile,
+        }
+    }
+
+    /// Check the HMAC against `secret`, in constant time.
+    pub fn verify(&self, secret: &str) -> std::result::Result<(), BundleError> {
+        if self.version != BUNDLE_VERSION {
+            return Err(BundleError::UnsupportedVersion(self.version));
+        }
+        let tag = hex::decode(&self.hmac).map_err(|_| BundleError::BadHmac)?;
+        mac(secret, &self.signed())
+            .verify_slice(&tag)
+            .map_err(|_| BundleError::BadHmac)
+    }
+
+    /// Which participant this machine is: `given`, else the one the local
+    /// `roster` leaves out. `None` when the bundle has no roster, or it
+    /// can't be told.
+    pub fn whoami(
+        &self,
+        roster: &[String],
+        given: Option<&str>,
+    ) -> std::result::Result<Option<String>, BundleError> {
+        if self.participants.is_empty() {
+            return Ok(None);
+        }
+        if let Some(name) = given {
+            if !self.participants.iter().any(|p| p == name) {
+                return Err(BundleError::NotAParticipant {
+                    name: name.to_string(),
+                    participants: self.participants.clone(),
+                });
+            }
+            return Ok(Some(name.to_string()));
+        }
+        if roster.is_empty() {
+            return Ok(None);
+        }
+        let mut missing = self.participants.iter().filter(|p| !roster.contains(p));
+        Ok(match (missing.next(), missing.next()) {
+            (Some(name), None) => Some(name.clone()),
+            _ => None,
+        })
+    }
+
+    /// How this bundle differs from `local`, for the participant named
+    /// `me` (see [`Bundle::whoami`]); the roster is left alone without one.
+    pub fn changes(&self, local: &Local, me: Option<&str>) -> Vec<Change> {
+        let mut changes = Vec::new();
+        for entry in &self.address_book {
+            // Unnamed entries can only be told apart by their address.
+            let same = |e: &&AddressBookEntry| {
+                if entry.name.is_empty() {
+                    e.address == entry.address
+                } else {
+                    e.description == entry.name
+                }
+            };
+            let named: Vec<&AddressBookEntry> = local.address_book.iter().filter(same).collect();
+            match named.first() {
+                None => changes.push(Change::AddEntry(entry.clone())),
+                Some(_) if named.iter().any(|e| e.address == entry.address) => {}
+                Some(first) => changes.push(Change::EntryConflict {
+                    name: entry.name.clone(),
+                    index: first.index,
+                    local: first.address.clone(),
+                    remote: entry.address.clone(),
+                }),
+            }
+        }
+
+        if let Some(me) = me {
+            let remote: Vec<String> = self
+                .participants
+                .iter()
+                .filter(|p| *p != me)
+                .cloned()
+                .collect();
+            let same = remote.len() == local.roster.len()
+                && remote.iter().all(|p| local.roster.contains(p));
+            if local.roster.is_empty() {
+                changes.push(Change::SetRoster(remote));
+            } else if !same {
+                changes.push(Change::RosterConflict {
+                    local: local.roster.clone(),
+                    remote,
+                });
+            }
+        }
+
+        // A bundle without an allowlist leaves this machine's alone.
+        if let Some(remote) = &self.allowed_destinations {
+            if local.allowed_destinations.as_ref() != Some(remote) {
+                changes.push(Change::Allowlist {
+                    local: local.allowed_destinations.clone(),
+                    remote: remote.clone(),
+                });
+            }
+        }
+
+        for (key, remote) in &self.profile {
+            let local = local.profile.get(key);
+            match (remote, local) {
+                // Sections are compared setting by setting, so a local
+                // setting the bundle doesn't know about is kept.
+                (Value::Object(remote), Some(Value::Object(local))) => {
+                    for (inner, value) in remote {
+                        if local.get(inner) != Some(value) {
+                            changes.push(Change::Setting {
+                                key: format!("{key}.{inner}"),
+                                local: local.get(inner).cloned(),
+                                remote: value.clone(),
+                            });
+                        }
+                    }
+                }
+                _ if local != Some(remote) => changes.push(Change::Setting {
+                    key: key.clone(),
+                    local: local.cloned(),
+                    remote: remote.clone(),
+                }),
+                _ => {}
+            }
+        }
+        changes
+    }
+}
+
+/// Apply the allowlist and setting `changes` to `file`, the config file's
+/// JSON. Other keys in it, secrets included, are kept as they are.
+pub fn apply_to_config(file: &mut Map<String, Value>, changes: &[&Change]) {
+    for change in changes {
+        match change {
+            Change::Allowlist { remote, .. } => {
+                section(file, "policy")
+                    .insert("allowed_destinations".into(), serde_json::json!(remote));
+            }
+            Change::Setting { key, remote, .. } => match key.split_once('.') {
+                Some((outer, inner)) => {
+                    section(file, outer).insert(inner.to_string(), remote.clone());
+                }
+                None => {
+                    file.insert(key.clone(), remote.clone());
+                }
+            },
+            _ => {}
+        }
+    }
+}
+
+/// The object at `key` in `file`, created if missing or not an object.
+fn section<'a>(file: &'a mut Map<String, Value>, key: &str) -> &'a mut Map<String, Value> {
+    let value = file.entry(key).or_insert_with(|| Value::Object(Map::new()));
+    if !value.is_object() {
+        *value = Value::Object(Map::new());
+    }
+    value.as_object_mut().expect("just made an object")
+}
+
+/// Write `bundle` to `path` as pretty JSON.
+pub fn write_bundle(path: &Path, bundle: &Bundle) -> Result<()> {
+    let json = serde_json::to_string_pretty(bundle)?;
+    crate::utils::write_atomic(path, json.as_bytes())?;
+    Ok(())
+}
+
+/// Read a bundle file written by [`write_bundle`]. The HMAC is not checked;
+/// see [`Bundle::verify`].
+pub fn read_bundle(path: &Path) -> Result<Bundle> {
+    let text = crate::utils::read_multisig_data(Some(path), MAX_BUNDLE_BYTES)?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(index: u64, name: &str, address: &str) -> AddressBookEntry {
+        AddressBookEntry {
+            index,
+            address: address.into(),
+            description: name.into(),
+        }
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn exporter() -> Local {
+        let mut config = Config::default();
+        config.policy.allowed_destinations = Some(names(&["4rent", "4payroll"]));
+        config.notifications.secret = Some("hook-secret".into());
+        config.notifications.webhook_url = Some("https://hooks.example/t0ken".into());
+        config.sweep_warning_percent = 50;
+        Local {
+            address_book: vec![entry(0, "rent", "4rent"), entry(1, "payroll", "4payroll")],
+            roster: names(&["bob", "carol"]),
+            allowed_destinations: config.policy.allowed_destinations.clone(),
+            profile: profile(&config),
+        }
+    }
+
+    #[test]
+    fn test_bundle_hmac_and_file() {
+        let bundle = Bundle::new(names(&["alice", "bob", "carol"]), &exporter(), "s3cret");
+        bundle.verify("s3cret").unwrap();
+        assert!(matches!(bundle.verify("other"), Err(BundleError::BadHmac)));
+
+        // Every field is covered.
+        let mut edited = bundle.clone();
+        edited.address_book[0].address = "4attacker".into();
+        assert!(matches!(edited.verify("s3cret"), Err(BundleError::BadHmac)));
+        let mut edited = bundle.clone();
+        edited.participants.push("mallory".into());
+        assert!(matches!(edited.verify("s3cret"), Err(BundleError::BadHmac)));
+        let mut edited = bundle.clone();
+        edited
+            .profile
+            .insert("trusted_daemon".into(), Value::Bool(true));
+        assert!(matches!(edited.verify("s3cret"), Err(BundleError::BadHmac)));
+        let mut edited = bundle.clone();
+        edited.version = 2;
+        assert!(matches!(
+            edited.verify("s3cret"),
+            Err(BundleError::UnsupportedVersion(2))
+        ));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("group.bundle");
+        write_bundle(&path, &bundle).unwrap();
+        let read = read_bundle(&path).unwrap();
+        assert_eq!(read, bundle);
+        read.verify("s3cret").unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(!text.contains("hook-secret"), "{text}");
+        assert!(!text.contains("t0ken"), "{text}");
+        assert!(!text.contains("data_dir"), "{text}");
+    }
+
+    #[test]
+    fn test_whoami() {
+        let bundle = Bundle::new(names(&["alice", "bob", "carol"]), &exporter(), "s");
+        assert_eq!(
+            bundle.whoami(&[], Some("bob")).unwrap().as_deref(),
+            Some("bob")
+        );
+        assert!(matches!(
+            bundle.whoami(&[], Some("mallory")),
+            Err(BundleError::NotAParticipant { .. })
+        ));
+        // Told from the roster here: the one name it leaves out.
+        assert_eq!(
+            bundle
+                .whoami(&names(&["alice", "carol"]), None)
+                .unwrap()
+                .as_deref(),
+            Some("bob")
+        );
+        assert_eq!(bundle.whoami(&[], None).unwrap(), None);
+        assert_eq!(
+            bundle.whoami(&names(&["dave", "erin"]), None).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_changes() {
+        let bundle = Bundle::new(names(&["alice", "bob", "carol"]), &exporter(), "s");
+        let config = Config {
+            sweep_warning_percent: 80,
+            ..Config::default()
+        };
+        let local = Local {
+            address_book: vec![entry(0, "payroll", "4elsewhere"), entry(1, "cafe", "4cafe")],
+            roster: Vec::new(),
+            allowed_destinations: None,
+            profile: profile(&config),
+        };
+        let changes = bundle.changes(&local, Some("bob"));
+        assert_eq!(
+            changes,
+            [
+                Change::AddEntry(BookEntry {
+                    name: "rent".into(),
+                    address: "4rent".into()
+                }),
+                Change::EntryConflict {
+                    name: "payroll".into(),
+                    index: 0,
+                    local: "4elsewhere".into(),
+                    remote: "4payroll".into(),
+                },
+                Change::SetRoster(names(&["alice", "carol"])),
+                Change::Allowlist {
+                    local: None,
+                    remote: names(&["4rent", "4payroll"]),
+                },
+                Change::Setting {
+                    key: "sweep_warning_percent".into(),
+                    local: Some(80.into()),
+                    remote: 50.into(),
+                },
+            ]
+        );
+        assert_eq!(
+            changes[1].to_string(),
+            "- address book: payroll = 4elsewhere\n+ address book: payroll = 4payroll"
+        );
+        assert!(changes[1].is_conflict() && !changes[0].is_conflict());
+        // Setting an allowlist or a setting this machine lacks is no conflict.
+        assert!(!changes[3].is_conflict() && !changes[4].is_conflict());
+
+        // The same roster in another order isn't a change; another one is.
+        let mut same = local.clone();
+        same.roster = names(&["carol", "alice"]);
+        assert!(!bundle
+            .changes(&same, Some("bob"))
+            .iter()
+            .any(|c| matches!(c, Change::RosterConflict { .. } | Change::SetRoster(_))));
+        let mut other = local.clone();
+        other.roster = names(&["alice", "dave"]);
+        assert!(bundle
+            .changes(&other, Some("bob"))
+            .contains(&Change::RosterConflict {
+                local: names(&["alice", "dave"]),
+                remote: names(&["alice", "carol"]),
+            }));
+
+        // A machine set up like the exporter has nothing to import.
+        assert_eq!(bundle.changes(&exporter(), Some("alice")), []);
+    }
+
+    #[test]
+    fn test_changes_guard_local_limits() {
+        let mut config = Config::default();
+        config.policy.max_fee_piconero = Some(crate::transaction::Amount(100));
+        let local = Local {
+            allowed_destinations: Some(names(&["4rent"])),
+            profile: profile(&config),
+            ..Local::default()
+        };
+
+        // A bundle without an allowlist or fee limit leaves both alone, or
+        // asks before lifting the limit.
+        let defaults = Local {
+            profile: profile(&Config::default()),
+            ..Local::default()
+        };
+        let bare = Bundle::new(Vec::new(), &defaults, "s");
+        let changes = bare.changes(&local, None);
+        assert!(!changes
+            .iter()
+            .any(|c| matches!(c, Change::Allowlist { .. })));
+        let lifted = changes
+            .iter()
+            .find(|c| matches!(c, Change::Setting { key, .. } if key == "policy.max_fee_piconero"))
+            .unwrap();
+        assert!(lifted.is_conflict());
+
+        // One that replaces them is a conflict for each.
+        let changes = Bundle::new(Vec::new(), &exporter(), "s").changes(&local, None);
+        let allowlist = changes
+            .iter()
+            .find(|c| matches!(c, Change::Allowlist { .. }))
+            .unwrap();
+        assert!(allowlist.is_conflict());
+        assert!(changes
+            .iter()
+            .filter(|c| matches!(c, Change::Setting { key, .. } if key.starts_with("policy.")))
+            .all(Change::is_conflict));
+        // Where this machine has no limit, taking the bundle's isn't one.
+        let mut strict = exporter();
+        strict.profile = profile(&config);
+        let changes = Bundle::new(Vec::new(), &strict, "s").changes(&exporter(), None);
+        let set = changes
+            .iter()
+            .find(|c| matches!(c, Change::Setting { key, .. } if key == "policy.max_fee_piconero"))
+            .unwrap();
+        assert!(!set.is_conflict());
+    }
+
+    #[test]
+    fn test_apply_to_config_keeps_local_secrets() {
+        let mut file: Map<String, Value> = serde_json::from_value(serde_json::json!({
+            "network": "mainnet",
+            "manifest_secret": "keep-me",
+            "notifications": { "webhook_url": "https://old", "secret": "keep-me-too" },
+            "policy": { "max_fee_piconero": 100 },
+        }))
+        .unwrap();
+        let changes = [
+            Change::Setting {
+                key: "policy.max_fee_piconero".into(),
+                local: Some(100.into()),
+                remote: 200.into(),
+            },
+            Change::Setting {
+                key: "sweep_warning_percent".into(),
+                local: None,
+                remote: 50.into(),
+            },
+            Change::Allowlist {
+                local: None,
+                remote: names(&["4rent"]),
+            },
+        ];
+        apply_to_config(&mut file, &changes.iter().collect::<Vec<_>>());
+        assert_eq!(
+            Value::Object(file),
+            serde_json::json!({
+                "network": "mainnet",
+                "manifest_secret": "keep-me",
+                "notifications": { "webhook_url": "https://old", "secret": "keep-me-too" },
+                "policy": { "max_fee_piconero": 200, "allowed_destinations": ["4rent"] },
+                "sweep_warning_percent": 50,
+            })
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! What the wallet RPC on the other end supports.
+//!
+//! Older monero-wallet-rpc builds lack some methods and run multisig setup
+//! differently. Rather than fail halfway through a command with "Method not
+//! found", callers ask [`RpcClient::capabilities`] and branch. The server is
+//! probed once: `get_version`, then a trial call of each optional method
+//! with arguments that can't do anything.
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{RpcClient, RpcError, RpcVersion, MIN_WALLET_RPC_VERSION};
+
+/// How the server runs multisig key exchange.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MultisigApi {
+    /// Before Monero v0.18: `exchange_multisig_keys` rounds, then, for an
+    /// N-1/N wallet only, a last `finalize_multisig`.
+    Legacy,
+    /// Monero v0.18 and later: every round through `exchange_multisig_keys`.
+    Current,
+}
+
+impl std::fmt::Display for MultisigApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            MultisigApi::Legacy => "legacy (finalize_multisig)",
+            MultisigApi::Current => "current (exchange_multisig_keys)",
+        })
+    }
+}
+
+/// The result of probing a wallet RPC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Capabilities {
+    pub version: RpcVersion,
+    pub supports_transfer_split: bool,
+    pub supports_describe_transfer: bool,
+    pub multisig_api_generation: MultisigApi,
+}
+
+/// Probe `rpc`. Use [`RpcClient::capabilities`], which caches the answer.
+///
+/// An offline client can't try `transfer_split`; it is assumed present,
+/// since building needs the network anyway.
+pub(crate) async fn probe(rpc: &RpcClient) -> Result<Capabilities, RpcError> {
+    let version = rpc.server_version().await?;
+    let supports_describe_transfer = supports(
+        rpc,
+        "describe_transfer",
+        serde_json::json!({ "multisig_txset": "" }),
+    )
+    .await?;
+    let supports_transfer_split = rpc.is_offline()
+        || supports(
+            rpc,
+            "transfer_split",
+            serde_json::json!({ "destinations": [], "do_not_relay": true }),
+        )
+        .await?;
+    let capabilities = Capabilities {
+        version,
+        supports_transfer_split,
+        supports_describe_transfer,
+        multisig_api_generation: if version < MIN_WALLET_RPC_VERSION {
+            MultisigApi::Legacy
+        } else {
+            MultisigApi::Current
+        },
+    };
+    tracing::debug!(?capabilities, "probed wallet RPC");
+    Ok(capabilities)
+}
+
+/// Call `method` with `params` it is bound to refuse, and tell from the
+/// refusal whether the server knows the method at all. The version floor is
+/// bypassed so an old server can still be described.
+async fn supports(
+    rpc: &RpcClient,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<bool, RpcError> {
+    match rpc.call::<_, serde_json::Value>(method, &params).await {
+        Ok(_) => Ok(true),
+        Err(e) if e.is_method_not_found() => Ok(false),
+        Err(e) if e.is_unreachable() => Err(e),
+        Err(_) => Ok(true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::method_daemon;
+
+    #[tokio::test]
+    async fn test_old_server_lacks_describe_transfer() {
+        let rpc = RpcClient::new(&method_daemon(&[("get_version", r#"{"version":65556}"#)]).await);
+        let capabilities = rpc.capabilities().await.unwrap();
+        assert_eq!(capabilities.version.to_string(), "1.20");
+        assert!(!capabilities.supports_describe_transfer);
+        assert!(!capabilities.supports_transfer_split);
+        assert_eq!(capabilities.multisig_api_generation, MultisigApi::Legacy);
+    }
+
+    #[tokio::test]
+    async fn test_current_server_supports_everything() {
+        let rpc = RpcClient::new(
+            &method_daemon(&[
+                ("get_version", r#"{"version":65563}"#),
+                ("describe_transfer", r#"{"desc":[]}"#),
+                ("transfer_split", r#"{}"#),
+            ])
+            .await,
+        );
+        let capabilities = rpc.capabilities().await.unwrap();
+        assert!(capabilities.supports_describe_transfer && capabilities.supports_transfer_split);
+        assert_eq!(capabilities.multisig_api_generation, MultisigApi::Current);
+    }
+}
@@ -1,6 +1,14 @@
-use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU16, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use thiserror::Error;
+use tracing::Instrument;
+
+use crate::rpc::methods::{GetVersion, GetVersionRequest, GetVersionResponse};
+use crate::rpc::RpcMethod;
+use crate::secret::Secret;
 
 #[derive(Error, Debug)]
 pub enum ConfigError {
@@ -12,21 +20,237 @@ pub enum ConfigError {
 
     #[error("missing required field: {0}")]
     MissingField(String),
+
+    #[error("invalid RPC endpoint \"{value}\": {reason}")]
+    InvalidUrl { value: String, reason: String },
+
+    #[error("data directory {path}: {reason}")]
+    DataDir { path: String, reason: String },
+
+    #[error("max_rps {value} for {endpoint}: use a positive number of calls per second")]
+    InvalidMaxRps { endpoint: String, value: f64 },
+
+    #[error("invalid wallet name \"{0}\": use a plain name, without path separators")]
+    InvalidWalletName(String),
+
+    #[error(
+        "{dir} holds several wallets ({}); choose one with --wallet or `config set default-wallet`",
+        .names.join(", ")
+    )]
+    WalletNotChosen { dir: String, names: Vec<String> },
+}
+
+/// Errors returned by [`RpcClient::request`].
+///
+/// Every variant carries the name of the RPC method that failed so callers can
+/// tell which step of a workflow went wrong.
+#[derive(Error, Debug)]
+pub enum RpcError {
+    #[error("{method} RPC call failed")]
+    Transport {
+        method: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    #[error("{method} RPC call failed: could not parse response")]
+    InvalidResponse {
+        method: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("{method} RPC call failed: {message} (code: {code})")]
+    Server {
+        method: String,
+        code: i64,
+        message: String,
+    },
+
+    #[error("{method} RPC call failed: response contained neither result nor error")]
+    EmptyResponse { method: String },
+
+    #[error(
+        "{method} RPC call failed: endpoint returned {what} (status {status}) — is this really \
+         a monero JSON-RPC port?{}",
+        excerpt_note(.excerpt)
+    )]
+    NotJson {
+        method: String,
+        status: u16,
+        /// What came back instead: "HTML", "an empty body", or the
+        /// content type.
+        what: String,
+        /// The start of the body, cut to [`NON_JSON_EXCERPT_CHARS`] and with
+        /// control characters replaced.
+        excerpt: String,
+    },
+
+    #[error(
+        "{method} RPC call failed: {message} — {method} is a {expected} method, so this \
+         endpoint is probably {}; check that `daemon` points at monerod and `wallet_rpc` at \
+         monero-wallet-rpc, not the other way round",
+        .expected.other()
+    )]
+    WrongEndpoint {
+        method: String,
+        message: String,
+        /// Which kind of endpoint has `method`.
+        expected: EndpointKind,
+    },
+
+    #[error("{method} RPC call failed: unexpected result ({source}): {response}")]
+    UnexpectedResult {
+        method: String,
+        /// The result as received, cut to [`RESPONSE_EXCERPT_LEN`] bytes.
+        response: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("{method} needs network access and is disabled in offline mode")]
+    Offline { method: String },
+
+    #[error(
+        "wallet RPC version {found} is older than the minimum supported {minimum} — upgrade \
+         monero-wallet-rpc, or pass --skip-version-check to try anyway"
+    )]
+    UnsupportedVersion {
+        method: String,
+        found: RpcVersion,
+        minimum: RpcVersion,
+    },
+
+    #[error("{method} is not available in wallet RPC {version} — upgrade monero-wallet-rpc")]
+    Unsupported { method: String, version: RpcVersion },
+}
+
+/// JSON-RPC error code for a method the server doesn't have.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Wallet RPC error code for a daemon or wallet too busy to answer, as while
+/// a refresh is running.
+pub const BUSY: i64 = -3;
+
+impl RpcError {
+    /// The RPC method that produced this error.
+    pub fn method(&self) -> &str {
+        match self {
+            RpcError::Transport { method, .. }
+            | RpcError::InvalidResponse { method, .. }
+            | RpcError::Server { method, .. }
+            | RpcError::EmptyResponse { method }
+            | RpcError::NotJson { method, .. }
+            | RpcError::WrongEndpoint { method, .. }
+            | RpcError::UnexpectedResult { method, .. }
+            | RpcError::Offline { method }
+            | RpcError::UnsupportedVersion { method, .. }
+            | RpcError::Unsupported { method, .. } => method,
+        }
+    }
+
+    /// The JSON-RPC error code, if the server returned a JSON-RPC error object.
+    pub fn code(&self) -> Option<i64> {
+        match self {
+            RpcError::Server { code, .. } => Some(*code),
+            RpcError::WrongEndpoint { .. } => Some(METHOD_NOT_FOUND),
+            _ => None,
+        }
+    }
+
+    /// Whether the endpoint itself couldn't be reached or is failing (no
+    /// connection, a timeout, or an HTTP 5xx), as opposed to answering with
+    /// an error. Only these errors fail over to another daemon.
+    pub fn is_unreachable(&self) -> bool {
+        match self {
+            RpcError::Transport { source, .. } => {
+                source.is_connect()
+                    || source.is_timeout()
+                    || source.status().is_some_and(|s| s.is_server_error())
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the server answered that it is busy with something else, so
+    /// the same call should succeed later.
+    pub fn is_busy(&self) -> bool {
+        match self {
+            RpcError::Server { code, message, .. } => {
+                *code == BUSY || message.to_ascii_lowercase().contains("busy")
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the server doesn't have the method at all.
+    pub fn is_method_not_found(&self) -> bool {
+        matches!(
+            self,
+            RpcError::Server {
+                code: METHOD_NOT_FOUND,
+                ..
+            } | RpcError::WrongEndpoint { .. }
+                | RpcError::Unsupported { .. }
+        )
+    }
+
+    /// The server-supplied error message, if any.
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            RpcError::Server { message, .. } | RpcError::WrongEndpoint { message, .. } => {
+                Some(message)
+            }
+            _ => None,
+        }
+    }
+
+    /// The JSON-RPC error object the server answered with, if it did.
+    pub fn json_rpc_error(&self) -> Option<JsonRpcError> {
+        match self {
+            RpcError::Server { code, message, .. } => Some(JsonRpcError {
+                code: *code,
+                message: message.clone(),
+            }),
+            RpcError::WrongEndpoint { message, .. } => Some(JsonRpcError {
+                code: METHOD_NOT_FOUND,
+                message: message.clone(),
+            }),
+            _ => None,
+        }
+    }
 }
 
 /// Connection settings for a Monero daemon RPC endpoint.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DaemonRpc {
-    /// Hostname or IP address of the Monero daemon.
+    /// Hostname or IP address of the Monero daemon. IPv6 literals may be
+    /// given with or without brackets.
     pub host: String,
     /// RPC port (default: 18081 for mainnet, 28081 for testnet).
     pub port: u16,
     /// Use TLS for the RPC connection.
     pub tls: bool,
+    /// Full endpoint URL, used instead of `host`/`port`. A scheme given here
+    /// wins over `tls`; without a path, `path` (or `/json_rpc`) is appended.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// JSON-RPC path, for endpoints behind a reverse proxy such as
+    /// `/wallet/json_rpc`. Defaults to `/json_rpc`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
     /// Optional username for digest authentication.
     pub username: Option<String>,
-    /// Optional password for digest authentication.
-    pub password: Option<String>,
+    /// Optional password for digest authentication. Never written back out.
+    #[serde(skip_serializing)]
+    pub password: Option<Secret<String>>,
+    /// Most calls in flight at once to this endpoint; further calls wait.
+    pub max_concurrent_requests: usize,
+    /// Most calls started per second, for remote nodes that rate-limit.
+    /// Unlimited when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_rps: Option<f64>,
 }
 
 impl Default for DaemonRpc {
@@ -35,17 +259,69 @@ impl Default for DaemonRpc {
             host: "127.0.0.1".to_string(),
             port: 18081,
             tls: false,
+            url: None,
+            path: None,
             username: None,
             password: None,
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            max_rps: None,
         }
     }
 }
 
 impl DaemonRpc {
-    /// Build the full RPC URL from the connection settings.
+    /// The full JSON-RPC URL. Settings that fail [`DaemonRpc::parse_url`]
+    /// come back unnormalized; [`Config::load`] rejects them up front.
     pub fn url(&self) -> String {
+        self.parse_url()
+            .map(String::from)
+            .unwrap_or_else(|_| self.raw_url())
+    }
+
+    /// Build and validate the JSON-RPC URL.
+    pub fn parse_url(&self) -> Result<url::Url, ConfigError> {
+        let raw = self.raw_url();
+        let invalid = |reason: String| ConfigError::InvalidUrl {
+            value: self.url.clone().unwrap_or_else(|| raw.clone()),
+            reason,
+        };
+        let mut url = url::Url::parse(&raw).map_err(|e| invalid(e.to_string()))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(invalid(format!("unsupported scheme {}", url.scheme())));
+        }
+        if url.host().is_none() {
+            return Err(invalid("no host".to_string()));
+        }
+        if url.path() == "/" {
+            url.set_path(&self.json_rpc_path());
+        }
+        Ok(url)
+    }
+
+    fn json_rpc_path(&self) -> String {
+        match self.path.as_deref().map(str::trim) {
+            Some(path) if !path.is_empty() => format!("/{}", path.trim_start_matches('/')),
+            _ => "/json_rpc".to_string(),
+        }
+    }
+
+    fn raw_url(&self) -> String {
         let scheme = if self.tls { "https" } else { "http" };
-        format!("{scheme}://{}:{}/json_rpc", self.host, self.port)
+        if let Some(url) = &self.url {
+            let url = url.trim();
+            return if url.contains("://") {
+                url.to_string()
+            } else {
+                format!("{scheme}://{url}")
+            };
+        }
+        let host = self.host.trim();
+        let host = if host.contains(':') && !host.starts_with('[') {
+            format!("[{host}]")
+        } else {
+            host.to_string()
+        };
+        format!("{scheme}://{host}:{}{}", self.port, self.json_rpc_path())
     }
 }
 
@@ -54,10 +330,252 @@ impl DaemonRpc {
 pub struct Config {
     /// Monero network to operate on.
     pub network: Network,
-    /// Daemon RPC connection settings.
-    pub daemon: DaemonRpc,
-    /// Directory for storing wallet files and key exchange data.
-    pub data_dir: PathBuf,
+    /// Daemon RPC endpoints, tried in order. A single object is accepted
+    /// for older config files.
+    #[serde(deserialize_with = "one_or_many")]
+    pub daemon: Vec<DaemonRpc>,
+    /// Wallet RPC connection settings. When unset, wallet calls go to the
+    /// first `daemon` entry, matching setups that point it at
+    /// `monero-wallet-rpc`. The wallet never fails over.
+    #[serde(default)]
+    pub wallet_rpc: Option<DaemonRpc>,
+    /// Directory for storing wallet files and key exchange data. `~` and
+    /// `$VAR` are expanded on load; `--data-dir` takes precedence.
+    pub data_dir: DataDir,
+    /// Optional path for a JSON-lines audit log, rotated daily.
+    #[serde(default)]
+    pub log_file: Option<PathBuf>,
+    /// Number of rotated log files to keep before the oldest is deleted.
+    #[serde(default = "default_log_retention")]
+    pub log_retention: usize,
+    /// Encoding used when displaying blob and address fingerprints.
+    #[serde(default)]
+    pub fingerprint_format: FingerprintFormat,
+    /// BuildTx refuses to run when the last ImportInfo is older than this.
+    #[serde(default = "default_max_sync_age_hours")]
+    pub max_sync_age_hours: u64,
+    /// Warn and ask for confirmation when a transaction spends more than
+    /// this percentage of the wallet balance.
+    #[serde(default = "default_sweep_warning_percent")]
+    pub sweep_warning_percent: u8,
+    /// Seconds SubmitTx waits for the daemon to see a submitted transaction.
+    #[serde(default = "default_broadcast_grace_secs")]
+    pub broadcast_grace_secs: u64,
+    /// Spending limits checked at build and sign time.
+    #[serde(default)]
+    pub policy: crate::policy::Policy,
+    /// Webhook for lifecycle events.
+    #[serde(default)]
+    pub notifications: crate::notify::Notifications,
+    /// Abort instead of warning when the daemon reports itself untrusted.
+    #[serde(default)]
+    pub trusted_daemon: bool,
+    /// Largest tx set, armored or not, accepted from another participant.
+    #[serde(default = "default_max_blob_bytes")]
+    pub max_blob_bytes: usize,
+    /// Look up and store wallet passwords in the OS keyring. Off for hardened
+    /// setups that keep no secrets on the machine.
+    #[serde(default = "default_use_keyring")]
+    pub use_keyring: bool,
+    /// RPC calls taking at least this long are logged at info level; the
+    /// rest at debug.
+    #[serde(default = "default_slow_rpc_ms")]
+    pub slow_rpc_ms: u64,
+    /// How long calls keep being retried while the wallet RPC answers that
+    /// it is busy, before failing.
+    #[serde(default = "default_busy_wait_secs")]
+    pub busy_wait_secs: u64,
+    /// How long calls may take before `doctor --latency` calls them slow,
+    /// and a command's total RPC time that prompts a hint to run it.
+    #[serde(default)]
+    pub latency: crate::doctor::LatencyBudgets,
+    /// Show XMR amounts in tables and summaries with their digits grouped,
+    /// as `--group-digits` does.
+    #[serde(default)]
+    pub group_digits: bool,
+    /// Wallet used when `--wallet` is left out, by its name under the data
+    /// directory's `wallets/`.
+    #[serde(default)]
+    pub default_wallet: Option<String>,
+    /// Secret shared by the whole group for signing tx set manifests
+    /// (`build-tx --manifest-out`, `verify-tx-set`). Never written back out.
+    #[serde(default, skip_serializing)]
+    pub manifest_secret: Option<Secret<String>>,
+}
+
+/// The directory holding the state file, journal, ledger, notification
+/// spool, lock, trash and archive. Everything that reads or writes there
+/// takes a `DataDir` and names its file with [`DataDir::file`], so whichever
+/// directory [`Config::load`] settled on is used throughout.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DataDir(PathBuf);
+
+impl DataDir {
+    /// Use `path` as is, without expanding or checking it.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        DataDir(path.into())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// The path of `name` inside the directory.
+    pub fn file(&self, name: impl AsRef<Path>) -> PathBuf {
+        self.0.join(name)
+    }
+
+    /// Expand `~` and environment variables, then make sure the directory
+    /// exists (created 0700 on Unix) and is writable.
+    pub fn resolve(&self) -> Result<Self, ConfigError> {
+        let dir = DataDir(expand_path(&self.0)?);
+        let error = |reason: String| ConfigError::DataDir {
+            path: dir.0.display().to_string(),
+            reason,
+        };
+        if !dir.0.exists() {
+            let mut builder = std::fs::DirBuilder::new();
+            builder.recursive(true);
+            #[cfg(unix)]
+            std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+            builder
+                .create(&dir.0)
+                .map_err(|e| error(format!("could not create it: {e}")))?;
+        } else if !dir.0.is_dir() {
+            return Err(error("exists but is not a directory".into()));
+        }
+        // Permission bits don't tell the whole story (read-only mounts,
+        // ACLs), so try writing.
+        let probe = dir.file(format!(".write-test-{}", std::process::id()));
+        std::fs::write(&probe, b"")
+            .map_err(|e| error(format!("exists but isn't writable: {e}")))?;
+        let _ = std::fs::remove_file(&probe);
+        Ok(dir)
+    }
+}
+
+impl std::ops::Deref for DataDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for DataDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+/// `path` with a leading `~` replaced by the home directory and `$VAR` or
+/// `${VAR}` replaced by the variable's value. Unset variables are an error.
+fn expand_path(path: &Path) -> Result<PathBuf, ConfigError> {
+    let raw = path.to_string_lossy();
+    let error = |reason: String| ConfigError::DataDir {
+        path: raw.to_string(),
+        reason,
+    };
+    let home = || dirs::home_dir().ok_or_else(|| error("no home directory to expand ~".into()));
+
+    if !raw.starts_with('~') && !raw.contains('$') {
+        return Ok(path.to_path_buf());
+    }
+    let (mut out, rest) = match raw.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            (home()?.to_string_lossy().into_owned(), rest)
+        }
+        _ => (String::new(), &raw[..]),
+    };
+    let mut chars = rest.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            out.push(c);
+            continue;
+        }
+        let braced = chars.next_if_eq(&'{').is_some();
+        let mut name = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+            name.push(c);
+        }
+        if braced && chars.next() != Some('}') {
+            return Err(error("unterminated ${...}".into()));
+        }
+        if name.is_empty() {
+            out.push('$');
+            continue;
+        }
+        let value = std::env::var(&name)
+            .map_err(|_| error(format!("environment variable {name} is not set")))?;
+        out.push_str(&value);
+    }
+    Ok(PathBuf::from(out))
+}
+
+/// How fingerprints are rendered for out-of-band verification.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FingerprintFormat {
+    /// Eight words, e.g. `banjo-nickel-ohio-tango-…`, easy to read aloud.
+    #[default]
+    Words,
+    /// Eight groups of four hex digits, e.g. `3F2A 91C4 …`.
+    Hex,
+}
+
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<DaemonRpc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        // Lists first: with every field defaulted, `[]` would also parse as
+        // a single entry.
+        Many(Vec<DaemonRpc>),
+        One(DaemonRpc),
+    }
+
+    match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(daemon) => Ok(vec![daemon]),
+        OneOrMany::Many(list) if list.is_empty() => {
+            Err(serde::de::Error::custom("daemon list is empty"))
+        }
+        OneOrMany::Many(list) => Ok(list),
+    }
+}
+
+fn default_log_retention() -> usize {
+    14
+}
+
+fn default_max_sync_age_hours() -> u64 {
+    24
+}
+
+fn default_sweep_warning_percent() -> u8 {
+    90
+}
+
+fn default_broadcast_grace_secs() -> u64 {
+    60
+}
+
+fn default_max_blob_bytes() -> usize {
+    8 * 1024 * 1024
+}
+
+fn default_use_keyring() -> bool {
+    true
+}
+
+fn default_slow_rpc_ms() -> u64 {
+    DEFAULT_SLOW_RPC.as_millis() as u64
+}
+
+fn default_busy_wait_secs() -> u64 {
+    DEFAULT_BUSY_WAIT.as_secs()
 }
 
 /// The Monero network variant.
@@ -69,6 +587,21 @@ pub enum Network {
     Stagenet,
 }
 
+impl Network {
+    /// The network an address belongs to, read from its first character,
+    /// or `None` if that isn't an address prefix. Standard, integrated and
+    /// subaddresses are all recognized; the rest of the address is not
+    /// checked.
+    pub fn of_address(address: &str) -> Option<Network> {
+        match address.trim().chars().next()? {
+            '4' | '8' => Some(Network::Mainnet),
+            '9' | 'A' | 'B' => Some(Network::Testnet),
+            '5' | '7' => Some(Network::Stagenet),
+            _ => None,
+        }
+    }
+}
+
 impl std::fmt::Display for Network {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -87,73 +620,817 @@ impl Default for Config {
 
         Self {
             network: Network::Mainnet,
-            daemon: DaemonRpc::default(),
-            data_dir,
+            daemon: vec![DaemonRpc::default()],
+            wallet_rpc: None,
+            data_dir: DataDir::new(data_dir),
+            log_file: None,
+            log_retention: default_log_retention(),
+            fingerprint_format: FingerprintFormat::default(),
+            max_sync_age_hours: default_max_sync_age_hours(),
+            sweep_warning_percent: default_sweep_warning_percent(),
+            broadcast_grace_secs: default_broadcast_grace_secs(),
+            policy: crate::policy::Policy::default(),
+            notifications: crate::notify::Notifications::default(),
+            trusted_daemon: false,
+            max_blob_bytes: default_max_blob_bytes(),
+            use_keyring: default_use_keyring(),
+            slow_rpc_ms: default_slow_rpc_ms(),
+            busy_wait_secs: default_busy_wait_secs(),
+            latency: crate::doctor::LatencyBudgets::default(),
+            group_digits: false,
+            default_wallet: None,
+            manifest_secret: None,
         }
     }
 }
 
 impl Config {
     /// Load configuration from a JSON file, falling back to defaults.
-    pub fn load(path: Option<&PathBuf>) -> Result<Self, ConfigError> {
-        match path {
+    /// `data_dir`, from `--data-dir`, replaces the configured directory,
+    /// which is then [resolved](DataDir::resolve).
+    pub fn load(path: Option<&PathBuf>, data_dir: Option<&Path>) -> Result<Self, ConfigError> {
+        let mut config = match path {
             Some(p) => {
                 let contents = std::fs::read_to_string(p)?;
                 let config: Config = serde_json::from_str(&contents)?;
-                Ok(config)
+                config.validate()?;
+                config
+            }
+            None => Self::default(),
+        };
+        if let Some(dir) = data_dir {
+            config.data_dir = DataDir::new(dir);
+        }
+        config.data_dir = config.data_dir.resolve()?;
+        Ok(config)
+    }
+
+    /// Check every RPC endpoint builds a usable URL and has a `max_rps` the
+    /// gap between calls can be worked out from.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for endpoint in self.daemon.iter().chain(&self.wallet_rpc) {
+            endpoint.parse_url()?;
+            if let Some(rps) = endpoint.max_rps {
+                if rps < 0.0 || (rps > 0.0 && call_interval(rps).is_none()) {
+                    return Err(ConfigError::InvalidMaxRps {
+                        endpoint: endpoint.url(),
+                        value: rps,
+                    });
+                }
             }
-            None => Ok(Self::default()),
         }
+        Ok(())
+    }
+
+    /// Move `data_dir` to the wallet named by `name`, from `--wallet`, or
+    /// else by `default_wallet`; each named wallet lives in its own
+    /// directory under `wallets/`. With neither set, the only wallet there is
+    /// used and its name returned so the caller can say so, and several are
+    /// an error. When there are none the data directory itself holds the
+    /// wallet, as it did before wallets had names.
+    pub fn select_wallet(&mut self, name: Option<&str>) -> Result<Option<String>, ConfigError> {
+        let (name, automatic) = match name.or(self.default_wallet.as_deref()) {
+            Some(name) => (name.to_string(), false),
+            None => {
+                let mut names = wallet_names(&self.data_dir);
+                match names.len() {
+                    0 => return Ok(None),
+                    1 => (names.remove(0), true),
+                    _ => {
+                        return Err(ConfigError::WalletNotChosen {
+                            dir: self.data_dir.display().to_string(),
+                            names,
+                        })
+                    }
+                }
+            }
+        };
+        check_wallet_name(&name)?;
+        self.data_dir = DataDir::new(self.data_dir.file(WALLETS_DIR).join(&name)).resolve()?;
+        Ok(automatic.then_some(name))
     }
 
-    /// Persist the current configuration to a JSON file.
+    /// Persist the current configuration to a JSON file. Digest-auth
+    /// passwords are left out of it, with a warning for each endpoint that
+    /// had one: they have to be put back in by hand.
     pub fn save(&self, path: &PathBuf) -> Result<(), ConfigError> {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
+        for endpoint in self.daemon.iter().chain(&self.wallet_rpc) {
+            if endpoint.password.is_some() {
+                tracing::warn!(
+                    endpoint = %endpoint.url(),
+                    path = %path.display(),
+                    "the digest-auth password is not written to the saved config"
+                );
+            }
+        }
         let json = serde_json::to_string_pretty(self)?;
         std::fs::write(path, json)?;
         Ok(())
     }
 }
 
+/// Directory under the data directory holding one directory per named
+/// wallet.
+pub const WALLETS_DIR: &str = "wallets";
+
+/// The named wallets in `data_dir`, sorted.
+fn wallet_names(data_dir: &DataDir) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(data_dir.file(WALLETS_DIR))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| check_wallet_name(name).is_ok())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Refuse wallet names that would leave `wallets/` or hide the directory.
+pub fn check_wallet_name(name: &str) -> Result<(), ConfigError> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(ConfigError::InvalidWalletName(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Set `default_wallet` in the config file at `path`, keeping the rest of
+/// the file as written.
+pub fn save_default_wallet(path: &Path, name: &str) -> Result<(), ConfigError> {
+    check_wallet_name(name)?;
+    let mut fields: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    fields.insert("default_wallet".into(), name.into());
+    let json = serde_json::to_string_pretty(&fields)?;
+    crate::utils::write_atomic(path, json.as_bytes())?;
+    Ok(())
+}
+
+/// Wallet RPC methods that work without the wallet contacting a daemon. An
+/// [offline](RpcClient::offline) client refuses everything else.
+pub const OFFLINE_METHODS: &[&str] = &[
+    "sign_multisig",
+    "sign_transfer",
+    "export_multisig_info",
+    "describe_transfer",
+    "get_address",
+    "get_accounts",
+    "get_balance",
+    "is_multisig",
+    "get_version",
+];
+
+/// The two kinds of server this tool talks to, as told apart by
+/// [`RpcError::WrongEndpoint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointKind {
+    Daemon,
+    Wallet,
+}
+
+impl EndpointKind {
+    fn other(self) -> EndpointKind {
+        match self {
+            EndpointKind::Daemon => EndpointKind::Wallet,
+            EndpointKind::Wallet => EndpointKind::Daemon,
+        }
+    }
+
+    /// Which kind of server has `method`, for methods only one kind has in
+    /// every version this tool supports. A "Method not found" for one of
+    /// them means the call went to the other kind. Methods a restricted or
+    /// old server may lack, like `hard_fork_info` or `transfer_split`, are
+    /// left out.
+    pub fn of_method(method: &str) -> Option<EndpointKind> {
+        const WALLET: &[&str] = &[
+            "get_balance",
+            "get_address",
+            "get_accounts",
+            "get_height",
+            "is_multisig",
+            "prepare_multisig",
+            "make_multisig",
+            "exchange_multisig_keys",
+            "export_multisig_info",
+            "import_multisig_info",
+            "sign_multisig",
+            "submit_multisig",
+            "transfer",
+            "get_transfers",
+        ];
+        const DAEMON: &[&str] = &["get_info", "get_block_count", "get_last_block_header"];
+        if WALLET.contains(&method) {
+            Some(EndpointKind::Wallet)
+        } else if DAEMON.contains(&method) {
+            Some(EndpointKind::Daemon)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for EndpointKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EndpointKind::Daemon => "monerod",
+            EndpointKind::Wallet => "monero-wallet-rpc",
+        })
+    }
+}
+
 /// A lightweight JSON-RPC client for communicating with the Monero daemon.
+/// A wallet RPC version as reported by `get_version`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct RpcVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl RpcVersion {
+    /// Split the `(major << 16) | minor` value `get_version` returns.
+    pub fn from_packed(version: u32) -> Self {
+        Self {
+            major: (version >> 16) as u16,
+            minor: (version & 0xffff) as u16,
+        }
+    }
+}
+
+impl std::fmt::Display for RpcVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Oldest wallet RPC this tool is tested against: the one shipped with
+/// Monero v0.18, whose multisig key exchange rounds it expects.
+pub const MIN_WALLET_RPC_VERSION: RpcVersion = RpcVersion {
+    major: 1,
+    minor: 25,
+};
+
+/// Sent with every request.
+const USER_AGENT: &str = concat!("monero-multisig/", env!("CARGO_PKG_VERSION"));
+
+/// How long an unreachable daemon is skipped before it is tried again.
+pub const FAILOVER_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Calls at least this slow are logged at info level unless the config
+/// says otherwise.
+pub const DEFAULT_SLOW_RPC: Duration = Duration::from_secs(1);
+
+/// Calls allowed in flight to one endpoint unless the config says
+/// otherwise.
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 8;
+
+/// How long the wallet RPC client retries a call while the wallet is busy,
+/// unless the config says otherwise.
+pub const DEFAULT_BUSY_WAIT: Duration = Duration::from_secs(120);
+
+/// First pause between retries of a busy call; it doubles up to
+/// [`BUSY_RETRY_MAX`].
+const BUSY_RETRY_START: Duration = Duration::from_millis(250);
+const BUSY_RETRY_MAX: Duration = Duration::from_secs(8);
+
+/// Endpoints of one client in preference order, with the health state
+/// shared by all clones of it.
+#[derive(Debug)]
+struct Endpoints {
+    urls: Vec<String>,
+    limits: Vec<Limiter>,
+    health: Mutex<Health>,
+}
+
+#[derive(Debug)]
+struct Health {
+    /// The endpoint that last answered.
+    preferred: usize,
+    down_until: Vec<Option<Instant>>,
+}
+
+impl Endpoints {
+    fn new(daemons: &[DaemonRpc]) -> Self {
+        let down_until = vec![None; daemons.len()];
+        Self {
+            urls: daemons.iter().map(DaemonRpc::url).collect(),
+            limits: daemons.iter().map(Limiter::new).collect(),
+            health: Mutex::new(Health {
+                preferred: 0,
+                down_until,
+            }),
+        }
+    }
+
+    /// Indices to try: the last healthy endpoint first, then the rest in
+    /// configured order, with those still cooling down moved to the end.
+    fn order(&self, now: Instant) -> Vec<usize> {
+        let health = self.health.lock().expect("endpoint health lock poisoned");
+        let mut order: Vec<usize> = std::iter::once(health.preferred)
+            .chain((0..self.urls.len()).filter(|&i| i != health.preferred))
+            .collect();
+        order.sort_by_key(|&i| health.down_until[i].is_some_and(|until| until > now));
+        order
+    }
+
+    fn mark_down(&self, index: usize, now: Instant) {
+        let mut health = self.health.lock().expect("endpoint health lock poisoned");
+        health.down_until[index] = Some(now + FAILOVER_COOLDOWN);
+    }
+
+    fn mark_up(&self, index: usize) {
+        let mut health = self.health.lock().expect("endpoint health lock poisoned");
+        health.down_until[index] = None;
+        health.preferred = index;
+    }
+}
+
+/// The gap between call starts for `rps` calls per second, `None` when
+/// it's too long (or not a number) to be a [`Duration`].
+fn call_interval(rps: f64) -> Option<Duration> {
+    Duration::try_from_secs_f64(1.0 / rps).ok()
+}
+
+/// The `max_concurrent_requests` and `max_rps` caps of one endpoint.
+#[derive(Debug)]
+struct Limiter {
+    in_flight: tokio::sync::Semaphore,
+    /// Gap between call starts, when `max_rps` is set.
+    interval: Option<Duration>,
+    next_start: Mutex<Instant>,
+}
+
+impl Limiter {
+    fn new(daemon: &DaemonRpc) -> Self {
+        Self {
+            in_flight: tokio::sync::Semaphore::new(daemon.max_concurrent_requests.max(1)),
+            interval: daemon
+                .max_rps
+                .filter(|rps| *rps > 0.0)
+                .and_then(call_interval),
+            next_start: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait for a free slot and, when throttled, for the next start time.
+    /// Returns the permit to hold for the call and how long was waited.
+    async fn acquire(&self, method: &str) -> (tokio::sync::SemaphorePermit<'_>, Duration) {
+        let started = Instant::now();
+        let mut waited = false;
+        let permit = match self.in_flight.try_acquire() {
+            Ok(permit) => permit,
+            Err(_) => {
+                waited = true;
+                self.in_flight
+                    .acquire()
+                    .await
+                    .expect("rpc limiter semaphore closed")
+            }
+        };
+        if let Some(interval) = self.interval {
+            let start = {
+                let mut next = self.next_start.lock().expect("rpc limiter lock poisoned");
+                let start = (*next).max(Instant::now());
+                *next = start + interval;
+                start
+            };
+            if start > Instant::now() {
+                waited = true;
+                tokio::time::sleep_until(start.into()).await;
+            }
+        }
+        let elapsed = if waited {
+            started.elapsed()
+        } else {
+            Duration::ZERO
+        };
+        if waited {
+            tracing::debug!(
+                rpc_method = method,
+                wait_ms = elapsed.as_millis() as u64,
+                "rpc call throttled"
+            );
+        }
+        (permit, elapsed)
+    }
+}
+
+/// Request ids, unique across every client in the process so log lines
+/// from concurrent calls can't be confused.
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The parts of a client shared by all its clones: the HTTP connection
+/// pool and the endpoint health.
+#[derive(Debug)]
+struct Shared {
+    http: reqwest::Client,
+    endpoints: Endpoints,
+    /// Whether the server answers JSON-RPC batches, learned from the first
+    /// batch sent.
+    batches: std::sync::OnceLock<bool>,
+}
+
+/// A JSON-RPC client. Clones are cheap and share one connection pool,
+/// endpoint health and version check, so a single client can serve
+/// concurrent tasks.
 #[derive(Debug, Clone)]
 pub struct RpcClient {
-    client: reqwest::Client,
-    url: String,
-    request_id: std::sync::Arc<std::sync::atomic::AtomicU64>,
+    shared: Arc<Shared>,
+    progress: bool,
+    offline: bool,
+    slow_after: Duration,
+    busy_wait: Duration,
+    min_version: Option<RpcVersion>,
+    /// `get_version` result, fetched once and shared by clones.
+    version: Arc<tokio::sync::OnceCell<RpcVersion>>,
+    capabilities: Arc<tokio::sync::OnceCell<crate::capabilities::Capabilities>>,
+    /// Outcome of the [`RpcClient::require_version`] check; `None` when the
+    /// version couldn't be read.
+    checked: Arc<tokio::sync::OnceCell<Option<RpcVersion>>>,
 }
 
 impl RpcClient {
     /// Create a new RPC client from daemon connection settings.
     pub fn new(daemon: &DaemonRpc) -> Self {
+        Self::failover(std::slice::from_ref(daemon))
+    }
+
+    /// Create a client that tries `daemons` in order, moving on when one is
+    /// unreachable. Only use this for daemons: a wallet RPC holds local
+    /// state and must not be swapped for another.
+    ///
+    /// # Panics
+    ///
+    /// If `daemons` is empty.
+    pub fn failover(daemons: &[DaemonRpc]) -> Self {
         let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
+            .timeout(Duration::from_secs(30))
+            .user_agent(USER_AGENT)
             .build()
             .expect("failed to build HTTP client");
+        Self::build(client, daemons)
+    }
+
+    /// Create a client for `daemon` that sends through `client`, for
+    /// embedders that tune the connection pool, keep-alive or timeouts
+    /// themselves.
+    pub fn with_client(client: reqwest::Client, daemon: &DaemonRpc) -> Self {
+        Self::build(client, std::slice::from_ref(daemon))
+    }
 
+    fn build(http: reqwest::Client, daemons: &[DaemonRpc]) -> Self {
+        assert!(!daemons.is_empty(), "an RPC client needs an endpoint");
         Self {
-            client,
-            url: daemon.url(),
-            request_id: std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            shared: Arc::new(Shared {
+                http,
+                endpoints: Endpoints::new(daemons),
+                batches: std::sync::OnceLock::new(),
+            }),
+            progress: false,
+            offline: false,
+            slow_after: DEFAULT_SLOW_RPC,
+            busy_wait: Duration::ZERO,
+            min_version: None,
+            version: Arc::new(tokio::sync::OnceCell::new()),
+            capabilities: Arc::new(tokio::sync::OnceCell::new()),
+            checked: Arc::new(tokio::sync::OnceCell::new()),
         }
     }
 
-    /// Return the configured RPC endpoint URL.
-    pub fn url(&self) -> &str {
-        &self.url
+    /// Refuse every request with [`RpcError::UnsupportedVersion`] when the
+    /// server's `get_version` is older than `minimum`. The check runs once,
+    /// before the first request. If the version can't be read the check is
+    /// skipped with a warning and the request itself reports the problem.
+    pub fn require_version(mut self, minimum: RpcVersion) -> Self {
+        self.min_version = Some(minimum);
+        self
+    }
+
+    /// The server's `get_version`, fetched on first use and cached.
+    pub async fn server_version(&self) -> Result<RpcVersion, RpcError> {
+        self.version
+            .get_or_try_init(|| async {
+                let resp: GetVersionResponse =
+                    self.call(GetVersion::NAME, &GetVersionRequest {}).await?;
+                Ok(RpcVersion::from_packed(resp.version))
+            })
+            .await
+            .copied()
+    }
+
+    /// What the server supports, probed on first use and cached like
+    /// [`RpcClient::server_version`].
+    pub async fn capabilities(&self) -> Result<crate::capabilities::Capabilities, RpcError> {
+        self.capabilities
+            .get_or_try_init(|| crate::capabilities::probe(self))
+            .await
+            .copied()
+    }
+
+    /// Guard this client for air-gapped use: any method outside
+    /// [`OFFLINE_METHODS`] fails with [`RpcError::Offline`] before a request is
+    /// sent. In unit tests the guard panics instead, so a forbidden call can't
+    /// go unnoticed.
+    pub fn offline(mut self) -> Self {
+        self.offline = true;
+        self
+    }
+
+    /// Whether the offline guard is active.
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Show a stderr spinner for calls that take longer than
+    /// [`PROGRESS_DELAY`](crate::utils::PROGRESS_DELAY). Off by default.
+    pub fn with_progress(mut self, enabled: bool) -> Self {
+        self.progress = enabled;
+        self
+    }
+
+    /// Log calls that take at least `threshold` at info level instead of
+    /// debug.
+    pub fn with_slow_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_after = threshold;
+        self
+    }
+
+    /// Retry calls the server answers as busy for up to `wait` in total,
+    /// backing off between tries; zero, the default, fails at once. Other
+    /// errors are never retried. Meant for the wallet RPC, whose busy
+    /// answers end when its refresh does; a daemon's don't.
+    pub fn with_busy_wait(mut self, wait: Duration) -> Self {
+        self.busy_wait = wait;
+        self
+    }
+
+    /// Whether this client draws progress spinners.
+    pub fn progress_enabled(&self) -> bool {
+        self.progress
+    }
+
+    /// The RPC endpoint URL that will be tried first.
+    pub fn url(&self) -> String {
+        let index = self.shared.endpoints.order(Instant::now())[0];
+        self.shared.endpoints.urls[index].clone()
+    }
+
+    /// Run `attempt` against each endpoint in turn until one is reachable,
+    /// within that endpoint's limits. Any response, including an RPC error,
+    /// ends the search.
+    async fn with_failover<R, F, Fut>(
+        &self,
+        method: &str,
+        stats: &CallStats,
+        attempt: F,
+    ) -> Result<R, RpcError>
+    where
+        F: Fn(String) -> Fut,
+        Fut: std::future::Future<Output = Result<R, RpcError>>,
+    {
+        let order = self.shared.endpoints.order(Instant::now());
+        let mut tries = order.iter().peekable();
+        while let Some(&index) = tries.next() {
+            let url = &self.shared.endpoints.urls[index];
+            let (_permit, waited) = self.shared.endpoints.limits[index].acquire(method).await;
+            stats.add_wait(waited);
+            match attempt(url.clone()).await {
+                Err(e) if e.is_unreachable() => {
+                    self.shared.endpoints.mark_down(index, Instant::now());
+                    let Some(&&next) = tries.peek() else {
+                        return Err(e);
+                    };
+                    tracing::warn!(
+                        rpc_method = method,
+                        from = %url,
+                        to = %self.shared.endpoints.urls[next],
+                        error = %e,
+                        "daemon unreachable, failing over"
+                    );
+                }
+                result => {
+                    self.shared.endpoints.mark_up(index);
+                    return result;
+                }
+            }
+        }
+        unreachable!("a client always has at least one endpoint")
     }
 
     /// Send a JSON-RPC request and deserialize the result.
-    pub async fn request<P, R>(&self, method: &str, params: &P) -> anyhow::Result<R>
+    pub async fn request<P, R>(&self, method: &str, params: &P) -> Result<R, RpcError>
+    where
+        P: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.check_version(method).await?;
+        self.call(method, params).await
+    }
+
+    /// Call the method `M` from the [`crate::rpc::methods`] catalog.
+    /// Errors are the same as for [`RpcClient::request`].
+    pub async fn invoke<M: RpcMethod>(&self, req: M::Request) -> Result<M::Response, RpcError> {
+        self.request(M::NAME, &req).await
+    }
+
+    /// Call any wallet RPC method, including ones this crate doesn't wrap,
+    /// and return its `result` as it came. Errors are the same as for
+    /// [`RpcClient::request`]; a JSON-RPC error object is an
+    /// [`RpcError::Server`] whose [`RpcError::json_rpc_error`] has the code.
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    /// use monero_multisig::config::{DaemonRpc, RpcClient};
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// # let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    /// # let port = listener.local_addr().unwrap().port();
+    /// # tokio::spawn(async move {
+    /// #     while let Ok((mut stream, _)) = listener.accept().await {
+    /// #         let _ = stream.read(&mut [0; 4096]).await;
+    /// #         let body = r#"{"jsonrpc":"2.0","id":"0","result":{"version":65563}}"#;
+    /// #         let response = format!(
+    /// #             "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+    /// #             body.len()
+    /// #         );
+    /// #         let _ = stream.write_all(response.as_bytes()).await;
+    /// #     }
+    /// # });
+    /// let rpc = RpcClient::new(&DaemonRpc { port, ..DaemonRpc::default() });
+    /// let result = rpc.call_raw("get_version", serde_json::json!({})).await?;
+    /// assert_eq!(result["version"], 65563);
+    /// # Ok::<_, monero_multisig::config::RpcError>(())
+    /// # }).unwrap();
+    /// ```
+    pub async fn call_raw(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, RpcError> {
+        self.request(method, &params).await
+    }
+
+    /// [`RpcClient::call_raw`], then deserialize the result as `R`. If it
+    /// doesn't fit, the error is [`RpcError::UnexpectedResult`], naming the
+    /// method and quoting the start of what came back.
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    /// use monero_multisig::config::{DaemonRpc, RpcClient, RpcError};
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Version {
+    ///     version: u32,
+    /// }
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// # let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    /// # let port = listener.local_addr().unwrap().port();
+    /// # tokio::spawn(async move {
+    /// #     while let Ok((mut stream, _)) = listener.accept().await {
+    /// #         let _ = stream.read(&mut [0; 4096]).await;
+    /// #         let body = r#"{"jsonrpc":"2.0","id":"0","result":{"version":65563}}"#;
+    /// #         let response = format!(
+    /// #             "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+    /// #             body.len()
+    /// #         );
+    /// #         let _ = stream.write_all(response.as_bytes()).await;
+    /// #     }
+    /// # });
+    /// let rpc = RpcClient::new(&DaemonRpc { port, ..DaemonRpc::default() });
+    /// let version: Version = rpc.call_typed("get_version", serde_json::json!({})).await?;
+    /// assert_eq!(version.version, 65563);
+    ///
+    /// let err = rpc
+    ///     .call_typed::<String>("get_version", serde_json::json!({}))
+    ///     .await
+    ///     .unwrap_err();
+    /// assert!(matches!(err, RpcError::UnexpectedResult { .. }));
+    /// assert!(err.to_string().contains(r#"{"version":65563}"#));
+    /// # Ok::<_, RpcError>(())
+    /// # }).unwrap();
+    /// ```
+    pub async fn call_typed<R>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<R, RpcError>
+    where
+        R: serde::de::DeserializeOwned,
+    {
+        let result = self.call_raw(method, params).await?;
+        R::deserialize(&result).map_err(|source| {
+            let mut response = result.to_string();
+            if response.len() > RESPONSE_EXCERPT_LEN {
+                let mut end = RESPONSE_EXCERPT_LEN;
+                while !response.is_char_boundary(end) {
+                    end -= 1;
+                }
+                response.truncate(end);
+                response.push('…');
+            }
+            RpcError::UnexpectedResult {
+                method: method.to_string(),
+                response,
+                source,
+            }
+        })
+    }
+
+    /// Send several read-only calls as one JSON-RPC batch and return one
+    /// result per call, in the order given; a failing call fails only its
+    /// own entry. Servers that don't take batches get the calls one at a
+    /// time instead. The first batch finds out which kind the server is and
+    /// the answer is kept for every clone.
+    pub async fn request_batch(
+        &self,
+        calls: &[(&str, serde_json::Value)],
+    ) -> Result<Vec<Result<serde_json::Value, RpcError>>, RpcError> {
+        let Some(&(first, _)) = calls.first() else {
+            return Ok(Vec::new());
+        };
+        for (method, _) in calls {
+            self.guard_offline(method)?;
+        }
+        self.check_version(first).await?;
+
+        if self.shared.batches.get() != Some(&false) {
+            match self.call_batch(calls).await {
+                Ok(results) => {
+                    let _ = self.shared.batches.set(true);
+                    return Ok(results);
+                }
+                Err(e) if self.shared.batches.get().is_none() && !e.is_unreachable() => {
+                    tracing::debug!(error = %e, "batch requests not supported, sending calls one at a time");
+                    let _ = self.shared.batches.set(false);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        let mut results = Vec::with_capacity(calls.len());
+        for (method, params) in calls {
+            results.push(self.call(method, params).await);
+        }
+        Ok(results)
+    }
+
+    /// Fail with [`RpcError::UnsupportedVersion`] when the server is older
+    /// than [`RpcClient::require_version`] asked for.
+    async fn check_version(&self, method: &str) -> Result<(), RpcError> {
+        if let Some(minimum) = self.min_version {
+            let checked = self
+                .checked
+                .get_or_init(|| async {
+                    match self.server_version().await {
+                        Ok(found) => Some(found),
+                        Err(e) => {
+                            tracing::warn!(
+                                error = %e,
+                                "could not read the wallet RPC version; skipping the compatibility check"
+                            );
+                            None
+                        }
+                    }
+                })
+                .await;
+            if let Some(found) = checked.filter(|found| *found < minimum) {
+                return Err(RpcError::UnsupportedVersion {
+                    method: method.to_string(),
+                    found,
+                    minimum,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Refuse `method` when the offline guard is on and it needs the
+    /// network.
+    fn guard_offline(&self, method: &str) -> Result<(), RpcError> {
+        if self.offline && !OFFLINE_METHODS.contains(&method) {
+            if cfg!(test) {
+                panic!("offline guard: forbidden RPC method {method}");
+            }
+            return Err(RpcError::Offline {
+                method: method.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// [`RpcClient::request`] without the version check, for probing the
+    /// server itself.
+    pub(crate) async fn call<P, R>(&self, method: &str, params: &P) -> Result<R, RpcError>
     where
         P: Serialize,
         R: for<'de> Deserialize<'de>,
     {
-        let id = self
-            .request_id
-            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.guard_offline(method)?;
+
+        let id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
 
         let body = serde_json::json!({
             "jsonrpc": "2.0",
@@ -162,31 +1439,334 @@ impl RpcClient {
             "params": params,
         });
 
+        let stats = CallStats {
+            request_bytes: body.to_string().len(),
+            ..CallStats::default()
+        };
+        let span = tracing::info_span!(
+            "rpc",
+            rpc_method = method,
+            request_id = id,
+            request_bytes = stats.request_bytes
+        );
+        let started = std::time::Instant::now();
+        let progress = self.progress.then(|| crate::utils::Progress::start(method));
+        let mut pause = BUSY_RETRY_START;
+        let result = loop {
+            let result = self
+                .with_failover(method, &stats, |url| self.send(method, url, &body, &stats))
+                .instrument(span.clone())
+                .await;
+            let left = self.busy_wait.saturating_sub(started.elapsed());
+            match result {
+                Err(e) if e.is_busy() && !left.is_zero() => {
+                    tracing::debug!(rpc_method = method, error = %e, "server busy, retrying");
+                    if let Some(progress) = &progress {
+                        progress.set_message("wallet busy, waiting…");
+                    }
+                    tokio::time::sleep(pause.min(left)).await;
+                    pause = (pause * 2).min(BUSY_RETRY_MAX);
+                }
+                result => break result,
+            }
+        };
+        drop(progress);
+        self.log_call(method, Some(id), &stats, started, result.as_ref().err());
+        result
+    }
+
+    /// Send `calls` as one batch array and match the answers to them by id.
+    async fn call_batch(
+        &self,
+        calls: &[(&str, serde_json::Value)],
+    ) -> Result<Vec<Result<serde_json::Value, RpcError>>, RpcError> {
+        let ids: Vec<String> = calls
+            .iter()
+            .map(|_| NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed).to_string())
+            .collect();
+        let body: serde_json::Value = calls
+            .iter()
+            .zip(&ids)
+            .map(|((method, params), id)| {
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": method,
+                    "params": params,
+                })
+            })
+            .collect();
+
+        let stats = CallStats {
+            request_bytes: body.to_string().len(),
+            ..CallStats::default()
+        };
+        let span = tracing::info_span!(
+            "rpc",
+            rpc_method = BATCH_METHOD,
+            calls = calls.len(),
+            request_bytes = stats.request_bytes
+        );
+        let started = std::time::Instant::now();
+        let progress = self
+            .progress
+            .then(|| crate::utils::Progress::start(BATCH_METHOD));
+        let result = self
+            .with_failover(BATCH_METHOD, &stats, |url| {
+                self.send::<serde_json::Value>(BATCH_METHOD, url, &body, &stats)
+            })
+            .instrument(span)
+            .await;
+        drop(progress);
+        self.log_call(BATCH_METHOD, None, &stats, started, result.as_ref().err());
+
+        let mut answers: Vec<BatchAnswer> =
+            serde_json::from_value(result?).map_err(|source| RpcError::InvalidResponse {
+                method: BATCH_METHOD.to_string(),
+                source,
+            })?;
+        Ok(calls
+            .iter()
+            .zip(&ids)
+            .map(|((method, _), id)| {
+                let Some(i) = answers.iter().position(|a| a.id_matches(id)) else {
+                    return Err(RpcError::EmptyResponse {
+                        method: method.to_string(),
+                    });
+                };
+                match answers.swap_remove(i) {
+                    BatchAnswer {
+                        result: Some(result),
+                        ..
+                    } => Ok(result),
+                    BatchAnswer { error: Some(e), .. } => Err(server_error(method, e)),
+                    _ => Err(RpcError::EmptyResponse {
+                        method: method.to_string(),
+                    }),
+                }
+            })
+            .collect())
+    }
+
+    /// POST to one of the daemon's plain-JSON endpoints (e.g.
+    /// `/send_raw_transaction`) that sit beside `/json_rpc`, and deserialize
+    /// the whole response body.
+    pub async fn request_other<P, R>(&self, path: &str, params: &P) -> Result<R, RpcError>
+    where
+        P: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.guard_offline(path)?;
+
+        let stats = CallStats {
+            request_bytes: serde_json::to_string(params).map_or(0, |s| s.len()),
+            ..CallStats::default()
+        };
+        let span = tracing::info_span!(
+            "rpc",
+            rpc_method = path,
+            request_bytes = stats.request_bytes
+        );
+        let started = std::time::Instant::now();
+        let result = self
+            .with_failover(path, &stats, |url| {
+                self.send_other(path, url, params, &stats)
+            })
+            .instrument(span)
+            .await;
+        self.log_call(path, None, &stats, started, result.as_ref().err());
+        result
+    }
+
+    /// Log a finished call, at info level when it was slow, and count it in
+    /// [`crate::metrics`]. Logged outside the `rpc` span so the line still
+    /// names the command it belongs to.
+    fn log_call(
+        &self,
+        method: &str,
+        request_id: Option<u64>,
+        stats: &CallStats,
+        started: Instant,
+        error: Option<&RpcError>,
+    ) {
+        let elapsed = started.elapsed();
+        let response_bytes = stats.response_bytes.load(Ordering::Relaxed);
+        let http_status = Some(stats.http_status.load(Ordering::Relaxed)).filter(|&s| s != 0);
+        let waited = stats.waited();
+        crate::metrics::record_rpc(method, error.is_none(), elapsed, waited, response_bytes);
+        let message = if error.is_some() {
+            "rpc call failed"
+        } else {
+            "rpc call completed"
+        };
+        macro_rules! log_at {
+            ($level:expr) => {
+                tracing::event!(
+                    $level,
+                    rpc_method = method,
+                    request_id,
+                    request_bytes = stats.request_bytes,
+                    response_bytes,
+                    http_status,
+                    duration_ms = elapsed.as_millis() as u64,
+                    wait_ms = waited.as_millis() as u64,
+                    error = error.map(tracing::field::display),
+                    "{message}"
+                )
+            };
+        }
+        if elapsed >= self.slow_after {
+            log_at!(tracing::Level::INFO);
+        } else {
+            log_at!(tracing::Level::DEBUG);
+        }
+    }
+
+    async fn send_other<P, R>(
+        &self,
+        path: &str,
+        url: String,
+        params: &P,
+        stats: &CallStats,
+    ) -> Result<R, RpcError>
+    where
+        P: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        let transport = |source| RpcError::Transport {
+            method: path.to_string(),
+            source,
+        };
+        // Resolve next to the JSON-RPC path, so a proxy prefix is kept.
+        let url = url::Url::parse(&url)
+            .and_then(|base| base.join(path))
+            .map(String::from)
+            .unwrap_or_else(|_| format!("{}/{path}", url.trim_end_matches("/json_rpc")));
         let response = self
-            .client
-            .post(&self.url)
-            .json(&body)
+            .shared
+            .http
+            .post(&url)
+            .json(params)
             .send()
-            .await?
-            .error_for_status()?;
+            .await
+            .map_err(transport)?;
+        stats.record_status(&response);
+        let response = response.error_for_status().map_err(transport)?;
+        let status = response.status().as_u16();
+        let content_type = content_type(&response);
+        let resp_text = response.text().await.map_err(transport)?;
+        stats
+            .response_bytes
+            .store(resp_text.len(), Ordering::Relaxed);
+        if let Some(e) = non_json(path, status, content_type.as_deref(), &resp_text) {
+            return Err(e);
+        }
 
-        let resp_text = response.text().await?;
+        serde_json::from_str(&resp_text).map_err(|source| RpcError::InvalidResponse {
+            method: path.to_string(),
+            source,
+        })
+    }
 
-        tracing::debug!("RPC response for {method}: {resp_text}");
+    async fn send<R>(
+        &self,
+        method: &str,
+        url: String,
+        body: &serde_json::Value,
+        stats: &CallStats,
+    ) -> Result<R, RpcError>
+    where
+        R: for<'de> Deserialize<'de>,
+    {
+        tracing::debug!(
+            "RPC request for {method}: {}",
+            crate::utils::redact_payload(&body.to_string())
+        );
 
-        let rpc_response: JsonRpcResponse<R> = serde_json::from_str(&resp_text)
-            .map_err(|e| anyhow::anyhow!("failed to parse RPC response: {e}"))?;
+        let transport = |source| RpcError::Transport {
+            method: method.to_string(),
+            source,
+        };
 
-        match rpc_response.result {
-            Some(result) => Ok(result),
-            None => {
-                let err = rpc_response
-                    .error
-                    .map(|e| format!("{} (code: {})", e.message, e.code))
-                    .unwrap_or_else(|| "unknown RPC error".to_string());
-                Err(anyhow::anyhow!("RPC error: {err}"))
+        let response = self
+            .shared
+            .http
+            .post(&url)
+            .json(body)
+            .send()
+            .await
+            .map_err(transport)?;
+        stats.record_status(&response);
+        let response = response.error_for_status().map_err(transport)?;
+        let status = response.status().as_u16();
+        let content_type = content_type(&response);
+
+        let resp_text = response.text().await.map_err(transport)?;
+        stats
+            .response_bytes
+            .store(resp_text.len(), Ordering::Relaxed);
+
+        tracing::debug!(
+            "RPC response for {method}: {}",
+            crate::utils::redact_payload(&resp_text)
+        );
+        if let Some(e) = non_json(method, status, content_type.as_deref(), &resp_text) {
+            return Err(e);
+        }
+
+        if method == BATCH_METHOD {
+            // A batch answer is a bare array; a server without batch
+            // support answers with a single error object instead.
+            if resp_text.trim_start().starts_with('[') {
+                return serde_json::from_str(&resp_text).map_err(|source| {
+                    RpcError::InvalidResponse {
+                        method: method.to_string(),
+                        source,
+                    }
+                });
             }
         }
+
+        let rpc_response: JsonRpcResponse<R> =
+            serde_json::from_str(&resp_text).map_err(|source| RpcError::InvalidResponse {
+                method: method.to_string(),
+                source,
+            })?;
+
+        match (rpc_response.result, rpc_response.error) {
+            (Some(result), _) => Ok(result),
+            (None, Some(e)) => Err(server_error(method, e)),
+            (None, None) => Err(RpcError::EmptyResponse {
+                method: method.to_string(),
+            }),
+        }
+    }
+}
+
+/// Sizes and status of one call, filled in by the endpoint that answered.
+#[derive(Debug, Default)]
+struct CallStats {
+    request_bytes: usize,
+    /// 0 until a response arrives.
+    http_status: AtomicU16,
+    response_bytes: AtomicUsize,
+    /// Time spent waiting on endpoint limits, in microseconds.
+    waited_us: AtomicU64,
+}
+
+impl CallStats {
+    fn add_wait(&self, waited: Duration) {
+        self.waited_us
+            .fetch_add(waited.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn waited(&self) -> Duration {
+        Duration::from_micros(self.waited_us.load(Ordering::Relaxed))
+    }
+
+    fn record_status(&self, response: &reqwest::Response) {
+        self.http_status
+            .store(response.status().as_u16(), Ordering::Relaxed);
     }
 }
 
@@ -196,8 +1776,842 @@ struct JsonRpcResponse<T> {
     error: Option<JsonRpcError>,
 }
 
+/// The `error` object of a JSON-RPC response, as
+/// [`RpcError::json_rpc_error`] returns it.
+#[derive(Error, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[error("{message} (code: {code})")]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+/// How much of an unparseable result [`RpcError::UnexpectedResult`] keeps.
+pub const RESPONSE_EXCERPT_LEN: usize = 200;
+
+/// How much of a non-JSON body [`RpcError::NotJson`] keeps, in characters.
+pub const NON_JSON_EXCERPT_CHARS: usize = 120;
+
+fn excerpt_note(excerpt: &str) -> String {
+    if excerpt.is_empty() {
+        String::new()
+    } else {
+        format!(" It starts: {excerpt:?}")
+    }
+}
+
+fn content_type(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_ascii_lowercase)
+}
+
+/// [`RpcError::NotJson`] for a body that can't be a JSON-RPC answer, such
+/// as a web server's page or a proxy's error text, so the error says what
+/// came back instead of where the JSON parser gave up. A body that looks
+/// like JSON is left to the parser whatever its content type.
+fn non_json(method: &str, status: u16, content_type: Option<&str>, body: &str) -> Option<RpcError> {
+    let body = body.trim();
+    let what = if body.is_empty() {
+        "an empty body".to_string()
+    } else if body.starts_with(['{', '[']) {
+        return None;
+    } else if body.starts_with('<') || content_type.is_some_and(|t| t.contains("html")) {
+        "HTML".to_string()
+    } else {
+        match content_type {
+            Some(t) => format!("non-JSON {t}"),
+            None => "non-JSON text".to_string(),
+        }
+    };
+    let mut excerpt = String::new();
+    let mut last_space = false;
+    for c in body.chars() {
+        let space = c.is_whitespace() || c.is_control();
+        if !(space && last_space) {
+            excerpt.push(if space { ' ' } else { c });
+        }
+        last_space = space;
+        if excerpt.chars().count() == NON_JSON_EXCERPT_CHARS {
+            break;
+        }
+    }
+    Some(RpcError::NotJson {
+        method: method.to_string(),
+        status,
+        what,
+        excerpt,
+    })
+}
+
+/// The error for a JSON-RPC error object answering `method`: a
+/// [`RpcError::WrongEndpoint`] when the server lacks a method its kind of
+/// server would have, else [`RpcError::Server`].
+fn server_error(method: &str, e: JsonRpcError) -> RpcError {
+    match EndpointKind::of_method(method) {
+        Some(expected) if e.code == METHOD_NOT_FOUND => RpcError::WrongEndpoint {
+            method: method.to_string(),
+            message: e.message,
+            expected,
+        },
+        _ => RpcError::Server {
+            method: method.to_string(),
+            code: e.code,
+            message: e.message,
+        },
+    }
+}
+
+/// Name a batch request is logged and counted under.
+const BATCH_METHOD: &str = "batch";
+
+/// One entry of a batch response.
 #[derive(Debug, Deserialize)]
-struct JsonRpcError {
-    code: i64,
-    message: String,
+struct BatchAnswer {
+    #[serde(default)]
+    id: serde_json::Value,
+    result: Option<serde_json::Value>,
+    error: Option<JsonRpcError>,
+}
+
+impl BatchAnswer {
+    /// Whether this answers the call sent with `id`. Some servers echo ids
+    /// back as numbers.
+    fn id_matches(&self, id: &str) -> bool {
+        match &self.id {
+            serde_json::Value::String(s) => s == id,
+            serde_json::Value::Number(n) => n.to_string() == id,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        echo_daemon, handler_daemon, method_daemon, slow_daemon, stub_daemon, typed_stub_daemon,
+    };
+    use std::sync::atomic::Ordering;
+
+    fn dead_daemon() -> DaemonRpc {
+        // Nothing listens on port 1.
+        DaemonRpc {
+            port: 1,
+            ..DaemonRpc::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_and_remembers_healthy_endpoint() {
+        let (live, hits) = stub_daemon(r#"{"jsonrpc":"2.0","id":"0","result":{"ok":true}}"#).await;
+        let rpc = RpcClient::failover(&[dead_daemon(), live.clone()]);
+
+        let result: serde_json::Value = rpc
+            .request("get_info", &serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(result["ok"], true);
+        assert_eq!(rpc.url(), live.url());
+
+        // The dead node is cooling down, so the next call goes straight to
+        // the live one.
+        let _: serde_json::Value = rpc
+            .request("get_info", &serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    /// A server that answers busy to the first `busy` calls, then `{}`.
+    async fn busy_daemon(busy: usize, code: i64) -> (DaemonRpc, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let daemon = handler_daemon(move |_| {
+            if counter.fetch_add(1, Ordering::SeqCst) < busy {
+                Err((code, "Wallet is busy refreshing".into()))
+            } else {
+                Ok(serde_json::json!({}))
+            }
+        })
+        .await;
+        (daemon, hits)
+    }
+
+    #[tokio::test]
+    async fn test_busy_server_is_retried() {
+        let (daemon, hits) = busy_daemon(2, BUSY).await;
+        let rpc = RpcClient::new(&daemon).with_busy_wait(DEFAULT_BUSY_WAIT);
+        rpc.call_raw("refresh", serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        // Some builds answer with a generic code and only say so.
+        let (daemon, hits) = busy_daemon(2, -1).await;
+        let rpc = RpcClient::new(&daemon).with_busy_wait(DEFAULT_BUSY_WAIT);
+        rpc.call_raw("refresh", serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_busy_wait_gives_up() {
+        let (daemon, hits) = busy_daemon(usize::MAX, BUSY).await;
+        // Without a busy wait, as for the daemon client.
+        let rpc = RpcClient::new(&daemon);
+        let err = rpc
+            .call_raw("refresh", serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(err.is_busy(), "{err}");
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        let rpc = RpcClient::new(&daemon).with_busy_wait(Duration::from_millis(600));
+        let started = std::time::Instant::now();
+        assert!(rpc
+            .call_raw("refresh", serde_json::json!({}))
+            .await
+            .unwrap_err()
+            .is_busy());
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(hits.load(Ordering::SeqCst) > 2);
+    }
+
+    #[tokio::test]
+    async fn test_other_errors_are_not_retried() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let daemon = handler_daemon(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Err((-1, "Failed to open wallet".into()))
+        })
+        .await;
+        let err = RpcClient::new(&daemon)
+            .call_raw("open_wallet", serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(!err.is_busy());
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_call_typed_quotes_the_unexpected_result() {
+        let daemon = method_daemon(&[(
+            "t_long", r#"{"items":["aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"]}"#,
+        )])
+        .await;
+        let rpc = RpcClient::new(&daemon);
+
+        let raw = rpc.call_raw("t_long", serde_json::json!({})).await.unwrap();
+        assert!(raw["items"].is_array());
+
+        let err = rpc
+            .call_typed::<Vec<String>>("t_long", serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(err.method(), "t_long");
+        let RpcError::UnexpectedResult { response, .. } = &err else {
+            panic!("expected UnexpectedResult, got {err:?}");
+        };
+        assert!(response.starts_with(r#"{"items":["aaa"#), "{response}");
+        assert_eq!(response.len(), RESPONSE_EXCERPT_LEN + '…'.len_utf8());
+        assert!(err
+            .to_string()
+            .starts_with("t_long RPC call failed: unexpected result"));
+
+        let err = rpc
+            .call_raw("t_missing", serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.json_rpc_error(),
+            Some(JsonRpcError {
+                code: METHOD_NOT_FOUND,
+                message: "Method not found".into(),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_html_and_empty_bodies_are_named() {
+        let (web, _) = typed_stub_daemon(
+            "text/html; charset=utf-8",
+            "<!DOCTYPE html>\n<html>\n  <head><title>Welcome to nginx!</title></head>\n</html>",
+        )
+        .await;
+        let rpc = RpcClient::new(&web);
+        let err = rpc
+            .request::<_, serde_json::Value>("get_info", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, RpcError::NotJson { status: 200, .. }),
+            "{err:?}"
+        );
+        let message = err.to_string();
+        assert!(
+            message.contains(
+                "endpoint returned HTML (status 200) — is this really a monero JSON-RPC port?"
+            ),
+            "{message}"
+        );
+        // Newlines and runs of spaces are folded so the excerpt stays on
+        // one line.
+        assert!(
+            message.contains(r#"It starts: "<!DOCTYPE html> <html> <head><title>Welcome"#),
+            "{message}"
+        );
+        // The daemon's plain endpoints are checked the same way.
+        let err = rpc
+            .request_other::<_, serde_json::Value>("get_transactions", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RpcError::NotJson { .. }), "{err:?}");
+
+        let long: &'static str = "x".repeat(500).leak();
+        let (text, _) = typed_stub_daemon("text/plain", long).await;
+        let err = RpcClient::new(&text)
+            .request::<_, serde_json::Value>("get_info", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        let RpcError::NotJson { what, excerpt, .. } = &err else {
+            panic!("{err:?}");
+        };
+        assert_eq!(what, "non-JSON text/plain");
+        assert_eq!(excerpt.chars().count(), NON_JSON_EXCERPT_CHARS);
+
+        let (empty, _) = stub_daemon("").await;
+        let err = RpcClient::new(&empty)
+            .request::<_, serde_json::Value>("get_info", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "get_info RPC call failed: endpoint returned an empty body (status 200) — is this \
+             really a monero JSON-RPC port?"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restricted_rpc_method_not_found_suggests_swapped_endpoints() {
+        // A daemon's /json_rpc knows no wallet methods.
+        let daemon = method_daemon(&[("get_info", r#"{"height":100}"#)]).await;
+        let rpc = RpcClient::new(&daemon);
+        let err = rpc
+            .request::<_, serde_json::Value>("get_balance", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                RpcError::WrongEndpoint {
+                    expected: EndpointKind::Wallet,
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+        assert!(err.is_method_not_found());
+        assert_eq!(err.code(), Some(METHOD_NOT_FOUND));
+        let message = err.to_string();
+        assert!(
+            message.contains(
+                "get_balance is a monero-wallet-rpc method, so this endpoint is probably monerod"
+            ),
+            "{message}"
+        );
+        assert!(message.contains("not the other way round"), "{message}");
+
+        // And a wallet RPC knows no daemon methods.
+        let wallet = method_daemon(&[("get_balance", r#"{"balance":0}"#)]).await;
+        let err = RpcClient::new(&wallet)
+            .request::<_, serde_json::Value>("get_info", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains(
+                "get_info is a monerod method, so this endpoint is probably monero-wallet-rpc"
+            ),
+            "{err}"
+        );
+
+        // A method an older wallet RPC may lack is just missing.
+        let err = rpc
+            .request::<_, serde_json::Value>("describe_transfer", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RpcError::Server { .. }), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn test_calls_are_timed_with_response_size() {
+        const BODY: &str = r#"{"jsonrpc":"2.0","id":"0","result":{"ok":true}}"#;
+        let (stub, _) = stub_daemon(BODY).await;
+        let rpc = RpcClient::new(&stub).with_slow_threshold(Duration::ZERO);
+        let _: serde_json::Value = rpc
+            .request("t_timed_get_info", &serde_json::json!({}))
+            .await
+            .unwrap();
+        let timing = crate::metrics::rpc_timings()
+            .into_iter()
+            .find(|t| t.method == "t_timed_get_info")
+            .unwrap();
+        assert_eq!(timing.count, 1);
+        assert!(crate::metrics::render().contains(&format!(
+            "multisig_rpc_response_bytes_total{{method=\"t_timed_get_info\"}} {}",
+            BODY.len()
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_parallel_clones_use_unique_request_ids() {
+        let rpc = RpcClient::with_client(reqwest::Client::new(), &echo_daemon().await);
+        let calls = (0..100).map(|_| {
+            let rpc = rpc.clone();
+            tokio::spawn(async move {
+                let result: serde_json::Value = rpc
+                    .request("get_info", &serde_json::json!({}))
+                    .await
+                    .unwrap();
+                result["id"].as_str().unwrap().to_string()
+            })
+        });
+        let mut ids = std::collections::HashSet::new();
+        for call in calls.collect::<Vec<_>>() {
+            assert!(ids.insert(call.await.unwrap()), "request id reused");
+        }
+        assert_eq!(ids.len(), 100);
+    }
+
+    #[tokio::test]
+    async fn test_parallel_calls_stay_within_concurrency_limit() {
+        let (daemon, peak) = slow_daemon(
+            r#"{"jsonrpc":"2.0","id":"0","result":{}}"#,
+            Duration::from_millis(50),
+        )
+        .await;
+        let rpc = RpcClient::new(&DaemonRpc {
+            max_concurrent_requests: 3,
+            ..daemon
+        });
+        let calls: Vec<_> = (0..12)
+            .map(|_| {
+                let rpc = rpc.clone();
+                tokio::spawn(async move {
+                    rpc.request::<_, serde_json::Value>("t_limited", &serde_json::json!({}))
+                        .await
+                        .unwrap();
+                })
+            })
+            .collect();
+        for call in calls {
+            call.await.unwrap();
+        }
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+        let timing = crate::metrics::rpc_timings()
+            .into_iter()
+            .find(|t| t.method == "t_limited")
+            .unwrap();
+        assert!(timing.waited > Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_max_rps_spaces_out_calls() {
+        let (daemon, _) = stub_daemon(r#"{"jsonrpc":"2.0","id":"0","result":{}}"#).await;
+        let rpc = RpcClient::new(&DaemonRpc {
+            max_rps: Some(20.0),
+            ..daemon
+        });
+        let started = Instant::now();
+        for _ in 0..3 {
+            let _: serde_json::Value = rpc
+                .request("get_info", &serde_json::json!({}))
+                .await
+                .unwrap();
+        }
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_batch_matches_answers_by_id_and_fails_per_call() {
+        let rpc = RpcClient::new(&echo_daemon().await);
+        let results = rpc
+            .request_batch(&[
+                ("get_height", serde_json::json!({})),
+                ("t_fail", serde_json::json!({})),
+                ("get_accounts", serde_json::json!({})),
+            ])
+            .await
+            .unwrap();
+        // The stub answers in reverse order.
+        assert_eq!(results[0].as_ref().unwrap()["method"], "get_height");
+        let error = results[1].as_ref().unwrap_err();
+        assert_eq!((error.method(), error.code()), ("t_fail", Some(-1)));
+        assert_eq!(results[2].as_ref().unwrap()["method"], "get_accounts");
+    }
+
+    #[tokio::test]
+    async fn test_batch_falls_back_to_single_calls_once_probed() {
+        // Answers every request, batch or not, with a single object.
+        let (stub, hits) = stub_daemon(r#"{"jsonrpc":"2.0","id":"0","result":{"ok":true}}"#).await;
+        let rpc = RpcClient::new(&stub);
+        let calls = [
+            ("get_height", serde_json::json!({})),
+            ("get_accounts", serde_json::json!({})),
+        ];
+        let results = rpc.request_batch(&calls).await.unwrap();
+        assert!(results.iter().all(|r| r.as_ref().unwrap()["ok"] == true));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        // The failed probe isn't repeated.
+        rpc.clone().request_batch(&calls).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn test_rpc_error_is_not_retried_elsewhere() {
+        let (first, first_hits) =
+            stub_daemon(r#"{"jsonrpc":"2.0","id":"0","error":{"code":-1,"message":"busy"}}"#).await;
+        let (second, second_hits) = stub_daemon(r#"{"jsonrpc":"2.0","id":"0","result":{}}"#).await;
+        let rpc = RpcClient::failover(&[first, second]);
+
+        let err = rpc
+            .request::<_, serde_json::Value>("get_info", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RpcError::Server { .. }));
+        assert_eq!(first_hits.load(Ordering::SeqCst), 1);
+        assert_eq!(second_hits.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_version_floor_checked_once() {
+        // 1.27: every request gets the same body, which also parses as a
+        // plain JSON value.
+        let (wallet, hits) =
+            stub_daemon(r#"{"jsonrpc":"2.0","id":"0","result":{"version":65563}}"#).await;
+        let rpc = RpcClient::new(&wallet).require_version(MIN_WALLET_RPC_VERSION);
+        for _ in 0..3 {
+            let _: serde_json::Value = rpc
+                .request("get_balance", &serde_json::json!({}))
+                .await
+                .unwrap();
+        }
+        assert_eq!(hits.load(Ordering::SeqCst), 4);
+        assert_eq!(rpc.server_version().await.unwrap().to_string(), "1.27");
+    }
+
+    #[tokio::test]
+    async fn test_old_wallet_rpc_refused() {
+        // 1.20
+        let (wallet, hits) =
+            stub_daemon(r#"{"jsonrpc":"2.0","id":"0","result":{"version":65556}}"#).await;
+        let rpc = RpcClient::new(&wallet).require_version(MIN_WALLET_RPC_VERSION);
+        let err = rpc
+            .request::<_, serde_json::Value>("exchange_multisig_keys", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RpcError::UnsupportedVersion { .. }));
+        let message = err.to_string();
+        assert!(
+            message.contains("1.20") && message.contains("1.25"),
+            "{message}"
+        );
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_endpoint_order_skips_cooling_down() {
+        let endpoints = Endpoints::new(&[endpoint("a"), endpoint("b"), endpoint("c")]);
+        let now = Instant::now();
+        assert_eq!(endpoints.order(now), [0, 1, 2]);
+
+        endpoints.mark_down(0, now);
+        endpoints.mark_up(2);
+        assert_eq!(endpoints.order(now), [2, 1, 0]);
+
+        // After the cool-down the first node is back in its usual place.
+        assert_eq!(endpoints.order(now + FAILOVER_COOLDOWN * 2), [2, 0, 1]);
+    }
+
+    fn endpoint(host: &str) -> DaemonRpc {
+        DaemonRpc {
+            host: host.into(),
+            ..DaemonRpc::default()
+        }
+    }
+
+    #[test]
+    fn test_url_brackets_ipv6() {
+        assert_eq!(endpoint("::1").url(), "http://[::1]:18081/json_rpc");
+        assert_eq!(
+            endpoint("[fe80::2]").url(),
+            "http://[fe80::2]:18081/json_rpc"
+        );
+        assert_eq!(
+            endpoint("127.0.0.1").url(),
+            "http://127.0.0.1:18081/json_rpc"
+        );
+    }
+
+    #[test]
+    fn test_url_onion_and_custom_path() {
+        let onion = DaemonRpc {
+            port: 18089,
+            path: Some("wallet/json_rpc".into()),
+            ..endpoint("abcdefghijklmnopqrstuvwxyz234567abcdefghijklmnopqrstuvwx.onion")
+        };
+        assert_eq!(
+            onion.url(),
+            "http://abcdefghijklmnopqrstuvwxyz234567abcdefghijklmnopqrstuvwx.onion:18089/wallet/json_rpc"
+        );
+    }
+
+    #[test]
+    fn test_explicit_url_scheme_overrides_tls() {
+        let plain = DaemonRpc {
+            tls: true,
+            url: Some("http://node.example.com:18081".into()),
+            ..DaemonRpc::default()
+        };
+        assert_eq!(plain.url(), "http://node.example.com:18081/json_rpc");
+
+        let proxied = DaemonRpc {
+            tls: true,
+            url: Some("rpc.example.com/wallet/json_rpc".into()),
+            ..DaemonRpc::default()
+        };
+        assert_eq!(proxied.url(), "https://rpc.example.com/wallet/json_rpc");
+    }
+
+    #[test]
+    fn test_invalid_url_names_value() {
+        let bad = DaemonRpc {
+            url: Some("ftp://node.example.com".into()),
+            ..DaemonRpc::default()
+        };
+        let err = bad.parse_url().unwrap_err();
+        assert!(err.to_string().contains("ftp://node.example.com"), "{err}");
+
+        let spaces = endpoint("bad host");
+        assert!(matches!(
+            spaces.parse_url(),
+            Err(ConfigError::InvalidUrl { value, .. }) if value.contains("bad host")
+        ));
+    }
+
+    #[test]
+    fn test_max_rps_must_be_usable() {
+        for (rps, ok) in [(5.0, true), (0.0, true), (-1.0, false), (1e-320, false)] {
+            let config = Config {
+                daemon: vec![DaemonRpc {
+                    max_rps: Some(rps),
+                    ..DaemonRpc::default()
+                }],
+                ..Config::default()
+            };
+            assert_eq!(config.validate().is_ok(), ok, "{rps}");
+            // The limiter never panics on one that slipped past.
+            Limiter::new(&config.daemon[0]);
+        }
+    }
+
+    #[test]
+    fn test_daemon_accepts_object_or_list() {
+        let one: Config = serde_json::from_value(serde_json::json!({
+            "network": "mainnet",
+            "daemon": { "host": "a", "port": 1, "tls": false },
+            "data_dir": "/tmp/x",
+        }))
+        .unwrap();
+        assert_eq!(one.daemon.len(), 1);
+
+        let many: Config = serde_json::from_value(serde_json::json!({
+            "network": "mainnet",
+            "daemon": [
+                { "host": "a", "port": 1, "tls": false },
+                { "host": "b", "port": 2, "tls": true },
+            ],
+            "data_dir": "/tmp/x",
+        }))
+        .unwrap();
+        assert_eq!(many.daemon[1].host, "b");
+
+        let empty = serde_json::from_value::<Config>(serde_json::json!({
+            "network": "mainnet",
+            "daemon": [],
+            "data_dir": "/tmp/x",
+        }));
+        assert!(empty.is_err());
+    }
+
+    #[test]
+    fn test_expand_data_dir() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_path(Path::new("~")).unwrap(), home);
+        assert_eq!(
+            expand_path(Path::new("~/wallets")).unwrap(),
+            home.join("wallets")
+        );
+        assert_eq!(
+            expand_path(Path::new("/srv/~x")).unwrap(),
+            PathBuf::from("/srv/~x")
+        );
+
+        std::env::set_var("MONERO_MULTISIG_TEST_DIR", "/srv/backup");
+        assert_eq!(
+            expand_path(Path::new("$MONERO_MULTISIG_TEST_DIR/a")).unwrap(),
+            PathBuf::from("/srv/backup/a")
+        );
+        assert_eq!(
+            expand_path(Path::new("${MONERO_MULTISIG_TEST_DIR}x")).unwrap(),
+            PathBuf::from("/srv/backupx")
+        );
+        let err = expand_path(Path::new("/a/$MONERO_MULTISIG_UNSET_VAR")).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("MONERO_MULTISIG_UNSET_VAR is not set"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_resolve_creates_private_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = DataDir::new(tmp.path().join("a/b")).resolve().unwrap();
+        assert!(dir.is_dir());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(dir.path()).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        let file = tmp.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        let err = DataDir::new(&file).resolve().unwrap_err();
+        assert!(
+            err.to_string().contains(&file.display().to_string()),
+            "{err}"
+        );
+        assert!(err.to_string().contains("not a directory"), "{err}");
+    }
+
+    #[test]
+    fn test_select_wallet() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = || Config {
+            data_dir: DataDir::new(tmp.path()),
+            ..Config::default()
+        };
+        let wallets = tmp.path().join(WALLETS_DIR);
+
+        // None yet: the data directory itself holds the wallet.
+        let mut none = config();
+        assert_eq!(none.select_wallet(None).unwrap(), None);
+        assert_eq!(none.data_dir.path(), tmp.path());
+
+        std::fs::create_dir_all(wallets.join("alice")).unwrap();
+        let mut one = config();
+        assert_eq!(one.select_wallet(None).unwrap(), Some("alice".into()));
+        assert_eq!(one.data_dir.path(), wallets.join("alice"));
+
+        std::fs::create_dir_all(wallets.join("bob")).unwrap();
+        let err = config().select_wallet(None).unwrap_err();
+        assert!(matches!(
+            &err,
+            ConfigError::WalletNotChosen { names, .. } if names == &["alice", "bob"]
+        ));
+        assert!(err.to_string().contains("alice, bob"), "{err}");
+
+        let mut configured = Config {
+            default_wallet: Some("bob".into()),
+            ..config()
+        };
+        assert_eq!(configured.select_wallet(None).unwrap(), None);
+        assert_eq!(configured.data_dir.path(), wallets.join("bob"));
+
+        // The flag wins over the config, and names a wallet still to come.
+        let mut flagged = Config {
+            default_wallet: Some("bob".into()),
+            ..config()
+        };
+        assert_eq!(flagged.select_wallet(Some("carol")).unwrap(), None);
+        assert_eq!(flagged.data_dir.path(), wallets.join("carol"));
+        assert!(wallets.join("carol").is_dir());
+
+        for name in ["", "..", ".hidden", "a/b", "a\\b"] {
+            assert!(matches!(
+                config().select_wallet(Some(name)),
+                Err(ConfigError::InvalidWalletName(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_save_default_wallet() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.json");
+        std::fs::write(
+            &path,
+            r#"{"network": "stagenet", "daemon": {"host": "node", "port": 18081}, "data_dir": "/tmp/x"}"#,
+        )
+        .unwrap();
+        save_default_wallet(&path, "alice").unwrap();
+        let config = Config::load(Some(&path), Some(tmp.path())).unwrap();
+        assert_eq!(config.default_wallet.as_deref(), Some("alice"));
+        assert_eq!(config.daemon[0].host, "node");
+
+        assert!(matches!(
+            save_default_wallet(&tmp.path().join("missing.json"), "bob"),
+            Err(ConfigError::ReadError(_))
+        ));
+        assert!(save_default_wallet(&path, "../elsewhere").is_err());
+    }
+
+    #[test]
+    fn test_save_leaves_out_the_password() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.json");
+        let config = Config {
+            daemon: vec![DaemonRpc {
+                username: Some("monero".into()),
+                password: Some(Secret::new("hunter2".into())),
+                ..DaemonRpc::default()
+            }],
+            ..Config::default()
+        };
+        config.save(&path).unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("hunter2"));
+        let saved = Config::load(Some(&path), Some(tmp.path())).unwrap();
+        assert_eq!(saved.daemon[0].username.as_deref(), Some("monero"));
+        assert!(saved.daemon[0].password.is_none());
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "offline guard: forbidden RPC method transfer")]
+    async fn test_offline_guard_panics_on_forbidden_method() {
+        let rpc = RpcClient::new(&DaemonRpc::default()).offline();
+        let _: Result<serde_json::Value, _> = rpc.request("transfer", &serde_json::json!({})).await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "offline guard: forbidden RPC method /send_raw_transaction")]
+    async fn test_offline_guard_covers_other_endpoints() {
+        let rpc = RpcClient::new(&DaemonRpc::default()).offline();
+        let _: Result<serde_json::Value, _> = rpc
+            .request_other("/send_raw_transaction", &serde_json::json!({}))
+            .await;
+    }
+
+    #[test]
+    fn test_offline_methods_exclude_network_calls() {
+        for method in ["transfer", "submit_multisig", "refresh", "get_transfers"] {
+            assert!(!OFFLINE_METHODS.contains(&method));
+        }
+    }
 }
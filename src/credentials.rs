@@ -0,0 +1,206 @@
+//! Wallet passwords kept in the OS keyring.
+//!
+//! With the `keyring` cargo feature, `credentials store` saves a wallet's
+//! password in the platform keyring (Secret Service or the kernel keyring on
+//! Linux, Keychain on macOS, Credential Manager on Windows) under the service
+//! [`SERVICE`], keyed by wallet label. Commands that need the password look
+//! there after `--password` and `MONERO_MULTISIG_WALLET_PASSWORD`, and ask
+//! on the terminal when none of them has one. A keyring that can't be reached
+//! only warns: the lookup falls through as if nothing were stored. Setting
+//! `use_keyring` to `false` in the config turns all of it off. Retrieved
+//! passwords are never printed or logged.
+
+#[cfg(feature = "cli")]
+use std::io::IsTerminal;
+
+use thiserror::Error;
+
+use crate::secret::Secret;
+
+/// Keyring service name the passwords are stored under.
+pub const SERVICE: &str = "monero-multisig";
+
+/// Environment variable checked for the wallet password after `--password`.
+pub const PASSWORD_ENV: &str = "MONERO_MULTISIG_WALLET_PASSWORD";
+
+#[derive(Error, Debug)]
+pub enum CredentialsError {
+    #[error("this build has no keyring support — rebuild with `--features keyring`")]
+    Unsupported,
+
+    #[error("keyring use is disabled by `use_keyring: false` in the config")]
+    Disabled,
+
+    #[error("keyring: {0}")]
+    Keyring(String),
+
+    #[error(
+        "no wallet password for \"{0}\" and no terminal to ask on; pass --password, set \
+         MONERO_MULTISIG_WALLET_PASSWORD (empty for a wallet without one) or store it with \
+         `credentials store`"
+    )]
+    NoPassword(String),
+
+    #[error("could not read the password: {0}")]
+    Prompt(String),
+}
+
+/// Keyring user name of the password for the wallet labelled `label`.
+pub fn account(label: &str) -> String {
+    format!("{label}/wallet-password")
+}
+
+/// The wallet password for `label`: `flag` if given, then the environment,
+/// then the keyring when `use_keyring` is set, and otherwise asked for on the
+/// terminal. With no terminal to ask on this fails with
+/// [`CredentialsError::NoPassword`] instead of trying an empty password.
+pub fn wallet_password(
+    flag: Option<String>,
+    label: &str,
+    use_keyring: bool,
+) -> Result<Secret<String>, CredentialsError> {
+    let found = flag
+        .map(Secret::new)
+        .or_else(|| std::env::var(PASSWORD_ENV).ok().map(Secret::new))
+        .or_else(|| {
+            if !use_keyring {
+                return None;
+            }
+            match lookup(label) {
+                Ok(found) => found,
+                Err(e) => {
+                    eprintln!("warning: could not read the keyring ({e}); continuing without it");
+                    None
+                }
+            }
+        });
+    match found {
+        Some(password) => Ok(password),
+        None => ask(label),
+    }
+}
+
+fn ask(label: &str) -> Result<Secret<String>, CredentialsError> {
+    #[cfg(feature = "cli")]
+    if std::io::stdin().is_terminal() {
+        return prompt_password(&format!("Wallet password for \"{label}\": "));
+    }
+    Err(CredentialsError::NoPassword(label.to_string()))
+}
+
+/// Save `password` for the wallet labelled `label`.
+pub fn store(
+    label: &str,
+    password: &Secret<String>,
+    use_keyring: bool,
+) -> Result<(), CredentialsError> {
+    if !use_keyring {
+        return Err(CredentialsError::Disabled);
+    }
+    imp::store(label, password)
+}
+
+/// Remove the password for `label`. Returns whether one was stored.
+pub fn clear(label: &str, use_keyring: bool) -> Result<bool, CredentialsError> {
+    if !use_keyring {
+        return Err(CredentialsError::Disabled);
+    }
+    imp::clear(label)
+}
+
+fn lookup(label: &str) -> Result<Option<Secret<String>>, CredentialsError> {
+    imp::lookup(label)
+}
+
+/// Ask for a password on the terminal without echoing it.
+#[cfg(feature = "cli")]
+pub fn prompt_password(prompt: &str) -> Result<Secret<String>, CredentialsError> {
+    let _prompt = crate::interrupt::Prompt::start();
+    rpassword::prompt_password(prompt)
+        .map(Secret::new)
+        .map_err(|e| CredentialsError::Prompt(e.to_string()))
+}
+
+#[cfg(feature = "keyring")]
+mod imp {
+    use super::{account, CredentialsError, Secret, SERVICE};
+
+    fn entry(label: &str) -> Result<keyring::Entry, CredentialsError> {
+        keyring::Entry::new(SERVICE, &account(label))
+            .map_err(|e| CredentialsError::Keyring(e.to_string()))
+    }
+
+    pub fn store(label: &str, password: &Secret<String>) -> Result<(), CredentialsError> {
+        entry(label)?
+            .set_password(password.expose())
+            .map_err(|e| CredentialsError::Keyring(e.to_string()))
+    }
+
+    pub fn clear(label: &str) -> Result<bool, CredentialsError> {
+        match entry(label)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(e) => Err(CredentialsError::Keyring(e.to_string())),
+        }
+    }
+
+    pub fn lookup(label: &str) -> Result<Option<Secret<String>>, CredentialsError> {
+        match entry(label)?.get_password() {
+            Ok(password) => Ok(Some(Secret::new(password))),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(CredentialsError::Keyring(e.to_string())),
+        }
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+mod imp {
+    use super::{CredentialsError, Secret};
+
+    pub fn store(_label: &str, _password: &Secret<String>) -> Result<(), CredentialsError> {
+        Err(CredentialsError::Unsupported)
+    }
+
+    pub fn clear(_label: &str) -> Result<bool, CredentialsError> {
+        Err(CredentialsError::Unsupported)
+    }
+
+    /// Nothing is ever stored without keyring support.
+    pub fn lookup(_label: &str) -> Result<Option<Secret<String>>, CredentialsError> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flag_wins_and_disabled_keyring_is_refused() {
+        assert_eq!(
+            wallet_password(Some("from-flag".into()), "ops", true)
+                .unwrap()
+                .expose(),
+            "from-flag"
+        );
+        assert_eq!(account("ops"), "ops/wallet-password");
+        assert!(matches!(
+            store("ops", &Secret::new("pw".into()), false),
+            Err(CredentialsError::Disabled)
+        ));
+        assert!(matches!(
+            clear("ops", false),
+            Err(CredentialsError::Disabled)
+        ));
+    }
+
+    #[cfg(not(feature = "keyring"))]
+    #[test]
+    fn test_without_feature_nothing_is_stored() {
+        assert!(matches!(
+            store("ops", &Secret::new("pw".into()), true),
+            Err(CredentialsError::Unsupported)
+        ));
+        assert!(lookup("ops").unwrap().is_none());
+    }
+}
@@ -0,0 +1,365 @@
+//! Terminal presentation: aligned tables and status colors.
+//!
+//! Everything here works on plain strings handed over by the command
+//! handlers; result types never know how they are drawn, so `--output json`
+//! is unaffected. Color is used only when stdout is a terminal, `NO_COLOR` is
+//! unset, and `--no-color` was not given. Tables shrink to the terminal width
+//! (from `COLUMNS`) by shortening columns marked as shrinkable; piped output
+//! is never shrunk.
+
+use std::io::IsTerminal;
+
+/// Width assumed for a terminal that doesn't report one.
+const DEFAULT_WIDTH: usize = 100;
+/// Shrinkable columns are never cut below this many characters.
+const MIN_COLUMN: usize = 10;
+const GAP: &str = "  ";
+
+/// ANSI colors used for statuses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Green,
+    Yellow,
+    Red,
+    Dim,
+}
+
+impl Color {
+    fn code(self) -> &'static str {
+        match self {
+            Color::Green => "32",
+            Color::Yellow => "33",
+            Color::Red => "31",
+            Color::Dim => "2",
+        }
+    }
+}
+
+/// How output is drawn on this run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    pub color: bool,
+    /// Terminal width to fit tables into; `None` when not a terminal.
+    pub width: Option<usize>,
+    /// Group the digits of XMR amounts in threes; see [`Style::xmr`].
+    pub group_digits: bool,
+}
+
+impl Style {
+    /// Plain text: no color, no width limit.
+    pub const PLAIN: Style = Style {
+        color: false,
+        width: None,
+        group_digits: false,
+    };
+
+    /// Decide from stdout, the environment, and `--no-color`.
+    pub fn detect(no_color: bool) -> Self {
+        if !std::io::stdout().is_terminal() {
+            return Self::PLAIN;
+        }
+        let env_no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let width = std::env::var("COLUMNS")
+            .ok()
+            .and_then(|c| c.parse().ok())
+            .filter(|&w| w > 0)
+            .unwrap_or(DEFAULT_WIDTH);
+        Style {
+            color: !no_color && !env_no_color,
+            width: Some(width),
+            group_digits: false,
+        }
+    }
+
+    /// `piconero` in XMR for a table or summary, grouped in threes when
+    /// [`Style::group_digits`] is set. Never for JSON or for text that is
+    /// parsed back.
+    pub fn xmr(&self, piconero: u64) -> String {
+        if self.group_digits {
+            crate::transaction::format_xmr_grouped(piconero)
+        } else {
+            crate::transaction::format_xmr(piconero)
+        }
+    }
+
+    /// `text` in `color`, or unchanged without color.
+    pub fn paint(&self, text: &str, color: Color) -> String {
+        if self.color {
+            format!("\x1b[{}m{text}\x1b[0m", color.code())
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn bold(&self, text: &str) -> String {
+        if self.color {
+            format!("\x1b[1m{text}\x1b[0m")
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// One table column.
+#[derive(Debug, Clone)]
+pub struct Column {
+    header: String,
+    align: Align,
+    shrink: bool,
+}
+
+impl Column {
+    pub fn left(header: &str) -> Self {
+        Column {
+            header: header.to_string(),
+            align: Align::Left,
+            shrink: false,
+        }
+    }
+
+    pub fn right(header: &str) -> Self {
+        Column {
+            align: Align::Right,
+            ..Column::left(header)
+        }
+    }
+
+    /// Let this column be shortened in the middle to fit the terminal. Leave
+    /// it off for anything the user must read in full, like a destination
+    /// they are about to pay.
+    pub fn shrink(mut self) -> Self {
+        self.shrink = true;
+        self
+    }
+}
+
+/// A table cell, optionally colored.
+#[derive(Debug, Clone, Default)]
+pub struct Cell {
+    text: String,
+    color: Option<Color>,
+}
+
+impl Cell {
+    pub fn colored(text: impl Into<String>, color: Color) -> Self {
+        Cell {
+            text: text.into(),
+            color: Some(color),
+        }
+    }
+}
+
+impl<T: Into<String>> From<T> for Cell {
+    fn from(text: T) -> Self {
+        Cell {
+            text: text.into(),
+            color: None,
+        }
+    }
+}
+
+/// Rows of cells under a header line.
+#[derive(Debug, Clone)]
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    pub fn new(columns: Vec<Column>) -> Self {
+        Table {
+            columns,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Append a row; missing trailing cells are left blank.
+    pub fn row(&mut self, cells: Vec<Cell>) {
+        self.rows.push(cells);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Render with a trailing newline. Trailing spaces are trimmed.
+    pub fn render(&self, style: &Style) -> String {
+        let widths = self.widths(style.width);
+        let mut out = String::new();
+
+        let headers: Vec<Cell> = self
+            .columns
+            .iter()
+            .map(|c| c.header.as_str().into())
+            .collect();
+        out.push_str(&self.line(&headers, &widths, style, true));
+        let rule: Vec<Cell> = widths.iter().map(|&w| "-".repeat(w).into()).collect();
+        out.push_str(&self.line(&rule, &widths, &Style::PLAIN, false));
+        for row in &self.rows {
+            out.push_str(&self.line(row, &widths, style, false));
+        }
+        out
+    }
+
+    fn widths(&self, limit: Option<usize>) -> Vec<usize> {
+        let mut widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                self.rows
+                    .iter()
+                    .filter_map(|r| r.get(i))
+                    .map(|cell| cell.text.chars().count())
+                    .chain([c.header.chars().count()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let Some(limit) = limit else {
+            return widths;
+        };
+        let gaps = GAP.len() * widths.len().saturating_sub(1);
+        while widths.iter().sum::<usize>() + gaps > limit {
+            let widest = (0..widths.len())
+                .filter(|&i| self.columns[i].shrink && widths[i] > MIN_COLUMN)
+                .max_by_key(|&i| widths[i]);
+            match widest {
+                Some(i) => widths[i] -= 1,
+                None => break,
+            }
+        }
+        widths
+    }
+
+    fn line(&self, cells: &[Cell], widths: &[usize], style: &Style, header: bool) -> String {
+        let mut parts = Vec::with_capacity(widths.len());
+        for (i, &width) in widths.iter().enumerate() {
+            let cell = cells.get(i).cloned().unwrap_or_default();
+            let text = fit(&cell.text, width);
+            let pad = " ".repeat(width - text.chars().count());
+            let text = match (header, cell.color) {
+                (true, _) => style.bold(&text),
+                (false, Some(color)) => style.paint(&text, color),
+                (false, None) => text,
+            };
+            parts.push(match self.columns[i].align {
+                Align::Left => format!("{text}{pad}"),
+                Align::Right => format!("{pad}{text}"),
+            });
+        }
+        let mut line = parts.join(GAP).trim_end().to_string();
+        line.push('\n');
+        line
+    }
+}
+
+/// `text` cut to `width` characters, keeping both ends.
+fn fit(text: &str, width: usize) -> String {
+    let count = text.chars().count();
+    if count <= width {
+        return text.to_string();
+    }
+    if width < 5 {
+        return text.chars().take(width).collect();
+    }
+    let head = (width - 3).div_ceil(2);
+    let tail = width - 3 - head;
+    let start: String = text.chars().take(head).collect();
+    let end: String = text.chars().skip(count - tail).collect();
+    format!("{start}...{end}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Table {
+        let mut table = Table::new(vec![
+            Column::left("Status"),
+            Column::right("Amount"),
+            Column::left("Tx hash").shrink(),
+        ]);
+        table.row(vec![
+            Cell::colored("ready", Color::Green),
+            "1.5".into(),
+            "0123456789abcdef0123456789abcdef".into(),
+        ]);
+        table.row(vec!["waiting".into(), "12.25".into(), "ff".into()]);
+        table
+    }
+
+    #[test]
+    fn plain_table_aligns_columns() {
+        assert_eq!(
+            sample().render(&Style::PLAIN),
+            "Status   Amount  Tx hash\n\
+             -------  ------  --------------------------------\n\
+             ready       1.5  0123456789abcdef0123456789abcdef\n\
+             waiting   12.25  ff\n"
+        );
+    }
+
+    #[test]
+    fn narrow_terminal_shrinks_marked_columns() {
+        let style = Style {
+            width: Some(30),
+            ..Style::PLAIN
+        };
+        let rendered = sample().render(&style);
+        assert!(
+            rendered.lines().all(|l| l.chars().count() <= 30),
+            "{rendered}"
+        );
+        assert!(rendered.contains("01234...bcdef"), "{rendered}");
+
+        // Columns that may not shrink stay whole even past the width.
+        let mut table = Table::new(vec![Column::left("Address")]);
+        table.row(vec!["4".repeat(95).into()]);
+        assert!(table.render(&style).contains(&"4".repeat(95)));
+    }
+
+    #[test]
+    fn grouped_amounts_only_for_display() {
+        const PICONERO: u64 = 1_234_567_890_000_000_000;
+        let grouped = Style {
+            group_digits: true,
+            ..Style::PLAIN
+        };
+        assert_eq!(grouped.xmr(PICONERO), "1,234,567.890000000000");
+        assert_eq!(Style::PLAIN.xmr(PICONERO), "1234567.890000000000");
+        // JSON carries a plain integer whatever the style.
+        let amount = crate::transaction::Amount(PICONERO);
+        assert_eq!(
+            serde_json::to_string(&amount).unwrap(),
+            "1234567890000000000"
+        );
+    }
+
+    #[test]
+    fn color_only_when_enabled() {
+        let style = Style {
+            color: true,
+            ..Style::PLAIN
+        };
+        let rendered = sample().render(&style);
+        assert!(rendered.contains("\x1b[32mready\x1b[0m  "), "{rendered}");
+        assert!(rendered.starts_with("\x1b[1mStatus\x1b[0m"));
+        assert!(!sample().render(&Style::PLAIN).contains('\x1b'));
+    }
+
+    #[test]
+    fn fit_keeps_both_ends() {
+        assert_eq!(fit("abcdefghij", 10), "abcdefghij");
+        assert_eq!(fit("abcdefghij", 7), "ab...ij");
+        assert_eq!(fit("🦀🦀🦀🦀🦀🦀", 5), "🦀...🦀");
+        assert_eq!(fit("abcdef", 3), "abc");
+    }
+}
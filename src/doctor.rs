@@ -0,0 +1,306 @@
+//! `doctor --latency`: how quickly the wallet RPC answers, and why it might
+//! not.
+//!
+//! Three cheap calls are timed [`ITERATIONS`] times each. Samples come from
+//! the client's own instrumentation ([`crate::metrics::rpc_timing`]), with
+//! time spent waiting on the endpoint's request limits left out, so they
+//! measure the server and the link to it. Which calls are slow points at a
+//! cause:
+//!
+//! - `get_version` touches neither the chain nor the wallet file, so it is
+//!   the bare round trip. When it is slow, so is everything: a Tor circuit
+//!   or a distant node.
+//! - `get_height` is slow beyond the round trip when the wallet RPC is busy
+//!   keeping up with a daemon that is still syncing. The daemon saying it
+//!   isn't synchronized points the same way.
+//! - `get_balance` is slow beyond the round trip when the wallet file is
+//!   large or on slow storage.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{RpcClient, RpcError};
+use crate::rpc::methods::{
+    GetBalance, GetBalanceRequest, GetHeight, GetHeightRequest, GetInfo, GetInfoRequest,
+    GetVersion, GetVersionRequest,
+};
+
+/// Times each call is made.
+pub const ITERATIONS: usize = 10;
+
+/// The `latency` section of the config file: how long calls may take before
+/// `doctor --latency` calls them slow, in milliseconds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyBudgets {
+    /// 95th percentile of `get_version`, the bare round trip.
+    #[serde(default = "default_get_version_ms")]
+    pub get_version_ms: u64,
+    #[serde(default = "default_get_height_ms")]
+    pub get_height_ms: u64,
+    #[serde(default = "default_get_balance_ms")]
+    pub get_balance_ms: u64,
+    /// Total RPC time, waits for request limits excluded, after which any
+    /// command suggests running `doctor --latency`.
+    #[serde(default = "default_command_total_ms")]
+    pub command_total_ms: u64,
+}
+
+impl Default for LatencyBudgets {
+    fn default() -> Self {
+        Self {
+            get_version_ms: default_get_version_ms(),
+            get_height_ms: default_get_height_ms(),
+            get_balance_ms: default_get_balance_ms(),
+            command_total_ms: default_command_total_ms(),
+        }
+    }
+}
+
+fn default_get_version_ms() -> u64 {
+    500
+}
+
+fn default_get_height_ms() -> u64 {
+    1_000
+}
+
+fn default_get_balance_ms() -> u64 {
+    2_000
+}
+
+fn default_command_total_ms() -> u64 {
+    15_000
+}
+
+impl LatencyBudgets {
+    fn budget(&self, method: &str) -> u64 {
+        match method {
+            "get_version" => self.get_version_ms,
+            "get_height" => self.get_height_ms,
+            _ => self.get_balance_ms,
+        }
+    }
+
+    /// Whether RPC calls taking `total` in all, as a command's
+    /// [`crate::metrics::rpc_timings`] add up, are worth a hint.
+    pub fn command_too_slow(&self, total: Duration) -> bool {
+        total > Duration::from_millis(self.command_total_ms)
+    }
+}
+
+/// How one method fared over the iterations.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MethodLatency {
+    pub method: String,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub budget_ms: u64,
+    /// Whether `p95_ms` is over `budget_ms`.
+    pub over_budget: bool,
+}
+
+impl MethodLatency {
+    /// Summarize `samples`, which must not be empty.
+    pub fn new(method: &str, samples: &[Duration], budgets: &LatencyBudgets) -> Self {
+        let budget_ms = budgets.budget(method);
+        let p95_ms = percentile(samples, 95).as_millis() as u64;
+        Self {
+            method: method.to_string(),
+            p50_ms: percentile(samples, 50).as_millis() as u64,
+            p95_ms,
+            budget_ms,
+            over_budget: p95_ms > budget_ms,
+        }
+    }
+}
+
+/// A likely reason for slow calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cause {
+    /// Every call pays a slow round trip.
+    NetworkLatency,
+    /// The daemon is catching up with the chain.
+    DaemonSyncing,
+    /// Reading the wallet file is slow.
+    DiskBoundWallet,
+}
+
+impl Cause {
+    /// What the operator can do about it.
+    pub fn advice(self) -> &'static str {
+        match self {
+            Cause::NetworkLatency => {
+                "every call pays a slow round trip: expect this over Tor, otherwise use a closer node"
+            }
+            Cause::DaemonSyncing => {
+                "the daemon is still syncing and keeps the wallet RPC busy: wait for it to catch up, or use a synced node"
+            }
+            Cause::DiskBoundWallet => {
+                "reading the wallet file is slow: move it to faster storage, or check that the disk isn't saturated"
+            }
+        }
+    }
+}
+
+/// The `doctor --latency` result.
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyReport {
+    pub iterations: usize,
+    pub methods: Vec<MethodLatency>,
+    /// Whether the daemon says it is synchronized; `None` when it couldn't
+    /// be asked.
+    pub daemon_synchronized: Option<bool>,
+    /// Likely causes, most likely first. Empty when every call is within
+    /// budget.
+    pub causes: Vec<Cause>,
+}
+
+/// The `p`th percentile of `samples` by the nearest-rank method, or zero for
+/// no samples.
+pub fn percentile(samples: &[Duration], p: usize) -> Duration {
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied().unwrap_or_default()
+}
+
+/// Which causes explain `methods`. `get_height` and `get_balance` only count
+/// against their own cause when they are slower than the round trip by more
+/// than a whole `get_version` budget; otherwise the round trip explains them.
+pub fn diagnose(methods: &[MethodLatency], daemon_synchronized: Option<bool>) -> Vec<Cause> {
+    let find = |name: &str| methods.iter().find(|m| m.method == name);
+    let mut causes = Vec::new();
+    let (round_trip, slack) = match find("get_version") {
+        Some(version) => {
+            if version.over_budget {
+                causes.push(Cause::NetworkLatency);
+            }
+            (version.p50_ms, version.budget_ms)
+        }
+        None => (0, 0),
+    };
+    let slow_by_itself = |name: &str| {
+        find(name).is_some_and(|m| m.over_budget && m.p95_ms.saturating_sub(round_trip) > slack)
+    };
+    if slow_by_itself("get_height") || daemon_synchronized == Some(false) {
+        causes.push(Cause::DaemonSyncing);
+    }
+    if slow_by_itself("get_balance") {
+        causes.push(Cause::DiskBoundWallet);
+    }
+    causes
+}
+
+/// Time `get_version`, `get_height` and `get_balance` of `account` on
+/// `rpc`, [`ITERATIONS`] rounds of each, and ask `daemon` whether it is
+/// synchronized. Any failed call ends the measurement.
+pub async fn measure(
+    rpc: &RpcClient,
+    daemon: &RpcClient,
+    account: u32,
+    budgets: &LatencyBudgets,
+) -> Result<LatencyReport, RpcError> {
+    const METHODS: [&str; 3] = ["get_version", "get_height", "get_balance"];
+    let mut samples: [Vec<Duration>; 3] = Default::default();
+    for _ in 0..ITERATIONS {
+        let before = METHODS.map(served_time);
+        rpc.invoke::<GetVersion>(GetVersionRequest {}).await?;
+        rpc.invoke::<GetHeight>(GetHeightRequest {}).await?;
+        rpc.invoke::<GetBalance>(GetBalanceRequest {
+            account_index: Some(account),
+            ..Default::default()
+        })
+        .await?;
+        for (i, method) in METHODS.into_iter().enumerate() {
+            samples[i].push(served_time(method).saturating_sub(before[i]));
+        }
+    }
+    let methods: Vec<MethodLatency> = METHODS
+        .iter()
+        .zip(&samples)
+        .map(|(method, samples)| MethodLatency::new(method, samples, budgets))
+        .collect();
+    // Only a hint: a daemon that can't be reached doesn't stop the report.
+    let daemon_synchronized = daemon
+        .invoke::<GetInfo>(GetInfoRequest {})
+        .await
+        .map(|info| info.synchronized)
+        .ok();
+    Ok(LatencyReport {
+        iterations: ITERATIONS,
+        causes: diagnose(&methods, daemon_synchronized),
+        methods,
+        daemon_synchronized,
+    })
+}
+
+/// Time spent so far on calls of `method`, waits for request limits left out.
+fn served_time(method: &str) -> Duration {
+    crate::metrics::rpc_timing(method)
+        .map(|t| t.total.saturating_sub(t.waited))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn latency(method: &str, p50_ms: u64, p95_ms: u64) -> MethodLatency {
+        let budgets = LatencyBudgets::default();
+        MethodLatency::new(
+            method,
+            &[Duration::from_millis(p50_ms), Duration::from_millis(p95_ms)],
+            &budgets,
+        )
+    }
+
+    #[test]
+    fn test_percentile() {
+        let ms = Duration::from_millis;
+        let samples: Vec<Duration> = (1..=10).rev().map(|i| ms(i * 10)).collect();
+        assert_eq!(percentile(&samples, 50), ms(50));
+        assert_eq!(percentile(&samples, 95), ms(100));
+        assert_eq!(percentile(&samples[9..], 95), ms(10));
+        assert_eq!(percentile(&[], 50), Duration::ZERO);
+
+        let two = latency("get_height", 200, 1_500);
+        assert_eq!((two.p50_ms, two.p95_ms), (200, 1_500));
+        assert!(two.over_budget);
+    }
+
+    #[test]
+    fn test_diagnose() {
+        let fast = [
+            latency("get_version", 20, 40),
+            latency("get_height", 30, 50),
+            latency("get_balance", 40, 90),
+        ];
+        assert_eq!(diagnose(&fast, Some(true)), []);
+        assert_eq!(diagnose(&fast, Some(false)), [Cause::DaemonSyncing]);
+        assert_eq!(diagnose(&fast, None), []);
+
+        // Over Tor every call is slow, and no slower than the round trip.
+        let tor = [
+            latency("get_version", 900, 1_200),
+            latency("get_height", 950, 1_300),
+            latency("get_balance", 1_000, 1_900),
+        ];
+        assert_eq!(diagnose(&tor, Some(true)), [Cause::NetworkLatency]);
+
+        let disk = [
+            latency("get_version", 20, 40),
+            latency("get_height", 30, 50),
+            latency("get_balance", 1_800, 4_000),
+        ];
+        assert_eq!(diagnose(&disk, Some(true)), [Cause::DiskBoundWallet]);
+
+        let syncing = [
+            latency("get_version", 20, 40),
+            latency("get_height", 900, 3_000),
+            latency("get_balance", 40, 90),
+        ];
+        assert_eq!(diagnose(&syncing, None), [Cause::DaemonSyncing]);
+    }
+}
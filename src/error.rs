@@ -1,5 +1,10 @@
 use thiserror::Error;
 
+pub use crate::config::RpcError;
+
+/// Convenience alias for results returned by the library.
+pub type Result<T, E = MultisigError> = std::result::Result<T, E>;
+
 /// Top-level error type for the multisig wallet tool.
 #[derive(Error, Debug)]
 pub enum MultisigError {
@@ -12,11 +17,44 @@ pub enum MultisigError {
     #[error("configuration error: {0}")]
     Config(#[from] crate::config::ConfigError),
 
-    #[error("RPC error: {0}")]
-    Rpc(String),
+    #[error(transparent)]
+    Rpc(#[from] RpcError),
+
+    #[error("invalid armored data: {0}")]
+    Armor(#[from] crate::armor::ArmorError),
+
+    #[error("invalid blob: {0}")]
+    Blob(#[from] crate::utils::BlobError),
+
+    #[error("tx set manifest: {0}")]
+    Manifest(#[from] crate::manifest::ManifestError),
+
+    #[error("group bundle: {0}")]
+    Bundle(#[from] crate::bundle::BundleError),
+
+    #[error("spend proof error: {0}")]
+    Proof(#[from] crate::proof::ProofError),
+
+    #[error("invalid payment URI: {0}")]
+    Uri(#[from] crate::uri::UriError),
 
-    #[error("{0}")]
-    Other(#[from] anyhow::Error),
+    #[error(transparent)]
+    Payout(#[from] crate::payout::PayoutError),
+
+    #[error(transparent)]
+    Escrow(#[from] crate::escrow::EscrowError),
+
+    #[error(transparent)]
+    Credentials(#[from] crate::credentials::CredentialsError),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("interrupted by Ctrl+C before this step started")]
+    Interrupted,
 }
 
 /// Errors specific to wallet operations.
@@ -25,17 +63,229 @@ pub enum WalletError {
     #[error("invalid multisig parameters: {0}")]
     InvalidParams(String),
 
-    #[error("wallet not found at {0}")]
+    #[error("wallet not found at {0} — run create-wallet first")]
     NotFound(String),
 
-    #[error("wallet already exists at {0}")]
+    #[error(
+        "the wallet RPC has a {wallet} wallet open but you are configured for {configured} \
+         — key exchange would build a {wallet} multisig wallet, so check the network flag \
+         monero-wallet-rpc was started with"
+    )]
+    WrongNetwork {
+        wallet: crate::config::Network,
+        configured: crate::config::Network,
+    },
+
+    #[error("wallet already exists at {0} — run `reset` to start over")]
     AlreadyExists(String),
 
+    #[error(
+        "wallet still holds {0}; resetting would make those funds inaccessible — pass \
+         --i-understand-funds-will-be-inaccessible to reset anyway"
+    )]
+    HasFunds(crate::transaction::Amount),
+
+    #[error(
+        "wallet still holds {} ({} piconero); move the funds out before deleting it",
+        .0,
+        .0.0
+    )]
+    NotEmpty(crate::transaction::Amount),
+
+    #[error("\"{name}\" is not in the roster ({roster})")]
+    UnknownParticipant { name: String, roster: String },
+
+    #[error("\"{0}\" is named for more than one --info blob")]
+    DuplicateParticipant(String),
+
+    #[error("bad peer info {bad}; fine: {fine} — only the bad blobs need sending again")]
+    BadPeerInfo { bad: String, fine: String },
+
+    #[error(
+        "the wallet RPC accepted a partial set of peer info while looking for the bad blob \
+         — run `repair-state` before anything else"
+    )]
+    ProbeCompleted,
+
+    #[error(
+        "the blob passed as from \"{claimed}\" was received from \"{previous}\" in round \
+         {round} — check who really sent it before going on"
+    )]
+    ParticipantMismatch {
+        claimed: String,
+        previous: String,
+        round: u32,
+    },
+
     #[error("key exchange failed: {0}")]
     KeyExchangeFailed(String),
 
+    #[error("account {index} does not exist: the wallet has {count} (0 to {}) — see list-accounts", .count.saturating_sub(1))]
+    NoSuchAccount { index: u32, count: u32 },
+
+    #[error("no account is tagged \"{0}\" — see `account list`")]
+    NoSuchAccountTag(String),
+
+    #[error(
+        "tag \"{tag}\" is on accounts {accounts:?}, but this command works on one account; \
+         pass --account with the index of the one you mean"
+    )]
+    AmbiguousAccountTag { tag: String, accounts: Vec<u32> },
+
+    #[error("subaddress {index} does not exist in account {account}, which has {count}")]
+    NoSuchSubaddress {
+        account: u32,
+        index: u32,
+        count: u32,
+    },
+
     #[error("wallet is not ready — complete key exchange first")]
     NotReady,
+
+    #[error(
+        "the wallet open in the wallet RPC is not a multisig wallet — is the right wallet open?"
+    )]
+    NotMultisig,
+
+    #[error(
+        "the wallet open in the wallet RPC hasn't finished multisig key exchange — finish it \
+         with the wallet it was started in, then adopt it"
+    )]
+    SetupUnfinished,
+
+    #[error("wallet is watch-only and cannot build or sign transactions")]
+    WatchOnly,
+
+    #[error("wallet RPC reports address {rpc} but the state file records {state} — is the right wallet open?")]
+    AddressMismatch { state: String, rpc: String },
+
+    #[error(
+        "the daemon reports itself as untrusted (a public remote node) but trusted_daemon is \
+         set — point daemon at your own node"
+    )]
+    UntrustedDaemon,
+
+    #[error("restore height {height} is beyond the daemon tip at {tip}")]
+    RestoreHeightInFuture { height: u64, tip: u64 },
+
+    #[error("state file version {found} is newer than supported version {supported} — upgrade monero-multisig")]
+    UnsupportedStateVersion { found: u32, supported: u32 },
+
+    #[error(
+        "state file {0} has no integrity checksum (written by an older version) — review it \
+         with `state rehash`"
+    )]
+    StateUnsigned(String),
+
+    #[error(
+        "state file {0} failed its integrity check: it was changed outside this tool — run \
+         `state rehash` only if you made the change yourself"
+    )]
+    StateTampered(String),
+
+    #[error(
+        "state key {0} is missing, so the state file can't be verified — restore it from a \
+         backup, or review the state with `state rehash`"
+    )]
+    StateKeyMissing(String),
+}
+
+/// Why a transaction was rejected, by the network or by this machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionReason {
+    /// The daemon already has a transaction spending the same inputs.
+    DoubleSpend,
+    /// An input's key image is already spent on chain.
+    KeyImageSpent,
+    FeeTooLow,
+    TooBig,
+    /// A ring is malformed or the wrong size.
+    InvalidRing,
+    /// Refused here by the policy, allowlist, spend limit or sweep check.
+    Policy,
+    Other,
+}
+
+impl RejectionReason {
+    /// What to do about it.
+    pub fn advice(self) -> &'static str {
+        match self {
+            RejectionReason::DoubleSpend | RejectionReason::KeyImageSpent => {
+                "its inputs were already spent: either the tx set was built on stale multisig \
+                 info, or another transaction from this wallet spent them first. Every \
+                 co-signer must run export-info and import-info, then rebuild and re-sign"
+            }
+            RejectionReason::FeeTooLow => {
+                "the daemon wants a higher fee: rebuild with a higher --priority and re-sign"
+            }
+            RejectionReason::TooBig => {
+                "the transaction is too large: rebuild it with fewer destinations, or consolidate \
+                 small outputs first"
+            }
+            RejectionReason::InvalidRing => {
+                "the daemon refused its rings: refresh the wallet against a trusted daemon, then \
+                 rebuild and re-sign"
+            }
+            RejectionReason::Policy => "see the configured policy, or the flag named above",
+            RejectionReason::Other => "check the daemon log for details",
+        }
+    }
+
+    /// Recognise a rejection in a wallet RPC or daemon message. `None` when it
+    /// doesn't read as one of the known reasons.
+    pub fn parse(message: &str) -> Option<Self> {
+        let message = message.to_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|n| message.contains(n));
+        if has(&["double spend", "double_spend"]) {
+            Some(RejectionReason::DoubleSpend)
+        } else if has(&["already spent", "key image spent", "spent key image"]) {
+            Some(RejectionReason::KeyImageSpent)
+        } else if has(&["fee too low", "low fee", "fee_too_low"]) {
+            Some(RejectionReason::FeeTooLow)
+        } else if has(&["too big", "too large", "tx_too_big"]) {
+            Some(RejectionReason::TooBig)
+        } else if has(&["ring size", "invalid ring", "invalid input", "low mixin"]) {
+            Some(RejectionReason::InvalidRing)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RejectionReason::DoubleSpend => "double spend",
+            RejectionReason::KeyImageSpent => "key image already spent",
+            RejectionReason::FeeTooLow => "fee too low",
+            RejectionReason::TooBig => "too big",
+            RejectionReason::InvalidRing => "invalid ring",
+            RejectionReason::Policy => "policy",
+            RejectionReason::Other => "other",
+        })
+    }
+}
+
+impl TransactionError {
+    /// [`TransactionError::Rejected`] by this machine's own checks.
+    pub fn policy(detail: impl Into<String>) -> Self {
+        TransactionError::Rejected {
+            reason: RejectionReason::Policy,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// When [`TransactionError::Locked`] funds unlock, at two minutes a block.
+fn unlock_estimate(blocks: Option<u64>) -> String {
+    match blocks {
+        Some(blocks) => match blocks.checked_mul(2) {
+            Some(minutes) => format!("within about {blocks} block(s) (~{minutes} min)"),
+            None => format!("within about {blocks} block(s)"),
+        },
+        None => "once it has enough confirmations".into(),
+    }
 }
 
 /// Errors specific to transaction operations.
@@ -44,15 +294,86 @@ pub enum TransactionError {
     #[error("insufficient balance: need {need} but have {have}")]
     InsufficientBalance { need: u64, have: u64 },
 
+    #[error(
+        "not enough unlocked balance: need {need} but only {unlocked} is unlocked; the rest \
+         unlocks {}",
+        unlock_estimate(*blocks_to_unlock)
+    )]
+    Locked {
+        need: u64,
+        unlocked: u64,
+        blocks_to_unlock: Option<u64>,
+    },
+
     #[error("invalid destination address: {0}")]
     InvalidAddress(String),
 
+    #[error(
+        "this transaction was built for {built_for} but you are configured for {configured} \
+         — it was not signed"
+    )]
+    WrongNetwork {
+        built_for: crate::config::Network,
+        configured: crate::config::Network,
+    },
+
     #[error("signing failed: {0}")]
     SigningFailed(String),
 
     #[error("not enough signatures: have {have}, need {need}")]
     InsufficientSignatures { have: u32, need: u32 },
 
-    #[error("transaction rejected by daemon: {0}")]
-    Rejected(String),
+    #[error("transaction rejected ({reason}): {detail}")]
+    Rejected {
+        reason: RejectionReason,
+        detail: String,
+    },
+
+    #[error(
+        "multisig sync data is stale: {0} — exchange export-info/import-info with your \
+         co-signers first, or pass --allow-stale-sync"
+    )]
+    StaleSync(String),
+
+    #[error("invalid multisig info: {0}")]
+    InvalidMultisigInfo(String),
+
+    #[error("this tx set was discarded (journal entry #{0}) — pass --force to submit it anyway")]
+    Discarded(u64),
+
+    #[error("amount overflow: the total does not fit in a 64-bit piconero amount")]
+    AmountOverflow,
+
+    #[error("amount underflow: {amount} minus {minus} piconero is negative")]
+    AmountUnderflow { amount: u64, minus: u64 },
+
+    #[error("invalid amount {0:?}: expected XMR with at most 12 decimal places")]
+    InvalidAmount(String),
+
+    #[error(
+        "duplicate destination addresses: {} — pass --merge-duplicates to combine their amounts",
+        .0.join(", ")
+    )]
+    DuplicateDestinations(Vec<String>),
+
+    #[error(
+        "{count} destinations don't fit in one transaction (at most {max} plus change) — pass \
+         --allow-split to build several"
+    )]
+    TooManyDestinations { count: usize, max: usize },
+
+    #[error(
+        "this is {0} ({desc}), not a multisig tx set: it comes from a normal wallet's \
+         cold-signing workflow, while sign-tx and submit-tx take the multisig_monero_tx \
+         set from build-tx — handle it with the Monero wallet, or cold-sign and \
+         cold-submit in a build with the cold-sign feature",
+        desc = .0.describe()
+    )]
+    ColdSigningFile(crate::transaction::ColdSigningKind),
+
+    #[error("not {0} ({desc})", desc = .0.describe())]
+    NotColdSigningFile(crate::transaction::ColdSigningKind),
+
+    #[error("not a multisig tx set: {0}")]
+    NotMultisigTxSet(String),
 }
@@ -0,0 +1,342 @@
+//! The 2-of-2 buyer/seller escrow workflow behind the `escrow` commands.
+//!
+//! An escrow wallet is an ordinary 2-of-2 multisig wallet whose state file
+//! also records [`EscrowTerms`]: which side this participant is on and the
+//! buyer's refund address, agreed before funding. The two sides swap an
+//! [`EscrowInvite`] instead of bare key exchange info:
+//!
+//! ```text
+//! MoneroEscrowV1:buyer:4AdUndXHHZ6cf...:MultisigxV2R1...
+//! ```
+//!
+//! so joining can check that the other side took the other role and has the
+//! same refund address on record before any keys are exchanged. A refund
+//! goes to that address; paying it anywhere else takes an explicit override,
+//! and as with any 2-of-2 spend the other side still has to sign it.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::{Network, RpcClient};
+use crate::error::Result;
+use crate::wallet::{self, StateFile};
+
+const INVITE_PREFIX: &str = "MoneroEscrowV1";
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum EscrowError {
+    #[error("this wallet was not set up with `escrow init`")]
+    NotEscrow,
+
+    #[error("an escrow wallet must be 2-of-2, but this one is {threshold}-of-{total}")]
+    NotTwoOfTwo { threshold: u32, total: u32 },
+
+    #[error("the other side's invite is also from the {0}; one side must be the buyer and the other the seller")]
+    SameRole(EscrowRole),
+
+    #[error(
+        "the other side has {theirs} as the buyer's refund address, but this wallet has \
+         {ours} — agree on one before joining"
+    )]
+    RefundAddressMismatch { ours: String, theirs: String },
+
+    #[error(
+        "refunds go to the buyer's registered address {registered}; paying {requested} \
+         instead needs --override, and the other side has to sign it too"
+    )]
+    RefundOverrideRequired {
+        registered: String,
+        requested: String,
+    },
+
+    #[error("not an escrow invite: {0}")]
+    InvalidInvite(String),
+
+    #[error("invalid escrow role {0:?}; expected buyer or seller")]
+    InvalidRole(String),
+}
+
+/// Which side of the deal this participant is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EscrowRole {
+    Buyer,
+    Seller,
+}
+
+impl EscrowRole {
+    /// The role the other participant must have.
+    pub fn other(self) -> Self {
+        match self {
+            EscrowRole::Buyer => EscrowRole::Seller,
+            EscrowRole::Seller => EscrowRole::Buyer,
+        }
+    }
+}
+
+impl fmt::Display for EscrowRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EscrowRole::Buyer => "buyer",
+            EscrowRole::Seller => "seller",
+        })
+    }
+}
+
+impl FromStr for EscrowRole {
+    type Err = EscrowError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "buyer" => Ok(EscrowRole::Buyer),
+            "seller" => Ok(EscrowRole::Seller),
+            _ => Err(EscrowError::InvalidRole(s.to_string())),
+        }
+    }
+}
+
+/// What `escrow init` recorded about the deal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EscrowTerms {
+    pub role: EscrowRole,
+    /// Where a refund goes: the buyer's own address, which the seller
+    /// records too.
+    pub refund_address: String,
+}
+
+impl EscrowTerms {
+    /// Terms for `role` refunding to `refund_address`, which must be a valid
+    /// address on `network`.
+    pub fn new(role: EscrowRole, refund_address: &str, network: Network) -> Result<Self> {
+        let refund_address = refund_address.trim();
+        crate::transaction::validate_address(refund_address, network)?;
+        Ok(EscrowTerms {
+            role,
+            refund_address: refund_address.to_string(),
+        })
+    }
+
+    /// Fail unless `invite` is from the other role and names the same
+    /// refund address.
+    pub fn check_peer(&self, invite: &EscrowInvite) -> Result<(), EscrowError> {
+        if invite.role == self.role {
+            return Err(EscrowError::SameRole(invite.role));
+        }
+        if invite.refund_address != self.refund_address {
+            return Err(EscrowError::RefundAddressMismatch {
+                ours: self.refund_address.clone(),
+                theirs: invite.refund_address.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Where a refund may go: the registered address, or `requested` when
+    /// it is the same or `override_address` is set.
+    pub fn refund_destination(
+        &self,
+        requested: Option<&str>,
+        override_address: bool,
+    ) -> Result<String, EscrowError> {
+        match requested.map(str::trim) {
+            None => Ok(self.refund_address.clone()),
+            Some(to) if to == self.refund_address || override_address => Ok(to.to_string()),
+            Some(to) => Err(EscrowError::RefundOverrideRequired {
+                registered: self.refund_address.clone(),
+                requested: to.to_string(),
+            }),
+        }
+    }
+}
+
+/// What one side sends the other to join: its role, the refund address it
+/// has on record, and its key exchange info.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscrowInvite {
+    pub role: EscrowRole,
+    pub refund_address: String,
+    pub info: String,
+}
+
+impl EscrowInvite {
+    pub fn new(terms: &EscrowTerms, info: &str) -> Self {
+        EscrowInvite {
+            role: terms.role,
+            refund_address: terms.refund_address.clone(),
+            info: info.trim().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for EscrowInvite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{INVITE_PREFIX}:{}:{}:{}",
+            self.role, self.refund_address, self.info
+        )
+    }
+}
+
+impl FromStr for EscrowInvite {
+    type Err = EscrowError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |why: &str| EscrowError::InvalidInvite(why.to_string());
+        let mut parts = s.trim().splitn(4, ':');
+        if parts.next() != Some(INVITE_PREFIX) {
+            return Err(invalid(&format!("it doesn't start with {INVITE_PREFIX}:")));
+        }
+        let (Some(role), Some(refund_address), Some(info)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid("it is cut short"));
+        };
+        if refund_address.is_empty() || info.is_empty() {
+            return Err(invalid("it is cut short"));
+        }
+        Ok(EscrowInvite {
+            role: role.parse()?,
+            refund_address: refund_address.to_string(),
+            info: info.to_string(),
+        })
+    }
+}
+
+/// The escrow terms of `state`, checking that the wallet it describes is
+/// 2-of-2.
+pub fn terms(state: &StateFile) -> Result<&EscrowTerms, EscrowError> {
+    let terms = state.escrow.as_ref().ok_or(EscrowError::NotEscrow)?;
+    check_two_of_two(
+        state.wallet.params().map_or(0, |p| p.threshold),
+        state.wallet.params().map_or(0, |p| p.total),
+    )?;
+    Ok(terms)
+}
+
+fn check_two_of_two(threshold: u32, total: u32) -> Result<(), EscrowError> {
+    if (threshold, total) == (2, 2) {
+        Ok(())
+    } else {
+        Err(EscrowError::NotTwoOfTwo { threshold, total })
+    }
+}
+
+/// Ask the wallet RPC whether the open wallet really is a finished 2-of-2
+/// multisig wallet, whatever the state file says.
+pub async fn check_wallet(rpc: &RpcClient) -> Result<()> {
+    let status = wallet::is_multisig(rpc).await?;
+    if !status.ready {
+        return Err(crate::error::WalletError::NotReady.into());
+    }
+    Ok(check_two_of_two(status.threshold, status.total)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{MultisigError, WalletError};
+    use crate::testing::method_daemon;
+
+    const REFUND: &str = "4AdUndXHHZ6cfufTMvppY6JwXNouMBzSkbLYfpAV5Usx3skxNgYeYTRj5UzqtReoS44qo9mtmXCqY45DJ852K5Jv2684Rge";
+    const OTHER: &str = "888tNkZrPN6JsEgekjMnABU4TBzc2Dt29EPAvkRxbANsAnjyPbb3iQ1YBRk1UXcdRsiKc9dhwMVgN5S9cQUiyoogDavup3H";
+
+    #[test]
+    fn test_invite_round_trip() {
+        let terms = EscrowTerms::new(EscrowRole::Buyer, REFUND, Network::Mainnet).unwrap();
+        let invite = EscrowInvite::new(&terms, "MultisigxV2R1abc\n");
+        let text = invite.to_string();
+        assert_eq!(
+            text,
+            format!("MoneroEscrowV1:buyer:{REFUND}:MultisigxV2R1abc")
+        );
+        assert_eq!(text.parse::<EscrowInvite>().unwrap(), invite);
+
+        assert!(matches!(
+            "MultisigxV2R1abc".parse::<EscrowInvite>(),
+            Err(EscrowError::InvalidInvite(_))
+        ));
+        assert!(matches!(
+            format!("MoneroEscrowV1:buyer:{REFUND}").parse::<EscrowInvite>(),
+            Err(EscrowError::InvalidInvite(_))
+        ));
+        assert_eq!(
+            format!("MoneroEscrowV1:agent:{REFUND}:Multisigx").parse::<EscrowInvite>(),
+            Err(EscrowError::InvalidRole("agent".into()))
+        );
+        assert!(EscrowTerms::new(EscrowRole::Buyer, "4abc", Network::Mainnet).is_err());
+    }
+
+    #[test]
+    fn test_roles_must_be_consistent() {
+        let buyer = EscrowTerms::new(EscrowRole::Buyer, REFUND, Network::Mainnet).unwrap();
+        let seller = EscrowTerms {
+            role: EscrowRole::Seller,
+            ..buyer.clone()
+        };
+        assert_eq!(buyer.check_peer(&EscrowInvite::new(&seller, "x")), Ok(()));
+        assert_eq!(seller.check_peer(&EscrowInvite::new(&buyer, "x")), Ok(()));
+        assert_eq!(
+            buyer.check_peer(&EscrowInvite::new(&buyer, "x")),
+            Err(EscrowError::SameRole(EscrowRole::Buyer))
+        );
+        let elsewhere = EscrowTerms {
+            refund_address: OTHER.into(),
+            ..seller
+        };
+        assert!(matches!(
+            buyer.check_peer(&EscrowInvite::new(&elsewhere, "x")),
+            Err(EscrowError::RefundAddressMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_refund_only_to_the_registered_address() {
+        let terms = EscrowTerms::new(EscrowRole::Seller, REFUND, Network::Mainnet).unwrap();
+        assert_eq!(terms.refund_destination(None, false).unwrap(), REFUND);
+        assert_eq!(
+            terms.refund_destination(Some(REFUND), false).unwrap(),
+            REFUND
+        );
+        assert!(matches!(
+            terms.refund_destination(Some(OTHER), false),
+            Err(EscrowError::RefundOverrideRequired { .. })
+        ));
+        assert_eq!(terms.refund_destination(Some(OTHER), true).unwrap(), OTHER);
+    }
+
+    #[tokio::test]
+    async fn test_check_wallet_wants_a_ready_two_of_two() {
+        static READY: [(&str, &str); 1] = [(
+            "is_multisig",
+            r#"{"multisig":true,"ready":true,"threshold":2,"total":2}"#,
+        )];
+        static TWO_OF_THREE: [(&str, &str); 1] = [(
+            "is_multisig",
+            r#"{"multisig":true,"ready":true,"threshold":2,"total":3}"#,
+        )];
+        static UNFINISHED: [(&str, &str); 1] = [(
+            "is_multisig",
+            r#"{"multisig":true,"ready":false,"threshold":2,"total":2}"#,
+        )];
+        let check = |answers| async move {
+            let rpc = RpcClient::new(&method_daemon(answers).await);
+            check_wallet(&rpc).await
+        };
+        assert!(check(&READY).await.is_ok());
+        assert!(matches!(
+            check(&TWO_OF_THREE).await,
+            Err(MultisigError::Escrow(EscrowError::NotTwoOfTwo {
+                threshold: 2,
+                total: 3
+            }))
+        ));
+        assert!(matches!(
+            check(&UNFINISHED).await,
+            Err(MultisigError::Wallet(WalletError::NotReady))
+        ));
+    }
+}
@@ -0,0 +1,74 @@
+//! Ctrl+C handling.
+//!
+//! Killing the process mid-RPC can leave the wallet RPC ahead of the local
+//! state: a key exchange round or a submit went through, but the state file
+//! or journal never heard about it. So the first Ctrl+C only raises a flag.
+//! The running command finishes its current RPC and the bookkeeping that
+//! follows, then stops at the next point that checks [`requested`], and the
+//! process exits with [`EXIT_CODE`]. Command futures are never dropped
+//! half-way, so everything after an RPC returns runs to completion. Polling
+//! loops wait on [`interrupted`] instead of sleeping blindly and stop at once.
+//!
+//! A second Ctrl+C exits immediately, as does the first one while the tool
+//! sits at a confirmation prompt, where nothing is in flight.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tokio::sync::Notify;
+
+/// Exit status after an interrupt, as a shell reports a process killed by
+/// SIGINT.
+pub const EXIT_CODE: u8 = 130;
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+static PROMPTING: AtomicBool = AtomicBool::new(false);
+static SIGNAL: Notify = Notify::const_new();
+
+/// Start listening for Ctrl+C. Call once, from inside the runtime.
+pub fn install() {
+    tokio::spawn(async {
+        loop {
+            if tokio::signal::ctrl_c().await.is_err() {
+                return;
+            }
+            if PROMPTING.load(Ordering::SeqCst) || REQUESTED.swap(true, Ordering::SeqCst) {
+                eprintln!();
+                std::process::exit(EXIT_CODE.into());
+            }
+            eprintln!("\nFinishing current operation, press Ctrl+C again to force quit...");
+            SIGNAL.notify_waiters();
+        }
+    });
+}
+
+/// Whether Ctrl+C has been pressed.
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::SeqCst)
+}
+
+/// Resolve once Ctrl+C has been pressed, immediately if it already was.
+pub async fn interrupted() {
+    let notified = SIGNAL.notified();
+    tokio::pin!(notified);
+    notified.as_mut().enable();
+    if requested() {
+        return;
+    }
+    notified.await;
+}
+
+/// Marks a blocking prompt, during which Ctrl+C quits at once.
+pub struct Prompt(());
+
+impl Prompt {
+    pub fn start() -> Self {
+        PROMPTING.store(true, Ordering::SeqCst);
+        Prompt(())
+    }
+}
+
+impl Drop for Prompt {
+    fn drop(&mut self) {
+        PROMPTING.store(false, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,1360 @@
+//! Local journal of every transaction this participant built, signed, or
+//! submitted.
+//!
+//! The journal lives in `data_dir/journal.json` and doubles as the pending-tx
+//! signature tracker: each entry records how many signatures have been
+//! collected against the wallet's threshold, so one entry shows the whole
+//! lifecycle of a payment from build to broadcast.
+//!
+//! Tx sets and even tx hashes change as signatures are added, so entries are
+//! linked across steps by the digest of every tx set version we have seen,
+//! any reported tx hash, or — when the set can be decoded — its recipients
+//! and fee, which stay fixed for the life of a transaction.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::config::DataDir;
+use crate::error::{RejectionReason, Result};
+use crate::transaction::{Amount, Destination, Priority, TransferDescription, Visibility};
+
+const JOURNAL_FILE: &str = "journal.json";
+
+/// Where a journaled transaction is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxStatus {
+    /// Built here (or received for signing) and waiting for signatures.
+    AwaitingSignatures,
+    /// Enough signatures collected; ready to submit.
+    ReadyToSubmit,
+    /// Fully signed and written out with `submit-tx --no-submit`, to be
+    /// broadcast later with `relay`.
+    AwaitingRelay,
+    /// Broadcast to the network.
+    Submitted,
+    /// Abandoned with `discard-tx`; kept for the record.
+    Discarded,
+}
+
+impl TxStatus {
+    /// Whether the transaction is still waiting to be signed or submitted.
+    pub fn is_pending(self) -> bool {
+        matches!(
+            self,
+            TxStatus::AwaitingSignatures | TxStatus::ReadyToSubmit | TxStatus::AwaitingRelay
+        )
+    }
+}
+
+impl std::fmt::Display for TxStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TxStatus::AwaitingSignatures => "awaiting signatures",
+            TxStatus::ReadyToSubmit => "ready to submit",
+            TxStatus::AwaitingRelay => "awaiting relay",
+            TxStatus::Submitted => "submitted",
+            TxStatus::Discarded => "discarded",
+        })
+    }
+}
+
+/// A single step in a transaction's lifecycle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEvent {
+    pub at: DateTime<Utc>,
+    pub action: JournalAction,
+    /// `user@host` of whoever performed the action on this machine.
+    pub by: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JournalAction {
+    Built,
+    /// A tx set built elsewhere was first seen here.
+    Received,
+    Signed,
+    /// A co-signer's signature was credited with `record-signature`.
+    SignatureRecorded {
+        signer: String,
+    },
+    /// Fully signed but held back for a later `relay`.
+    HeldForRelay,
+    Submitted {
+        tx_hashes: Vec<String>,
+    },
+    /// After submitting, the daemon was asked whether it sees the
+    /// transaction.
+    BroadcastChecked {
+        visibility: Visibility,
+    },
+    /// Submitting failed because the network or the wallet refused it.
+    Rejected {
+        reason: RejectionReason,
+        detail: String,
+    },
+    /// The wallet saw the submitted transaction mined.
+    Confirmed {
+        height: u64,
+    },
+    Discarded,
+    /// Rebuilt with a higher fee as entry `by`, which needs signing again.
+    Superseded {
+        by: u64,
+    },
+}
+
+impl std::fmt::Display for JournalAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JournalAction::Built => write!(f, "built"),
+            JournalAction::Received => write!(f, "received"),
+            JournalAction::Signed => write!(f, "signed"),
+            JournalAction::SignatureRecorded { signer } => {
+                write!(f, "credited {signer}'s signature")
+            }
+            JournalAction::HeldForRelay => write!(f, "held for relay"),
+            JournalAction::Submitted { .. } => write!(f, "submitted"),
+            JournalAction::BroadcastChecked { visibility } => {
+                write!(f, "broadcast checked: {visibility}")
+            }
+            JournalAction::Rejected { reason, .. } => write!(f, "rejected: {reason}"),
+            JournalAction::Confirmed { .. } => write!(f, "confirmed"),
+            JournalAction::Discarded => write!(f, "discarded"),
+            JournalAction::Superseded { by } => write!(f, "superseded by #{by}"),
+        }
+    }
+}
+
+/// One transaction's journal record.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: u64,
+    pub created_at: DateTime<Utc>,
+    pub status: TxStatus,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub destinations: Vec<Destination>,
+    #[serde(default)]
+    pub fee: u64,
+    /// Total weight and byte size, when built here; 0 when unknown.
+    #[serde(default)]
+    pub weight: u64,
+    #[serde(default)]
+    pub size_bytes: u64,
+    /// Fee priority the set was built at, when built here.
+    #[serde(default)]
+    pub priority: Option<Priority>,
+    /// `user@host` of the builder, when built on this machine.
+    #[serde(default)]
+    pub built_by: Option<String>,
+    /// Every tx hash reported for this transaction, oldest first.
+    #[serde(default)]
+    pub tx_hashes: Vec<String>,
+    /// Digests of every version of the tx set seen here, oldest first.
+    #[serde(default)]
+    pub tx_set_digests: Vec<String>,
+    /// The newest version of the tx set, so it can be handed out again.
+    /// Dropped by [`Journal::prune`] once the entry is finished.
+    #[serde(default)]
+    pub tx_set: Option<String>,
+    /// The entry this one was rebuilt from with a higher fee.
+    #[serde(default)]
+    pub supersedes: Option<u64>,
+    /// The entry rebuilt from this one with a higher fee.
+    #[serde(default)]
+    pub superseded_by: Option<u64>,
+    pub signatures_collected: u32,
+    pub signatures_required: u32,
+    /// Who each signature is credited to, by roster name. Advisory: tx sets
+    /// don't say who signed them, so this is only what was recorded here.
+    #[serde(default)]
+    pub signers: Vec<SignerRecord>,
+    #[serde(default)]
+    pub events: Vec<JournalEvent>,
+}
+
+/// A signature credited to a participant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignerRecord {
+    pub name: String,
+    /// Digest of the tx set version that signature produced.
+    pub tx_set_digest: String,
+    pub at: DateTime<Utc>,
+}
+
+/// What [`Journal::record_signer`] did with a claimed signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Attribution {
+    Recorded,
+    /// This set was already credited to the same signer.
+    Unchanged,
+    /// This set version is already credited to someone else.
+    SetCreditedTo(String),
+    /// The signer is already credited on another version of the set.
+    AlreadySigned,
+}
+
+impl JournalEntry {
+    /// Total amount paid to destinations, excluding the fee. Saturates:
+    /// entries recorded from another co-signer's tx set aren't trusted to
+    /// add up, and the journal is only a record.
+    pub fn amount(&self) -> u64 {
+        self.destinations
+            .iter()
+            .fold(0u64, |total, d| total.saturating_add(d.amount().0))
+    }
+
+    /// The most recent tx hash, which is the final one after submission.
+    pub fn latest_tx_hash(&self) -> Option<&str> {
+        self.tx_hashes.last().map(String::as_str)
+    }
+
+    /// The time of the most recent event.
+    pub fn last_action_at(&self) -> DateTime<Utc> {
+        self.events.last().map(|e| e.at).unwrap_or(self.created_at)
+    }
+
+    fn push_hash(&mut self, hash: &str) {
+        if !hash.is_empty() && !self.tx_hashes.iter().any(|h| h == hash) {
+            self.tx_hashes.push(hash.to_string());
+        }
+    }
+
+    /// Whether a signature is credited to `name`.
+    pub fn signed_by(&self, name: &str) -> bool {
+        self.signers.iter().any(|s| s.name == name)
+    }
+
+    fn credit(&mut self, name: &str, tx_set: &str) {
+        self.signers.push(SignerRecord {
+            name: name.to_string(),
+            tx_set_digest: crate::utils::blob_digest(tx_set),
+            at: Utc::now(),
+        });
+    }
+
+    fn see_tx_set(&mut self, tx_set: &str) {
+        let digest = crate::utils::blob_digest(tx_set);
+        if !self.tx_set_digests.contains(&digest) {
+            self.tx_set_digests.push(digest);
+        }
+        self.tx_set = Some(tx_set.trim().to_string());
+    }
+
+    fn push_event(&mut self, action: JournalAction) {
+        self.events.push(JournalEvent {
+            at: Utc::now(),
+            action,
+            by: crate::utils::identity(),
+        });
+    }
+
+    fn refresh_status(&mut self) {
+        if matches!(
+            self.status,
+            TxStatus::AwaitingSignatures | TxStatus::ReadyToSubmit
+        ) {
+            // A zero threshold means the signer had no local wallet state.
+            let enough = self.signatures_required > 0
+                && self.signatures_collected >= self.signatures_required;
+            self.status = if enough {
+                TxStatus::ReadyToSubmit
+            } else {
+                TxStatus::AwaitingSignatures
+            };
+        }
+    }
+
+    /// Whether a decoded tx set pays exactly this entry's destinations and fee.
+    fn matches_description(&self, desc: &[TransferDescription]) -> bool {
+        if self.destinations.is_empty() || desc.is_empty() {
+            return false;
+        }
+        let mut ours: Vec<_> = self
+            .destinations
+            .iter()
+            .map(|d| (d.address(), d.amount().0))
+            .collect();
+        let mut theirs: Vec<_> = desc
+            .iter()
+            .flat_map(|d| d.recipients.iter().map(|r| (r.address.as_str(), r.amount)))
+            .collect();
+        ours.sort_unstable();
+        theirs.sort_unstable();
+        let fee = desc
+            .iter()
+            .fold(0u64, |total, d| total.saturating_add(d.fee));
+        ours == theirs && fee == self.fee
+    }
+}
+
+/// Criteria for listing journal entries. Empty fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct JournalFilter {
+    /// Only entries created on or after this date (UTC).
+    pub since: Option<NaiveDate>,
+    /// Only entries created on or before this date (UTC).
+    pub until: Option<NaiveDate>,
+    /// Only entries paying this address.
+    pub address: Option<String>,
+    /// Only entries whose note contains this text (case-insensitive).
+    pub note: Option<String>,
+}
+
+impl JournalFilter {
+    pub fn matches(&self, entry: &JournalEntry) -> bool {
+        let day = entry.created_at.date_naive();
+        self.since.is_none_or(|since| day >= since)
+            && self.until.is_none_or(|until| day <= until)
+            && self
+                .address
+                .as_ref()
+                .is_none_or(|a| entry.destinations.iter().any(|d| d.address() == a))
+            && self.note.as_ref().is_none_or(|needle| {
+                entry
+                    .note
+                    .as_ref()
+                    .is_some_and(|n| n.to_lowercase().contains(&needle.to_lowercase()))
+            })
+    }
+}
+
+/// The full journal file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Journal {
+    pub entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    /// Load the journal from `data_dir`, returning an empty journal if none
+    /// has been written yet.
+    pub fn load(data_dir: &DataDir) -> Result<Self> {
+        match std::fs::read_to_string(data_dir.file(JOURNAL_FILE)) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Where the journal for `data_dir` is kept.
+    pub fn path(data_dir: &DataDir) -> PathBuf {
+        data_dir.file(JOURNAL_FILE)
+    }
+
+    /// Atomically write the journal to `data_dir`.
+    pub fn save(&self, data_dir: &DataDir) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        crate::utils::write_atomic(&data_dir.file(JOURNAL_FILE), json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Record a transaction built on this machine. The builder's wallet signs
+    /// as part of `transfer`, so the entry starts with one signature.
+    pub fn record_built(
+        &mut self,
+        destinations: &[Destination],
+        fee: u64,
+        tx_hashes: &[String],
+        tx_set: &str,
+        signatures_required: u32,
+        note: Option<String>,
+    ) -> &JournalEntry {
+        let mut entry = self.new_entry(signatures_required);
+        entry.destinations = destinations.to_vec();
+        entry.fee = fee;
+        entry.note = note;
+        entry.built_by = Some(crate::utils::identity());
+        entry.signatures_collected = 1;
+        for hash in tx_hashes {
+            entry.push_hash(hash);
+        }
+        entry.see_tx_set(tx_set);
+        entry.credit(crate::wallet::ME, tx_set);
+        entry.push_event(JournalAction::Built);
+        entry.refresh_status();
+        self.entries.push(entry);
+        self.entries.last().unwrap()
+    }
+
+    /// Record the weight and size the wallet reported for the set built as
+    /// `tx_set`.
+    pub fn record_size(&mut self, tx_set: &str, weight: u64, size_bytes: u64) {
+        if let Some(index) = self.find(tx_set, &[], None) {
+            self.entries[index].weight = weight;
+            self.entries[index].size_bytes = size_bytes;
+        }
+    }
+
+    /// Record the fee priority the set built as `tx_set` was built at.
+    pub fn record_priority(&mut self, tx_set: &str, priority: Priority) {
+        if let Some(index) = self.find(tx_set, &[], None) {
+            self.entries[index].priority = Some(priority);
+        }
+    }
+
+    /// Record that this participant signed `incoming`, producing `signed`.
+    /// A signature this participant is already credited with, such as the
+    /// builder's own, isn't counted again.
+    ///
+    /// `description` is the decoded set, if available, and is used both to
+    /// link to an existing entry and to fill in a new one.
+    pub fn record_signed(
+        &mut self,
+        incoming: &str,
+        signed: &str,
+        tx_hashes: &[String],
+        description: Option<&[TransferDescription]>,
+        signatures_required: u32,
+    ) -> &JournalEntry {
+        let index = self.find_or_receive(incoming, tx_hashes, description, signatures_required);
+        let entry = &mut self.entries[index];
+        let digest = crate::utils::blob_digest(signed);
+        let credited = entry.signed_by(crate::wallet::ME)
+            || entry.signers.iter().any(|s| s.tx_set_digest == digest);
+        if !credited {
+            // Without the builder's entry we only know that this signature
+            // was added on top of the builder's one.
+            entry.signatures_collected = entry.signatures_collected.max(1) + 1;
+            entry.credit(crate::wallet::ME, signed);
+        }
+        entry.see_tx_set(signed);
+        for hash in tx_hashes {
+            entry.push_hash(hash);
+        }
+        entry.push_event(JournalAction::Signed);
+        entry.refresh_status();
+        &self.entries[index]
+    }
+
+    /// Credit `signer` with the signature that produced `tx_set`, a set they
+    /// sent back. Importing the same set again changes nothing, and a set or
+    /// signer that is already credited is refused rather than recounted.
+    /// While the entry counts a signature it credits to no one, such as the
+    /// builder's on a set received from them, that is the one `signer` is
+    /// credited with.
+    pub fn record_signer(
+        &mut self,
+        tx_set: &str,
+        description: Option<&[TransferDescription]>,
+        signer: &str,
+        signatures_required: u32,
+    ) -> (Attribution, &JournalEntry) {
+        let digest = crate::utils::blob_digest(tx_set);
+        let existing = self.find(tx_set, &[], description).and_then(|index| {
+            let entry = &self.entries[index];
+            let outcome = match entry.signers.iter().find(|s| s.tx_set_digest == digest) {
+                Some(s) if s.name == signer => Attribution::Unchanged,
+                Some(s) => Attribution::SetCreditedTo(s.name.clone()),
+                None if entry.signed_by(signer) => Attribution::AlreadySigned,
+                None => return None,
+            };
+            Some((index, outcome))
+        });
+        if let Some((index, outcome)) = existing {
+            return (outcome, &self.entries[index]);
+        }
+
+        let index = self.find_or_receive(tx_set, &[], description, signatures_required);
+        let entry = &mut self.entries[index];
+        // Any set carries at least the builder's signature.
+        let counted = entry.signatures_collected.max(1);
+        let credited = u32::try_from(entry.signers.len()).unwrap_or(u32::MAX);
+        entry.signatures_collected = if counted > credited {
+            counted
+        } else {
+            counted + 1
+        };
+        entry.credit(signer, tx_set);
+        entry.push_event(JournalAction::SignatureRecorded {
+            signer: signer.to_string(),
+        });
+        entry.refresh_status();
+        (Attribution::Recorded, &self.entries[index])
+    }
+
+    /// Record that the fully signed `tx_set` was written out for a later
+    /// `relay` instead of being broadcast.
+    pub fn record_held(
+        &mut self,
+        tx_set: &str,
+        tx_hashes: &[String],
+        description: Option<&[TransferDescription]>,
+        signatures_required: u32,
+    ) -> &JournalEntry {
+        let index = self.find_or_receive(tx_set, tx_hashes, description, signatures_required);
+        let entry = &mut self.entries[index];
+        for hash in tx_hashes {
+            entry.push_hash(hash);
+        }
+        entry.signatures_collected = entry.signatures_collected.max(entry.signatures_required);
+        entry.status = TxStatus::AwaitingRelay;
+        entry.push_event(JournalAction::HeldForRelay);
+        &self.entries[index]
+    }
+
+    /// Record the successful submission of `tx_set`.
+    pub fn record_submitted(
+        &mut self,
+        tx_set: &str,
+        tx_hashes: &[String],
+        description: Option<&[TransferDescription]>,
+        signatures_required: u32,
+    ) -> &JournalEntry {
+        let index = self.find_or_receive(tx_set, tx_hashes, description, signatures_required);
+        let entry = &mut self.entries[index];
+        for hash in tx_hashes {
+            entry.push_hash(hash);
+        }
+        entry.signatures_collected = entry.signatures_collected.max(entry.signatures_required);
+        entry.status = TxStatus::Submitted;
+        entry.push_event(JournalAction::Submitted {
+            tx_hashes: tx_hashes.to_vec(),
+        });
+        &self.entries[index]
+    }
+
+    /// Record what the daemon saw of the submitted entry with `tx_hashes`,
+    /// every tx of a split set: `visibility` is of the set as a whole.
+    pub fn record_broadcast(
+        &mut self,
+        tx_hashes: &[String],
+        visibility: Visibility,
+    ) -> Option<&JournalEntry> {
+        if tx_hashes.is_empty() {
+            return None;
+        }
+        let index = self.entries.iter().rposition(|e| {
+            e.status == TxStatus::Submitted && tx_hashes.iter().all(|h| e.tx_hashes.contains(h))
+        })?;
+        self.entries[index].push_event(JournalAction::BroadcastChecked { visibility });
+        Some(&self.entries[index])
+    }
+
+    /// Record that submitting the tx set was refused. The entry keeps its
+    /// status, so it can be resubmitted or discarded. Returns `None` if no
+    /// entry matches the set.
+    pub fn record_rejected(
+        &mut self,
+        tx_set: &str,
+        description: Option<&[TransferDescription]>,
+        reason: RejectionReason,
+        detail: &str,
+    ) -> Option<&JournalEntry> {
+        let index = self.find(tx_set, &[], description)?;
+        self.entries[index].push_event(JournalAction::Rejected {
+            reason,
+            detail: detail.to_string(),
+        });
+        Some(&self.entries[index])
+    }
+
+    /// Entries other than `except` that may have spent the same outputs: the
+    /// pending ones, and submitted ones not yet seen mined. The journal
+    /// doesn't know which outputs a set spends, so these are candidates only.
+    pub fn may_share_inputs(&self, except: Option<u64>) -> Vec<&JournalEntry> {
+        self.entries
+            .iter()
+            .filter(|e| Some(e.id) != except)
+            .filter(|e| {
+                e.status.is_pending()
+                    || (e.status == TxStatus::Submitted
+                        && !e
+                            .events
+                            .iter()
+                            .any(|ev| matches!(ev.action, JournalAction::Confirmed { .. })))
+            })
+            .collect()
+    }
+
+    /// Mark the entry with `tx_hash` as discarded. Returns `None` if no pending
+    /// entry has that hash.
+    pub fn record_discarded(&mut self, tx_hash: &str) -> Option<&JournalEntry> {
+        let index = self
+            .entries
+            .iter()
+            .rposition(|e| e.status.is_pending() && e.tx_hashes.iter().any(|h| h == tx_hash))?;
+        let entry = &mut self.entries[index];
+        entry.status = TxStatus::Discarded;
+        entry.push_event(JournalAction::Discarded);
+        Some(&self.entries[index])
+    }
+
+    /// Mark entry `old` as replaced by `new`, a rebuild of it with a higher
+    /// fee. The old entry is discarded: its set can no longer be submitted
+    /// once the new one is. Returns `false` if either entry is missing.
+    pub fn record_superseded(&mut self, old: u64, new: u64) -> bool {
+        let (Some(o), Some(n)) = (
+            self.entries.iter().position(|e| e.id == old),
+            self.entries.iter().position(|e| e.id == new),
+        ) else {
+            return false;
+        };
+        self.entries[n].supersedes = Some(old);
+        let entry = &mut self.entries[o];
+        entry.superseded_by = Some(new);
+        entry.status = TxStatus::Discarded;
+        entry.push_event(JournalAction::Superseded { by: new });
+        true
+    }
+
+    /// Find the entry for a tx set by digest, tx hash, or decoded content.
+    ///
+    /// Content matches skip submitted entries, since a submitted transaction
+    /// can't be signed again, but do find discarded ones so a discarded set
+    /// that was signed further elsewhere is still recognised.
+    pub fn find(
+        &self,
+        tx_set: &str,
+        tx_hashes: &[String],
+        description: Option<&[TransferDescription]>,
+    ) -> Option<usize> {
+        let digest = crate::utils::blob_digest(tx_set);
+        self.entries
+            .iter()
+            .rposition(|e| e.tx_set_digests.contains(&digest))
+            .or_else(|| {
+                self.entries
+                    .iter()
+                    .rposition(|e| tx_hashes.iter().any(|h| e.tx_hashes.contains(h)))
+            })
+            .or_else(|| {
+                let desc = description?;
+                self.entries
+                    .iter()
+                    .rposition(|e| e.status != TxStatus::Submitted && e.matches_description(desc))
+            })
+    }
+
+    /// Entries still waiting for signatures or submission, newest first.
+    pub fn pending(&self) -> Vec<&JournalEntry> {
+        let mut entries: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|e| e.status.is_pending())
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.created_at));
+        entries
+    }
+
+    /// Clean up entries whose last action is before `cutoff`. Pending ones
+    /// are considered abandoned and marked discarded; finished ones lose
+    /// their stored tx set. Entries themselves are kept for the record.
+    pub fn prune(&mut self, cutoff: DateTime<Utc>) -> PruneSummary {
+        let mut summary = PruneSummary::default();
+        for entry in &mut self.entries {
+            if entry.last_action_at() >= cutoff {
+                continue;
+            }
+            if entry.status.is_pending() {
+                entry.status = TxStatus::Discarded;
+                entry.push_event(JournalAction::Discarded);
+                summary.abandoned += 1;
+            }
+            if entry.tx_set.take().is_some() {
+                summary.tx_sets_dropped += 1;
+            }
+        }
+        summary
+    }
+
+    /// Record that the submitted transaction `tx_hash` was mined at `height`.
+    /// Returns `None` if no submitted entry has that hash or it was already
+    /// recorded as confirmed.
+    pub fn record_confirmed(&mut self, tx_hash: &str, height: u64) -> Option<&JournalEntry> {
+        let index = self.entries.iter().rposition(|e| {
+            e.status == TxStatus::Submitted && e.tx_hashes.iter().any(|h| h == tx_hash)
+        })?;
+        let entry = &mut self.entries[index];
+        if entry
+            .events
+            .iter()
+            .any(|e| matches!(e.action, JournalAction::Confirmed { .. }))
+        {
+            return None;
+        }
+        entry.push_event(JournalAction::Confirmed { height });
+        Some(&self.entries[index])
+    }
+
+    /// Number of submitted transactions and the total they paid out,
+    /// excluding fees.
+    pub fn submitted_totals(&self) -> (usize, u64) {
+        self.entries
+            .iter()
+            .filter(|e| e.status == TxStatus::Submitted)
+            .fold((0, 0u64), |(count, total), e| {
+                (count + 1, total.saturating_add(e.amount()))
+            })
+    }
+
+    /// Replace the note of the entry with `tx_hash`. Returns `None` if no
+    /// entry has it.
+    pub fn set_note(&mut self, tx_hash: &str, note: &str) -> Option<&JournalEntry> {
+        let index = self
+            .entries
+            .iter()
+            .rposition(|e| e.tx_hashes.iter().any(|h| h == tx_hash))?;
+        self.entries[index].note = Some(note.to_string());
+        Some(&self.entries[index])
+    }
+
+    /// Look up an entry by any of its tx hashes.
+    pub fn find_by_hash(&self, tx_hash: &str) -> Option<&JournalEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|e| e.tx_hashes.iter().any(|h| h == tx_hash))
+    }
+
+    /// What is known about `tx_set` before it is submitted: its decoded
+    /// `description` if there is one, otherwise its journal entry. The note
+    /// always comes from the journal. `None` when neither says where the
+    /// money goes.
+    pub fn submit_summary(
+        &self,
+        tx_set: &str,
+        description: Option<&[TransferDescription]>,
+    ) -> Option<SubmitSummary> {
+        let description = description.filter(|d| !d.is_empty());
+        let entry = self
+            .find(tx_set, &[], description)
+            .map(|i| &self.entries[i]);
+        let (source, destinations, fee) = match (description, entry) {
+            (Some(desc), _) => (
+                SummarySource::Decoded,
+                desc.iter()
+                    .flat_map(|d| &d.recipients)
+                    .map(|r| Destination::new_unchecked(&r.address, Amount(r.amount)))
+                    .collect::<Vec<_>>(),
+                desc.iter()
+                    .fold(0u64, |total, d| total.saturating_add(d.fee)),
+            ),
+            (None, Some(e)) if !e.destinations.is_empty() => {
+                (SummarySource::Journal, e.destinations.clone(), e.fee)
+            }
+            _ => return None,
+        };
+        let total = destinations
+            .iter()
+            .fold(fee, |total, d| total.saturating_add(d.amount().0));
+        Some(SubmitSummary {
+            source,
+            destinations,
+            total,
+            fee,
+            note: entry.and_then(|e| e.note.clone()),
+            journal_id: entry.map(|e| e.id),
+        })
+    }
+
+    /// Entries matching `filter`, oldest first.
+    pub fn filter<'a>(
+        &'a self,
+        filter: &'a JournalFilter,
+    ) -> impl Iterator<Item = &'a JournalEntry> {
+        self.entries.iter().filter(move |e| filter.matches(e))
+    }
+
+    fn find_or_receive(
+        &mut self,
+        tx_set: &str,
+        tx_hashes: &[String],
+        description: Option<&[TransferDescription]>,
+        signatures_required: u32,
+    ) -> usize {
+        if let Some(index) = self.find(tx_set, tx_hashes, description) {
+            let entry = &mut self.entries[index];
+            entry.see_tx_set(tx_set);
+            return index;
+        }
+
+        let mut entry = self.new_entry(signatures_required);
+        if let Some(desc) = description {
+            entry.destinations = desc
+                .iter()
+                .flat_map(|d| d.recipients.iter())
+                .map(|r| Destination::new_unchecked(r.address.clone(), Amount(r.amount)))
+                .collect();
+            entry.fee = desc
+                .iter()
+                .fold(0u64, |total, d| total.saturating_add(d.fee));
+        }
+        entry.see_tx_set(tx_set);
+        entry.push_event(JournalAction::Received);
+        self.entries.push(entry);
+        self.entries.len() - 1
+    }
+
+    fn new_entry(&self, signatures_required: u32) -> JournalEntry {
+        JournalEntry {
+            id: self
+                .entries
+                .iter()
+                .map(|e| e.id)
+                .max()
+                .map_or(1, |id| id + 1),
+            created_at: Utc::now(),
+            status: TxStatus::AwaitingSignatures,
+            note: None,
+            destinations: Vec::new(),
+            fee: 0,
+            weight: 0,
+            size_bytes: 0,
+            priority: None,
+            built_by: None,
+            tx_hashes: Vec::new(),
+            tx_set_digests: Vec::new(),
+            tx_set: None,
+            supersedes: None,
+            superseded_by: None,
+            signatures_collected: 0,
+            signatures_required,
+            signers: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+}
+
+/// What [`Journal::prune`] cleaned up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneSummary {
+    /// Pending entries marked discarded.
+    pub abandoned: usize,
+    /// Stored tx sets removed.
+    pub tx_sets_dropped: usize,
+}
+
+/// What `submit-tx` shows before broadcasting, from
+/// [`Journal::submit_summary`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SubmitSummary {
+    pub source: SummarySource,
+    pub destinations: Vec<Destination>,
+    /// Everything leaving the wallet: the destinations plus the fee.
+    pub total: u64,
+    pub fee: u64,
+    pub note: Option<String>,
+    pub journal_id: Option<u64>,
+}
+
+/// Where a [`SubmitSummary`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummarySource {
+    /// The wallet RPC decoded the tx set itself.
+    Decoded,
+    /// Only the journal entry recorded when the set was built or signed.
+    Journal,
+}
+
+/// A transaction's note as shown: the journal's and the wallet's merged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergedNote {
+    pub text: String,
+    /// The wallet has a different note, which the journal's overrides.
+    pub conflict: bool,
+}
+
+/// Merge a transaction's `local` journal note with the one its `wallet`
+/// keeps. The journal's wins when both have one and they differ. Empty
+/// notes count as none.
+pub fn merge_notes(local: Option<&str>, wallet: Option<&str>) -> Option<MergedNote> {
+    let local = local.filter(|n| !n.is_empty());
+    let wallet = wallet.filter(|n| !n.is_empty());
+    let text = local.or(wallet)?;
+    Some(MergedNote {
+        text: text.to_string(),
+        conflict: matches!((local, wallet), (Some(l), Some(w)) if l != w),
+    })
+}
+
+/// Render entries as CSV with a header row.
+pub fn to_csv<'a>(entries: impl IntoIterator<Item = &'a JournalEntry>) -> String {
+    fn field(value: &str) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+
+    let mut out = String::from(
+        "id,created_at,status,amount_piconero,fee_piconero,destinations,tx_hashes,signatures,built_by,note\n",
+    );
+    for e in entries {
+        let status = serde_json::to_value(e.status)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let destinations = e
+            .destinations
+            .iter()
+            .map(|d| format!("{}:{}", d.address(), d.amount()))
+            .collect::<Vec<_>>()
+            .join(";");
+        let row = [
+            e.id.to_string(),
+            e.created_at.to_rfc3339(),
+            status,
+            e.amount().to_string(),
+            e.fee.to_string(),
+            destinations,
+            e.tx_hashes.join(";"),
+            format!("{}/{}", e.signatures_collected, e.signatures_required),
+            e.built_by.clone().unwrap_or_default(),
+            e.note.clone().unwrap_or_default(),
+        ];
+        out.push_str(&row.iter().map(|f| field(f)).collect::<Vec<_>>().join(","));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::Recipient;
+
+    fn dest(address: &str, amount: u64) -> Destination {
+        Destination::new_unchecked(address, Amount(amount))
+    }
+
+    fn describe(address: &str, amount: u64, fee: u64) -> Vec<TransferDescription> {
+        vec![TransferDescription {
+            recipients: vec![Recipient {
+                address: address.into(),
+                amount,
+            }],
+            amount_in: 0,
+            amount_out: 0,
+            change_amount: 0,
+            change_address: String::new(),
+            fee,
+            ring_size: 16,
+            unlock_time: 0,
+        }]
+    }
+
+    #[test]
+    fn test_lifecycle_links_by_digest() {
+        let mut journal = Journal::default();
+        journal.record_built(
+            &[dest("4a", 10)],
+            1,
+            &["h0".into()],
+            "set0",
+            2,
+            Some("rent".into()),
+        );
+        assert_eq!(journal.entries[0].status, TxStatus::AwaitingSignatures);
+        journal.record_size("set0", 1536, 1500);
+        assert_eq!(journal.entries[0].weight, 1536);
+
+        journal.record_signed("set0", "set1", &["h1".into()], None, 2);
+        assert_eq!(journal.entries.len(), 1);
+        // The builder's signature was counted when it built the set.
+        assert_eq!(journal.entries[0].signatures_collected, 1);
+        assert_eq!(journal.entries[0].status, TxStatus::AwaitingSignatures);
+
+        journal.record_submitted("set1", &["h2".into()], None, 2);
+        let entry = &journal.entries[0];
+        assert_eq!(entry.status, TxStatus::Submitted);
+        assert_eq!(entry.latest_tx_hash(), Some("h2"));
+        assert_eq!(entry.events.len(), 3);
+    }
+
+    #[test]
+    fn test_submitted_totals() {
+        let mut journal = Journal::default();
+        journal.record_built(
+            &[dest("4a", 10), dest("4b", 5)],
+            1,
+            &["h0".into()],
+            "s0",
+            2,
+            None,
+        );
+        journal.record_submitted("s0", &["h0".into()], None, 2);
+        journal.record_built(&[dest("4a", 99)], 1, &["h1".into()], "s1", 2, None);
+        journal.record_built(&[dest("4c", 7)], 1, &["h2".into()], "s2", 2, None);
+        journal.record_submitted("s2", &["h2".into()], None, 2);
+        assert_eq!(journal.submitted_totals(), (2, 22));
+    }
+
+    #[test]
+    fn test_submit_summary() {
+        let mut journal = Journal::default();
+        journal.record_built(
+            &[dest("4a", 10)],
+            1,
+            &["h0".into()],
+            "set0",
+            2,
+            Some("rent".into()),
+        );
+
+        let summary = journal.submit_summary("set0", None).unwrap();
+        assert_eq!(summary.source, SummarySource::Journal);
+        assert_eq!(summary.destinations, [dest("4a", 10)]);
+        assert_eq!((summary.total, summary.fee), (11, 1));
+        assert_eq!(summary.note.as_deref(), Some("rent"));
+        assert_eq!(summary.journal_id, Some(1));
+
+        // A decoded set wins over the journal, but keeps its note.
+        let desc = describe("4a", 10, 2);
+        let summary = journal.submit_summary("set1", Some(&desc)).unwrap();
+        assert_eq!(summary.source, SummarySource::Decoded);
+        assert_eq!(summary.total, 12);
+        assert_eq!(summary.note, None);
+        let desc = describe("4a", 10, 1);
+        let summary = journal.submit_summary("set1", Some(&desc)).unwrap();
+        assert_eq!(summary.note.as_deref(), Some("rent"));
+
+        assert_eq!(journal.submit_summary("unknown", None), None);
+        assert_eq!(journal.submit_summary("unknown", Some(&[])), None);
+    }
+
+    #[test]
+    fn test_fee_bump_supersedes_the_rejected_entry() {
+        let mut journal = Journal::default();
+        journal.record_built(&[dest("4a", 10)], 1, &["h0".into()], "set0", 2, None);
+        journal.record_priority("set0", Priority::Default);
+        let next = journal.entries[0].priority.and_then(Priority::next);
+        assert_eq!(next, Some(Priority::Medium));
+        assert_eq!(Priority::High.next(), None);
+
+        let id = journal
+            .record_built(&[dest("4a", 10)], 3, &["h1".into()], "set1", 2, None)
+            .id;
+        journal.record_priority("set1", Priority::Medium);
+        assert!(journal.record_superseded(1, id));
+        let (old, new) = (&journal.entries[0], &journal.entries[1]);
+        assert_eq!(old.status, TxStatus::Discarded);
+        assert_eq!(old.superseded_by, Some(2));
+        assert!(matches!(
+            old.events.last().unwrap().action,
+            JournalAction::Superseded { by: 2 }
+        ));
+        assert_eq!(new.supersedes, Some(1));
+        assert_eq!(new.priority, Some(Priority::Medium));
+        assert_eq!(new.status, TxStatus::AwaitingSignatures);
+
+        assert!(!journal.record_superseded(1, 9));
+    }
+
+    #[test]
+    fn test_signers_credited_once_per_set() {
+        let mut journal = Journal::default();
+        journal.record_built(&[dest("4a", 10)], 1, &["h0".into()], "set0", 3, None);
+        assert!(journal.entries[0].signed_by("me"));
+
+        // alice signed set0 into set1 and sent it back.
+        let desc = describe("4a", 10, 1);
+        let (outcome, entry) = journal.record_signer("set1", Some(&desc), "alice", 3);
+        assert_eq!(outcome, Attribution::Recorded);
+        assert_eq!(entry.signatures_collected, 2);
+        assert!(entry.signed_by("alice"));
+
+        // Re-importing the same set changes nothing.
+        let (outcome, entry) = journal.record_signer("set1", Some(&desc), "alice", 3);
+        assert_eq!(outcome, Attribution::Unchanged);
+        assert_eq!(entry.signatures_collected, 2);
+
+        let (outcome, _) = journal.record_signer("set1", Some(&desc), "bob", 3);
+        assert_eq!(outcome, Attribution::SetCreditedTo("alice".into()));
+        let (outcome, entry) = journal.record_signer("set2", Some(&desc), "alice", 3);
+        assert_eq!(outcome, Attribution::AlreadySigned);
+        assert_eq!(entry.signatures_collected, 2);
+
+        let (outcome, entry) = journal.record_signer("set2", Some(&desc), "bob", 3);
+        assert_eq!(outcome, Attribution::Recorded);
+        assert_eq!(entry.status, TxStatus::ReadyToSubmit);
+        let names: Vec<_> = entry.signers.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["me", "alice", "bob"]);
+    }
+
+    #[test]
+    fn test_signatures_already_credited_are_not_recounted() {
+        let mut journal = Journal::default();
+        journal.record_built(&[dest("4a", 10)], 1, &["h0".into()], "set0", 3, None);
+        let desc = describe("4a", 10, 1);
+        journal.record_signer("set1", Some(&desc), "alice", 3);
+
+        // The builder signing alice's set again adds no third signature.
+        let entry = journal.record_signed("set1", "set2", &[], Some(&desc), 3);
+        assert_eq!(entry.signatures_collected, 2);
+        assert_eq!(entry.status, TxStatus::AwaitingSignatures);
+        let entry = journal.record_signed("set1", "set2", &[], Some(&desc), 3);
+        assert_eq!(entry.signatures_collected, 2);
+
+        // Nor does a set already credited to alice, whoever signs it.
+        let mut journal = Journal::default();
+        let desc = describe("4b", 7, 2);
+        journal.record_signer("setA", Some(&desc), "alice", 3);
+        let entry = journal.record_signed("setX", "setA", &[], Some(&desc), 3);
+        assert_eq!(entry.signatures_collected, 1);
+        assert_eq!(entry.status, TxStatus::AwaitingSignatures);
+    }
+
+    #[test]
+    fn test_crediting_the_builder_adds_no_signature() {
+        // bob got alice's freshly built set and credits her with it.
+        let mut journal = Journal::default();
+        let desc = describe("4a", 10, 1);
+        let (outcome, entry) = journal.record_signer("set0", Some(&desc), "alice", 2);
+        assert_eq!(outcome, Attribution::Recorded);
+        assert_eq!(entry.signatures_collected, 1);
+        assert_eq!(entry.status, TxStatus::AwaitingSignatures);
+
+        // Signed here, the set has its second signature and no more.
+        let entry = journal.record_signed("set0", "set1", &[], Some(&desc), 2);
+        assert_eq!(entry.signatures_collected, 2);
+        assert_eq!(entry.status, TxStatus::ReadyToSubmit);
+
+        // Signed here first, alice's signature is the uncredited one.
+        let mut journal = Journal::default();
+        journal.record_signed("set0", "set1", &[], Some(&desc), 2);
+        let (outcome, entry) = journal.record_signer("set0", Some(&desc), "alice", 2);
+        assert_eq!(outcome, Attribution::Recorded);
+        assert_eq!(entry.signatures_collected, 2);
+        let names: Vec<_> = entry.signers.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["me", "alice"]);
+    }
+
+    #[test]
+    fn test_submit_links_by_description() {
+        let mut journal = Journal::default();
+        journal.record_built(&[dest("4a", 10)], 1, &["h0".into()], "set0", 2, None);
+
+        // A co-signer's set we've never seen, paying the same thing.
+        let desc = describe("4a", 10, 1);
+        journal.record_submitted("setX", &["hX".into()], Some(&desc), 2);
+        assert_eq!(journal.entries.len(), 1);
+        assert_eq!(journal.entries[0].status, TxStatus::Submitted);
+    }
+
+    #[test]
+    fn test_unknown_set_creates_received_entry() {
+        let mut journal = Journal::default();
+        let desc = describe("4b", 7, 2);
+        journal.record_signed("setA", "setB", &[], Some(&desc), 3);
+        let entry = &journal.entries[0];
+        assert!(matches!(entry.events[0].action, JournalAction::Received));
+        assert_eq!(entry.signatures_collected, 2);
+        assert_eq!(entry.status, TxStatus::AwaitingSignatures);
+        assert_eq!(entry.amount(), 7);
+    }
+
+    #[test]
+    fn test_discarded_entry_is_found_by_content() {
+        let mut journal = Journal::default();
+        journal.record_built(&[dest("4a", 10)], 1, &["h0".into()], "set0", 2, None);
+        assert!(journal.record_discarded("nope").is_none());
+        assert_eq!(
+            journal.record_discarded("h0").unwrap().status,
+            TxStatus::Discarded
+        );
+        assert!(journal.record_discarded("h0").is_none());
+
+        let desc = describe("4a", 10, 1);
+        let index = journal.find("setY", &[], Some(&desc)).unwrap();
+        assert_eq!(journal.entries[index].status, TxStatus::Discarded);
+    }
+
+    #[test]
+    fn test_held_for_relay_then_submitted() {
+        let mut journal = Journal::default();
+        journal.record_built(&[dest("4a", 10)], 1, &["h0".into()], "set0", 2, None);
+        journal.record_signed("set0", "set1", &["h0".into()], None, 2);
+        journal.record_held("set1", &[], None, 2);
+        assert_eq!(journal.entries[0].status, TxStatus::AwaitingRelay);
+        assert_eq!(journal.pending().len(), 1);
+
+        // Relaying the same file links back to the held entry.
+        journal.record_submitted("set1", &[], None, 2);
+        assert_eq!(journal.entries.len(), 1);
+        assert_eq!(journal.entries[0].status, TxStatus::Submitted);
+    }
+
+    #[test]
+    fn test_confirmed_recorded_once() {
+        let mut journal = Journal::default();
+        journal.record_built(&[dest("4a", 10)], 1, &["h0".into()], "set0", 2, None);
+        assert!(journal.record_confirmed("h0", 100).is_none());
+        journal.record_submitted("set0", &["h0".into()], None, 2);
+        assert!(journal.record_confirmed("h0", 100).is_some());
+        assert!(journal.record_confirmed("h0", 101).is_none());
+        assert_eq!(journal.entries[0].status, TxStatus::Submitted);
+
+        let entry = journal
+            .record_broadcast(&["h0".into()], Visibility::Mined)
+            .unwrap();
+        assert!(matches!(
+            entry.events.last().unwrap().action,
+            JournalAction::BroadcastChecked {
+                visibility: Visibility::Mined
+            }
+        ));
+        assert!(journal
+            .record_broadcast(&["h9".into()], Visibility::InPool)
+            .is_none());
+        assert!(journal.record_broadcast(&[], Visibility::InPool).is_none());
+    }
+
+    #[test]
+    fn test_broadcast_of_a_split_set_is_recorded_once() {
+        let mut journal = Journal::default();
+        let hashes = ["h0".to_string(), "h1".to_string()];
+        journal.record_built(&[dest("4a", 10)], 1, &hashes, "set0", 2, None);
+        journal.record_submitted("set0", &hashes, None, 2);
+
+        let entry = journal
+            .record_broadcast(&hashes, Visibility::NotVisible)
+            .unwrap();
+        let checks: Vec<_> = entry
+            .events
+            .iter()
+            .filter(|e| matches!(e.action, JournalAction::BroadcastChecked { .. }))
+            .collect();
+        assert_eq!(checks.len(), 1);
+        // Hashes from more than one entry are no one entry's broadcast.
+        assert!(journal
+            .record_broadcast(&["h0".into(), "h9".into()], Visibility::InPool)
+            .is_none());
+    }
+
+    #[test]
+    fn test_rejection_lists_possible_conflicts() {
+        let mut journal = Journal::default();
+        journal.record_built(&[dest("4a", 10)], 1, &["h0".into()], "set0", 2, None);
+        journal.record_built(&[dest("4b", 20)], 1, &["h1".into()], "set1", 2, None);
+        journal.record_built(&[dest("4c", 30)], 1, &["h2".into()], "set2", 2, None);
+        journal.record_submitted("set1", &["h1".into()], None, 2);
+        journal.record_submitted("set2", &["h2".into()], None, 2);
+        journal.record_confirmed("h2", 100);
+
+        let entry = journal
+            .record_rejected("set0", None, RejectionReason::DoubleSpend, "double spend")
+            .unwrap();
+        assert_eq!(entry.status, TxStatus::AwaitingSignatures);
+        assert!(matches!(
+            entry.events.last().unwrap().action,
+            JournalAction::Rejected {
+                reason: RejectionReason::DoubleSpend,
+                ..
+            }
+        ));
+        let ids: Vec<u64> = journal
+            .may_share_inputs(Some(1))
+            .iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, [2]);
+        assert!(journal
+            .record_rejected("unknown", None, RejectionReason::Other, "")
+            .is_none());
+    }
+
+    #[test]
+    fn test_pending_and_prune() {
+        let mut journal = Journal::default();
+        journal.record_built(&[dest("4a", 10)], 1, &["h0".into()], "set0", 2, None);
+        journal.record_built(&[dest("4b", 20)], 1, &["h1".into()], "set1", 2, None);
+        journal.record_submitted("set1", &["h1".into()], None, 2);
+        assert_eq!(journal.entries[0].tx_set.as_deref(), Some("set0"));
+
+        let pending = journal.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, 1);
+
+        // Nothing is older than a cutoff in the past.
+        let past = Utc::now() - chrono::Duration::days(30);
+        assert_eq!(journal.prune(past), PruneSummary::default());
+
+        let future = Utc::now() + chrono::Duration::days(1);
+        let summary = journal.prune(future);
+        assert_eq!(summary.abandoned, 1);
+        assert_eq!(summary.tx_sets_dropped, 2);
+        assert_eq!(journal.entries.len(), 2);
+        assert!(journal.pending().is_empty());
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut journal = Journal::default();
+        journal.record_built(
+            &[dest("4a", 10)],
+            1,
+            &["h0".into()],
+            "s0",
+            2,
+            Some("Office rent".into()),
+        );
+        journal.record_built(&[dest("4b", 20)], 1, &["h1".into()], "s1", 2, None);
+
+        let by_note = JournalFilter {
+            note: Some("RENT".into()),
+            ..Default::default()
+        };
+        assert_eq!(journal.filter(&by_note).count(), 1);
+
+        let by_address = JournalFilter {
+            address: Some("4b".into()),
+            ..Default::default()
+        };
+        assert_eq!(journal.filter(&by_address).next().unwrap().id, 2);
+
+        let future = JournalFilter {
+            since: Some(NaiveDate::from_ymd_opt(9999, 1, 1).unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(journal.filter(&future).count(), 0);
+    }
+
+    #[test]
+    fn test_csv_escaping() {
+        let mut journal = Journal::default();
+        journal.record_built(
+            &[dest("4a", 10)],
+            1,
+            &["h0".into()],
+            "s0",
+            2,
+            Some("a, \"b\"".into()),
+        );
+        let csv = to_csv(&journal.entries);
+        assert!(csv.lines().nth(1).unwrap().ends_with("\"a, \"\"b\"\"\""));
+    }
+
+    #[test]
+    fn test_notes_merge_with_the_journal_winning() {
+        let merged = |local, wallet| merge_notes(local, wallet).map(|m| (m.text, m.conflict));
+        assert_eq!(merged(Some("rent"), None), Some(("rent".into(), false)));
+        assert_eq!(merged(None, Some("rent")), Some(("rent".into(), false)));
+        assert_eq!(
+            merged(Some("rent"), Some("rent")),
+            Some(("rent".into(), false))
+        );
+        assert_eq!(
+            merged(Some("rent"), Some("bills")),
+            Some(("rent".into(), true))
+        );
+        assert_eq!(
+            merged(Some(""), Some("bills")),
+            Some(("bills".into(), false))
+        );
+        assert_eq!(merged(None, Some("")), None);
+
+        let mut journal = Journal::default();
+        journal.record_built(&[dest("4a", 10)], 1, &["h0".into()], "set0", 2, None);
+        assert_eq!(
+            journal.set_note("h0", "rent").unwrap().note.as_deref(),
+            Some("rent")
+        );
+        assert!(journal.set_note("h9", "rent").is_none());
+    }
+}
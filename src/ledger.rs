@@ -0,0 +1,293 @@
+//! Rolling record of amounts this machine has signed, backing the
+//! `policy.spend_limit` cap.
+//!
+//! The ledger lives in `data_dir/spend_ledger.json` so the cap survives
+//! restarts. Only signatures made here count: the limit bounds what a single
+//! compromised or careless signer can approve, not what the wallet spends.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::config::DataDir;
+use crate::error::Result;
+use crate::transaction::Amount;
+
+const LEDGER_FILE: &str = "spend_ledger.json";
+
+/// A source of the current time, so tests can move time forward.
+pub trait Clock {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// At most `amount` piconero signed in any `window_hours` window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendLimit {
+    pub amount: Amount,
+    pub window_hours: u64,
+}
+
+impl SpendLimit {
+    fn window(&self) -> Duration {
+        Duration::hours(self.window_hours as i64)
+    }
+}
+
+/// One signature counted against the limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub signed_at: DateTime<Utc>,
+    /// Amount sent to destinations other than our own (change excluded).
+    pub amount: Amount,
+    pub tx_hash: String,
+}
+
+/// A signature the spend limit doesn't allow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpendLimitExceeded {
+    pub amount: Amount,
+    pub limit: Amount,
+    pub window_hours: u64,
+    pub remaining: Amount,
+    /// When enough earlier signatures leave the window for `amount` to fit,
+    /// or `None` if `amount` alone is over the limit.
+    pub frees_at: Option<DateTime<Utc>>,
+}
+
+impl fmt::Display for SpendLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "signing {} would exceed the spend limit of {} per {}h; {} remaining",
+            self.amount, self.limit, self.window_hours, self.remaining
+        )?;
+        match self.frees_at {
+            Some(at) => write!(
+                f,
+                ", enough capacity frees up at {}",
+                at.format("%Y-%m-%d %H:%M UTC")
+            ),
+            None => write!(f, ", and the amount alone is over the limit"),
+        }
+    }
+}
+
+/// The persisted ledger.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpendLedger {
+    pub entries: Vec<LedgerEntry>,
+}
+
+impl SpendLedger {
+    /// Load the ledger from `data_dir`, or an empty one if none exists.
+    pub fn load(data_dir: &DataDir) -> Result<Self> {
+        match std::fs::read_to_string(data_dir.file(LEDGER_FILE)) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Atomically write the ledger to `data_dir`.
+    pub fn save(&self, data_dir: &DataDir) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        crate::utils::write_atomic(&data_dir.file(LEDGER_FILE), json.as_bytes())?;
+        Ok(())
+    }
+
+    /// The time the ledger treats as now: the system time, but never earlier
+    /// than the newest entry. Otherwise a signature made while the clock was
+    /// set back would be stamped in the past and leave the window early once
+    /// the clock is corrected.
+    fn effective_now(&self, clock: &dyn Clock) -> DateTime<Utc> {
+        let now = clock.now();
+        self.entries
+            .iter()
+            .map(|e| e.signed_at)
+            .max()
+            .map_or(now, |newest| newest.max(now))
+    }
+
+    /// Entries still inside the window, oldest first.
+    fn in_window(&self, limit: &SpendLimit, now: DateTime<Utc>) -> Vec<&LedgerEntry> {
+        let start = now - limit.window();
+        let mut entries: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|e| e.signed_at > start)
+            .collect();
+        entries.sort_by_key(|e| e.signed_at);
+        entries
+    }
+
+    /// Amount signed inside the current window.
+    pub fn used(&self, limit: &SpendLimit, clock: &dyn Clock) -> Amount {
+        let now = self.effective_now(clock);
+        Amount(used(&self.in_window(limit, now)))
+    }
+
+    /// Check whether signing `amount` now stays within `limit`.
+    pub fn check(
+        &self,
+        limit: &SpendLimit,
+        amount: Amount,
+        clock: &dyn Clock,
+    ) -> std::result::Result<(), SpendLimitExceeded> {
+        let now = self.effective_now(clock);
+        let window = self.in_window(limit, now);
+        let used = used(&window);
+        if used.saturating_add(amount.0) <= limit.amount.0 {
+            return Ok(());
+        }
+
+        let frees_at = if amount > limit.amount {
+            None
+        } else {
+            // Walk entries oldest first until enough have expired.
+            let mut remaining_used = used;
+            window.iter().find_map(|e| {
+                remaining_used = remaining_used.saturating_sub(e.amount.0);
+                (remaining_used.saturating_add(amount.0) <= limit.amount.0)
+                    .then(|| e.signed_at + limit.window())
+            })
+        };
+        Err(SpendLimitExceeded {
+            amount,
+            limit: limit.amount,
+            window_hours: limit.window_hours,
+            remaining: Amount(limit.amount.0.saturating_sub(used)),
+            frees_at,
+        })
+    }
+
+    /// Record a signature and drop entries older than the window.
+    pub fn record(&mut self, limit: &SpendLimit, amount: Amount, tx_hash: &str, clock: &dyn Clock) {
+        let now = self.effective_now(clock);
+        let start = now - limit.window();
+        self.entries.retain(|e| e.signed_at > start);
+        self.entries.push(LedgerEntry {
+            signed_at: now,
+            amount,
+            tx_hash: tx_hash.to_string(),
+        });
+    }
+}
+
+/// Total of `entries`. Saturates rather than wrapping, so a tampered ledger
+/// can only make the limit stricter.
+fn used(entries: &[&LedgerEntry]) -> u64 {
+    entries
+        .iter()
+        .fold(0u64, |total, e| total.saturating_add(e.amount.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    struct MockClock(Cell<DateTime<Utc>>);
+
+    impl MockClock {
+        fn new() -> Self {
+            Self(Cell::new(
+                DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z")
+                    .unwrap()
+                    .with_timezone(&Utc),
+            ))
+        }
+
+        fn advance(&self, hours: i64) {
+            self.0.set(self.0.get() + Duration::hours(hours));
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> DateTime<Utc> {
+            self.0.get()
+        }
+    }
+
+    const LIMIT: SpendLimit = SpendLimit {
+        amount: Amount(100),
+        window_hours: 24,
+    };
+
+    #[test]
+    fn test_window_rolls_forward() {
+        let clock = MockClock::new();
+        let mut ledger = SpendLedger::default();
+
+        ledger.record(&LIMIT, Amount(60), "a", &clock);
+        clock.advance(6);
+        ledger.record(&LIMIT, Amount(40), "b", &clock);
+        assert_eq!(ledger.used(&LIMIT, &clock), Amount(100));
+
+        let err = ledger.check(&LIMIT, Amount(1), &clock).unwrap_err();
+        assert_eq!(err.remaining, Amount::ZERO);
+        // "a" leaves the window 24h after it was signed, 18h from now.
+        assert_eq!(err.frees_at, Some(clock.now() + Duration::hours(18)));
+
+        clock.advance(18);
+        assert!(ledger.check(&LIMIT, Amount(60), &clock).is_ok());
+        assert!(ledger.check(&LIMIT, Amount(61), &clock).is_err());
+
+        clock.advance(6);
+        assert!(ledger.check(&LIMIT, Amount(100), &clock).is_ok());
+    }
+
+    #[test]
+    fn test_amount_over_limit_never_frees() {
+        let clock = MockClock::new();
+        let ledger = SpendLedger::default();
+        assert!(ledger.check(&LIMIT, Amount(100), &clock).is_ok());
+        let err = ledger.check(&LIMIT, Amount(101), &clock).unwrap_err();
+        assert_eq!(err.frees_at, None);
+        assert!(err.to_string().contains("amount alone"));
+    }
+
+    #[test]
+    fn test_clock_going_backwards_keeps_entries() {
+        let clock = MockClock::new();
+        let mut ledger = SpendLedger::default();
+        ledger.record(&LIMIT, Amount(100), "a", &clock);
+
+        // Winding the clock back doesn't free capacity, and a new entry is
+        // stamped no earlier than the newest one.
+        clock.advance(-48);
+        assert!(ledger.check(&LIMIT, Amount(1), &clock).is_err());
+        ledger.record(&LIMIT, Amount(0), "b", &clock);
+        assert!(ledger.entries[1].signed_at >= ledger.entries[0].signed_at);
+    }
+
+    #[test]
+    fn test_record_prunes_old_entries() {
+        let clock = MockClock::new();
+        let mut ledger = SpendLedger::default();
+        ledger.record(&LIMIT, Amount(10), "a", &clock);
+        clock.advance(25);
+        ledger.record(&LIMIT, Amount(10), "b", &clock);
+        assert_eq!(ledger.entries.len(), 1);
+        assert_eq!(ledger.entries[0].tx_hash, "b");
+    }
+
+    #[test]
+    fn test_tampered_ledger_saturates() {
+        let clock = MockClock::new();
+        let mut ledger = SpendLedger::default();
+        ledger.record(&LIMIT, Amount(u64::MAX), "a", &clock);
+        ledger.record(&LIMIT, Amount(u64::MAX), "b", &clock);
+        assert_eq!(ledger.used(&LIMIT, &clock), Amount(u64::MAX));
+        assert!(ledger.check(&LIMIT, Amount::ZERO, &clock).is_err());
+    }
+}
@@ -1,5 +1,60 @@
+//! The library behind the `monero-multisig` tool, usable on its own to
+//! drive a multisig wallet through monero-wallet-rpc.
+//!
+//! # Cargo features
+//!
+//! - `cli` (default): the `monero-multisig` binary and what only it needs:
+//!   clap, anyhow, tracing-subscriber, tracing-appender and rpassword.
+//!   Turns on `metrics`.
+//! - `metrics`: the Prometheus endpoint, [`metrics::serve`]. The registry
+//!   itself is always there.
+//! - `cold-sign`: the binary's `cold-sign` and `cold-submit` commands. The
+//!   library functions behind them, [`transaction::sign_cold_tx`] and
+//!   [`transaction::submit_cold_tx`], are always there.
+//! - `blocking`: the [`blocking`] module, synchronous wrappers for callers
+//!   without an async runtime.
+//! - `keyring`: [`credentials`] reads and writes the OS keyring; without it
+//!   the keyring calls fail with
+//!   [`CredentialsError::Unsupported`](credentials::CredentialsError::Unsupported).
+//!
+//! With `default-features = false` the rest of the crate, including
+//! [`config::RpcClient`], [`wallet`], [`transaction`] and [`journal`], builds
+//! on reqwest, serde and tokio plus a few small hashing and encoding crates.
+
+pub mod address;
+pub mod armor;
+pub mod audit;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod bundle;
+pub mod capabilities;
 pub mod config;
+pub mod credentials;
+pub mod display;
+pub mod doctor;
 pub mod error;
+pub mod escrow;
+pub mod interrupt;
+pub mod journal;
+pub mod ledger;
+pub mod lock;
+pub mod manifest;
+pub mod messages;
+pub mod metrics;
+pub mod monitor;
+pub mod notify;
+pub mod output;
+pub mod payout;
+pub mod policy;
+pub mod proof;
+pub mod report;
+pub mod rpc;
+pub mod secret;
+#[cfg(test)]
+mod testing;
 pub mod transaction;
+pub mod uri;
 pub mod utils;
 pub mod wallet;
+mod wordlist;
+pub mod workflow;
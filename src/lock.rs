@@ -0,0 +1,193 @@
+//! Advisory lock on the data directory.
+//!
+//! Two invocations writing `wallet_state.json` or the journal at the same time
+//! would each save over the other's changes, so commands that may write take
+//! an exclusive lock on `data_dir/.lock` and read-only ones take a shared
+//! lock. The lock is an OS file lock tied to the open file, so it goes away
+//! with the process however it ends — including a panic or Ctrl+C — and a
+//! stale lock file never blocks anyone.
+//!
+//! An exclusive holder writes its pid and hostname into the file so a
+//! waiting invocation can say who it is waiting for. Shared holders aren't
+//! recorded; they clear the line instead, so a writer waiting on readers
+//! doesn't name a process that finished long ago.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+use crate::config::DataDir;
+
+const LOCK_FILE: &str = ".lock";
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How a command uses the data directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
+    /// Only reads; any number of readers may run together.
+    Shared,
+    /// May write; runs alone.
+    Exclusive,
+}
+
+#[derive(Error, Debug)]
+pub enum LockError {
+    #[error(
+        "{} is in use by {holder}; gave up after {}s (raise --lock-timeout to wait longer)",
+        dir.display(),
+        waited.as_secs()
+    )]
+    Timeout {
+        dir: PathBuf,
+        holder: String,
+        waited: Duration,
+    },
+
+    #[error("failed to lock {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// A held lock on the data directory, released when dropped.
+#[derive(Debug)]
+pub struct DataDirLock {
+    _file: File,
+}
+
+/// Lock `data_dir`, waiting up to `timeout` for other invocations to finish.
+///
+/// `command` is recorded next to the pid and hostname so the holder is easy
+/// to identify.
+pub fn acquire(
+    data_dir: &DataDir,
+    mode: LockMode,
+    timeout: Duration,
+    command: &str,
+) -> Result<DataDirLock, LockError> {
+    let path = data_dir.file(LOCK_FILE);
+    let io = |source| LockError::Io {
+        path: path.clone(),
+        source,
+    };
+    std::fs::create_dir_all(data_dir).map_err(io)?;
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .map_err(io)?;
+
+    let started = Instant::now();
+    loop {
+        let attempt = match mode {
+            LockMode::Shared => file.try_lock_shared(),
+            LockMode::Exclusive => file.try_lock(),
+        };
+        match attempt {
+            Ok(()) => break,
+            Err(TryLockError::WouldBlock) if started.elapsed() < timeout => {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(LockError::Timeout {
+                    dir: data_dir.to_path_buf(),
+                    holder: holder(&mut file),
+                    waited: started.elapsed(),
+                });
+            }
+            Err(TryLockError::Error(e)) => return Err(io(e)),
+        }
+    }
+
+    // Holding the lock at all means the last exclusive holder is gone, so its
+    // line is stale either way.
+    file.set_len(0).map_err(io)?;
+    if mode == LockMode::Exclusive {
+        let owner = format!(
+            "pid {} on {} ({command})\n",
+            std::process::id(),
+            gethostname::gethostname().to_string_lossy()
+        );
+        file.seek(SeekFrom::Start(0))
+            .and_then(|_| file.write_all(owner.as_bytes()))
+            .map_err(io)?;
+    }
+    Ok(DataDirLock { _file: file })
+}
+
+/// The owner line of the exclusive holder, if that is who holds the lock.
+fn holder(file: &mut File) -> String {
+    const UNKNOWN: &str = "another process (unknown; readers aren't recorded)";
+    let mut contents = String::new();
+    if file.seek(SeekFrom::Start(0)).is_err() || file.read_to_string(&mut contents).is_err() {
+        return UNKNOWN.to_string();
+    }
+    match contents.trim() {
+        "" => UNKNOWN.to_string(),
+        owner => owner.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHORT: Duration = Duration::from_millis(200);
+
+    #[test]
+    fn exclusive_excludes_everyone() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = DataDir::new(tmp.path());
+        let _held = acquire(&dir, LockMode::Exclusive, SHORT, "build-tx").unwrap();
+
+        for mode in [LockMode::Exclusive, LockMode::Shared] {
+            let err = acquire(&dir, mode, SHORT, "status").unwrap_err();
+            let message = err.to_string();
+            assert!(
+                message.contains(&format!("pid {}", std::process::id())),
+                "{message}"
+            );
+            assert!(message.contains("(build-tx)"), "{message}");
+        }
+    }
+
+    #[test]
+    fn readers_share_but_block_writers() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = DataDir::new(tmp.path());
+        let _first = acquire(&dir, LockMode::Shared, SHORT, "status").unwrap();
+        let _second = acquire(&dir, LockMode::Shared, SHORT, "balance").unwrap();
+
+        let err = acquire(&dir, LockMode::Exclusive, SHORT, "build-tx").unwrap_err();
+        assert!(matches!(err, LockError::Timeout { .. }));
+    }
+
+    #[test]
+    fn readers_are_not_reported_as_the_last_writer() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = DataDir::new(tmp.path());
+        drop(acquire(&dir, LockMode::Exclusive, SHORT, "sign-tx").unwrap());
+        let _reader = acquire(&dir, LockMode::Shared, SHORT, "status").unwrap();
+
+        let message = acquire(&dir, LockMode::Exclusive, SHORT, "build-tx")
+            .unwrap_err()
+            .to_string();
+        assert!(!message.contains("(sign-tx)"), "{message}");
+        assert!(message.contains("unknown"), "{message}");
+    }
+
+    #[test]
+    fn released_on_drop() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = DataDir::new(tmp.path());
+        drop(acquire(&dir, LockMode::Exclusive, SHORT, "build-tx").unwrap());
+        acquire(&dir, LockMode::Exclusive, SHORT, "sign-tx").unwrap();
+    }
+}
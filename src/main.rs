@@ -1,12 +1,43 @@
+use std::io::IsTerminal;
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
-use tracing_subscriber::EnvFilter;
+use clap::{Parser, Subcommand, ValueEnum};
+use tracing::Instrument;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
 
-use monero_multisig::config::{Config, RpcClient};
+use monero_multisig::armor;
+use monero_multisig::audit;
+use monero_multisig::bundle;
+use monero_multisig::config::{self, Config, RpcClient};
+use monero_multisig::credentials;
+use monero_multisig::display::{Cell, Color, Column, Style, Table};
+use monero_multisig::doctor;
+use monero_multisig::error::{MultisigError, RejectionReason, TransactionError, WalletError};
+use monero_multisig::escrow::{self, EscrowRole};
+use monero_multisig::interrupt;
+use monero_multisig::journal::{self, Journal, JournalFilter};
+use monero_multisig::ledger::{SpendLedger, SystemClock};
+use monero_multisig::lock::{self, LockMode};
+use monero_multisig::manifest::{self, ManifestError};
+use monero_multisig::messages::{self, Msg};
+use monero_multisig::metrics;
+use monero_multisig::monitor;
+use monero_multisig::notify::{self, EventKind};
+use monero_multisig::output;
+use monero_multisig::payout;
+use monero_multisig::proof;
+use monero_multisig::report;
+use monero_multisig::secret::Secret;
 use monero_multisig::transaction;
+use monero_multisig::uri;
+use monero_multisig::utils;
 use monero_multisig::wallet;
+use monero_multisig::workflow;
 
 #[derive(Parser)]
 #[command(
@@ -21,18 +52,113 @@ struct Cli {
     #[arg(short, long, global = true)]
     config: Option<PathBuf>,
 
-    /// Monero daemon RPC host.
-    #[arg(long, global = true, default_value = "127.0.0.1")]
-    daemon_host: String,
+    /// Keep state, journal and the rest here instead of the configured
+    /// `data_dir`. Created if missing.
+    #[arg(long, global = true)]
+    data_dir: Option<PathBuf>,
 
-    /// Monero daemon RPC port.
-    #[arg(long, global = true, default_value_t = 18081)]
-    daemon_port: u16,
+    /// Use the wallet kept under `wallets/<WALLET>` in the data directory.
+    /// Defaults to the config's `default_wallet`, then to the only wallet
+    /// there.
+    #[arg(long, global = true)]
+    wallet: Option<String>,
+
+    /// Monero daemon RPC host. Replaces the configured daemon list with a
+    /// single endpoint.
+    #[arg(long, global = true)]
+    daemon_host: Option<String>,
+
+    /// Monero daemon RPC port. Replaces the configured daemon list with a
+    /// single endpoint.
+    #[arg(long, global = true)]
+    daemon_port: Option<u16>,
+
+    /// Write a JSON-lines audit log to this path (rotated daily).
+    #[arg(long, global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Output format for command results.
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// Air-gapped mode: never let the wallet RPC reach a daemon. Only
+    /// sign-tx, export-info, describe, and status are available.
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Wrap blob output (tx sets, multisig info) in ASCII armor.
+    #[arg(long, global = true)]
+    armor: bool,
+
+    /// Talk to a wallet RPC older than the minimum supported version.
+    #[arg(long, global = true)]
+    skip_version_check: bool,
+
+    /// Don't compare the wallet RPC's address with the state file, e.g.
+    /// while recovering a wallet.
+    #[arg(long, global = true)]
+    skip_address_check: bool,
+
+    /// Never color the output. Setting `NO_COLOR` does the same.
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Language for prompts, warnings and next steps, e.g. `es`; defaults
+    /// to `MONERO_MULTISIG_LANG`, then English. JSON output and logs are
+    /// always English.
+    #[arg(long, global = true, value_name = "LANG")]
+    lang: Option<String>,
+
+    /// Group the digits of XMR amounts in tables and summaries, as in
+    /// `1,234,567.890000000000`. JSON output is never grouped.
+    #[arg(long, global = true)]
+    group_digits: bool,
+
+    /// Print the JSON Schema of the command's `--output json` result and
+    /// exit, without running it or needing its other arguments.
+    #[arg(long, global = true)]
+    schema: bool,
+
+    /// Seconds to wait for another invocation using the same data directory
+    /// to finish before giving up.
+    #[arg(long, global = true, value_name = "SECS", default_value_t = 30)]
+    lock_timeout: u64,
+
+    /// Wallet account to work on: the balance, history, outputs and address
+    /// shown, and the account build-tx spends from. A tag stands for the
+    /// accounts tagged with it; most commands need it to be on just one.
+    /// Defaults to 0, except for `account tag` and `account untag`, which
+    /// need it.
+    #[arg(long, global = true, value_name = "INDEX|TAG")]
+    account: Option<wallet::AccountSelector>,
+
+    /// After the command, print how many calls each RPC method took and
+    /// how long they ran, to stderr.
+    #[arg(long, global = true)]
+    timing: bool,
 
     #[command(subcommand)]
     command: Command,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable text.
+    Text,
+    /// Machine-readable JSON on stdout.
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    /// Markdown, for a wiki.
+    Md,
+    /// A standalone HTML page.
+    Html,
+    /// The `report` JSON result.
+    Json,
+}
+
 #[derive(Subcommand)]
 enum Command {
     /// Create a new multisig wallet and output your multisig info for sharing.
@@ -48,6 +174,20 @@ enum Command {
         /// Human-readable wallet label.
         #[arg(short, long, default_value = "default")]
         label: String,
+
+        /// Name of another participant; repeat once for each of them (N-1
+        /// times). Lets exchange-keys attribute blobs with `--from`.
+        #[arg(long = "participant")]
+        roster: Vec<String>,
+
+        /// Block height to start scanning from (skips history before it).
+        #[arg(long, conflicts_with = "restore_date")]
+        restore_height: Option<u64>,
+
+        /// Start scanning from about this date (YYYY-MM-DD), looked up on the
+        /// daemon.
+        #[arg(long)]
+        restore_date: Option<chrono::NaiveDate>,
     },
 
     /// Perform a key exchange round with peer multisig info strings.
@@ -56,191 +196,6738 @@ enum Command {
         #[arg(short, long, num_args = 1..)]
         info: Vec<String>,
 
-        /// Wallet password.
-        #[arg(short, long, default_value = "")]
-        password: String,
+        /// Expected fingerprint of each `--info` blob, in the same order.
+        /// Aborts before contacting the wallet if any of them differ.
+        #[arg(long = "expect-fingerprint", num_args = 1..)]
+        expect_fingerprint: Vec<String>,
+
+        /// Roster name of whoever sent each `--info` blob, in the same order.
+        #[arg(long = "from", num_args = 1..)]
+        from: Vec<String>,
+
+        /// Wallet password. Otherwise taken from
+        /// MONERO_MULTISIG_WALLET_PASSWORD or the keyring, else asked for on
+        /// a terminal; without one, the command fails.
+        #[arg(short, long)]
+        password: Option<String>,
+
+        /// If the wallet RPC rejects the round, send it halves of the peer
+        /// info until the blob it rejects is found.
+        #[arg(long)]
+        isolate_bad_blob: bool,
+
+        /// Keep going round after round in this process: after each partial
+        /// round, ask for the peers' next info at a prompt. Without --info
+        /// the first round is asked for too.
+        #[arg(long)]
+        interactive_rounds: bool,
     },
 
     /// Export multisig info for balance synchronization.
-    ExportInfo,
+    ExportInfo {
+        /// Sender name for the armor header (with --armor); left out when
+        /// not given.
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Export again even if the wallet hasn't changed since the last
+        /// export.
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Show what a multisig info blob is and how fresh it looks, without
+    /// importing it.
+    InspectInfo {
+        /// File holding the blob, armored or raw.
+        #[arg(long = "in")]
+        input: PathBuf,
+    },
 
     /// Import multisig info from co-signers before building transactions.
     ImportInfo {
         /// Multisig info strings from co-signers.
         #[arg(short, long, num_args = 1..)]
         info: Vec<String>,
+
+        /// Import blobs again even if they were imported before (for recovery).
+        #[arg(long)]
+        allow_reimport: bool,
+    },
+
+    /// Show the locally saved wallet state.
+    Status,
+
+    /// Decode a multisig tx set and show what it would pay, without signing.
+    Describe {
+        /// Hex-encoded (or armored) multisig transaction set data.
+        #[arg(short, long)]
+        tx_data: String,
+    },
+
+    /// Check a tx set against the manifest its builder wrote with
+    /// `build-tx --manifest-out`, and show any difference.
+    VerifyTxSet {
+        /// File holding the tx set, as for `sign-tx --tx-file`.
+        #[arg(long = "tx-in", value_name = "SET")]
+        tx_in: PathBuf,
+
+        /// The manifest file that came with it.
+        #[arg(long, value_name = "MANIFEST")]
+        manifest: PathBuf,
     },
 
     /// Check the wallet's current balance.
     Balance,
 
+    /// Show the wallet's transfer history.
+    History,
+
+    /// Show what the wallet knows about one transaction, e.g. a hash a
+    /// counterparty sent, looking in the `--account` account. For one the
+    /// wallet doesn't know, asks the daemon whether it is in the pool or the
+    /// chain.
+    ShowTx {
+        #[arg(long)]
+        tx_hash: String,
+    },
+
+    /// List payments to the account: those still in the pool (zero-conf)
+    /// first, then confirmed ones.
+    Incoming {
+        /// Only payments still in the pool.
+        #[arg(long)]
+        pool: bool,
+
+        /// Refresh the list every `--interval` until interrupted.
+        #[arg(long)]
+        watch: bool,
+
+        /// Time between refreshes with `--watch`, e.g. `5s`.
+        #[arg(long, default_value = "5s", value_parser = utils::parse_duration)]
+        interval: chrono::Duration,
+    },
+
+    /// Watch for incoming transfers and report each new one.
+    Monitor {
+        /// Time between polls, e.g. `30s` or `5m`.
+        #[arg(long, default_value = "30s", value_parser = utils::parse_duration)]
+        interval: chrono::Duration,
+
+        /// Exit once this many piconero have been received.
+        #[arg(long)]
+        until_amount: Option<u64>,
+
+        /// Confirmations a transfer needs before it counts toward
+        /// `--until-amount`.
+        #[arg(long, default_value_t = 0)]
+        min_confirmations: u64,
+
+        /// Also report transfers already in the history at startup.
+        #[arg(long)]
+        include_existing: bool,
+
+        /// POST each event as JSON to this URL.
+        #[arg(long)]
+        notify_url: Option<String>,
+
+        /// Serve Prometheus metrics at `http://ADDR/metrics`, e.g.
+        /// `127.0.0.1:9465`.
+        #[arg(long)]
+        metrics_addr: Option<std::net::SocketAddr>,
+    },
+
+    /// Manage lifecycle webhook notifications.
+    Notifications {
+        #[command(subcommand)]
+        action: NotificationsCommand,
+    },
+
+    /// Keep the wallet password in the OS keyring (builds with the
+    /// `keyring` feature only).
+    Credentials {
+        #[command(subcommand)]
+        action: CredentialsCommand,
+    },
+
+    /// Change settings in the `--config` file.
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+
+    /// List the outputs owned by the wallet.
+    ListOutputs {
+        /// Include spent outputs.
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Print the wallet's private view key (for setting up a watch-only copy).
+    ExportViewkey,
+
+    /// Take over a multisig wallet already set up with monero-wallet-cli and
+    /// open in the wallet RPC, without redoing key exchange.
+    Adopt {
+        /// Human-readable wallet label; asked for when not given.
+        #[arg(short, long)]
+        label: Option<String>,
+
+        /// Name of another participant; repeat once for each of them (N-1
+        /// times).
+        #[arg(long = "participant")]
+        roster: Vec<String>,
+
+        /// The wallet's file name on the wallet RPC, for the record.
+        #[arg(long, default_value = "wallet")]
+        filename: String,
+    },
+
+    /// Create a watch-only wallet that can see, but never spend, the funds.
+    CreateWatchWallet {
+        /// Private view key from `export-viewkey`.
+        #[arg(long)]
+        viewkey: String,
+
+        /// The multisig wallet's primary address.
+        #[arg(short, long)]
+        address: String,
+
+        /// Human-readable wallet label.
+        #[arg(short, long, default_value = "watch-only")]
+        label: String,
+
+        /// Wallet file name to create on the wallet RPC.
+        #[arg(long, default_value = "watch-only")]
+        filename: String,
+
+        /// Password for the new wallet file. Otherwise taken from
+        /// MONERO_MULTISIG_WALLET_PASSWORD or the keyring, else asked for on
+        /// a terminal; without one, the command fails.
+        #[arg(short, long)]
+        password: Option<String>,
+
+        /// Block height to start scanning from.
+        #[arg(long, default_value_t = 0)]
+        restore_height: u64,
+    },
+
+    /// Print the wallet's primary address, or the base address of
+    /// `--account`.
+    Address {
+        /// Also list the subaddresses of the account.
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// List the wallet's accounts with their balances.
+    ListAccounts,
+
+    /// List the wallet files the wallet RPC has, matched against the wallets
+    /// known in this data directory. Unknown ones are candidates for `adopt`.
+    ListWalletFiles,
+
+    /// Tag accounts and list them by tag, as the GUI wallet does.
+    Account {
+        #[command(subcommand)]
+        action: AccountCommand,
+    },
+
+    /// Add an account to the wallet, to keep funds for one purpose apart.
+    CreateAccount {
+        /// Label for the new account.
+        #[arg(short, long, default_value = "")]
+        label: String,
+    },
+
+    /// Confirm with your co-signers that everyone derived the same address.
+    VerifyAddress {
+        /// Address fingerprint read out by a peer (repeat once per peer).
+        #[arg(long = "peer-fingerprint")]
+        peer_fingerprint: Vec<String>,
+    },
+
+    /// Show the wallet RPC's version and which optional methods it has.
+    GetVersion,
+
+    /// Show what each priority costs right now, from the daemon's fee
+    /// estimate.
+    Fees,
+
+    /// Show daemon and wallet heights and whether both are in sync.
+    ChainInfo {
+        /// Exit with an error if the wallet is more than N blocks behind
+        /// the daemon.
+        #[arg(long, value_name = "N")]
+        fail_if_behind: Option<u64>,
+    },
+
+    /// Look for what makes commands slow. With no check named, run every
+    /// check.
+    Doctor {
+        /// Time cheap wallet RPC calls and compare them with the `latency`
+        /// budgets in the config file.
+        #[arg(long)]
+        latency: bool,
+    },
+
+    /// Rescan the blockchain for transactions belonging to this wallet.
+    Refresh {
+        /// Block height to start scanning from.
+        #[arg(long)]
+        start_height: Option<u64>,
+    },
+
     /// Build an unsigned transaction and output the multisig tx set.
     BuildTx {
-        /// Recipient address.
+        /// Recipient address. Repeat with `--amount` for several
+        /// destinations; they are paired in order.
+        #[arg(short, long, required_unless_present_any = ["uri", "interactive"])]
+        address: Vec<String>,
+
+        /// Amount in atomic units (piconero), one per `--address`.
+        #[arg(short = 'x', long, required_unless_present_any = ["uri", "interactive"])]
+        amount: Vec<u64>,
+
+        /// Ask for each destination's address (or address book name),
+        /// amount in XMR and note in turn, then review them with the fee
+        /// estimate before building.
+        #[arg(long, conflicts_with_all = ["address", "amount", "uri"])]
+        interactive: bool,
+
+        /// Pay a `monero:` payment request URI instead of `--address`. Its
+        /// description becomes the journal note unless `--note` is given;
+        /// `--amount` is only needed if it has no amount.
+        #[arg(long, conflicts_with = "address")]
+        uri: Option<String>,
+
+        /// Combine destinations that repeat an address instead of refusing
+        /// them.
+        #[arg(long)]
+        merge_duplicates: bool,
+
+        /// Spread more destinations than fit in one transaction over
+        /// several, with `transfer_split`.
+        #[arg(long)]
+        allow_split: bool,
+
+        /// Spend only from these subaddresses of `--account`, e.g. `1,2`.
+        #[arg(long, value_name = "I,J,K", value_delimiter = ',')]
+        subaddr_index: Vec<u32>,
+
+        /// Transaction priority (0=default, 1=low, 2=medium, 3=high).
+        #[arg(short, long, default_value_t = 0)]
+        priority: u32,
+
+        /// Note recorded in the local journal (what this payment is for).
         #[arg(short, long)]
-        address: String,
+        note: Option<String>,
+
+        /// Build even if the last import-info is old or the balance has
+        /// changed since.
+        #[arg(long)]
+        allow_stale_sync: bool,
+
+        /// Allow a transaction outside the configured policy limits after
+        /// confirming the exact numbers.
+        #[arg(long)]
+        override_policy: bool,
+
+        /// Allow destinations outside `allowed_destinations` after typing
+        /// each one back.
+        #[arg(long)]
+        ignore_allowlist: bool,
+
+        /// Skip the confirmation for spending most of the balance; required
+        /// to do so in JSON output mode.
+        #[arg(long)]
+        allow_sweep: bool,
+
+        /// Build the transaction to show its fee, inputs and change, then
+        /// discard it: no tx set is printed and the journal is untouched.
+        /// Fails like a real build would, so payouts can be checked first.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Also write the tx set as the binary file the Monero GUI and CLI
+        /// wallets load for multisig signing (they call it
+        /// `multisig_monero_tx`).
+        #[arg(long, value_name = "FILE", conflicts_with = "dry_run")]
+        export_monero_format: Option<PathBuf>,
+
+        /// Also write a manifest of the destinations, change, fee and note,
+        /// signed with the config's `manifest_secret`, for co-signers to check
+        /// the set against with `verify-tx-set` or `sign-tx --manifest`.
+        #[arg(long, value_name = "FILE", conflicts_with = "dry_run")]
+        manifest_out: Option<PathBuf>,
+    },
+
+    /// Make a `monero:` payment request URI for the account, so a payer
+    /// doesn't have to copy the address and amount by hand.
+    RequestPayment {
+        /// Amount in XMR, e.g. `1.5`.
+        #[arg(long)]
+        amount: Option<transaction::Amount>,
+
+        /// What the payment is for; shown by the payer's wallet.
+        #[arg(long)]
+        description: Option<String>,
+
+        /// Create a fresh subaddress with this label to receive the payment,
+        /// instead of using the account's base address.
+        #[arg(long)]
+        subaddress_label: Option<String>,
+    },
+
+    /// Build tx sets for every payout in a CSV or JSON batch file.
+    BuildBatch {
+        /// Batch file: rows of address, amount in XMR, and an optional note.
+        /// Read as JSON when it ends in `.json`, as CSV otherwise.
+        #[arg(short, long)]
+        file: PathBuf,
+
+        /// Most destinations per transaction.
+        #[arg(long, default_value_t = transaction::MAX_DESTINATIONS)]
+        max_per_tx: usize,
 
-        /// Amount in atomic units (piconero).
-        #[arg(short = 'x', long)]
-        amount: u64,
+        /// Directory for the tx set files, named after the batch file:
+        /// `payouts-1.txset`, `payouts-2.txset`, ...
+        #[arg(long, default_value = ".")]
+        out_dir: PathBuf,
 
         /// Transaction priority (0=default, 1=low, 2=medium, 3=high).
         #[arg(short, long, default_value_t = 0)]
         priority: u32,
+
+        /// Build even if the last import-info is old or the balance has
+        /// changed since.
+        #[arg(long)]
+        allow_stale_sync: bool,
+
+        /// Skip the confirmation for paying out most of the balance.
+        #[arg(long)]
+        allow_sweep: bool,
     },
 
     /// Apply this participant's signature to a multisig transaction set.
     SignTx {
         /// Hex-encoded multisig transaction set data.
+        #[arg(short, long, required_unless_present = "tx_file")]
+        tx_data: Option<String>,
+
+        /// Read the tx set from a file instead: hex or armored text, or a
+        /// `multisig_monero_tx` file saved by the Monero GUI or CLI wallet.
+        #[arg(long, conflicts_with = "tx_data")]
+        tx_file: Option<PathBuf>,
+
+        /// Refuse to sign unless the set matches this manifest from its
+        /// builder; see `verify-tx-set`.
+        #[arg(long, value_name = "MANIFEST")]
+        manifest: Option<PathBuf>,
+
+        /// Allow a transaction outside the configured policy limits after
+        /// confirming the exact numbers.
+        #[arg(long)]
+        override_policy: bool,
+
+        /// Allow destinations outside `allowed_destinations` after typing
+        /// each one back.
+        #[arg(long)]
+        ignore_allowlist: bool,
+
+        /// Skip the confirmation for spending most of the balance; required
+        /// to do so in JSON output mode.
+        #[arg(long)]
+        allow_sweep: bool,
+    },
+
+    /// Review and sign every tx set in a directory with one confirmation.
+    SignBatch {
+        /// Directory of `*.txset` / `*.asc` files. Each signed set is written
+        /// next to its input as `<file>.signed`.
+        #[arg(long)]
+        in_dir: PathBuf,
+
+        /// Sign without asking for confirmation.
         #[arg(short, long)]
-        tx_data: String,
+        yes: bool,
+
+        /// Skip the confirmation for spending most of the balance.
+        #[arg(long)]
+        allow_sweep: bool,
     },
 
     /// Submit a fully signed multisig transaction to the network.
     SubmitTx {
         /// Hex-encoded fully signed transaction data.
+        #[arg(short, long, required_unless_present = "tx_file")]
+        tx_data: Option<String>,
+
+        /// Read the tx set from a file instead, as for `sign-tx`.
+        #[arg(long, conflicts_with = "tx_data")]
+        tx_file: Option<PathBuf>,
+
+        /// Submit even if the journal marks this set as discarded.
+        #[arg(long)]
+        force: bool,
+
+        /// Don't broadcast; write the fully signed set to `--out` for a later
+        /// `relay`.
+        #[arg(long, requires = "out")]
+        no_submit: bool,
+
+        /// File to write the held transaction to with `--no-submit`.
+        #[arg(long)]
+        out: Option<PathBuf>,
+
+        /// Don't wait for the daemon to see the submitted transaction.
+        #[arg(long)]
+        no_verify_broadcast: bool,
+
+        /// Submit without asking for confirmation. A set that can't be
+        /// described still needs its hash typed back.
         #[arg(short, long)]
-        tx_data: String,
+        yes: bool,
+
+        /// If the daemon wants a higher fee, rebuild the same payment at the
+        /// next priority without asking. The new set needs signing again.
+        #[arg(long)]
+        auto_bump: bool,
     },
-}
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
+    /// Prove this wallet sent a transaction, e.g. to a payee who disputes
+    /// being paid. Unlike a tx proof, checking it needs nothing from them.
+    ProveSpend {
+        #[arg(long)]
+        tx_hash: String,
 
-    let cli = Cli::parse();
-    let config = Config::load(cli.config.as_ref())?;
+        /// Text the proof commits to, such as an invoice number.
+        #[arg(long, default_value = "")]
+        message: String,
 
-    let mut daemon = config.daemon.clone();
-    daemon.host = cli.daemon_host;
-    daemon.port = cli.daemon_port;
+        /// File to write the proof to; printed when not given.
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
 
-    let rpc = RpcClient::new(&daemon);
+    /// Check a proof made by `prove-spend`. Works from any wallet, for any
+    /// transaction the daemon knows.
+    CheckSpendProof {
+        #[arg(long)]
+        tx_hash: String,
 
-    match cli.command {
-        Command::CreateWallet {
-            threshold,
-            participants,
-            label,
-        } => {
-            let params = wallet::MultisigParams::new(threshold, participants, label)?;
-            println!(
-                "Creating {}-of-{} multisig wallet \"{}\"...",
-                params.threshold, params.total, params.label
-            );
+        /// Proof file written by `prove-spend`.
+        #[arg(long)]
+        proof: PathBuf,
+    },
 
-            let info = wallet::prepare_multisig(&rpc).await?;
+    /// Broadcast a transaction held back with `submit-tx --no-submit`.
+    Relay {
+        /// File written by `submit-tx --no-submit`. A raw signed transaction
+        /// is sent through the daemon instead of the wallet.
+        #[arg(long = "in")]
+        input: PathBuf,
 
-            let state = wallet::WalletState::Created {
-                wallet_path: config.data_dir.join("wallet"),
-                params: wallet::SerializableParams::from(&params),
-            };
-            wallet::save_wallet_state(&config.data_dir, &state)?;
+        /// Relay even if the journal marks this set as discarded.
+        #[arg(long)]
+        force: bool,
+    },
 
-            println!("\nYour multisig info (share with all other participants):\n");
-            println!("{info}");
-        }
+    /// Sign a normal wallet's `unsigned_monero_tx` with the offline wallet
+    /// open in the wallet RPC, for the non-multisig cold-signing workflow.
+    #[cfg(feature = "cold-sign")]
+    ColdSign {
+        /// Hex of the unsigned_monero_tx.
+        #[arg(short, long, required_unless_present = "tx_file")]
+        tx_data: Option<String>,
 
-        Command::ExchangeKeys { info, password } => {
-            let state = wallet::load_wallet_state(&config.data_dir)
-                .context("load wallet state")?;
+        /// Read it from a file instead: the one the Monero wallet saved, or
+        /// its hex.
+        #[arg(long, conflicts_with = "tx_data")]
+        tx_file: Option<PathBuf>,
 
-            let threshold = match &state {
-                wallet::WalletState::Created { params, .. }
-                | wallet::WalletState::KeyExchangeInProgress { params, .. } => params.threshold,
-                wallet::WalletState::Ready { .. } => {
-                    anyhow::bail!("wallet is already fully set up");
-                }
-            };
+        /// Write the signed_monero_tx to this file, as the Monero wallet
+        /// saves it, instead of printing its hex.
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
 
-            println!("Performing key exchange round...");
-            let result = wallet::exchange_keys(&rpc, &info, threshold, &password).await?;
+    /// Broadcast a `signed_monero_tx` through the view-only wallet that
+    /// built the unsigned one.
+    #[cfg(feature = "cold-sign")]
+    ColdSubmit {
+        /// Hex of the signed_monero_tx.
+        #[arg(short, long, required_unless_present = "tx_file")]
+        tx_data: Option<String>,
 
-            match result {
-                wallet::KeyExchangeResult::Partial { next_info } => {
-                    println!("\nKey exchange round complete. More rounds needed.");
-                    println!("Share this info with peers for the next round:\n");
-                    println!("{next_info}");
-                }
-                wallet::KeyExchangeResult::Complete { address } => {
-                    let state = wallet::WalletState::Ready {
-                        wallet_path: config.data_dir.join("wallet"),
-                        address: address.clone(),
-                        params: match state {
-                            wallet::WalletState::Created { params, .. }
-                            | wallet::WalletState::KeyExchangeInProgress { params, .. } => params,
-                            _ => unreachable!(),
-                        },
-                    };
-                    wallet::save_wallet_state(&config.data_dir, &state)?;
+        /// Read it from a file instead, as for `cold-sign`.
+        #[arg(long, conflicts_with = "tx_data")]
+        tx_file: Option<PathBuf>,
+    },
 
-                    println!("\nMultisig wallet is ready!");
-                    println!("Address: {address}");
-                }
-            }
-        }
+    /// Abandon a pending transaction and release the outputs it reserved.
+    DiscardTx {
+        /// Tx hash printed by build-tx or sign-tx.
+        #[arg(long)]
+        tx_hash: String,
+    },
 
-        Command::ExportInfo => {
-            let info = transaction::export_multisig_info(&rpc).await?;
-            println!("Multisig info (share with co-signers):\n");
-            println!("{info}");
-        }
+    /// List multisig transactions that are built but not yet submitted.
+    Pending {
+        #[command(subcommand)]
+        action: Option<PendingCommand>,
+    },
 
-        Command::ImportInfo { info } => {
-            transaction::import_multisig_info(&rpc, &info).await?;
-            println!("Multisig info imported successfully. Balance is now synchronized.");
-        }
+    /// List transactions recorded in the local journal.
+    Journal {
+        #[command(flatten)]
+        filter: JournalArgs,
 
-        Command::Balance => {
-            let balance = transaction::get_balance(&rpc).await?;
-            println!("Balance:          {} XMR", transaction::format_xmr(balance.balance));
-            println!("Unlocked balance: {} XMR", transaction::format_xmr(balance.unlocked_balance));
-        }
+        #[command(subcommand)]
+        action: Option<JournalCommand>,
+    },
 
-        Command::BuildTx {
-            address,
-            amount,
-            priority,
-        } => {
-            let priority = match priority {
-                1 => transaction::Priority::Low,
-                2 => transaction::Priority::Medium,
-                3 => transaction::Priority::High,
-                _ => transaction::Priority::Default,
-            };
+    /// Read or change a transaction's note, kept both in the journal and in
+    /// the wallet file.
+    Note {
+        #[command(subcommand)]
+        action: NoteCommand,
+    },
 
-            let destinations = vec![transaction::Destination { address, amount }];
+    /// Write one document describing the wallet for an audit: its setup,
+    /// every journaled transaction with amounts and notes, totals, and the
+    /// balance checked against the history.
+    Report {
+        /// Only transactions on or after this date (YYYY-MM-DD).
+        #[arg(long)]
+        from: Option<chrono::NaiveDate>,
 
-            println!("Building unsigned multisig transaction...");
-            let unsigned = transaction::build_unsigned_tx(&rpc, &destinations, priority).await?;
+        /// Only transactions on or before this date (YYYY-MM-DD).
+        #[arg(long)]
+        to: Option<chrono::NaiveDate>,
 
-            println!("\nTransaction built successfully:");
-            println!("  Hash: {}", unsigned.tx_hash);
-            println!("  Fee:  {} XMR", transaction::format_xmr(unsigned.fee));
-            println!("\nMultisig tx set (share with co-signers):\n");
-            println!("{}", unsigned.tx_data_hex);
-        }
+        /// Document format; JSON with `--output json`, Markdown otherwise.
+        #[arg(long, value_enum)]
+        format: Option<ReportFormat>,
 
-        Command::SignTx { tx_data } => {
-            println!("Signing multisig transaction...");
-            let signed = transaction::sign_multisig_tx(&rpc, &tx_data).await?;
+        /// File to write the report to; printed when not given.
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
 
-            println!("\nSignature applied:");
-            println!("  Hash: {}", signed.tx_hash);
-            println!("\nUpdated tx set (share with remaining co-signers or submit):\n");
-            println!("{}", signed.tx_data_hex);
-        }
+    /// Run a 2-of-2 buyer/seller escrow: set it up, then pay the seller or
+    /// refund the buyer.
+    Escrow {
+        #[command(subcommand)]
+        action: EscrowCommand,
+    },
 
-        Command::SubmitTx { tx_data } => {
-            println!("Submitting fully signed transaction...");
-            let result = transaction::submit_multisig_tx(&rpc, &tx_data).await?;
+    /// Maintain the local state file.
+    State {
+        #[command(subcommand)]
+        action: StateCommand,
+    },
 
-            println!("\nTransaction submitted successfully!");
-            println!("  Hash: {}", result.tx_hash);
-        }
-    }
+    /// Compare the state file with the wallet RPC's multisig setup and fix
+    /// the state file to match, e.g. after an interrupted key exchange.
+    RepairState {
+        /// Record an interrupted key exchange round as having gone through
+        /// when the wallet RPC can't tell.
+        #[arg(long)]
+        round_done: bool,
+    },
 
-    Ok(())
+    /// Abandon this wallet so the group can start setup over. The state file
+    /// and journal are moved to `data_dir/trash/`.
+    Reset {
+        /// Also close the wallet in the wallet RPC and move its wallet files
+        /// to the trash, if they are on this machine.
+        #[arg(long)]
+        delete_wallet_file: bool,
+
+        /// Reset a ready wallet even though it still holds funds.
+        #[arg(long = "i-understand-funds-will-be-inaccessible")]
+        funds_inaccessible: bool,
+    },
+
+    /// List the roster and which key exchange rounds each participant has
+    /// contributed to.
+    Participants,
+
+    /// Share the address book, roster, destination allowlist and group
+    /// settings with a co-signer's new machine as one signed file.
+    Bundle {
+        #[command(subcommand)]
+        action: BundleCommand,
+    },
+
+    /// Credit a co-signer with the signature on a tx set they sent back, for
+    /// the `pending` checklist. Advisory: tx sets don't say who signed.
+    RecordSignature {
+        /// Roster name of the co-signer.
+        #[arg(long)]
+        from: String,
+
+        /// The tx set as they sent it.
+        #[arg(short, long)]
+        tx_data: String,
+    },
+
+    /// Work with `data_dir/audit.log`, the hash-chained record of every
+    /// state-changing command.
+    Audit {
+        #[command(subcommand)]
+        action: AuditCommand,
+    },
+
+    /// Retire a finished wallet: check it is empty, close it, and replace
+    /// its state and journal with a record in `data_dir/archive/`.
+    DeleteWallet {
+        /// The wallet's label, as a check that the right wallet is deleted.
+        #[arg(long)]
+        label: String,
+
+        /// Move the wallet files into the archive instead of deleting them.
+        #[arg(long)]
+        keep_files: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AccountCommand {
+    /// Tag `--account` with a tag, replacing the tag it had. Given a tag,
+    /// `--account` retags every account that has it.
+    Tag {
+        #[arg(long)]
+        tag: String,
+
+        /// Description to show with the tag.
+        #[arg(long)]
+        description: Option<String>,
+    },
+
+    /// Remove the tag of `--account`.
+    Untag,
+
+    /// List the wallet's accounts with their tags.
+    List {
+        /// Only the accounts with this tag.
+        #[arg(long, value_name = "TAG")]
+        by_tag: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum EscrowCommand {
+    /// Create the 2-of-2 wallet for one side of the deal and print the
+    /// invite to send the other side.
+    Init {
+        /// Which side you are on.
+        #[arg(long)]
+        role: EscrowRole,
+
+        /// The buyer's address for a refund, agreed by both sides; the
+        /// seller enters the same one.
+        #[arg(long)]
+        refund_address: String,
+
+        /// Human-readable wallet label.
+        #[arg(short, long, default_value = "escrow")]
+        label: String,
+
+        /// Block height to start scanning from (skips history before it).
+        #[arg(long)]
+        restore_height: Option<u64>,
+    },
+
+    /// Finish setup with the other side's invite: its role and refund
+    /// address are checked, then the single key exchange round runs.
+    Join {
+        /// The invite `escrow init` printed for the other side.
+        #[arg(long)]
+        peer_info: String,
+
+        /// Wallet password. Otherwise taken from
+        /// MONERO_MULTISIG_WALLET_PASSWORD or the keyring, else asked for on
+        /// a terminal; without one, the command fails.
+        #[arg(short, long)]
+        password: Option<String>,
+    },
+
+    /// Build the payout to the seller, for the other side to sign.
+    Release {
+        /// The seller's address.
+        #[arg(long)]
+        to: String,
+
+        /// Amount in XMR, e.g. `1.5`.
+        #[arg(long)]
+        amount: transaction::Amount,
+
+        /// Note recorded in the local journal.
+        #[arg(short, long)]
+        note: Option<String>,
+
+        /// Build even if the last import-info is old or the balance has
+        /// changed since.
+        #[arg(long)]
+        allow_stale_sync: bool,
+
+        /// Skip the confirmation for spending most of the balance.
+        #[arg(long)]
+        allow_sweep: bool,
+    },
+
+    /// Build the refund to the buyer's registered address, for the other
+    /// side to sign.
+    Refund {
+        /// Amount in XMR, e.g. `1.5`.
+        #[arg(long)]
+        amount: transaction::Amount,
+
+        /// Refund somewhere other than the registered address. Needs
+        /// `--override`, and the other side has to agree by signing.
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Allow `--to` to differ from the registered refund address after
+        /// typing it back.
+        #[arg(long = "override")]
+        override_address: bool,
+
+        /// Note recorded in the local journal.
+        #[arg(short, long)]
+        note: Option<String>,
+
+        /// Build even if the last import-info is old or the balance has
+        /// changed since.
+        #[arg(long)]
+        allow_stale_sync: bool,
+
+        /// Skip the confirmation for spending most of the balance.
+        #[arg(long)]
+        allow_sweep: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum StateCommand {
+    /// Show the state file for review and re-sign it, e.g. after an
+    /// upgrade from a version without checksums or a deliberate edit.
+    Rehash,
+}
+
+#[derive(Subcommand)]
+enum AuditCommand {
+    /// Walk the hash chain and report the first broken link.
+    Verify,
+}
+
+#[derive(Subcommand)]
+enum BundleCommand {
+    /// Write this machine's share of the group setup, signed with
+    /// `manifest_secret`. Secrets and machine-specific settings are left
+    /// out.
+    Export {
+        #[arg(long)]
+        out: PathBuf,
+
+        /// Your name in the group, so co-signers' rosters can list you.
+        /// Needed when this wallet has a roster.
+        #[arg(long, value_name = "NAME")]
+        from: Option<String>,
+    },
+
+    /// Check a bundle's signature, show how it differs from this machine,
+    /// and apply it. Nothing here is deleted.
+    Import {
+        file: PathBuf,
+
+        /// Your name in the bundle's roster. Worked out from this wallet's
+        /// roster when it has one.
+        #[arg(long = "as", value_name = "NAME")]
+        me: Option<String>,
+
+        /// Settle every conflict this way instead of asking about each.
+        #[arg(long, value_enum)]
+        conflicts: Option<ConflictChoice>,
+
+        /// Apply without asking for confirmation. Conflicts are still asked
+        /// about unless `--conflicts` settles them.
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+/// How `bundle import` settles a name saved here with another address, or
+/// a roster naming other people.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ConflictChoice {
+    KeepLocal,
+    TakeRemote,
+}
+
+#[derive(Subcommand)]
+enum CredentialsCommand {
+    /// Save the wallet password, typed without echo.
+    Store {
+        /// Wallet label to store it under; defaults to this wallet's.
+        #[arg(long)]
+        label: Option<String>,
+    },
+
+    /// Remove the stored wallet password.
+    Clear {
+        /// Wallet label it was stored under; defaults to this wallet's.
+        #[arg(long)]
+        label: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Write one setting into the file, leaving the rest as it is.
+    Set { setting: Setting, value: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Setting {
+    /// The wallet used when `--wallet` is left out.
+    DefaultWallet,
+}
+
+#[derive(Subcommand)]
+enum NotificationsCommand {
+    /// Send a `ping` event to the configured webhook.
+    Test,
+}
+
+#[derive(Subcommand)]
+enum PendingCommand {
+    /// Print a pending transaction's latest tx set again, armored.
+    Show {
+        #[arg(long)]
+        tx_hash: String,
+    },
+
+    /// Mark stale pending entries abandoned and drop stored tx sets of
+    /// finished ones.
+    Prune {
+        /// Age of the last action, e.g. `30d` or `12h`.
+        #[arg(long, value_parser = utils::parse_duration)]
+        older_than: chrono::Duration,
+    },
+}
+
+#[derive(Subcommand)]
+enum NoteCommand {
+    /// Set the note in the journal entry with the tx hash, if there is
+    /// one, and in the wallet.
+    Set {
+        #[arg(long)]
+        tx_hash: String,
+
+        #[arg(long)]
+        note: String,
+    },
+
+    /// Show the journal's and the wallet's note for a transaction.
+    Show {
+        #[arg(long)]
+        tx_hash: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum JournalCommand {
+    /// Dump matching journal entries.
+    Export {
+        /// Write CSV instead of JSON.
+        #[arg(long)]
+        csv: bool,
+
+        #[command(flatten)]
+        filter: JournalArgs,
+    },
+}
+
+#[derive(clap::Args, Clone, Default)]
+struct JournalArgs {
+    /// Only entries created on or after this date (YYYY-MM-DD).
+    #[arg(long)]
+    since: Option<chrono::NaiveDate>,
+
+    /// Only entries created on or before this date (YYYY-MM-DD).
+    #[arg(long)]
+    until: Option<chrono::NaiveDate>,
+
+    /// Only entries paying this address.
+    #[arg(long)]
+    address: Option<String>,
+
+    /// Only entries whose note contains this text.
+    #[arg(long)]
+    note: Option<String>,
+}
+
+impl From<JournalArgs> for JournalFilter {
+    fn from(args: JournalArgs) -> Self {
+        JournalFilter {
+            since: args.since,
+            until: args.until,
+            address: args.address,
+            note: args.note,
+        }
+    }
+}
+
+impl Command {
+    /// The subcommand name as typed on the command line.
+    fn name(&self) -> &'static str {
+        match self {
+            Command::CreateWallet { .. } => "create-wallet",
+            Command::ExchangeKeys { .. } => "exchange-keys",
+            Command::ExportInfo { .. } => "export-info",
+            Command::InspectInfo { .. } => "inspect-info",
+            Command::ImportInfo { .. } => "import-info",
+            Command::Status => "status",
+            Command::Describe { .. } => "describe",
+            Command::VerifyTxSet { .. } => "verify-tx-set",
+            Command::Balance => "balance",
+            Command::History => "history",
+            Command::ShowTx { .. } => "show-tx",
+            Command::Incoming { .. } => "incoming",
+            Command::Monitor { .. } => "monitor",
+            Command::Notifications { .. } => "notifications",
+            Command::Credentials { .. } => "credentials",
+            Command::Config { .. } => "config",
+            Command::ListOutputs { .. } => "list-outputs",
+            Command::ExportViewkey => "export-viewkey",
+            Command::CreateWatchWallet { .. } => "create-watch-wallet",
+            Command::Adopt { .. } => "adopt",
+            Command::Address { .. } => "address",
+            Command::ListAccounts => "list-accounts",
+            Command::ListWalletFiles => "list-wallet-files",
+            Command::Account { .. } => "account",
+            Command::CreateAccount { .. } => "create-account",
+            Command::VerifyAddress { .. } => "verify-address",
+            Command::GetVersion => "get-version",
+            Command::Fees => "fees",
+            Command::ChainInfo { .. } => "chain-info",
+            Command::Doctor { .. } => "doctor",
+            Command::Refresh { .. } => "refresh",
+            Command::BuildTx { .. } => "build-tx",
+            Command::RequestPayment { .. } => "request-payment",
+            Command::SignTx { .. } => "sign-tx",
+            Command::BuildBatch { .. } => "build-batch",
+            Command::SignBatch { .. } => "sign-batch",
+            Command::SubmitTx { .. } => "submit-tx",
+            Command::Relay { .. } => "relay",
+            #[cfg(feature = "cold-sign")]
+            Command::ColdSign { .. } => "cold-sign",
+            #[cfg(feature = "cold-sign")]
+            Command::ColdSubmit { .. } => "cold-submit",
+            Command::ProveSpend { .. } => "prove-spend",
+            Command::CheckSpendProof { .. } => "check-spend-proof",
+            Command::DiscardTx { .. } => "discard-tx",
+            Command::Pending { .. } => "pending",
+            Command::Journal { .. } => "journal",
+            Command::Note { .. } => "note",
+            Command::Report { .. } => "report",
+            Command::Escrow { .. } => "escrow",
+            Command::State { .. } => "state",
+            Command::RepairState { .. } => "repair-state",
+            Command::Reset { .. } => "reset",
+            Command::DeleteWallet { .. } => "delete-wallet",
+            Command::Participants => "participants",
+            Command::Bundle { .. } => "bundle",
+            Command::RecordSignature { .. } => "record-signature",
+            Command::Audit { .. } => "audit",
+        }
+    }
+}
+
+impl Command {
+    /// Whether the command works with the wallet the state file describes
+    /// through the wallet RPC, so the open wallet must be checked first.
+    fn uses_wallet(&self) -> bool {
+        // Cold signing runs against a normal wallet, not the multisig one.
+        #[cfg(feature = "cold-sign")]
+        if matches!(self, Command::ColdSign { .. } | Command::ColdSubmit { .. }) {
+            return false;
+        }
+        !matches!(
+            self,
+            Command::CreateWallet { .. }
+                | Command::Escrow {
+                    action: EscrowCommand::Init { .. }
+                }
+                | Command::CreateWatchWallet { .. }
+                | Command::Adopt { .. }
+                | Command::Status
+                | Command::InspectInfo { .. }
+                | Command::GetVersion
+                | Command::ListWalletFiles
+                | Command::Fees
+                | Command::ChainInfo { .. }
+                | Command::Doctor { .. }
+                | Command::Notifications { .. }
+                | Command::Credentials { .. }
+                | Command::Relay { .. }
+                | Command::CheckSpendProof { .. }
+                | Command::DiscardTx { .. }
+                | Command::Pending { .. }
+                | Command::Journal { .. }
+                | Command::State { .. }
+                | Command::RepairState { .. }
+                | Command::Reset { .. }
+                | Command::Participants
+                | Command::Audit { .. }
+        )
+    }
+
+    /// How the command locks the data directory, or `None` for `monitor`,
+    /// which runs indefinitely and only appends to the notification spool,
+    /// and `incoming --watch`, which runs indefinitely and writes nothing.
+    fn lock_mode(&self) -> Option<LockMode> {
+        match self {
+            Command::Monitor { .. } | Command::Incoming { watch: true, .. } => None,
+            Command::Status
+            | Command::Describe { .. }
+            | Command::VerifyTxSet { .. }
+            | Command::InspectInfo { .. }
+            | Command::Balance
+            | Command::History
+            | Command::ShowTx { .. }
+            | Command::Incoming { .. }
+            | Command::ListOutputs { .. }
+            | Command::ExportViewkey
+            | Command::Address { .. }
+            | Command::ListAccounts
+            | Command::Account {
+                action: AccountCommand::List { .. },
+            }
+            | Command::ListWalletFiles
+            | Command::GetVersion
+            | Command::Fees
+            | Command::ChainInfo { .. }
+            | Command::Doctor { .. }
+            | Command::Participants
+            | Command::Report { .. }
+            | Command::Note {
+                action: NoteCommand::Show { .. },
+            }
+            | Command::Bundle {
+                action: BundleCommand::Export { .. },
+            }
+            | Command::Audit { .. }
+            | Command::Credentials { .. }
+            | Command::ProveSpend { .. }
+            | Command::CheckSpendProof { .. }
+            | Command::BuildTx { dry_run: true, .. }
+            | Command::Journal {
+                action: None | Some(JournalCommand::Export { .. }),
+                ..
+            }
+            | Command::Pending {
+                action: None | Some(PendingCommand::Show { .. }),
+            } => Some(LockMode::Shared),
+            #[cfg(feature = "cold-sign")]
+            Command::ColdSign { .. } | Command::ColdSubmit { .. } => Some(LockMode::Shared),
+            _ => Some(LockMode::Exclusive),
+        }
+    }
+
+    /// Whether the command can work on several accounts at once, so
+    /// `--account` may name a tag that more than one account has.
+    fn several_accounts(&self) -> bool {
+        matches!(
+            self,
+            Command::Balance
+                | Command::Account {
+                    action: AccountCommand::Tag { .. } | AccountCommand::Untag,
+                }
+        )
+    }
+
+    /// Whether the command can run on an air-gapped machine.
+    fn allowed_offline(&self) -> bool {
+        #[cfg(feature = "cold-sign")]
+        if matches!(self, Command::ColdSign { .. }) {
+            return true;
+        }
+        matches!(
+            self,
+            Command::SignTx { .. }
+                | Command::SignBatch { .. }
+                | Command::ExportInfo { .. }
+                | Command::InspectInfo { .. }
+                | Command::Address { .. }
+                | Command::ListAccounts
+                | Command::Account { .. }
+                | Command::Describe { .. }
+                | Command::VerifyTxSet { .. }
+                | Command::Status
+                | Command::GetVersion
+                | Command::SubmitTx {
+                    no_submit: true,
+                    ..
+                }
+                | Command::Pending { .. }
+                | Command::Journal { .. }
+                | Command::State { .. }
+                | Command::Participants
+                | Command::RecordSignature { .. }
+                | Command::Audit { .. }
+                | Command::Credentials { .. }
+        )
+    }
+}
+
+/// The command named alongside `--schema`, if given. Parsed leniently so the
+/// command's required arguments can be left out.
+fn schema_request() -> Option<String> {
+    use clap::CommandFactory;
+    let matches = Cli::command().ignore_errors(true).try_get_matches().ok()?;
+    let (name, sub) = matches.subcommand()?;
+    let requested = matches.get_flag("schema") || sub.get_flag("schema");
+    requested.then(|| name.to_string())
+}
+
+/// Install the human-readable stderr layer and, when requested, a second
+/// JSON-lines layer writing to a daily-rotated log file.
+fn init_tracing(log_file: Option<&PathBuf>, retention: usize) -> Result<()> {
+    let console = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_filter(EnvFilter::from_default_env());
+
+    let file = match log_file {
+        Some(path) => {
+            let dir = match path.parent() {
+                Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+                _ => PathBuf::from("."),
+            };
+            let prefix = path
+                .file_name()
+                .context("--log-file must name a file, not a directory")?
+                .to_string_lossy()
+                .into_owned();
+            let appender = RollingFileAppender::builder()
+                .rotation(Rotation::DAILY)
+                .filename_prefix(prefix)
+                .max_log_files(retention.max(1))
+                .build(&dir)
+                .with_context(|| format!("failed to open log file in {}", dir.display()))?;
+
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_current_span(true)
+                    .with_span_list(false)
+                    .with_writer(appender)
+                    .with_filter(
+                        Targets::new()
+                            .with_target("monero_multisig", LevelFilter::DEBUG)
+                            .with_default(LevelFilter::WARN),
+                    ),
+            )
+        }
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(console)
+        .with(file)
+        .init();
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<std::process::ExitCode> {
+    if let Some(command) = schema_request() {
+        let schema =
+            output::schema(&command).with_context(|| format!("{command} has no JSON output"))?;
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        return Ok(std::process::ExitCode::SUCCESS);
+    }
+    let cli = Cli::parse();
+    messages::select(cli.lang.as_deref());
+    // Needs no wallet, so it runs before one is chosen: choosing could fail
+    // for lack of the default it sets.
+    if let Command::Config {
+        action: ConfigCommand::Set { setting, value },
+    } = &cli.command
+    {
+        let path = cli
+            .config
+            .as_ref()
+            .context("config set writes to the file given with --config")?;
+        match setting {
+            Setting::DefaultWallet => {
+                config::save_default_wallet(path, value)?;
+                println!("Default wallet set to \"{value}\" in {}.", path.display());
+            }
+        }
+        return Ok(std::process::ExitCode::SUCCESS);
+    }
+    let mut config = Config::load(cli.config.as_ref(), cli.data_dir.as_deref())?;
+    if let Some(name) = config.select_wallet(cli.wallet.as_deref())? {
+        eprintln!("Using wallet \"{name}\", the only one in this data directory.");
+    }
+
+    let log_file = cli.log_file.as_ref().or(config.log_file.as_ref());
+    init_tracing(log_file, config.log_retention)?;
+    interrupt::install();
+
+    let mut daemons = config.daemon.clone();
+    if cli.daemon_host.is_some() || cli.daemon_port.is_some() {
+        let mut daemon = daemons.swap_remove(0);
+        if let Some(host) = cli.daemon_host {
+            daemon.host = host;
+        }
+        if let Some(port) = cli.daemon_port {
+            daemon.port = port;
+        }
+        daemon.parse_url()?;
+        daemons = vec![daemon];
+    }
+
+    if let Command::Account {
+        action: action @ (AccountCommand::Tag { .. } | AccountCommand::Untag),
+    } = &cli.command
+    {
+        if cli.account.is_none() {
+            let name = match action {
+                AccountCommand::Tag { .. } => "tag",
+                _ => "untag",
+            };
+            anyhow::bail!("account {name} needs --account to say which account(s)");
+        }
+    }
+
+    if cli.offline && !cli.command.allowed_offline() {
+        anyhow::bail!(
+            "{} is not available in --offline mode (allowed: sign-tx, export-info, describe, status)",
+            cli.command.name()
+        );
+    }
+
+    // Held until main returns; the OS drops it if the process dies first.
+    let _lock = match cli.command.lock_mode() {
+        Some(mode) => Some(lock::acquire(
+            &config.data_dir,
+            mode,
+            std::time::Duration::from_secs(cli.lock_timeout),
+            cli.command.name(),
+        )?),
+        None => None,
+    };
+
+    let progress = cli.output == OutputFormat::Text && std::io::stderr().is_terminal();
+    let wallet_rpc = config
+        .wallet_rpc
+        .clone()
+        .unwrap_or_else(|| daemons[0].clone());
+    let slow_rpc = std::time::Duration::from_millis(config.slow_rpc_ms);
+    let mut rpc = RpcClient::new(&wallet_rpc)
+        .with_progress(progress)
+        .with_slow_threshold(slow_rpc)
+        .with_busy_wait(std::time::Duration::from_secs(config.busy_wait_secs));
+    if !cli.skip_version_check {
+        rpc = rpc.require_version(config::MIN_WALLET_RPC_VERSION);
+    }
+    let mut daemon_rpc = RpcClient::failover(&daemons)
+        .with_progress(progress)
+        .with_slow_threshold(slow_rpc);
+    if cli.offline {
+        rpc = rpc.offline();
+        daemon_rpc = daemon_rpc.offline();
+    }
+
+    let label = match &cli.command {
+        Command::CreateWallet { label, .. }
+        | Command::Escrow {
+            action: EscrowCommand::Init { label, .. },
+        }
+        | Command::Adopt {
+            label: Some(label), ..
+        } => label.clone(),
+        _ => wallet::load_wallet_state(&config.data_dir)
+            .map(|state| state.label().to_string())
+            .unwrap_or_default(),
+    };
+    let span = tracing::info_span!("command", command = cli.command.name(), wallet = %label);
+
+    // Commands that may write get an audit log line, written under the
+    // exclusive lock still held by `_lock`.
+    let command_name = cli.command.name();
+    let data_dir = config.data_dir.clone();
+    let timing = cli.timing.then(|| Style::detect(cli.no_color));
+    // `monitor` runs until interrupted, so there's no "after" to advise on.
+    let guide = cli.output == OutputFormat::Text && !matches!(cli.command, Command::Monitor { .. });
+    // Rescans are slow by nature, and `doctor` already says why.
+    let latency_hint = (!matches!(
+        cli.command,
+        Command::Monitor { .. } | Command::Refresh { .. } | Command::Doctor { .. }
+    ))
+    .then(|| config.latency.clone());
+    let audit_before = (cli.command.lock_mode() == Some(LockMode::Exclusive))
+        .then(|| audit::Snapshot::capture(&data_dir));
+
+    let (result, summary) = async move {
+        tracing::info!("command started");
+        let selector = cli
+            .account
+            .clone()
+            .unwrap_or(wallet::AccountSelector::Index(0));
+        let accounts = match selector.resolve(&rpc).await {
+            Ok(accounts) => accounts,
+            Err(e) => return (Err(e.into()), None),
+        };
+        let account = if cli.command.several_accounts() {
+            accounts[0]
+        } else {
+            match wallet::single_account(&selector, &accounts) {
+                Ok(account) => account,
+                Err(e) => return (Err(e.into()), None),
+            }
+        };
+        let session = Session {
+            config: &config,
+            config_path: cli.config.as_deref(),
+            rpc: &rpc,
+            daemon: &daemon_rpc,
+            output: cli.output,
+            command: cli.command.name(),
+            style: Style {
+                group_digits: cli.group_digits || config.group_digits,
+                ..Style::detect(cli.no_color)
+            },
+            armor: cli.armor,
+            warned_untrusted: std::cell::Cell::new(false),
+            address_checked: std::cell::Cell::new(cli.skip_address_check),
+            account,
+            accounts,
+            audit_summary: std::cell::RefCell::new(None),
+            fee_schedule: std::cell::OnceCell::new(),
+        };
+        let result = run(cli.command, &session).await;
+        match &result {
+            Ok(()) => tracing::info!("command finished"),
+            // Logged at info so the console layer stays quiet; anyhow prints the
+            // error to the terminal on exit.
+            Err(e) => tracing::info!(error = %format!("{e:#}"), "command failed"),
+        }
+        (result, session.audit_summary.into_inner())
+    }
+    .instrument(span)
+    .await;
+
+    let result = match audit_before {
+        Some(before) => {
+            let mut record = audit::AuditRecord::new(
+                command_name,
+                utils::redact_args(std::env::args().skip(1)),
+                result.as_ref().map(|_| ()).map_err(|e| format!("{e:#}")),
+                &before,
+                &audit::Snapshot::capture(&data_dir),
+            );
+            record.summary = summary;
+            match (audit::append(&data_dir, &record), result) {
+                (Err(e), Ok(())) => Err(anyhow::Error::from(e).context("write audit log")),
+                (Err(e), Err(failed)) => {
+                    eprintln!("warning: could not write audit log: {e}");
+                    Err(failed)
+                }
+                (Ok(()), result) => result,
+            }
+        }
+        None => result,
+    };
+
+    if guide && result.is_ok() && !interrupt::requested() {
+        let steps = workflow::render(&workflow::load(&data_dir));
+        if !steps.is_empty() {
+            eprint!("\n{steps}");
+        }
+    }
+
+    if let Some(style) = timing {
+        eprint!("{}", timing_table(&metrics::rpc_timings()).render(&style));
+    }
+
+    if let Some(budgets) = latency_hint.filter(|_| !interrupt::requested()) {
+        let served: std::time::Duration = metrics::rpc_timings()
+            .iter()
+            .map(|t| t.total.saturating_sub(t.waited))
+            .sum();
+        if budgets.command_too_slow(served) {
+            eprintln!(
+                "{}",
+                messages::format(
+                    Msg::HintSlowRpc,
+                    &[("seconds", &format!("{:.1}", served.as_secs_f64()))]
+                )
+            );
+        }
+    }
+
+    if interrupt::requested() {
+        if let Err(e) = &result {
+            eprintln!("Error: {e:?}");
+        }
+        tracing::info!("command interrupted");
+        return Ok(std::process::ExitCode::from(interrupt::EXIT_CODE));
+    }
+    result.map(|()| std::process::ExitCode::SUCCESS)
+}
+
+/// The `--timing` summary: one row per RPC method called.
+fn timing_table(timings: &[metrics::MethodTiming]) -> Table {
+    let mut table = Table::new(vec![
+        Column::left("Method"),
+        Column::right("Calls"),
+        Column::right("Total ms"),
+        Column::right("Max ms"),
+        Column::right("Wait ms"),
+    ]);
+    for t in timings {
+        table.row(vec![
+            t.method.as_str().into(),
+            t.count.to_string().into(),
+            t.total.as_millis().to_string().into(),
+            t.max.as_millis().to_string().into(),
+            t.waited.as_millis().to_string().into(),
+        ]);
+    }
+    table
+}
+
+/// The `doctor --latency` table: one row per method timed.
+fn latency_table(methods: &[doctor::MethodLatency]) -> Table {
+    let mut table = Table::new(vec![
+        Column::left("Method"),
+        Column::right("p50 ms"),
+        Column::right("p95 ms"),
+        Column::right("Budget ms"),
+        Column::left("Status"),
+    ]);
+    for m in methods {
+        let status = if m.over_budget {
+            Cell::colored("slow", Color::Red)
+        } else {
+            Cell::colored("ok", Color::Green)
+        };
+        table.row(vec![
+            m.method.as_str().into(),
+            m.p50_ms.to_string().into(),
+            m.p95_ms.to_string().into(),
+            m.budget_ms.to_string().into(),
+            status,
+        ]);
+    }
+    table
+}
+
+/// The tx set given with `--tx-data` or `--tx-file`, not yet checked.
+fn tx_input(
+    config: &Config,
+    tx_data: Option<String>,
+    tx_file: Option<&std::path::Path>,
+) -> Result<String> {
+    match tx_file {
+        Some(path) => Ok(transaction::read_tx_file(path, config.max_blob_bytes)?),
+        None => Ok(tx_data.expect("clap requires --tx-data or --tx-file")),
+    }
+}
+
+/// The cold-signing file given with `--tx-data` or `--tx-file`, as checked
+/// hex.
+#[cfg(feature = "cold-sign")]
+fn cold_input(
+    config: &Config,
+    tx_data: Option<String>,
+    tx_file: Option<&std::path::Path>,
+) -> Result<String> {
+    let max = config.max_blob_bytes;
+    let hex = match tx_file {
+        Some(path) => transaction::read_cold_file(path, max)?,
+        None => tx_data
+            .expect("clap requires --tx-data or --tx-file")
+            .trim()
+            .to_string(),
+    };
+    utils::validate_hex_blob(&hex, max).map_err(MultisigError::from)?;
+    Ok(hex)
+}
+
+/// Accept either a raw hex tx set or an armored one, and check it is hex of
+/// an acceptable size before anything else looks at it.
+fn unwrap_tx_set(config: &Config, data: &str) -> Result<String> {
+    let max = config.max_blob_bytes;
+    if data.len() > max {
+        let len = data.len();
+        return Err(MultisigError::from(utils::BlobError::TooLarge { len, max }).into());
+    }
+    let tx_data = normalize_input(data, Some(armor::BlobKind::TxSet), "tx set")?;
+    utils::validate_hex_blob(&tx_data, max).map_err(MultisigError::from)?;
+    transaction::reject_cold_signing(&tx_data)?;
+    Ok(tx_data)
+}
+
+/// The config's `manifest_secret`, which manifests are signed and checked
+/// with.
+fn manifest_secret(config: &Config) -> Result<&str> {
+    match &config.manifest_secret {
+        Some(secret) if !secret.is_empty() => Ok(secret.expose()),
+        _ => Err(MultisigError::from(ManifestError::NoSecret).into()),
+    }
+}
+
+/// Read the manifest at `path` and check its HMAC.
+fn read_verified_manifest(config: &Config, path: &std::path::Path) -> Result<manifest::Manifest> {
+    let secret = manifest_secret(config)?;
+    let manifest = manifest::read_manifest(path)
+        .with_context(|| format!("read manifest {}", path.display()))?;
+    manifest.verify(secret).map_err(MultisigError::from)?;
+    Ok(manifest)
+}
+
+/// Print how a tx set differs from its manifest as a diff, manifest side
+/// `-`, tx set side `+`, and fail if it differs at all.
+fn report_differences(differences: &[manifest::Difference]) -> Result<()> {
+    if differences.is_empty() {
+        return Ok(());
+    }
+    eprintln!("The tx set doesn't match the manifest (- manifest, + tx set):");
+    for difference in differences {
+        eprintln!("{difference}");
+    }
+    Err(MultisigError::from(ManifestError::Mismatch(differences.len())).into())
+}
+
+/// Clean up a pasted blob with [`utils::normalize_blob`], saying what was
+/// removed if anything was.
+fn normalize_input(text: &str, kind: Option<armor::BlobKind>, what: &str) -> Result<String> {
+    let (blob, stripped) = utils::normalize_blob(text, kind).map_err(MultisigError::from)?;
+    if !stripped.is_empty() {
+        eprintln!("Cleaned up {what}: removed {stripped}.");
+        tracing::info!(blob = what, %stripped, "normalized pasted blob");
+    }
+    Ok(blob)
+}
+
+/// Ask for the peers' info for key exchange `round`, one blob per prompt,
+/// checking and fingerprinting each as it comes. A blob that fails the
+/// checks `exchange-keys` would run on it is asked for again. The prompts
+/// name the sender when `from` does.
+fn prompt_round_info(
+    config: &Config,
+    state: &wallet::StateFile,
+    round: u32,
+    from: &[String],
+) -> Result<Vec<String>> {
+    let expected = state.wallet.params().map_or(0, |p| p.total as usize - 1);
+    let rounds = state
+        .wallet
+        .params()
+        .map_or(0, |p| wallet::key_exchange_rounds(p.threshold, p.total));
+    eprintln!(
+        "{}",
+        messages::format(
+            Msg::PromptCollectingRound,
+            &[
+                ("round", &round),
+                ("rounds", &rounds),
+                ("expected", &expected)
+            ],
+        )
+    );
+    let mut blobs: Vec<String> = Vec::new();
+    while blobs.len() < expected {
+        let n = blobs.len() + 1;
+        let remaining = expected - blobs.len();
+        let prompt = match from.get(n - 1) {
+            Some(name) => messages::format(
+                Msg::PromptRoundInfoFrom,
+                &[
+                    ("round", &round),
+                    ("n", &n),
+                    ("name", name),
+                    ("remaining", &remaining),
+                ],
+            ),
+            None => messages::format(
+                Msg::PromptRoundInfo,
+                &[("round", &round), ("n", &n), ("remaining", &remaining)],
+            ),
+        };
+        let answer = utils::ask_line(&prompt).ok_or_else(|| {
+            anyhow::anyhow!(
+                "aborted; input ended before round {round}'s peer info was complete. The \
+                 state file is saved, so run exchange-keys with the round {round} info to go on"
+            )
+        })?;
+        if answer.is_empty() {
+            continue;
+        }
+        let blob = match normalize_input(&answer, None, &format!("peer info #{n}")) {
+            Ok(blob) => blob,
+            Err(e) => {
+                eprintln!("  {e}");
+                continue;
+            }
+        };
+        blobs.push(blob);
+        let checks = state.check_peer_info(&blobs, from, &[], config.max_blob_bytes);
+        match checks.last().and_then(|c| c.problem.as_ref()) {
+            Some(problem) => {
+                eprintln!("  Rejected: {problem}. Paste it again.");
+                blobs.pop();
+            }
+            None => eprintln!(
+                "  Fingerprint: {}",
+                utils::fingerprint_with(&blobs[n - 1], config.fingerprint_format)
+            ),
+        }
+    }
+    Ok(blobs)
+}
+
+/// The text form of `inspect-info`.
+fn print_inspected_info(info: &output::InspectedInfo) {
+    let armor = if info.armored { "armored" } else { "raw" };
+    println!("Kind:         {} ({armor})", info.kind);
+    println!("Fingerprint:  {}", info.fingerprint);
+    println!("Size:         {} characters", info.length);
+    match (info.created_at, info.export_height) {
+        (None, None) => println!(
+            "Exported:     unknown — no export header, and the blob itself is encrypted; \
+             `export-info --armor` adds one"
+        ),
+        (created, height) => {
+            let created = created.map_or("unknown time".to_string(), |at| {
+                at.format("%Y-%m-%d %H:%M UTC").to_string()
+            });
+            let height = height.map_or("unknown height".to_string(), |h| format!("height {h}"));
+            println!("Exported:     {created}, at {height}");
+        }
+    }
+    if let Some(from) = &info.from {
+        println!("From:         {from}");
+    }
+    match (info.blocks_behind, info.wallet_height) {
+        (Some(behind), Some(ours)) => {
+            let verdict = if info.stale == Some(true) {
+                " — stale, ask for a fresh export"
+            } else {
+                ""
+            };
+            println!(
+                "Freshness:    {behind} blocks behind this wallet's last import at {ours}{verdict}"
+            );
+        }
+        (None, Some(ours)) => {
+            println!("Freshness:    unknown; this wallet last imported at {ours}")
+        }
+        _ => {}
+    }
+    match info.status {
+        Some(output::InfoStatus::New) => println!("Imported:     no"),
+        Some(output::InfoStatus::OwnExport) => {
+            println!("Imported:     no — this is this wallet's own export")
+        }
+        Some(output::InfoStatus::Imported) => println!(
+            "Imported:     yes, {}",
+            info.imported_at.map_or(String::new(), |at| at
+                .format("%Y-%m-%d %H:%M UTC")
+                .to_string())
+        ),
+        None => println!("Imported:     unknown (no local wallet state)"),
+    }
+}
+
+/// Account indices for messages: `1, 3`.
+fn account_list(accounts: &[u32]) -> String {
+    accounts
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// One row per account, for `list-accounts` and `account list`.
+fn accounts_table(session: &Session<'_>, accounts: &[wallet::Account]) -> Table {
+    let mut table = Table::new(vec![
+        Column::right("Account"),
+        Column::left("Label"),
+        Column::left("Tag"),
+        Column::right("Balance (XMR)"),
+        Column::right("Unlocked (XMR)"),
+        Column::left("Base address").shrink(),
+    ]);
+    for a in accounts {
+        table.row(vec![
+            a.account_index.to_string().into(),
+            a.label.as_str().into(),
+            a.tag.as_deref().unwrap_or("").into(),
+            session.style.xmr(a.balance.0).into(),
+            session.style.xmr(a.unlocked_balance.0).into(),
+            a.base_address.as_str().into(),
+        ]);
+    }
+    table
+}
+
+/// One row per peer info blob, with what was found wrong with it.
+fn peer_info_table(config: &Config, info: &[String], checks: &[wallet::PeerInfoCheck]) -> Table {
+    let mut table = Table::new(vec![
+        Column::right("#"),
+        Column::left("From"),
+        Column::left("Fingerprint"),
+        Column::left("Status").shrink(),
+    ]);
+    for (blob, check) in info.iter().zip(checks) {
+        let status = match &check.problem {
+            None => Cell::colored("ok", Color::Green),
+            Some(problem) => Cell::colored(format!("rejected: {problem}"), Color::Red),
+        };
+        table.row(vec![
+            check.index.to_string().into(),
+            check.from.as_deref().unwrap_or("-").into(),
+            utils::fingerprint_with(blob, config.fingerprint_format).into(),
+            status,
+        ]);
+    }
+    table
+}
+
+/// The `list-wallet-files` table.
+fn print_wallet_files(files: &[wallet::WalletFile], listed_by_rpc: bool, style: &Style) {
+    if !listed_by_rpc {
+        println!(
+            "The wallet RPC has no method to list its wallet files; showing the wallets known here."
+        );
+    }
+    if files.is_empty() {
+        println!("No wallet files found.");
+        return;
+    }
+    let mut table = Table::new(vec![
+        Column::left("Filename").shrink(),
+        Column::left("Label"),
+        Column::left("Multisig"),
+        Column::left("Modified"),
+    ]);
+    for file in files {
+        let label = match &file.label {
+            Some(label) if file.retired => format!("{label} (retired)").into(),
+            Some(label) => label.as_str().into(),
+            None => Cell::colored("unknown", Color::Yellow),
+        };
+        let multisig = match file.multisig {
+            Some(true) => "yes",
+            Some(false) => "no",
+            None => "?",
+        };
+        let modified = file
+            .modified
+            .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "-".into());
+        table.row(vec![
+            file.filename.as_str().into(),
+            label,
+            multisig.into(),
+            modified.into(),
+        ]);
+    }
+    print!("{}", table.render(style));
+    if files.iter().any(wallet::WalletFile::adopt_candidate) {
+        println!(
+            "Wallets marked unknown may have been set up elsewhere: open one in the wallet RPC \
+             and run `adopt` to take it over."
+        );
+    }
+}
+
+/// The `incoming` tables: pool payments with how long ago they arrived, and
+/// unless `pool_only` the confirmed ones.
+fn print_incoming(
+    session: &Session<'_>,
+    pool: &[transaction::PoolPayment],
+    confirmed: &[transaction::TransferEntry],
+    pool_only: bool,
+) {
+    let now = chrono::Utc::now().timestamp();
+    if pool.is_empty() {
+        println!("No payments in the pool for account {}.", session.account);
+    } else {
+        let mut table = Table::new(vec![
+            Column::right("Age"),
+            Column::right("Amount (XMR)"),
+            Column::left("Subaddress"),
+            Column::left("Double spend"),
+            Column::left("Tx hash").shrink(),
+        ]);
+        for p in pool {
+            let age = now.saturating_sub(p.timestamp as i64).max(0);
+            let age = if age < 60 {
+                Cell::colored(format!("{age}s"), Color::Green)
+            } else if age < 3600 {
+                format!("{}m", age / 60).into()
+            } else {
+                format!("{}h", age / 3600).into()
+            };
+            let double_spend = if p.double_spend_seen {
+                Cell::colored("SEEN — do not accept", Color::Red)
+            } else {
+                "no".into()
+            };
+            table.row(vec![
+                age,
+                session.style.xmr(p.amount).into(),
+                format!("{}/{}", p.subaddr_index.major, p.subaddr_index.minor).into(),
+                double_spend,
+                p.txid.as_str().into(),
+            ]);
+        }
+        println!("In the pool (unconfirmed):\n");
+        print!("{}", table.render(&session.style));
+    }
+    if pool_only {
+        return;
+    }
+    println!();
+    if confirmed.is_empty() {
+        println!("No confirmed payments to account {}.", session.account);
+        return;
+    }
+    let mut table = Table::new(vec![
+        Column::left("Date"),
+        Column::right("Amount (XMR)"),
+        Column::left("Subaddress"),
+        Column::right("Confirmations"),
+        Column::left("Tx hash").shrink(),
+    ]);
+    for t in confirmed {
+        let when = chrono::DateTime::from_timestamp(t.timestamp as i64, 0)
+            .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        table.row(vec![
+            when.into(),
+            session.style.xmr(t.amount).into(),
+            format!("{}/{}", t.subaddr_index.major, t.subaddr_index.minor).into(),
+            t.confirmations.to_string().into(),
+            t.txid.as_str().into(),
+        ]);
+    }
+    println!("Confirmed:\n");
+    print!("{}", table.render(&session.style));
+}
+
+fn print_repair(repair: &wallet::Repair) {
+    for fix in &repair.fixed {
+        println!("Fixed: {fix}");
+    }
+    for issue in &repair.unresolved {
+        eprintln!("Not fixed: {issue}");
+    }
+    if let Some(round) = repair.undecided_round {
+        println!(
+            "Round {round} was interrupted and the wallet RPC can't tell whether it went \
+             through. Run exchange-keys again with the same peer info to retry it, or \
+             `repair-state --round-done` if it did go through."
+        );
+    }
+}
+
+fn print_status(state: &wallet::StateFile) {
+    let stage = match &state.wallet {
+        wallet::WalletState::Created { .. } => "created, awaiting key exchange".to_string(),
+        wallet::WalletState::KeyExchangeInProgress {
+            rounds_completed,
+            rounds_required,
+            ..
+        } => format!("key exchange round {rounds_completed} of {rounds_required}"),
+        wallet::WalletState::Ready { .. } => "ready".to_string(),
+        wallet::WalletState::WatchOnly { .. } => "watch-only".to_string(),
+    };
+
+    println!("Wallet:   {}", state.wallet.label());
+    println!("Stage:    {stage}");
+    if let Some(params) = state.wallet.params() {
+        println!("Scheme:   {}-of-{}", params.threshold, params.total);
+        if !params.roster.is_empty() {
+            println!("Roster:   {}, {}", wallet::ME, params.roster.join(", "));
+        }
+    }
+    if let wallet::WalletState::Ready { address, .. }
+    | wallet::WalletState::WatchOnly { address, .. } = &state.wallet
+    {
+        println!("Address:  {address}");
+        let verified = match state.verified_at {
+            Some(at) if state.verified => format!("yes ({})", at.format("%Y-%m-%d %H:%M UTC")),
+            _ => "no".to_string(),
+        };
+        println!("Verified: {verified}");
+    }
+    if let Some(height) = state.wallet.restore_height() {
+        println!("Restore:  block {height}");
+    }
+    println!("Imports:  {} peer blob(s)", state.imports.len());
+    if let Some(export) = &state.last_export {
+        println!(
+            "Exported: {}",
+            export.exported_at.format("%Y-%m-%d %H:%M UTC")
+        );
+    }
+    if let Some(attempt) = &state.exchange_attempt {
+        println!(
+            "Warning:  key exchange round {} was interrupted at {}; run repair-state",
+            attempt.round,
+            attempt.started_at.format("%Y-%m-%d %H:%M UTC")
+        );
+    }
+}
+
+/// Refuse signing operations on a watch-only wallet. Having no local state is
+/// fine; the wallet RPC rejects whatever it can't do.
+fn ensure_can_sign(config: &Config) -> Result<()> {
+    match wallet::load_wallet_state(&config.data_dir) {
+        Ok(state) => Ok(state.ensure_can_sign()?),
+        Err(MultisigError::Wallet(WalletError::NotFound(_))) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// The in-flight transactions for `pending`, one row per destination.
+fn pending_table(entries: &[&journal::JournalEntry], style: &Style) -> Table {
+    let mut table = Table::new(vec![
+        Column::right("#"),
+        Column::left("Status"),
+        Column::right("Signatures"),
+        Column::right("Amount (XMR)"),
+        Column::right("Fee (XMR)"),
+        Column::left("To").shrink(),
+        Column::left("Tx hash").shrink(),
+        Column::left("Last action"),
+        Column::left("Note").shrink(),
+    ]);
+    for e in entries {
+        let first = e.destinations.first();
+        table.row(vec![
+            format!("{}", e.id).into(),
+            status_cell(e.status),
+            format!("{}/{}", e.signatures_collected, e.signatures_required).into(),
+            first
+                .map(|d| style.xmr(d.amount().0))
+                .unwrap_or_default()
+                .into(),
+            style.xmr(e.fee).into(),
+            first
+                .map(|d| short_address(d.address()))
+                .unwrap_or_default()
+                .into(),
+            e.latest_tx_hash().unwrap_or_default().into(),
+            e.last_action_at()
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+                .into(),
+            e.note.clone().unwrap_or_default().into(),
+        ]);
+        for d in e.destinations.iter().skip(1) {
+            table.row(vec![
+                Cell::default(),
+                Cell::default(),
+                Cell::default(),
+                style.xmr(d.amount().0).into(),
+                Cell::default(),
+                short_address(d.address()).into(),
+            ]);
+        }
+    }
+    table
+}
+
+/// `me ✓, alice ✓, bob ✗` for the roster, plus any signatures counted but
+/// not credited to anyone.
+fn signer_checklist(entry: &journal::JournalEntry, roster: &[String]) -> String {
+    let names = std::iter::once(wallet::ME).chain(roster.iter().map(String::as_str));
+    let mut items: Vec<String> = names
+        .map(|name| {
+            let mark = if entry.signed_by(name) { "✓" } else { "✗" };
+            format!("{name} {mark}")
+        })
+        .collect();
+    let credited = entry
+        .signers
+        .iter()
+        .filter(|s| s.name == wallet::ME || roster.contains(&s.name))
+        .count() as u32;
+    let unknown = entry.signatures_collected.saturating_sub(credited);
+    if unknown > 0 {
+        items.push(format!("{unknown} uncredited"));
+    }
+    items.join(", ")
+}
+
+/// A journal status, colored by what it needs next.
+fn status_cell(status: journal::TxStatus) -> Cell {
+    let (text, color) = match status {
+        journal::TxStatus::AwaitingSignatures => ("awaiting signatures", Color::Yellow),
+        journal::TxStatus::ReadyToSubmit => ("ready to submit", Color::Green),
+        journal::TxStatus::AwaitingRelay => ("awaiting relay", Color::Green),
+        journal::TxStatus::Submitted => ("submitted", Color::Dim),
+        journal::TxStatus::Discarded => ("discarded", Color::Red),
+    };
+    Cell::colored(text, color)
+}
+
+/// Print the `show-tx` details of a transfer the wallet knows.
+fn print_transfer_record(t: &monero_multisig::rpc::methods::TransferRecord, style: &Style) {
+    println!(
+        "  Direction:     {}",
+        format!("{:?}", t.kind).to_lowercase()
+    );
+    println!("  Amount:        {} XMR", style.xmr(t.amount));
+    if t.fee > 0 {
+        println!("  Fee:           {} XMR", style.xmr(t.fee));
+    }
+    if t.height == 0 {
+        println!("  Height:        not mined yet");
+    } else {
+        println!(
+            "  Height:        {} ({} confirmation(s))",
+            t.height, t.confirmations
+        );
+    }
+    // Below this an unlock time is a block height, from it a Unix time.
+    const UNLOCK_TIME_IS_TIMESTAMP: u64 = 500_000_000;
+    let unlock = match t.unlock_time {
+        0 => "none".to_string(),
+        n if n < UNLOCK_TIME_IS_TIMESTAMP => format!("block {n}"),
+        n => chrono::DateTime::from_timestamp(n as i64, 0).map_or_else(
+            || n.to_string(),
+            |d| d.format("%Y-%m-%d %H:%M UTC").to_string(),
+        ),
+    };
+    println!("  Unlock time:   {unlock}");
+    let index = |i: &transaction::SubaddressIndex| format!("{}/{}", i.major, i.minor);
+    if t.subaddr_indices.is_empty() {
+        println!("  Subaddress:    {}", index(&t.subaddr_index));
+    } else {
+        let spent: Vec<_> = t.subaddr_indices.iter().map(index).collect();
+        println!("  Spent from:    {}", spent.join(", "));
+    }
+    if !t.address.is_empty() {
+        println!("  Address:       {}", t.address);
+    }
+    if !t.destinations.is_empty() {
+        println!("  Destinations:");
+        for d in &t.destinations {
+            println!("    {}  {} XMR", d.address, style.xmr(d.amount));
+        }
+    }
+}
+
+/// An address shortened for listings; the JSON output and exports keep it
+/// whole.
+fn short_address(address: &str) -> String {
+    utils::abbreviate_middle(address, 12, 12)
+}
+
+/// Print one journal entry with its full lifecycle.
+fn print_journal_entry(e: &journal::JournalEntry, style: &Style) {
+    println!(
+        "#{} {}  {:?}  {} XMR (fee {})  {}/{} signatures",
+        e.id,
+        e.created_at.format("%Y-%m-%d %H:%M"),
+        e.status,
+        style.xmr(e.amount()),
+        style.xmr(e.fee),
+        e.signatures_collected,
+        e.signatures_required
+    );
+    if let Some(note) = &e.note {
+        println!("    Note:  {note}");
+    }
+    for d in &e.destinations {
+        println!("    To:    {} ({})", short_address(d.address()), d.amount());
+    }
+    if let Some(hash) = e.latest_tx_hash() {
+        println!("    Hash:  {hash}");
+    }
+    if let Some(id) = e.supersedes {
+        println!("    Rebuilds #{id} with a higher fee");
+    }
+    if !e.signers.is_empty() {
+        let names: Vec<_> = e.signers.iter().map(|s| s.name.as_str()).collect();
+        println!("    Signed: {} (as recorded here)", names.join(", "));
+    }
+    for event in &e.events {
+        println!(
+            "    {}  {} by {}",
+            event.at.format("%Y-%m-%d %H:%M:%S"),
+            event.action,
+            event.by
+        );
+    }
+}
+
+/// Refuse a transaction that breaks the configured policy, unless the user
+/// passed `--override-policy` and confirms the exact numbers.
+fn enforce_policy(
+    config: &Config,
+    amount: transaction::Amount,
+    fee: transaction::Amount,
+    override_policy: bool,
+) -> Result<()> {
+    let violations = config.policy.check(amount, fee);
+    if violations.is_empty() {
+        return Ok(());
+    }
+    let report = violations
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    if override_policy {
+        eprintln!("Policy violations:");
+        for v in &violations {
+            eprintln!("  - {v}");
+        }
+        let prompt = messages::format(
+            Msg::ConfirmOverridePolicy,
+            &[
+                ("amount", &amount),
+                ("amount_atomic", &amount.0),
+                ("fee", &fee),
+                ("fee_atomic", &fee.0),
+            ],
+        );
+        if utils::confirm(&prompt) {
+            tracing::warn!(amount = amount.0, fee = fee.0, violations = %report, "policy overridden");
+            return Ok(());
+        }
+    }
+    Err(MultisigError::from(TransactionError::policy(format!(
+        "policy violation: {report}"
+    )))
+    .into())
+}
+
+/// Ask for the amount to be typed back when a transaction spends most of the
+/// balance. JSON mode can't prompt, so there it fails unless `--allow-sweep`
+/// was given.
+fn confirm_sweep(
+    session: &Session<'_>,
+    amount: transaction::Amount,
+    fee: transaction::Amount,
+    balance: transaction::Amount,
+) -> Result<()> {
+    let percent = session.config.sweep_warning_percent;
+    let total = amount.checked_add(fee).map_err(MultisigError::from)?;
+    if !monero_multisig::policy::is_sweep(total, balance, percent) {
+        return Ok(());
+    }
+
+    eprintln!("\n{}", messages::text(Msg::WarningSweep));
+    eprintln!(
+        "    {}",
+        messages::format(
+            Msg::WarningSweepTotal,
+            &[("total", &total), ("amount", &amount.0), ("fee", &fee.0)],
+        )
+    );
+    eprintln!(
+        "    {}\n",
+        messages::format(
+            Msg::WarningSweepBalance,
+            &[("balance", &balance), ("percent", &percent)],
+        )
+    );
+
+    let confirmed = session.output == OutputFormat::Text
+        && utils::confirm_typed(
+            &messages::format(Msg::PromptTypeAmount, &[("amount", &amount.0)]),
+            &amount.0.to_string(),
+        );
+    if confirmed {
+        tracing::warn!(
+            amount = amount.0,
+            fee = fee.0,
+            balance = balance.0,
+            "sweep confirmed"
+        );
+        return Ok(());
+    }
+    Err(MultisigError::from(TransactionError::policy(format!(
+        "transaction spends more than {percent}% of the balance; pass --allow-sweep to skip this check"
+    )))
+    .into())
+}
+
+/// Show what `submit-tx` is about to broadcast, as decoded or journaled, and
+/// ask to go ahead unless `yes`. A set nothing describes needs the start of
+/// its tx hash typed back even with `yes`, so a blind broadcast is always
+/// deliberate. Either way, what was shown goes into the audit log.
+fn confirm_submission(
+    session: &Session<'_>,
+    tx_set: &str,
+    description: Option<&[transaction::TransferDescription]>,
+    yes: bool,
+) -> Result<()> {
+    let journal = Journal::load(&session.config.data_dir)?;
+    let text = session.output == OutputFormat::Text;
+    if let Some(summary) = journal.submit_summary(tx_set, description) {
+        *session.audit_summary.borrow_mut() = serde_json::to_value(&summary).ok();
+        let shown = submit_summary_text(&summary, &session.style);
+        if text {
+            print!("{shown}");
+        } else {
+            eprint!("{shown}");
+        }
+        if !yes && !utils::confirm(&messages::text(Msg::ConfirmBroadcast)) {
+            anyhow::bail!("aborted; nothing was submitted");
+        }
+        return Ok(());
+    }
+
+    // The tx hash is only known here if the journal recorded one; otherwise
+    // the set's own digest stands in for it.
+    let hash = journal
+        .find(tx_set, &[], None)
+        .and_then(|i| journal.entries[i].latest_tx_hash())
+        .map(|h| ("tx hash", h.to_string()));
+    let (what, id) = hash.unwrap_or_else(|| ("tx set digest", utils::blob_digest(tx_set)));
+    *session.audit_summary.borrow_mut() = Some(serde_json::json!({
+        "source": "none",
+        what.replace(' ', "_"): id,
+    }));
+    eprintln!("\n{}", messages::text(Msg::WarningUndescribed));
+    eprintln!("    {}", messages::text(Msg::WarningUndescribedDetail));
+    eprintln!("    {what}: {id}\n");
+    let prefix = id.get(..8).unwrap_or(&id);
+    let confirmed = text
+        && utils::confirm_typed(
+            &messages::format(Msg::PromptTypeId, &[("what", &what)]),
+            prefix,
+        );
+    if !confirmed {
+        anyhow::bail!(
+            "aborted; nothing was submitted. Check the set with `describe`, or submit it in \
+             text mode and confirm the {what}"
+        );
+    }
+    tracing::warn!(id = %id, "tx set submitted without a description");
+    Ok(())
+}
+
+/// The lines [`confirm_submission`] shows for `summary`.
+fn submit_summary_text(summary: &journal::SubmitSummary, style: &Style) -> String {
+    let mut out = match summary.journal_id {
+        Some(id) => format!("\nAbout to broadcast (journal entry #{id}):\n"),
+        None => "\nAbout to broadcast:\n".to_string(),
+    };
+    for d in &summary.destinations {
+        out.push_str(&format!(
+            "  {:>20} XMR  to {}\n",
+            style.xmr(d.amount().0),
+            d.address()
+        ));
+    }
+    out.push_str(&format!("  {:>20} XMR  fee\n", style.xmr(summary.fee)));
+    out.push_str(&format!("  {:>20} XMR  total\n", style.xmr(summary.total)));
+    if let Some(note) = &summary.note {
+        out.push_str(&format!("  Note: {note}\n"));
+    }
+    if summary.source == journal::SummarySource::Journal {
+        out.push_str(
+            "  (from the journal: the wallet RPC couldn't decode the set to confirm it)\n",
+        );
+    }
+    out
+}
+
+/// One tx set in a `sign-batch` run.
+struct BatchItem {
+    path: PathBuf,
+    tx_data: String,
+    description: Option<Vec<transaction::TransferDescription>>,
+    outputs: Vec<transaction::ReviewedOutput>,
+    /// Amount paid outside the wallet, excluding change and self-transfers.
+    amount: transaction::Amount,
+    fee: transaction::Amount,
+    /// Why the set won't be signed, if it won't.
+    problem: Option<String>,
+}
+
+impl BatchItem {
+    fn failed(path: PathBuf, problem: String) -> Self {
+        Self {
+            path,
+            tx_data: String::new(),
+            description: None,
+            outputs: Vec::new(),
+            amount: transaction::Amount::ZERO,
+            fee: transaction::Amount::ZERO,
+            problem: Some(problem),
+        }
+    }
+
+    fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    fn output_path(&self) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(".signed");
+        PathBuf::from(name)
+    }
+}
+
+/// The tx set files in `dir`, sorted by name.
+fn batch_inputs(dir: &std::path::Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))? {
+        let path = entry?.path();
+        let is_set = matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("txset" | "asc")
+        );
+        if is_set && path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Read and decode one batch input and check it against the policy. Batch
+/// signing has no overrides: anything outside the policy is skipped.
+async fn review_batch_item(
+    session: &Session<'_>,
+    path: &std::path::Path,
+    own: &[String],
+) -> Result<BatchItem> {
+    let contents = utils::read_multisig_data(Some(path), session.config.max_blob_bytes)?;
+    let tx_data = unwrap_tx_set(session.config, &contents)?;
+    let description = transaction::describe_transfer(session.rpc, &tx_data).await?;
+    transaction::check_network(&description, session.config.network)?;
+
+    let requested = requested_destinations(session.config, &tx_data, &description);
+    let outputs = transaction::review_outputs(&description, own, &requested);
+    let amount = transaction::leaving_amount(&outputs)?;
+    let fee = transaction::total_fee(&description)?;
+
+    let policy = &session.config.policy;
+    let mut problems: Vec<String> = policy
+        .check(amount, fee)
+        .iter()
+        .map(|v| v.to_string())
+        .collect();
+    let rejected = policy.disallowed_destinations(outputs.iter().map(|o| o.address.as_str()), own);
+    if !rejected.is_empty() {
+        problems.push(format!(
+            "destination not in allowlist: {}",
+            rejected.join(", ")
+        ));
+    }
+
+    Ok(BatchItem {
+        path: path.to_path_buf(),
+        tx_data,
+        description: Some(description),
+        outputs,
+        amount,
+        fee,
+        problem: (!problems.is_empty()).then(|| problems.join("; ")),
+    })
+}
+
+/// Refuse destinations outside the allowlist, unless the user passed
+/// `--ignore-allowlist` and types every offending address back.
+fn enforce_allowlist(rejected: &[String], ignore_allowlist: bool) -> Result<()> {
+    if rejected.is_empty() {
+        return Ok(());
+    }
+    eprintln!("Destinations not in allowed_destinations:");
+    for address in rejected {
+        eprintln!("  - {address}");
+    }
+
+    if ignore_allowlist {
+        let confirmed = rejected.iter().all(|address| {
+            utils::confirm_typed(
+                &format!("Type the address {address} to pay it anyway:"),
+                address,
+            )
+        });
+        if confirmed {
+            tracing::warn!(destinations = %rejected.join(","), "allowlist ignored");
+            return Ok(());
+        }
+    }
+    Err(MultisigError::from(TransactionError::policy(format!(
+        "destination not in allowlist: {}",
+        rejected.join(", ")
+    )))
+    .into())
+}
+
+/// Checks shared by `build-tx` and `build-batch` before anything is built:
+/// warn once about an unverified address, and refuse to build on a stale
+/// multisig sync unless `allow_stale_sync`. A `dry_run` holds only a shared
+/// lock, so it warns without recording that it did.
+async fn check_before_build(
+    session: &Session<'_>,
+    allow_stale_sync: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let config = session.config;
+    let Ok(mut state) = wallet::load_state(&config.data_dir) else {
+        return Ok(());
+    };
+    if !state.verified && !state.unverified_warned {
+        eprintln!("{}", messages::text(Msg::WarningUnverifiedAddress));
+        if !dry_run {
+            state.unverified_warned = true;
+            wallet::save_state(&config.data_dir, &state)?;
+        }
+    }
+
+    let balance = transaction::total_balance(session.rpc).await?;
+    let max_age = chrono::Duration::hours(config.max_sync_age_hours as i64);
+    if let Err(stale) = state.check_sync(chrono::Utc::now(), max_age, balance.balance.0) {
+        if !allow_stale_sync {
+            return Err(MultisigError::from(TransactionError::StaleSync(stale.to_string())).into());
+        }
+        eprintln!(
+            "{}",
+            messages::format(Msg::WarningStaleSync, &[("reason", &stale)])
+        );
+    }
+    Ok(())
+}
+
+/// The wallet RPC takes its fee from the daemon; a public node could
+/// inflate it.
+async fn check_fee_source(session: &Session<'_>) -> Result<()> {
+    match session.fee_schedule().await {
+        Ok(schedule) => session.check_daemon_trust(schedule.estimate.untrusted)?,
+        Err(e) => tracing::debug!(error = %e, "could not fetch the daemon fee estimate"),
+    }
+    Ok(())
+}
+
+/// Row numbers as `1, 2, 5`.
+fn join_rows(rows: &[usize]) -> String {
+    rows.iter()
+        .map(usize::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Ask for `build-tx --interactive`'s destinations one at a time, with an
+/// optional note for each. Every answer is checked as it is given, by the
+/// same [`transaction::Destination::new`] and allowlist as `--address`, and
+/// asked again until it passes. An address book name, or the start of one,
+/// stands for its address.
+fn prompt_destinations(
+    config: &Config,
+    book: &[wallet::AddressBookEntry],
+    ignore_allowlist: bool,
+) -> Result<Vec<(transaction::Destination, Option<String>)>> {
+    let closed = || anyhow::anyhow!("aborted; input ended before the destinations were complete");
+    let mut picked = Vec::new();
+    loop {
+        let n = picked.len() + 1;
+        let address = loop {
+            let answer = utils::ask_line(&messages::format(Msg::PromptDestination, &[("n", &n)]))
+                .ok_or_else(closed)?;
+            if answer == "?" {
+                if book.is_empty() {
+                    eprintln!("  The address book is empty.");
+                }
+                for entry in book {
+                    eprintln!("  {}  {}", entry.description, entry.address);
+                }
+                continue;
+            }
+            if answer.is_empty() {
+                continue;
+            }
+            let address = match transaction::validate_address(&answer, config.network) {
+                Ok(_) => answer,
+                Err(e) => match wallet::lookup_address_book(book, &answer)[..] {
+                    [entry] => {
+                        eprintln!("  {}: {}", entry.description, entry.address);
+                        if let Err(e) =
+                            transaction::validate_address(&entry.address, config.network)
+                        {
+                            eprintln!("  {e}");
+                            continue;
+                        }
+                        entry.address.clone()
+                    }
+                    [] => {
+                        eprintln!("  {e}");
+                        continue;
+                    }
+                    ref several => {
+                        eprintln!("  \"{answer}\" could be any of:");
+                        for entry in several {
+                            eprintln!("    {}  {}", entry.description, entry.address);
+                        }
+                        continue;
+                    }
+                },
+            };
+            let rejected = config
+                .policy
+                .disallowed_destinations([address.as_str()], &[]);
+            if !rejected.is_empty() && !ignore_allowlist {
+                eprintln!("  {address} is not in allowed_destinations");
+                continue;
+            }
+            break address;
+        };
+        let destination = loop {
+            let answer = utils::ask_line(&messages::text(Msg::PromptAmount)).ok_or_else(closed)?;
+            let amount = match answer.parse::<transaction::Amount>() {
+                Ok(amount) if amount.0 > 0 => amount,
+                Ok(_) => {
+                    eprintln!("  The amount must be more than zero.");
+                    continue;
+                }
+                Err(e) => {
+                    eprintln!("  {e}");
+                    continue;
+                }
+            };
+            match transaction::Destination::new(&address, amount, config.network) {
+                Ok(destination) => break destination,
+                Err(e) => eprintln!("  {e}"),
+            }
+        };
+        let note = utils::ask_line(&messages::text(Msg::PromptNote)).ok_or_else(closed)?;
+        picked.push((destination, (!note.is_empty()).then_some(note)));
+        if !utils::confirm(&messages::text(Msg::ConfirmAddDestination)) {
+            return Ok(picked);
+        }
+    }
+}
+
+/// Show what `build-tx --interactive` is about to build, with `fee` as the
+/// estimate, and ask before going on.
+fn review_destinations(
+    session: &Session<'_>,
+    destinations: &[transaction::Destination],
+    fee: Option<transaction::Amount>,
+) -> Result<()> {
+    let total = transaction::Amount::checked_sum(destinations.iter().map(|d| d.amount()))
+        .map_err(MultisigError::from)?;
+    let mut table = Table::new(vec![Column::left("To"), Column::right("Amount (XMR)")]);
+    for d in destinations {
+        table.row(vec![
+            d.address().into(),
+            session.style.xmr(d.amount().0).into(),
+        ]);
+    }
+    println!();
+    print!("{}", table.render(&session.style));
+    println!("\n  Total:         {} XMR", session.style.xmr(total.0));
+    match fee {
+        Some(fee) => println!("  Estimated fee: about {} XMR", session.style.xmr(fee.0)),
+        None => println!("  Estimated fee: unknown (the daemon gave no estimate)"),
+    }
+    println!();
+    if !utils::confirm(&messages::text(Msg::ConfirmBuild)) {
+        anyhow::bail!("aborted; nothing was built");
+    }
+    Ok(())
+}
+
+/// When `balance`'s locked part is expected to unlock, at two minutes a
+/// block.
+fn unlock_estimate(balance: &transaction::Balance) -> String {
+    if balance.blocks_to_unlock == 0 && balance.time_to_unlock == 0 {
+        return "waiting for confirmations".into();
+    }
+    let mut estimate = format!(
+        "unlocks within about {} block(s), ~{} min",
+        balance.blocks_to_unlock,
+        balance.blocks_to_unlock.saturating_mul(2)
+    );
+    if balance.time_to_unlock > 0 {
+        estimate.push_str(&format!(
+            "; time-locked outputs in ~{} min",
+            balance.time_to_unlock.div_ceil(60)
+        ));
+    }
+    estimate
+}
+
+/// The wallet's notes for `txids`, or empty ones from a wallet RPC that
+/// doesn't keep notes, leaving the journal's.
+async fn wallet_notes(rpc: &RpcClient, txids: &[String]) -> Vec<String> {
+    transaction::get_tx_notes(rpc, txids)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::debug!(error = %e, "no tx notes from the wallet; showing the journal's only");
+            vec![String::new(); txids.len()]
+        })
+}
+
+/// Copy a journal note into the wallet, so it travels with the wallet file.
+/// A wallet RPC without notes leaves it in the journal only.
+async fn mirror_note(rpc: &RpcClient, txids: &[String], note: &str) {
+    match transaction::set_tx_notes(rpc, txids, note).await {
+        Ok(()) => {}
+        Err(MultisigError::Rpc(e)) if e.is_method_not_found() => {
+            tracing::debug!("the wallet RPC doesn't keep tx notes; the note stays in the journal");
+        }
+        Err(e) => eprintln!("warning: could not save the note in the wallet: {e}"),
+    }
+}
+
+/// Refuse a tx set the journal marks as discarded unless `force` is given.
+fn check_not_discarded(
+    config: &Config,
+    tx_set: &str,
+    description: Option<&[transaction::TransferDescription]>,
+    force: bool,
+) -> Result<()> {
+    let journal = Journal::load(&config.data_dir)?;
+    if let Some(index) = journal.find(tx_set, &[], description) {
+        let entry = &journal.entries[index];
+        if entry.status == journal::TxStatus::Discarded {
+            if !force {
+                return Err(MultisigError::from(TransactionError::Discarded(entry.id)).into());
+            }
+            eprintln!(
+                "warning: submitting tx set from discarded journal entry #{}",
+                entry.id
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The addresses given to `build-tx` for this tx set, if it was built here,
+/// so a deliberate payment to an own address isn't shown as change.
+fn requested_destinations(
+    config: &Config,
+    tx_set: &str,
+    description: &[transaction::TransferDescription],
+) -> Vec<String> {
+    let Ok(journal) = Journal::load(&config.data_dir) else {
+        return Vec::new();
+    };
+    journal
+        .find(tx_set, &[], Some(description))
+        .map(|i| {
+            journal.entries[i]
+                .destinations
+                .iter()
+                .map(|d| d.address().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The wallet's signing threshold, or 0 when there is no local state.
+fn signatures_required(config: &Config) -> u32 {
+    wallet::load_wallet_state(&config.data_dir)
+        .ok()
+        .and_then(|s| s.params().map(|p| p.threshold))
+        .unwrap_or(0)
+}
+
+/// Apply `update` to the journal and save it. The RPC side of the operation
+/// has already happened by the time we journal it, so failures only warn.
+/// Turn a refused submit into advice: what to do about the reason, and for a
+/// spent input, which journal entries may have spent it first. The refusal
+/// is journaled and, with `--output json`, printed as the result. Other
+/// errors pass through unchanged.
+fn report_rejection(
+    session: &Session<'_>,
+    tx_set: &str,
+    description: Option<&[transaction::TransferDescription]>,
+    err: MultisigError,
+) -> anyhow::Error {
+    let MultisigError::Transaction(TransactionError::Rejected { reason, detail }) = &err else {
+        return err.into();
+    };
+    let reason = *reason;
+    let spent = matches!(
+        reason,
+        RejectionReason::DoubleSpend | RejectionReason::KeyImageSpent
+    );
+    let conflicts = update_journal(session.config, |j| {
+        let id = j
+            .record_rejected(tx_set, description, reason, detail)
+            .map(|e| e.id);
+        if spent {
+            j.may_share_inputs(id).into_iter().cloned().collect()
+        } else {
+            Vec::new()
+        }
+    })
+    .unwrap_or_default();
+
+    if session.output == OutputFormat::Json {
+        let printed = session.print_json(&output::SubmitResult {
+            tx_hashes: Vec::new(),
+            visibility: None,
+            rejection: Some(output::Rejection {
+                reason,
+                detail: detail.clone(),
+                may_share_inputs: conflicts.iter().map(|e| e.id).collect(),
+            }),
+        });
+        if let Err(e) = printed {
+            return e;
+        }
+    } else {
+        println!("\nThe transaction was rejected: {}.", reason.advice());
+        if !conflicts.is_empty() {
+            let refs: Vec<&journal::JournalEntry> = conflicts.iter().collect();
+            println!("\nJournal entries that may have spent the same outputs:");
+            print!(
+                "{}",
+                pending_table(&refs, &session.style).render(&session.style)
+            );
+            println!(
+                "If one of these was signed by co-signers without you, ask them about it; \
+                 discard the ones that will never be submitted."
+            );
+        }
+    }
+    err.into()
+}
+
+/// After a fee-too-low rejection, offer to rebuild the journaled payment at
+/// the next priority. The rebuild is a new set every co-signer must sign
+/// again, so the rejected entry is discarded and linked to it. Returns what
+/// to tell the user when a new set was built; the command still fails, since
+/// nothing was broadcast.
+async fn offer_fee_bump(
+    session: &Session<'_>,
+    tx_set: &str,
+    description: Option<&[transaction::TransferDescription]>,
+    auto_bump: bool,
+) -> Result<Option<String>> {
+    let config = session.config;
+    let journal = Journal::load(&config.data_dir)?;
+    let Some(entry) = journal
+        .find(tx_set, &[], description)
+        .map(|i| journal.entries[i].clone())
+        .filter(|e| !e.destinations.is_empty())
+    else {
+        eprintln!(
+            "The journal doesn't know this set's destinations, so it can't be rebuilt here; \
+             ask whoever built it to rebuild it with a higher --priority."
+        );
+        return Ok(None);
+    };
+    let current = entry.priority.unwrap_or(transaction::Priority::Default);
+    let Some(priority) = current.next() else {
+        eprintln!("It was already built at high priority; wait for the pool to clear.");
+        return Ok(None);
+    };
+    let confirmed = auto_bump
+        || (session.output == OutputFormat::Text
+            && utils::confirm(&messages::format(
+                Msg::ConfirmRebuild,
+                &[("id", &entry.id), ("priority", &priority)],
+            )));
+    if !confirmed {
+        return Ok(None);
+    }
+
+    // Submitting may have marked the set's inputs spent in the wallet.
+    eprintln!("Releasing the outputs of the rejected set...");
+    wallet::rescan_spent(session.rpc).await?;
+    wallet::refresh(session.rpc, None, None).await?;
+
+    let rejected = config
+        .policy
+        .disallowed_destinations(entry.destinations.iter().map(|d| d.address()), &[]);
+    enforce_allowlist(&rejected, false)?;
+    check_fee_source(session).await?;
+    let source = transaction::Source {
+        account: session.account,
+        subaddresses: Vec::new(),
+    };
+    let estimate = session.fee_estimate(priority).await;
+    let unsigned = transaction::build_unsigned_tx(
+        session.rpc,
+        &entry.destinations,
+        priority,
+        false,
+        &source,
+        estimate,
+    )
+    .await?;
+    enforce_policy(
+        config,
+        transaction::Amount(entry.amount()),
+        unsigned.fee,
+        false,
+    )?;
+
+    metrics::TX_SETS.inc(&["built"]);
+    let event = update_journal(config, |j| {
+        let built = j.record_built(
+            &entry.destinations,
+            unsigned.fee.0,
+            &unsigned.tx_hashes,
+            &unsigned.tx_data_hex,
+            signatures_required(config),
+            entry.note.clone(),
+        );
+        let (id, event) = (
+            built.id,
+            notify::Event::for_entry(EventKind::TxBuilt, built),
+        );
+        j.record_size(&unsigned.tx_data_hex, unsigned.weight, unsigned.size_bytes);
+        j.record_priority(&unsigned.tx_data_hex, priority);
+        j.record_superseded(entry.id, id);
+        (id, event)
+    });
+    let Some((id, event)) = event else {
+        anyhow::bail!("the new set was built but the journal could not be updated");
+    };
+    send_notification(session, Some(event)).await;
+
+    let old_fee = transaction::Amount(entry.fee);
+    if session.output == OutputFormat::Text {
+        println!(
+            "\nRebuilt as journal entry #{id} at {priority} priority: fee {old_fee} -> {}",
+            unsigned.fee
+        );
+        println!("\nNew multisig tx set (share with co-signers):\n");
+        println!(
+            "{}\n",
+            session.blob(armor::BlobKind::TxSet, &unsigned.tx_data_hex)
+        );
+        print_fingerprint(config, "Tx set", &unsigned.tx_data_hex);
+    }
+    let old_hash = entry.latest_tx_hash().unwrap_or("<hash>");
+    eprintln!(
+        "\nEvery co-signer must sign the new set; their signatures on the old one don't carry \
+         over. Journal entry #{} is discarded here: ask them to discard their copies too \
+         (discard-tx --tx-hash {old_hash}) so it is never submitted.",
+        entry.id
+    );
+    Ok(Some(format!(
+        "rebuilt as journal entry #{id} at {priority} priority, which needs signing again"
+    )))
+}
+
+fn update_journal<R>(config: &Config, update: impl FnOnce(&mut Journal) -> R) -> Option<R> {
+    let result = Journal::load(&config.data_dir).and_then(|mut journal| {
+        let value = update(&mut journal);
+        journal.save(&config.data_dir).map(|()| value)
+    });
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            eprintln!("warning: could not update the transaction journal: {e}");
+            None
+        }
+    }
+}
+
+/// Post a lifecycle event to the configured webhook, if any. Like the
+/// journal, delivery problems only warn; nothing is sent in offline mode.
+async fn send_notification(session: &Session<'_>, event: Option<notify::Event>) {
+    let config = session.config;
+    let Some(mut event) = event else {
+        return;
+    };
+    if session.rpc.is_offline() {
+        return;
+    }
+    let Some(notifier) = notify::Notifier::new(&config.notifications, &config.data_dir) else {
+        return;
+    };
+    event.wallet_label = wallet::load_wallet_state(&config.data_dir)
+        .ok()
+        .map(|s| s.label().to_string());
+    if let Err(e) = notifier.notify(&event).await {
+        eprintln!("warning: {e}");
+    }
+}
+
+/// Print a set's weight, size and fee rate, so the fee can be judged.
+fn print_weight(fee: transaction::Amount, weight: u64, size_bytes: u64) {
+    let rate = transaction::fee_per_byte(fee, weight)
+        .map_or_else(|| "unknown".to_string(), |r| format!("{r} piconero/byte"));
+    println!("  Weight: {weight} ({size_bytes} bytes), fee rate {rate}");
+}
+
+/// Print the fingerprint of a blob or address for out-of-band comparison.
+fn print_fingerprint(config: &Config, what: &str, data: &str) {
+    println!(
+        "{what} fingerprint: {}",
+        utils::fingerprint_with(data, config.fingerprint_format)
+    );
+}
+
+/// Settings shared by every command handler.
+struct Session<'a> {
+    config: &'a Config,
+    /// The `--config` file, which `bundle import` writes settings back to.
+    config_path: Option<&'a std::path::Path>,
+    rpc: &'a RpcClient,
+    daemon: &'a RpcClient,
+    output: OutputFormat,
+    /// Name of the running command, for the JSON envelope.
+    command: &'static str,
+    /// How tables and statuses are drawn in text output.
+    style: Style,
+    armor: bool,
+    /// Set once the untrusted-daemon warning has been shown.
+    warned_untrusted: std::cell::Cell<bool>,
+    /// Set once the open wallet has been checked, or when told to skip it.
+    address_checked: std::cell::Cell<bool>,
+    /// The `--account` the command works on.
+    account: u32,
+    /// Every account `--account` names: more than `account` only for a tag
+    /// on several accounts, given to a command that takes several.
+    accounts: Vec<u32>,
+    /// What the command asked the operator to confirm, for the audit log.
+    audit_summary: std::cell::RefCell<Option<serde_json::Value>>,
+    /// The daemon's fees, fetched at most once per command.
+    fee_schedule: std::cell::OnceCell<transaction::FeeSchedule>,
+}
+
+impl Session<'_> {
+    /// Abort when the wallet RPC has a different wallet open than the state
+    /// file describes. Checked once per run.
+    async fn check_wallet_address(&self) -> Result<()> {
+        if self.address_checked.get() {
+            return Ok(());
+        }
+        if let Ok(state) = wallet::load_wallet_state(&self.config.data_dir) {
+            wallet::check_open_wallet(self.rpc, &state).await?;
+        }
+        self.address_checked.set(true);
+        Ok(())
+    }
+
+    /// Fail unless `--account`, and each of `subaddresses` in it, exist.
+    async fn check_account(&self, subaddresses: &[u32]) -> Result<()> {
+        wallet::check_account(self.rpc, self.account, subaddresses).await?;
+        Ok(())
+    }
+
+    /// React to the daemon flagging itself untrusted: abort when the config
+    /// demands a trusted daemon, otherwise warn once per session.
+    fn check_daemon_trust(&self, untrusted: bool) -> Result<()> {
+        if !untrusted {
+            return Ok(());
+        }
+        if self.config.trusted_daemon {
+            return Err(MultisigError::from(WalletError::UntrustedDaemon).into());
+        }
+        if !self.warned_untrusted.replace(true) {
+            eprintln!(
+                "warning: the daemon reports itself as untrusted (a public remote node); its \
+                 fee estimates and chain data are not verified"
+            );
+        }
+        Ok(())
+    }
+
+    /// The daemon's fee schedule, asked for on first use and reused for the
+    /// rest of the command.
+    async fn fee_schedule(&self) -> Result<&transaction::FeeSchedule> {
+        if let Some(schedule) = self.fee_schedule.get() {
+            return Ok(schedule);
+        }
+        let schedule = transaction::fee_schedule(self.daemon).await?;
+        Ok(self.fee_schedule.get_or_init(|| schedule))
+    }
+
+    /// The fee of a typical transaction at `priority`, or `None` when the
+    /// daemon can't say.
+    async fn fee_estimate(&self, priority: transaction::Priority) -> Option<transaction::Amount> {
+        let schedule = self.fee_schedule().await.ok()?;
+        Some(schedule.fee(priority, transaction::TYPICAL_TX_WEIGHT))
+    }
+
+    /// Print the command's result in the versioned JSON envelope, with the
+    /// next steps for the wallet as it now stands.
+    fn print_json<T: serde::Serialize>(&self, result: &T) -> Result<()> {
+        let steps = workflow::actions(&workflow::load(&self.config.data_dir));
+        println!(
+            "{}",
+            output::to_json_with_steps(self.command, result, &steps)?
+        );
+        Ok(())
+    }
+
+    /// Format a blob for output, armoring it when `--armor` was given.
+    fn blob(&self, kind: armor::BlobKind, data: &str) -> String {
+        if self.armor {
+            armor::armor(kind, &[], data)
+        } else {
+            data.to_string()
+        }
+    }
+
+    /// Format exported multisig info like [`Session::blob`]. Armored, it
+    /// carries an export header with the time, the wallet height when the
+    /// wallet RPC gives it, and who exported it when `from` says. A `reused`
+    /// export keeps the time and height it was first exported at, so its
+    /// armor comes out the same as before.
+    async fn export_blob(
+        &self,
+        info: &str,
+        from: Option<String>,
+        reused: Option<&wallet::ExportRecord>,
+    ) -> Result<String> {
+        if !self.armor {
+            return Ok(info.to_string());
+        }
+        let (created, height) = match reused {
+            Some(record) => (record.exported_at, record.height),
+            None => (chrono::Utc::now(), None),
+        };
+        let height = match height {
+            Some(height) => Some(height),
+            None => match wallet::get_height(self.rpc).await {
+                Ok(height) => Some(height),
+                Err(e) => {
+                    tracing::debug!(error = %e, "no wallet height for the export header");
+                    None
+                }
+            },
+        };
+        let header = armor::ExportHeader {
+            created: Some(created),
+            height,
+            from,
+        };
+        let headers = header.headers();
+        let headers: Vec<(&str, &str)> = headers.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        Ok(armor::armor(armor::BlobKind::MultisigInfo, &headers, info))
+    }
+}
+
+/// Create the wallet `params` describe and save its state file, returning
+/// our key exchange info for the first round.
+async fn create_wallet(
+    session: &Session<'_>,
+    params: wallet::MultisigParams,
+    restore_height: Option<u64>,
+    restore_date: Option<chrono::NaiveDate>,
+    escrow: Option<escrow::EscrowTerms>,
+) -> Result<String> {
+    let config = session.config;
+    if wallet::wallet_exists(&config.data_dir) {
+        let path = config.data_dir.display().to_string();
+        // A state file that doesn't load can't be resumed either.
+        let Ok(mut state) = wallet::load_state(&config.data_dir) else {
+            return Err(MultisigError::from(WalletError::AlreadyExists(path)).into());
+        };
+        state.check_rerun(&params, &path)?;
+        return resume_create(session, &mut state).await;
+    }
+    let rounds = params.exchange_rounds();
+    if rounds >= wallet::MANY_ROUNDS {
+        eprintln!(
+            "{}",
+            messages::format(
+                Msg::WarningManyRounds,
+                &[
+                    ("threshold", &params.threshold),
+                    ("total", &params.total),
+                    ("rounds", &rounds),
+                ],
+            )
+        );
+    }
+    let restore_height = match (restore_height, restore_date) {
+        (Some(height), _) => Some(wallet::validate_restore_height(session.daemon, height).await?),
+        (None, Some(date)) => {
+            let height = wallet::restore_height_for_date(session.daemon, date).await?;
+            println!("Restore date {date} is around block {height}.");
+            Some(height)
+        }
+        (None, None) => None,
+    };
+    println!(
+        "Creating {}-of-{} multisig wallet \"{}\"...",
+        params.threshold, params.total, params.label
+    );
+
+    let info = wallet::prepare_multisig(session.rpc).await?;
+
+    let state = wallet::WalletState::Created {
+        wallet_path: config.data_dir.file("wallet"),
+        params: wallet::SerializableParams::from(&params),
+        restore_height,
+    };
+    let mut file = wallet::StateFile {
+        created_at: Some(chrono::Utc::now()),
+        escrow,
+        ..wallet::StateFile::new(state)
+    };
+    file.record_contribution(wallet::ME, 1, &info);
+    file.prepared_info = Some(info.clone());
+    wallet::save_state(&config.data_dir, &file)?;
+    Ok(info)
+}
+
+/// The first-round multisig info of the wallet `state` describes, for a
+/// re-run of `create-wallet` with the same parameters. State files from
+/// before the info was kept get it from `prepare_multisig` again, unless key
+/// exchange has already moved the wallet RPC past it.
+async fn resume_create(session: &Session<'_>, state: &mut wallet::StateFile) -> Result<String> {
+    println!(
+        "Wallet \"{}\" already exists with these parameters ({}); nothing was changed.",
+        state.wallet.label(),
+        state.wallet.stage()
+    );
+    if let Some(info) = &state.prepared_info {
+        return Ok(info.clone());
+    }
+    if wallet::is_multisig(session.rpc).await?.multisig {
+        anyhow::bail!(
+            "this state file predates keeping the first-round multisig info, and the wallet \
+             RPC is past prepare_multisig, so it can't be shown again"
+        );
+    }
+    let info = wallet::prepare_multisig(session.rpc).await?;
+    state.prepared_info = Some(info.clone());
+    wallet::save_state(&session.config.data_dir, state)?;
+    Ok(info)
+}
+
+/// The state file, or `None` when there is no wallet yet.
+fn load_state_if_any(config: &Config) -> Result<Option<wallet::StateFile>> {
+    match wallet::load_state(&config.data_dir) {
+        Ok(state) => Ok(Some(state)),
+        Err(MultisigError::Wallet(WalletError::NotFound(_))) => Ok(None),
+        Err(e) => Err(anyhow::Error::from(e).context("load wallet state")),
+    }
+}
+
+/// What this machine has of what a bundle carries.
+async fn local_bundle_share(session: &Session<'_>, roster: Vec<String>) -> Result<bundle::Local> {
+    Ok(bundle::Local {
+        address_book: wallet::address_book(session.rpc).await?,
+        roster,
+        allowed_destinations: session.config.policy.allowed_destinations.clone(),
+        profile: bundle::profile(session.config),
+    })
+}
+
+/// `bundle export`: the roster goes out as the whole group, `from` first.
+async fn export_bundle(
+    session: &Session<'_>,
+    out: &std::path::Path,
+    from: Option<String>,
+) -> Result<()> {
+    let secret = manifest_secret(session.config)?;
+    let roster = load_state_if_any(session.config)?
+        .map(|state| state.roster().to_vec())
+        .unwrap_or_default();
+    let participants = match from {
+        _ if roster.is_empty() => Vec::new(),
+        Some(name) if name == wallet::ME || roster.contains(&name) => {
+            anyhow::bail!("--from {name} names a co-signer; give your own name in the group")
+        }
+        Some(name) => std::iter::once(name)
+            .chain(roster.iter().cloned())
+            .collect(),
+        None => anyhow::bail!(
+            "this wallet has a roster ({}); name yourself with --from so co-signers' machines \
+             can list you",
+            roster.join(", ")
+        ),
+    };
+    let local = local_bundle_share(session, roster).await?;
+    let bundle = bundle::Bundle::new(participants, &local, secret);
+    bundle::write_bundle(out, &bundle).with_context(|| format!("write {}", out.display()))?;
+    println!("Bundle written to {}:", out.display());
+    println!("  Address book:  {} entries", bundle.address_book.len());
+    println!(
+        "  Roster:        {}",
+        if bundle.participants.is_empty() {
+            "none".to_string()
+        } else {
+            bundle.participants.join(", ")
+        }
+    );
+    println!(
+        "  Allowlist:     {}",
+        bundle
+            .allowed_destinations
+            .as_ref()
+            .map_or("none".to_string(), |list| format!(
+                "{} addresses",
+                list.len()
+            ))
+    );
+    println!("  Settings:      {}", bundle.profile.len());
+    Ok(())
+}
+
+/// `bundle import`: verify, diff, settle conflicts, confirm, apply.
+async fn import_bundle(
+    session: &Session<'_>,
+    file: &std::path::Path,
+    me: Option<&str>,
+    conflicts: Option<ConflictChoice>,
+    yes: bool,
+) -> Result<()> {
+    let config = session.config;
+    let secret = manifest_secret(config)?;
+    let bundle =
+        bundle::read_bundle(file).with_context(|| format!("read bundle {}", file.display()))?;
+    // A bundle that fails its HMAC isn't looked at any further.
+    bundle.verify(secret).map_err(MultisigError::from)?;
+    println!(
+        "Bundle from {}, written {}, HMAC good.",
+        bundle
+            .participants
+            .first()
+            .map_or("a co-signer", String::as_str),
+        bundle.created_at.format("%Y-%m-%d %H:%M UTC")
+    );
+
+    let mut state = load_state_if_any(config)?;
+    let roster = state
+        .as_ref()
+        .map(|state| state.roster().to_vec())
+        .unwrap_or_default();
+    let me = match &state {
+        Some(_) => bundle.whoami(&roster, me).map_err(MultisigError::from)?,
+        None => None,
+    };
+    if me.is_none() && state.is_some() && !bundle.participants.is_empty() {
+        println!(
+            "The bundle's roster ({}) is left out; pass --as with your name in it to take it.",
+            bundle.participants.join(", ")
+        );
+    }
+    let local = local_bundle_share(session, roster).await?;
+    let changes = bundle.changes(&local, me.as_deref());
+    if changes.is_empty() {
+        println!("Nothing to import: this machine already matches the bundle.");
+        return Ok(());
+    }
+    let writes_config = changes.iter().any(|c| {
+        matches!(
+            c,
+            bundle::Change::Allowlist { .. } | bundle::Change::Setting { .. }
+        )
+    });
+    if writes_config && session.config_path.is_none() {
+        anyhow::bail!(
+            "the bundle changes config settings, but no config file was given to write them to \
+             — pass --config"
+        );
+    }
+
+    println!("\nChanges (- this machine, + bundle):");
+    for change in &changes {
+        println!("{change}");
+    }
+    let mut accepted = Vec::new();
+    for change in &changes {
+        let take = !change.is_conflict()
+            || match conflicts {
+                Some(choice) => choice == ConflictChoice::TakeRemote,
+                None => take_remote(change),
+            };
+        if take {
+            accepted.push(change);
+        }
+    }
+    *session.audit_summary.borrow_mut() = Some(serde_json::json!({
+        "bundle": file.display().to_string(),
+        "changes": accepted.iter().map(|c| c.to_string()).collect::<Vec<_>>(),
+    }));
+    if accepted.is_empty() {
+        println!("Nothing to apply: every conflict keeps this machine's side.");
+        return Ok(());
+    }
+    if !yes
+        && !utils::confirm(&messages::format(
+            Msg::ConfirmApplyBundle,
+            &[("count", &accepted.len())],
+        ))
+    {
+        anyhow::bail!("aborted; nothing was imported");
+    }
+
+    for change in &accepted {
+        match change {
+            bundle::Change::AddEntry(entry) => {
+                wallet::add_address_book(session.rpc, &entry.address, &entry.name).await?;
+            }
+            bundle::Change::EntryConflict { index, remote, .. } => {
+                wallet::set_address_book_address(session.rpc, *index, remote).await?;
+            }
+            bundle::Change::SetRoster(remote) | bundle::Change::RosterConflict { remote, .. } => {
+                let state = state.as_mut().expect("a roster change needs a state file");
+                state.set_roster(remote.clone())?;
+                wallet::save_state(&config.data_dir, state)?;
+            }
+            bundle::Change::Allowlist { .. } | bundle::Change::Setting { .. } => {}
+        }
+    }
+    if writes_config {
+        let path = session.config_path.expect("checked above");
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("read config {}", path.display()))?;
+        let mut file: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&text)?;
+        bundle::apply_to_config(&mut file, &accepted);
+        let file = serde_json::Value::Object(file);
+        // Refuse to leave a config behind that won't load.
+        serde_json::from_value::<Config>(file.clone())
+            .context("the bundle's settings don't make a valid config")?;
+        utils::write_atomic(path, serde_json::to_string_pretty(&file)?.as_bytes())?;
+    }
+    println!("Applied {} change(s).", accepted.len());
+    Ok(())
+}
+
+/// Ask whether to take the bundle's side of a conflict. A closed stdin
+/// keeps this machine's side.
+fn take_remote(change: &bundle::Change) -> bool {
+    let catalog = messages::catalog();
+    println!("\nConflict:\n{change}");
+    loop {
+        let Some(answer) = utils::ask_line(&messages::text(Msg::PromptConflict)) else {
+            return false;
+        };
+        if catalog.is_answer(Msg::PromptTakeAnswers, &answer) {
+            return true;
+        }
+        if catalog.is_answer(Msg::PromptKeepAnswers, &answer) {
+            return false;
+        }
+    }
+}
+
+async fn run(command: Command, session: &Session<'_>) -> Result<()> {
+    let Session {
+        config,
+        rpc,
+        output,
+        ..
+    } = *session;
+
+    if command.uses_wallet() {
+        session.check_wallet_address().await?;
+    }
+
+    match command {
+        Command::CreateWallet {
+            threshold,
+            participants,
+            label,
+            roster,
+            restore_height,
+            restore_date,
+        } => {
+            let params = wallet::MultisigParams::new(threshold, participants, label)
+                .inspect_err(|e| {
+                    if matches!(e, MultisigError::Wallet(WalletError::InvalidParams(_))) {
+                        eprintln!(
+                            "Valid parameters: {} <= threshold (-m) <= participants (-n) <= {}.",
+                            wallet::MIN_THRESHOLD,
+                            wallet::MAX_PARTICIPANTS
+                        );
+                    }
+                })?
+                .with_roster(roster)?;
+            let info = create_wallet(session, params, restore_height, restore_date, None).await?;
+
+            println!("\nYour multisig info (share with all other participants):\n");
+            println!("{info}\n");
+            print_fingerprint(config, "Info", &info);
+        }
+
+        Command::ExchangeKeys {
+            info,
+            expect_fingerprint,
+            from,
+            password,
+            isolate_bad_blob,
+            interactive_rounds,
+        } => {
+            let mut state = wallet::load_state(&config.data_dir).context("load wallet state")?;
+            let password =
+                credentials::wallet_password(password, state.wallet.label(), config.use_keyring)
+                    .map_err(MultisigError::from)?;
+            // Later interactive rounds reuse it rather than asking again.
+            let password_flag = Some(password.expose().clone());
+            let info = match state.wallet.next_round() {
+                Some(round) if interactive_rounds && info.is_empty() => {
+                    prompt_round_info(config, &state, round, &from)?
+                }
+                _ => info,
+            };
+
+            if !expect_fingerprint.is_empty() && expect_fingerprint.len() != info.len() {
+                anyhow::bail!(
+                    "got {} --expect-fingerprint values for {} --info blobs; pass one per blob",
+                    expect_fingerprint.len(),
+                    info.len()
+                );
+            }
+            if !from.is_empty() && from.len() != info.len() {
+                anyhow::bail!(
+                    "got {} --from names for {} --info blobs; pass one per blob",
+                    from.len(),
+                    info.len()
+                );
+            }
+            let info = info
+                .iter()
+                .enumerate()
+                .map(|(i, blob)| normalize_input(blob, None, &format!("peer info #{}", i + 1)))
+                .collect::<Result<Vec<_>>>()?;
+            let checks =
+                state.check_peer_info(&info, &from, &expect_fingerprint, config.max_blob_bytes);
+            print!(
+                "{}",
+                peer_info_table(config, &info, &checks).render(&session.style)
+            );
+            if checks
+                .iter()
+                .any(|c| matches!(c.problem, Some(wallet::PeerInfoProblem::Mismatch { .. })))
+            {
+                eprintln!("\n!!! WARNING: peer info does not match who sent it before !!!\n");
+            }
+            wallet::peer_info_verdict(&checks)?;
+
+            let mut retrying = false;
+            if let Some(attempt) = state.exchange_attempt.clone() {
+                println!(
+                    "Round {} was interrupted at {}; checking the wallet RPC...",
+                    attempt.round,
+                    attempt.started_at.format("%Y-%m-%d %H:%M UTC")
+                );
+                let repair = wallet::repair(rpc, &mut state, false).await?;
+                wallet::save_state(&config.data_dir, &state)?;
+                print_repair(&repair);
+                if !repair.unresolved.is_empty() {
+                    anyhow::bail!("the state file and the wallet RPC disagree; see above");
+                }
+                let same = attempt.same_inputs(&info);
+                if repair.undecided_round.is_some() {
+                    if !same {
+                        anyhow::bail!(
+                            "round {} is undecided; retry it with the same peer info first",
+                            attempt.round
+                        );
+                    }
+                    retrying = true;
+                }
+                let went_through = state
+                    .wallet
+                    .next_round()
+                    .is_none_or(|next| next > attempt.round);
+                if went_through && same {
+                    match &state.wallet {
+                        wallet::WalletState::Ready { address, .. } => {
+                            println!("\nMultisig wallet is ready!");
+                            println!("Address: {address}");
+                        }
+                        _ => println!(
+                            "\nRound {} already went through with this peer info; move on to \
+                             the next round.",
+                            attempt.round
+                        ),
+                    }
+                    return Ok(());
+                }
+            }
+
+            let params = match &state.wallet {
+                wallet::WalletState::Created { params, .. }
+                | wallet::WalletState::KeyExchangeInProgress { params, .. } => params.clone(),
+                wallet::WalletState::Ready { .. } => {
+                    anyhow::bail!("wallet is already fully set up");
+                }
+                wallet::WalletState::WatchOnly { .. } => {
+                    return Err(MultisigError::from(WalletError::WatchOnly).into());
+                }
+            };
+
+            // Rounds keep to the API the first one used, even if the wallet
+            // RPC is upgraded part way through.
+            let api = match state.multisig_api {
+                Some(api) => api,
+                None => rpc.capabilities().await?.multisig_api_generation,
+            };
+            state.multisig_api = Some(api);
+            wallet::check_network(rpc, config.network).await?;
+            let round = state.wallet.next_round().unwrap_or(1);
+            state.begin_exchange(round, &info);
+            wallet::save_state(&config.data_dir, &state)?;
+
+            println!("Performing key exchange round {round}...");
+            let result = wallet::exchange_keys(rpc, &info, &params, round, api, &password).await;
+            let result = match result {
+                Err(e) if retrying => {
+                    return Err(anyhow::Error::from(e).context(format!(
+                        "retrying round {round} failed; if it went through before the \
+                         interruption, run `repair-state --round-done`"
+                    )));
+                }
+                Err(MultisigError::Rpc(e)) if info.len() > 1 && !isolate_bad_blob => {
+                    return Err(anyhow::Error::from(MultisigError::Rpc(e)).context(
+                        "the wallet RPC rejected the round; run again with \
+                         --isolate-bad-blob to find which peer info it rejects",
+                    ));
+                }
+                Err(MultisigError::Rpc(e)) if isolate_bad_blob => {
+                    println!("The wallet RPC rejected the round; looking for the bad blob...");
+                    let found =
+                        wallet::isolate_bad_blob(rpc, &info, &params, round, api, &password)
+                            .await?;
+                    let Some(bad) = found else {
+                        return Err(anyhow::Error::from(MultisigError::Rpc(e))
+                            .context("the failure couldn't be traced to a single peer info blob"));
+                    };
+                    let mut checks = checks;
+                    checks[bad].problem = Some(wallet::PeerInfoProblem::Rejected(e.to_string()));
+                    return Err(wallet::peer_info_verdict(&checks).unwrap_err().into());
+                }
+                result => result?,
+            };
+
+            for (name, blob) in from.iter().zip(&info) {
+                state.record_contribution(name, round, blob);
+            }
+            match result {
+                wallet::KeyExchangeResult::Partial { next_info } => {
+                    state.record_contribution(wallet::ME, round + 1, &next_info);
+                    state.complete_round(round, None);
+                    wallet::save_state(&config.data_dir, &state)?;
+
+                    println!("\nKey exchange round complete. More rounds needed.");
+                    println!("Share this info with peers for the next round:\n");
+                    println!("{next_info}\n");
+                    print_fingerprint(config, "Info", &next_info);
+                    metrics::KEY_EXCHANGE_ROUNDS.inc(&[]);
+                    let event = notify::Event::new(EventKind::KeyExchangeRoundComplete);
+                    send_notification(session, Some(event)).await;
+
+                    if interactive_rounds {
+                        if interrupt::requested() {
+                            println!(
+                                "Stopped after round {round}. To go on, run exchange-keys with \
+                                 the peers' round {} info.",
+                                round + 1
+                            );
+                            return Ok(());
+                        }
+                        let next = prompt_round_info(config, &state, round + 1, &from)?;
+                        println!();
+                        let exchange = Command::ExchangeKeys {
+                            info: next,
+                            expect_fingerprint: Vec::new(),
+                            from,
+                            password: password_flag,
+                            isolate_bad_blob,
+                            interactive_rounds,
+                        };
+                        return Box::pin(run(exchange, session)).await;
+                    }
+                }
+                wallet::KeyExchangeResult::Complete { address } => {
+                    state.complete_round(round, Some(address.clone()));
+                    wallet::save_state(&config.data_dir, &state)?;
+
+                    println!("\nMultisig wallet is ready!");
+                    println!("Address: {address}");
+                    print_fingerprint(config, "Address", &address);
+                    metrics::KEY_EXCHANGE_ROUNDS.inc(&[]);
+                    let event = notify::Event {
+                        address: Some(address),
+                        ..notify::Event::new(EventKind::KeyExchangeRoundComplete)
+                    };
+                    send_notification(session, Some(event)).await;
+                }
+            }
+        }
+
+        Command::ExportInfo { from, force } => {
+            let mut state = match wallet::load_state(&config.data_dir) {
+                Ok(state) => Some(state),
+                Err(MultisigError::Wallet(WalletError::NotFound(_))) => {
+                    tracing::warn!("no local wallet state; export not recorded");
+                    None
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            // Telling whether the wallet changed takes a call the offline
+            // guard doesn't allow; without an answer, export afresh.
+            let height = if state.is_some() && !rpc.is_offline() {
+                match wallet::get_height(rpc).await {
+                    Ok(height) => Some(height),
+                    Err(e) => {
+                        tracing::debug!(error = %e, "can't tell whether the wallet changed since the last export");
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            let last_tx_activity = Journal::load(&config.data_dir)?
+                .entries
+                .iter()
+                .map(|e| e.last_action_at())
+                .max();
+            let reused = match (&state, height) {
+                (Some(state), Some(height)) if !force => state
+                    .reusable_export(height, last_tx_activity)
+                    .map(|(record, info)| (record.clone(), info.to_string())),
+                _ => None,
+            };
+
+            let (info, record) = match reused {
+                Some((record, info)) => (info, Some(record)),
+                None => {
+                    let info = transaction::export_multisig_info(rpc).await?;
+                    if let Some(state) = state.as_mut() {
+                        state.record_export(&info, height);
+                        wallet::save_state(&config.data_dir, state)?;
+                    }
+                    (info, None)
+                }
+            };
+
+            println!("Multisig info (share with co-signers):\n");
+            println!(
+                "{}\n",
+                session.export_blob(&info, from, record.as_ref()).await?
+            );
+            print_fingerprint(config, "Info", &info);
+            if let Some(height) = record.and_then(|r| r.height) {
+                println!("(unchanged since height {height}; --force exports again)");
+            }
+        }
+
+        Command::ImportInfo {
+            info,
+            allow_reimport,
+        } => {
+            let mut state = match wallet::load_state(&config.data_dir) {
+                Ok(state) => Some(state),
+                Err(MultisigError::Wallet(WalletError::NotFound(_))) => None,
+                Err(e) => return Err(e.into()),
+            };
+
+            let headers: Vec<armor::ExportHeader> = info
+                .iter()
+                .map(|blob| {
+                    utils::find_armor(blob)
+                        .map(|a| armor::ExportHeader::parse(&a.headers))
+                        .unwrap_or_default()
+                })
+                .collect();
+            let info = info
+                .iter()
+                .enumerate()
+                .map(|(i, blob)| {
+                    let what = format!("multisig info #{}", i + 1);
+                    normalize_input(blob, Some(armor::BlobKind::MultisigInfo), &what)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            let mut accepted = Vec::new();
+            let mut accepted_digests = Vec::new();
+            for (i, blob) in info.iter().enumerate() {
+                let digest = utils::blob_digest(blob);
+                let decision = state
+                    .as_ref()
+                    .map(|s| s.classify_import(&digest))
+                    .unwrap_or(wallet::ImportDecision::Import);
+                let short = utils::fingerprint_with(blob, config.fingerprint_format);
+
+                match decision {
+                    wallet::ImportDecision::OwnExport => {
+                        println!("  #{} {short}: rejected — this is your own export", i + 1);
+                    }
+                    wallet::ImportDecision::AlreadyImported(at) if !allow_reimport => {
+                        println!(
+                            "  #{} {short}: skipped — already imported on {}",
+                            i + 1,
+                            at.format("%Y-%m-%d %H:%M UTC")
+                        );
+                    }
+                    _ => {
+                        accepted.push(blob.clone());
+                        accepted_digests.push((i, digest));
+                    }
+                }
+            }
+
+            if accepted.is_empty() {
+                anyhow::bail!("no new multisig info to import");
+            }
+
+            // Raw blobs say nothing about their age; armored ones from
+            // export-info carry the height they were exported at.
+            if accepted_digests
+                .iter()
+                .any(|(i, _)| headers[*i].height.is_some())
+            {
+                let height = wallet::get_height(rpc).await?;
+                for (i, _) in &accepted_digests {
+                    let header = &headers[*i];
+                    let Some(behind) = header.height.map(|h| height.saturating_sub(h)) else {
+                        continue;
+                    };
+                    if behind > transaction::STALE_EXPORT_BLOCKS {
+                        let from = header.from.as_deref().unwrap_or("the sender");
+                        println!(
+                            "  #{} {}: warning — exported at height {}, {behind} blocks behind \
+                             this wallet ({height}); if the balance looks wrong, ask {from} to \
+                             export again",
+                            i + 1,
+                            utils::fingerprint_with(&info[*i], config.fingerprint_format),
+                            height - behind,
+                        );
+                    }
+                }
+            }
+
+            let report = transaction::import_multisig_info(rpc, &accepted).await?;
+
+            // The report identifies blobs by their default fingerprint.
+            let index_of = |fingerprint: &str| {
+                accepted_digests
+                    .iter()
+                    .find(|(i, _)| utils::fingerprint(&info[*i]) == fingerprint)
+            };
+
+            if let Some(state) = state.as_mut() {
+                if !report.succeeded.is_empty() {
+                    for (_, digest) in report.succeeded.iter().filter_map(|f| index_of(f)) {
+                        state.record_import(digest);
+                    }
+                    let height = wallet::get_height(rpc).await?;
+                    let balance = transaction::total_balance(rpc).await?;
+                    state.record_sync(height, balance.balance.0);
+                    wallet::save_state(&config.data_dir, state)?;
+                }
+            }
+
+            for fingerprint in &report.succeeded {
+                if let Some((i, _)) = index_of(fingerprint) {
+                    println!(
+                        "  #{} {}: imported",
+                        i + 1,
+                        utils::fingerprint_with(&info[*i], config.fingerprint_format)
+                    );
+                }
+            }
+            for (fingerprint, error) in &report.failed {
+                let label = match index_of(fingerprint) {
+                    Some((i, _)) => format!(
+                        "#{} {}",
+                        i + 1,
+                        utils::fingerprint_with(&info[*i], config.fingerprint_format)
+                    ),
+                    None => fingerprint.clone(),
+                };
+                println!("  {label}: FAILED — {error}");
+            }
+            println!(
+                "Imported {} of {} multisig info blob(s); {} output(s) updated.",
+                report.succeeded.len(),
+                info.len(),
+                report.n_outputs
+            );
+            if !report.all_succeeded() {
+                anyhow::bail!(
+                    "{} multisig info blob(s) failed to import — ask those co-signers to \
+                     export again",
+                    report.failed.len()
+                );
+            }
+            println!("Balance is now synchronized.");
+        }
+
+        Command::InspectInfo { input } => {
+            let text = utils::read_multisig_data(Some(&input), config.max_blob_bytes)?;
+            let armored = utils::find_armor(&text);
+            let (blob, _) = utils::normalize_blob(&text, armored.as_ref().map(|a| a.kind))
+                .map_err(MultisigError::from)?;
+            let header = armored
+                .as_ref()
+                .map(|a| armor::ExportHeader::parse(&a.headers))
+                .unwrap_or_default();
+            let kind = match armored.as_ref().map(|a| a.kind) {
+                Some(armor::BlobKind::TxSet) => output::InfoKind::TxSet,
+                Some(armor::BlobKind::MultisigInfo) => output::InfoKind::MultisigInfo,
+                None if utils::validate_key_exchange_blob(&blob, config.max_blob_bytes).is_ok()
+                    && blob.starts_with("Multisig") =>
+                {
+                    output::InfoKind::KeyExchangeInfo
+                }
+                None if transaction::is_multisig_export(&blob) => output::InfoKind::MultisigInfo,
+                None if transaction::is_multisig_tx_set(&blob) => output::InfoKind::TxSet,
+                None => output::InfoKind::Unknown,
+            };
+
+            let digest = utils::blob_digest(&blob);
+            let state = match wallet::load_state(&config.data_dir) {
+                Ok(state) => Some(state),
+                Err(MultisigError::Wallet(WalletError::NotFound(_))) => None,
+                Err(e) => return Err(e.into()),
+            };
+            let (status, imported_at) = match state.as_ref().map(|s| s.classify_import(&digest)) {
+                None => (None, None),
+                Some(wallet::ImportDecision::Import) => (Some(output::InfoStatus::New), None),
+                Some(wallet::ImportDecision::OwnExport) => {
+                    (Some(output::InfoStatus::OwnExport), None)
+                }
+                Some(wallet::ImportDecision::AlreadyImported(at)) => {
+                    (Some(output::InfoStatus::Imported), Some(at))
+                }
+            };
+            let wallet_height = state
+                .as_ref()
+                .and_then(|s| s.last_sync.as_ref())
+                .map(|sync| sync.wallet_height);
+            let blocks_behind = header
+                .height
+                .zip(wallet_height)
+                .map(|(exported, ours)| ours.saturating_sub(exported));
+            let inspected = output::InspectedInfo {
+                kind,
+                armored: armored.is_some(),
+                fingerprint: utils::fingerprint_with(&blob, config.fingerprint_format),
+                digest,
+                length: blob.len(),
+                created_at: header.created,
+                export_height: header.height,
+                from: header.from,
+                wallet_height,
+                blocks_behind,
+                stale: blocks_behind.map(|b| b > transaction::STALE_EXPORT_BLOCKS),
+                status,
+                imported_at,
+            };
+            if output == OutputFormat::Json {
+                session.print_json(&inspected)?;
+            } else {
+                print_inspected_info(&inspected);
+            }
+        }
+
+        Command::Balance if session.accounts.len() > 1 => {
+            let mut table = Table::new(vec![
+                Column::right("Account"),
+                Column::right("Balance (XMR)"),
+                Column::right("Unlocked (XMR)"),
+                Column::right("Locked (XMR)"),
+            ]);
+            let mut balances = Vec::new();
+            for &account in &session.accounts {
+                let balance = transaction::get_balance(rpc, account).await?;
+                table.row(vec![
+                    account.to_string().into(),
+                    session.style.xmr(balance.balance.0).into(),
+                    session.style.xmr(balance.unlocked_balance.0).into(),
+                    session.style.xmr(balance.locked.0).into(),
+                ]);
+                balances.push(balance);
+            }
+            let sum = |part: fn(&transaction::Balance) -> transaction::Amount| {
+                transaction::Amount::checked_sum(balances.iter().map(part))
+                    .map_err(MultisigError::from)
+            };
+            table.row(vec![
+                "Total".into(),
+                session.style.xmr(sum(|b| b.balance)?.0).into(),
+                session.style.xmr(sum(|b| b.unlocked_balance)?.0).into(),
+                session.style.xmr(sum(|b| b.locked)?.0).into(),
+            ]);
+            print!("{}", table.render(&session.style));
+        }
+
+        Command::Balance => {
+            session.check_account(&[]).await?;
+            let balance = transaction::get_balance(rpc, session.account).await?;
+            println!("Account:          {}", session.account);
+            println!("Balance:          {}", balance.balance);
+            println!(
+                "Unlocked balance: {}  (spendable now)",
+                balance.unlocked_balance
+            );
+            if balance.locked.0 > 0 {
+                println!(
+                    "Locked:           {}  ({})",
+                    balance.locked,
+                    unlock_estimate(&balance)
+                );
+                println!(
+                    "\nLocked funds are outputs with fewer than 10 confirmations, including \
+                     change from\nrecent transactions, and young coinbase outputs. build-tx \
+                     can only spend the\nunlocked balance."
+                );
+            }
+        }
+
+        Command::Status => {
+            let state = wallet::load_state(&config.data_dir).context("load wallet state")?;
+            if output == OutputFormat::Json {
+                session.print_json(&state)?;
+            } else {
+                print_status(&state);
+            }
+        }
+
+        Command::VerifyTxSet { tx_in, manifest } => {
+            let tx_data = tx_input(config, None, Some(&tx_in))?;
+            let tx_data = unwrap_tx_set(config, &tx_data)?;
+            let manifest = read_verified_manifest(config, &manifest)?;
+            let descriptions = transaction::describe_if_supported(rpc, &tx_data)
+                .await?
+                .ok_or(MultisigError::from(ManifestError::Undecodable))?;
+            transaction::check_network(&descriptions, config.network)?;
+            let differences = manifest.compare(&descriptions);
+            if output == OutputFormat::Json {
+                session.print_json(&output::TxSetVerification {
+                    fingerprint: utils::fingerprint_with(&tx_data, config.fingerprint_format),
+                    matches: differences.is_empty(),
+                    manifest: &manifest,
+                    differences: &differences,
+                })?;
+            } else {
+                print_fingerprint(config, "Tx set", &tx_data);
+                println!(
+                    "Manifest written {}, HMAC good.",
+                    manifest.created_at.format("%Y-%m-%d %H:%M UTC")
+                );
+                if let Some(note) = &manifest.note {
+                    println!("  Note: {note}");
+                }
+            }
+            report_differences(&differences)?;
+            if output == OutputFormat::Text {
+                println!(
+                    "The tx set matches the manifest: {} destination(s), fee {}.",
+                    manifest.destinations.len(),
+                    transaction::Amount(manifest.fee)
+                );
+            }
+        }
+
+        Command::Describe { tx_data } => {
+            let tx_data = unwrap_tx_set(config, &tx_data)?;
+            let descriptions = transaction::describe_transfer(rpc, &tx_data).await?;
+            let own = wallet::own_addresses(rpc).await?;
+            let requested = requested_destinations(config, &tx_data, &descriptions);
+            let described = descriptions
+                .iter()
+                .map(|description| {
+                    let outputs = transaction::review_outputs(
+                        std::slice::from_ref(description),
+                        &own,
+                        &requested,
+                    );
+                    Ok(output::DescribedTransfer {
+                        description,
+                        leaving_amount: transaction::leaving_amount(&outputs)?,
+                        outputs,
+                    })
+                })
+                .collect::<std::result::Result<Vec<_>, TransactionError>>()
+                .map_err(MultisigError::from)?;
+            if output == OutputFormat::Json {
+                session.print_json(&described)?;
+            } else {
+                print_fingerprint(config, "Tx set", &tx_data);
+                for (i, d) in described.iter().enumerate() {
+                    println!("\nTransaction #{}:", i + 1);
+                    for o in &d.outputs {
+                        println!(
+                            "  {:<28} {:>20} XMR  to {}",
+                            o.kind.to_string(),
+                            session.style.xmr(o.amount),
+                            o.address
+                        );
+                    }
+                    println!(
+                        "  {:<28} {:>20} XMR",
+                        "fee",
+                        session.style.xmr(d.description.fee)
+                    );
+                    let total = d
+                        .leaving_amount
+                        .checked_add(transaction::Amount(d.description.fee))
+                        .map_err(MultisigError::from)?;
+                    println!(
+                        "  {:<28} {:>20} XMR",
+                        "total leaving the wallet",
+                        session.style.xmr(total.0)
+                    );
+                }
+                // Only the builder's journal knows the weight: the wallet
+                // RPC doesn't decode it from a tx set.
+                let journal = Journal::load(&config.data_dir).unwrap_or_default();
+                let built = journal
+                    .find(&tx_data, &[], Some(&descriptions))
+                    .map(|i| &journal.entries[i])
+                    .filter(|e| e.weight > 0);
+                println!();
+                match built {
+                    Some(e) => print_weight(transaction::Amount(e.fee), e.weight, e.size_bytes),
+                    None => {
+                        println!("  Weight: unknown here; the builder's build-tx output shows it")
+                    }
+                }
+            }
+        }
+
+        Command::History => {
+            session.check_account(&[]).await?;
+            let transfers = transaction::get_transfers(rpc, Some(session.account)).await?;
+            if output == OutputFormat::Json {
+                session.print_json(&transfers)?;
+            } else if transfers.is_empty() {
+                println!("No transfers yet in account {}.", session.account);
+            } else {
+                println!("Account {}\n", session.account);
+                let journal = Journal::load(&config.data_dir)?;
+                let txids: Vec<_> = transfers.iter().map(|t| t.txid.clone()).collect();
+                let wallet_notes = wallet_notes(rpc, &txids).await;
+                let mut conflicts = false;
+                let mut table = Table::new(vec![
+                    Column::left("Date"),
+                    Column::left("Type"),
+                    Column::right("Amount (XMR)"),
+                    Column::right("Confirmations"),
+                    Column::left("Tx hash").shrink(),
+                    Column::left("Note").shrink(),
+                ]);
+                for (t, wallet_note) in transfers.iter().zip(&wallet_notes) {
+                    let local = journal
+                        .find_by_hash(&t.txid)
+                        .and_then(|e| e.note.as_deref());
+                    let note = match journal::merge_notes(local, Some(wallet_note)) {
+                        Some(note) if note.conflict => {
+                            conflicts = true;
+                            format!("{} *", note.text)
+                        }
+                        Some(note) => note.text,
+                        None => String::new(),
+                    };
+                    let when = chrono::DateTime::from_timestamp(t.timestamp as i64, 0)
+                        .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_default();
+                    let kind = format!("{:?}", t.kind).to_lowercase();
+                    let kind = match t.kind {
+                        transaction::TransferKind::In => Cell::colored(kind, Color::Green),
+                        transaction::TransferKind::Pending | transaction::TransferKind::Pool => {
+                            Cell::colored(kind, Color::Yellow)
+                        }
+                        transaction::TransferKind::Failed => Cell::colored(kind, Color::Red),
+                        transaction::TransferKind::Out => kind.into(),
+                    };
+                    table.row(vec![
+                        when.into(),
+                        kind,
+                        session.style.xmr(t.amount).into(),
+                        t.confirmations.to_string().into(),
+                        t.txid.as_str().into(),
+                        note.into(),
+                    ]);
+                }
+                print!("{}", table.render(&session.style));
+                if conflicts {
+                    println!(
+                        "\n* The wallet has a different note; the journal's is shown. See \
+                         `note show`."
+                    );
+                }
+            }
+        }
+
+        Command::ShowTx { tx_hash } => {
+            session.check_account(&[]).await?;
+            let transfer =
+                transaction::get_transfer_by_txid(rpc, &tx_hash, session.account).await?;
+            let visibility = match &transfer {
+                Some(_) => None,
+                None => transaction::visibility(session.daemon, std::slice::from_ref(&tx_hash))
+                    .await?
+                    .pop(),
+            };
+            let journal = Journal::load(&config.data_dir)?;
+            let local = journal
+                .find_by_hash(&tx_hash)
+                .and_then(|e| e.note.as_deref());
+            let note = journal::merge_notes(local, transfer.as_ref().map(|t| t.note.as_str()));
+            if output == OutputFormat::Json {
+                session.print_json(&output::ShownTx {
+                    txid: &tx_hash,
+                    transfer: transfer.as_ref(),
+                    note: note.as_ref().map(|n| n.text.as_str()),
+                    visibility,
+                })?;
+            } else {
+                println!("Transaction {tx_hash}");
+                match &transfer {
+                    Some(t) => print_transfer_record(t, &session.style),
+                    None => {
+                        println!("  Not in this wallet.");
+                        if let Some(visibility) = visibility {
+                            println!("  Daemon:        {visibility}");
+                        }
+                    }
+                }
+                if let Some(note) = &note {
+                    let mark = if note.conflict { " *" } else { "" };
+                    println!("  Note:          {}{mark}", note.text);
+                    if note.conflict {
+                        println!(
+                            "\n* The wallet has a different note; the journal's is shown. See \
+                             `note show`."
+                        );
+                    }
+                }
+            }
+        }
+
+        Command::Incoming {
+            pool,
+            watch,
+            interval,
+        } => {
+            let interval = interval
+                .to_std()
+                .ok()
+                .filter(|d| !d.is_zero())
+                .context("--interval must be positive")?;
+            session.check_account(&[]).await?;
+            loop {
+                let mut payments = transaction::get_pool_payments(rpc).await?;
+                payments.retain(|p| p.subaddr_index.major == session.account);
+                let confirmed = if pool {
+                    Vec::new()
+                } else {
+                    let mut confirmed = transaction::get_incoming_transfers(rpc, session.account)
+                        .await?
+                        .into_iter()
+                        .filter(|t| t.kind == transaction::TransferKind::In)
+                        .collect::<Vec<_>>();
+                    confirmed.reverse();
+                    confirmed
+                };
+
+                if output == OutputFormat::Json {
+                    let result = output::IncomingResult {
+                        pool: &payments,
+                        confirmed: &confirmed,
+                    };
+                    if watch {
+                        println!("{}", output::to_json_line(session.command, &result)?);
+                    } else {
+                        session.print_json(&result)?;
+                    }
+                } else {
+                    if watch {
+                        println!("{}", chrono::Utc::now().format("%H:%M:%S"));
+                    }
+                    print_incoming(session, &payments, &confirmed, pool);
+                }
+                if !watch {
+                    break;
+                }
+                tokio::select! {
+                    _ = interrupt::interrupted() => break,
+                    _ = tokio::time::sleep(interval) => {}
+                }
+            }
+        }
+
+        Command::Monitor {
+            interval,
+            until_amount,
+            min_confirmations,
+            include_existing,
+            notify_url,
+            metrics_addr,
+        } => {
+            if let Some(addr) = metrics_addr {
+                let bound = metrics::serve(addr)
+                    .await
+                    .with_context(|| format!("binding metrics endpoint {addr}"))?;
+                eprintln!("Serving metrics at http://{bound}/metrics");
+            }
+            let interval = interval
+                .to_std()
+                .ok()
+                .filter(|d| !d.is_zero())
+                .context("--interval must be positive")?;
+            let webhook = notify_url.map(notify::Webhook::new);
+            let mut watcher = monitor::Monitor::default();
+
+            session.check_account(&[]).await?;
+            if !include_existing {
+                let existing = transaction::get_incoming_transfers(rpc, session.account).await?;
+                watcher.baseline(&existing);
+            }
+            if output != OutputFormat::Json {
+                println!(
+                    "Watching for incoming transfers to account {} (Ctrl+C to stop)...",
+                    session.account
+                );
+            }
+
+            // A busy wallet is just skipped until the next poll rather than
+            // waited out.
+            let rpc = &rpc.clone().with_busy_wait(std::time::Duration::ZERO);
+            loop {
+                match transaction::get_incoming_transfers(rpc, session.account).await {
+                    Ok(transfers) => {
+                        for event in watcher.observe(&transfers) {
+                            if output == OutputFormat::Json {
+                                println!("{}", output::to_json_line(session.command, &event)?);
+                            } else {
+                                println!(
+                                    "{}  {:>24}  subaddress {}/{}  {:>4} conf{}",
+                                    event.txid,
+                                    transaction::Amount(event.amount),
+                                    event.subaddr_index.major,
+                                    event.subaddr_index.minor,
+                                    event.confirmations,
+                                    if event.in_pool { "  (pool)" } else { "" }
+                                );
+                            }
+                            if let Some(webhook) = &webhook {
+                                if let Err(e) = webhook.send(&event).await {
+                                    eprintln!("warning: webhook for {} failed: {e}", event.txid);
+                                }
+                            }
+                        }
+                        if let Some(target) = until_amount {
+                            let received = watcher
+                                .received(min_confirmations)
+                                .map_err(MultisigError::from)?;
+                            if received >= transaction::Amount(target) {
+                                if output != OutputFormat::Json {
+                                    println!(
+                                        "Received {received} of {} target; done.",
+                                        transaction::Amount(target)
+                                    );
+                                }
+                                break;
+                            }
+                        }
+                    }
+                    Err(MultisigError::Rpc(e)) if e.is_busy() => {
+                        tracing::debug!(error = %e, "wallet busy, polling again later")
+                    }
+                    Err(e) => eprintln!("warning: poll failed, retrying: {e}"),
+                }
+                if metrics_addr.is_some() {
+                    match wallet::chain_info(session.daemon, rpc).await {
+                        Ok(chain) => metrics::HEIGHT_GAP.set(chain.wallet_behind() as f64),
+                        Err(MultisigError::Rpc(e)) if e.is_busy() => {
+                            tracing::debug!(error = %e, "wallet busy, heights not read")
+                        }
+                        Err(e) => tracing::warn!(error = %e, "could not read chain heights"),
+                    }
+                }
+
+                tokio::select! {
+                    _ = interrupt::interrupted() => break,
+                    _ = tokio::time::sleep(interval) => {}
+                }
+            }
+        }
+
+        Command::Config { .. } => unreachable!("config commands run before the wallet is chosen"),
+
+        Command::Credentials { action } => {
+            let (CredentialsCommand::Store { label } | CredentialsCommand::Clear { label }) =
+                &action;
+            let label = match label {
+                Some(label) => label.clone(),
+                None => wallet::load_wallet_state(&config.data_dir)
+                    .map(|s| s.label().to_string())
+                    .context("no wallet here to take the label from; pass --label")?,
+            };
+            match action {
+                CredentialsCommand::Store { .. } => {
+                    if !config.use_keyring {
+                        return Err(
+                            MultisigError::from(credentials::CredentialsError::Disabled).into()
+                        );
+                    }
+                    let password =
+                        credentials::prompt_password(&format!("Wallet password for \"{label}\": "))
+                            .map_err(MultisigError::from)?;
+                    credentials::store(&label, &password, config.use_keyring)
+                        .map_err(MultisigError::from)?;
+                    println!(
+                        "Stored the wallet password for \"{label}\" in the keyring (service \
+                         {}).",
+                        credentials::SERVICE
+                    );
+                }
+                CredentialsCommand::Clear { .. } => {
+                    if credentials::clear(&label, config.use_keyring)
+                        .map_err(MultisigError::from)?
+                    {
+                        println!("Removed the wallet password for \"{label}\" from the keyring.");
+                    } else {
+                        println!("No wallet password was stored for \"{label}\".");
+                    }
+                }
+            }
+        }
+
+        Command::Notifications {
+            action: NotificationsCommand::Test,
+        } => {
+            let notifier = notify::Notifier::new(&config.notifications, &config.data_dir)
+                .context("notifications.webhook_url is not set in the config")?;
+            let mut event = notify::Event::new(EventKind::Ping);
+            event.wallet_label = wallet::load_wallet_state(&config.data_dir)
+                .ok()
+                .map(|s| s.label().to_string());
+            notifier.ping(&event).await?;
+            println!("Ping delivered.");
+            let replayed = notifier.flush_spool().await?;
+            if replayed > 0 {
+                println!("Resent {replayed} spooled notification(s).");
+            }
+        }
+
+        Command::ListOutputs { all } => {
+            session.check_account(&[]).await?;
+            let outputs = transaction::list_outputs(rpc, session.account, !all).await?;
+            if output == OutputFormat::Json {
+                session.print_json(&outputs)?;
+            } else if outputs.is_empty() {
+                println!("No outputs in account {}.", session.account);
+            } else {
+                println!("Account {}\n", session.account);
+                let mut table = Table::new(vec![
+                    Column::right("Amount (XMR)"),
+                    Column::left("Status"),
+                    Column::left("Tx hash").shrink(),
+                ]);
+                for o in &outputs {
+                    let status = match (o.spent, o.frozen, o.unlocked) {
+                        (true, _, _) => Cell::colored("spent", Color::Dim),
+                        (_, true, _) => Cell::colored("frozen", Color::Yellow),
+                        (_, _, true) => Cell::colored("unlocked", Color::Green),
+                        _ => Cell::colored("locked", Color::Yellow),
+                    };
+                    table.row(vec![
+                        session.style.xmr(o.amount).into(),
+                        status,
+                        o.tx_hash.as_str().into(),
+                    ]);
+                }
+                print!("{}", table.render(&session.style));
+            }
+        }
+
+        Command::ExportViewkey => {
+            eprintln!(
+                "The private view key lets anyone holding it see every incoming payment \
+                 to this wallet. It cannot spend funds."
+            );
+            if !utils::confirm(&messages::text(Msg::ConfirmViewKey)) {
+                anyhow::bail!("aborted");
+            }
+            let key = wallet::export_view_key(rpc).await?;
+            println!("{}", key.expose());
+        }
+
+        Command::CreateWatchWallet {
+            viewkey,
+            address,
+            label,
+            filename,
+            password,
+            restore_height,
+        } => {
+            if wallet::wallet_exists(&config.data_dir) {
+                return Err(MultisigError::from(WalletError::AlreadyExists(
+                    config.data_dir.display().to_string(),
+                ))
+                .into());
+            }
+            transaction::validate_address(&address, config.network)?;
+            let viewkey = Secret::new(viewkey);
+            let password = credentials::wallet_password(password, &label, config.use_keyring)
+                .map_err(MultisigError::from)?;
+
+            println!("Creating watch-only wallet \"{label}\"...");
+            let address = wallet::create_watch_wallet(
+                rpc,
+                &filename,
+                &address,
+                &viewkey,
+                &password,
+                restore_height,
+            )
+            .await?;
+
+            let state = wallet::WalletState::WatchOnly {
+                wallet_path: config.data_dir.file(&filename),
+                address: address.clone(),
+                label,
+                restore_height: Some(restore_height),
+            };
+            let file = wallet::StateFile {
+                created_at: Some(chrono::Utc::now()),
+                ..wallet::StateFile::new(state)
+            };
+            wallet::save_state(&config.data_dir, &file)?;
+
+            println!("\nWatch-only wallet created.");
+            println!("Address: {address}");
+            print_fingerprint(config, "Address", &address);
+        }
+
+        Command::Adopt {
+            label,
+            roster,
+            filename,
+        } => {
+            if wallet::wallet_exists(&config.data_dir) {
+                return Err(MultisigError::from(WalletError::AlreadyExists(
+                    config.data_dir.display().to_string(),
+                ))
+                .into());
+            }
+            let label = label.unwrap_or_else(|| utils::ask(&messages::text(Msg::PromptLabel)));
+            if label.is_empty() {
+                anyhow::bail!("a wallet label is needed; pass --label");
+            }
+
+            println!("Checking the wallet open in the wallet RPC...");
+            let mut state =
+                wallet::adopt(rpc, config.data_dir.file(&filename), label, roster).await?;
+            wallet::save_state(&config.data_dir, &state)?;
+            // Start an empty journal unless one is already there.
+            Journal::load(&config.data_dir)?.save(&config.data_dir)?;
+
+            let wallet::WalletState::Ready {
+                address, params, ..
+            } = state.wallet.clone()
+            else {
+                unreachable!("adopt returns a ready wallet");
+            };
+            println!(
+                "\nAdopted {}-of-{} multisig wallet \"{}\".",
+                params.threshold, params.total, params.label
+            );
+            println!("Address: {address}");
+            print_fingerprint(config, "Address", &address);
+
+            // Balances are only right once multisig info has been swapped.
+            eprintln!();
+            if utils::confirm(&messages::text(Msg::ConfirmExportNow)) {
+                let info = transaction::export_multisig_info(rpc).await?;
+                state.record_export(&info, None);
+                wallet::save_state(&config.data_dir, &state)?;
+                println!("\nMultisig info (share with co-signers):\n");
+                println!("{}\n", session.export_blob(&info, None, None).await?);
+                print_fingerprint(config, "Info", &info);
+                println!("Then run import-info with the info each co-signer sends back.");
+            } else {
+                println!(
+                    "\nBefore building or signing, run export-info and import-info with your \
+                     co-signers."
+                );
+            }
+        }
+
+        Command::Address { all } => {
+            session.check_account(&[]).await?;
+            let address = wallet::account_address(rpc, session.account).await?;
+            let subaddresses = if all {
+                Some(wallet::subaddresses(rpc, session.account).await?)
+            } else {
+                None
+            };
+            if output == OutputFormat::Json {
+                session.print_json(&output::AddressResult {
+                    account: session.account,
+                    address,
+                    subaddresses,
+                })?;
+            } else if let Some(subaddresses) = subaddresses {
+                println!("Account {}\n", session.account);
+                for s in &subaddresses {
+                    let used = if s.used { "  (used)" } else { "" };
+                    println!("{:>4}  {}  {}{used}", s.address_index, s.address, s.label);
+                }
+            } else {
+                // Bare, so scripts can capture it; `--all` names the account.
+                println!("{address}");
+            }
+        }
+
+        Command::ListAccounts => {
+            let accounts = wallet::tagged_accounts(rpc, None).await?;
+            if output == OutputFormat::Json {
+                session.print_json(&accounts)?;
+            } else {
+                print!(
+                    "{}",
+                    accounts_table(session, &accounts).render(&session.style)
+                );
+            }
+        }
+
+        Command::Account { action } => match action {
+            AccountCommand::Tag { tag, description } => {
+                if matches!(tag.parse(), Ok(wallet::AccountSelector::Index(_))) {
+                    anyhow::bail!("a tag can't be a number: --account would read it as an index");
+                }
+                for &account in &session.accounts {
+                    wallet::check_account(rpc, account, &[]).await?;
+                }
+                wallet::tag_accounts(rpc, &tag, &session.accounts).await?;
+                if let Some(description) = &description {
+                    wallet::set_account_tag_description(rpc, &tag, description).await?;
+                }
+                println!(
+                    "Tagged account(s) {} \"{tag}\".",
+                    account_list(&session.accounts)
+                );
+                println!("Use them with --account {tag}.");
+            }
+            AccountCommand::Untag => {
+                for &account in &session.accounts {
+                    wallet::check_account(rpc, account, &[]).await?;
+                }
+                wallet::untag_accounts(rpc, &session.accounts).await?;
+                println!("Untagged account(s) {}.", account_list(&session.accounts));
+            }
+            AccountCommand::List { by_tag } => {
+                let accounts = wallet::tagged_accounts(rpc, by_tag.as_deref()).await?;
+                if output == OutputFormat::Json {
+                    session.print_json(&accounts)?;
+                } else {
+                    if let Some(tag) = &by_tag {
+                        let tags = wallet::account_tags(rpc).await?;
+                        match tags.iter().find(|t| t.tag == *tag) {
+                            Some(t) if !t.label.is_empty() => {
+                                println!("Tag \"{tag}\": {}\n", t.label)
+                            }
+                            _ => println!("Tag \"{tag}\"\n"),
+                        }
+                    }
+                    print!(
+                        "{}",
+                        accounts_table(session, &accounts).render(&session.style)
+                    );
+                }
+            }
+        },
+
+        Command::CreateAccount { label } => {
+            let (index, address) = wallet::create_account(rpc, &label).await?;
+            if output == OutputFormat::Json {
+                session.print_json(&output::NewAccount {
+                    account_index: index,
+                    address: &address,
+                    label: &label,
+                })?;
+            } else {
+                println!("Created account {index}.");
+                println!("Address: {address}");
+                println!("Use it with --account {index}.");
+            }
+        }
+
+        Command::VerifyAddress { peer_fingerprint } => {
+            let mut state = wallet::load_state(&config.data_dir).context("load wallet state")?;
+            let wallet::WalletState::Ready {
+                address, params, ..
+            } = &state.wallet
+            else {
+                return Err(WalletError::NotReady.into());
+            };
+
+            let rpc_address = wallet::get_address(rpc).await?;
+            if &rpc_address != address {
+                return Err(MultisigError::from(WalletError::AddressMismatch {
+                    state: address.clone(),
+                    rpc: rpc_address,
+                })
+                .into());
+            }
+
+            let report = wallet::AddressVerification::new(
+                address,
+                params,
+                &peer_fingerprint,
+                config.fingerprint_format,
+            );
+
+            if report.all_match() {
+                state.mark_verified();
+                wallet::save_state(&config.data_dir, &state)?;
+            }
+
+            if output == OutputFormat::Json {
+                session.print_json(&output::VerifyAddressResult {
+                    report: &report,
+                    verified: state.verified,
+                    verified_at: state.verified_at,
+                })?;
+            } else {
+                println!("Address:     {}", report.address);
+                println!("Fingerprint: {}", report.fingerprint);
+                println!("Wallet:      {}-of-{}", report.threshold, report.total);
+                for (i, peer) in report.peers.iter().enumerate() {
+                    let mark = if peer.matches { "match" } else { "MISMATCH" };
+                    println!("  peer #{}: {} — {mark}", i + 1, peer.provided);
+                }
+            }
+
+            if report.peers.is_empty() {
+                if output == OutputFormat::Text {
+                    println!(
+                        "\nRead your fingerprint to each co-signer, then re-run with \
+                         --peer-fingerprint for every fingerprint they read back."
+                    );
+                }
+            } else if !report.all_match() {
+                anyhow::bail!(
+                    "address verification failed — do NOT fund this wallet until every \
+                     participant reports the same fingerprint"
+                );
+            } else if output == OutputFormat::Text {
+                println!("\nAll peers match. Address marked as verified.");
+            }
+        }
+
+        Command::ListWalletFiles => {
+            let listed = wallet::rpc_wallet_files(rpc).await?;
+            let known = wallet::known_wallet_files(&config.data_dir);
+            let files = wallet::match_wallet_files(listed.as_deref(), &known);
+            if output == OutputFormat::Json {
+                session.print_json(&output::WalletFilesResult {
+                    listed_by_rpc: listed.is_some(),
+                    files: &files,
+                })?;
+            } else {
+                print_wallet_files(&files, listed.is_some(), &session.style);
+            }
+        }
+
+        Command::GetVersion => {
+            let capabilities = rpc.capabilities().await?;
+            if output == OutputFormat::Json {
+                session.print_json(&capabilities)?;
+            } else {
+                let yes_no = |b: bool| if b { "yes" } else { "no" };
+                println!("Wallet RPC version: {}", capabilities.version);
+                println!(
+                    "transfer_split:     {}",
+                    yes_no(capabilities.supports_transfer_split)
+                );
+                println!(
+                    "describe_transfer:  {}",
+                    yes_no(capabilities.supports_describe_transfer)
+                );
+                println!(
+                    "Multisig API:       {}",
+                    capabilities.multisig_api_generation
+                );
+            }
+        }
+
+        Command::Fees => {
+            let schedule = session.fee_schedule().await?;
+            session.check_daemon_trust(schedule.estimate.untrusted)?;
+            let levels: Vec<_> = [
+                transaction::Priority::Default,
+                transaction::Priority::Low,
+                transaction::Priority::Medium,
+                transaction::Priority::High,
+            ]
+            .into_iter()
+            .map(|priority| output::PriorityFee {
+                priority,
+                rate: schedule.rate(priority),
+                typical_fee: schedule.fee(priority, transaction::TYPICAL_TX_WEIGHT),
+            })
+            .collect();
+            if output == OutputFormat::Json {
+                session.print_json(&output::FeesResult {
+                    base_fee: schedule.estimate.fee,
+                    quantization_mask: schedule.estimate.quantization_mask,
+                    algorithm: schedule.algorithm,
+                    hard_fork: schedule.hard_fork,
+                    typical_weight: transaction::TYPICAL_TX_WEIGHT,
+                    levels,
+                })?;
+            } else {
+                let unit = match schedule.algorithm {
+                    transaction::FeeAlgorithm::PerKilobyte => "kB",
+                    _ => "byte",
+                };
+                println!(
+                    "Base fee:          {} piconero per {unit}",
+                    schedule.estimate.fee
+                );
+                println!("Quantization mask: {}", schedule.estimate.quantization_mask);
+                match schedule.hard_fork {
+                    Some(version) => println!("Hard fork:         {version}"),
+                    None => println!("Hard fork:         unknown; the daemon wouldn't say"),
+                }
+                println!();
+                let mut table = Table::new(vec![
+                    Column::left("Priority"),
+                    Column::right(&format!("Piconero/{unit}")),
+                    Column::right("Typical fee (XMR)"),
+                ]);
+                for level in &levels {
+                    table.row(vec![
+                        level.priority.to_string().into(),
+                        level.rate.to_string().into(),
+                        session.style.xmr(level.typical_fee.0).into(),
+                    ]);
+                }
+                print!("{}", table.render(&session.style));
+                println!(
+                    "\nTypical fee: a 2-input, 2-output transaction of {} bytes. The default \
+                     is charged as low unless the pool is backed up.",
+                    transaction::TYPICAL_TX_WEIGHT
+                );
+            }
+        }
+
+        Command::ChainInfo { fail_if_behind } => {
+            let chain = wallet::chain_info(session.daemon, rpc).await?;
+            session.check_daemon_trust(chain.untrusted)?;
+            if output == OutputFormat::Json {
+                session.print_json(&output::ChainInfoResult {
+                    chain: &chain,
+                    wallet_behind: chain.wallet_behind(),
+                })?;
+            } else {
+                println!("Daemon height:  {}", chain.daemon_height);
+                println!("Target height:  {}", chain.target_height);
+                println!(
+                    "Wallet height:  {} ({} behind)",
+                    chain.wallet_height,
+                    chain.wallet_behind()
+                );
+                println!("Difficulty:     {}", chain.difficulty);
+                println!(
+                    "Synchronized:   {}",
+                    if chain.synchronized { "yes" } else { "no" }
+                );
+                println!(
+                    "Trusted:        {}",
+                    if chain.untrusted { "no" } else { "yes" }
+                );
+            }
+            if let Some(limit) = fail_if_behind {
+                if chain.wallet_behind() > limit {
+                    anyhow::bail!(
+                        "wallet is {} blocks behind the daemon (limit {limit})",
+                        chain.wallet_behind()
+                    );
+                }
+            }
+        }
+
+        // Latency is the only check so far, so it runs with or without
+        // `--latency`.
+        Command::Doctor { latency: _ } => {
+            let report =
+                doctor::measure(rpc, session.daemon, session.account, &config.latency).await?;
+            if output == OutputFormat::Json {
+                session.print_json(&report)?;
+            } else {
+                println!(
+                    "Wallet RPC latency over {} calls of each method:",
+                    report.iterations
+                );
+                print!("{}", latency_table(&report.methods).render(&session.style));
+                println!(
+                    "Daemon synchronized: {}",
+                    match report.daemon_synchronized {
+                        Some(true) => "yes",
+                        Some(false) => "no",
+                        None => "unknown (the daemon didn't answer get_info)",
+                    }
+                );
+                if report.causes.is_empty() {
+                    println!("Every call is within its budget.");
+                } else {
+                    println!("Likely causes:");
+                    for cause in &report.causes {
+                        println!("  - {}", cause.advice());
+                    }
+                }
+            }
+        }
+
+        Command::Refresh { start_height } => {
+            let start_height = start_height.or_else(|| {
+                wallet::load_wallet_state(&config.data_dir)
+                    .ok()
+                    .and_then(|s| s.restore_height())
+            });
+            let result = wallet::refresh(rpc, start_height, None).await?;
+            println!(
+                "Refresh complete: {} new blocks scanned.",
+                result.blocks_fetched
+            );
+            if result.received_money {
+                println!("New incoming funds were found.");
+            }
+
+            // Note submitted transactions that are now mined, from any
+            // account.
+            match transaction::get_transfers(rpc, None).await {
+                Ok(transfers) => {
+                    let confirmed = update_journal(config, |j| {
+                        transfers
+                            .iter()
+                            .filter(|t| t.kind == transaction::TransferKind::Out && t.height > 0)
+                            .filter_map(|t| {
+                                let entry = j.record_confirmed(&t.txid, t.height)?;
+                                Some(notify::Event::for_entry(EventKind::TxConfirmed, entry))
+                            })
+                            .collect::<Vec<_>>()
+                    });
+                    for event in confirmed.unwrap_or_default() {
+                        println!("Confirmed: {}", event.tx_hashes.join(", "));
+                        send_notification(session, Some(event)).await;
+                    }
+                }
+                Err(e) => eprintln!("warning: could not check for confirmations: {e}"),
+            }
+        }
+
+        Command::RequestPayment {
+            amount,
+            description,
+            subaddress_label,
+        } => {
+            session.check_account(&[]).await?;
+            let (subaddress_index, address) = match &subaddress_label {
+                Some(label) => {
+                    let (index, address) =
+                        wallet::create_subaddress(rpc, session.account, label).await?;
+                    (Some(index), address)
+                }
+                None => (None, wallet::account_address(rpc, session.account).await?),
+            };
+            let request = uri::PaymentRequest {
+                address,
+                amount,
+                description,
+                recipient_name: None,
+            };
+            let link = uri::encode(&request);
+            if output == OutputFormat::Json {
+                session.print_json(&output::PaymentRequestResult {
+                    uri: &link,
+                    address: &request.address,
+                    amount: request.amount,
+                    subaddress_index,
+                })?;
+            } else {
+                if let Some(index) = subaddress_index {
+                    println!(
+                        "New subaddress {}/{index}: {}",
+                        session.account, request.address
+                    );
+                }
+                println!("{link}");
+            }
+        }
+
+        Command::BuildTx {
+            address,
+            amount,
+            uri,
+            interactive,
+            merge_duplicates,
+            allow_split,
+            subaddr_index,
+            priority,
+            note,
+            allow_stale_sync,
+            override_policy,
+            ignore_allowlist,
+            allow_sweep,
+            dry_run,
+            export_monero_format,
+            manifest_out,
+        } => {
+            let priority = match priority {
+                1 => transaction::Priority::Low,
+                2 => transaction::Priority::Medium,
+                3 => transaction::Priority::High,
+                _ => transaction::Priority::Default,
+            };
+
+            ensure_can_sign(config)?;
+            // Checked before building, so a missing secret costs nothing.
+            let manifest_secret = match manifest_out {
+                Some(_) => Some(manifest_secret(config)?),
+                None => None,
+            };
+            check_before_build(session, allow_stale_sync, dry_run).await?;
+
+            let (address, amount, note) = match uri {
+                Some(text) => {
+                    let request = uri::parse(&text).map_err(MultisigError::from)?;
+                    let amount = match (request.amount, amount.as_slice()) {
+                        (Some(asked), []) => asked.0,
+                        (None, [given]) => *given,
+                        (Some(_), _) => anyhow::bail!("the URI already has an amount"),
+                        (None, _) => anyhow::bail!("the URI has no amount; give one --amount"),
+                    };
+                    (
+                        vec![request.address],
+                        vec![amount],
+                        note.or(request.description),
+                    )
+                }
+                None if interactive => {
+                    if output == OutputFormat::Json {
+                        anyhow::bail!("--interactive needs text output");
+                    }
+                    let book = wallet::address_book(rpc).await.unwrap_or_else(|e| {
+                        tracing::debug!(error = %e, "no address book to complete names from");
+                        Vec::new()
+                    });
+                    let picked = prompt_destinations(config, &book, ignore_allowlist)?;
+                    let notes: Vec<_> = note
+                        .into_iter()
+                        .chain(picked.iter().filter_map(|(_, note)| note.clone()))
+                        .collect();
+                    (
+                        picked
+                            .iter()
+                            .map(|(d, _)| d.address().to_string())
+                            .collect(),
+                        picked.iter().map(|(d, _)| d.amount().0).collect(),
+                        (!notes.is_empty()).then(|| notes.join("; ")),
+                    )
+                }
+                None => (address, amount, note),
+            };
+            if address.len() != amount.len() {
+                anyhow::bail!(
+                    "{} --address but {} --amount; give one amount per address",
+                    address.len(),
+                    amount.len()
+                );
+            }
+            let mut destinations: Vec<_> = address
+                .into_iter()
+                .zip(amount)
+                .map(|(address, amount)| {
+                    transaction::Destination::new(
+                        &address,
+                        transaction::Amount(amount),
+                        config.network,
+                    )
+                })
+                .collect::<std::result::Result<_, _>>()
+                .map_err(MultisigError::from)?;
+            if merge_duplicates {
+                let duplicates = transaction::duplicate_destinations(&destinations);
+                if !duplicates.is_empty() {
+                    destinations = transaction::merge_duplicates(&destinations)?;
+                    println!("Merged repeated destinations: {}", duplicates.join(", "));
+                }
+            }
+            let rejected = config
+                .policy
+                .disallowed_destinations(destinations.iter().map(|d| d.address()), &[]);
+            enforce_allowlist(&rejected, ignore_allowlist)?;
+            check_fee_source(session).await?;
+
+            let source = transaction::Source {
+                account: session.account,
+                subaddresses: subaddr_index,
+            };
+            session.check_account(&source.subaddresses).await?;
+            let estimate = session.fee_estimate(priority).await;
+            if let Some(fee) = estimate {
+                println!(
+                    "Fee at {priority} priority: about {} XMR for a typical transaction \
+                     (see `fees`)",
+                    session.style.xmr(fee.0)
+                );
+            }
+            if interactive {
+                review_destinations(session, &destinations, estimate)?;
+            }
+            println!("Building unsigned multisig transaction from {source}...");
+            let unsigned = transaction::build_unsigned_tx(
+                rpc,
+                &destinations,
+                priority,
+                allow_split,
+                &source,
+                estimate,
+            )
+            .await?;
+            let total = transaction::Amount::checked_sum(destinations.iter().map(|d| d.amount()))
+                .map_err(MultisigError::from)?;
+            enforce_policy(config, total, unsigned.fee, override_policy)?;
+            // Checked after building so the real fee is included. A dry run
+            // spends nothing, so there is nothing to confirm.
+            if !allow_sweep && !dry_run {
+                let balance = transaction::get_balance(rpc, session.account).await?;
+                confirm_sweep(session, total, unsigned.fee, balance.balance)?;
+            }
+
+            if dry_run {
+                println!("\nDry run: the wallet would build this transaction:");
+            } else {
+                println!("\nTransaction built successfully:");
+            }
+            for hash in &unsigned.tx_hashes {
+                println!("  Hash: {hash}");
+            }
+            println!("  Fee:  {}", unsigned.fee);
+            print_weight(unsigned.fee, unsigned.weight, unsigned.size_bytes);
+            println!();
+            // A split transfer is already sized to fit by the wallet.
+            if unsigned.tx_hashes.len() == 1 && transaction::near_weight_limit(unsigned.weight) {
+                eprintln!(
+                    "warning: the transaction weighs {} of the {} a daemon will relay; build \
+                     it with fewer destinations, or consolidate small outputs first",
+                    unsigned.weight,
+                    transaction::MAX_TX_WEIGHT
+                );
+            }
+            // Addresses are shown whole: this is what the user checks before
+            // sharing the set.
+            let mut table = Table::new(vec![Column::left("To"), Column::right("Amount (XMR)")]);
+            for d in &destinations {
+                table.row(vec![
+                    d.address().into(),
+                    session.style.xmr(d.amount().0).into(),
+                ]);
+            }
+            print!("{}", table.render(&session.style));
+
+            if dry_run {
+                // The wallet RPC reports inputs only as a total. An unsigned
+                // multisig set reserves nothing in the wallet, so dropping it
+                // is all the discarding there is.
+                let descriptions = transaction::describe_transfer(rpc, &unsigned.tx_data_hex)
+                    .await
+                    .unwrap_or_default();
+                for d in &descriptions {
+                    println!(
+                        "\n  Inputs: {} XMR (ring size {})",
+                        session.style.xmr(d.amount_in),
+                        d.ring_size
+                    );
+                    if d.change_amount > 0 {
+                        println!(
+                            "  Change: {} XMR to {}",
+                            session.style.xmr(d.change_amount),
+                            d.change_address
+                        );
+                    } else {
+                        println!("  Change: none");
+                    }
+                }
+                println!("\nDry run: the tx set was discarded and nothing was recorded.");
+                return Ok(());
+            }
+            // The change isn't known until the set is built, so decode it the
+            // way a co-signer will, before anything is shown or recorded.
+            let manifest = match manifest_secret {
+                Some(secret) => {
+                    let descriptions =
+                        transaction::describe_if_supported(rpc, &unsigned.tx_data_hex)
+                            .await?
+                            .ok_or(MultisigError::from(ManifestError::Undecodable))?;
+                    Some(manifest::Manifest::new(
+                        &destinations,
+                        unsigned.fee,
+                        &descriptions,
+                        note.clone(),
+                        secret,
+                    )?)
+                }
+                None => None,
+            };
+            println!("\nMultisig tx set (share with co-signers):\n");
+            println!(
+                "{}\n",
+                session.blob(armor::BlobKind::TxSet, &unsigned.tx_data_hex)
+            );
+            print_fingerprint(config, "Tx set", &unsigned.tx_data_hex);
+            if let Some(path) = &export_monero_format {
+                let file = transaction::to_monero_file(&unsigned.tx_data_hex)?;
+                utils::write_atomic(path, &file)
+                    .with_context(|| format!("writing {}", path.display()))?;
+                println!(
+                    "Wrote the tx set for the Monero GUI/CLI wallet to {}",
+                    path.display()
+                );
+            }
+            if let (Some(path), Some(manifest)) = (&manifest_out, &manifest) {
+                manifest::write_manifest(path, manifest)
+                    .with_context(|| format!("writing {}", path.display()))?;
+                println!(
+                    "Wrote the manifest to {}; send it with the tx set.",
+                    path.display()
+                );
+            }
+
+            metrics::TX_SETS.inc(&["built"]);
+            let event = update_journal(config, |j| {
+                let entry = j.record_built(
+                    &destinations,
+                    unsigned.fee.0,
+                    &unsigned.tx_hashes,
+                    &unsigned.tx_data_hex,
+                    signatures_required(config),
+                    note,
+                );
+                let event = notify::Event::for_entry(EventKind::TxBuilt, entry);
+                j.record_size(&unsigned.tx_data_hex, unsigned.weight, unsigned.size_bytes);
+                j.record_priority(&unsigned.tx_data_hex, priority);
+                event
+            });
+            send_notification(session, event).await;
+        }
+
+        Command::BuildBatch {
+            file,
+            max_per_tx,
+            out_dir,
+            priority,
+            allow_stale_sync,
+            allow_sweep,
+        } => {
+            let priority = match priority {
+                1 => transaction::Priority::Low,
+                2 => transaction::Priority::Medium,
+                3 => transaction::Priority::High,
+                _ => transaction::Priority::Default,
+            };
+            if max_per_tx == 0 {
+                anyhow::bail!("--max-per-tx must be at least 1");
+            }
+
+            let text = std::fs::read_to_string(&file)
+                .with_context(|| format!("read {}", file.display()))?;
+            let payouts = payout::parse(
+                &text,
+                payout::Format::from_path(&file),
+                config.network,
+                &config.policy,
+            )
+            .map_err(MultisigError::from)?;
+            let grand_total =
+                transaction::Amount::checked_sum(payouts.iter().map(|p| p.destination.amount()))
+                    .map_err(MultisigError::from)?;
+            let groups = payout::group(&payouts, max_per_tx);
+            println!(
+                "{} payouts totalling {grand_total} in {} transaction(s).",
+                payouts.len(),
+                groups.len()
+            );
+
+            ensure_can_sign(config)?;
+            check_before_build(session, allow_stale_sync, false).await?;
+            check_fee_source(session).await?;
+            let source = transaction::Source {
+                account: session.account,
+                subaddresses: Vec::new(),
+            };
+            session.check_account(&[]).await?;
+            if !allow_sweep {
+                let balance = transaction::get_balance(rpc, session.account).await?;
+                confirm_sweep(
+                    session,
+                    grand_total,
+                    transaction::Amount(0),
+                    balance.balance,
+                )?;
+            }
+
+            std::fs::create_dir_all(&out_dir)
+                .with_context(|| format!("create {}", out_dir.display()))?;
+            let stem = file
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "batch".into());
+
+            let mut table = Table::new(vec![
+                Column::right("Set"),
+                Column::left("Rows"),
+                Column::right("Amount (XMR)"),
+                Column::right("Fee (XMR)"),
+                Column::left("File"),
+            ]);
+            let mut built_total = transaction::Amount(0);
+            let mut fee_total = transaction::Amount(0);
+            let mut built_sets = 0;
+            let mut failed: Vec<(usize, Vec<usize>, String)> = Vec::new();
+            // Inputs spent by the sets built so far are frozen, so each set
+            // spends different ones, and thawed once the batch is done.
+            let mut frozen = Vec::new();
+            let mut may_share_inputs = false;
+            let estimate = session.fee_estimate(priority).await;
+            let batch = async {
+                for (i, group) in groups.iter().enumerate() {
+                    let set = i + 1;
+                    let rows: Vec<usize> = group.iter().map(|p| p.row).collect();
+                    let destinations: Vec<_> =
+                        group.iter().map(|p| p.destination.clone()).collect();
+                    let amount = transaction::Amount::checked_sum(
+                        group.iter().map(|p| p.destination.amount()),
+                    )
+                    .map_err(MultisigError::from)?;
+
+                    let built = async {
+                        let unsigned = transaction::build_unsigned_tx(
+                            rpc,
+                            &destinations,
+                            priority,
+                            true,
+                            &source,
+                            estimate,
+                        )
+                        .await?;
+                        enforce_policy(config, amount, unsigned.fee, false)?;
+                        let path = out_dir.join(format!("{stem}-{set}.txset"));
+                        let blob = session.blob(armor::BlobKind::TxSet, &unsigned.tx_data_hex);
+                        utils::write_atomic(&path, format!("{blob}\n").as_bytes())
+                            .with_context(|| format!("write {}", path.display()))?;
+                        anyhow::Ok((unsigned, path))
+                    }
+                    .await;
+                    let (unsigned, path) = match built {
+                        Ok(built) => built,
+                        Err(e) => {
+                            failed.push((set, rows, format!("{e:#}")));
+                            continue;
+                        }
+                    };
+
+                    built_sets += 1;
+                    built_total = built_total
+                        .checked_add(amount)
+                        .map_err(MultisigError::from)?;
+                    fee_total = fee_total
+                        .checked_add(unsigned.fee)
+                        .map_err(MultisigError::from)?;
+                    table.row(vec![
+                        set.to_string().into(),
+                        join_rows(&rows).into(),
+                        session.style.xmr(amount.0).into(),
+                        session.style.xmr(unsigned.fee.0).into(),
+                        path.display().to_string().into(),
+                    ]);
+
+                    metrics::TX_SETS.inc(&["built"]);
+                    let event = update_journal(config, |j| {
+                        let entry = j.record_built(
+                            &destinations,
+                            unsigned.fee.0,
+                            &unsigned.tx_hashes,
+                            &unsigned.tx_data_hex,
+                            signatures_required(config),
+                            payout::journal_note(group),
+                        );
+                        let event = notify::Event::for_entry(EventKind::TxBuilt, entry);
+                        j.record_size(&unsigned.tx_data_hex, unsigned.weight, unsigned.size_bytes);
+                        j.record_priority(&unsigned.tx_data_hex, priority);
+                        event
+                    });
+                    send_notification(session, event).await;
+                    if interrupt::requested() {
+                        let rest = groups[set..].iter().flatten().map(|p| p.row).collect();
+                        failed.push((set + 1, rest, "interrupted".into()));
+                        break;
+                    }
+                    if set == groups.len() || may_share_inputs {
+                        continue;
+                    }
+                    if unsigned.spent_key_images.is_empty() {
+                        may_share_inputs = true;
+                        eprintln!(
+                            "warning: the wallet RPC didn't say which inputs set {set} spends, so \
+                         later sets may spend them too"
+                        );
+                    } else if let Err(e) =
+                        transaction::freeze_outputs(rpc, &unsigned.spent_key_images, &mut frozen)
+                            .await
+                    {
+                        may_share_inputs = true;
+                        eprintln!(
+                            "warning: could not set aside the inputs of set {set}, so later sets \
+                         may spend them too: {e}"
+                        );
+                    }
+                }
+                anyhow::Ok(())
+            }
+            .await;
+            if let Err(e) = transaction::thaw_outputs(rpc, &frozen).await {
+                eprintln!(
+                    "warning: {} input(s) the batch set aside are still frozen in the wallet \
+                     (thaw them with the wallet RPC's `thaw`): {e}",
+                    frozen.len()
+                );
+            }
+            batch?;
+
+            if !table.is_empty() {
+                println!();
+                print!("{}", table.render(&session.style));
+                println!(
+                    "\nTotal: {built_total} plus {fee_total} in fees, in {built_sets} tx set(s)."
+                );
+                if built_total != grand_total {
+                    println!("The batch lists {grand_total}.");
+                }
+                if may_share_inputs {
+                    println!(
+                        "Some sets may spend the same inputs. Sign and submit them in order; \
+                         a set refused as a double spend must be built again."
+                    );
+                }
+            }
+            if !failed.is_empty() {
+                eprintln!("\nRows not included in any transaction:");
+                for (set, rows, error) in &failed {
+                    eprintln!("  set {set}, rows {}: {error}", join_rows(rows));
+                }
+                let count: usize = failed.iter().map(|(_, rows, _)| rows.len()).sum();
+                anyhow::bail!("{count} of {} payouts were not built", payouts.len());
+            }
+        }
+
+        Command::SignTx {
+            tx_data,
+            tx_file,
+            manifest,
+            override_policy,
+            ignore_allowlist,
+            allow_sweep,
+        } => {
+            ensure_can_sign(config)?;
+            let tx_data = tx_input(config, tx_data, tx_file.as_deref())?;
+            let tx_data = unwrap_tx_set(config, &tx_data)?;
+            print_fingerprint(config, "Incoming tx set", &tx_data);
+
+            // The policy and sweep checks need the decoded set; without them
+            // the description only links the journal.
+            let mut spend = None;
+            let described = transaction::describe_if_supported(rpc, &tx_data).await;
+            if let Ok(Some(descriptions)) = &described {
+                transaction::check_network(descriptions, config.network)?;
+            }
+            if let Some(path) = &manifest {
+                let manifest = read_verified_manifest(config, path)?;
+                let descriptions = match &described {
+                    Ok(Some(descriptions)) => descriptions,
+                    Ok(None) => return Err(MultisigError::from(ManifestError::Undecodable).into()),
+                    Err(e) => {
+                        anyhow::bail!("decode the tx set to check it against the manifest: {e}")
+                    }
+                };
+                report_differences(&manifest.compare(descriptions)).context("refusing to sign")?;
+                println!("The tx set matches the manifest.");
+            }
+            let description = if let Ok(None) = described {
+                if !config.policy.is_empty() {
+                    return Err(MultisigError::from(TransactionError::policy(
+                        "this wallet RPC has no describe_transfer, so the tx set can't be \
+                         checked against the policy — upgrade monero-wallet-rpc",
+                    ))
+                    .into());
+                }
+                eprintln!(
+                    "warning: this wallet RPC has no describe_transfer, so the tx set can't be \
+                     decoded: signing without reviewing its outputs or checking for a sweep"
+                );
+                None
+            } else if config.policy.is_empty() && allow_sweep {
+                described.ok().flatten()
+            } else {
+                let description = described?.unwrap_or_default();
+                let fee = transaction::total_fee(&description).map_err(MultisigError::from)?;
+
+                // Change and self-transfers stay in the wallet, so only
+                // payouts count against the policy.
+                let own = wallet::own_addresses(rpc).await?;
+                let requested = requested_destinations(config, &tx_data, &description);
+                let outputs = transaction::review_outputs(&description, &own, &requested);
+                let amount = transaction::leaving_amount(&outputs).map_err(MultisigError::from)?;
+
+                if config.policy.allowed_destinations.is_some() {
+                    let rejected = config
+                        .policy
+                        .disallowed_destinations(outputs.iter().map(|o| o.address.as_str()), &own);
+                    enforce_allowlist(&rejected, ignore_allowlist)?;
+                }
+                enforce_policy(config, amount, fee, override_policy)?;
+
+                if let Some(limit) = &config.policy.spend_limit {
+                    let ledger = SpendLedger::load(&config.data_dir)?;
+                    if let Err(exceeded) = ledger.check(limit, amount, &SystemClock) {
+                        return Err(MultisigError::from(TransactionError::policy(
+                            exceeded.to_string(),
+                        ))
+                        .into());
+                    }
+                    spend = Some((limit, ledger, amount));
+                }
+
+                // The signer's balance view may differ from the builder's, and
+                // the set doesn't say which account it spends from.
+                if !allow_sweep {
+                    let balance = transaction::total_balance(rpc).await?;
+                    confirm_sweep(session, amount, fee, balance.balance)?;
+                }
+                Some(description)
+            };
+
+            println!("Signing multisig transaction...");
+            let signed = transaction::sign_multisig_tx(rpc, &tx_data).await?;
+
+            // Count the signature before handing out the signed set, so a
+            // ledger that can't be written never lets a signature go uncounted.
+            if let Some((limit, mut ledger, external)) = spend {
+                ledger.record(limit, external, &signed.tx_hash, &SystemClock);
+                ledger.save(&config.data_dir)?;
+            }
+
+            println!("\nSignature applied:");
+            println!("  Hash: {}", signed.tx_hash);
+            println!("\nUpdated tx set (share with remaining co-signers or submit):\n");
+            println!(
+                "{}\n",
+                session.blob(armor::BlobKind::TxSet, &signed.tx_data_hex)
+            );
+            print_fingerprint(config, "Tx set", &signed.tx_data_hex);
+
+            metrics::TX_SETS.inc(&["signed"]);
+            let event = update_journal(config, |j| {
+                let entry = j.record_signed(
+                    &tx_data,
+                    &signed.tx_data_hex,
+                    std::slice::from_ref(&signed.tx_hash),
+                    description.as_deref(),
+                    signatures_required(config),
+                );
+                println!(
+                    "Journal #{}: {} of {} signatures collected.",
+                    entry.id, entry.signatures_collected, entry.signatures_required
+                );
+                notify::Event::for_entry(EventKind::TxSigned, entry)
+            });
+            send_notification(session, event).await;
+        }
+
+        Command::SignBatch {
+            in_dir,
+            yes,
+            allow_sweep,
+        } => {
+            ensure_can_sign(config)?;
+            // JSON output is for scripts, which nothing can ask.
+            let text = output == OutputFormat::Text;
+            if !text && !yes {
+                anyhow::bail!("sign-batch with --output json needs --yes");
+            }
+            let files = batch_inputs(&in_dir)?;
+            if files.is_empty() {
+                anyhow::bail!("no *.txset or *.asc files in {}", in_dir.display());
+            }
+            let own = wallet::own_addresses(rpc).await?;
+
+            // Review every set up front; sets that can't be decoded or break
+            // the policy are reported and left unsigned.
+            let mut items = Vec::new();
+            let mut ledger = SpendLedger::load(&config.data_dir)?;
+            let mut projected = ledger.clone();
+            for path in files {
+                let item = review_batch_item(session, &path, &own).await;
+                let item = match item {
+                    Ok(mut item) => {
+                        if let Some(limit) = &config.policy.spend_limit {
+                            match projected.check(limit, item.amount, &SystemClock) {
+                                Ok(()) => projected.record(limit, item.amount, "", &SystemClock),
+                                Err(exceeded) => item.problem = Some(exceeded.to_string()),
+                            }
+                        }
+                        item
+                    }
+                    Err(e) => BatchItem::failed(path, format!("{e:#}")),
+                };
+                items.push(item);
+            }
+
+            if text {
+                println!(
+                    "{:<24}  {:<19}  {:>20}  {:>16}",
+                    "Tx set", "Destination", "Amount (XMR)", "Fee (XMR)"
+                );
+                for item in &items {
+                    let name = item.name();
+                    match &item.problem {
+                        Some(problem) => println!("{name:<24}  SKIPPED: {problem}"),
+                        None => {
+                            // Change is left out of the table and the totals.
+                            let shown: Vec<_> = item
+                                .outputs
+                                .iter()
+                                .filter(|o| o.kind != transaction::OutputKind::Change)
+                                .collect();
+                            if shown.is_empty() {
+                                println!(
+                                    "{name:<24}  {:<19}  {:>20}  {:>16}",
+                                    "(change only)",
+                                    "",
+                                    session.style.xmr(item.fee.0)
+                                );
+                            }
+                            for (j, o) in shown.into_iter().enumerate() {
+                                let fee = if j == 0 {
+                                    session.style.xmr(item.fee.0)
+                                } else {
+                                    String::new()
+                                };
+                                println!(
+                                    "{:<24}  {:<19}  {:>20}  {fee:>16}",
+                                    if j == 0 { name.as_str() } else { "" },
+                                    utils::abbreviate_middle(&o.address, 8, 8),
+                                    session.style.xmr(o.amount)
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+            let ready: Vec<&BatchItem> = items.iter().filter(|i| i.problem.is_none()).collect();
+            let total_amount = transaction::Amount::checked_sum(ready.iter().map(|i| i.amount))
+                .map_err(MultisigError::from)?;
+            let total_fee = transaction::Amount::checked_sum(ready.iter().map(|i| i.fee))
+                .map_err(MultisigError::from)?;
+            if text {
+                println!(
+                    "{:<24}  {:<19}  {:>20}  {:>16}",
+                    format!("TOTAL ({} sets)", ready.len()),
+                    "",
+                    session.style.xmr(total_amount.0),
+                    session.style.xmr(total_fee.0)
+                );
+            }
+
+            if ready.is_empty() {
+                anyhow::bail!("none of the {} tx sets can be signed", items.len());
+            }
+            if !allow_sweep {
+                let balance = transaction::total_balance(rpc).await?;
+                confirm_sweep(session, total_amount, total_fee, balance.balance)?;
+            }
+            if !yes
+                && !utils::confirm(&messages::format(
+                    Msg::ConfirmSignSets,
+                    &[("count", &ready.len())],
+                ))
+            {
+                anyhow::bail!("aborted; nothing was signed");
+            }
+
+            let sets: Vec<String> = ready.iter().map(|i| i.tx_data.clone()).collect();
+            let results = transaction::sign_many(rpc, &sets).await;
+
+            let mut signed = Vec::new();
+            let mut failed: Vec<(String, String)> = items
+                .iter()
+                .filter_map(|i| Some((i.name(), i.problem.clone()?)))
+                .collect();
+            for (item, result) in ready.iter().zip(results) {
+                let outcome = match result {
+                    Ok(tx) => {
+                        if let Some(limit) = &config.policy.spend_limit {
+                            ledger.record(limit, item.amount, &tx.tx_hash, &SystemClock);
+                            ledger.save(&config.data_dir)?;
+                        }
+                        let out = item.output_path();
+                        std::fs::write(&out, session.blob(armor::BlobKind::TxSet, &tx.tx_data_hex))
+                            .map_err(MultisigError::from)
+                            .map(|()| (tx, out))
+                    }
+                    Err(e) => Err(e),
+                };
+                match outcome {
+                    Ok((tx, out)) => {
+                        if text {
+                            println!("  {}: signed → {}", item.name(), out.display());
+                        }
+                        metrics::TX_SETS.inc(&["signed"]);
+                        let event = update_journal(config, |j| {
+                            let entry = j.record_signed(
+                                &item.tx_data,
+                                &tx.tx_data_hex,
+                                std::slice::from_ref(&tx.tx_hash),
+                                item.description.as_deref(),
+                                signatures_required(config),
+                            );
+                            notify::Event::for_entry(EventKind::TxSigned, entry)
+                        });
+                        send_notification(session, event).await;
+                        signed.push(item.name());
+                    }
+                    Err(e) => {
+                        if text {
+                            println!("  {}: FAILED — {e}", item.name());
+                        }
+                        failed.push((item.name(), e.to_string()));
+                    }
+                }
+            }
+
+            if output == OutputFormat::Json {
+                session.print_json(&output::SignBatchResult {
+                    signed: signed.clone(),
+                    failed: failed
+                        .iter()
+                        .map(|(file, error)| output::BatchFailure {
+                            file: file.clone(),
+                            error: error.clone(),
+                        })
+                        .collect(),
+                })?;
+            } else {
+                println!(
+                    "\nSigned {} of {} tx set(s): {}",
+                    signed.len(),
+                    items.len(),
+                    if signed.is_empty() {
+                        "none".to_string()
+                    } else {
+                        signed.join(", ")
+                    }
+                );
+            }
+            if !failed.is_empty() {
+                anyhow::bail!("{} tx set(s) were not signed", failed.len());
+            }
+        }
+
+        Command::SubmitTx {
+            tx_data,
+            tx_file,
+            force,
+            no_submit,
+            out,
+            no_verify_broadcast,
+            yes,
+            auto_bump,
+        } => {
+            ensure_can_sign(config)?;
+            let tx_data = tx_input(config, tx_data, tx_file.as_deref())?;
+            let tx_data = unwrap_tx_set(config, &tx_data)?;
+            print_fingerprint(config, "Tx set", &tx_data);
+            let description = transaction::describe_transfer(rpc, &tx_data).await.ok();
+            check_not_discarded(config, &tx_data, description.as_deref(), force)?;
+
+            if no_submit {
+                let out = out.expect("clap requires --out with --no-submit");
+                let blob = session.blob(armor::BlobKind::TxSet, &tx_data);
+                utils::write_atomic(&out, format!("{blob}\n").as_bytes())
+                    .with_context(|| format!("writing {}", out.display()))?;
+                println!(
+                    "Transaction held, not broadcast. Written to {}",
+                    out.display()
+                );
+                println!("Broadcast it later with: relay --in {}", out.display());
+
+                update_journal(config, |j| {
+                    let entry = j.record_held(
+                        &tx_data,
+                        &[],
+                        description.as_deref(),
+                        signatures_required(config),
+                    );
+                    if entry.signatures_required > 0
+                        && entry.signatures_collected < entry.signatures_required
+                    {
+                        eprintln!(
+                            "warning: journal entry #{} has only {} of {} signatures; relay \
+                             will fail until the set is fully signed",
+                            entry.id, entry.signatures_collected, entry.signatures_required
+                        );
+                    }
+                });
+                return Ok(());
+            }
+
+            confirm_submission(session, &tx_data, description.as_deref(), yes)?;
+            let text = output == OutputFormat::Text;
+            if text {
+                println!("Submitting fully signed transaction...");
+            }
+            let result = match transaction::submit_multisig_tx(rpc, &tx_data).await {
+                Ok(result) => result,
+                Err(e) => {
+                    let fee_too_low = matches!(
+                        &e,
+                        MultisigError::Transaction(TransactionError::Rejected {
+                            reason: RejectionReason::FeeTooLow,
+                            ..
+                        })
+                    );
+                    let err = report_rejection(session, &tx_data, description.as_deref(), e);
+                    if !fee_too_low {
+                        return Err(err);
+                    }
+                    let bumped =
+                        offer_fee_bump(session, &tx_data, description.as_deref(), auto_bump).await;
+                    return Err(match bumped {
+                        Ok(None) => err,
+                        Ok(Some(message)) => err.context(message),
+                        Err(e) => {
+                            err.context(format!("rebuilding with a higher fee failed: {e:#}"))
+                        }
+                    });
+                }
+            };
+
+            if text {
+                println!("\nTransaction submitted successfully!");
+                for hash in &result.tx_hashes {
+                    println!("  Hash: {hash}");
+                }
+            }
+
+            metrics::TX_SETS.inc(&["submitted"]);
+            let recorded = update_journal(config, |j| {
+                let entry = j.record_submitted(
+                    &tx_data,
+                    &result.tx_hashes,
+                    description.as_deref(),
+                    signatures_required(config),
+                );
+                let event = notify::Event::for_entry(EventKind::TxSubmitted, entry);
+                (event, entry.note.clone())
+            });
+            let (event, note) = recorded.unzip();
+            if let Some(note) = note.flatten() {
+                mirror_note(rpc, &result.tx_hashes, &note).await;
+            }
+            send_notification(session, event).await;
+
+            if no_verify_broadcast {
+                if !text {
+                    session.print_json(&output::SubmitResult {
+                        tx_hashes: result.tx_hashes,
+                        visibility: None,
+                        rejection: None,
+                    })?;
+                }
+                return Ok(());
+            }
+            let hashes = &result.tx_hashes;
+            let grace = std::time::Duration::from_secs(config.broadcast_grace_secs);
+            if text {
+                println!(
+                    "\nChecking that the daemon sees it (up to {}s)...",
+                    grace.as_secs()
+                );
+            }
+            let seen = transaction::wait_for_broadcast(session.daemon, hashes, grace).await;
+            let visibility = if seen.contains(&transaction::Visibility::NotVisible) {
+                transaction::Visibility::NotVisible
+            } else if seen.iter().all(|v| *v == transaction::Visibility::Mined) {
+                transaction::Visibility::Mined
+            } else {
+                transaction::Visibility::InPool
+            };
+            update_journal(config, |j| {
+                j.record_broadcast(hashes, visibility);
+            });
+            if text {
+                for (hash, seen) in hashes.iter().zip(&seen) {
+                    println!("  {hash}: {seen}");
+                }
+            } else {
+                session.print_json(&output::SubmitResult {
+                    tx_hashes: hashes.clone(),
+                    visibility: Some(visibility),
+                    rejection: None,
+                })?;
+            }
+            if visibility == transaction::Visibility::NotVisible {
+                anyhow::bail!(
+                    "the wallet RPC reported the transaction as submitted, but the daemon \
+                     doesn't see it after {}s. Check that the wallet RPC is connected to its \
+                     daemon (chain-info), then broadcast it again with `relay`; keep the \
+                     signed set until it is mined",
+                    grace.as_secs()
+                );
+            }
+        }
+
+        #[cfg(feature = "cold-sign")]
+        Command::ColdSign {
+            tx_data,
+            tx_file,
+            out,
+        } => {
+            let unsigned = cold_input(config, tx_data, tx_file.as_deref())?;
+            let signed = transaction::sign_cold_tx(rpc, &unsigned).await?;
+            if let Some(path) = &out {
+                let bytes = hex::decode(&signed.signed_txset).map_err(|e| {
+                    anyhow::anyhow!("the wallet RPC returned a signed tx set that isn't hex: {e}")
+                })?;
+                std::fs::write(path, bytes)
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+            }
+            if output == OutputFormat::Json {
+                session.print_json(&signed)?;
+            } else {
+                println!("Signed {} transaction(s):", signed.tx_hashes.len());
+                for hash in &signed.tx_hashes {
+                    println!("  {hash}");
+                }
+                match &out {
+                    Some(path) => println!(
+                        "\nWrote {}; submit it from the view-only wallet.",
+                        path.display()
+                    ),
+                    None => println!("\nsigned_monero_tx (hex):\n\n{}\n", signed.signed_txset),
+                }
+            }
+        }
+
+        #[cfg(feature = "cold-sign")]
+        Command::ColdSubmit { tx_data, tx_file } => {
+            let signed = cold_input(config, tx_data, tx_file.as_deref())?;
+            let tx_hashes = transaction::submit_cold_tx(rpc, &signed).await?;
+            if output == OutputFormat::Json {
+                session.print_json(&output::SubmitResult {
+                    tx_hashes,
+                    visibility: None,
+                    rejection: None,
+                })?;
+            } else {
+                println!("Submitted:");
+                for hash in &tx_hashes {
+                    println!("  {hash}");
+                }
+            }
+        }
+
+        Command::Relay { input, force } => {
+            let text = transaction::read_tx_file(&input, config.max_blob_bytes)?;
+            let blob = unwrap_tx_set(config, &text)?;
+            print_fingerprint(config, "Tx set", &blob);
+
+            let text = output == OutputFormat::Text;
+            let (tx_hashes, description) = if transaction::is_multisig_tx_set(&blob) {
+                ensure_can_sign(config)?;
+                let description = transaction::describe_transfer(rpc, &blob).await.ok();
+                check_not_discarded(config, &blob, description.as_deref(), force)?;
+                if text {
+                    println!("Relaying held multisig transaction...");
+                }
+                let result = transaction::submit_multisig_tx(rpc, &blob)
+                    .await
+                    .map_err(|e| report_rejection(session, &blob, description.as_deref(), e))?;
+                (result.tx_hashes, description)
+            } else {
+                check_not_discarded(config, &blob, None, force)?;
+                if text {
+                    println!("Relaying raw transaction through the daemon...");
+                }
+                transaction::send_raw_transaction(session.daemon, &blob)
+                    .await
+                    .map_err(|e| report_rejection(session, &blob, None, e))?;
+                (Vec::new(), None)
+            };
+
+            if text {
+                println!("\nTransaction relayed successfully!");
+                for hash in &tx_hashes {
+                    println!("  Hash: {hash}");
+                }
+            } else {
+                session.print_json(&output::SubmitResult {
+                    tx_hashes: tx_hashes.clone(),
+                    visibility: None,
+                    rejection: None,
+                })?;
+            }
+
+            metrics::TX_SETS.inc(&["submitted"]);
+            let event = update_journal(config, |j| {
+                let entry = j.record_submitted(
+                    &blob,
+                    &tx_hashes,
+                    description.as_deref(),
+                    signatures_required(config),
+                );
+                notify::Event::for_entry(EventKind::TxSubmitted, entry)
+            });
+            send_notification(session, event).await;
+        }
+
+        Command::DiscardTx { tx_hash } => {
+            let mut journal = Journal::load(&config.data_dir)?;
+            let Some(entry) = journal.record_discarded(&tx_hash) else {
+                anyhow::bail!("no pending journal entry with tx hash {tx_hash}");
+            };
+            let id = entry.id;
+            journal.save(&config.data_dir)?;
+            println!("Journal entry #{id} marked as discarded.");
+
+            println!("Releasing outputs reserved by the transaction...");
+            match wallet::rescan_spent(rpc).await {
+                Ok(()) => {
+                    wallet::refresh(rpc, None, None).await?;
+                    println!("Outputs rescanned; they are spendable again.");
+                }
+                Err(e) => {
+                    eprintln!(
+                        "warning: rescan_spent failed ({e}); outputs may stay reserved until \
+                         the wallet is restarted or rescanned against a trusted daemon"
+                    );
+                }
+            }
+
+            println!(
+                "\nAsk your co-signers to discard their copies too (discard-tx --tx-hash \
+                 {tx_hash}) so nobody signs or submits it later."
+            );
+        }
+
+        Command::State {
+            action: StateCommand::Rehash,
+        } => {
+            let state = wallet::load_state_unverified(&config.data_dir)?;
+            match wallet::load_state(&config.data_dir) {
+                Ok(_) => {
+                    println!("The state file already passes its integrity check.");
+                    return Ok(());
+                }
+                Err(e) => eprintln!("{e}\n"),
+            }
+            println!("{}", serde_json::to_string_pretty(&state)?);
+            eprintln!(
+                "\nCheck every field above, the wallet address above all: once re-signed, this \
+                 tool treats these contents as your wallet."
+            );
+            if output == OutputFormat::Json
+                || !utils::confirm(&messages::text(Msg::ConfirmResignState))
+            {
+                anyhow::bail!("aborted; the state file was not changed");
+            }
+            wallet::save_state(&config.data_dir, &state)?;
+            println!("State file re-signed.");
+        }
+
+        Command::RepairState { round_done } => {
+            let mut state = wallet::load_state(&config.data_dir).context("load wallet state")?;
+            let repair = wallet::repair(rpc, &mut state, round_done).await?;
+            if repair == wallet::Repair::default() {
+                println!("The state file matches the wallet RPC.");
+                return Ok(());
+            }
+            if !repair.fixed.is_empty() {
+                wallet::save_state(&config.data_dir, &state)?;
+            }
+            print_repair(&repair);
+            if !repair.unresolved.is_empty() {
+                anyhow::bail!("the state file and the wallet RPC disagree; see above");
+            }
+        }
+
+        Command::Reset {
+            delete_wallet_file,
+            funds_inaccessible,
+        } => {
+            // Unverified: a state file failing its checksum is no reason to
+            // keep it, and it is kept in the trash regardless.
+            let state = wallet::load_state_unverified(&config.data_dir)?;
+            let label = state.wallet.label().to_string();
+            let stage = state.wallet.stage();
+            let journal = Journal::load(&config.data_dir)?;
+            let pending = journal.pending().len();
+
+            let mut balance = None;
+            if matches!(state.wallet, wallet::WalletState::Ready { .. }) && !funds_inaccessible {
+                wallet::check_open_wallet(rpc, &state.wallet).await?;
+                let total = transaction::total_balance(rpc).await?.balance;
+                if total.0 > 0 {
+                    return Err(MultisigError::from(WalletError::HasFunds(total)).into());
+                }
+                balance = Some(total);
+            }
+
+            let wallet_path = state.wallet.wallet_path().to_path_buf();
+            let mut keys_path = wallet_path.clone().into_os_string();
+            keys_path.push(".keys");
+            let mut files = vec![
+                wallet::state_path(&config.data_dir),
+                Journal::path(&config.data_dir),
+            ];
+            if delete_wallet_file {
+                files.extend([wallet_path.clone(), PathBuf::from(keys_path)]);
+            }
+
+            eprintln!("Resetting wallet \"{label}\" ({stage}):");
+            eprintln!(
+                "  - the state file and journal ({} entries, {pending} pending) move to {}",
+                journal.entries.len(),
+                config.data_dir.file("trash").display()
+            );
+            if delete_wallet_file {
+                eprintln!(
+                    "  - the wallet RPC closes the wallet, and {} moves to the trash if it is on \
+                     this machine",
+                    wallet_path.display()
+                );
+            }
+            if funds_inaccessible {
+                eprintln!("  - any funds in this wallet become inaccessible to this group");
+            }
+            let confirmed = output == OutputFormat::Text
+                && utils::confirm_typed(
+                    &format!("Type the wallet label ({label}) to reset it from \"{stage}\":"),
+                    &label,
+                );
+            if !confirmed {
+                anyhow::bail!("aborted; nothing was reset");
+            }
+
+            if delete_wallet_file {
+                wallet::close_wallet(rpc).await?;
+            }
+            let (trash, moved) =
+                utils::move_to_trash(&config.data_dir, &files).map_err(MultisigError::from)?;
+            tracing::warn!(
+                label = %label,
+                stage = %stage,
+                balance = balance.map(|b| b.0),
+                funds_inaccessible,
+                journal_entries = journal.entries.len(),
+                pending,
+                wallet_closed = delete_wallet_file,
+                trash = %trash.display(),
+                moved = ?moved,
+                "wallet reset"
+            );
+            println!(
+                "Wallet \"{label}\" reset; backups are in {}.",
+                trash.display()
+            );
+            println!("Run create-wallet to start over.");
+        }
+
+        Command::Participants => {
+            let state = wallet::load_state(&config.data_dir).context("load wallet state")?;
+            let participants = state.participants();
+            if output == OutputFormat::Json {
+                session.print_json(&participants)?;
+            } else if participants.is_empty() {
+                println!("No roster recorded; name participants with create-wallet --participant.");
+            } else {
+                let mut table = Table::new(vec![
+                    Column::left("Name"),
+                    Column::left("Rounds"),
+                    Column::left("Last info fingerprint").shrink(),
+                ]);
+                for p in &participants {
+                    let rounds = if p.rounds.is_empty() {
+                        "-".to_string()
+                    } else {
+                        p.rounds
+                            .iter()
+                            .map(u32::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    };
+                    let fingerprint = p
+                        .last_digest
+                        .as_deref()
+                        .and_then(|d| utils::fingerprint_from_digest(d, config.fingerprint_format))
+                        .unwrap_or_default();
+                    table.row(vec![
+                        p.name.clone().into(),
+                        rounds.into(),
+                        fingerprint.into(),
+                    ]);
+                }
+                print!("{}", table.render(&session.style));
+            }
+        }
+
+        Command::Bundle {
+            action: BundleCommand::Export { out, from },
+        } => export_bundle(session, &out, from).await?,
+
+        Command::Bundle {
+            action:
+                BundleCommand::Import {
+                    file,
+                    me,
+                    conflicts,
+                    yes,
+                },
+        } => import_bundle(session, &file, me.as_deref(), conflicts, yes).await?,
+
+        Command::ProveSpend {
+            tx_hash,
+            message,
+            out,
+        } => {
+            let proof = proof::prove_spend(rpc, &tx_hash, &message).await?;
+            if let Some(out) = &out {
+                proof::write_proof(out, &proof)?;
+            }
+            if output == OutputFormat::Json {
+                session.print_json(&proof)?;
+            } else if let Some(out) = &out {
+                println!("Spend proof for {tx_hash} written to {}.", out.display());
+            } else {
+                println!("{}", serde_json::to_string_pretty(&proof)?);
+            }
+        }
+
+        Command::CheckSpendProof { tx_hash, proof } => {
+            let spend_proof = proof::read_proof(&proof)?;
+            let good = proof::check_spend_proof(rpc, &tx_hash, &spend_proof).await?;
+            if output == OutputFormat::Json {
+                session.print_json(&output::SpendProofCheck {
+                    txid: &tx_hash,
+                    message: &spend_proof.message,
+                    good,
+                })?;
+            } else {
+                println!(
+                    "Spend proof for {tx_hash}: {}",
+                    if good { "VALID" } else { "INVALID" }
+                );
+                if spend_proof.message.is_empty() {
+                    println!("Message: (none)");
+                } else {
+                    println!("Message: {}", spend_proof.message);
+                }
+            }
+            if !good {
+                anyhow::bail!("spend proof does not match transaction {tx_hash}");
+            }
+        }
+
+        Command::Audit {
+            action: AuditCommand::Verify,
+        } => {
+            let verification = audit::verify(&config.data_dir)?;
+            if output == OutputFormat::Json {
+                session.print_json(&verification)?;
+            }
+            match verification {
+                audit::Verification::Intact { entries } => {
+                    if output == OutputFormat::Text {
+                        println!("Audit log intact: {entries} entries.");
+                    }
+                }
+                audit::Verification::Broken { line, reason } => {
+                    anyhow::bail!("audit log broken at line {line}: {reason}")
+                }
+            }
+        }
+
+        Command::RecordSignature { from, tx_data } => {
+            let state = wallet::load_state(&config.data_dir).context("load wallet state")?;
+            state.check_member(&from)?;
+            let tx_data = unwrap_tx_set(config, &tx_data)?;
+            print_fingerprint(config, "Tx set", &tx_data);
+            let description = transaction::describe_transfer(rpc, &tx_data).await.ok();
+
+            let mut journal = Journal::load(&config.data_dir)?;
+            let (outcome, entry) = journal.record_signer(
+                &tx_data,
+                description.as_deref(),
+                &from,
+                signatures_required(config),
+            );
+            let checklist = signer_checklist(entry, state.roster());
+            let id = entry.id;
+            match outcome {
+                journal::Attribution::Recorded => {
+                    journal.save(&config.data_dir)?;
+                    println!("Journal #{id}: credited {from}'s signature.");
+                }
+                journal::Attribution::Unchanged => {
+                    println!("Journal #{id}: this tx set was already credited to {from}.");
+                }
+                journal::Attribution::SetCreditedTo(other) => {
+                    anyhow::bail!(
+                        "journal #{id}: this tx set version is already credited to {other}, not \
+                         {from}"
+                    );
+                }
+                journal::Attribution::AlreadySigned => {
+                    anyhow::bail!(
+                        "journal #{id}: {from} is already credited with a signature on another \
+                         version of this tx set"
+                    );
+                }
+            }
+            println!("Signers (as recorded here; tx sets don't say who signed): {checklist}");
+        }
+
+        Command::DeleteWallet { label, keep_files } => {
+            let state = wallet::load_state(&config.data_dir).context("load wallet state")?;
+            if state.wallet.label() != label {
+                anyhow::bail!(
+                    "the wallet here is labelled \"{}\", not \"{label}\"",
+                    state.wallet.label()
+                );
+            }
+            let address = match &state.wallet {
+                wallet::WalletState::Ready { address, .. }
+                | wallet::WalletState::WatchOnly { address, .. } => address.clone(),
+                _ => anyhow::bail!("wallet setup never finished; use `reset` to abandon it"),
+            };
+
+            println!("Refreshing to make sure no funds arrived...");
+            wallet::refresh(rpc, state.wallet.restore_height(), None).await?;
+            let balance = transaction::total_balance(rpc).await?;
+            let remaining = balance.balance.max(balance.unlocked_balance);
+            if remaining.0 > 0 {
+                return Err(MultisigError::from(WalletError::NotEmpty(remaining)).into());
+            }
+
+            let journal = Journal::load(&config.data_dir)?;
+            let (transactions, total_sent) = journal.submitted_totals();
+            eprintln!("Deleting wallet \"{label}\" ({address}):");
+            eprintln!(
+                "  - balance is zero; {transactions} transaction(s) sent {} in total",
+                transaction::Amount(total_sent)
+            );
+            eprintln!(
+                "  - the state file and journal are removed, leaving a record in the archive"
+            );
+            if keep_files {
+                eprintln!("  - the wallet files are moved into the archive");
+            } else {
+                eprintln!("  - the wallet files are deleted");
+            }
+            let confirmed = output == OutputFormat::Text
+                && utils::confirm_typed(
+                    &format!("Type the wallet label ({label}) to delete it:"),
+                    &label,
+                );
+            if !confirmed {
+                anyhow::bail!("aborted; nothing was deleted");
+            }
+
+            wallet::close_wallet(rpc).await?;
+
+            let safe_label: String = label
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '-' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            let archive =
+                utils::timestamped_dir(&config.data_dir.file("archive"), &format!("{safe_label}-"))
+                    .map_err(MultisigError::from)?;
+            let wallet_path = state.wallet.wallet_path().to_path_buf();
+            let mut keys_path = wallet_path.clone().into_os_string();
+            keys_path.push(".keys");
+            let wallet_files = [wallet_path, PathBuf::from(keys_path)];
+            let kept_files = if keep_files {
+                utils::move_into(&archive, &wallet_files).map_err(MultisigError::from)?
+            } else {
+                for file in wallet_files.iter().filter(|f| f.exists()) {
+                    std::fs::remove_file(file)
+                        .with_context(|| format!("delete {}", file.display()))?;
+                }
+                Vec::new()
+            };
+
+            let tombstone = wallet::Tombstone {
+                label: label.clone(),
+                address,
+                created_at: state.created_at,
+                closed_at: chrono::Utc::now(),
+                transactions,
+                total_sent,
+                kept_files,
+            };
+            let record = archive.join("tombstone.json");
+            utils::write_atomic(
+                &record,
+                serde_json::to_string_pretty(&tombstone)?.as_bytes(),
+            )
+            .map_err(MultisigError::from)?;
+            for file in [
+                wallet::state_path(&config.data_dir),
+                Journal::path(&config.data_dir),
+            ] {
+                if file.exists() {
+                    std::fs::remove_file(&file)
+                        .with_context(|| format!("delete {}", file.display()))?;
+                }
+            }
+            tracing::warn!(
+                label = %label,
+                address = %tombstone.address,
+                transactions,
+                total_sent,
+                kept_files = ?tombstone.kept_files,
+                record = %record.display(),
+                "wallet deleted"
+            );
+            println!(
+                "Wallet \"{label}\" deleted; its record is in {}.",
+                record.display()
+            );
+        }
+
+        Command::Pending { action } => {
+            let mut journal = Journal::load(&config.data_dir)?;
+            match action {
+                None => {
+                    let pending = journal.pending();
+                    if output == OutputFormat::Json {
+                        session.print_json(&pending)?;
+                    } else if pending.is_empty() {
+                        println!("No pending transactions.");
+                    } else {
+                        print!(
+                            "{}",
+                            pending_table(&pending, &session.style).render(&session.style)
+                        );
+                        let roster = wallet::load_state(&config.data_dir)
+                            .map(|s| s.roster().to_vec())
+                            .unwrap_or_default();
+                        if !roster.is_empty() {
+                            println!("\nSigners (as recorded here; tx sets don't say who signed):");
+                            for e in &pending {
+                                println!("  #{}  {}", e.id, signer_checklist(e, &roster));
+                            }
+                        }
+                    }
+                }
+                Some(PendingCommand::Show { tx_hash }) => {
+                    let entry = journal
+                        .find_by_hash(&tx_hash)
+                        .with_context(|| format!("no journal entry with tx hash {tx_hash}"))?;
+                    let tx_set = entry.tx_set.as_deref().with_context(|| {
+                        format!("journal entry #{} no longer stores its tx set", entry.id)
+                    })?;
+                    let id = format!("#{}", entry.id);
+                    let mut headers = vec![("Journal-Entry", id.as_str())];
+                    if let Some(note) = &entry.note {
+                        headers.push(("Comment", note.as_str()));
+                    }
+                    println!("{}", armor::armor(armor::BlobKind::TxSet, &headers, tx_set));
+                }
+                Some(PendingCommand::Prune { older_than }) => {
+                    let cutoff = chrono::Utc::now()
+                        .checked_sub_signed(older_than)
+                        .context("--older-than reaches back further than any date")?;
+                    let summary = journal.prune(cutoff);
+                    journal.save(&config.data_dir)?;
+                    println!(
+                        "Marked {} stale pending transaction(s) as abandoned; dropped {} stored \
+                         tx set(s).",
+                        summary.abandoned, summary.tx_sets_dropped
+                    );
+                }
+            }
+        }
+
+        Command::Journal { filter, action } => {
+            let journal = Journal::load(&config.data_dir)?;
+            match action {
+                Some(JournalCommand::Export { csv, filter }) => {
+                    let filter = filter.into();
+                    if csv {
+                        print!("{}", journal::to_csv(journal.filter(&filter)));
+                    } else {
+                        let entries: Vec<_> = journal.filter(&filter).collect();
+                        println!("{}", serde_json::to_string_pretty(&entries)?);
+                    }
+                }
+                None => {
+                    let filter = filter.into();
+                    let entries: Vec<_> = journal.filter(&filter).collect();
+                    if output == OutputFormat::Json {
+                        session.print_json(&entries)?;
+                    } else if entries.is_empty() {
+                        println!("No journal entries.");
+                    } else {
+                        for e in entries {
+                            print_journal_entry(e, &session.style);
+                        }
+                    }
+                }
+            }
+        }
+
+        Command::Report {
+            from,
+            to,
+            format,
+            out,
+        } => {
+            if let (Some(from), Some(to)) = (from, to) {
+                anyhow::ensure!(from <= to, "--from {from} is after --to {to}");
+            }
+            let state = wallet::load_state(&config.data_dir).context("load wallet state")?;
+            let journal = Journal::load(&config.data_dir)?;
+            let history = transaction::get_transfers(rpc, None).await?;
+            let balance = transaction::total_balance(rpc).await?;
+            let report = report::Report::new(&state, &journal, &history, balance, from, to)?;
+            if let Some(discrepancy) = &report.reconciliation.discrepancy {
+                eprintln!("warning: {discrepancy}; see the report");
+            }
+            let format = format.unwrap_or(match output {
+                OutputFormat::Json => ReportFormat::Json,
+                OutputFormat::Text => ReportFormat::Md,
+            });
+            let document = match format {
+                ReportFormat::Md => report.to_markdown(),
+                ReportFormat::Html => report.to_html(),
+                ReportFormat::Json => output::to_json("report", &report)? + "\n",
+            };
+            match out {
+                Some(path) => {
+                    utils::write_atomic(&path, document.as_bytes())
+                        .with_context(|| format!("write {}", path.display()))?;
+                    if output != OutputFormat::Json {
+                        println!("Report written to {}", path.display());
+                    }
+                }
+                None => print!("{document}"),
+            }
+        }
+
+        Command::Note { action } => match action {
+            NoteCommand::Set { tx_hash, note } => {
+                let in_journal =
+                    update_journal(config, |j| j.set_note(&tx_hash, &note).map(|e| e.id)).flatten();
+                if let Some(id) = in_journal {
+                    println!("Journal entry #{id}: note set.");
+                }
+                let txids = std::slice::from_ref(&tx_hash);
+                match transaction::set_tx_notes(rpc, txids, &note).await {
+                    Ok(()) => println!("Wallet: note set."),
+                    Err(MultisigError::Rpc(e)) if e.is_method_not_found() => {
+                        tracing::debug!("the wallet RPC doesn't keep tx notes");
+                        if in_journal.is_none() {
+                            anyhow::bail!(
+                                "no journal entry has tx hash {tx_hash}, and this wallet RPC \
+                                 can't keep notes; the note was not saved"
+                            );
+                        }
+                        println!("Wallet: this wallet RPC can't keep notes; journal only.");
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            NoteCommand::Show { tx_hash } => {
+                let journal = Journal::load(&config.data_dir)?;
+                let local = journal.find_by_hash(&tx_hash).and_then(|e| e.note.clone());
+                let txids = std::slice::from_ref(&tx_hash);
+                let wallet_note = wallet_notes(rpc, txids).await.remove(0);
+                let shown = |note: Option<&str>| match note.filter(|n| !n.is_empty()) {
+                    Some(note) => note.to_string(),
+                    None => "(none)".into(),
+                };
+                println!("Journal: {}", shown(local.as_deref()));
+                println!("Wallet:  {}", shown(Some(&wallet_note)));
+                let merged = journal::merge_notes(local.as_deref(), Some(&wallet_note));
+                if merged.is_some_and(|m| m.conflict) {
+                    println!("\nThey differ; history shows the journal's.");
+                }
+            }
+        },
+
+        Command::Escrow { action } => run_escrow(action, session).await?,
+    }
+
+    Ok(())
+}
+
+/// The `escrow` steps. Setup and payouts go through `create-wallet`,
+/// `exchange-keys` and `build-tx` once the escrow terms check out.
+async fn run_escrow(action: EscrowCommand, session: &Session<'_>) -> Result<()> {
+    let config = session.config;
+    match action {
+        EscrowCommand::Init {
+            role,
+            refund_address,
+            label,
+            restore_height,
+        } => {
+            let terms = escrow::EscrowTerms::new(role, &refund_address, config.network)?;
+            let params = wallet::MultisigParams::new(2, 2, label)?
+                .with_roster(vec![role.other().to_string()])?;
+            let info =
+                create_wallet(session, params, restore_height, None, Some(terms.clone())).await?;
+            let invite = escrow::EscrowInvite::new(&terms, &info);
+
+            println!(
+                "\nYou are the {role}; refunds go to {}.",
+                terms.refund_address
+            );
+            println!("Send this invite to the {}:\n", role.other());
+            println!("{invite}\n");
+            print_fingerprint(config, "Invite", &invite.to_string());
+            println!("\nThen run `escrow join` with theirs.");
+        }
+
+        EscrowCommand::Join {
+            peer_info,
+            password,
+        } => {
+            let state = wallet::load_state(&config.data_dir).context("load wallet state")?;
+            let terms = escrow::terms(&state).map_err(MultisigError::from)?;
+            let invite: escrow::EscrowInvite = peer_info.parse().map_err(MultisigError::from)?;
+            terms.check_peer(&invite).map_err(MultisigError::from)?;
+            println!(
+                "The {}'s invite matches: refunds go to {}.",
+                invite.role, invite.refund_address
+            );
+            let exchange = Command::ExchangeKeys {
+                info: vec![invite.info],
+                expect_fingerprint: Vec::new(),
+                from: vec![invite.role.to_string()],
+                password,
+                isolate_bad_blob: false,
+                interactive_rounds: false,
+            };
+            Box::pin(run(exchange, session)).await?;
+        }
+
+        EscrowCommand::Release {
+            to,
+            amount,
+            note,
+            allow_stale_sync,
+            allow_sweep,
+        } => {
+            let state = wallet::load_state(&config.data_dir).context("load wallet state")?;
+            let terms = escrow::terms(&state).map_err(MultisigError::from)?;
+            escrow::check_wallet(session.rpc).await?;
+            if to.trim() == terms.refund_address {
+                anyhow::bail!(
+                    "{to} is the buyer's refund address; use `escrow refund` to pay it back"
+                );
+            }
+            Box::pin(run(
+                escrow_payout(to, amount, note, allow_stale_sync, allow_sweep),
+                session,
+            ))
+            .await?;
+        }
+
+        EscrowCommand::Refund {
+            amount,
+            to,
+            override_address,
+            note,
+            allow_stale_sync,
+            allow_sweep,
+        } => {
+            let state = wallet::load_state(&config.data_dir).context("load wallet state")?;
+            let terms = escrow::terms(&state).map_err(MultisigError::from)?;
+            escrow::check_wallet(session.rpc).await?;
+            let destination = terms
+                .refund_destination(to.as_deref(), override_address)
+                .map_err(MultisigError::from)?;
+            if destination != terms.refund_address {
+                eprintln!(
+                    "\n!!! WARNING: refunding to {destination}, not the buyer's registered \
+                     address {} !!!\n",
+                    terms.refund_address
+                );
+                let confirmed = session.output == OutputFormat::Text
+                    && utils::confirm_typed("Type the address again to continue:", &destination);
+                if !confirmed {
+                    anyhow::bail!("aborted; the refund was not built");
+                }
+                tracing::warn!(
+                    registered = %terms.refund_address,
+                    to = %destination,
+                    "escrow refund address overridden"
+                );
+            }
+            Box::pin(run(
+                escrow_payout(destination, amount, note, allow_stale_sync, allow_sweep),
+                session,
+            ))
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// The `build-tx` an escrow release or refund runs.
+fn escrow_payout(
+    to: String,
+    amount: transaction::Amount,
+    note: Option<String>,
+    allow_stale_sync: bool,
+    allow_sweep: bool,
+) -> Command {
+    Command::BuildTx {
+        address: vec![to],
+        amount: vec![amount.0],
+        uri: None,
+        interactive: false,
+        merge_duplicates: false,
+        allow_split: false,
+        subaddr_index: Vec::new(),
+        priority: 0,
+        note,
+        allow_stale_sync,
+        override_policy: false,
+        ignore_allowlist: false,
+        allow_sweep,
+        dry_run: false,
+        export_monero_format: None,
+        manifest_out: None,
+    }
 }
@@ -0,0 +1,465 @@
+//! Tx set manifests: what the builder meant to pay, sent alongside the set.
+//!
+//! A tx set is opaque hex, so a builder machine that is compromised could
+//! hand co-signers a different set from the one its user reviewed. With
+//! `build-tx --manifest-out`, the builder also writes a small JSON file
+//! listing the destinations, change, fee and note, with an HMAC-SHA256 over them
+//! keyed by the group's `manifest_secret`. A co-signer decodes the set with
+//! `describe_transfer` and checks it against the manifest
+//! (`verify-tx-set`, or `sign-tx --manifest`) before signing:
+//!
+//! ```json
+//! { "version": 1, "created_at": "…", "destinations": [{ "address": "4…", "amount": 1500000000000 }],
+//!   "change_address": "4…", "change_amount": 480000000000, "fee": 30000000, "note": "rent",
+//!   "hmac": "…" }
+//! ```
+//!
+//! The secret has to reach every participant out of band; a manifest is only
+//! as trustworthy as the channel the secret travelled over.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::error::{Result, TransactionError};
+use crate::transaction::{Amount, Destination, Recipient, TransferDescription};
+
+/// Manifests are a few hundred bytes per destination; anything this big is
+/// not one.
+const MAX_MANIFEST_BYTES: usize = 1024 * 1024;
+
+/// The only manifest format so far.
+const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum ManifestError {
+    #[error(
+        "no manifest_secret in the config — every participant needs the same secret to write \
+         or check manifests"
+    )]
+    NoSecret,
+
+    #[error("unsupported manifest version {0}; this build reads version {MANIFEST_VERSION}")]
+    UnsupportedVersion(u32),
+
+    #[error(
+        "the manifest's HMAC doesn't match: it was changed after it was written, or written \
+         with a different manifest_secret"
+    )]
+    BadHmac,
+
+    #[error(
+        "this wallet RPC has no describe_transfer, so the tx set can't be matched with \
+         the manifest — upgrade monero-wallet-rpc"
+    )]
+    Undecodable,
+
+    #[error("the tx set doesn't match the manifest ({0} difference(s))")]
+    Mismatch(usize),
+}
+
+/// A tx set manifest as written to and read from a manifest file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub destinations: Vec<Recipient>,
+    /// Where the set's change goes, empty when it has none.
+    pub change_address: String,
+    /// The change summed over every tx in the set.
+    pub change_amount: u64,
+    pub fee: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Hex HMAC-SHA256 of every other field; see [`Manifest::verify`].
+    pub hmac: String,
+}
+
+/// The fields the HMAC covers, in the order they are serialized for it.
+#[derive(Serialize)]
+struct Signed<'a> {
+    version: u32,
+    created_at: &'a DateTime<Utc>,
+    destinations: &'a [Recipient],
+    change_address: &'a str,
+    change_amount: u64,
+    fee: u64,
+    note: Option<&'a str>,
+}
+
+/// One way a described tx set differs from its manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Difference {
+    /// A destination the manifest lists and the tx set doesn't pay.
+    Missing {
+        address: String,
+        amount: u64,
+    },
+    /// A payment in the tx set the manifest doesn't list.
+    Unexpected {
+        address: String,
+        amount: u64,
+    },
+    /// Change going elsewhere, or in a different amount, than listed.
+    Change {
+        expected_address: String,
+        expected_amount: u64,
+        actual_address: String,
+        actual_amount: u64,
+    },
+    Fee {
+        expected: u64,
+        actual: u64,
+    },
+}
+
+impl std::fmt::Display for Difference {
+    /// One `-`/`+` line per side, like a diff of the manifest against the
+    /// tx set.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difference::Missing { address, amount } => {
+                write!(f, "- {} to {address}", Amount(*amount))
+            }
+            Difference::Unexpected { address, amount } => {
+                write!(f, "+ {} to {address}", Amount(*amount))
+            }
+            Difference::Change {
+                expected_address,
+                expected_amount,
+                actual_address,
+                actual_amount,
+            } => write!(
+                f,
+                "- change {} to {expected_address}\n+ change {} to {actual_address}",
+                Amount(*expected_amount),
+                Amount(*actual_amount)
+            ),
+            Difference::Fee { expected, actual } => {
+                write!(f, "- fee {}\n+ fee {}", Amount(*expected), Amount(*actual))
+            }
+        }
+    }
+}
+
+fn mac(secret: &str, signed: &Signed<'_>) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(&serde_json::to_vec(signed).expect("manifest fields serialize"));
+    mac
+}
+
+/// The change of a described set: the address of the first tx with any, and
+/// the amount summed over all of them. A sum that overflows is `None`.
+fn change_of(descriptions: &[TransferDescription]) -> (&str, Option<u64>) {
+    let address = descriptions
+        .iter()
+        .find(|d| d.change_amount > 0)
+        .map_or("", |d| d.change_address.as_str());
+    let amount = descriptions
+        .iter()
+        .try_fold(0u64, |sum, d| sum.checked_add(d.change_amount));
+    (address, amount)
+}
+
+impl Manifest {
+    /// A manifest for a set paying `destinations` with `fee`, signed with
+    /// `secret`. The change is taken from `descriptions`, the builder's own
+    /// decoding of the set.
+    pub fn new(
+        destinations: &[Destination],
+        fee: Amount,
+        descriptions: &[TransferDescription],
+        note: Option<String>,
+        secret: &str,
+    ) -> Result<Self> {
+        let (change_address, change_amount) = change_of(descriptions);
+        let change_amount = change_amount.ok_or(TransactionError::AmountOverflow)?;
+        let mut manifest = Manifest {
+            version: MANIFEST_VERSION,
+            created_at: Utc::now(),
+            destinations: destinations
+                .iter()
+                .map(|d| Recipient {
+                    address: d.address().to_string(),
+                    amount: d.amount().0,
+                })
+                .collect(),
+            change_address: change_address.to_string(),
+            change_amount,
+            fee: fee.0,
+            note,
+            hmac: String::new(),
+        };
+        manifest.hmac = hex::encode(mac(secret, &manifest.signed()).finalize().into_bytes());
+        Ok(manifest)
+    }
+
+    fn signed(&self) -> Signed<'_> {
+        Signed {
+            version: self.version,
+            created_at: &self.created_at,
+            destinations: &self.destinations,
+            change_address: &self.change_address,
+            change_amount: self.change_amount,
+            fee: self.fee,
+            note: self.note.as_deref(),
+        }
+    }
+
+    /// Check the HMAC against `secret`, in constant time.
+    pub fn verify(&self, secret: &str) -> std::result::Result<(), ManifestError> {
+        if self.version != MANIFEST_VERSION {
+            return Err(ManifestError::UnsupportedVersion(self.version));
+        }
+        let tag = hex::decode(&self.hmac).map_err(|_| ManifestError::BadHmac)?;
+        mac(secret, &self.signed())
+            .verify_slice(&tag)
+            .map_err(|_| ManifestError::BadHmac)
+    }
+
+    /// How the tx set `descriptions` decode to differs from this manifest;
+    /// empty when it pays exactly the listed destinations and change with the
+    /// listed fee. Destinations are compared as a multiset, so their order doesn't
+    /// matter but a repeated payment does.
+    pub fn compare(&self, descriptions: &[TransferDescription]) -> Vec<Difference> {
+        let mut unmatched: Vec<&Recipient> =
+            descriptions.iter().flat_map(|d| &d.recipients).collect();
+        let mut differences = Vec::new();
+        for expected in &self.destinations {
+            match unmatched
+                .iter()
+                .position(|r| r.address == expected.address && r.amount == expected.amount)
+            {
+                Some(i) => {
+                    unmatched.remove(i);
+                }
+                None => differences.push(Difference::Missing {
+                    address: expected.address.clone(),
+                    amount: expected.amount,
+                }),
+            }
+        }
+        differences.extend(unmatched.into_iter().map(|r| Difference::Unexpected {
+            address: r.address.clone(),
+            amount: r.amount,
+        }));
+        // Every tx's change has to go to the listed address, so a split set
+        // can't slip some of it elsewhere.
+        let (first, amount) = change_of(descriptions);
+        let actual_address = descriptions
+            .iter()
+            .filter(|d| d.change_amount > 0)
+            .map(|d| d.change_address.as_str())
+            .find(|a| *a != self.change_address)
+            .unwrap_or(first);
+        let actual_amount = amount.unwrap_or(u64::MAX);
+        if actual_address != self.change_address || actual_amount != self.change_amount {
+            differences.push(Difference::Change {
+                expected_address: self.change_address.clone(),
+                expected_amount: self.change_amount,
+                actual_address: actual_address.to_string(),
+                actual_amount,
+            });
+        }
+        // A fee sum that overflows can't equal the manifest's.
+        let actual = crate::transaction::total_fee(descriptions).map_or(u64::MAX, |fee| fee.0);
+        if actual != self.fee {
+            differences.push(Difference::Fee {
+                expected: self.fee,
+                actual,
+            });
+        }
+        differences
+    }
+}
+
+/// Write `manifest` to `path` as pretty JSON.
+pub fn write_manifest(path: &Path, manifest: &Manifest) -> Result<()> {
+    let json = serde_json::to_string_pretty(manifest)?;
+    crate::utils::write_atomic(path, json.as_bytes())?;
+    Ok(())
+}
+
+/// Read a manifest file written by [`write_manifest`]. The HMAC is not
+/// checked; see [`Manifest::verify`].
+pub fn read_manifest(path: &Path) -> Result<Manifest> {
+    let text = crate::utils::read_multisig_data(Some(path), MAX_MANIFEST_BYTES)?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "4AdUndXHHZ6cfufTMvppY6JwXNouMBzSkbLYfpAV5Usx3skxNgYeYTRj5UzqtReoS44qo9mtmXCqY45DJ852K5Jv2684Rge";
+    const OWN: &str = "4self";
+
+    /// A set paying `recipients` with `fee`, its change of 500 to [`OWN`].
+    fn described(recipients: &[(&str, u64)], fee: u64) -> Vec<TransferDescription> {
+        with_change(recipients, fee, OWN, 500)
+    }
+
+    fn with_change(
+        recipients: &[(&str, u64)],
+        fee: u64,
+        change_address: &str,
+        change_amount: u64,
+    ) -> Vec<TransferDescription> {
+        vec![TransferDescription {
+            recipients: recipients
+                .iter()
+                .map(|(address, amount)| Recipient {
+                    address: address.to_string(),
+                    amount: *amount,
+                })
+                .collect(),
+            amount_in: 0,
+            amount_out: 0,
+            change_amount,
+            change_address: change_address.into(),
+            fee,
+            ring_size: 16,
+            unlock_time: 0,
+        }]
+    }
+
+    #[test]
+    fn test_manifest_hmac_and_compare() {
+        let destinations = [Destination::new_unchecked(ADDRESS, Amount(1_500))];
+        let manifest = Manifest::new(
+            &destinations,
+            Amount(30),
+            &described(&[(ADDRESS, 1_500)], 30),
+            Some("rent".into()),
+            "s3cret",
+        )
+        .unwrap();
+        assert_eq!(
+            (manifest.change_address.as_str(), manifest.change_amount),
+            (OWN, 500)
+        );
+        manifest.verify("s3cret").unwrap();
+        assert!(matches!(
+            manifest.verify("other"),
+            Err(ManifestError::BadHmac)
+        ));
+
+        // Every field is covered, the note included.
+        let mut edited = manifest.clone();
+        edited.note = Some("bonus".into());
+        assert!(matches!(
+            edited.verify("s3cret"),
+            Err(ManifestError::BadHmac)
+        ));
+        let mut edited = manifest.clone();
+        edited.destinations[0].amount = 1_600;
+        assert!(matches!(
+            edited.verify("s3cret"),
+            Err(ManifestError::BadHmac)
+        ));
+        let mut edited = manifest.clone();
+        edited.change_address = "4Attacker".into();
+        assert!(matches!(
+            edited.verify("s3cret"),
+            Err(ManifestError::BadHmac)
+        ));
+
+        // The file round-trips and still verifies.
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("set.manifest");
+        write_manifest(&path, &manifest).unwrap();
+        let read = read_manifest(&path).unwrap();
+        assert_eq!(read, manifest);
+        read.verify("s3cret").unwrap();
+
+        assert!(manifest
+            .compare(&described(&[(ADDRESS, 1_500)], 30))
+            .is_empty());
+        let swapped = manifest.compare(&described(&[("4Attacker", 1_500)], 31));
+        assert_eq!(
+            swapped,
+            [
+                Difference::Missing {
+                    address: ADDRESS.into(),
+                    amount: 1_500
+                },
+                Difference::Unexpected {
+                    address: "4Attacker".into(),
+                    amount: 1_500
+                },
+                Difference::Fee {
+                    expected: 30,
+                    actual: 31
+                },
+            ]
+        );
+        assert_eq!(
+            swapped[2].to_string(),
+            "- fee 0.000000000030 XMR\n+ fee 0.000000000031 XMR"
+        );
+        // Paying the listed destination twice is a difference too.
+        assert_eq!(
+            manifest
+                .compare(&described(&[(ADDRESS, 1_500), (ADDRESS, 1_500)], 30))
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_compare_checks_the_change() {
+        let destinations = [Destination::new_unchecked(ADDRESS, Amount(1_500))];
+        let manifest = Manifest::new(
+            &destinations,
+            Amount(30),
+            &described(&[(ADDRESS, 1_500)], 30),
+            None,
+            "s3cret",
+        )
+        .unwrap();
+
+        // Same destinations and fee, only the change redirected.
+        let redirected = manifest.compare(&with_change(&[(ADDRESS, 1_500)], 30, "4Attacker", 500));
+        assert_eq!(
+            redirected,
+            [Difference::Change {
+                expected_address: OWN.into(),
+                expected_amount: 500,
+                actual_address: "4Attacker".into(),
+                actual_amount: 500,
+            }]
+        );
+        assert_eq!(
+            redirected[0].to_string(),
+            "- change 0.000000000500 XMR to 4self\n+ change 0.000000000500 XMR to 4Attacker"
+        );
+        assert_eq!(
+            manifest
+                .compare(&with_change(&[(ADDRESS, 1_500)], 30, OWN, 400))
+                .len(),
+            1
+        );
+
+        // In a split set, every tx's change has to go to the listed address.
+        let mut split = with_change(&[(ADDRESS, 1_500)], 20, OWN, 300);
+        split.extend(with_change(&[], 10, "4Attacker", 200));
+        assert_eq!(
+            manifest.compare(&split),
+            [Difference::Change {
+                expected_address: OWN.into(),
+                expected_amount: 500,
+                actual_address: "4Attacker".into(),
+                actual_amount: 500,
+            }]
+        );
+        let mut split = with_change(&[(ADDRESS, 1_500)], 20, OWN, 300);
+        split.extend(with_change(&[], 10, OWN, 200));
+        assert!(manifest.compare(&split).is_empty());
+    }
+}
@@ -0,0 +1,304 @@
+//! Translations of the prompts, warnings and next steps people read while
+//! running a ceremony.
+//!
+//! Texts live in `messages.json`, one object per language keyed by [`Msg`]
+//! keys, with `{name}` placeholders filled in by [`format`]. Adding a
+//! language means adding an object there; keys a language leaves out, and
+//! languages that don't exist, fall back to English. `--lang` picks the
+//! language, then `MONERO_MULTISIG_LANG`, as `es` or a locale such as
+//! `es_ES.UTF-8`.
+//!
+//! Only text meant for a person goes through here. JSON output, log lines
+//! and error messages stay in English so scripts and bug reports can rely on
+//! them.
+
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, OnceLock};
+
+/// Environment variable checked for the language after `--lang`.
+pub const LANG_ENV: &str = "MONERO_MULTISIG_LANG";
+
+/// The language every key has a text in.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+type Texts = BTreeMap<String, String>;
+
+static CATALOGS: LazyLock<BTreeMap<String, Texts>> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("messages.json")).expect("messages.json is valid")
+});
+
+static SELECTED: OnceLock<Catalog> = OnceLock::new();
+
+macro_rules! messages {
+    ($($variant:ident => $key:literal,)*) => {
+        /// A translatable text, named by its key in `messages.json`.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Msg {
+            $($variant,)*
+        }
+
+        impl Msg {
+            /// Every key a catalog may hold.
+            pub const ALL: &'static [Msg] = &[$(Msg::$variant,)*];
+
+            pub fn key(self) -> &'static str {
+                match self {
+                    $(Msg::$variant => $key,)*
+                }
+            }
+        }
+    };
+}
+
+messages! {
+    ConfirmYesNo => "confirm.yes_no",
+    ConfirmYesAnswers => "confirm.yes_answers",
+    ConfirmOverridePolicy => "confirm.override_policy",
+    ConfirmBuild => "confirm.build",
+    ConfirmBroadcast => "confirm.broadcast",
+    ConfirmAddDestination => "confirm.add_destination",
+    ConfirmRebuild => "confirm.rebuild",
+    ConfirmViewKey => "confirm.view_key",
+    ConfirmExportNow => "confirm.export_now",
+    ConfirmSignSets => "confirm.sign_sets",
+    ConfirmResignState => "confirm.resign_state",
+    ConfirmApplyBundle => "confirm.apply_bundle",
+    PromptLabel => "prompt.label",
+    PromptDestination => "prompt.destination",
+    PromptAmount => "prompt.amount",
+    PromptNote => "prompt.note",
+    PromptCollectingRound => "prompt.collecting_round",
+    PromptRoundInfo => "prompt.round_info",
+    PromptRoundInfoFrom => "prompt.round_info_from",
+    PromptTypeAmount => "prompt.type_amount",
+    PromptTypeId => "prompt.type_id",
+    PromptConflict => "prompt.conflict",
+    PromptKeepAnswers => "prompt.keep_answers",
+    PromptTakeAnswers => "prompt.take_answers",
+    WarningSweep => "warning.sweep",
+    WarningSweepTotal => "warning.sweep_total",
+    WarningSweepBalance => "warning.sweep_balance",
+    WarningUndescribed => "warning.undescribed",
+    WarningUndescribedDetail => "warning.undescribed_detail",
+    WarningUnverifiedAddress => "warning.unverified_address",
+    WarningStaleSync => "warning.stale_sync",
+    WarningManyRounds => "warning.many_rounds",
+    HintSlowRpc => "hint.slow_rpc",
+    NextHeader => "next.header",
+    NextPeers => "next.peers",
+    NextCreateWallet => "next.create_wallet",
+    NextRepairState => "next.repair_state",
+    NextExchangeFirst => "next.exchange_first",
+    NextExchangeAgain => "next.exchange_again",
+    NextVerifyAddress => "next.verify_address",
+    NextImportInfo => "next.import_info",
+    NextExportInfo => "next.export_info",
+    NextSignTx => "next.sign_tx",
+    NextSendTxSet => "next.send_tx_set",
+    NextAnyOf => "next.any_of",
+    NextMoreCosigners => "next.more_cosigners",
+    NextSubmitTx => "next.submit_tx",
+    NextRelay => "next.relay",
+}
+
+/// The texts of one bundled language.
+#[derive(Debug, Clone, Copy)]
+pub struct Catalog {
+    language: &'static str,
+    texts: &'static Texts,
+}
+
+impl Catalog {
+    /// The catalog for `language`, a language code or a locale name. Falls
+    /// back to English when no bundled language matches.
+    pub fn new(language: &str) -> Self {
+        let wanted = language.trim().to_lowercase();
+        let primary = wanted.split(['_', '-', '.']).next().unwrap_or_default();
+        let found = [wanted.as_str(), primary]
+            .into_iter()
+            .find_map(|code| CATALOGS.get_key_value(code));
+        match found {
+            Some((language, texts)) => Catalog { language, texts },
+            None => Self::english(),
+        }
+    }
+
+    pub fn english() -> Self {
+        let (language, texts) = CATALOGS
+            .get_key_value(DEFAULT_LANGUAGE)
+            .expect("messages.json has English");
+        Catalog { language, texts }
+    }
+
+    /// The code of the language this catalog is in.
+    pub fn language(&self) -> &'static str {
+        self.language
+    }
+
+    /// The text for `msg` with `args` filled into its `{name}` placeholders.
+    pub fn format(&self, msg: Msg, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+        let english = &CATALOGS[DEFAULT_LANGUAGE];
+        let mut text = lookup(self.texts, english, msg.key()).to_string();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        text
+    }
+
+    /// Whether `answer` says yes to a `[y/N]` question. English answers are
+    /// taken in every language.
+    pub fn is_yes(&self, answer: &str) -> bool {
+        self.is_answer(Msg::ConfirmYesAnswers, answer)
+    }
+
+    /// Whether `answer` is one of the `|`-separated answers `msg` lists, in
+    /// this language or in English.
+    pub fn is_answer(&self, msg: Msg, answer: &str) -> bool {
+        let answer = answer.trim().to_lowercase();
+        [self, &Catalog::english()].iter().any(|catalog| {
+            catalog
+                .format(msg, &[])
+                .split('|')
+                .any(|listed| !listed.is_empty() && listed == answer)
+        })
+    }
+}
+
+/// `key` in `texts`, else in `english`. A key missing from English too, which
+/// the tests rule out, shows as itself.
+fn lookup<'a>(texts: &'a Texts, english: &'a Texts, key: &'a str) -> &'a str {
+    texts
+        .get(key)
+        .or_else(|| english.get(key))
+        .map_or(key, String::as_str)
+}
+
+/// Pick the language for the rest of the process: `language`, else
+/// [`LANG_ENV`], else English. Only the first call has an effect.
+pub fn select(language: Option<&str>) -> Catalog {
+    let language = language
+        .map(str::to_string)
+        .or_else(|| std::env::var(LANG_ENV).ok());
+    *SELECTED.get_or_init(|| language.map_or_else(Catalog::english, |l| Catalog::new(&l)))
+}
+
+/// The selected catalog; English until [`select`] is called.
+pub fn catalog() -> Catalog {
+    SELECTED.get().copied().unwrap_or_else(Catalog::english)
+}
+
+/// [`Catalog::format`] in the selected language.
+pub fn format(msg: Msg, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    catalog().format(msg, args)
+}
+
+/// The text for `msg` in the selected language, for texts without
+/// placeholders.
+pub fn text(msg: Msg) -> String {
+    format(msg, &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = text
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    #[test]
+    fn test_english_matches_the_key_list() {
+        let english = &CATALOGS[DEFAULT_LANGUAGE];
+        for key in english.keys() {
+            assert!(
+                Msg::ALL.iter().any(|msg| msg.key() == key),
+                "messages.json has English for \"{key}\", which no Msg names"
+            );
+        }
+        for msg in Msg::ALL {
+            assert!(english.contains_key(msg.key()), "no English for {msg:?}");
+        }
+    }
+
+    #[test]
+    fn test_translations_use_known_keys_and_placeholders() {
+        let english = &CATALOGS[DEFAULT_LANGUAGE];
+        assert!(CATALOGS.len() >= 2);
+        for (language, texts) in CATALOGS.iter() {
+            for (key, text) in texts {
+                let Some(original) = english.get(key) else {
+                    panic!("{language} translates unknown key \"{key}\"");
+                };
+                assert_eq!(
+                    placeholders(text),
+                    placeholders(original),
+                    "{language} \"{key}\" has other placeholders than English"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_language_selection_and_fallback() {
+        assert_eq!(Catalog::new("es").language(), "es");
+        assert_eq!(Catalog::new("es_ES.UTF-8").language(), "es");
+        assert_eq!(Catalog::new("ES").language(), "es");
+        assert_eq!(Catalog::new("tlh").language(), "en");
+
+        let spanish = Catalog::new("es");
+        assert_eq!(
+            spanish.format(Msg::ConfirmSignSets, &[("count", &2)]),
+            "¿Firmar 2 conjunto(s) de transacciones?"
+        );
+        assert!(spanish.is_yes("Sí"));
+        assert!(spanish.is_yes("y"));
+        assert!(!spanish.is_yes("n"));
+        assert!(!Catalog::english().is_yes("s"));
+        assert!(spanish.is_answer(Msg::PromptTakeAnswers, "t"));
+        assert!(spanish.is_answer(Msg::PromptTakeAnswers, "r"));
+        assert!(!Catalog::english().is_answer(Msg::PromptTakeAnswers, "t"));
+
+        // A key a language leaves out comes back in English.
+        let partial: Texts = [("next.header".to_string(), "Próximos pasos:".to_string())]
+            .into_iter()
+            .collect();
+        let english = &CATALOGS[DEFAULT_LANGUAGE];
+        assert_eq!(lookup(&partial, english, "next.header"), "Próximos pasos:");
+        assert_eq!(
+            lookup(&partial, english, "confirm.build"),
+            "Build this transaction?"
+        );
+    }
+
+    #[test]
+    fn test_no_catalog_takes_an_empty_answer() {
+        let answers = [
+            Msg::ConfirmYesAnswers,
+            Msg::PromptKeepAnswers,
+            Msg::PromptTakeAnswers,
+        ];
+        for language in CATALOGS.keys() {
+            let catalog = Catalog::new(language);
+            for msg in answers {
+                assert!(!catalog.is_answer(msg, ""), "{language} {msg:?}");
+                assert!(!catalog.is_answer(msg, "  "), "{language} {msg:?}");
+            }
+        }
+        // A stray `|` in a translation doesn't make Enter an answer either.
+        let texts: Texts = [("confirm.yes_answers".to_string(), "s||sí|".to_string())]
+            .into_iter()
+            .collect();
+        let catalog = Catalog {
+            language: "xx",
+            texts: Box::leak(Box::new(texts)),
+        };
+        assert!(catalog.is_yes("s"));
+        assert!(!catalog.is_yes(""));
+    }
+}
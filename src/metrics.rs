@@ -0,0 +1,412 @@
+//! A tiny metrics registry with a Prometheus text-format endpoint, for
+//! long-running commands such as `monitor`.
+//!
+//! Metrics are process-wide statics and are always recorded; they are only
+//! exposed when [`serve`] is started with `--metrics-addr`. `serve` needs the
+//! `metrics` cargo feature (on with the default `cli`); embedders without it
+//! can still [`render`] the registry themselves.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+#[cfg(feature = "metrics")]
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+#[cfg(feature = "metrics")]
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Upper bounds, in seconds, of the latency histogram buckets.
+const LATENCY_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+type Series<T> = Mutex<BTreeMap<Vec<String>, T>>;
+
+/// A monotonically increasing count, split by label values.
+pub struct Counter {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    series: Series<u64>,
+}
+
+impl Counter {
+    const fn new(name: &'static str, help: &'static str, labels: &'static [&'static str]) -> Self {
+        Self {
+            name,
+            help,
+            labels,
+            series: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Add one to the series for `values`, given in label order.
+    pub fn inc(&self, values: &[&str]) {
+        self.add(values, 1);
+    }
+
+    /// Add `n` to the series for `values`.
+    pub fn add(&self, values: &[&str], n: u64) {
+        let mut series = self.series.lock().expect("metrics lock poisoned");
+        *series.entry(owned(values)).or_default() += n;
+    }
+
+    fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "counter");
+        for (values, count) in self.series.lock().expect("metrics lock poisoned").iter() {
+            let _ = writeln!(
+                out,
+                "{}{} {count}",
+                self.name,
+                labels(self.labels, values, None)
+            );
+        }
+    }
+}
+
+/// A value that can go up and down.
+pub struct Gauge {
+    name: &'static str,
+    help: &'static str,
+    value: Mutex<Option<f64>>,
+}
+
+impl Gauge {
+    const fn new(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            value: Mutex::new(None),
+        }
+    }
+
+    pub fn set(&self, value: f64) {
+        *self.value.lock().expect("metrics lock poisoned") = Some(value);
+    }
+
+    fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "gauge");
+        if let Some(value) = *self.value.lock().expect("metrics lock poisoned") {
+            let _ = writeln!(out, "{} {value}", self.name);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Buckets {
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+/// Observations counted into [`LATENCY_BUCKETS`], split by label values.
+pub struct Histogram {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    series: Series<Buckets>,
+}
+
+impl Histogram {
+    const fn new(name: &'static str, help: &'static str, labels: &'static [&'static str]) -> Self {
+        Self {
+            name,
+            help,
+            labels,
+            series: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn observe(&self, values: &[&str], value: f64) {
+        let mut series = self.series.lock().expect("metrics lock poisoned");
+        let buckets = series.entry(owned(values)).or_default();
+        if buckets.counts.is_empty() {
+            buckets.counts = vec![0; LATENCY_BUCKETS.len()];
+        }
+        for (count, bound) in buckets.counts.iter_mut().zip(LATENCY_BUCKETS) {
+            if value <= *bound {
+                *count += 1;
+            }
+        }
+        buckets.sum += value;
+        buckets.count += 1;
+    }
+
+    fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "histogram");
+        let name = self.name;
+        for (values, b) in self.series.lock().expect("metrics lock poisoned").iter() {
+            for (count, bound) in b.counts.iter().zip(LATENCY_BUCKETS) {
+                let le = bound.to_string();
+                let _ = writeln!(
+                    out,
+                    "{name}_bucket{} {count}",
+                    labels(self.labels, values, Some(&le))
+                );
+            }
+            let all = labels(self.labels, values, Some("+Inf"));
+            let _ = writeln!(out, "{name}_bucket{all} {}", b.count);
+            let plain = labels(self.labels, values, None);
+            let _ = writeln!(out, "{name}_sum{plain} {}", b.sum);
+            let _ = writeln!(out, "{name}_count{plain} {}", b.count);
+        }
+    }
+}
+
+fn owned(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// Format `{a="x",b="y"}`, with an optional histogram `le` label.
+fn labels(names: &[&str], values: &[String], le: Option<&str>) -> String {
+    let mut pairs: Vec<String> = names
+        .iter()
+        .zip(values)
+        .map(|(n, v)| format!("{n}=\"{}\"", v.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect();
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{le}\""));
+    }
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+pub static RPC_REQUESTS: Counter = Counter::new(
+    "multisig_rpc_requests_total",
+    "RPC requests by method and outcome.",
+    &["method", "outcome"],
+);
+
+pub static RPC_LATENCY: Histogram = Histogram::new(
+    "multisig_rpc_duration_seconds",
+    "RPC request latency in seconds.",
+    &["method"],
+);
+
+pub static RPC_RESPONSE_BYTES: Counter = Counter::new(
+    "multisig_rpc_response_bytes_total",
+    "Bytes of RPC response bodies by method.",
+    &["method"],
+);
+
+pub static KEY_EXCHANGE_ROUNDS: Counter = Counter::new(
+    "multisig_key_exchange_rounds_total",
+    "Key exchange rounds completed.",
+    &[],
+);
+
+pub static TX_SETS: Counter = Counter::new(
+    "multisig_tx_sets_total",
+    "Tx sets built, signed, or submitted here.",
+    &["stage"],
+);
+
+pub static WEBHOOK_FAILURES: Counter = Counter::new(
+    "multisig_webhook_failures_total",
+    "Webhook deliveries that failed after all retries.",
+    &[],
+);
+
+pub static HEIGHT_GAP: Gauge = Gauge::new(
+    "multisig_wallet_height_gap_blocks",
+    "Blocks the wallet is behind the daemon.",
+);
+
+/// Calls of one RPC method made by this process, for `--timing`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodTiming {
+    pub method: String,
+    pub count: u64,
+    pub total: Duration,
+    pub max: Duration,
+    /// Part of `total` spent waiting on the endpoint's request limits.
+    pub waited: Duration,
+}
+
+static RPC_TIMINGS: Mutex<BTreeMap<String, MethodTiming>> = Mutex::new(BTreeMap::new());
+
+/// Record one finished RPC call.
+pub fn record_rpc(
+    method: &str,
+    ok: bool,
+    elapsed: Duration,
+    waited: Duration,
+    response_bytes: usize,
+) {
+    RPC_REQUESTS.inc(&[method, if ok { "ok" } else { "error" }]);
+    RPC_LATENCY.observe(&[method], elapsed.as_secs_f64());
+    RPC_RESPONSE_BYTES.add(&[method], response_bytes as u64);
+    let mut timings = RPC_TIMINGS.lock().expect("metrics lock poisoned");
+    let timing = timings
+        .entry(method.to_string())
+        .or_insert_with(|| MethodTiming {
+            method: method.to_string(),
+            count: 0,
+            total: Duration::ZERO,
+            max: Duration::ZERO,
+            waited: Duration::ZERO,
+        });
+    timing.count += 1;
+    timing.total += elapsed;
+    timing.max = timing.max.max(elapsed);
+    timing.waited += waited;
+}
+
+/// Every RPC method called so far, the one that took longest in total first.
+pub fn rpc_timings() -> Vec<MethodTiming> {
+    let mut timings: Vec<MethodTiming> = RPC_TIMINGS
+        .lock()
+        .expect("metrics lock poisoned")
+        .values()
+        .cloned()
+        .collect();
+    timings.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.method.cmp(&b.method)));
+    timings
+}
+
+/// Calls of `method` made so far, if any.
+pub fn rpc_timing(method: &str) -> Option<MethodTiming> {
+    RPC_TIMINGS
+        .lock()
+        .expect("metrics lock poisoned")
+        .get(method)
+        .cloned()
+}
+
+/// Every metric in Prometheus text format.
+pub fn render() -> String {
+    let mut out = String::new();
+    RPC_REQUESTS.render(&mut out);
+    RPC_LATENCY.render(&mut out);
+    RPC_RESPONSE_BYTES.render(&mut out);
+    KEY_EXCHANGE_ROUNDS.render(&mut out);
+    TX_SETS.render(&mut out);
+    WEBHOOK_FAILURES.render(&mut out);
+    HEIGHT_GAP.render(&mut out);
+    out
+}
+
+/// First pause after a failed accept; it doubles up to [`ACCEPT_RETRY_MAX`]
+/// while accepting keeps failing.
+#[cfg(feature = "metrics")]
+const ACCEPT_RETRY_START: Duration = Duration::from_millis(10);
+#[cfg(feature = "metrics")]
+const ACCEPT_RETRY_MAX: Duration = Duration::from_secs(1);
+
+/// Serve `GET /metrics` on `addr` in the background. Returns the bound
+/// address, which differs from `addr` when it asks for port 0.
+#[cfg(feature = "metrics")]
+pub async fn serve(addr: SocketAddr) -> std::io::Result<SocketAddr> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    let bound = listener.local_addr()?;
+    tokio::spawn(async move {
+        let mut pause = ACCEPT_RETRY_START;
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                // Usually out of file descriptors: spinning on accept
+                // would only keep them from being freed.
+                Err(e) => {
+                    tracing::warn!(error = %e, "metrics endpoint could not accept a connection");
+                    tokio::time::sleep(pause).await;
+                    pause = (pause * 2).min(ACCEPT_RETRY_MAX);
+                    continue;
+                }
+            };
+            pause = ACCEPT_RETRY_START;
+            tokio::spawn(async move {
+                if let Err(e) = respond(stream).await {
+                    tracing::debug!(error = %e, "metrics request failed");
+                }
+            });
+        }
+    });
+    Ok(bound)
+}
+
+#[cfg(feature = "metrics")]
+async fn respond(mut stream: tokio::net::TcpStream) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 8192 {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+    let line = String::from_utf8_lossy(&request);
+    let ok = line.starts_with("GET /metrics ") || line.starts_with("GET /metrics?");
+    let (status, body) = if ok {
+        ("200 OK", render())
+    } else {
+        ("404 Not Found", "not found\n".to_string())
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_and_labels() {
+        let counter = Counter::new("t_total", "Test.", &["method", "outcome"]);
+        counter.inc(&["get_info", "ok"]);
+        counter.inc(&["get_info", "ok"]);
+        counter.inc(&["say \"hi\"", "error"]);
+        let mut out = String::new();
+        counter.render(&mut out);
+        assert!(out.contains("# TYPE t_total counter"));
+        assert!(out.contains("t_total{method=\"get_info\",outcome=\"ok\"} 2"));
+        assert!(out.contains("t_total{method=\"say \\\"hi\\\"\",outcome=\"error\"} 1"));
+    }
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let histogram = Histogram::new("t_seconds", "Test.", &[]);
+        histogram.observe(&[], 0.02);
+        histogram.observe(&[], 3.0);
+        let mut out = String::new();
+        histogram.render(&mut out);
+        assert!(out.contains("t_seconds_bucket{le=\"0.01\"} 0"));
+        assert!(out.contains("t_seconds_bucket{le=\"0.025\"} 1"));
+        assert!(out.contains("t_seconds_bucket{le=\"5\"} 2"));
+        assert!(out.contains("t_seconds_bucket{le=\"+Inf\"} 2"));
+        assert!(out.contains("t_seconds_count 2"));
+    }
+
+    #[test]
+    fn test_rpc_timings_keep_count_total_and_max() {
+        let ms = Duration::from_millis;
+        record_rpc("t_timed_method", true, ms(30), ms(10), 100);
+        record_rpc("t_timed_method", false, ms(120), ms(0), 20);
+        let timing = rpc_timings()
+            .into_iter()
+            .find(|t| t.method == "t_timed_method")
+            .unwrap();
+        assert_eq!(
+            (timing.count, timing.total, timing.max, timing.waited),
+            (2, ms(150), ms(120), ms(10))
+        );
+        assert_eq!(rpc_timing("t_timed_method"), Some(timing));
+        assert_eq!(rpc_timing("t_never_called"), None);
+        assert!(
+            render().contains("multisig_rpc_response_bytes_total{method=\"t_timed_method\"} 120")
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Incoming payment detection for the `monitor` command. Webhook delivery
+//! lives in [`crate::notify`].
+//!
+//! [`Monitor`] turns repeated `get_transfers` snapshots into one event per
+//! new incoming transfer. A transfer is keyed by txid and subaddress, so one
+//! seen first in the pool isn't reported again once it is mined.
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::error::TransactionError;
+use crate::transaction::{Amount, SubaddressIndex, TransferEntry, TransferKind};
+
+/// A newly detected incoming transfer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IncomingTransfer {
+    pub txid: String,
+    pub amount: u64,
+    pub subaddr_index: SubaddressIndex,
+    pub confirmations: u64,
+    /// Still in the mempool when first seen.
+    pub in_pool: bool,
+}
+
+impl From<&TransferEntry> for IncomingTransfer {
+    fn from(t: &TransferEntry) -> Self {
+        Self {
+            txid: t.txid.clone(),
+            amount: t.amount,
+            subaddr_index: t.subaddr_index,
+            confirmations: t.confirmations,
+            in_pool: t.kind == TransferKind::Pool,
+        }
+    }
+}
+
+/// Remembers which incoming transfers have been seen across polls.
+#[derive(Debug, Default)]
+pub struct Monitor {
+    /// Latest view of each reported transfer.
+    seen: HashMap<(String, SubaddressIndex), IncomingTransfer>,
+    /// Transfers present at startup; never reported or counted.
+    ignored: HashSet<(String, SubaddressIndex)>,
+}
+
+fn key(t: &TransferEntry) -> (String, SubaddressIndex) {
+    (t.txid.clone(), t.subaddr_index)
+}
+
+fn is_incoming(t: &TransferEntry) -> bool {
+    matches!(t.kind, TransferKind::In | TransferKind::Pool)
+}
+
+impl Monitor {
+    /// Treat `transfers` as already known, so only later ones are reported.
+    pub fn baseline(&mut self, transfers: &[TransferEntry]) {
+        for t in transfers.iter().filter(|t| is_incoming(t)) {
+            self.ignored.insert(key(t));
+        }
+    }
+
+    /// Take a new snapshot and return the transfers not seen before.
+    /// Confirmation counts of already reported transfers are updated.
+    pub fn observe(&mut self, transfers: &[TransferEntry]) -> Vec<IncomingTransfer> {
+        let mut new = Vec::new();
+        for t in transfers.iter().filter(|t| is_incoming(t)) {
+            let key = key(t);
+            if self.ignored.contains(&key) {
+                continue;
+            }
+            match self.seen.get_mut(&key) {
+                Some(known) => {
+                    known.confirmations = known.confirmations.max(t.confirmations);
+                }
+                None => {
+                    let event = IncomingTransfer::from(t);
+                    self.seen.insert(key, event.clone());
+                    new.push(event);
+                }
+            }
+        }
+        new
+    }
+
+    /// Total reported so far with at least `min_confirmations`.
+    pub fn received(&self, min_confirmations: u64) -> Result<Amount, TransactionError> {
+        Amount::checked_sum(
+            self.seen
+                .values()
+                .filter(|t| t.confirmations >= min_confirmations)
+                .map(|t| Amount(t.amount)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer(txid: &str, kind: TransferKind, amount: u64, confirmations: u64) -> TransferEntry {
+        TransferEntry {
+            txid: txid.into(),
+            kind,
+            amount,
+            fee: 0,
+            height: 0,
+            timestamp: 0,
+            confirmations,
+            address: String::new(),
+            subaddr_index: SubaddressIndex { major: 0, minor: 1 },
+        }
+    }
+
+    #[test]
+    fn test_pool_transfer_reported_once() {
+        let mut monitor = Monitor::default();
+        let first = monitor.observe(&[transfer("aa", TransferKind::Pool, 5, 0)]);
+        assert_eq!(first.len(), 1);
+        assert!(first[0].in_pool);
+
+        let mined = monitor.observe(&[transfer("aa", TransferKind::In, 5, 3)]);
+        assert!(mined.is_empty());
+        assert_eq!(monitor.received(0).unwrap(), Amount(5));
+        assert_eq!(monitor.received(3).unwrap(), Amount(5));
+        assert_eq!(monitor.received(4).unwrap(), Amount(0));
+    }
+
+    #[test]
+    fn test_baseline_and_outgoing_ignored() {
+        let mut monitor = Monitor::default();
+        monitor.baseline(&[transfer("old", TransferKind::In, 7, 100)]);
+        let events = monitor.observe(&[
+            transfer("old", TransferKind::In, 7, 101),
+            transfer("out", TransferKind::Out, 2, 1),
+            transfer("new", TransferKind::In, 9, 1),
+        ]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].txid, "new");
+        assert_eq!(monitor.received(0).unwrap(), Amount(9));
+    }
+
+    #[test]
+    fn test_same_tx_to_two_subaddresses() {
+        let mut monitor = Monitor::default();
+        let mut other = transfer("aa", TransferKind::In, 3, 1);
+        other.subaddr_index.minor = 2;
+        let events = monitor.observe(&[transfer("aa", TransferKind::In, 5, 1), other]);
+        assert_eq!(events.len(), 2);
+        assert_eq!(monitor.received(1).unwrap(), Amount(8));
+    }
+
+    #[test]
+    fn test_received_overflow_is_an_error() {
+        let mut monitor = Monitor::default();
+        monitor.observe(&[
+            transfer("aa", TransferKind::In, u64::MAX, 1),
+            transfer("bb", TransferKind::In, 1, 1),
+        ]);
+        assert!(matches!(
+            monitor.received(1),
+            Err(TransactionError::AmountOverflow)
+        ));
+    }
+}
@@ -0,0 +1,364 @@
+//! Webhook notifications for the multisig lifecycle, and the HTTP delivery
+//! shared with the `monitor` command.
+//!
+//! Payloads are signed with HMAC-SHA256 over the raw body when a secret is
+//! configured; receivers should check the `X-Multisig-Signature` header.
+//! Events that can't be delivered are appended to
+//! `data_dir/notification_spool.jsonl` and retried before the next event.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::config::DataDir;
+use crate::journal::JournalEntry;
+
+const SPOOL_FILE: &str = "notification_spool.jsonl";
+
+/// Delivery attempts per webhook event before giving up.
+pub const WEBHOOK_ATTEMPTS: u32 = 5;
+
+/// Delay before the first webhook retry; doubles after each failure.
+const WEBHOOK_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Header carrying `sha256=<hex hmac>` of the request body.
+pub const SIGNATURE_HEADER: &str = "X-Multisig-Signature";
+
+/// The `notifications` section of the config file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Notifications {
+    /// Where lifecycle events are POSTed. Notifications are off when unset.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Shared secret for the payload signature.
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+/// Which lifecycle step an [`Event`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    TxBuilt,
+    TxSigned,
+    TxSubmitted,
+    TxConfirmed,
+    KeyExchangeRoundComplete,
+    /// Sent by `notifications test`.
+    Ping,
+}
+
+/// One notification payload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub event: EventKind,
+    pub at: DateTime<Utc>,
+    /// Hostname of the machine that performed the action.
+    pub actor: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wallet_label: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tx_hashes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signatures_collected: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signatures_required: Option<u32>,
+    /// Multisig address, once key exchange has finished.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+}
+
+impl Event {
+    pub fn new(event: EventKind) -> Self {
+        Self {
+            event,
+            at: Utc::now(),
+            actor: gethostname::gethostname().to_string_lossy().into_owned(),
+            wallet_label: None,
+            tx_hashes: Vec::new(),
+            amount: None,
+            fee: None,
+            signatures_collected: None,
+            signatures_required: None,
+            address: None,
+        }
+    }
+
+    /// An event describing the transaction in a journal entry.
+    pub fn for_entry(event: EventKind, entry: &JournalEntry) -> Self {
+        Self {
+            tx_hashes: entry.tx_hashes.clone(),
+            amount: Some(entry.amount()),
+            fee: Some(entry.fee),
+            signatures_collected: Some(entry.signatures_collected),
+            signatures_required: Some(entry.signatures_required),
+            ..Self::new(event)
+        }
+    }
+}
+
+/// Hex HMAC-SHA256 of `body` under `secret`.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// A webhook delivery that failed on every attempt.
+#[derive(Error, Debug)]
+#[error("giving up after {attempts} attempts: {last}")]
+pub struct WebhookError {
+    pub attempts: u32,
+    pub last: String,
+}
+
+/// Posts JSON payloads to a URL, retrying with backoff.
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    client: reqwest::Client,
+    url: String,
+    secret: Option<String>,
+    attempts: u32,
+}
+
+impl Webhook {
+    pub fn new(url: String) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("failed to build HTTP client");
+        Self {
+            client,
+            url,
+            secret: None,
+            attempts: WEBHOOK_ATTEMPTS,
+        }
+    }
+
+    /// Sign every payload with `secret`.
+    pub fn with_secret(mut self, secret: Option<String>) -> Self {
+        self.secret = secret;
+        self
+    }
+
+    /// Try each delivery at most `attempts` times (at least once).
+    pub fn with_attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Deliver `payload`, retrying on failure.
+    pub async fn send<T: Serialize>(&self, payload: &T) -> Result<(), WebhookError> {
+        let body = serde_json::to_vec(payload).expect("payloads always serialize");
+        let mut delay = WEBHOOK_BACKOFF;
+        let mut last = String::new();
+        for attempt in 1..=self.attempts {
+            let mut request = self
+                .client
+                .post(&self.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.clone());
+            if let Some(secret) = &self.secret {
+                request =
+                    request.header(SIGNATURE_HEADER, format!("sha256={}", sign(secret, &body)));
+            }
+            match request.send().await.and_then(|r| r.error_for_status()) {
+                Ok(_) => return Ok(()),
+                Err(e) => {
+                    tracing::warn!(attempt, error = %e, "webhook delivery failed");
+                    last = e.to_string();
+                }
+            }
+            if attempt < self.attempts {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+        }
+        crate::metrics::WEBHOOK_FAILURES.inc(&[]);
+        Err(WebhookError {
+            attempts: self.attempts,
+            last,
+        })
+    }
+}
+
+/// Errors from [`Notifier::notify`].
+#[derive(Error, Debug)]
+pub enum NotifyError {
+    #[error("webhook delivery failed, event spooled for retry: {0}")]
+    Spooled(WebhookError),
+
+    #[error("webhook delivery failed and the event could not be spooled: {0}")]
+    Lost(std::io::Error),
+}
+
+/// Sends lifecycle events, spooling those that can't be delivered.
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    webhook: Webhook,
+    spool: PathBuf,
+}
+
+impl Notifier {
+    /// Lifecycle events are sent from interactive commands, so give up
+    /// sooner than the monitor does and rely on the spool.
+    const ATTEMPTS: u32 = 3;
+
+    /// A notifier for `settings`, or `None` when no webhook is configured.
+    pub fn new(settings: &Notifications, data_dir: &DataDir) -> Option<Self> {
+        let url = settings.webhook_url.clone()?;
+        Some(Self {
+            webhook: Webhook::new(url)
+                .with_secret(settings.secret.clone())
+                .with_attempts(Self::ATTEMPTS),
+            spool: data_dir.file(SPOOL_FILE),
+        })
+    }
+
+    /// Replay spooled events, then deliver `event`.
+    pub async fn notify(&self, event: &Event) -> Result<(), NotifyError> {
+        if let Err(e) = self.flush_spool().await {
+            tracing::warn!(error = %e, "could not replay notification spool");
+        }
+        match self.webhook.send(event).await {
+            Ok(()) => Ok(()),
+            Err(err) => match append_spool(&self.spool, event) {
+                Ok(()) => Err(NotifyError::Spooled(err)),
+                Err(io) => Err(NotifyError::Lost(io)),
+            },
+        }
+    }
+
+    /// Deliver `event` once, without retries or spooling.
+    pub async fn ping(&self, event: &Event) -> Result<(), WebhookError> {
+        self.webhook.clone().with_attempts(1).send(event).await
+    }
+
+    /// Send spooled events in order, stopping at the first failure so the
+    /// endpoint isn't hammered while it is still down. Returns how many were
+    /// delivered.
+    pub async fn flush_spool(&self) -> std::io::Result<usize> {
+        let spooled = read_spool(&self.spool)?;
+        if spooled.is_empty() {
+            return Ok(0);
+        }
+        let once = self.webhook.clone().with_attempts(1);
+        let mut delivered = 0;
+        for event in &spooled {
+            if once.send(event).await.is_err() {
+                break;
+            }
+            delivered += 1;
+        }
+        write_spool(&self.spool, &spooled[delivered..])?;
+        Ok(delivered)
+    }
+}
+
+fn read_spool(path: &Path) -> std::io::Result<Vec<Event>> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(text
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|l| match serde_json::from_str(l) {
+            Ok(event) => Some(event),
+            Err(e) => {
+                tracing::warn!(error = %e, "skipping unreadable spooled notification");
+                None
+            }
+        })
+        .collect())
+}
+
+fn write_spool(path: &Path, events: &[Event]) -> std::io::Result<()> {
+    if events.is_empty() {
+        return match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    let mut text = String::new();
+    for event in events {
+        text.push_str(&serde_json::to_string(event)?);
+        text.push('\n');
+    }
+    crate::utils::write_atomic(path, text.as_bytes())
+}
+
+fn append_spool(path: &Path, event: &Event) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_matches_rfc_4231() {
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_event_omits_unset_fields() {
+        let json = serde_json::to_value(Event::new(EventKind::Ping)).unwrap();
+        assert_eq!(json["event"], "ping");
+        assert!(json.get("amount").is_none());
+        assert!(json.get("tx_hashes").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_undeliverable_event_is_spooled_and_kept() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = DataDir::new(tmp.path());
+        let settings = Notifications {
+            // Nothing listens on port 1.
+            webhook_url: Some("http://127.0.0.1:1/hook".into()),
+            secret: Some("s".into()),
+        };
+        let notifier = Notifier::new(&settings, &dir).unwrap();
+        let notifier = Notifier {
+            webhook: notifier.webhook.with_attempts(1),
+            ..notifier
+        };
+
+        let err = notifier.notify(&Event::new(EventKind::TxBuilt)).await;
+        assert!(matches!(err, Err(NotifyError::Spooled(_))));
+        assert_eq!(read_spool(&dir.file(SPOOL_FILE)).unwrap().len(), 1);
+
+        // Still down: the spooled event stays, and the new one joins it.
+        let err = notifier.notify(&Event::new(EventKind::TxSigned)).await;
+        assert!(err.is_err());
+        let spooled = read_spool(&dir.file(SPOOL_FILE)).unwrap();
+        assert_eq!(spooled.len(), 2);
+        assert_eq!(spooled[0].event, EventKind::TxBuilt);
+    }
+
+    #[test]
+    fn test_notifier_off_without_url() {
+        assert!(Notifier::new(&Notifications::default(), &DataDir::new(".")).is_none());
+    }
+}
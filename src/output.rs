@@ -0,0 +1,1885 @@
+//! The `--output json` format.
+//!
+//! Every JSON result is wrapped in an envelope:
+//!
+//! ```json
+//! { "schema": 1, "command": "balance", "result": { ... } }
+//! ```
+//!
+//! Commands run against a wallet also list what to do next as
+//! `"next_steps": ["export_info", ...]`, identifiers from
+//! [`workflow::Action`](crate::workflow::Action); the list is empty when
+//! nothing is waiting.
+//!
+//! Scripts should check `schema` before reading `result`. Within one schema
+//! version fields may be added but are never removed, renamed, or retyped;
+//! anything else bumps [`SCHEMA_VERSION`]. Fields are written in struct
+//! declaration order, so the output is byte-for-byte stable for the same
+//! data. `--schema` prints the JSON Schema from [`schema`] for a command.
+//!
+//! Types that exist only to shape a command's JSON result live here; the rest
+//! are the library's own types, serialized as they are.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::error::RejectionReason;
+use crate::manifest::{Difference, Manifest};
+use crate::rpc::methods::TransferRecord;
+use crate::transaction::{
+    Amount, FeeAlgorithm, PoolPayment, Priority, ReviewedOutput, TransferDescription,
+    TransferEntry, Visibility,
+};
+use crate::wallet::{AddressVerification, ChainInfo, Subaddress, WalletFile};
+use crate::workflow::Action;
+
+/// Version of the envelope and every result inside it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    schema: u32,
+    command: &'a str,
+    result: &'a T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_steps: Option<&'a [Action]>,
+}
+
+/// `result` wrapped in the envelope for `command`, pretty-printed.
+pub fn to_json<T: Serialize>(command: &str, result: &T) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&Envelope {
+        schema: SCHEMA_VERSION,
+        command,
+        result,
+        next_steps: None,
+    })
+}
+
+/// Like [`to_json`], with the envelope's `next_steps`.
+pub fn to_json_with_steps<T: Serialize>(
+    command: &str,
+    result: &T,
+    next_steps: &[Action],
+) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&Envelope {
+        schema: SCHEMA_VERSION,
+        command,
+        result,
+        next_steps: Some(next_steps),
+    })
+}
+
+/// `result` wrapped in the envelope for `command` on a single line, for
+/// commands like `monitor` that stream one result per line.
+pub fn to_json_line<T: Serialize>(command: &str, result: &T) -> serde_json::Result<String> {
+    serde_json::to_string(&Envelope {
+        schema: SCHEMA_VERSION,
+        command,
+        result,
+        next_steps: None,
+    })
+}
+
+/// The verdict on a spend proof, for `check-spend-proof`.
+#[derive(Serialize)]
+pub struct SpendProofCheck<'a> {
+    pub txid: &'a str,
+    pub message: &'a str,
+    pub good: bool,
+}
+
+/// A decoded transfer with its outputs labelled, for `describe`.
+#[derive(Serialize)]
+pub struct DescribedTransfer<'a> {
+    #[serde(flatten)]
+    pub description: &'a TransferDescription,
+    pub outputs: Vec<ReviewedOutput>,
+    pub leaving_amount: Amount,
+}
+
+/// What is known about one transaction, for `show-tx`: the wallet's
+/// transfer, or where the daemon sees a transaction the wallet doesn't know.
+#[derive(Serialize)]
+pub struct ShownTx<'a> {
+    pub txid: &'a str,
+    pub transfer: Option<&'a TransferRecord>,
+    /// The journal's note, else the wallet's.
+    pub note: Option<&'a str>,
+    /// Only asked for when the wallet has no transfer.
+    pub visibility: Option<Visibility>,
+}
+
+/// The `address` result; `subaddresses` only with `--all`.
+#[derive(Serialize)]
+pub struct AddressResult {
+    pub account: u32,
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subaddresses: Option<Vec<Subaddress>>,
+}
+
+/// The `create-account` result.
+#[derive(Serialize)]
+pub struct NewAccount<'a> {
+    pub account_index: u32,
+    pub address: &'a str,
+    pub label: &'a str,
+}
+
+/// The `verify-address` result: the report plus the state's verification
+/// status after this run.
+#[derive(Serialize)]
+pub struct VerifyAddressResult<'a> {
+    #[serde(flatten)]
+    pub report: &'a AddressVerification,
+    pub verified: bool,
+    pub verified_at: Option<DateTime<Utc>>,
+}
+
+/// The `incoming` result. `confirmed` is empty with `--pool`.
+#[derive(Serialize)]
+pub struct IncomingResult<'a> {
+    pub pool: &'a [PoolPayment],
+    pub confirmed: &'a [TransferEntry],
+}
+
+/// The `list-wallet-files` result. Without `listed_by_rpc`, `files` are
+/// only the wallets known in the data directory.
+#[derive(Serialize)]
+pub struct WalletFilesResult<'a> {
+    pub listed_by_rpc: bool,
+    pub files: &'a [WalletFile],
+}
+
+/// The `request-payment` result; `subaddress_index` only when a fresh
+/// subaddress was made.
+#[derive(Serialize)]
+pub struct PaymentRequestResult<'a> {
+    pub uri: &'a str,
+    pub address: &'a str,
+    pub amount: Option<Amount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subaddress_index: Option<u32>,
+}
+
+/// The `chain-info` result.
+#[derive(Serialize)]
+pub struct ChainInfoResult<'a> {
+    #[serde(flatten)]
+    pub chain: &'a ChainInfo,
+    pub wallet_behind: u64,
+}
+
+/// The `fees` result.
+#[derive(Serialize)]
+pub struct FeesResult {
+    /// The daemon's base fee, per byte or per kilobyte as `algorithm` says.
+    pub base_fee: u64,
+    pub quantization_mask: u64,
+    pub algorithm: FeeAlgorithm,
+    pub hard_fork: Option<u8>,
+    /// Weight `typical_fee` is worked out for.
+    pub typical_weight: u64,
+    pub levels: Vec<PriorityFee>,
+}
+
+#[derive(Serialize)]
+pub struct PriorityFee {
+    pub priority: Priority,
+    /// Piconero per byte, or per kilobyte.
+    pub rate: u64,
+    pub typical_fee: Amount,
+}
+
+/// The `sign-batch` result.
+#[derive(Serialize)]
+pub struct SignBatchResult {
+    /// File names of the sets that were signed.
+    pub signed: Vec<String>,
+    pub failed: Vec<BatchFailure>,
+}
+
+/// The `submit-tx` and `relay` result.
+#[derive(Serialize)]
+pub struct SubmitResult {
+    pub tx_hashes: Vec<String>,
+    /// What the daemon saw after submitting; `None` when not checked.
+    pub visibility: Option<Visibility>,
+    /// Why the transaction was refused; `None` when it was accepted.
+    pub rejection: Option<Rejection>,
+}
+
+#[derive(Serialize)]
+pub struct Rejection {
+    pub reason: RejectionReason,
+    /// The message from the wallet RPC or daemon.
+    pub detail: String,
+    /// Ids of journal entries that may have spent the same outputs.
+    pub may_share_inputs: Vec<u64>,
+}
+
+/// A tx set `sign-batch` skipped or failed to sign.
+#[derive(Serialize)]
+pub struct BatchFailure {
+    pub file: String,
+    pub error: String,
+}
+
+/// The `inspect-info` result.
+#[derive(Serialize)]
+pub struct InspectedInfo {
+    pub kind: InfoKind,
+    pub armored: bool,
+    pub fingerprint: String,
+    pub digest: String,
+    /// Characters in the blob, armor and whitespace left out.
+    pub length: usize,
+    /// From the `export-info` armor header, when there is one.
+    pub created_at: Option<DateTime<Utc>>,
+    pub export_height: Option<u64>,
+    pub from: Option<String>,
+    /// Wallet height at this wallet's last import.
+    pub wallet_height: Option<u64>,
+    /// How far `export_height` trails `wallet_height`.
+    pub blocks_behind: Option<u64>,
+    pub stale: Option<bool>,
+    /// `None` without a local wallet state.
+    pub status: Option<InfoStatus>,
+    pub imported_at: Option<DateTime<Utc>>,
+}
+
+/// The `verify-tx-set` result.
+#[derive(Serialize)]
+pub struct TxSetVerification<'a> {
+    /// Of the tx set.
+    pub fingerprint: String,
+    pub matches: bool,
+    pub manifest: &'a Manifest,
+    /// Empty when `matches`.
+    pub differences: &'a [Difference],
+}
+
+/// What a blob given to `inspect-info` holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InfoKind {
+    /// `export_multisig_info` output, for `import-info`.
+    MultisigInfo,
+    /// Key exchange info, for `exchange-keys`.
+    KeyExchangeInfo,
+    TxSet,
+    Unknown,
+}
+
+impl std::fmt::Display for InfoKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            InfoKind::MultisigInfo => "multisig info (for import-info)",
+            InfoKind::KeyExchangeInfo => "key exchange info (for exchange-keys)",
+            InfoKind::TxSet => "multisig tx set",
+            InfoKind::Unknown => "unknown",
+        })
+    }
+}
+
+/// Whether this wallet has seen an `inspect-info` blob before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InfoStatus {
+    New,
+    Imported,
+    OwnExport,
+}
+
+/// JSON Schema for the envelope `command` prints with `--output json`, or
+/// `None` when the command has no JSON result.
+pub fn schema(command: &str) -> Option<Value> {
+    let result = match command {
+        "status" => reference("state_file"),
+        "describe" => array(reference("described_transfer")),
+        "history" => array(reference("transfer_entry")),
+        "monitor" => reference("incoming_transfer"),
+        "incoming" => object(
+            json!({
+                "pool": array(reference("pool_payment")),
+                "confirmed": array(reference("transfer_entry")),
+            }),
+            &["pool", "confirmed"],
+        ),
+        "list-outputs" => array(reference("owned_output")),
+        "address" => object(
+            json!({
+                "account": integer(),
+                "address": string(),
+                "subaddresses": array(reference("subaddress")),
+            }),
+            &["account", "address"],
+        ),
+        "list-accounts" | "account" => array(object(
+            json!({
+                "account_index": integer(),
+                "base_address": string(),
+                "balance": integer(),
+                "unlocked_balance": integer(),
+                "label": string(),
+                "tag": nullable(string()),
+            }),
+            &[
+                "account_index",
+                "base_address",
+                "balance",
+                "unlocked_balance",
+                "label",
+                "tag",
+            ],
+        )),
+        "create-account" => object(
+            json!({
+                "account_index": integer(),
+                "address": string(),
+                "label": string(),
+            }),
+            &["account_index", "address", "label"],
+        ),
+        "verify-address" => object(
+            json!({
+                "address": string(),
+                "fingerprint": string(),
+                "threshold": integer(),
+                "total": integer(),
+                "peers": array(object(
+                    json!({ "provided": string(), "matches": boolean() }),
+                    &["provided", "matches"],
+                )),
+                "verified": boolean(),
+                "verified_at": nullable(timestamp()),
+            }),
+            &[
+                "address",
+                "fingerprint",
+                "threshold",
+                "total",
+                "peers",
+                "verified",
+                "verified_at",
+            ],
+        ),
+        "list-wallet-files" => object(
+            json!({
+                "listed_by_rpc": boolean(),
+                "files": array(object(
+                    json!({
+                        "filename": string(),
+                        "label": nullable(string()),
+                        "retired": boolean(),
+                        "multisig": nullable(boolean()),
+                        "modified": nullable(timestamp()),
+                    }),
+                    &["filename", "label", "retired", "multisig", "modified"],
+                )),
+            }),
+            &["listed_by_rpc", "files"],
+        ),
+        "request-payment" => object(
+            json!({
+                "uri": string(),
+                "address": string(),
+                "amount": nullable(integer()),
+                "subaddress_index": integer(),
+            }),
+            &["uri", "address", "amount"],
+        ),
+        "get-version" => object(
+            json!({
+                "version": object(
+                    json!({ "major": integer(), "minor": integer() }),
+                    &["major", "minor"],
+                ),
+                "supports_transfer_split": boolean(),
+                "supports_describe_transfer": boolean(),
+                "multisig_api_generation": { "enum": ["legacy", "current"] },
+            }),
+            &[
+                "version",
+                "supports_transfer_split",
+                "supports_describe_transfer",
+                "multisig_api_generation",
+            ],
+        ),
+        "fees" => object(
+            json!({
+                "base_fee": integer(),
+                "quantization_mask": integer(),
+                "algorithm": { "enum": ["per_kilobyte", "per_byte", "per_priority"] },
+                "hard_fork": nullable(integer()),
+                "typical_weight": integer(),
+                "levels": array(object(
+                    json!({
+                        "priority": { "enum": ["default", "low", "medium", "high"] },
+                        "rate": integer(),
+                        "typical_fee": integer(),
+                    }),
+                    &["priority", "rate", "typical_fee"],
+                )),
+            }),
+            &[
+                "base_fee",
+                "quantization_mask",
+                "algorithm",
+                "hard_fork",
+                "typical_weight",
+                "levels",
+            ],
+        ),
+        "chain-info" => object(
+            json!({
+                "daemon_height": integer(),
+                "wallet_height": integer(),
+                "target_height": integer(),
+                "difficulty": integer(),
+                "synchronized": boolean(),
+                "untrusted": boolean(),
+                "wallet_behind": integer(),
+            }),
+            &[
+                "daemon_height",
+                "wallet_height",
+                "target_height",
+                "difficulty",
+                "synchronized",
+                "untrusted",
+                "wallet_behind",
+            ],
+        ),
+        "doctor" => object(
+            json!({
+                "iterations": integer(),
+                "methods": array(object(
+                    json!({
+                        "method": string(),
+                        "p50_ms": integer(),
+                        "p95_ms": integer(),
+                        "budget_ms": integer(),
+                        "over_budget": boolean(),
+                    }),
+                    &["method", "p50_ms", "p95_ms", "budget_ms", "over_budget"],
+                )),
+                "daemon_synchronized": nullable(boolean()),
+                "causes": array(json!({
+                    "enum": ["network_latency", "daemon_syncing", "disk_bound_wallet"]
+                })),
+            }),
+            &["iterations", "methods", "daemon_synchronized", "causes"],
+        ),
+        "sign-batch" => object(
+            json!({
+                "signed": array(string()),
+                "failed": array(object(
+                    json!({ "file": string(), "error": string() }),
+                    &["file", "error"],
+                )),
+            }),
+            &["signed", "failed"],
+        ),
+        "show-tx" => {
+            let subaddress_index = object(
+                json!({ "major": integer(), "minor": integer() }),
+                &["major", "minor"],
+            );
+            let destination = object(
+                json!({ "address": string(), "amount": integer() }),
+                &["address", "amount"],
+            );
+            let transfer = object(
+                json!({
+                    "txid": string(),
+                    "type": { "enum": ["in", "out", "pending", "failed", "pool"] },
+                    "amount": integer(),
+                    "fee": integer(),
+                    "height": integer(),
+                    "timestamp": integer(),
+                    "confirmations": integer(),
+                    "address": string(),
+                    "subaddr_index": subaddress_index,
+                    "subaddr_indices": array(subaddress_index.clone()),
+                    "destinations": array(destination),
+                    "unlock_time": integer(),
+                    "note": string(),
+                    "double_spend_seen": boolean(),
+                }),
+                &[
+                    "txid",
+                    "type",
+                    "amount",
+                    "fee",
+                    "height",
+                    "timestamp",
+                    "confirmations",
+                    "address",
+                    "subaddr_index",
+                    "subaddr_indices",
+                    "destinations",
+                    "unlock_time",
+                    "note",
+                    "double_spend_seen",
+                ],
+            );
+            object(
+                json!({
+                    "txid": string(),
+                    "transfer": nullable(transfer),
+                    "note": nullable(string()),
+                    "visibility": nullable(json!({ "enum": ["in_pool", "mined", "not_visible"] })),
+                }),
+                &["txid", "transfer", "note", "visibility"],
+            )
+        }
+        "submit-tx" | "relay" => object(
+            json!({
+                "tx_hashes": array(string()),
+                "visibility": nullable(json!({ "enum": ["in_pool", "mined", "not_visible"] })),
+                "rejection": nullable(object(
+                    json!({
+                        "reason": reference("rejection_reason"),
+                        "detail": string(),
+                        "may_share_inputs": array(integer()),
+                    }),
+                    &["reason", "detail", "may_share_inputs"],
+                )),
+            }),
+            &["tx_hashes", "visibility", "rejection"],
+        ),
+        "pending" | "journal" => array(reference("journal_entry")),
+        "audit" => object(
+            json!({
+                "status": { "enum": ["intact", "broken"] },
+                "entries": integer(),
+                "line": integer(),
+                "reason": string(),
+            }),
+            &["status"],
+        ),
+        "inspect-info" => object(
+            json!({
+                "kind": { "enum": ["multisig_info", "key_exchange_info", "tx_set", "unknown"] },
+                "armored": boolean(),
+                "fingerprint": string(),
+                "digest": string(),
+                "length": integer(),
+                "created_at": nullable(timestamp()),
+                "export_height": nullable(integer()),
+                "from": nullable(string()),
+                "wallet_height": nullable(integer()),
+                "blocks_behind": nullable(integer()),
+                "stale": nullable(boolean()),
+                "status": nullable(json!({ "enum": ["new", "imported", "own_export"] })),
+                "imported_at": nullable(timestamp()),
+            }),
+            &[
+                "kind",
+                "armored",
+                "fingerprint",
+                "digest",
+                "length",
+                "created_at",
+                "export_height",
+                "from",
+                "wallet_height",
+                "blocks_behind",
+                "stale",
+                "status",
+                "imported_at",
+            ],
+        ),
+        "verify-tx-set" => object(
+            json!({
+                "fingerprint": string(),
+                "matches": boolean(),
+                "manifest": object(
+                    json!({
+                        "version": integer(),
+                        "created_at": timestamp(),
+                        "destinations": array(object(
+                            json!({ "address": string(), "amount": integer() }),
+                            &["address", "amount"],
+                        )),
+                        "change_address": string(),
+                        "change_amount": integer(),
+                        "fee": integer(),
+                        "note": string(),
+                        "hmac": string(),
+                    }),
+                    &[
+                        "version",
+                        "created_at",
+                        "destinations",
+                        "change_address",
+                        "change_amount",
+                        "fee",
+                        "hmac",
+                    ],
+                ),
+                "differences": array(object(
+                    json!({
+                        "kind": { "enum": ["missing", "unexpected", "change", "fee"] },
+                        "address": string(),
+                        "amount": integer(),
+                        "expected_address": string(),
+                        "expected_amount": integer(),
+                        "actual_address": string(),
+                        "actual_amount": integer(),
+                        "expected": integer(),
+                        "actual": integer(),
+                    }),
+                    &["kind"],
+                )),
+            }),
+            &["fingerprint", "matches", "manifest", "differences"],
+        ),
+        "prove-spend" => object(
+            json!({
+                "txid": string(),
+                "message": string(),
+                "signature": string(),
+                "created_at": timestamp(),
+            }),
+            &["txid", "message", "signature"],
+        ),
+        "check-spend-proof" => object(
+            json!({
+                "txid": string(),
+                "message": string(),
+                "good": boolean(),
+            }),
+            &["txid", "message", "good"],
+        ),
+        "report" => {
+            let wallet = object(
+                json!({
+                    "label": string(),
+                    "threshold": nullable(integer()),
+                    "total": nullable(integer()),
+                    "participants": array(string()),
+                    "address": nullable(string()),
+                    "address_verified": boolean(),
+                    "created_at": nullable(timestamp()),
+                    "stage": string(),
+                }),
+                &[
+                    "label",
+                    "threshold",
+                    "total",
+                    "participants",
+                    "address",
+                    "address_verified",
+                    "created_at",
+                    "stage",
+                ],
+            );
+            let reconciliation = object(
+                json!({
+                    "expected": nullable(integer()),
+                    "balance": integer(),
+                    "discrepancy": nullable(object(
+                        json!({
+                            "kind": { "enum": ["surplus", "shortfall"] },
+                            "amount": integer(),
+                        }),
+                        &["kind", "amount"],
+                    )),
+                }),
+                &["expected", "balance", "discrepancy"],
+            );
+            object(
+                json!({
+                    "generated_at": timestamp(),
+                    "from": nullable(date()),
+                    "to": nullable(date()),
+                    "wallet": wallet,
+                    "transactions": array(reference("report_transaction")),
+                    "totals": object(
+                        json!({
+                            "received": integer(),
+                            "sent": integer(),
+                            "fees": integer(),
+                            "transfers": integer(),
+                        }),
+                        &["received", "sent", "fees", "transfers"],
+                    ),
+                    "balance": object(
+                        json!({
+                            "balance": integer(),
+                            "unlocked_balance": integer(),
+                            "locked": integer(),
+                            "blocks_to_unlock": integer(),
+                            "time_to_unlock": integer(),
+                        }),
+                        &["balance", "unlocked_balance"],
+                    ),
+                    "reconciliation": reconciliation,
+                }),
+                &[
+                    "generated_at",
+                    "from",
+                    "to",
+                    "wallet",
+                    "transactions",
+                    "totals",
+                    "balance",
+                    "reconciliation",
+                ],
+            )
+        }
+        "participants" => array(object(
+            json!({
+                "name": string(),
+                "rounds": array(integer()),
+                "last_digest": nullable(string()),
+            }),
+            &["name", "rounds", "last_digest"],
+        )),
+        _ => return None,
+    };
+
+    Some(json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": format!("monero-multisig {command} output, schema {SCHEMA_VERSION}"),
+        "type": "object",
+        "properties": {
+            "schema": { "const": SCHEMA_VERSION },
+            "command": { "const": command },
+            "result": result,
+            "next_steps": array(json!({ "enum": Action::ALL })),
+        },
+        "required": ["schema", "command", "result"],
+        "$defs": definitions(),
+    }))
+}
+
+fn definitions() -> Value {
+    let subaddress_index = object(
+        json!({ "major": integer(), "minor": integer() }),
+        &["major", "minor"],
+    );
+    let params = object(
+        json!({
+            "threshold": integer(),
+            "total": integer(),
+            "label": string(),
+            "roster": array(string()),
+        }),
+        &["threshold", "total", "label"],
+    );
+    let stage = |name: &str, props: Value, required: &[&str]| {
+        object(json!({ name: object(props, required) }), &[name])
+    };
+
+    let journal_action = json!({
+        "type": "object",
+        "properties": {
+            "kind": {
+                "enum": [
+                    "built",
+                    "received",
+                    "signed",
+                    "signature_recorded",
+                    "held_for_relay",
+                    "submitted",
+                    "broadcast_checked",
+                    "rejected",
+                    "confirmed",
+                    "discarded",
+                    "superseded",
+                ]
+            },
+            "tx_hashes": array(string()),
+            "height": integer(),
+            "signer": string(),
+            "visibility": {
+                "enum": ["in_pool", "mined", "not_visible"]
+            },
+            "reason": reference("rejection_reason"),
+            "detail": string(),
+            "by": integer(),
+        },
+        "required": ["kind"],
+    });
+    let journal_event = object(
+        json!({
+            "at": timestamp(),
+            "action": journal_action,
+            "by": string(),
+        }),
+        &["at", "action", "by"],
+    );
+    let tx_status = json!({
+        "enum": [
+            "awaiting_signatures",
+            "ready_to_submit",
+            "awaiting_relay",
+            "submitted",
+            "discarded",
+        ]
+    });
+    let destination = object(
+        json!({ "address": string(), "amount": integer() }),
+        &["address", "amount"],
+    );
+    let escrow = object(
+        json!({
+            "role": { "enum": ["buyer", "seller"] },
+            "refund_address": string(),
+        }),
+        &["role", "refund_address"],
+    );
+
+    json!({
+        "state_file": object(
+            json!({
+                "version": integer(),
+                "wallet": {
+                    "oneOf": [
+                        stage(
+                            "Created",
+                            json!({
+                                "wallet_path": string(),
+                                "params": params,
+                                "restore_height": nullable(integer()),
+                            }),
+                            &["wallet_path", "params", "restore_height"],
+                        ),
+                        stage(
+                            "KeyExchangeInProgress",
+                            json!({
+                                "wallet_path": string(),
+                                "params": params,
+                                "rounds_completed": integer(),
+                                "rounds_required": integer(),
+                                "restore_height": nullable(integer()),
+                            }),
+                            &[
+                                "wallet_path",
+                                "params",
+                                "rounds_completed",
+                                "rounds_required",
+                                "restore_height",
+                            ],
+                        ),
+                        stage(
+                            "Ready",
+                            json!({
+                                "wallet_path": string(),
+                                "address": string(),
+                                "params": params,
+                                "restore_height": nullable(integer()),
+                            }),
+                            &["wallet_path", "address", "params", "restore_height"],
+                        ),
+                        stage(
+                            "WatchOnly",
+                            json!({
+                                "wallet_path": string(),
+                                "address": string(),
+                                "label": string(),
+                                "restore_height": nullable(integer()),
+                            }),
+                            &["wallet_path", "address", "label", "restore_height"],
+                        ),
+                    ]
+                },
+                "imports": array(object(
+                    json!({ "digest": string(), "imported_at": timestamp() }),
+                    &["digest", "imported_at"],
+                )),
+                "last_export": nullable(object(
+                    json!({
+                        "digest": string(),
+                        "exported_at": timestamp(),
+                        "height": integer(),
+                        "outputs": string(),
+                        "info": string(),
+                    }),
+                    &["digest", "exported_at"],
+                )),
+                "verified": boolean(),
+                "verified_at": nullable(timestamp()),
+                "unverified_warned": boolean(),
+                "last_sync": nullable(object(
+                    json!({
+                        "synced_at": timestamp(),
+                        "wallet_height": integer(),
+                        "balance": integer(),
+                    }),
+                    &["synced_at", "wallet_height", "balance"],
+                )),
+                "exchange_attempt": object(
+                    json!({
+                        "round": integer(),
+                        "inputs": array(string()),
+                        "started_at": timestamp(),
+                    }),
+                    &["round", "inputs", "started_at"],
+                ),
+                "created_at": timestamp(),
+                "contributions": array(object(
+                    json!({
+                        "name": string(),
+                        "round": integer(),
+                        "digest": string(),
+                        "received_at": timestamp(),
+                    }),
+                    &["name", "round", "digest", "received_at"],
+                )),
+                "multisig_api": { "enum": ["legacy", "current"] },
+                "escrow": escrow,
+                "prepared_info": string(),
+            }),
+            &[
+                "version",
+                "wallet",
+                "imports",
+                "last_export",
+                "verified",
+                "verified_at",
+                "last_sync",
+            ],
+        ),
+        "described_transfer": object(
+            json!({
+                "recipients": array(object(
+                    json!({ "address": string(), "amount": integer() }),
+                    &["address", "amount"],
+                )),
+                "amount_in": integer(),
+                "amount_out": integer(),
+                "change_amount": integer(),
+                "change_address": string(),
+                "fee": integer(),
+                "ring_size": integer(),
+                "unlock_time": integer(),
+                "outputs": array(object(
+                    json!({
+                        "address": string(),
+                        "amount": integer(),
+                        "kind": { "enum": ["payout", "change", "self_transfer"] },
+                    }),
+                    &["address", "amount", "kind"],
+                )),
+                "leaving_amount": integer(),
+            }),
+            &[
+                "recipients",
+                "amount_in",
+                "amount_out",
+                "change_amount",
+                "change_address",
+                "fee",
+                "ring_size",
+                "unlock_time",
+                "outputs",
+                "leaving_amount",
+            ],
+        ),
+        "transfer_entry": object(
+            json!({
+                "txid": string(),
+                "type": { "enum": ["in", "out", "pending", "failed", "pool"] },
+                "amount": integer(),
+                "fee": integer(),
+                "height": integer(),
+                "timestamp": integer(),
+                "confirmations": integer(),
+                "address": string(),
+                "subaddr_index": subaddress_index,
+            }),
+            &[
+                "txid",
+                "type",
+                "amount",
+                "fee",
+                "height",
+                "timestamp",
+                "confirmations",
+                "address",
+                "subaddr_index",
+            ],
+        ),
+        "incoming_transfer": object(
+            json!({
+                "txid": string(),
+                "amount": integer(),
+                "subaddr_index": subaddress_index,
+                "confirmations": integer(),
+                "in_pool": boolean(),
+            }),
+            &["txid", "amount", "subaddr_index", "confirmations", "in_pool"],
+        ),
+        "pool_payment": object(
+            json!({
+                "txid": string(),
+                "amount": integer(),
+                "address": string(),
+                "subaddr_index": subaddress_index,
+                "timestamp": integer(),
+                "double_spend_seen": boolean(),
+            }),
+            &[
+                "txid",
+                "amount",
+                "address",
+                "subaddr_index",
+                "timestamp",
+                "double_spend_seen",
+            ],
+        ),
+        "owned_output": object(
+            json!({
+                "amount": integer(),
+                "tx_hash": string(),
+                "spent": boolean(),
+                "unlocked": boolean(),
+                "frozen": boolean(),
+                "subaddr_index": subaddress_index,
+            }),
+            &[
+                "amount",
+                "tx_hash",
+                "spent",
+                "unlocked",
+                "frozen",
+                "subaddr_index",
+            ],
+        ),
+        "subaddress": object(
+            json!({
+                "address_index": integer(),
+                "address": string(),
+                "label": string(),
+                "used": boolean(),
+            }),
+            &["address_index", "address", "label", "used"],
+        ),
+        "rejection_reason": {
+            "enum": [
+                "double_spend",
+                "key_image_spent",
+                "fee_too_low",
+                "too_big",
+                "invalid_ring",
+                "policy",
+                "other",
+            ]
+        },
+        "journal_entry": object(
+            json!({
+                "id": integer(),
+                "created_at": timestamp(),
+                "status": tx_status,
+                "note": nullable(string()),
+                "destinations": array(destination.clone()),
+                "fee": integer(),
+                "weight": integer(),
+                "size_bytes": integer(),
+                "priority": nullable(json!({ "enum": ["default", "low", "medium", "high"] })),
+                "built_by": nullable(string()),
+                "tx_hashes": array(string()),
+                "tx_set_digests": array(string()),
+                "tx_set": nullable(string()),
+                "supersedes": nullable(integer()),
+                "superseded_by": nullable(integer()),
+                "signatures_collected": integer(),
+                "signatures_required": integer(),
+                "signers": array(object(
+                    json!({ "name": string(), "tx_set_digest": string(), "at": timestamp() }),
+                    &["name", "tx_set_digest", "at"],
+                )),
+                "events": array(journal_event.clone()),
+            }),
+            &[
+                "id",
+                "created_at",
+                "status",
+                "note",
+                "destinations",
+                "fee",
+                "weight",
+                "size_bytes",
+                "priority",
+                "built_by",
+                "tx_hashes",
+                "tx_set_digests",
+                "tx_set",
+                "supersedes",
+                "superseded_by",
+                "signatures_collected",
+                "signatures_required",
+                "signers",
+                "events",
+            ],
+        ),
+        "report_transaction": object(
+            json!({
+                "id": integer(),
+                "created_at": timestamp(),
+                "status": tx_status,
+                "note": nullable(string()),
+                "destinations": array(destination),
+                "amount": integer(),
+                "fee": integer(),
+                "tx_hash": nullable(string()),
+                "signatures_collected": integer(),
+                "signatures_required": integer(),
+                "events": array(journal_event),
+            }),
+            &[
+                "id",
+                "created_at",
+                "status",
+                "note",
+                "destinations",
+                "amount",
+                "fee",
+                "tx_hash",
+                "signatures_collected",
+                "signatures_required",
+                "events",
+            ],
+        ),
+    })
+}
+
+fn object(properties: Value, required: &[&str]) -> Value {
+    json!({ "type": "object", "properties": properties, "required": required })
+}
+
+fn array(items: Value) -> Value {
+    json!({ "type": "array", "items": items })
+}
+
+fn nullable(schema: Value) -> Value {
+    json!({ "oneOf": [{ "type": "null" }, schema] })
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{name}") })
+}
+
+fn string() -> Value {
+    json!({ "type": "string" })
+}
+
+/// Amounts are piconero and heights are block counts, so every integer in a
+/// result is non-negative.
+fn integer() -> Value {
+    json!({ "type": "integer", "minimum": 0 })
+}
+
+fn boolean() -> Value {
+    json!({ "type": "boolean" })
+}
+
+fn timestamp() -> Value {
+    json!({ "type": "string", "format": "date-time" })
+}
+
+fn date() -> Value {
+    json!({ "type": "string", "format": "date" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capabilities::{Capabilities, MultisigApi};
+    use crate::config::RpcVersion;
+    use crate::doctor::{Cause, LatencyReport, MethodLatency};
+    use crate::journal::{
+        Journal, JournalAction, JournalEntry, JournalEvent, SignerRecord, TxStatus,
+    };
+    use crate::monitor::IncomingTransfer;
+    use crate::report::Report;
+    use crate::transaction::{
+        Balance, Destination, OutputKind, OwnedOutput, Recipient, SubaddressIndex, TransferKind,
+    };
+    use crate::wallet::{Account, PeerFingerprint, SerializableParams, StateFile, WalletState};
+
+    fn at() -> DateTime<Utc> {
+        "2024-03-01T12:00:00Z".parse().unwrap()
+    }
+
+    fn description() -> TransferDescription {
+        TransferDescription {
+            recipients: vec![Recipient {
+                address: "4dest".into(),
+                amount: 5,
+            }],
+            amount_in: 20,
+            amount_out: 19,
+            change_amount: 14,
+            change_address: "4own".into(),
+            fee: 1,
+            ring_size: 16,
+            unlock_time: 0,
+        }
+    }
+
+    fn chain() -> ChainInfo {
+        ChainInfo {
+            daemon_height: 100,
+            wallet_height: 98,
+            target_height: 100,
+            difficulty: 7,
+            synchronized: true,
+            untrusted: false,
+        }
+    }
+
+    fn verification() -> AddressVerification {
+        AddressVerification {
+            address: "4addr".into(),
+            fingerprint: "banjo-nickel".into(),
+            threshold: 2,
+            total: 3,
+            peers: vec![PeerFingerprint {
+                provided: "banjo-nickel".into(),
+                matches: true,
+            }],
+        }
+    }
+
+    fn journal_entry() -> JournalEntry {
+        JournalEntry {
+            id: 3,
+            created_at: at(),
+            status: TxStatus::Submitted,
+            note: Some("rent".into()),
+            destinations: vec![Destination::new_unchecked("4dest", Amount(5))],
+            fee: 1,
+            weight: 1536,
+            size_bytes: 1536,
+            priority: Some(Priority::Medium),
+            built_by: None,
+            tx_hashes: vec!["ab".into()],
+            tx_set_digests: vec!["cd".into()],
+            tx_set: None,
+            supersedes: Some(2),
+            superseded_by: None,
+            signatures_collected: 2,
+            signatures_required: 2,
+            signers: vec![SignerRecord {
+                name: "alice".into(),
+                tx_set_digest: "cd".into(),
+                at: at(),
+            }],
+            events: vec![JournalEvent {
+                at: at(),
+                action: JournalAction::Submitted {
+                    tx_hashes: vec!["ab".into()],
+                },
+                by: "alice@host".into(),
+            }],
+        }
+    }
+
+    /// One sample result per command, serialized as the command would.
+    fn samples() -> Vec<(&'static str, Value)> {
+        let desc = description();
+        let mut state = StateFile::new(WalletState::Ready {
+            wallet_path: "w".into(),
+            address: "4addr".into(),
+            params: SerializableParams {
+                threshold: 2,
+                total: 3,
+                label: "ops".into(),
+                roster: vec!["alice".into(), "bob".into()],
+            },
+            restore_height: Some(10),
+        });
+        state.begin_exchange(1, &["ab".to_string()]);
+        state.record_contribution("alice", 1, "ab");
+        let value = |v: serde_json::Result<Value>| v.unwrap();
+        vec![
+            ("status", value(serde_json::to_value(&state))),
+            (
+                "describe",
+                value(serde_json::to_value([DescribedTransfer {
+                    description: &desc,
+                    outputs: vec![ReviewedOutput {
+                        address: "4dest".into(),
+                        amount: 5,
+                        kind: OutputKind::Payout,
+                    }],
+                    leaving_amount: Amount(5),
+                }])),
+            ),
+            (
+                "history",
+                value(serde_json::to_value([TransferEntry {
+                    txid: "ab".into(),
+                    kind: TransferKind::In,
+                    amount: 5,
+                    fee: 0,
+                    height: 90,
+                    timestamp: 1_700_000_000,
+                    confirmations: 10,
+                    address: "4addr".into(),
+                    subaddr_index: SubaddressIndex { major: 0, minor: 1 },
+                }])),
+            ),
+            (
+                "monitor",
+                value(serde_json::to_value(IncomingTransfer {
+                    txid: "ab".into(),
+                    amount: 5,
+                    subaddr_index: SubaddressIndex { major: 0, minor: 1 },
+                    confirmations: 0,
+                    in_pool: true,
+                })),
+            ),
+            (
+                "incoming",
+                value(serde_json::to_value(IncomingResult {
+                    pool: &[PoolPayment {
+                        txid: "cd".into(),
+                        amount: 7,
+                        address: "8sub".into(),
+                        subaddr_index: SubaddressIndex { major: 0, minor: 2 },
+                        timestamp: 1_700_000_100,
+                        double_spend_seen: true,
+                    }],
+                    confirmed: &[],
+                })),
+            ),
+            (
+                "list-outputs",
+                value(serde_json::to_value([OwnedOutput {
+                    amount: 5,
+                    tx_hash: "ab".into(),
+                    spent: false,
+                    unlocked: true,
+                    frozen: false,
+                    subaddr_index: SubaddressIndex { major: 1, minor: 0 },
+                }])),
+            ),
+            (
+                "address",
+                value(serde_json::to_value(AddressResult {
+                    account: 0,
+                    address: "4addr".into(),
+                    subaddresses: Some(vec![Subaddress {
+                        address_index: 0,
+                        address: "4addr".into(),
+                        label: "Primary".into(),
+                        used: true,
+                    }]),
+                })),
+            ),
+            (
+                "list-accounts",
+                value(serde_json::to_value([Account {
+                    account_index: 1,
+                    base_address: "8addr".into(),
+                    balance: Amount(5),
+                    unlocked_balance: Amount(0),
+                    label: "payroll".into(),
+                    tag: Some("ops".into()),
+                }])),
+            ),
+            (
+                "account",
+                value(serde_json::to_value([Account {
+                    account_index: 2,
+                    base_address: "8addr".into(),
+                    balance: Amount(0),
+                    unlocked_balance: Amount(0),
+                    label: String::new(),
+                    tag: None,
+                }])),
+            ),
+            (
+                "create-account",
+                value(serde_json::to_value(NewAccount {
+                    account_index: 1,
+                    address: "8addr",
+                    label: "payroll",
+                })),
+            ),
+            (
+                "verify-address",
+                value(serde_json::to_value(VerifyAddressResult {
+                    report: &verification(),
+                    verified: true,
+                    verified_at: Some(at()),
+                })),
+            ),
+            (
+                "list-wallet-files",
+                value(serde_json::to_value(WalletFilesResult {
+                    listed_by_rpc: true,
+                    files: &[WalletFile {
+                        filename: "stray".into(),
+                        label: None,
+                        retired: false,
+                        multisig: None,
+                        modified: Some(DateTime::from_timestamp(1_700_000_000, 0).unwrap()),
+                    }],
+                })),
+            ),
+            (
+                "request-payment",
+                value(serde_json::to_value(PaymentRequestResult {
+                    uri: "monero:8sub?tx_amount=1.5",
+                    address: "8sub",
+                    amount: Some(Amount(1_500_000_000_000)),
+                    subaddress_index: Some(3),
+                })),
+            ),
+            (
+                "get-version",
+                value(serde_json::to_value(Capabilities {
+                    version: RpcVersion {
+                        major: 1,
+                        minor: 20,
+                    },
+                    supports_transfer_split: true,
+                    supports_describe_transfer: false,
+                    multisig_api_generation: MultisigApi::Legacy,
+                })),
+            ),
+            (
+                "fees",
+                value(serde_json::to_value(FeesResult {
+                    base_fee: 20000,
+                    quantization_mask: 10000,
+                    algorithm: FeeAlgorithm::PerPriority,
+                    hard_fork: Some(16),
+                    typical_weight: 1550,
+                    levels: vec![PriorityFee {
+                        priority: Priority::Low,
+                        rate: 20000,
+                        typical_fee: Amount(31_000_000),
+                    }],
+                })),
+            ),
+            (
+                "chain-info",
+                value(serde_json::to_value(ChainInfoResult {
+                    chain: &chain(),
+                    wallet_behind: 2,
+                })),
+            ),
+            (
+                "show-tx",
+                value(serde_json::to_value(ShownTx {
+                    txid: "ab",
+                    transfer: Some(&TransferRecord {
+                        txid: "ab".into(),
+                        kind: TransferKind::Out,
+                        amount: 5,
+                        fee: 1,
+                        height: 90,
+                        timestamp: 1_700_000_000,
+                        confirmations: 10,
+                        address: "4addr".into(),
+                        subaddr_index: SubaddressIndex::default(),
+                        subaddr_indices: vec![SubaddressIndex::default()],
+                        destinations: vec![Recipient {
+                            address: "4dest".into(),
+                            amount: 5,
+                        }],
+                        unlock_time: 0,
+                        note: String::new(),
+                        double_spend_seen: false,
+                    }),
+                    note: Some("rent"),
+                    visibility: None,
+                })),
+            ),
+            (
+                "show-tx",
+                value(serde_json::to_value(ShownTx {
+                    txid: "cd",
+                    transfer: None,
+                    note: None,
+                    visibility: Some(Visibility::InPool),
+                })),
+            ),
+            (
+                "doctor",
+                value(serde_json::to_value(LatencyReport {
+                    iterations: 10,
+                    methods: vec![MethodLatency {
+                        method: "get_balance".into(),
+                        p50_ms: 1800,
+                        p95_ms: 4000,
+                        budget_ms: 2000,
+                        over_budget: true,
+                    }],
+                    daemon_synchronized: None,
+                    causes: vec![Cause::DiskBoundWallet],
+                })),
+            ),
+            (
+                "sign-batch",
+                value(serde_json::to_value(SignBatchResult {
+                    signed: vec!["a.txset".into()],
+                    failed: vec![BatchFailure {
+                        file: "b.txset".into(),
+                        error: "over the limit".into(),
+                    }],
+                })),
+            ),
+            (
+                "submit-tx",
+                value(serde_json::to_value(SubmitResult {
+                    tx_hashes: vec!["ab".into()],
+                    visibility: Some(Visibility::InPool),
+                    rejection: None,
+                })),
+            ),
+            (
+                "relay",
+                value(serde_json::to_value(SubmitResult {
+                    tx_hashes: vec![],
+                    visibility: None,
+                    rejection: Some(Rejection {
+                        reason: RejectionReason::DoubleSpend,
+                        detail: "double spend".into(),
+                        may_share_inputs: vec![2],
+                    }),
+                })),
+            ),
+            ("journal", value(serde_json::to_value([journal_entry()]))),
+            ("pending", value(serde_json::to_value([journal_entry()]))),
+            (
+                "participants",
+                value(serde_json::to_value(state.participants())),
+            ),
+            (
+                "report",
+                value(serde_json::to_value(
+                    Report::new(
+                        &state,
+                        &Journal {
+                            entries: vec![journal_entry()],
+                        },
+                        &[],
+                        Balance::unlocked(Amount(5)),
+                        chrono::NaiveDate::from_ymd_opt(2024, 1, 1),
+                        None,
+                    )
+                    .unwrap(),
+                )),
+            ),
+            (
+                "prove-spend",
+                value(serde_json::to_value(crate::proof::SpendProof {
+                    txid: "ab".repeat(32),
+                    message: "invoice 42".into(),
+                    signature: "SpendProofV1abc".into(),
+                    created_at: Some(at()),
+                })),
+            ),
+            (
+                "check-spend-proof",
+                value(serde_json::to_value(SpendProofCheck {
+                    txid: "ab",
+                    message: "invoice 42",
+                    good: true,
+                })),
+            ),
+            (
+                "inspect-info",
+                value(serde_json::to_value(InspectedInfo {
+                    kind: InfoKind::MultisigInfo,
+                    armored: true,
+                    fingerprint: "baker-zenith".into(),
+                    digest: "ab".into(),
+                    length: 2_000,
+                    created_at: Some(at()),
+                    export_height: Some(90),
+                    from: Some("alice".into()),
+                    wallet_height: Some(1_000),
+                    blocks_behind: Some(910),
+                    stale: Some(true),
+                    status: Some(InfoStatus::New),
+                    imported_at: None,
+                })),
+            ),
+            (
+                "verify-tx-set",
+                value(serde_json::to_value(TxSetVerification {
+                    fingerprint: "baker-zenith".into(),
+                    matches: false,
+                    manifest: &Manifest {
+                        version: 1,
+                        created_at: at(),
+                        destinations: vec![crate::transaction::Recipient {
+                            address: "4addr".into(),
+                            amount: 5,
+                        }],
+                        change_address: "4self".into(),
+                        change_amount: 3,
+                        fee: 1,
+                        note: Some("rent".into()),
+                        hmac: "ab".into(),
+                    },
+                    differences: &[
+                        Difference::Missing {
+                            address: "4addr".into(),
+                            amount: 5,
+                        },
+                        Difference::Change {
+                            expected_address: "4self".into(),
+                            expected_amount: 3,
+                            actual_address: "4other".into(),
+                            actual_amount: 3,
+                        },
+                        Difference::Fee {
+                            expected: 1,
+                            actual: 2,
+                        },
+                    ],
+                })),
+            ),
+            (
+                "audit",
+                value(serde_json::to_value(crate::audit::Verification::Broken {
+                    line: 2,
+                    reason: "no hash".into(),
+                })),
+            ),
+        ]
+    }
+
+    #[test]
+    fn envelope_is_stable() {
+        assert_eq!(
+            to_json_line("balance", &json!({ "x": 1 })).unwrap(),
+            r#"{"schema":1,"command":"balance","result":{"x":1}}"#
+        );
+        assert!(to_json("balance", &1)
+            .unwrap()
+            .starts_with("{\n  \"schema\": 1,\n  \"command\": \"balance\""));
+        let guided = to_json_with_steps("balance", &1, &[Action::ExportInfo]).unwrap();
+        assert!(guided.ends_with("\"next_steps\": [\n    \"export_info\"\n  ]\n}"));
+        let schema = schema("status").unwrap();
+        let steps = &schema["properties"]["next_steps"];
+        assert!(conforms(&json!(["export_info"]), steps, &schema["$defs"]));
+        assert!(!conforms(&json!(["dance"]), steps, &schema["$defs"]));
+    }
+
+    fn line<T: Serialize>(command: &str, result: &T) -> String {
+        to_json_line(command, result).unwrap()
+    }
+
+    #[test]
+    fn result_serialization_is_stable() {
+        assert_eq!(
+            line(
+                "status",
+                &StateFile::new(WalletState::WatchOnly {
+                    wallet_path: "w".into(),
+                    address: "4addr".into(),
+                    label: "ops".into(),
+                    restore_height: None,
+                })
+            ),
+            r#"{"schema":1,"command":"status","result":{"version":2,"wallet":{"WatchOnly":{"wallet_path":"w","address":"4addr","label":"ops","restore_height":null}},"imports":[],"last_export":null,"verified":false,"verified_at":null,"unverified_warned":false,"last_sync":null}}"#
+        );
+        assert_eq!(
+            line(
+                "describe",
+                &[DescribedTransfer {
+                    description: &description(),
+                    outputs: vec![ReviewedOutput {
+                        address: "4own".into(),
+                        amount: 14,
+                        kind: OutputKind::Change,
+                    }],
+                    leaving_amount: Amount(0),
+                }]
+            ),
+            r#"{"schema":1,"command":"describe","result":[{"recipients":[{"address":"4dest","amount":5}],"amount_in":20,"amount_out":19,"change_amount":14,"change_address":"4own","fee":1,"ring_size":16,"unlock_time":0,"outputs":[{"address":"4own","amount":14,"kind":"change"}],"leaving_amount":0}]}"#
+        );
+        assert_eq!(
+            line(
+                "history",
+                &[TransferEntry {
+                    txid: "ab".into(),
+                    kind: TransferKind::Out,
+                    amount: 5,
+                    fee: 1,
+                    height: 90,
+                    timestamp: 1_700_000_000,
+                    confirmations: 10,
+                    address: "4addr".into(),
+                    subaddr_index: SubaddressIndex::default(),
+                }]
+            ),
+            r#"{"schema":1,"command":"history","result":[{"txid":"ab","type":"out","amount":5,"fee":1,"height":90,"timestamp":1700000000,"confirmations":10,"address":"4addr","subaddr_index":{"major":0,"minor":0}}]}"#
+        );
+        assert_eq!(
+            line(
+                "monitor",
+                &IncomingTransfer {
+                    txid: "ab".into(),
+                    amount: 5,
+                    subaddr_index: SubaddressIndex { major: 0, minor: 2 },
+                    confirmations: 3,
+                    in_pool: false,
+                }
+            ),
+            r#"{"schema":1,"command":"monitor","result":{"txid":"ab","amount":5,"subaddr_index":{"major":0,"minor":2},"confirmations":3,"in_pool":false}}"#
+        );
+        assert_eq!(
+            line(
+                "list-outputs",
+                &[OwnedOutput {
+                    amount: 5,
+                    tx_hash: "ab".into(),
+                    spent: true,
+                    unlocked: true,
+                    frozen: false,
+                    subaddr_index: SubaddressIndex { major: 0, minor: 3 },
+                }]
+            ),
+            r#"{"schema":1,"command":"list-outputs","result":[{"amount":5,"tx_hash":"ab","spent":true,"unlocked":true,"frozen":false,"subaddr_index":{"major":0,"minor":3}}]}"#
+        );
+        assert_eq!(
+            line(
+                "address",
+                &AddressResult {
+                    account: 2,
+                    address: "8addr".into(),
+                    subaddresses: None,
+                }
+            ),
+            r#"{"schema":1,"command":"address","result":{"account":2,"address":"8addr"}}"#
+        );
+        assert_eq!(
+            line(
+                "verify-address",
+                &VerifyAddressResult {
+                    report: &verification(),
+                    verified: false,
+                    verified_at: None,
+                }
+            ),
+            r#"{"schema":1,"command":"verify-address","result":{"address":"4addr","fingerprint":"banjo-nickel","threshold":2,"total":3,"peers":[{"provided":"banjo-nickel","matches":true}],"verified":false,"verified_at":null}}"#
+        );
+        assert_eq!(
+            line(
+                "chain-info",
+                &ChainInfoResult {
+                    chain: &chain(),
+                    wallet_behind: 2,
+                }
+            ),
+            r#"{"schema":1,"command":"chain-info","result":{"daemon_height":100,"wallet_height":98,"target_height":100,"difficulty":7,"synchronized":true,"untrusted":false,"wallet_behind":2}}"#
+        );
+        assert_eq!(
+            line(
+                "sign-batch",
+                &SignBatchResult {
+                    signed: vec!["a.txset".into()],
+                    failed: vec![BatchFailure {
+                        file: "b.txset".into(),
+                        error: "over the limit".into(),
+                    }],
+                }
+            ),
+            r#"{"schema":1,"command":"sign-batch","result":{"signed":["a.txset"],"failed":[{"file":"b.txset","error":"over the limit"}]}}"#
+        );
+        assert_eq!(
+            line(
+                "submit-tx",
+                &SubmitResult {
+                    tx_hashes: vec![],
+                    visibility: None,
+                    rejection: Some(Rejection {
+                        reason: RejectionReason::KeyImageSpent,
+                        detail: "already spent".into(),
+                        may_share_inputs: vec![2],
+                    }),
+                }
+            ),
+            r#"{"schema":1,"command":"submit-tx","result":{"tx_hashes":[],"visibility":null,"rejection":{"reason":"key_image_spent","detail":"already spent","may_share_inputs":[2]}}}"#
+        );
+        assert_eq!(
+            line("journal", &[journal_entry()]),
+            r#"{"schema":1,"command":"journal","result":[{"id":3,"created_at":"2024-03-01T12:00:00Z","status":"submitted","note":"rent","destinations":[{"address":"4dest","amount":5}],"fee":1,"weight":1536,"size_bytes":1536,"priority":"medium","built_by":null,"tx_hashes":["ab"],"tx_set_digests":["cd"],"tx_set":null,"supersedes":2,"superseded_by":null,"signatures_collected":2,"signatures_required":2,"signers":[{"name":"alice","tx_set_digest":"cd","at":"2024-03-01T12:00:00Z"}],"events":[{"at":"2024-03-01T12:00:00Z","action":{"kind":"submitted","tx_hashes":["ab"]},"by":"alice@host"}]}]}"#
+        );
+    }
+
+    /// Just enough of JSON Schema to check the hand-written schemas against
+    /// real output: `$ref`, `type`, `properties`, `required`, `items`,
+    /// `oneOf`, `enum`, and `const`.
+    fn conforms(value: &Value, schema: &Value, defs: &Value) -> bool {
+        if let Some(name) = schema["$ref"].as_str() {
+            let name = name.trim_start_matches("#/$defs/");
+            return conforms(value, &defs[name], defs);
+        }
+        if let Some(options) = schema["oneOf"].as_array() {
+            return options.iter().filter(|s| conforms(value, s, defs)).count() == 1;
+        }
+        if let Some(allowed) = schema["enum"].as_array() {
+            return allowed.contains(value);
+        }
+        if !schema["const"].is_null() {
+            return &schema["const"] == value;
+        }
+        match schema["type"].as_str() {
+            Some("object") => {
+                let Some(map) = value.as_object() else {
+                    return false;
+                };
+                let props = &schema["properties"];
+                let required = schema["required"].as_array().cloned().unwrap_or_default();
+                required
+                    .iter()
+                    .all(|r| map.contains_key(r.as_str().unwrap()))
+                    && map
+                        .iter()
+                        .all(|(k, v)| props.get(k).is_some_and(|s| conforms(v, s, defs)))
+            }
+            Some("array") => value
+                .as_array()
+                .is_some_and(|items| items.iter().all(|v| conforms(v, &schema["items"], defs))),
+            Some("string") => value.is_string(),
+            Some("integer") => value.is_u64(),
+            Some("boolean") => value.is_boolean(),
+            Some("null") => value.is_null(),
+            _ => true,
+        }
+    }
+
+    #[test]
+    fn samples_match_their_schemas() {
+        for (command, result) in samples() {
+            let schema = schema(command).unwrap_or_else(|| panic!("no schema for {command}"));
+            let envelope = serde_json::from_str(&to_json(command, &result).unwrap()).unwrap();
+            assert!(
+                conforms(&envelope, &schema, &schema["$defs"]),
+                "{command} output does not match its schema: {envelope}"
+            );
+        }
+    }
+
+    #[test]
+    fn checker_catches_undocumented_fields() {
+        let schema = schema("chain-info").unwrap();
+        let mut result = serde_json::to_value(ChainInfoResult {
+            chain: &chain(),
+            wallet_behind: 2,
+        })
+        .unwrap();
+        result["extra"] = json!(1);
+        let envelope = json!({ "schema": 1, "command": "chain-info", "result": result });
+        assert!(!conforms(&envelope, &schema, &schema["$defs"]));
+
+        let envelope = json!({ "schema": 1, "command": "status", "result": {} });
+        assert!(!conforms(&envelope, &schema, &schema["$defs"]));
+        assert!(super::schema("build-tx").is_none());
+    }
+}
@@ -0,0 +1,339 @@
+//! Payout batch files for `build-batch`.
+//!
+//! A batch lists one payment per row: an address, an amount in XMR, and an
+//! optional note. As CSV, with an optional `address,amount,note` header line:
+//!
+//! ```text
+//! address,amount,note
+//! 4AdUndXHHZ…,1.25,"March salary, Alice"
+//! ```
+//!
+//! As JSON, an array of objects with the same keys; the amount may be a
+//! string or a number. Rows are numbered as the user sees them: by line for
+//! CSV, from 1 for JSON. Every row is checked before anything is built, and
+//! every problem is reported at once.
+
+use std::path::Path;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::config::Network;
+use crate::policy::Policy;
+use crate::transaction::{Amount, Destination};
+
+#[derive(Error, Debug)]
+pub enum PayoutError {
+    #[error("{} invalid row(s) in the batch file:\n{}", .0.len(), list(.0))]
+    InvalidRows(Vec<RowError>),
+
+    #[error("the batch file is not valid JSON: {0}")]
+    InvalidJson(String),
+
+    #[error("the batch file has no payouts")]
+    Empty,
+}
+
+fn list(errors: &[RowError]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("  {e}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A problem with one row of a batch file.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("row {row}: {message}")]
+pub struct RowError {
+    pub row: usize,
+    pub message: String,
+}
+
+/// One validated payment from a batch file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payout {
+    /// Row number in the file, for reports.
+    pub row: usize,
+    pub destination: Destination,
+    pub note: Option<String>,
+}
+
+/// Layout of a batch file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    /// JSON for a `.json` file, CSV for anything else.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("json") => Format::Json,
+            _ => Format::Csv,
+        }
+    }
+}
+
+/// A row as written, before validation.
+#[derive(Debug, Deserialize)]
+struct RawRow {
+    address: String,
+    amount: serde_json::Value,
+    #[serde(default)]
+    note: Option<String>,
+}
+
+/// Parse and validate a batch file. Addresses must be valid for `network`
+/// and, when `policy` has an allowlist, on it; amounts must be positive.
+pub fn parse(
+    text: &str,
+    format: Format,
+    network: Network,
+    policy: &Policy,
+) -> Result<Vec<Payout>, PayoutError> {
+    let rows = match format {
+        Format::Csv => csv_rows(text),
+        Format::Json => json_rows(text)?,
+    };
+    let mut payouts = Vec::new();
+    let mut errors = Vec::new();
+    for (row, fields) in rows {
+        match fields.and_then(|raw| validate(raw, network, policy)) {
+            Ok((destination, note)) => payouts.push(Payout {
+                row,
+                destination,
+                note,
+            }),
+            Err(message) => errors.push(RowError { row, message }),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(PayoutError::InvalidRows(errors));
+    }
+    if payouts.is_empty() {
+        return Err(PayoutError::Empty);
+    }
+    Ok(payouts)
+}
+
+type Row = (usize, Result<RawRow, String>);
+
+fn validate(
+    raw: RawRow,
+    network: Network,
+    policy: &Policy,
+) -> Result<(Destination, Option<String>), String> {
+    let address = raw.address.trim().to_string();
+    if address.is_empty() {
+        return Err("no address".into());
+    }
+    let text = match &raw.amount {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Number(n) => n.to_string(),
+        other => return Err(format!("amount {other} is not a number")),
+    };
+    let amount: Amount = text.parse().map_err(|e| format!("amount: {e}"))?;
+    if amount.0 == 0 {
+        return Err("amount is zero".into());
+    }
+    let destination = Destination::new(&address, amount, network).map_err(|e| e.to_string())?;
+    if !policy
+        .disallowed_destinations([address.as_str()], &[])
+        .is_empty()
+    {
+        return Err(format!("{address} is not in allowed_destinations"));
+    }
+    let note = raw
+        .note
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+    Ok((destination, note))
+}
+
+fn json_rows(text: &str) -> Result<Vec<Row>, PayoutError> {
+    let values: Vec<serde_json::Value> =
+        serde_json::from_str(text).map_err(|e| PayoutError::InvalidJson(e.to_string()))?;
+    Ok(values
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let raw = serde_json::from_value(value).map_err(|e| e.to_string());
+            (i + 1, raw)
+        })
+        .collect())
+}
+
+fn csv_rows(text: &str) -> Vec<Row> {
+    let mut rows = Vec::new();
+    let mut header_checked = false;
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = split_csv(line);
+        if !header_checked {
+            header_checked = true;
+            if fields[0].trim().eq_ignore_ascii_case("address") {
+                continue;
+            }
+        }
+        let raw = match fields.as_slice() {
+            [address, amount] | [address, amount, _] => Ok(RawRow {
+                address: address.clone(),
+                amount: serde_json::Value::String(amount.clone()),
+                note: fields.get(2).cloned(),
+            }),
+            _ => Err(format!(
+                "expected address,amount[,note] but found {} fields",
+                fields.len()
+            )),
+        };
+        rows.push((i + 1, raw));
+    }
+    rows
+}
+
+/// Split one CSV line. Fields may be quoted with `"`, which allows commas
+/// inside; `""` in a quoted field is a literal quote.
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Group payouts into transactions of at most `max_per_tx` destinations,
+/// keeping file order and using as few groups as possible. A transaction
+/// can't pay one address twice, so a repeated address goes to a later group.
+pub fn group(payouts: &[Payout], max_per_tx: usize) -> Vec<Vec<Payout>> {
+    let max_per_tx = max_per_tx.max(1);
+    let mut groups: Vec<Vec<Payout>> = Vec::new();
+    for payout in payouts {
+        let fits = groups.iter_mut().find(|g| {
+            g.len() < max_per_tx
+                && g.iter()
+                    .all(|p| p.destination.address() != payout.destination.address())
+        });
+        match fits {
+            Some(group) => group.push(payout.clone()),
+            None => groups.push(vec![payout.clone()]),
+        }
+    }
+    groups
+}
+
+/// The notes of `payouts` for one journal entry, each with its row number.
+pub fn journal_note(payouts: &[Payout]) -> Option<String> {
+    let notes: Vec<String> = payouts
+        .iter()
+        .filter_map(|p| Some(format!("row {}: {}", p.row, p.note.as_ref()?)))
+        .collect();
+    (!notes.is_empty()).then(|| notes.join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(c: char) -> String {
+        match c {
+            'a' => "47Y1NtfpLfSSnZrZRcy6BF18VrY5JHibsSkEsbgoTW5z1FU1cKooBSgY92YDdgy8kWDv8bv1YaD8eiC4sysM12M13sAZKHr",
+            'b' => "43n9wFhfKGqHdJ9mFkn6PBKd4j72dXSAqf2hTv3siSqjUBePrHrHB2aKFf5jCEYkxGBcvvGq7GHXJVeBgvFGPLFXHepgKya",
+            'c' => "43JXGKh7kapC5jnxNiGDh2KweAVSEPUQwb7mEDcVZBuMjp6PweXzXHeGreb3rfxLgDfDksTx3zsrAbbGnwKNgEqNGsg2pia",
+            _ => "862AUGVxwqN2ZkH7SusmNJ5ECsR8kNM6MHHgChUv1eChPir3CgAcnbW3cJf8LBnoTw6MR5ucqcfDMX1jCiwA1xJELYL243Q",
+        }
+        .to_string()
+    }
+
+    #[test]
+    fn test_parse_csv_reports_every_bad_row() {
+        let text = format!(
+            "address,amount,note\n{a},1.5,\"March, Alice\"\n\n{b},0\nnot-an-address,1\n{a},abc,x\n{b},2,\"say \"\"hi\"\"\"\n",
+            a = addr('a'),
+            b = addr('b'),
+        );
+        let Err(PayoutError::InvalidRows(errors)) =
+            parse(&text, Format::Csv, Network::Mainnet, &Policy::default())
+        else {
+            panic!("expected row errors");
+        };
+        let rows: Vec<usize> = errors.iter().map(|e| e.row).collect();
+        assert_eq!(rows, [4, 5, 6]);
+        assert_eq!(errors[0].message, "amount is zero");
+
+        let good = format!(
+            "{a},1.5,\"March, Alice\"\n{b},2,\"say \"\"hi\"\"\"\n",
+            a = addr('a'),
+            b = addr('b')
+        );
+        let payouts = parse(&good, Format::Csv, Network::Mainnet, &Policy::default()).unwrap();
+        assert_eq!(payouts[0].row, 1);
+        assert_eq!(payouts[0].destination.amount(), Amount(1_500_000_000_000));
+        assert_eq!(payouts[0].note.as_deref(), Some("March, Alice"));
+        assert_eq!(payouts[1].note.as_deref(), Some("say \"hi\""));
+    }
+
+    #[test]
+    fn test_parse_json_rows() {
+        let text = format!(
+            r#"[{{"address":"{a}","amount":"0.25","note":"rent"}},{{"address":"{b}","amount":2}},{{"address":"{b}"}}]"#,
+            a = addr('a'),
+            b = addr('b'),
+        );
+        let Err(PayoutError::InvalidRows(errors)) =
+            parse(&text, Format::Json, Network::Mainnet, &Policy::default())
+        else {
+            panic!("expected row errors");
+        };
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].row, 3);
+
+        let text = text.rsplit_once(",{").unwrap().0.to_string() + "]";
+        let payouts = parse(&text, Format::Json, Network::Mainnet, &Policy::default()).unwrap();
+        assert_eq!(payouts[1].destination.amount(), Amount(2_000_000_000_000));
+        assert!(matches!(
+            parse("[]", Format::Json, Network::Mainnet, &Policy::default()),
+            Err(PayoutError::Empty)
+        ));
+    }
+
+    #[test]
+    fn test_group_fills_and_separates_repeats() {
+        let payout = |row, c| Payout {
+            row,
+            destination: Destination::new_unchecked(addr(c), Amount(1)),
+            note: (row % 2 == 0).then(|| format!("n{row}")),
+        };
+        let payouts = [
+            payout(1, 'a'),
+            payout(2, 'b'),
+            payout(3, 'a'),
+            payout(4, 'c'),
+            payout(5, 'd'),
+        ];
+        let groups = group(&payouts, 2);
+        let rows: Vec<Vec<usize>> = groups
+            .iter()
+            .map(|g| g.iter().map(|p| p.row).collect())
+            .collect();
+        assert_eq!(rows, [vec![1, 2], vec![3, 4], vec![5]]);
+        assert_eq!(group(&payouts, 15).len(), 2);
+        assert_eq!(journal_note(&groups[1]).as_deref(), Some("row 4: n4"));
+        assert_eq!(journal_note(&groups[2]), None);
+    }
+}
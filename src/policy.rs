@@ -0,0 +1,259 @@
+//! Local spending policy checked before a transaction is built or signed.
+//!
+//! Limits are configured in the `policy` block of the config file. Every
+//! limit is optional; an empty policy allows everything.
+//!
+//! The destination allowlist is checked separately from the numeric limits
+//! because overriding it needs a stronger confirmation.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::transaction::Amount;
+
+/// Per-transaction limits from the config file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Policy {
+    /// Largest fee a single transaction may pay.
+    #[serde(default)]
+    pub max_fee_piconero: Option<Amount>,
+    /// Largest total amount a single transaction may send, excluding change.
+    #[serde(default)]
+    pub max_amount_piconero: Option<Amount>,
+    /// Largest allowed fee divided by amount, e.g. `0.01` for 1%.
+    #[serde(default)]
+    pub max_fee_ratio: Option<f64>,
+    /// The only addresses this wallet may pay. Change back to the wallet's
+    /// own addresses is always allowed.
+    #[serde(default)]
+    pub allowed_destinations: Option<Vec<String>>,
+    /// Rolling cap on what this machine signs, see [`crate::ledger`].
+    #[serde(default)]
+    pub spend_limit: Option<crate::ledger::SpendLimit>,
+}
+
+/// A limit a transaction exceeded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolicyViolation {
+    Fee {
+        fee: Amount,
+        max: Amount,
+    },
+    Amount {
+        amount: Amount,
+        max: Amount,
+    },
+    FeeRatio {
+        fee: Amount,
+        amount: Amount,
+        max: f64,
+    },
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyViolation::Fee { fee, max } => {
+                write!(f, "fee {fee} exceeds max_fee_piconero {} ({max})", max.0)
+            }
+            PolicyViolation::Amount { amount, max } => write!(
+                f,
+                "amount {amount} exceeds max_amount_piconero {} ({max})",
+                max.0
+            ),
+            PolicyViolation::FeeRatio { fee, amount, max } => {
+                let ratio = fee_ratio(*fee, *amount);
+                write!(
+                    f,
+                    "fee {fee} is {} of amount {amount}, above max_fee_ratio {max}",
+                    if ratio.is_finite() {
+                        format!("{ratio:.6}")
+                    } else {
+                        "all".to_string()
+                    }
+                )
+            }
+        }
+    }
+}
+
+/// Fee divided by amount. A fee on a zero amount is an infinite ratio; no fee
+/// at all is a ratio of zero.
+pub fn fee_ratio(fee: Amount, amount: Amount) -> f64 {
+    match (fee.0, amount.0) {
+        (0, _) => 0.0,
+        (_, 0) => f64::INFINITY,
+        (fee, amount) => fee as f64 / amount as f64,
+    }
+}
+
+/// Whether spending `total` (amount plus fee) uses more than `percent` of
+/// `balance`. Anything spent from an empty balance counts.
+pub fn is_sweep(total: Amount, balance: Amount, percent: u8) -> bool {
+    total.0 as u128 * 100 > balance.0 as u128 * percent as u128
+}
+
+impl Policy {
+    /// Check a transaction sending `amount` (excluding change) for `fee`.
+    /// Limits are inclusive: a value equal to the limit is allowed.
+    pub fn check(&self, amount: Amount, fee: Amount) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+        if let Some(max) = self.max_fee_piconero {
+            if fee > max {
+                violations.push(PolicyViolation::Fee { fee, max });
+            }
+        }
+        if let Some(max) = self.max_amount_piconero {
+            if amount > max {
+                violations.push(PolicyViolation::Amount { amount, max });
+            }
+        }
+        if let Some(max) = self.max_fee_ratio {
+            if fee_ratio(fee, amount) > max {
+                violations.push(PolicyViolation::FeeRatio { fee, amount, max });
+            }
+        }
+        violations
+    }
+
+    /// Destinations outside the allowlist, in order and without duplicates.
+    /// Addresses in `own` are treated as change and always allowed. Returns
+    /// nothing when no allowlist is configured.
+    pub fn disallowed_destinations<'a>(
+        &self,
+        destinations: impl IntoIterator<Item = &'a str>,
+        own: &[String],
+    ) -> Vec<String> {
+        let Some(allowed) = &self.allowed_destinations else {
+            return Vec::new();
+        };
+        let mut rejected: Vec<String> = Vec::new();
+        for address in destinations {
+            let permitted =
+                allowed.iter().any(|a| a == address) || own.iter().any(|a| a == address);
+            if !permitted && !rejected.iter().any(|r| r == address) {
+                rejected.push(address.to_string());
+            }
+        }
+        rejected
+    }
+
+    /// Whether any limit is configured.
+    pub fn is_empty(&self) -> bool {
+        self.max_fee_piconero.is_none()
+            && self.max_amount_piconero.is_none()
+            && self.max_fee_ratio.is_none()
+            && self.allowed_destinations.is_none()
+            && self.spend_limit.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> Policy {
+        Policy {
+            max_fee_piconero: Some(Amount(100)),
+            max_amount_piconero: Some(Amount(10_000)),
+            max_fee_ratio: Some(0.01),
+            allowed_destinations: None,
+            spend_limit: None,
+        }
+    }
+
+    #[test]
+    fn test_limits_are_inclusive() {
+        assert!(policy().check(Amount(10_000), Amount(100)).is_empty());
+        assert_eq!(
+            policy().check(Amount(10_001), Amount(100)),
+            vec![PolicyViolation::Amount {
+                amount: Amount(10_001),
+                max: Amount(10_000)
+            }]
+        );
+        assert_eq!(
+            policy().check(Amount(10_000), Amount(101)),
+            vec![
+                PolicyViolation::Fee {
+                    fee: Amount(101),
+                    max: Amount(100)
+                },
+                PolicyViolation::FeeRatio {
+                    fee: Amount(101),
+                    amount: Amount(10_000),
+                    max: 0.01
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_sweep() {
+        assert!(!is_sweep(Amount(90), Amount(100), 90));
+        assert!(is_sweep(Amount(91), Amount(100), 90));
+        assert!(is_sweep(Amount(1), Amount(0), 90));
+        assert!(!is_sweep(Amount(0), Amount(0), 90));
+        assert!(!is_sweep(Amount(u64::MAX), Amount(u64::MAX), 100));
+    }
+
+    #[test]
+    fn test_fee_ratio() {
+        assert_eq!(fee_ratio(Amount(1), Amount(4)), 0.25);
+        assert_eq!(fee_ratio(Amount(0), Amount(0)), 0.0);
+        assert_eq!(fee_ratio(Amount(0), Amount(5)), 0.0);
+        assert!(fee_ratio(Amount(1), Amount(0)).is_infinite());
+    }
+
+    #[test]
+    fn test_zero_amount_with_fee_violates_ratio() {
+        let only_ratio = Policy {
+            max_fee_ratio: Some(0.5),
+            ..Default::default()
+        };
+        assert!(only_ratio.check(Amount(0), Amount(0)).is_empty());
+        let violations = only_ratio.check(Amount(0), Amount(1));
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].to_string().contains("is all of amount"));
+    }
+
+    #[test]
+    fn test_allowlist_permits_change() {
+        let policy = Policy {
+            allowed_destinations: Some(vec!["4cold".into()]),
+            ..Default::default()
+        };
+        let own = vec!["4ours".to_string(), "8sub".to_string()];
+        assert!(policy
+            .disallowed_destinations(["4cold", "8sub"], &own)
+            .is_empty());
+        assert_eq!(
+            policy.disallowed_destinations(["4cold", "4evil", "4evil"], &own),
+            vec!["4evil".to_string()]
+        );
+        assert!(Policy::default()
+            .disallowed_destinations(["4evil"], &[])
+            .is_empty());
+    }
+
+    #[test]
+    fn test_empty_policy_allows_everything() {
+        let policy = Policy::default();
+        assert!(policy.is_empty());
+        assert!(policy.check(Amount(u64::MAX), Amount(u64::MAX)).is_empty());
+    }
+
+    #[test]
+    fn test_limits_are_plain_piconero_in_config() {
+        let policy: Policy = serde_json::from_str(r#"{"max_fee_piconero": 100}"#).unwrap();
+        assert_eq!(policy.max_fee_piconero, Some(Amount(100)));
+        assert_eq!(
+            PolicyViolation::Fee {
+                fee: Amount(101),
+                max: Amount(100)
+            }
+            .to_string(),
+            "fee 0.000000000101 XMR exceeds max_fee_piconero 100 (0.000000000100 XMR)"
+        );
+    }
+}
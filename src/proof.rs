@@ -0,0 +1,204 @@
+//! Spend proofs: showing that this wallet sent a given transaction.
+//!
+//! A tx proof (`get_tx_proof`) needs the recipient's address and their
+//! cooperation to check; a spend proof signs with the key images the
+//! transaction spent, so anyone whose daemon knows the transaction can check
+//! it. The wallet RPC returns only the signature, and checking needs the
+//! message it was made with, so proofs travel as a small JSON file holding
+//! the txid, the message and the signature:
+//!
+//! ```json
+//! { "txid": "…", "message": "invoice 42", "signature": "SpendProofV1…", "created_at": "…" }
+//! ```
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::{RpcClient, RpcError};
+use crate::error::Result;
+use crate::rpc::methods::{
+    CheckSpendProof, CheckSpendProofRequest, GetSpendProof, GetSpendProofRequest,
+};
+
+/// Proof files are a few hundred bytes per input; anything this big is not
+/// one.
+const MAX_PROOF_BYTES: usize = 1024 * 1024;
+
+#[derive(Error, Debug)]
+pub enum ProofError {
+    #[error("invalid tx hash {0:?}: expected 64 hex digits")]
+    InvalidTxHash(String),
+
+    #[error(
+        "transaction {0} was not sent by this wallet, so it can't prove the spend — check the \
+         hash, and refresh if the wallet was restored recently"
+    )]
+    NotOurTransaction(String),
+
+    #[error("transaction {0} is unknown to the daemon")]
+    UnknownTransaction(String),
+
+    #[error("the proof file is for transaction {found}, not {expected}")]
+    WrongTransaction { expected: String, found: String },
+}
+
+/// A spend proof as written to and read from a proof file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendProof {
+    pub txid: String,
+    /// The message the signature commits to; empty when none was given.
+    #[serde(default)]
+    pub message: String,
+    pub signature: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+fn validate_txid(txid: &str) -> Result<()> {
+    if txid.len() != 64 || !txid.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(ProofError::InvalidTxHash(txid.to_string()).into());
+    }
+    Ok(())
+}
+
+/// Map a spend proof RPC failure, recognising a transaction this wallet
+/// didn't send or the daemon doesn't have.
+fn proof_error(err: RpcError, txid: &str) -> crate::error::MultisigError {
+    let Some(message) = err.message().map(str::to_lowercase) else {
+        return err.into();
+    };
+    if message.contains("wasn't generated by this wallet")
+        || message.contains("key image not found")
+    {
+        ProofError::NotOurTransaction(txid.to_string()).into()
+    } else if message.contains("failed to get transaction") {
+        ProofError::UnknownTransaction(txid.to_string()).into()
+    } else {
+        err.into()
+    }
+}
+
+/// Sign a spend proof for `txid`, committing to `message`.
+pub async fn prove_spend(rpc: &RpcClient, txid: &str, message: &str) -> Result<SpendProof> {
+    validate_txid(txid)?;
+    let resp = rpc
+        .invoke::<GetSpendProof>(GetSpendProofRequest {
+            txid: txid.to_string(),
+            message: message.to_string(),
+        })
+        .await
+        .map_err(|e| proof_error(e, txid))?;
+    Ok(SpendProof {
+        txid: txid.to_string(),
+        message: message.to_string(),
+        signature: resp.signature,
+        created_at: Some(Utc::now()),
+    })
+}
+
+/// Check `proof` against the transaction `txid`. Any wallet can check a
+/// proof for any transaction its daemon knows.
+pub async fn check_spend_proof(rpc: &RpcClient, txid: &str, proof: &SpendProof) -> Result<bool> {
+    validate_txid(txid)?;
+    if !proof.txid.eq_ignore_ascii_case(txid) {
+        return Err(ProofError::WrongTransaction {
+            expected: txid.to_string(),
+            found: proof.txid.clone(),
+        }
+        .into());
+    }
+    let resp = rpc
+        .invoke::<CheckSpendProof>(CheckSpendProofRequest {
+            txid: txid.to_string(),
+            message: proof.message.clone(),
+            signature: proof.signature.clone(),
+        })
+        .await
+        .map_err(|e| proof_error(e, txid))?;
+    Ok(resp.good)
+}
+
+/// Write `proof` to `path` as pretty JSON.
+pub fn write_proof(path: &Path, proof: &SpendProof) -> Result<()> {
+    let json = serde_json::to_string_pretty(proof)?;
+    crate::utils::write_atomic(path, json.as_bytes())?;
+    Ok(())
+}
+
+/// Read a proof file written by [`write_proof`].
+pub fn read_proof(path: &Path) -> Result<SpendProof> {
+    let text = crate::utils::read_multisig_data(Some(path), MAX_PROOF_BYTES)?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::MultisigError;
+
+    const TXID: &str = "ab12ab12ab12ab12ab12ab12ab12ab12ab12ab12ab12ab12ab12ab12ab12ab12";
+
+    fn server(message: &str) -> RpcError {
+        RpcError::Server {
+            method: "get_spend_proof".into(),
+            code: -1,
+            message: message.into(),
+        }
+    }
+
+    #[test]
+    fn test_not_our_transaction_is_typed() {
+        assert!(matches!(
+            proof_error(server("This tx wasn't generated by this wallet!"), TXID),
+            MultisigError::Proof(ProofError::NotOurTransaction(txid)) if txid == TXID
+        ));
+        assert!(matches!(
+            proof_error(server("Failed to get transaction from daemon"), TXID),
+            MultisigError::Proof(ProofError::UnknownTransaction(_))
+        ));
+        assert!(matches!(
+            proof_error(server("no connection to daemon"), TXID),
+            MultisigError::Rpc(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_check_refuses_proof_for_other_tx() {
+        let rpc = RpcClient::new(&crate::config::DaemonRpc::default());
+        let proof = SpendProof {
+            txid: "cd".repeat(32),
+            message: String::new(),
+            signature: "SpendProofV1x".into(),
+            created_at: None,
+        };
+        assert!(matches!(
+            check_spend_proof(&rpc, TXID, &proof).await,
+            Err(MultisigError::Proof(ProofError::WrongTransaction { .. }))
+        ));
+        assert!(matches!(
+            check_spend_proof(&rpc, "abc", &proof).await,
+            Err(MultisigError::Proof(ProofError::InvalidTxHash(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_proof_round_trip_through_file() {
+        let (stub, _) = crate::testing::stub_daemon(
+            r#"{"jsonrpc":"2.0","id":"0","result":{"signature":"SpendProofV1abc","good":true}}"#,
+        )
+        .await;
+        let rpc = RpcClient::new(&stub);
+        let proof = prove_spend(&rpc, TXID, "invoice 42").await.unwrap();
+        assert_eq!(proof.signature, "SpendProofV1abc");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("proof.json");
+        write_proof(&path, &proof).unwrap();
+        let read = read_proof(&path).unwrap();
+        assert_eq!(read, proof);
+        assert!(check_spend_proof(&rpc, TXID, &read).await.unwrap());
+    }
+}
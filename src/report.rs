@@ -0,0 +1,617 @@
+//! The `report` document: everything an auditor asks about the wallet, in
+//! one place.
+//!
+//! A report is put together from the state file (M-of-N, participants,
+//! address, creation date), the journal (every transaction built, signed or
+//! submitted here, with its amounts and note) and the wallet RPC's transfer
+//! history and balance. It renders as Markdown for a wiki, as a plain HTML
+//! page, or as the `report` JSON result.
+//!
+//! Totals are added up with checked arithmetic, so a history that doesn't
+//! fit in a `u64` fails the report instead of wrapping. The balance is
+//! reconciled against the whole history, whatever period the report covers:
+//! what was received less what was sent and paid in fees should be what the
+//! wallet holds, and any difference is flagged.
+
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::error::TransactionError;
+use crate::journal::{Journal, JournalEvent, JournalFilter, TxStatus};
+use crate::transaction::{format_xmr, Amount, Balance, Destination, TransferEntry, TransferKind};
+use crate::wallet::{StateFile, WalletState};
+
+/// A whole report, as the `report` JSON result.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub generated_at: DateTime<Utc>,
+    /// First day covered; `None` from the start.
+    pub from: Option<NaiveDate>,
+    /// Last day covered; `None` up to now.
+    pub to: Option<NaiveDate>,
+    pub wallet: WalletSummary,
+    /// Journal entries created in the period, oldest first.
+    pub transactions: Vec<ReportTransaction>,
+    /// Transfers in the period, from the wallet's history.
+    pub totals: Totals,
+    /// The balance over every account right now.
+    pub balance: Balance,
+    pub reconciliation: Reconciliation,
+}
+
+/// How the wallet was set up.
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletSummary {
+    pub label: String,
+    /// Signatures required; `None` for a watch-only wallet.
+    pub threshold: Option<u32>,
+    pub total: Option<u32>,
+    /// Roster names, this participant first; empty when none were given.
+    pub participants: Vec<String>,
+    /// `None` until key exchange is finished.
+    pub address: Option<String>,
+    pub address_verified: bool,
+    /// Unknown for state files from older versions.
+    pub created_at: Option<DateTime<Utc>>,
+    pub stage: String,
+}
+
+/// One journal entry, without the tx set blobs.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportTransaction {
+    pub id: u64,
+    pub created_at: DateTime<Utc>,
+    pub status: TxStatus,
+    pub note: Option<String>,
+    pub destinations: Vec<Destination>,
+    pub amount: Amount,
+    pub fee: Amount,
+    pub tx_hash: Option<String>,
+    pub signatures_collected: u32,
+    pub signatures_required: u32,
+    pub events: Vec<JournalEvent>,
+}
+
+/// Money in and out according to the wallet's history.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Totals {
+    pub received: Amount,
+    pub sent: Amount,
+    pub fees: Amount,
+    /// Transfers counted.
+    pub transfers: usize,
+}
+
+impl Totals {
+    /// Add up `transfers`: confirmed incoming ones as received, outgoing
+    /// ones, confirmed or not, as sent plus their fee. Incoming transfers
+    /// still in the pool and failed ones are skipped, as the balance doesn't
+    /// count them either.
+    pub fn of<'a>(
+        transfers: impl IntoIterator<Item = &'a TransferEntry>,
+    ) -> Result<Self, TransactionError> {
+        let mut totals = Totals::default();
+        for t in transfers {
+            match t.kind {
+                TransferKind::In => {
+                    totals.received = totals.received.checked_add(t.amount.into())?
+                }
+                TransferKind::Out | TransferKind::Pending => {
+                    totals.sent = totals.sent.checked_add(t.amount.into())?;
+                    totals.fees = totals.fees.checked_add(t.fee.into())?;
+                }
+                TransferKind::Pool | TransferKind::Failed => continue,
+            }
+            totals.transfers += 1;
+        }
+        Ok(totals)
+    }
+}
+
+/// The balance checked against the whole history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Reconciliation {
+    /// Received less sent and fees; `None` when the history has more going
+    /// out than coming in.
+    pub expected: Option<Amount>,
+    pub balance: Amount,
+    /// `None` when the balance is what the history says.
+    pub discrepancy: Option<Discrepancy>,
+}
+
+impl Reconciliation {
+    /// Compare `balance` with what the all-time `history` totals leave.
+    pub fn new(history: &Totals, balance: Amount) -> Result<Self, TransactionError> {
+        let outgoing = history.sent.checked_add(history.fees)?;
+        let expected = history.received.checked_sub(outgoing).ok();
+        let discrepancy = match expected {
+            Some(expected) if balance > expected => {
+                Some(Discrepancy::Surplus(balance.checked_sub(expected)?))
+            }
+            Some(expected) if balance < expected => {
+                Some(Discrepancy::Shortfall(expected.checked_sub(balance)?))
+            }
+            Some(_) => None,
+            None => Some(Discrepancy::Surplus(
+                balance.checked_add(outgoing.checked_sub(history.received)?)?,
+            )),
+        };
+        Ok(Reconciliation {
+            expected,
+            balance,
+            discrepancy,
+        })
+    }
+}
+
+/// How far the balance is from what the history explains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "amount", rename_all = "snake_case")]
+pub enum Discrepancy {
+    /// The wallet holds more than the history explains.
+    Surplus(Amount),
+    /// The wallet holds less than the history explains.
+    Shortfall(Amount),
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Discrepancy::Surplus(a) => {
+                write!(f, "the balance is {a} more than the history explains")
+            }
+            Discrepancy::Shortfall(a) => {
+                write!(f, "the balance is {a} less than the history explains")
+            }
+        }
+    }
+}
+
+impl Report {
+    /// Assemble a report covering `from` to `to`, both inclusive. `history`
+    /// is the transfer history of every account and `balance` their total.
+    pub fn new(
+        state: &StateFile,
+        journal: &Journal,
+        history: &[TransferEntry],
+        balance: Balance,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<Self, TransactionError> {
+        let filter = JournalFilter {
+            since: from,
+            until: to,
+            ..JournalFilter::default()
+        };
+        let transactions = journal
+            .filter(&filter)
+            .map(|e| ReportTransaction {
+                id: e.id,
+                created_at: e.created_at,
+                status: e.status,
+                note: e.note.clone(),
+                destinations: e.destinations.clone(),
+                amount: Amount(e.amount()),
+                fee: Amount(e.fee),
+                tx_hash: e.latest_tx_hash().map(str::to_string),
+                signatures_collected: e.signatures_collected,
+                signatures_required: e.signatures_required,
+                events: e.events.clone(),
+            })
+            .collect();
+        let in_period = history.iter().filter(|t| {
+            let day = DateTime::from_timestamp(t.timestamp as i64, 0)
+                .unwrap_or_default()
+                .date_naive();
+            from.is_none_or(|from| day >= from) && to.is_none_or(|to| day <= to)
+        });
+        let totals = Totals::of(in_period)?;
+        let reconciliation = Reconciliation::new(&Totals::of(history)?, balance.balance)?;
+
+        let wallet = &state.wallet;
+        let address = match wallet {
+            WalletState::Ready { address, .. } | WalletState::WatchOnly { address, .. } => {
+                Some(address.clone())
+            }
+            _ => None,
+        };
+        Ok(Report {
+            generated_at: Utc::now(),
+            from,
+            to,
+            wallet: WalletSummary {
+                label: wallet.label().to_string(),
+                threshold: wallet.params().map(|p| p.threshold),
+                total: wallet.params().map(|p| p.total),
+                participants: state.participants().into_iter().map(|p| p.name).collect(),
+                address,
+                address_verified: state.verified,
+                created_at: state.created_at,
+                stage: wallet.stage(),
+            },
+            transactions,
+            totals,
+            balance,
+            reconciliation,
+        })
+    }
+
+    /// "all time", or the dates covered.
+    fn period(&self) -> String {
+        match (self.from, self.to) {
+            (None, None) => "all time".to_string(),
+            (Some(from), None) => format!("{from} onwards"),
+            (None, Some(to)) => format!("up to {to}"),
+            (Some(from), Some(to)) => format!("{from} to {to}"),
+        }
+    }
+
+    /// The wallet's setup as label and value pairs.
+    fn overview(&self) -> Vec<(&'static str, String)> {
+        let w = &self.wallet;
+        let mut rows = vec![("Label", w.label.clone())];
+        if let (Some(m), Some(n)) = (w.threshold, w.total) {
+            rows.push(("Signatures", format!("{m} of {n}")));
+        }
+        if !w.participants.is_empty() {
+            rows.push(("Participants", w.participants.join(", ")));
+        }
+        if let Some(address) = &w.address {
+            let verified = if w.address_verified {
+                "verified by all participants"
+            } else {
+                "not verified"
+            };
+            rows.push(("Address", format!("{address} ({verified})")));
+        }
+        let created = w
+            .created_at
+            .map(|at| at.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        rows.push(("Created", created));
+        rows.push(("Setup", w.stage.clone()));
+        rows
+    }
+
+    /// Amounts as label and XMR pairs: the balance, the period's totals and
+    /// the reconciliation.
+    fn amounts(&self) -> Vec<(String, String)> {
+        let xmr = |a: Amount| format_xmr(a.0);
+        let r = &self.reconciliation;
+        vec![
+            ("Balance".to_string(), xmr(self.balance.balance)),
+            ("Unlocked".to_string(), xmr(self.balance.unlocked_balance)),
+            (
+                format!("Received ({})", self.period()),
+                xmr(self.totals.received),
+            ),
+            (format!("Sent ({})", self.period()), xmr(self.totals.sent)),
+            (format!("Fees ({})", self.period()), xmr(self.totals.fees)),
+            (
+                "Balance the history explains".to_string(),
+                r.expected
+                    .map(xmr)
+                    .unwrap_or_else(|| "below zero".to_string()),
+            ),
+        ]
+    }
+
+    /// The report as Markdown, with pipe tables as most wikis render them.
+    pub fn to_markdown(&self) -> String {
+        let cell = |text: &str| text.replace('|', "\\|").replace('\n', " ");
+        let mut out = format!(
+            "# Wallet report: {}\n\nGenerated {} covering {}.\n\n## Wallet\n\n| | |\n|---|---|\n",
+            cell(&self.wallet.label),
+            self.generated_at.format("%Y-%m-%d %H:%M UTC"),
+            self.period()
+        );
+        for (label, value) in self.overview() {
+            out.push_str(&format!("| {label} | {} |\n", cell(&value)));
+        }
+        out.push_str("\n## Amounts\n\n| | XMR |\n|---|---:|\n");
+        for (label, value) in self.amounts() {
+            out.push_str(&format!("| {label} | {value} |\n"));
+        }
+        match &self.reconciliation.discrepancy {
+            Some(d) => out.push_str(&format!("\n**Discrepancy:** {d}.\n")),
+            None => out.push_str("\nThe balance matches the history.\n"),
+        }
+
+        out.push_str("\n## Transactions\n");
+        if self.transactions.is_empty() {
+            out.push_str("\nNo transactions in the journal for this period.\n");
+        }
+        for t in &self.transactions {
+            out.push_str(&format!(
+                "\n### #{} {}: {}\n\n",
+                t.id,
+                t.created_at.format("%Y-%m-%d %H:%M"),
+                t.status
+            ));
+            if let Some(note) = &t.note {
+                out.push_str(&format!("{}\n\n", cell(note)));
+            }
+            out.push_str("| Destination | XMR |\n|---|---:|\n");
+            for d in &t.destinations {
+                out.push_str(&format!(
+                    "| `{}` | {} |\n",
+                    d.address(),
+                    format_xmr(d.amount().0)
+                ));
+            }
+            out.push_str(&format!(
+                "| Fee | {} |\n| **Total** | **{}** |\n\n",
+                format_xmr(t.fee.0),
+                format_xmr(t.amount.0.saturating_add(t.fee.0))
+            ));
+            if let Some(hash) = &t.tx_hash {
+                out.push_str(&format!("Tx hash: `{hash}`  \n"));
+            }
+            out.push_str(&format!(
+                "Signatures: {} of {}\n\n",
+                t.signatures_collected, t.signatures_required
+            ));
+            for e in &t.events {
+                out.push_str(&format!(
+                    "- {} {} by {}\n",
+                    e.at.format("%Y-%m-%d %H:%M:%S"),
+                    e.action,
+                    cell(&e.by)
+                ));
+            }
+        }
+        out
+    }
+
+    /// The report as a standalone HTML page.
+    pub fn to_html(&self) -> String {
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Wallet report: {label}</title>\n<style>\n\
+             body {{ font-family: sans-serif; max-width: 60em; margin: 2em auto; }}\n\
+             table {{ border-collapse: collapse; margin: 1em 0; }}\n\
+             th, td {{ border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }}\n\
+             td.xmr {{ text-align: right; font-family: monospace; }}\n\
+             code {{ word-break: break-all; }}\n\
+             .discrepancy {{ color: #b00; font-weight: bold; }}\n\
+             </style>\n</head>\n<body>\n<h1>Wallet report: {label}</h1>\n\
+             <p>Generated {generated} covering {period}.</p>\n<h2>Wallet</h2>\n<table>\n",
+            label = escape_html(&self.wallet.label),
+            generated = self.generated_at.format("%Y-%m-%d %H:%M UTC"),
+            period = self.period(),
+        );
+        for (label, value) in self.overview() {
+            out.push_str(&format!(
+                "<tr><th>{label}</th><td>{}</td></tr>\n",
+                escape_html(&value)
+            ));
+        }
+        out.push_str("</table>\n<h2>Amounts</h2>\n<table>\n");
+        for (label, value) in self.amounts() {
+            out.push_str(&format!(
+                "<tr><th>{label}</th><td class=\"xmr\">{value}</td></tr>\n"
+            ));
+        }
+        out.push_str("</table>\n");
+        match &self.reconciliation.discrepancy {
+            Some(d) => out.push_str(&format!("<p class=\"discrepancy\">Discrepancy: {d}.</p>\n")),
+            None => out.push_str("<p>The balance matches the history.</p>\n"),
+        }
+
+        out.push_str("<h2>Transactions</h2>\n");
+        if self.transactions.is_empty() {
+            out.push_str("<p>No transactions in the journal for this period.</p>\n");
+        }
+        for t in &self.transactions {
+            out.push_str(&format!(
+                "<h3>#{} {}: {}</h3>\n",
+                t.id,
+                t.created_at.format("%Y-%m-%d %H:%M"),
+                t.status
+            ));
+            if let Some(note) = &t.note {
+                out.push_str(&format!("<p>{}</p>\n", escape_html(note)));
+            }
+            out.push_str("<table>\n");
+            for d in &t.destinations {
+                out.push_str(&format!(
+                    "<tr><td><code>{}</code></td><td class=\"xmr\">{}</td></tr>\n",
+                    escape_html(d.address()),
+                    format_xmr(d.amount().0)
+                ));
+            }
+            out.push_str(&format!(
+                "<tr><th>Fee</th><td class=\"xmr\">{}</td></tr>\n\
+                 <tr><th>Total</th><td class=\"xmr\">{}</td></tr>\n</table>\n",
+                format_xmr(t.fee.0),
+                format_xmr(t.amount.0.saturating_add(t.fee.0))
+            ));
+            if let Some(hash) = &t.tx_hash {
+                out.push_str(&format!(
+                    "<p>Tx hash: <code>{}</code></p>\n",
+                    escape_html(hash)
+                ));
+            }
+            out.push_str(&format!(
+                "<p>Signatures: {} of {}</p>\n<ul>\n",
+                t.signatures_collected, t.signatures_required
+            ));
+            for e in &t.events {
+                out.push_str(&format!(
+                    "<li>{} {} by {}</li>\n",
+                    e.at.format("%Y-%m-%d %H:%M:%S"),
+                    escape_html(&e.action.to_string()),
+                    escape_html(&e.by)
+                ));
+            }
+            out.push_str("</ul>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::SubaddressIndex;
+    use crate::wallet::SerializableParams;
+
+    fn transfer(kind: TransferKind, amount: u64, fee: u64, timestamp: u64) -> TransferEntry {
+        TransferEntry {
+            txid: "ab".into(),
+            kind,
+            amount,
+            fee,
+            height: 0,
+            timestamp,
+            confirmations: 10,
+            address: String::new(),
+            subaddr_index: SubaddressIndex::default(),
+        }
+    }
+
+    fn balance(piconero: u64) -> Balance {
+        Balance::unlocked(Amount(piconero))
+    }
+
+    fn state() -> StateFile {
+        StateFile::new(WalletState::Ready {
+            wallet_path: "w".into(),
+            address: "4addr".into(),
+            params: SerializableParams {
+                threshold: 2,
+                total: 3,
+                label: "ops <fund>".into(),
+                roster: vec!["alice".into(), "bob".into()],
+            },
+            restore_height: None,
+        })
+    }
+
+    #[test]
+    fn test_totals_skip_what_the_balance_does_not_count() {
+        let history = [
+            transfer(TransferKind::In, 100, 0, 0),
+            transfer(TransferKind::Pool, 50, 0, 0),
+            transfer(TransferKind::Out, 30, 2, 0),
+            transfer(TransferKind::Pending, 10, 1, 0),
+            transfer(TransferKind::Failed, 5, 1, 0),
+        ];
+        let totals = Totals::of(&history).unwrap();
+        assert_eq!(
+            totals,
+            Totals {
+                received: Amount(100),
+                sent: Amount(40),
+                fees: Amount(3),
+                transfers: 3,
+            }
+        );
+
+        let overflow = [
+            transfer(TransferKind::In, u64::MAX, 0, 0),
+            transfer(TransferKind::In, 1, 0, 0),
+        ];
+        assert!(matches!(
+            Totals::of(&overflow),
+            Err(TransactionError::AmountOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_reconciliation_flags_discrepancies() {
+        let history = Totals {
+            received: Amount(100),
+            sent: Amount(40),
+            fees: Amount(3),
+            transfers: 3,
+        };
+        let matched = Reconciliation::new(&history, Amount(57)).unwrap();
+        assert_eq!(matched.expected, Some(Amount(57)));
+        assert_eq!(matched.discrepancy, None);
+        assert_eq!(
+            Reconciliation::new(&history, Amount(50))
+                .unwrap()
+                .discrepancy,
+            Some(Discrepancy::Shortfall(Amount(7)))
+        );
+        assert_eq!(
+            Reconciliation::new(&history, Amount(60))
+                .unwrap()
+                .discrepancy,
+            Some(Discrepancy::Surplus(Amount(3)))
+        );
+
+        // More went out than the history shows coming in.
+        let short = Totals {
+            received: Amount(10),
+            ..history
+        };
+        let r = Reconciliation::new(&short, Amount(5)).unwrap();
+        assert_eq!(r.expected, None);
+        assert_eq!(r.discrepancy, Some(Discrepancy::Surplus(Amount(38))));
+    }
+
+    #[test]
+    fn test_report_covers_the_period() {
+        let mut journal = Journal::default();
+        journal.record_built(
+            &[Destination::new_unchecked("4dest", Amount(30))],
+            2,
+            &["ab".into()],
+            "set",
+            2,
+            Some("rent | March".into()),
+        );
+        // 2023-11-14 and 2024-02-26.
+        let history = [
+            transfer(TransferKind::In, 100, 0, 1_700_000_000),
+            transfer(TransferKind::Out, 30, 2, 1_709_000_000),
+        ];
+        let from = NaiveDate::from_ymd_opt(2024, 1, 1);
+        let report = Report::new(&state(), &journal, &history, balance(68), from, None).unwrap();
+        assert_eq!(report.totals.received, Amount(0));
+        assert_eq!(report.totals.sent, Amount(30));
+        assert_eq!(report.reconciliation.expected, Some(Amount(68)));
+        assert_eq!(report.reconciliation.discrepancy, None);
+        assert_eq!(report.wallet.participants, ["me", "alice", "bob"]);
+        assert_eq!(report.transactions.len(), 1);
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("| Signatures | 2 of 3 |"), "{markdown}");
+        assert!(markdown.contains("rent \\| March"), "{markdown}");
+        assert!(
+            markdown.contains("| `4dest` | 0.000000000030 |"),
+            "{markdown}"
+        );
+        assert!(markdown.contains("The balance matches the history."));
+        let html = report.to_html();
+        assert!(html.contains("Wallet report: ops &lt;fund&gt;"), "{html}");
+
+        // The journal entry was made today, after a period in the past.
+        let to = NaiveDate::from_ymd_opt(2024, 12, 31);
+        let report = Report::new(&state(), &journal, &history, balance(60), from, to).unwrap();
+        assert!(report.transactions.is_empty());
+        assert!(report
+            .to_markdown()
+            .contains("**Discrepancy:** the balance is 0.000000000008 XMR less"));
+    }
+}
@@ -0,0 +1,50 @@
+//! The JSON-RPC methods this crate calls, as types.
+//!
+//! Every method in [`methods`] is a marker type implementing [`RpcMethod`],
+//! which ties the method's name to its request and response structs. Calls
+//! go through [`RpcClient::invoke`], so a misspelt parameter is a compile
+//! error instead of a field the server silently ignores:
+//!
+//! ```no_run
+//! use monero_multisig::config::{DaemonRpc, RpcClient};
+//! use monero_multisig::rpc::methods::{GetBalance, GetBalanceRequest};
+//!
+//! # async fn run() -> Result<(), monero_multisig::config::RpcError> {
+//! let rpc = RpcClient::new(&DaemonRpc::default());
+//! let balance = rpc
+//!     .invoke::<GetBalance>(GetBalanceRequest {
+//!         account_index: Some(0),
+//!         ..Default::default()
+//!     })
+//!     .await?;
+//! println!("{}", balance.unlocked_balance);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Methods whose name is only known at run time, such as the probes in
+//! [`crate::capabilities`], still go through [`RpcClient::call_raw`]. The
+//! daemon's plain-JSON endpoints beside `/json_rpc` aren't JSON-RPC methods
+//! and go through [`RpcClient::request_other`].
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+#[cfg(doc)]
+use crate::config::RpcClient;
+
+pub mod methods;
+
+/// A JSON-RPC method: its name and the shape of its params and result.
+pub trait RpcMethod {
+    /// The method name sent on the wire.
+    const NAME: &'static str;
+    type Request: Serialize + DeserializeOwned;
+    type Response: DeserializeOwned;
+}
+
+/// `request` as one of the calls of an [`RpcClient::request_batch`].
+pub fn batch_call<M: RpcMethod>(request: &M::Request) -> (&'static str, serde_json::Value) {
+    let params = serde_json::to_value(request).expect("RPC requests serialize");
+    (M::NAME, params)
+}
@@ -0,0 +1,864 @@
+//! One request/response pair per JSON-RPC method this crate calls.
+//!
+//! Requests serialize to exactly the params the servers document; optional
+//! params are left out when unset rather than sent as `null` or a default.
+//! Responses hold only the fields the crate reads, and results that carry
+//! nothing useful are [`IgnoredAny`].
+
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+
+use super::RpcMethod;
+use crate::secret::Secret;
+use crate::transaction::{
+    Amount, FeeEstimate, OwnedOutput, Recipient, SubaddressIndex, TransferDescription, TransferKind,
+};
+use crate::wallet::{
+    Account, AccountTag, AddressBookEntry, MultisigStatus, RefreshResult, Subaddress,
+};
+
+macro_rules! rpc_methods {
+    ($($(#[$doc:meta])* $method:ident($name:literal): $request:ty => $response:ty;)*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Clone, Copy)]
+            pub struct $method;
+
+            impl RpcMethod for $method {
+                const NAME: &'static str = $name;
+                type Request = $request;
+                type Response = $response;
+            }
+        )*
+    };
+}
+
+rpc_methods! {
+    // ── Wallet: multisig setup ──
+    PrepareMultisig("prepare_multisig"): PrepareMultisigRequest => PrepareMultisigResponse;
+    MakeMultisig("make_multisig"): MakeMultisigRequest => MakeMultisigResponse;
+    ExchangeMultisigKeys("exchange_multisig_keys"):
+        ExchangeMultisigKeysRequest => ExchangeMultisigKeysResponse;
+    /// Only the legacy multisig API has it; see
+    /// [`MultisigApi`](crate::wallet::MultisigApi).
+    FinalizeMultisig("finalize_multisig"): FinalizeMultisigRequest => FinalizeMultisigResponse;
+    IsMultisig("is_multisig"): IsMultisigRequest => MultisigStatus;
+    ExportMultisigInfo("export_multisig_info"):
+        ExportMultisigInfoRequest => ExportMultisigInfoResponse;
+    ImportMultisigInfo("import_multisig_info"):
+        ImportMultisigInfoRequest => ImportMultisigInfoResponse;
+
+    // ── Wallet: addresses and accounts ──
+    GetAddress("get_address"): GetAddressRequest => GetAddressResponse;
+    GetAddressBook("get_address_book"): GetAddressBookRequest => GetAddressBookResponse;
+    AddAddressBook("add_address_book"): AddAddressBookRequest => AddAddressBookResponse;
+    EditAddressBook("edit_address_book"): EditAddressBookRequest => IgnoredAny;
+    GetAccounts("get_accounts"): GetAccountsRequest => GetAccountsResponse;
+    GetAccountTags("get_account_tags"): GetAccountTagsRequest => GetAccountTagsResponse;
+    TagAccounts("tag_accounts"): TagAccountsRequest => IgnoredAny;
+    UntagAccounts("untag_accounts"): UntagAccountsRequest => IgnoredAny;
+    SetAccountTagDescription("set_account_tag_description"):
+        SetAccountTagDescriptionRequest => IgnoredAny;
+    CreateAccount("create_account"): CreateAccountRequest => CreateAccountResponse;
+    CreateAddress("create_address"): CreateAddressRequest => CreateAddressResponse;
+    QueryKey("query_key"): QueryKeyRequest => QueryKeyResponse;
+
+    // ── Wallet: files and sync ──
+    GenerateFromKeys("generate_from_keys"): GenerateFromKeysRequest => GenerateFromKeysResponse;
+    CloseWallet("close_wallet"): CloseWalletRequest => IgnoredAny;
+    GetHeight("get_height"): GetHeightRequest => GetHeightResponse;
+    Refresh("refresh"): RefreshRequest => RefreshResult;
+    RescanSpent("rescan_spent"): RescanSpentRequest => IgnoredAny;
+
+    // ── Wallet: transactions ──
+    Transfer("transfer"): TransferRequest => TransferResponse;
+    /// Takes the same params as [`Transfer`].
+    TransferSplit("transfer_split"): TransferRequest => TransferSplitResponse;
+    DescribeTransfer("describe_transfer"): DescribeTransferRequest => DescribeTransferResponse;
+    SignMultisig("sign_multisig"): SignMultisigRequest => SignMultisigResponse;
+    SubmitMultisig("submit_multisig"): SubmitMultisigRequest => SubmitMultisigResponse;
+    SignTransfer("sign_transfer"): SignTransferRequest => SignTransferResponse;
+    SubmitTransfer("submit_transfer"): SubmitTransferRequest => SubmitTransferResponse;
+    Freeze("freeze"): KeyImageRequest => IgnoredAny;
+    Thaw("thaw"): KeyImageRequest => IgnoredAny;
+
+    // ── Wallet: balance and history ──
+    GetBalance("get_balance"): GetBalanceRequest => GetBalanceResponse;
+    GetTransfers("get_transfers"): GetTransfersRequest => GetTransfersResponse;
+    GetTransferByTxid("get_transfer_by_txid"):
+        GetTransferByTxidRequest => GetTransferByTxidResponse;
+    IncomingTransfers("incoming_transfers"):
+        IncomingTransfersRequest => IncomingTransfersResponse;
+    GetTxNotes("get_tx_notes"): GetTxNotesRequest => GetTxNotesResponse;
+    SetTxNotes("set_tx_notes"): SetTxNotesRequest => IgnoredAny;
+    GetSpendProof("get_spend_proof"): GetSpendProofRequest => GetSpendProofResponse;
+    CheckSpendProof("check_spend_proof"): CheckSpendProofRequest => CheckSpendProofResponse;
+
+    // ── Either server ──
+    GetVersion("get_version"): GetVersionRequest => GetVersionResponse;
+
+    // ── Daemon ──
+    GetInfo("get_info"): GetInfoRequest => GetInfoResponse;
+    GetBlockHeaderByHeight("get_block_header_by_height"):
+        GetBlockHeaderByHeightRequest => GetBlockHeaderByHeightResponse;
+    HardForkInfo("hard_fork_info"): HardForkInfoRequest => HardForkInfoResponse;
+    GetFeeEstimate("get_fee_estimate"): GetFeeEstimateRequest => FeeEstimate;
+}
+
+// ── Wallet: multisig setup ──────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrepareMultisigRequest {}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PrepareMultisigResponse {
+    pub multisig_info: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MakeMultisigRequest {
+    pub multisig_info: Vec<String>,
+    pub threshold: u32,
+    #[serde(serialize_with = "crate::secret::serialize")]
+    pub password: Secret<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MakeMultisigResponse {
+    /// Empty until the last round.
+    pub address: String,
+    pub multisig_info: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExchangeMultisigKeysRequest {
+    pub multisig_info: Vec<String>,
+    #[serde(serialize_with = "crate::secret::serialize")]
+    pub password: Secret<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExchangeMultisigKeysResponse {
+    /// Empty until the last round.
+    pub address: String,
+    pub multisig_info: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FinalizeMultisigRequest {
+    pub multisig_info: Vec<String>,
+    #[serde(serialize_with = "crate::secret::serialize")]
+    pub password: Secret<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FinalizeMultisigResponse {
+    pub address: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IsMultisigRequest {}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportMultisigInfoRequest {}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportMultisigInfoResponse {
+    /// Hex of the export.
+    pub info: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportMultisigInfoRequest {
+    pub info: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportMultisigInfoResponse {
+    #[serde(default)]
+    pub n_outputs: u64,
+}
+
+// ── Wallet: addresses and accounts ──────────────────────────────────────────
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetAddressRequest {
+    pub account_index: u32,
+    /// Subaddresses to list; all of them when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub address_index: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetAddressResponse {
+    /// The account's base address.
+    pub address: String,
+    #[serde(default)]
+    pub addresses: Vec<Subaddress>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetAddressBookRequest {
+    /// Entry indices to fetch; every entry when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entries: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetAddressBookResponse {
+    #[serde(default)]
+    pub entries: Vec<AddressBookEntry>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AddAddressBookRequest {
+    pub address: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AddAddressBookResponse {
+    pub index: u64,
+}
+
+/// Only the fields whose `set_` flag is true are changed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EditAddressBookRequest {
+    pub index: u64,
+    pub set_address: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    pub set_description: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetAccountsRequest {
+    /// Only the accounts with this tag.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetAccountsResponse {
+    pub subaddress_accounts: Vec<Account>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetAccountTagsRequest {}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetAccountTagsResponse {
+    #[serde(default)]
+    pub account_tags: Vec<AccountTag>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TagAccountsRequest {
+    pub tag: String,
+    pub accounts: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UntagAccountsRequest {
+    pub accounts: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetAccountTagDescriptionRequest {
+    pub tag: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateAccountRequest {
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateAccountResponse {
+    pub account_index: u32,
+    pub address: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateAddressRequest {
+    pub account_index: u32,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateAddressResponse {
+    pub address_index: u32,
+    pub address: String,
+}
+
+/// Which key `query_key` returns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyType {
+    #[default]
+    ViewKey,
+    SpendKey,
+    Mnemonic,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryKeyRequest {
+    pub key_type: KeyType,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QueryKeyResponse {
+    pub key: Secret<String>,
+}
+
+// ── Wallet: files and sync ──────────────────────────────────────────────────
+
+/// A watch-only wallet when `spendkey` is unset.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GenerateFromKeysRequest {
+    pub filename: String,
+    pub address: String,
+    #[serde(serialize_with = "crate::secret::serialize")]
+    pub viewkey: Secret<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_some_secret"
+    )]
+    pub spendkey: Option<Secret<String>>,
+    #[serde(serialize_with = "crate::secret::serialize")]
+    pub password: Secret<String>,
+    #[serde(default)]
+    pub restore_height: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GenerateFromKeysResponse {
+    pub address: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CloseWalletRequest {}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetHeightRequest {}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetHeightResponse {
+    pub height: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RefreshRequest {
+    /// Scan from here rather than from where the wallet left off.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_height: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RescanSpentRequest {}
+
+// ── Wallet: transactions ────────────────────────────────────────────────────
+
+/// Params of `transfer` and `transfer_split`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TransferRequest {
+    pub destinations: Vec<Recipient>,
+    pub account_index: u32,
+    /// Subaddresses of the account to spend from; the wallet picks when
+    /// empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subaddr_indices: Vec<u32>,
+    pub priority: u32,
+    #[serde(default)]
+    pub get_tx_hex: bool,
+    #[serde(default)]
+    pub do_not_relay: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransferResponse {
+    pub tx_hash: String,
+    pub fee: u64,
+    pub multisig_txset: String,
+    #[serde(default)]
+    pub weight: u64,
+    #[serde(default)]
+    pub tx_size: Option<u64>,
+    /// Older wallet RPCs leave it out.
+    #[serde(default)]
+    pub spent_key_images: KeyImages,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransferSplitResponse {
+    pub tx_hash_list: Vec<String>,
+    pub fee_list: Vec<u64>,
+    pub multisig_txset: String,
+    #[serde(default)]
+    pub weight_list: Vec<u64>,
+    #[serde(default)]
+    pub tx_size_list: Option<Vec<u64>>,
+    /// One per transaction; older wallet RPCs leave it out.
+    #[serde(default)]
+    pub spent_key_images_list: Vec<KeyImages>,
+}
+
+/// Key images of the inputs a built transaction spends.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KeyImages {
+    #[serde(default)]
+    pub key_images: Vec<String>,
+}
+
+/// Params of `freeze` and `thaw`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeyImageRequest {
+    pub key_image: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DescribeTransferRequest {
+    pub multisig_txset: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DescribeTransferResponse {
+    #[serde(default)]
+    pub desc: Vec<TransferDescription>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SignMultisigRequest {
+    pub tx_data_hex: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SignMultisigResponse {
+    pub tx_hash_list: Vec<String>,
+    pub tx_data_hex: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SubmitMultisigRequest {
+    pub tx_data_hex: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubmitMultisigResponse {
+    pub tx_hash_list: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SignTransferRequest {
+    pub unsigned_txset: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SignTransferResponse {
+    pub signed_txset: String,
+    #[serde(default)]
+    pub tx_hash_list: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SubmitTransferRequest {
+    /// Hex of the `signed_monero_tx`.
+    pub tx_data_hex: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubmitTransferResponse {
+    pub tx_hash_list: Vec<String>,
+}
+
+// ── Wallet: balance and history ─────────────────────────────────────────────
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetBalanceRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_index: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub address_indices: Vec<u32>,
+    /// Sum over every account instead.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub all_accounts: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetBalanceResponse {
+    pub balance: Amount,
+    pub unlocked_balance: Amount,
+    #[serde(default)]
+    pub blocks_to_unlock: u64,
+    #[serde(default)]
+    pub time_to_unlock: u64,
+}
+
+/// Which lists `get_transfers` fills, and for which accounts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetTransfersRequest {
+    #[serde(rename = "in", default, skip_serializing_if = "std::ops::Not::not")]
+    pub incoming: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub out: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pending: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub failed: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pool: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_index: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subaddr_indices: Vec<u32>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub all_accounts: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GetTransfersResponse {
+    #[serde(default, rename = "in")]
+    pub incoming: Vec<TransferRecord>,
+    #[serde(default)]
+    pub out: Vec<TransferRecord>,
+    #[serde(default)]
+    pub pending: Vec<TransferRecord>,
+    #[serde(default)]
+    pub failed: Vec<TransferRecord>,
+    #[serde(default)]
+    pub pool: Vec<TransferRecord>,
+}
+
+/// One entry of a `get_transfers` list, whichever list it is in, or the
+/// transfer `get_transfer_by_txid` finds.
+/// [`TransferEntry`](crate::transaction::TransferEntry) and
+/// [`PoolPayment`](crate::transaction::PoolPayment) are made from it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferRecord {
+    pub txid: String,
+    #[serde(rename = "type")]
+    pub kind: TransferKind,
+    pub amount: u64,
+    #[serde(default)]
+    pub fee: u64,
+    #[serde(default)]
+    pub height: u64,
+    #[serde(default)]
+    pub timestamp: u64,
+    #[serde(default)]
+    pub confirmations: u64,
+    #[serde(default)]
+    pub address: String,
+    #[serde(default)]
+    pub subaddr_index: SubaddressIndex,
+    /// Every subaddress an outgoing transfer spent from.
+    #[serde(default)]
+    pub subaddr_indices: Vec<SubaddressIndex>,
+    /// Where an outgoing transfer paid; only the wallet that sent it knows.
+    #[serde(default)]
+    pub destinations: Vec<Recipient>,
+    #[serde(default)]
+    pub unlock_time: u64,
+    /// The wallet's note for the transaction.
+    #[serde(default)]
+    pub note: String,
+    #[serde(default)]
+    pub double_spend_seen: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetTransferByTxidRequest {
+    pub txid: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_index: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetTransferByTxidResponse {
+    pub transfer: TransferRecord,
+    /// Every transfer with the txid, e.g. both halves of a self-transfer.
+    #[serde(default)]
+    pub transfers: Vec<TransferRecord>,
+}
+
+/// Which outputs `incoming_transfers` lists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferType {
+    #[default]
+    All,
+    /// Unspent ones.
+    Available,
+    /// Spent ones.
+    Unavailable,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IncomingTransfersRequest {
+    pub transfer_type: TransferType,
+    pub account_index: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subaddr_indices: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IncomingTransfersResponse {
+    #[serde(default)]
+    pub transfers: Vec<OwnedOutput>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetTxNotesRequest {
+    pub txids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetTxNotesResponse {
+    /// One per txid asked for, empty for a transaction without a note.
+    #[serde(default)]
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetTxNotesRequest {
+    pub txids: Vec<String>,
+    /// One per txid.
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetSpendProofRequest {
+    pub txid: String,
+    #[serde(default)]
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetSpendProofResponse {
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CheckSpendProofRequest {
+    pub txid: String,
+    #[serde(default)]
+    pub message: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckSpendProofResponse {
+    pub good: bool,
+}
+
+// ── Either server ───────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetVersionRequest {}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetVersionResponse {
+    /// Packed as `major << 16 | minor`; see
+    /// [`RpcVersion::from_packed`](crate::config::RpcVersion::from_packed).
+    pub version: u32,
+}
+
+// ── Daemon ──────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetInfoRequest {}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetInfoResponse {
+    pub height: u64,
+    #[serde(default)]
+    pub target_height: u64,
+    #[serde(default)]
+    pub difficulty: u64,
+    #[serde(default)]
+    pub synchronized: bool,
+    #[serde(default)]
+    pub untrusted: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetBlockHeaderByHeightRequest {
+    pub height: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetBlockHeaderByHeightResponse {
+    pub block_header: BlockHeader,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlockHeader {
+    /// Unix seconds, as the miner set it.
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HardForkInfoRequest {}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HardForkInfoResponse {
+    pub version: u8,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetFeeEstimateRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grace_blocks: Option<u64>,
+}
+
+fn serialize_some_secret<S: serde::Serializer>(
+    secret: &Option<Secret<String>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match secret {
+        Some(secret) => crate::secret::serialize(secret, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Requests as monero-wallet-rpc and monerod are sent them, from the
+    /// servers' documentation and from logged calls. Each must come back out
+    /// of its request struct unchanged: a field the struct dropped or renamed
+    /// would fail here rather than be ignored by the server.
+    fn round_trip<M: RpcMethod>(captured: &str) {
+        let request: M::Request =
+            serde_json::from_str(captured).unwrap_or_else(|e| panic!("{}: {e}", M::NAME));
+        let expected: serde_json::Value = serde_json::from_str(captured).unwrap();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            expected,
+            "{}",
+            M::NAME
+        );
+    }
+
+    const ADDRESS: &str = "55LTR8KniP4LQGJSPtbYDacR7dz8RBFnsfAKMaMuwUNYX6aQbBcovzDPyrQF9KXF9tVU6Xk3K8no1BywnJX6GvZX8yJsXvt";
+    const TXID: &str = "19d5089f9469db3d90aca9024dfcb17ce94b948300101c8345a5e9f7257353be";
+
+    #[test]
+    fn test_multisig_requests_round_trip() {
+        round_trip::<PrepareMultisig>("{}");
+        round_trip::<MakeMultisig>(
+            r#"{"multisig_info":["MultisigxV2R1bJ4...","MultisigxV2R1c8p..."],
+                "threshold":2,"password":"mypassword"}"#,
+        );
+        round_trip::<ExchangeMultisigKeys>(
+            r#"{"multisig_info":["MultisigxV2Rn1LVW...","MultisigxV2Rn1LVb..."],
+                "password":"mypassword"}"#,
+        );
+        round_trip::<FinalizeMultisig>(
+            r#"{"multisig_info":["MultisigxV2Rn1LVZ..."],"password":"mypassword"}"#,
+        );
+        round_trip::<IsMultisig>("{}");
+        round_trip::<ExportMultisigInfo>("{}");
+        round_trip::<ImportMultisigInfo>(r#"{"info":["4d6f6e65726f206d756c7469736967..."]}"#);
+    }
+
+    #[test]
+    fn test_account_requests_round_trip() {
+        round_trip::<GetAddress>(r#"{"account_index":0,"address_index":[0,1,4]}"#);
+        round_trip::<GetAddress>(r#"{"account_index":2}"#);
+        round_trip::<GetAddressBook>(r#"{"entries":[0,1]}"#);
+        round_trip::<GetAddressBook>("{}");
+        round_trip::<AddAddressBook>(&format!(
+            r#"{{"address":"{ADDRESS}","description":"rent"}}"#
+        ));
+        round_trip::<EditAddressBook>(&format!(
+            r#"{{"index":1,"set_address":true,"address":"{ADDRESS}","set_description":false}}"#
+        ));
+        round_trip::<GetAccounts>(r#"{"tag":"myTag"}"#);
+        round_trip::<GetAccounts>("{}");
+        round_trip::<GetAccountTags>("{}");
+        round_trip::<TagAccounts>(r#"{"tag":"myTag","accounts":[0,1]}"#);
+        round_trip::<UntagAccounts>(r#"{"accounts":[1]}"#);
+        round_trip::<SetAccountTagDescription>(r#"{"tag":"myTag","description":"Test tag"}"#);
+        round_trip::<CreateAccount>(r#"{"label":"Secondary account"}"#);
+        round_trip::<CreateAddress>(r#"{"account_index":0,"label":"new-sub"}"#);
+        round_trip::<QueryKey>(r#"{"key_type":"view_key"}"#);
+        round_trip::<QueryKey>(r#"{"key_type":"mnemonic"}"#);
+    }
+
+    #[test]
+    fn test_wallet_file_requests_round_trip() {
+        round_trip::<GenerateFromKeys>(&format!(
+            r#"{{"restore_height":2950000,"filename":"fund-watch","address":"{ADDRESS}",
+                "viewkey":"7b37d8922245a07244fd31855d1e705a590a9bd2881825f0542ad99cdaba090a",
+                "password":"pass"}}"#
+        ));
+        round_trip::<GenerateFromKeys>(&format!(
+            r#"{{"restore_height":0,"filename":"mywallet","address":"{ADDRESS}",
+                "spendkey":"29adefc8f67515b4b4bf48031780ab9d071d24f8a674b879ce7f245c37523807",
+                "viewkey":"7b37d8922245a07244fd31855d1e705a590a9bd2881825f0542ad99cdaba090a",
+                "password":"mypassword"}}"#
+        ));
+        round_trip::<CloseWallet>("{}");
+        round_trip::<GetHeight>("{}");
+        round_trip::<Refresh>(r#"{"start_height":100000}"#);
+        round_trip::<Refresh>("{}");
+        round_trip::<RescanSpent>("{}");
+    }
+
+    #[test]
+    fn test_transaction_requests_round_trip() {
+        let transfer = format!(
+            r#"{{"destinations":[{{"amount":100000000000,"address":"{ADDRESS}"}}],
+                "account_index":0,"subaddr_indices":[0],"priority":0,
+                "get_tx_hex":false,"do_not_relay":true}}"#
+        );
+        round_trip::<Transfer>(&transfer);
+        round_trip::<TransferSplit>(&transfer);
+        round_trip::<DescribeTransfer>(r#"{"multisig_txset":"4d6f6e65726f206d756c7469736967..."}"#);
+        round_trip::<SignMultisig>(r#"{"tx_data_hex":"4d6f6e65726f206d756c7469736967..."}"#);
+        round_trip::<SubmitMultisig>(r#"{"tx_data_hex":"4d6f6e65726f206d756c7469736967..."}"#);
+        round_trip::<SignTransfer>(r#"{"unsigned_txset":"4d6f6e65726f20756e7369676e6564..."}"#);
+        round_trip::<SubmitTransfer>(r#"{"tx_data_hex":"4d6f6e65726f207369676e6564..."}"#);
+    }
+
+    #[test]
+    fn test_history_requests_round_trip() {
+        round_trip::<GetBalance>(r#"{"account_index":0,"address_indices":[0,1]}"#);
+        round_trip::<GetBalance>(r#"{"all_accounts":true}"#);
+        round_trip::<GetTransfers>(r#"{"in":true,"account_index":1}"#);
+        round_trip::<GetTransfers>(r#"{"pool":true,"all_accounts":true}"#);
+        round_trip::<GetTransfers>(
+            r#"{"in":true,"out":true,"pending":true,"failed":true,"pool":true,
+                "account_index":0,"subaddr_indices":[1,2]}"#,
+        );
+        round_trip::<IncomingTransfers>(
+            r#"{"transfer_type":"all","account_index":0,"subaddr_indices":[3]}"#,
+        );
+        round_trip::<IncomingTransfers>(r#"{"transfer_type":"available","account_index":1}"#);
+        round_trip::<GetTransferByTxid>(&format!(r#"{{"txid":"{TXID}"}}"#));
+        round_trip::<GetTransferByTxid>(&format!(r#"{{"txid":"{TXID}","account_index":1}}"#));
+        round_trip::<GetTxNotes>(&format!(r#"{{"txids":["{TXID}"]}}"#));
+        round_trip::<SetTxNotes>(&format!(r#"{{"txids":["{TXID}"],"notes":["rent"]}}"#));
+        round_trip::<GetSpendProof>(&format!(r#"{{"txid":"{TXID}","message":"payment"}}"#));
+        round_trip::<CheckSpendProof>(&format!(
+            r#"{{"txid":"{TXID}","message":"payment",
+                "signature":"SpendProofV1aSh8Todhk54736iXgV6vJAFP7egxByuMWZeyhDGPqBhYvkd..."}}"#
+        ));
+    }
+
+    #[test]
+    fn test_daemon_requests_round_trip() {
+        round_trip::<GetVersion>("{}");
+        round_trip::<GetInfo>("{}");
+        round_trip::<GetBlockHeaderByHeight>(r#"{"height":912345}"#);
+        round_trip::<HardForkInfo>("{}");
+        round_trip::<GetFeeEstimate>("{}");
+        round_trip::<GetFeeEstimate>(r#"{"grace_blocks":10}"#);
+    }
+}
@@ -0,0 +1,120 @@
+//! Wrapper for passwords and keys held in memory.
+//!
+//! A [`Secret`] zeroizes its contents when dropped and prints as
+//! `<redacted>` through both `Debug` and `Display`, so a secret that ends up
+//! in a log line, an error or a `{:?}` of its containing struct shows nothing.
+//! It deserializes like the value it wraps but has no `Serialize` impl: a
+//! struct that has to write one out says so with
+//! `#[serde(serialize_with = "crate::secret::serialize")]`. Everywhere else
+//! the value is reached through [`Secret::expose`], which keeps the places a
+//! secret leaves the wrapper easy to find.
+
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serializer};
+use zeroize::Zeroize;
+
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Secret(value)
+    }
+
+    /// The wrapped value. Don't copy it anywhere that outlives the call.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl Secret<String> {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Secret(value)
+    }
+}
+
+impl<T: Zeroize + Clone> Clone for Secret<T> {
+    fn clone(&self) -> Self {
+        Secret(self.0.clone())
+    }
+}
+
+impl<T: Zeroize + Default> Default for Secret<T> {
+    fn default() -> Self {
+        Secret(T::default())
+    }
+}
+
+impl<T: Zeroize> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl<T: Zeroize> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl<'de, T: Zeroize + Deserialize<'de>> Deserialize<'de> for Secret<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Secret)
+    }
+}
+
+/// Write a secret's value, for `#[serde(serialize_with = "...")]` on fields
+/// that really have to be serialized.
+pub fn serialize<S: Serializer>(secret: &Secret<String>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(secret.expose())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secrets_never_print() {
+        let rpc = crate::config::DaemonRpc {
+            username: Some("monitor".into()),
+            password: Some(Secret::new("hunter2".to_string())),
+            ..crate::config::DaemonRpc::default()
+        };
+        let printed = format!("{rpc:?} {:?}", rpc.password);
+        assert!(!printed.contains("hunter2"));
+        assert!(printed.contains("<redacted>"));
+        assert_eq!(rpc.password.as_ref().unwrap().to_string(), "<redacted>");
+
+        let config: crate::config::Config = serde_json::from_str(
+            r#"{"network":"stagenet","data_dir":"/tmp/x","daemon":{"password":"hunter2"}}"#,
+        )
+        .unwrap();
+        assert!(!format!("{config:?}").contains("hunter2"));
+        assert!(!serde_json::to_string(&config).unwrap().contains("hunter2"));
+    }
+
+    #[test]
+    fn test_explicit_serialization() {
+        #[derive(serde::Serialize)]
+        struct Export {
+            #[serde(serialize_with = "serialize")]
+            key: Secret<String>,
+        }
+        let export = Export {
+            key: Secret::new("abcd".into()),
+        };
+        assert_eq!(serde_json::to_string(&export).unwrap(), r#"{"key":"abcd"}"#);
+    }
+}
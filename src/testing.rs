@@ -0,0 +1,196 @@
+//! Helpers shared by unit tests.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::config::DaemonRpc;
+
+/// Serve `body` as the HTTP response to every request, counting hits.
+pub async fn stub_daemon(body: &'static str) -> (DaemonRpc, Arc<AtomicUsize>) {
+    typed_stub_daemon("application/json", body).await
+}
+
+/// [`stub_daemon`] with another `Content-Type`, like a web server or proxy
+/// answering on the port.
+pub async fn typed_stub_daemon(
+    content_type: &'static str,
+    body: &'static str,
+) -> (DaemonRpc, Arc<AtomicUsize>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+    let daemon = DaemonRpc {
+        port,
+        ..DaemonRpc::default()
+    };
+    (daemon, hits)
+}
+
+/// Answer each JSON-RPC request with a result holding the request's own
+/// `id`, so tests can see which ids were sent. Batches are answered in
+/// reverse order, and a call to `t_fail` gets an error.
+pub async fn echo_daemon() -> DaemonRpc {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let request: serde_json::Value =
+                    serde_json::from_slice(&read_request(&mut stream).await).unwrap_or_default();
+                let body = match request.as_array() {
+                    Some(calls) => calls.iter().rev().map(echo).collect(),
+                    None => echo(&request),
+                }
+                .to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    DaemonRpc {
+        port,
+        ..DaemonRpc::default()
+    }
+}
+
+/// Serve `body` to every request after `delay`, handling requests in
+/// parallel and recording the most that were ever in flight at once.
+pub async fn slow_daemon(body: &'static str, delay: Duration) -> (DaemonRpc, Arc<AtomicUsize>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let max_seen = peak.clone();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let (in_flight, peak) = (in_flight.clone(), peak.clone());
+            tokio::spawn(async move {
+                read_request(&mut stream).await;
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(delay).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    let daemon = DaemonRpc {
+        port,
+        ..DaemonRpc::default()
+    };
+    (daemon, max_seen)
+}
+
+/// Answer each call to a method in `answers` with its result, given as
+/// JSON text, and anything else with "Method not found", like a wallet RPC
+/// that predates it.
+pub async fn method_daemon(answers: &'static [(&'static str, &'static str)]) -> DaemonRpc {
+    handler_daemon(move |call| {
+        let answer = answers.iter().find(|(method, _)| call["method"] == *method);
+        match answer {
+            Some((_, result)) => Ok(serde_json::from_str(result).unwrap()),
+            None => Err((-32601, "Method not found".into())),
+        }
+    })
+    .await
+}
+
+/// Answer each JSON-RPC call with what `handler` makes of it: a result, or
+/// an error code and message.
+pub async fn handler_daemon<F>(handler: F) -> DaemonRpc
+where
+    F: Fn(&serde_json::Value) -> Result<serde_json::Value, (i64, String)> + Send + Sync + 'static,
+{
+    let handler = Arc::new(handler);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let handler = Arc::clone(&handler);
+            tokio::spawn(async move {
+                let call: serde_json::Value =
+                    serde_json::from_slice(&read_request(&mut stream).await).unwrap_or_default();
+                let body = match handler(&call) {
+                    Ok(result) => serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": call["id"],
+                        "result": result,
+                    }),
+                    Err((code, message)) => serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": call["id"],
+                        "error": {"code": code, "message": message},
+                    }),
+                }
+                .to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    DaemonRpc {
+        port,
+        ..DaemonRpc::default()
+    }
+}
+
+fn echo(call: &serde_json::Value) -> serde_json::Value {
+    let id = &call["id"];
+    if call["method"] == "t_fail" {
+        serde_json::json!({"jsonrpc": "2.0", "id": id, "error": {"code": -1, "message": "failed"}})
+    } else {
+        serde_json::json!({"jsonrpc": "2.0", "id": id, "result": {"id": id, "method": call["method"]}})
+    }
+}
+
+/// The body of one HTTP request, read up to its `Content-Length`.
+async fn read_request(stream: &mut tokio::net::TcpStream) -> Vec<u8> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            let headers = String::from_utf8_lossy(&buf[..end]).to_ascii_lowercase();
+            let length = headers
+                .lines()
+                .find_map(|l| l.strip_prefix("content-length:"))
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            if buf.len() >= end + 4 + length {
+                return buf[end + 4..end + 4 + length].to_vec();
+            }
+        }
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return Vec::new(),
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+        }
+    }
+}
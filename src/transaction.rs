@@ -1,19 +1,107 @@
-use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
-use crate::config::RpcClient;
+use crate::address::{self, AddressKind};
+use crate::config::{Network, RpcClient, RpcError};
+use crate::error::{MultisigError, RejectionReason, Result, TransactionError};
+use crate::rpc::methods::{
+    DescribeTransfer, DescribeTransferRequest, ExportMultisigInfo, ExportMultisigInfoRequest,
+    Freeze, GetBalance, GetBalanceRequest, GetBalanceResponse, GetFeeEstimate,
+    GetFeeEstimateRequest, GetTransferByTxid, GetTransferByTxidRequest, GetTransfers,
+    GetTransfersRequest, GetTxNotes, GetTxNotesRequest, HardForkInfo, HardForkInfoRequest,
+    ImportMultisigInfo, ImportMultisigInfoRequest, IncomingTransfers, IncomingTransfersRequest,
+    KeyImageRequest, SetTxNotes, SetTxNotesRequest, SignMultisig, SignMultisigRequest,
+    SignTransfer, SignTransferRequest, SubmitMultisig, SubmitMultisigRequest, SubmitTransfer,
+    SubmitTransferRequest, Thaw, Transfer, TransferRecord, TransferRequest, TransferSplit,
+    TransferType,
+};
+use crate::rpc::RpcMethod;
 
-/// A destination for an outgoing transfer.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A destination for an outgoing transfer: an address checked when the
+/// destination is made, and an amount. Build one with [`Destination::new`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "DestinationFields")]
 pub struct Destination {
+    address: String,
+    amount: Amount,
+    #[serde(skip)]
+    kind: Option<AddressKind>,
+}
+
+#[derive(Deserialize)]
+struct DestinationFields {
+    address: String,
+    amount: Amount,
+}
+
+impl From<DestinationFields> for Destination {
+    fn from(fields: DestinationFields) -> Self {
+        Destination::new_unchecked(fields.address, fields.amount)
+    }
+}
+
+impl Destination {
+    /// Pay `amount` to `address`, which must be a valid address of
+    /// `network`: its base58, checksum and network tag are all checked.
+    pub fn new(
+        address: &str,
+        amount: Amount,
+        network: Network,
+    ) -> std::result::Result<Destination, TransactionError> {
+        let kind = check_address(address, network)?;
+        Ok(Destination {
+            address: address.to_string(),
+            amount,
+            kind: Some(kind),
+        })
+    }
+
+    /// Pay `amount` to `address` without checking it, for tests and for
+    /// addresses the wallet RPC reported itself. [`Destination::kind`] is
+    /// still filled in if the address decodes.
+    pub fn new_unchecked(address: impl Into<String>, amount: Amount) -> Destination {
+        let address = address.into();
+        let kind = address::decode(&address).ok().map(|(_, kind)| kind);
+        Destination {
+            address,
+            amount,
+            kind,
+        }
+    }
+
     /// Recipient Monero address.
-    pub address: String,
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
     /// Amount in atomic units (1 XMR = 1e12 piconero).
-    pub amount: u64,
+    pub fn amount(&self) -> Amount {
+        self.amount
+    }
+
+    /// The kind of address paid, or `None` for an unchecked destination
+    /// whose address doesn't decode.
+    pub fn kind(&self) -> Option<AddressKind> {
+        self.kind
+    }
+}
+
+/// Checks the address as [`Destination::new`] does, except for its
+/// network, which can be any.
+impl TryFrom<(String, u64)> for Destination {
+    type Error = TransactionError;
+
+    fn try_from((address, amount): (String, u64)) -> std::result::Result<Self, Self::Error> {
+        let (_, kind) = address::decode(&address)?;
+        Ok(Destination {
+            address,
+            amount: Amount(amount),
+            kind: Some(kind),
+        })
+    }
 }
 
 /// Priority level for transaction fee estimation.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Priority {
     Default = 0,
@@ -22,15 +110,68 @@ pub enum Priority {
     High = 3,
 }
 
+impl Priority {
+    /// The level to rebuild at when the fee was too low, or `None` at the
+    /// top. The wallet's default is low unless the pool is backed up, so it
+    /// moves straight to medium.
+    pub fn next(self) -> Option<Priority> {
+        match self {
+            Priority::Default | Priority::Low => Some(Priority::Medium),
+            Priority::Medium => Some(Priority::High),
+            Priority::High => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Priority::Default => "default",
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+        };
+        f.write_str(name)
+    }
+}
+
 /// An unsigned multisig transaction awaiting co-signatures.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UnsignedMultisigTx {
     /// Hex-encoded unsigned transaction data from the wallet RPC.
     pub tx_data_hex: String,
-    /// Transaction hash (available after construction).
-    pub tx_hash: String,
-    /// Fee in atomic units.
-    pub fee: u64,
+    /// Transaction hashes (available after construction). More than one
+    /// when the transfer was split.
+    pub tx_hashes: Vec<String>,
+    /// Total fee in atomic units.
+    pub fee: Amount,
+    /// Total weight, which the fee is charged on. Above the byte size only
+    /// for transactions with more than two outputs.
+    pub weight: u64,
+    /// Total size in bytes; the weight when the wallet RPC doesn't report it.
+    pub size_bytes: u64,
+    /// Key images of the inputs spent; empty when the wallet RPC doesn't
+    /// report them.
+    pub spent_key_images: Vec<String>,
+}
+
+/// Weight limit of a single transaction: half the full reward zone less the
+/// coinbase reserve. Daemons won't relay anything heavier.
+pub const MAX_TX_WEIGHT: u64 = 149_400;
+
+/// Share of [`MAX_TX_WEIGHT`], in percent, past which a build is warned.
+pub const WEIGHT_WARN_PERCENT: u64 = 90;
+
+/// Fee rate in piconero per byte of weight, rounded down; `None` without a
+/// weight.
+pub fn fee_per_byte(fee: Amount, weight: u64) -> Option<u64> {
+    (weight > 0).then(|| fee.0 / weight)
+}
+
+/// Whether a transaction of `weight` is close enough to [`MAX_TX_WEIGHT`]
+/// to warn about.
+pub fn near_weight_limit(weight: u64) -> bool {
+    weight.saturating_mul(100) >= MAX_TX_WEIGHT * WEIGHT_WARN_PERCENT
 }
 
 /// A partially signed multisig transaction.
@@ -49,59 +190,168 @@ pub struct PartiallySignedTx {
 /// The result of submitting a fully signed transaction to the network.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SubmitResult {
-    /// Transaction hash as confirmed by the daemon.
-    pub tx_hash: String,
+    /// Hashes of the transactions sent, as the wallet RPC reports them; more
+    /// than one when the set was split.
+    pub tx_hashes: Vec<String>,
 }
 
-// ── Wallet RPC response types ───────────────────────────────────────────────
+// ── Public API ──────────────────────────────────────────────────────────────
 
-#[derive(Debug, Deserialize)]
-struct TransferResponse {
-    tx_hash: String,
-    fee: u64,
-    multisig_txset: String,
+/// Export this wallet's partial key images so co-signers can see the correct
+/// balance. Must be called (and results shared) before building transactions.
+pub async fn export_multisig_info(rpc: &RpcClient) -> Result<String> {
+    let resp = rpc
+        .invoke::<ExportMultisigInfo>(ExportMultisigInfoRequest {})
+        .await?;
+
+    Ok(resp.info)
 }
 
-#[derive(Debug, Deserialize)]
-struct SignMultisigResponse {
-    tx_hash_list: Vec<String>,
-    tx_data_hex: String,
+/// Start of what `export_multisig_info` returns, before the part encrypted
+/// to the co-signers.
+const MULTISIG_EXPORT_MAGIC: &[u8] = b"Monero multisig export";
+
+/// Whether `blob` is hex of `export_multisig_info` output. Only the magic is
+/// decoded; the rest is encrypted and says nothing about when it was made.
+pub fn is_multisig_export(blob: &str) -> bool {
+    blob.trim()
+        .get(..MULTISIG_EXPORT_MAGIC.len() * 2)
+        .and_then(|prefix| hex::decode(prefix).ok())
+        .is_some_and(|prefix| prefix == MULTISIG_EXPORT_MAGIC)
 }
 
-#[derive(Debug, Deserialize)]
-struct SubmitMultisigResponse {
-    tx_hash_list: Vec<String>,
+/// How many blocks an export's recorded height may trail the importing
+/// wallet before the import warns: about a day.
+pub const STALE_EXPORT_BLOCKS: u64 = 720;
+
+/// Per-blob outcome of [`import_multisig_info`]. Blobs are identified by
+/// their [`fingerprint`](crate::utils::fingerprint).
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Fingerprints of blobs the wallet accepted.
+    pub succeeded: Vec<String>,
+    /// Fingerprints of rejected blobs, with the reason.
+    pub failed: Vec<(String, MultisigError)>,
+    /// Number of outputs the wallet updated, as reported by the wallet RPC.
+    pub n_outputs: u64,
 }
 
-#[derive(Debug, Deserialize)]
-struct ExportMultisigInfoResponse {
-    info: String,
+impl ImportReport {
+    pub fn all_succeeded(&self) -> bool {
+        self.failed.is_empty()
+    }
 }
 
-// ── Public API ──────────────────────────────────────────────────────────────
+/// Check that a blob looks like `export_multisig_info` output: non-empty,
+/// even-length hex.
+fn validate_multisig_info(blob: &str) -> Result<()> {
+    if blob.is_empty() {
+        return Err(TransactionError::InvalidMultisigInfo("blob is empty".into()).into());
+    }
+    if !blob.len().is_multiple_of(2) || !blob.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(TransactionError::InvalidMultisigInfo("blob is not hex".into()).into());
+    }
+    Ok(())
+}
 
-/// Export this wallet's partial key images so co-signers can see the correct
-/// balance. Must be called (and results shared) before building transactions.
-pub async fn export_multisig_info(rpc: &RpcClient) -> Result<String> {
-    let resp: ExportMultisigInfoResponse = rpc
-        .request("export_multisig_info", &serde_json::json!({}))
-        .await
-        .context("export_multisig_info RPC call failed")?;
+/// Import partial key images from co-signers to synchronize balance state.
+///
+/// Every blob is validated and de-duplicated first, so one malformed blob
+/// can't stop the others. The valid ones go to the wallet in a single call;
+/// if the wallet rejects that batch, each blob is retried on its own so the
+/// culprit is identified and the rest still get imported. Transport failures
+/// are returned as an error since nothing can be imported without the wallet.
+pub async fn import_multisig_info(rpc: &RpcClient, info: &[String]) -> Result<ImportReport> {
+    let mut report = ImportReport::default();
+    let mut valid: Vec<(&str, String)> = Vec::new();
+    let mut seen = Vec::new();
+    for blob in info {
+        let blob = blob.trim();
+        let fingerprint = crate::utils::fingerprint(blob);
+        if seen.contains(&fingerprint) {
+            report.failed.push((
+                fingerprint,
+                TransactionError::InvalidMultisigInfo("duplicate of an earlier blob".into()).into(),
+            ));
+            continue;
+        }
+        seen.push(fingerprint.clone());
+        match validate_multisig_info(blob) {
+            Ok(()) => valid.push((blob, fingerprint)),
+            Err(e) => report.failed.push((fingerprint, e)),
+        }
+    }
+    if valid.is_empty() {
+        return Ok(report);
+    }
 
-    Ok(resp.info)
+    let batch: Vec<&str> = valid.iter().map(|(blob, _)| *blob).collect();
+    match import_batch(rpc, &batch).await {
+        Ok(n_outputs) => {
+            report.n_outputs = n_outputs;
+            report.succeeded = valid.into_iter().map(|(_, f)| f).collect();
+        }
+        Err(MultisigError::Rpc(RpcError::Server { .. })) if valid.len() > 1 => {
+            for (blob, fingerprint) in valid {
+                match import_batch(rpc, &[blob]).await {
+                    Ok(n_outputs) => {
+                        report.n_outputs += n_outputs;
+                        report.succeeded.push(fingerprint);
+                    }
+                    Err(e @ MultisigError::Rpc(RpcError::Server { .. })) => {
+                        report.failed.push((fingerprint, e));
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        Err(e @ MultisigError::Rpc(RpcError::Server { .. })) => {
+            let (_, fingerprint) = valid.into_iter().next().expect("valid is not empty");
+            report.failed.push((fingerprint, e));
+        }
+        Err(e) => return Err(e),
+    }
+    Ok(report)
 }
 
-/// Import partial key images from co-signers to synchronize balance state.
-pub async fn import_multisig_info(rpc: &RpcClient, info: &[String]) -> Result<()> {
-    let _: serde_json::Value = rpc
-        .request(
-            "import_multisig_info",
-            &serde_json::json!({ "info": info }),
-        )
-        .await
-        .context("import_multisig_info RPC call failed")?;
+async fn import_batch(rpc: &RpcClient, info: &[&str]) -> Result<u64> {
+    let resp = rpc
+        .invoke::<ImportMultisigInfo>(ImportMultisigInfoRequest {
+            info: info.iter().map(|i| i.to_string()).collect(),
+        })
+        .await?;
+    Ok(resp.n_outputs)
+}
 
-    Ok(())
+/// Outputs a single Monero transaction may have (the Bulletproofs+ limit).
+pub const MAX_TX_OUTPUTS: usize = 16;
+
+/// Destinations that fit in one transaction next to its change output.
+pub const MAX_DESTINATIONS: usize = MAX_TX_OUTPUTS - 1;
+
+/// Addresses paid more than once, in first-seen order.
+pub fn duplicate_destinations(destinations: &[Destination]) -> Vec<String> {
+    let mut duplicates: Vec<String> = Vec::new();
+    for (i, d) in destinations.iter().enumerate() {
+        let repeated = destinations[..i].iter().any(|e| e.address == d.address);
+        if repeated && !duplicates.contains(&d.address) {
+            duplicates.push(d.address.clone());
+        }
+    }
+    duplicates
+}
+
+/// Combine destinations paying the same address into one, in first-seen
+/// order.
+pub fn merge_duplicates(destinations: &[Destination]) -> Result<Vec<Destination>> {
+    let mut merged: Vec<Destination> = Vec::new();
+    for d in destinations {
+        match merged.iter_mut().find(|m| m.address == d.address) {
+            Some(m) => m.amount = m.amount.checked_add(d.amount)?,
+            None => merged.push(d.clone()),
+        }
+    }
+    Ok(merged)
 }
 
 /// Build an unsigned multisig transaction.
@@ -109,191 +359,2579 @@ pub async fn import_multisig_info(rpc: &RpcClient, info: &[String]) -> Result<()
 /// Requires that multisig info has been exchanged between all participants via
 /// [`export_multisig_info`] / [`import_multisig_info`] so the wallet has an
 /// accurate view of the available balance.
+///
+/// Addresses were checked when the destinations were made (see
+/// [`Destination::new`]) and are not checked again here.
+///
+/// Duplicate addresses are refused (see [`merge_duplicates`]). More than
+/// [`MAX_DESTINATIONS`] are refused too, unless `allow_split` is set, in
+/// which case the wallet spreads them over several transactions with
+/// `transfer_split`, if the wallet RPC has it. All of this is checked
+/// before the RPC is called, since the wallet's own errors are opaque.
+///
+/// The inputs come from `source`; check it exists first with
+/// [`crate::wallet::check_account`]. `fee_estimate`, when known, is added to
+/// the amount needed in the error for a balance that falls short.
 pub async fn build_unsigned_tx(
     rpc: &RpcClient,
     destinations: &[Destination],
     priority: Priority,
+    allow_split: bool,
+    source: &Source,
+    fee_estimate: Option<Amount>,
 ) -> Result<UnsignedMultisigTx> {
-    let dest_params: Vec<_> = destinations
-        .iter()
-        .map(|d| {
-            serde_json::json!({
-                "address": d.address,
-                "amount": d.amount,
-            })
-        })
-        .collect();
+    let duplicates = duplicate_destinations(destinations);
+    if !duplicates.is_empty() {
+        return Err(TransactionError::DuplicateDestinations(duplicates).into());
+    }
+    let split = destinations.len() > MAX_DESTINATIONS;
+    if split && !allow_split {
+        return Err(TransactionError::TooManyDestinations {
+            count: destinations.len(),
+            max: MAX_DESTINATIONS,
+        }
+        .into());
+    }
 
-    let resp: TransferResponse = rpc
-        .request(
-            "transfer",
-            &serde_json::json!({
-                "destinations": dest_params,
-                "priority": priority as u32,
-                "get_tx_hex": false,
-                "do_not_relay": true,
-            }),
-        )
-        .await
-        .context("transfer RPC call failed")?;
+    let params = transfer_params(destinations, priority, source);
+    if split {
+        let capabilities = rpc.capabilities().await?;
+        if !capabilities.supports_transfer_split {
+            return Err(RpcError::Unsupported {
+                method: TransferSplit::NAME.to_string(),
+                version: capabilities.version,
+            }
+            .into());
+        }
+        let resp = match rpc.invoke::<TransferSplit>(params).await {
+            Ok(resp) => resp,
+            Err(e) => return Err(transfer_error(rpc, e, destinations, source, fee_estimate).await),
+        };
+        let weight = resp.weight_list.iter().sum();
+        return Ok(UnsignedMultisigTx {
+            tx_data_hex: resp.multisig_txset,
+            tx_hashes: resp.tx_hash_list,
+            fee: Amount::checked_sum(resp.fee_list.into_iter().map(Amount))?,
+            weight,
+            size_bytes: resp.tx_size_list.map_or(weight, |s| s.iter().sum()),
+            spent_key_images: resp
+                .spent_key_images_list
+                .into_iter()
+                .flat_map(|k| k.key_images)
+                .collect(),
+        });
+    }
 
+    let resp = match rpc.invoke::<Transfer>(params).await {
+        Ok(resp) => resp,
+        Err(e) => return Err(transfer_error(rpc, e, destinations, source, fee_estimate).await),
+    };
     Ok(UnsignedMultisigTx {
         tx_data_hex: resp.multisig_txset,
-        tx_hash: resp.tx_hash,
-        fee: resp.fee,
+        tx_hashes: vec![resp.tx_hash],
+        fee: Amount(resp.fee),
+        weight: resp.weight,
+        size_bytes: resp.tx_size.unwrap_or(resp.weight),
+        spent_key_images: resp.spent_key_images.key_images,
     })
 }
 
+/// Keep the wallet from spending the outputs with `key_images` until they
+/// are [thawed](thaw_outputs), so the next build picks other inputs. Stops
+/// at the first failure; the ones frozen so far are returned either way, for
+/// thawing.
+pub async fn freeze_outputs(
+    rpc: &RpcClient,
+    key_images: &[String],
+    frozen: &mut Vec<String>,
+) -> Result<()> {
+    for key_image in key_images {
+        rpc.invoke::<Freeze>(KeyImageRequest {
+            key_image: key_image.clone(),
+        })
+        .await?;
+        frozen.push(key_image.clone());
+    }
+    Ok(())
+}
+
+/// Let the wallet spend outputs [frozen](freeze_outputs) earlier again.
+/// Tries every one, and fails with the first error.
+pub async fn thaw_outputs(rpc: &RpcClient, key_images: &[String]) -> Result<()> {
+    let mut first_error = None;
+    for key_image in key_images {
+        let thawed = rpc
+            .invoke::<Thaw>(KeyImageRequest {
+                key_image: key_image.clone(),
+            })
+            .await;
+        if let Err(e) = thawed {
+            first_error.get_or_insert(e);
+        }
+    }
+    first_error.map_or(Ok(()), |e| Err(e.into()))
+}
+
+/// Wallet RPC error code for a destination it can't parse.
+const WRONG_ADDRESS: i64 = -2;
+/// Wallet RPC error code for a balance too small for the transfer.
+const NOT_ENOUGH_MONEY: i64 = -17;
+
+/// Map a `transfer` failure to [`TransactionError::InvalidAddress`],
+/// [`TransactionError::Locked`] or [`TransactionError::InsufficientBalance`]
+/// when it is one of those, so scripts can tell them apart from other
+/// failures. For the latter two, `need` is the amounts plus `fee_estimate`,
+/// and `have` the unlocked balance of the account spent from.
+async fn transfer_error(
+    rpc: &RpcClient,
+    err: RpcError,
+    destinations: &[Destination],
+    source: &Source,
+    fee_estimate: Option<Amount>,
+) -> MultisigError {
+    let Some(message) = err.message().map(str::to_lowercase) else {
+        return err.into();
+    };
+    if err.code() == Some(WRONG_ADDRESS) || message.contains("wrong_address") {
+        let detail = err.message().unwrap_or_default();
+        let address = detail.rsplit_once(": ").map_or(detail, |(_, a)| a);
+        return TransactionError::InvalidAddress(address.to_string()).into();
+    }
+    if err.code() == Some(NOT_ENOUGH_MONEY) || message.contains("not enough") {
+        let amounts = destinations.iter().map(|d| d.amount).chain(fee_estimate);
+        let need = match Amount::checked_sum(amounts) {
+            Ok(need) => need.0,
+            Err(e) => return e.into(),
+        };
+        let balance = match get_balance(rpc, source.account).await {
+            Ok(balance) => balance,
+            Err(_) => return err.into(),
+        };
+        // The wallet says "not enough unlocked money" whether or not the
+        // locked part would cover it, so tell waiting from topping up.
+        if balance.balance.0 >= need && balance.locked.0 > 0 {
+            return TransactionError::Locked {
+                need,
+                unlocked: balance.unlocked_balance.0,
+                // Zero when the wallet RPC doesn't say.
+                blocks_to_unlock: (balance.blocks_to_unlock > 0)
+                    .then_some(balance.blocks_to_unlock),
+            }
+            .into();
+        }
+        let have = balance.unlocked_balance.0;
+        return TransactionError::InsufficientBalance { need, have }.into();
+    }
+    err.into()
+}
+
+/// Parameters for `transfer` and `transfer_split`. A multisig set is never relayed from here, and
+/// the raw tx hex is never requested: the wallet RPC would pull it through
+/// its daemon, which may be an untrusted remote node.
+fn transfer_params(
+    destinations: &[Destination],
+    priority: Priority,
+    source: &Source,
+) -> TransferRequest {
+    TransferRequest {
+        destinations: destinations
+            .iter()
+            .map(|d| Recipient {
+                address: d.address.clone(),
+                amount: d.amount.0,
+            })
+            .collect(),
+        account_index: source.account,
+        subaddr_indices: source.subaddresses.clone(),
+        priority: priority as u32,
+        get_tx_hex: false,
+        do_not_relay: true,
+    }
+}
+
+/// Where a transaction's inputs come from: an account, and optionally only
+/// some of its subaddresses. Empty `subaddresses` lets the wallet pick from
+/// the whole account.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Source {
+    pub account: u32,
+    pub subaddresses: Vec<u32>,
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "account {}", self.account)?;
+        if !self.subaddresses.is_empty() {
+            let list: Vec<String> = self.subaddresses.iter().map(u32::to_string).collect();
+            write!(f, ", subaddresses {}", list.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// Per-byte fee from the daemon's `get_fee_estimate`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    /// Piconero per byte; per kilobyte before hard fork 8.
+    pub fee: u64,
+    #[serde(default)]
+    pub quantization_mask: u64,
+    /// Piconero per byte for each priority from low up, from hard fork 15
+    /// on. Empty from older daemons.
+    #[serde(default)]
+    pub fees: Vec<u64>,
+    /// The daemon flags itself as untrusted, so the estimate may be
+    /// inflated and should be checked against another node.
+    #[serde(default)]
+    pub untrusted: bool,
+}
+
+/// Ask the daemon for its current fee estimate.
+pub async fn get_fee_estimate(daemon: &RpcClient) -> Result<FeeEstimate> {
+    Ok(daemon
+        .invoke::<GetFeeEstimate>(GetFeeEstimateRequest::default())
+        .await?)
+}
+
+/// How a [`FeeEstimate`] turns into a fee, which changed with hard forks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeAlgorithm {
+    /// Before hard fork 8: `fee` is per started kilobyte of size.
+    PerKilobyte,
+    /// Hard forks 8 to 14: `fee` is per byte of weight, times a fixed
+    /// multiplier for the priority.
+    PerByte,
+    /// From hard fork 15: the daemon quotes a per-byte fee for each priority.
+    PerPriority,
+}
+
+/// Weight of a typical 2-input, 2-output transaction with CLSAG rings and
+/// Bulletproofs+. Multisig transactions are no bigger than others.
+pub const TYPICAL_TX_WEIGHT: u64 = 1_550;
+
+/// Multipliers wallets applied per priority, from low up, before the daemon
+/// quoted fees itself.
+const PER_BYTE_MULTIPLIERS: [u64; 3] = [1, 5, 25];
+const PER_KILOBYTE_MULTIPLIERS: [u64; 3] = [1, 4, 20];
+
+/// The daemon's fee estimate and what it means for each [`Priority`].
+#[derive(Debug, Clone)]
+pub struct FeeSchedule {
+    pub estimate: FeeEstimate,
+    /// `None` when the daemon wouldn't say, in which case the algorithm is
+    /// guessed from the estimate.
+    pub hard_fork: Option<u8>,
+    pub algorithm: FeeAlgorithm,
+}
+
+impl FeeSchedule {
+    /// Work out the algorithm from the estimate and the hard fork version.
+    /// An estimate with per-priority fees is current whatever the version;
+    /// without one, an unknown version is taken to be per byte.
+    pub fn new(estimate: FeeEstimate, hard_fork: Option<u8>) -> Self {
+        let algorithm = match hard_fork {
+            _ if !estimate.fees.is_empty() => FeeAlgorithm::PerPriority,
+            Some(version) if version < 8 => FeeAlgorithm::PerKilobyte,
+            _ => FeeAlgorithm::PerByte,
+        };
+        FeeSchedule {
+            estimate,
+            hard_fork,
+            algorithm,
+        }
+    }
+
+    /// The rate `priority` pays: piconero per byte, or per kilobyte with
+    /// [`FeeAlgorithm::PerKilobyte`]. The wallet's default is low unless the
+    /// pool is backed up, so it is quoted as low.
+    pub fn rate(&self, priority: Priority) -> u64 {
+        let level = match priority {
+            Priority::Default | Priority::Low => 0,
+            Priority::Medium => 1,
+            Priority::High => 2,
+        };
+        let base = self.estimate.fee;
+        match self.algorithm {
+            FeeAlgorithm::PerPriority => match self.estimate.fees.get(level) {
+                Some(&rate) => rate,
+                None => base.saturating_mul(PER_BYTE_MULTIPLIERS[level]),
+            },
+            FeeAlgorithm::PerByte => base.saturating_mul(PER_BYTE_MULTIPLIERS[level]),
+            FeeAlgorithm::PerKilobyte => base.saturating_mul(PER_KILOBYTE_MULTIPLIERS[level]),
+        }
+    }
+
+    /// The fee for a transaction of `weight` at `priority`, rounded up to
+    /// the quantization mask as wallets do.
+    pub fn fee(&self, priority: Priority, weight: u64) -> Amount {
+        let rate = self.rate(priority);
+        if self.algorithm == FeeAlgorithm::PerKilobyte {
+            return Amount(rate.saturating_mul(weight.div_ceil(1024)));
+        }
+        let fee = rate.saturating_mul(weight);
+        let mask = self.estimate.quantization_mask.max(1);
+        Amount(fee.div_ceil(mask).saturating_mul(mask))
+    }
+}
+
+/// [`get_fee_estimate`] with the hard fork version from `hard_fork_info`,
+/// which old or restricted daemons may not answer.
+pub async fn fee_schedule(daemon: &RpcClient) -> Result<FeeSchedule> {
+    let hard_fork = async {
+        let info = daemon
+            .invoke::<HardForkInfo>(HardForkInfoRequest {})
+            .await?;
+        Ok::<_, MultisigError>(info)
+    };
+    let (estimate, hard_fork) = tokio::join!(get_fee_estimate(daemon), hard_fork);
+    let hard_fork = match hard_fork {
+        Ok(info) => Some(info.version),
+        Err(e) => {
+            tracing::debug!(error = %e, "could not fetch the hard fork version");
+            None
+        }
+    };
+    Ok(FeeSchedule::new(estimate?, hard_fork))
+}
+
 /// Apply this participant's signature to a multisig transaction set.
 ///
 /// Each co-signer calls this with the same `tx_data_hex` received from the
 /// transaction builder. Once enough signatures are collected, the transaction
 /// can be submitted.
-pub async fn sign_multisig_tx(
-    rpc: &RpcClient,
-    tx_data_hex: &str,
-) -> Result<PartiallySignedTx> {
-    let resp: SignMultisigResponse = rpc
-        .request(
-            "sign_multisig",
-            &serde_json::json!({
-                "tx_data_hex": tx_data_hex,
-            }),
-        )
-        .await
-        .context("sign_multisig RPC call failed")?;
+pub async fn sign_multisig_tx(rpc: &RpcClient, tx_data_hex: &str) -> Result<PartiallySignedTx> {
+    let resp = rpc
+        .invoke::<SignMultisig>(SignMultisigRequest {
+            tx_data_hex: tx_data_hex.to_string(),
+        })
+        .await?;
 
-    let tx_hash = resp
-        .tx_hash_list
-        .into_iter()
-        .next()
-        .unwrap_or_default();
+    let tx_hash = resp.tx_hash_list.into_iter().next().unwrap_or_default();
 
     Ok(PartiallySignedTx {
         tx_data_hex: resp.tx_data_hex,
         tx_hash,
-        signatures_count: 0,  // actual count tracked externally
+        signatures_count: 0, // actual count tracked externally
         signatures_required: 0,
     })
 }
 
+/// How many [`sign_many`] requests are in flight at once.
+pub const SIGN_CONCURRENCY: usize = 4;
+
+/// Sign several tx sets, at most [`SIGN_CONCURRENCY`] at a time. Results are
+/// in the same order as `sets`; one failure doesn't stop the others. After
+/// Ctrl+C, signings already in flight finish and the rest report
+/// [`MultisigError::Interrupted`].
+pub async fn sign_many(rpc: &RpcClient, sets: &[String]) -> Vec<Result<PartiallySignedTx>> {
+    let permits = std::sync::Arc::new(tokio::sync::Semaphore::new(SIGN_CONCURRENCY));
+    let mut tasks = tokio::task::JoinSet::new();
+    for (i, set) in sets.iter().enumerate() {
+        let rpc = rpc.clone();
+        let set = set.clone();
+        let permits = permits.clone();
+        tasks.spawn(async move {
+            let _permit = permits
+                .acquire_owned()
+                .await
+                .expect("semaphore is never closed");
+            if crate::interrupt::requested() {
+                return (i, Err(MultisigError::Interrupted));
+            }
+            (i, sign_multisig_tx(&rpc, &set).await)
+        });
+    }
+
+    let mut results: Vec<Option<Result<PartiallySignedTx>>> = sets.iter().map(|_| None).collect();
+    while let Some(joined) = tasks.join_next().await {
+        let (i, result) = joined.expect("signing task panicked");
+        results[i] = Some(result);
+    }
+    results
+        .into_iter()
+        .map(|r| r.expect("every task reports a result"))
+        .collect()
+}
+
 /// Submit a fully signed multisig transaction to the Monero network.
-pub async fn submit_multisig_tx(
-    rpc: &RpcClient,
-    tx_data_hex: &str,
-) -> Result<SubmitResult> {
-    let resp: SubmitMultisigResponse = rpc
-        .request(
-            "submit_multisig",
-            &serde_json::json!({
-                "tx_data_hex": tx_data_hex,
-            }),
-        )
+pub async fn submit_multisig_tx(rpc: &RpcClient, tx_data_hex: &str) -> Result<SubmitResult> {
+    let resp = rpc
+        .invoke::<SubmitMultisig>(SubmitMultisigRequest {
+            tx_data_hex: tx_data_hex.to_string(),
+        })
         .await
-        .context("submit_multisig RPC call failed")?;
+        .map_err(submit_error)?;
 
-    let tx_hash = resp
-        .tx_hash_list
-        .into_iter()
-        .next()
-        .unwrap_or_default();
+    Ok(SubmitResult {
+        tx_hashes: resp.tx_hash_list,
+    })
+}
 
-    Ok(SubmitResult { tx_hash })
+/// A cold-signing `unsigned_monero_tx` signed by the offline wallet.
+#[derive(Debug, Clone, Serialize)]
+pub struct ColdSignedTx {
+    /// Hex of the `signed_monero_tx` to take back to the view-only wallet.
+    pub signed_txset: String,
+    pub tx_hashes: Vec<String>,
 }
 
-/// Format an atomic-unit amount as a human-readable XMR string.
-pub fn format_xmr(piconero: u64) -> String {
-    let whole = piconero / 1_000_000_000_000;
-    let frac = piconero % 1_000_000_000_000;
-    format!("{whole}.{frac:012}")
+/// Sign an `unsigned_monero_tx` with the (non-multisig) wallet open in the
+/// wallet RPC, through `sign_transfer`.
+pub async fn sign_cold_tx(rpc: &RpcClient, unsigned_hex: &str) -> Result<ColdSignedTx> {
+    expect_cold_signing(unsigned_hex, ColdSigningKind::Unsigned)?;
+    let resp = rpc
+        .invoke::<SignTransfer>(SignTransferRequest {
+            unsigned_txset: unsigned_hex.trim().to_string(),
+        })
+        .await?;
+    Ok(ColdSignedTx {
+        signed_txset: resp.signed_txset,
+        tx_hashes: resp.tx_hash_list,
+    })
 }
 
-// ── Balance queries ─────────────────────────────────────────────────────
+/// Broadcast a `signed_monero_tx` through the view-only wallet that built it,
+/// with `submit_transfer`. Returns the hashes of the submitted transactions.
+pub async fn submit_cold_tx(rpc: &RpcClient, signed_hex: &str) -> Result<Vec<String>> {
+    expect_cold_signing(signed_hex, ColdSigningKind::Signed)?;
+    let resp = rpc
+        .invoke::<SubmitTransfer>(SubmitTransferRequest {
+            tx_data_hex: signed_hex.trim().to_string(),
+        })
+        .await
+        .map_err(submit_error)?;
+    Ok(resp.tx_hash_list)
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Balance {
-    /// Confirmed balance in atomic units.
-    pub balance: u64,
-    /// Unconfirmed (pending) balance in atomic units.
-    pub unlocked_balance: u64,
+fn expect_cold_signing(hex: &str, expected: ColdSigningKind) -> Result<()> {
+    if ColdSigningKind::of_hex(hex) == Some(expected) {
+        Ok(())
+    } else {
+        Err(TransactionError::NotColdSigningFile(expected).into())
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct GetBalanceResponse {
-    balance: u64,
-    unlocked_balance: u64,
+/// Hex of the magic that prefixes a multisig tx set ("Monero multisig"), as
+/// opposed to a raw serialized transaction.
+const MULTISIG_SET_MAGIC_HEX: &str = "4d6f6e65726f206d756c7469736967";
+
+/// Whether `blob` is a multisig tx set rather than a raw transaction.
+pub fn is_multisig_tx_set(blob: &str) -> bool {
+    blob.trim()
+        .get(..MULTISIG_SET_MAGIC_HEX.len())
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(MULTISIG_SET_MAGIC_HEX))
 }
 
-/// Query the wallet's current balance.
-pub async fn get_balance(rpc: &RpcClient) -> Result<Balance> {
-    let resp: GetBalanceResponse = rpc
-        .request(
-            "get_balance",
-            &serde_json::json!({ "account_index": 0 }),
-        )
-        .await
-        .context("get_balance RPC call failed")?;
+/// Start of a multisig tx set file as the Monero CLI and GUI save it
+/// (`multisig_monero_tx`). The wallet RPC's hex tx set already starts with
+/// it, so the file is just that hex decoded.
+const MULTISIG_FILE_MAGIC: &[u8] = b"Monero multisig unsigned tx set\x01";
 
-    Ok(Balance {
-        balance: resp.balance,
-        unlocked_balance: resp.unlocked_balance,
-    })
+/// A file from a normal (non-multisig) wallet's cold-signing workflow: a
+/// view-only wallet builds `unsigned_monero_tx`, an offline wallet signs it
+/// into `signed_monero_tx`, and the view-only wallet submits that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColdSigningKind {
+    Unsigned,
+    Signed,
 }
 
-/// Validate that a Monero address has the expected length and prefix.
-pub fn validate_address(address: &str, network: crate::config::Network) -> Result<()> {
-    let expected_prefix = match network {
-        crate::config::Network::Mainnet => '4',
-        crate::config::Network::Testnet | crate::config::Network::Stagenet => '9',
-    };
+impl ColdSigningKind {
+    const ALL: [ColdSigningKind; 2] = [ColdSigningKind::Unsigned, ColdSigningKind::Signed];
 
-    anyhow::ensure!(
-        address.starts_with(expected_prefix),
-        "address should start with '{expected_prefix}' for {network}"
-    );
-    anyhow::ensure!(
-        address.len() == 95 || address.len() == 106,
-        "invalid address length: {} (expected 95 for standard or 106 for integrated)",
-        address.len()
-    );
+    /// The file name the Monero CLI and GUI wallets save it under.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ColdSigningKind::Unsigned => "unsigned_monero_tx",
+            ColdSigningKind::Signed => "signed_monero_tx",
+        }
+    }
 
-    Ok(())
+    /// What the file holds, for error messages.
+    pub fn describe(self) -> &'static str {
+        match self {
+            ColdSigningKind::Unsigned => {
+                "a transaction a view-only wallet built for an offline wallet to sign"
+            }
+            ColdSigningKind::Signed => {
+                "a transaction an offline wallet signed for a view-only wallet to submit"
+            }
+        }
+    }
+
+    fn magic(self) -> &'static [u8] {
+        match self {
+            ColdSigningKind::Unsigned => b"Monero unsigned tx set",
+            ColdSigningKind::Signed => b"Monero signed tx set",
+        }
+    }
+
+    /// The kind of cold-signing file `bytes` is, if any.
+    pub fn of_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| bytes.starts_with(k.magic()))
+    }
+
+    /// The kind of cold-signing file `hex` encodes, if any. Only the magic
+    /// is decoded.
+    pub fn of_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim();
+        Self::ALL.into_iter().find(|k| {
+            let len = k.magic().len() * 2;
+            hex.get(..len)
+                .and_then(|prefix| hex::decode(prefix).ok())
+                .is_some_and(|prefix| prefix == k.magic())
+        })
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl std::fmt::Display for ColdSigningKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.file_name())
+    }
+}
 
-    #[test]
-    fn test_format_xmr_whole() {
-        assert_eq!(format_xmr(1_000_000_000_000), "1.000000000000");
+/// Refuse a hex blob that is a cold-signing file rather than a multisig tx
+/// set, before the wallet RPC gives a less helpful error for it.
+pub fn reject_cold_signing(tx_data_hex: &str) -> Result<()> {
+    match ColdSigningKind::of_hex(tx_data_hex) {
+        Some(kind) => Err(TransactionError::ColdSigningFile(kind).into()),
+        None => Ok(()),
     }
+}
 
-    #[test]
-    fn test_format_xmr_fractional() {
-        assert_eq!(format_xmr(1_500_000_000), "0.001500000000");
+/// A hex tx set as the binary file the Monero CLI and GUI wallets load.
+pub fn to_monero_file(tx_data_hex: &str) -> Result<Vec<u8>> {
+    let bytes = hex::decode(tx_data_hex.trim())
+        .map_err(|e| TransactionError::NotMultisigTxSet(format!("not hex: {e}")))?;
+    if !bytes.starts_with(MULTISIG_FILE_MAGIC) {
+        return Err(TransactionError::NotMultisigTxSet(
+            "the multisig tx set magic is missing".into(),
+        )
+        .into());
     }
+    Ok(bytes)
+}
 
-    #[test]
-    fn test_format_xmr_zero() {
-        assert_eq!(format_xmr(0), "0.000000000000");
+/// The hex tx set in a file saved by the Monero CLI or GUI, or `None` when
+/// `bytes` isn't a binary wallet file (such as this tool's hex or armored
+/// text). Cold-signing files are refused.
+pub fn from_monero_file(bytes: &[u8]) -> Result<Option<String>> {
+    if bytes.starts_with(MULTISIG_FILE_MAGIC) {
+        return Ok(Some(hex::encode(bytes)));
+    }
+    if let Some(kind) = ColdSigningKind::of_bytes(bytes) {
+        return Err(TransactionError::ColdSigningFile(kind).into());
     }
+    Ok(None)
+}
 
-    #[test]
-    fn test_validate_address_mainnet() {
-        let valid = "4".to_string() + &"A".repeat(94);
-        assert!(validate_address(&valid, crate::config::Network::Mainnet).is_ok());
+/// Read a tx set file: a Monero wallet `multisig_monero_tx` file, or hex or
+/// armored text. Text is returned as is, for the usual clean-up. Files over
+/// `max_len` bytes as hex are refused.
+pub fn read_tx_file(path: &std::path::Path, max_len: usize) -> Result<String> {
+    use std::io::Read;
+    let file = std::fs::File::open(path).map_err(|e| {
+        std::io::Error::new(e.kind(), format!("failed to read {}: {e}", path.display()))
+    })?;
+    let mut bytes = Vec::new();
+    file.take(max_len as u64 + 1).read_to_end(&mut bytes)?;
+    if let Some(hex) = from_monero_file(&bytes)? {
+        if hex.len() > max_len {
+            let len = hex.len();
+            return Err(crate::utils::BlobError::TooLarge { len, max: max_len }.into());
+        }
+        return Ok(hex);
+    }
+    if bytes.len() > max_len {
+        let len = bytes.len();
+        return Err(crate::utils::BlobError::TooLarge { len, max: max_len }.into());
+    }
+    String::from_utf8(bytes)
+        .map(|text| text.trim().to_string())
+        .map_err(|_| {
+            TransactionError::NotMultisigTxSet(format!(
+                "{} is neither text nor a Monero wallet tx set file",
+                path.display()
+            ))
+            .into()
+        })
+}
+
+/// Read a cold-signing file as hex: the binary file the Monero CLI or GUI
+/// wallet saved, or its hex as text.
+pub fn read_cold_file(path: &std::path::Path, max_len: usize) -> Result<String> {
+    let bytes = std::fs::read(path).map_err(|e| {
+        std::io::Error::new(e.kind(), format!("failed to read {}: {e}", path.display()))
+    })?;
+    let hex = match ColdSigningKind::of_bytes(&bytes) {
+        Some(_) => hex::encode(&bytes),
+        None => String::from_utf8_lossy(&bytes).trim().to_string(),
+    };
+    if hex.len() > max_len {
+        let len = hex.len();
+        return Err(crate::utils::BlobError::TooLarge { len, max: max_len }.into());
     }
+    Ok(hex)
+}
 
-    #[test]
-    fn test_validate_address_wrong_prefix() {
-        let invalid = "9".to_string() + &"A".repeat(94);
-        assert!(validate_address(&invalid, crate::config::Network::Mainnet).is_err());
+#[derive(Debug, Deserialize)]
+struct SendRawTransactionResponse {
+    status: String,
+    #[serde(default)]
+    reason: String,
+    #[serde(default)]
+    double_spend: bool,
+    #[serde(default)]
+    fee_too_low: bool,
+    #[serde(default)]
+    too_big: bool,
+    #[serde(default)]
+    invalid_input: bool,
+    #[serde(default)]
+    low_mixin: bool,
+}
+
+/// Broadcast a raw signed transaction through the daemon's
+/// `/send_raw_transaction` endpoint.
+pub async fn send_raw_transaction(daemon: &RpcClient, tx_hex: &str) -> Result<()> {
+    let resp: SendRawTransactionResponse = daemon
+        .request_other(
+            "send_raw_transaction",
+            &serde_json::json!({
+                "tx_as_hex": tx_hex.trim(),
+                "do_not_relay": false,
+            }),
+        )
+        .await?;
+    raw_tx_result(resp)
+}
+
+/// Where the daemon sees a submitted transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    InPool,
+    Mined,
+    NotVisible,
+}
+
+impl std::fmt::Display for Visibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Visibility::InPool => "confirmed in pool",
+            Visibility::Mined => "already mined",
+            Visibility::NotVisible => "not visible on network",
+        })
     }
+}
 
-    #[test]
-    fn test_validate_address_wrong_length() {
+#[derive(Debug, Default, Deserialize)]
+struct GetTransactionsResponse {
+    #[serde(default)]
+    txs: Vec<DaemonTx>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DaemonTx {
+    tx_hash: String,
+    #[serde(default)]
+    in_pool: bool,
+}
+
+/// Ask the daemon where each of `tx_hashes` is, through `/get_transactions`.
+pub async fn visibility(daemon: &RpcClient, tx_hashes: &[String]) -> Result<Vec<Visibility>> {
+    let resp: GetTransactionsResponse = daemon
+        .request_other(
+            "get_transactions",
+            &serde_json::json!({ "txs_hashes": tx_hashes }),
+        )
+        .await?;
+    Ok(tx_hashes
+        .iter()
+        .map(|hash| match resp.txs.iter().find(|t| &t.tx_hash == hash) {
+            Some(tx) if tx.in_pool => Visibility::InPool,
+            Some(_) => Visibility::Mined,
+            None => Visibility::NotVisible,
+        })
+        .collect())
+}
+
+/// How often [`wait_for_broadcast`] asks the daemon again.
+const BROADCAST_POLL: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Poll the daemon until every one of `tx_hashes` is in its pool or mined,
+/// or `grace` runs out, or Ctrl+C is pressed. A submission the wallet RPC
+/// reported as sent can still be lost, e.g. when its daemon connection was
+/// down, so only the daemon's view counts. Failed polls are retried until
+/// the grace period ends.
+pub async fn wait_for_broadcast(
+    daemon: &RpcClient,
+    tx_hashes: &[String],
+    grace: std::time::Duration,
+) -> Vec<Visibility> {
+    let deadline = tokio::time::Instant::now() + grace;
+    loop {
+        let seen = match visibility(daemon, tx_hashes).await {
+            Ok(seen) => seen,
+            Err(e) => {
+                tracing::debug!(error = %e, "broadcast check failed");
+                vec![Visibility::NotVisible; tx_hashes.len()]
+            }
+        };
+        let now = tokio::time::Instant::now();
+        if !seen.contains(&Visibility::NotVisible)
+            || now >= deadline
+            || crate::interrupt::requested()
+        {
+            return seen;
+        }
+        tokio::select! {
+            _ = crate::interrupt::interrupted() => {}
+            _ = tokio::time::sleep(BROADCAST_POLL.min(deadline - now)) => {}
+        }
+    }
+}
+
+/// The daemon's verdict: its flags first, then its reason text.
+fn raw_tx_result(resp: SendRawTransactionResponse) -> Result<()> {
+    let flagged = if resp.double_spend {
+        Some(RejectionReason::DoubleSpend)
+    } else if resp.fee_too_low {
+        Some(RejectionReason::FeeTooLow)
+    } else if resp.too_big {
+        Some(RejectionReason::TooBig)
+    } else if resp.invalid_input || resp.low_mixin {
+        Some(RejectionReason::InvalidRing)
+    } else {
+        None
+    };
+    if resp.status == "OK" && flagged.is_none() {
+        return Ok(());
+    }
+    let reason = flagged
+        .or_else(|| RejectionReason::parse(&resp.reason))
+        .unwrap_or(RejectionReason::Other);
+    let detail = match (resp.reason.is_empty(), flagged) {
+        (false, _) => resp.reason,
+        (true, Some(flagged)) => flagged.to_string(),
+        (true, None) => resp.status,
+    };
+    Err(TransactionError::Rejected { reason, detail }.into())
+}
+
+/// Map a `submit_multisig` failure to [`TransactionError::Rejected`] when the
+/// message names a known reason, such as a double spend caused by a tx set
+/// built on stale multisig info.
+fn submit_error(err: RpcError) -> MultisigError {
+    match err
+        .message()
+        .and_then(|m| Some((RejectionReason::parse(m)?, m)))
+    {
+        Some((reason, message)) => TransactionError::Rejected {
+            reason,
+            detail: message.to_string(),
+        }
+        .into(),
+        None => err.into(),
+    }
+}
+
+// ── Tx set review ───────────────────────────────────────────────────────
+
+/// One recipient of a described transfer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recipient {
+    pub address: String,
+    pub amount: u64,
+}
+
+/// What a multisig tx set would do if signed, as decoded by the wallet RPC's
+/// `describe_transfer`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferDescription {
+    #[serde(default)]
+    pub recipients: Vec<Recipient>,
+    #[serde(default)]
+    pub amount_in: u64,
+    #[serde(default)]
+    pub amount_out: u64,
+    #[serde(default)]
+    pub change_amount: u64,
+    #[serde(default)]
+    pub change_address: String,
+    #[serde(default)]
+    pub fee: u64,
+    #[serde(default)]
+    pub ring_size: u32,
+    #[serde(default)]
+    pub unlock_time: u64,
+}
+
+/// Decode a multisig tx set without signing it, so it can be reviewed first.
+/// Works against an offline wallet RPC.
+pub async fn describe_transfer(
+    rpc: &RpcClient,
+    tx_data_hex: &str,
+) -> Result<Vec<TransferDescription>> {
+    let resp = rpc
+        .invoke::<DescribeTransfer>(DescribeTransferRequest {
+            multisig_txset: tx_data_hex.to_string(),
+        })
+        .await?;
+    Ok(resp.desc)
+}
+
+/// [`describe_transfer`], or `None` when the wallet RPC is too old to decode
+/// tx sets. Signing can go ahead without the description, but nothing that
+/// needs it can be checked, so callers warn. If the server can't be probed,
+/// the call is tried anyway and reports the problem itself.
+pub async fn describe_if_supported(
+    rpc: &RpcClient,
+    tx_data_hex: &str,
+) -> Result<Option<Vec<TransferDescription>>> {
+    let supported = rpc
+        .capabilities()
+        .await
+        .map_or(true, |c| c.supports_describe_transfer);
+    if !supported {
+        return Ok(None);
+    }
+    describe_transfer(rpc, tx_data_hex).await.map(Some)
+}
+
+/// How a described output relates to this wallet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputKind {
+    /// Leaves the wallet.
+    Payout,
+    /// Back to one of this wallet's own addresses.
+    Change,
+    /// To an own address that was deliberately given as a destination.
+    SelfTransfer,
+}
+
+impl std::fmt::Display for OutputKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            OutputKind::Payout => "pay",
+            OutputKind::Change => "change (back to this wallet)",
+            OutputKind::SelfTransfer => "self-transfer",
+        })
+    }
+}
+
+/// One output of a described transfer, labelled by [`review_outputs`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewedOutput {
+    pub address: String,
+    pub amount: u64,
+    pub kind: OutputKind,
+}
+
+/// Label every output of `descriptions` against the wallet's `own`
+/// addresses, including the separate change output. A recipient paying an
+/// own address counts as change unless it is in `requested`, the
+/// destinations the user asked for when building. Change going to an
+/// address that isn't the wallet's is a payout like any other.
+pub fn review_outputs(
+    descriptions: &[TransferDescription],
+    own: &[String],
+    requested: &[String],
+) -> Vec<ReviewedOutput> {
+    let mut outputs = Vec::new();
+    for desc in descriptions {
+        for r in &desc.recipients {
+            let kind = if !own.contains(&r.address) {
+                OutputKind::Payout
+            } else if requested.contains(&r.address) {
+                OutputKind::SelfTransfer
+            } else {
+                OutputKind::Change
+            };
+            outputs.push(ReviewedOutput {
+                address: r.address.clone(),
+                amount: r.amount,
+                kind,
+            });
+        }
+        if desc.change_amount > 0 {
+            let kind = if own.contains(&desc.change_address) {
+                OutputKind::Change
+            } else {
+                OutputKind::Payout
+            };
+            outputs.push(ReviewedOutput {
+                address: desc.change_address.clone(),
+                amount: desc.change_amount,
+                kind,
+            });
+        }
+    }
+    outputs
+}
+
+/// Total paid outside the wallet: what policy limits and the spend ledger
+/// count.
+pub fn leaving_amount(outputs: &[ReviewedOutput]) -> std::result::Result<Amount, TransactionError> {
+    Amount::checked_sum(
+        outputs
+            .iter()
+            .filter(|o| o.kind == OutputKind::Payout)
+            .map(|o| Amount(o.amount)),
+    )
+}
+
+/// Total fee of the decoded transfers.
+pub fn total_fee(
+    descriptions: &[TransferDescription],
+) -> std::result::Result<Amount, TransactionError> {
+    Amount::checked_sum(descriptions.iter().map(|d| Amount(d.fee)))
+}
+
+// ── Amounts ─────────────────────────────────────────────────────────────
+
+const PICONERO_PER_XMR: u64 = 1_000_000_000_000;
+
+/// An amount in piconero. There is no `+`: totals go through
+/// [`Amount::checked_add`] or [`Amount::checked_sum`], so a crafted
+/// destination list can't wrap around a balance or policy check.
+///
+/// Displays as XMR, e.g. `1.500000000000 XMR`, and serializes as the plain
+/// piconero integer the RPC uses.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Amount(pub u64);
+
+impl Amount {
+    pub const ZERO: Self = Self(0);
+
+    pub fn checked_add(self, rhs: Self) -> std::result::Result<Self, TransactionError> {
+        self.0
+            .checked_add(rhs.0)
+            .map(Self)
+            .ok_or(TransactionError::AmountOverflow)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> std::result::Result<Self, TransactionError> {
+        self.0
+            .checked_sub(rhs.0)
+            .map(Self)
+            .ok_or(TransactionError::AmountUnderflow {
+                amount: self.0,
+                minus: rhs.0,
+            })
+    }
+
+    /// Add up `amounts`, failing instead of wrapping.
+    pub fn checked_sum(
+        amounts: impl IntoIterator<Item = Self>,
+    ) -> std::result::Result<Self, TransactionError> {
+        amounts
+            .into_iter()
+            .try_fold(Self::ZERO, |total, a| total.checked_add(a))
+    }
+}
+
+impl From<u64> for Amount {
+    fn from(piconero: u64) -> Self {
+        Self(piconero)
+    }
+}
+
+impl std::fmt::Display for Amount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = format!("{} XMR", format_xmr(self.0));
+        f.pad(&text)
+    }
+}
+
+impl std::str::FromStr for Amount {
+    type Err = TransactionError;
+
+    /// Parse XMR with up to 12 decimal places, such as `1.5` or the
+    /// displayed form `1.500000000000 XMR`. The whole part may be grouped
+    /// in threes with `,` or `_`, as in `1,234,567.5`; see [`ungroup`] for
+    /// what is refused.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || TransactionError::InvalidAmount(s.to_string());
+        let text = s.trim();
+        let text = text.strip_suffix("XMR").unwrap_or(text).trim_end();
+        let (whole, frac) = text.split_once('.').unwrap_or((text, ""));
+        let whole = ungroup(whole, text.contains('.')).ok_or_else(invalid)?;
+        let whole = whole.as_str();
+        let digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if (whole.is_empty() && frac.is_empty())
+            || !digits(whole)
+            || !digits(frac)
+            || frac.len() > 12
+        {
+            return Err(invalid());
+        }
+        let whole: u64 = match whole {
+            "" => 0,
+            w => w.parse().map_err(|_| TransactionError::AmountOverflow)?,
+        };
+        let frac: u64 = format!("{frac:0<12}").parse().map_err(|_| invalid())?;
+        whole
+            .checked_mul(PICONERO_PER_XMR)
+            .map(Self)
+            .ok_or(TransactionError::AmountOverflow)?
+            .checked_add(Self(frac))
+    }
+}
+
+/// Format an atomic-unit amount as a human-readable XMR string.
+pub fn format_xmr(piconero: u64) -> String {
+    let whole = piconero / PICONERO_PER_XMR;
+    let frac = piconero % PICONERO_PER_XMR;
+    format!("{whole}.{frac:012}")
+}
+
+/// [`format_xmr`] with the whole part grouped in threes, as in
+/// `1,234,567.890000000000`. For people only: JSON output and anything
+/// parsed back use [`format_xmr`].
+pub fn format_xmr_grouped(piconero: u64) -> String {
+    let whole = (piconero / PICONERO_PER_XMR).to_string();
+    let frac = piconero % PICONERO_PER_XMR;
+    let mut grouped = String::with_capacity(whole.len() + whole.len() / 3);
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{grouped}.{frac:012}")
+}
+
+/// The whole part of an amount with its `,` or `_` grouping taken out, or
+/// `None` if the grouping is malformed: mixed separators, a group that
+/// isn't three digits, or a separator at either end. A lone comma with no
+/// decimal point, as in `1,500`, is refused too: in much of the world
+/// that is one and a half.
+fn ungroup(whole: &str, has_fraction: bool) -> Option<String> {
+    let separator = match (whole.contains(','), whole.contains('_')) {
+        (false, false) => return Some(whole.to_string()),
+        (true, true) => return None,
+        (true, false) => ',',
+        (false, true) => '_',
+    };
+    let groups: Vec<&str> = whole.split(separator).collect();
+    if separator == ',' && groups.len() == 2 && !has_fraction {
+        return None;
+    }
+    let (first, rest) = groups.split_first()?;
+    if !(1..=3).contains(&first.len()) || rest.iter().any(|g| g.len() != 3) {
+        return None;
+    }
+    Some(groups.concat())
+}
+
+// ── Balance queries ─────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Balance {
+    /// Everything the wallet holds in atomic units, locked or not.
+    pub balance: Amount,
+    /// The part that can be spent now.
+    pub unlocked_balance: Amount,
+    /// The part that can't be spent yet: outputs with fewer than 10
+    /// confirmations, including our own change, and young coinbase outputs.
+    #[serde(default)]
+    pub locked: Amount,
+    /// Blocks until all of `locked` unlocks, as the wallet RPC estimates it.
+    #[serde(default)]
+    pub blocks_to_unlock: u64,
+    /// Seconds until outputs locked to a time rather than a height unlock.
+    #[serde(default)]
+    pub time_to_unlock: u64,
+}
+
+impl Balance {
+    /// A balance that is all unlocked.
+    pub fn unlocked(amount: Amount) -> Self {
+        Balance {
+            balance: amount,
+            unlocked_balance: amount,
+            locked: Amount::ZERO,
+            blocks_to_unlock: 0,
+            time_to_unlock: 0,
+        }
+    }
+}
+
+impl From<GetBalanceResponse> for Balance {
+    fn from(resp: GetBalanceResponse) -> Self {
+        Balance {
+            balance: resp.balance,
+            unlocked_balance: resp.unlocked_balance,
+            locked: Amount(resp.balance.0.saturating_sub(resp.unlocked_balance.0)),
+            blocks_to_unlock: resp.blocks_to_unlock,
+            time_to_unlock: resp.time_to_unlock,
+        }
+    }
+}
+
+/// Query the current balance of `account`.
+pub async fn get_balance(rpc: &RpcClient, account: u32) -> Result<Balance> {
+    let resp = rpc
+        .invoke::<GetBalance>(GetBalanceRequest {
+            account_index: Some(account),
+            ..Default::default()
+        })
+        .await?;
+    Ok(resp.into())
+}
+
+/// Query the balance summed over every account: what the wallet holds as a
+/// whole, for checks that must not miss funds in another account.
+pub async fn total_balance(rpc: &RpcClient) -> Result<Balance> {
+    let resp = rpc
+        .invoke::<GetBalance>(GetBalanceRequest {
+            all_accounts: true,
+            ..Default::default()
+        })
+        .await?;
+    Ok(resp.into())
+}
+
+// ── History and outputs ─────────────────────────────────────────────────
+
+/// Direction or status of a wallet transfer, as reported by `get_transfers`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferKind {
+    In,
+    Out,
+    Pending,
+    Failed,
+    Pool,
+}
+
+/// A single entry from the wallet's transfer history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferEntry {
+    pub txid: String,
+    #[serde(rename = "type")]
+    pub kind: TransferKind,
+    pub amount: u64,
+    #[serde(default)]
+    pub fee: u64,
+    #[serde(default)]
+    pub height: u64,
+    #[serde(default)]
+    pub timestamp: u64,
+    #[serde(default)]
+    pub confirmations: u64,
+    #[serde(default)]
+    pub address: String,
+    #[serde(default)]
+    pub subaddr_index: SubaddressIndex,
+}
+
+/// Account (`major`) and subaddress (`minor`) index of an address.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SubaddressIndex {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl From<TransferRecord> for TransferEntry {
+    fn from(record: TransferRecord) -> Self {
+        TransferEntry {
+            txid: record.txid,
+            kind: record.kind,
+            amount: record.amount,
+            fee: record.fee,
+            height: record.height,
+            timestamp: record.timestamp,
+            confirmations: record.confirmations,
+            address: record.address,
+            subaddr_index: record.subaddr_index,
+        }
+    }
+}
+
+/// Fetch the transfer history of `account`, or of every account for `None`
+/// (confirmed, pending, pool, and failed), oldest first.
+pub async fn get_transfers(rpc: &RpcClient, account: Option<u32>) -> Result<Vec<TransferEntry>> {
+    let resp = rpc
+        .invoke::<GetTransfers>(GetTransfersRequest {
+            incoming: true,
+            out: true,
+            pending: true,
+            failed: true,
+            pool: true,
+            account_index: Some(account.unwrap_or(0)),
+            all_accounts: account.is_none(),
+            ..Default::default()
+        })
+        .await?;
+
+    let mut entries: Vec<TransferEntry> = resp
+        .incoming
+        .into_iter()
+        .chain(resp.out)
+        .chain(resp.pending)
+        .chain(resp.failed)
+        .chain(resp.pool)
+        .map(TransferEntry::from)
+        .collect();
+    entries.sort_by_key(|e| (e.timestamp, e.height));
+    Ok(entries)
+}
+
+/// Wallet RPC error code for a txid that is malformed or that the wallet has
+/// no transfer for.
+const WRONG_TXID: i64 = -8;
+
+/// The wallet's transfer with `tx_hash`, or `None` when the wallet has none,
+/// as for a transaction that neither paid nor spent from it. Like the wallet
+/// RPC, only looks in one account. A self-transfer is both incoming and
+/// outgoing; the outgoing half is returned, with its destinations.
+pub async fn get_transfer_by_txid(
+    rpc: &RpcClient,
+    tx_hash: &str,
+    account: u32,
+) -> Result<Option<TransferRecord>> {
+    let resp = rpc
+        .invoke::<GetTransferByTxid>(GetTransferByTxidRequest {
+            txid: tx_hash.to_string(),
+            account_index: Some(account),
+        })
+        .await;
+    match resp {
+        Ok(resp) => {
+            let out = resp
+                .transfers
+                .into_iter()
+                .find(|t| t.kind == TransferKind::Out);
+            Ok(Some(out.unwrap_or(resp.transfer)))
+        }
+        Err(e)
+            if e.code() == Some(WRONG_TXID)
+                && e.message()
+                    .is_some_and(|m| m.to_lowercase().contains("not found")) =>
+        {
+            Ok(None)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// The notes the wallet keeps for `txids`, in the same order; empty for a
+/// transaction without one. Unlike the journal's, they are stored in the
+/// wallet file, so they survive restoring it elsewhere.
+pub async fn get_tx_notes(rpc: &RpcClient, txids: &[String]) -> Result<Vec<String>> {
+    if txids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let resp = rpc
+        .invoke::<GetTxNotes>(GetTxNotesRequest {
+            txids: txids.to_vec(),
+        })
+        .await?;
+    let mut notes = resp.notes;
+    notes.resize(txids.len(), String::new());
+    Ok(notes)
+}
+
+/// Store `note` in the wallet for each of `txids`.
+pub async fn set_tx_notes(rpc: &RpcClient, txids: &[String], note: &str) -> Result<()> {
+    rpc.invoke::<SetTxNotes>(SetTxNotesRequest {
+        txids: txids.to_vec(),
+        notes: vec![note.to_string(); txids.len()],
+    })
+    .await?;
+    Ok(())
+}
+
+/// Fetch only the incoming transfers of `account`, confirmed and in the pool.
+pub async fn get_incoming_transfers(rpc: &RpcClient, account: u32) -> Result<Vec<TransferEntry>> {
+    let resp = rpc
+        .invoke::<GetTransfers>(GetTransfersRequest {
+            incoming: true,
+            pool: true,
+            account_index: Some(account),
+            ..Default::default()
+        })
+        .await?;
+
+    let mut entries: Vec<TransferEntry> = resp
+        .incoming
+        .into_iter()
+        .chain(resp.pool)
+        .map(TransferEntry::from)
+        .collect();
+    entries.sort_by_key(|e| (e.timestamp, e.height));
+    Ok(entries)
+}
+
+/// A payment to the wallet still waiting in the daemon's pool (zero-conf).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolPayment {
+    pub txid: String,
+    pub amount: u64,
+    #[serde(default)]
+    pub address: String,
+    #[serde(default)]
+    pub subaddr_index: SubaddressIndex,
+    /// When the wallet first saw it in the pool, as a Unix timestamp.
+    #[serde(default)]
+    pub timestamp: u64,
+    /// Whether another transaction spending the same inputs was seen. Such
+    /// a payment may never confirm, so it must not be trusted.
+    #[serde(default)]
+    pub double_spend_seen: bool,
+}
+
+impl From<TransferRecord> for PoolPayment {
+    fn from(record: TransferRecord) -> Self {
+        PoolPayment {
+            txid: record.txid,
+            amount: record.amount,
+            address: record.address,
+            subaddr_index: record.subaddr_index,
+            timestamp: record.timestamp,
+            double_spend_seen: record.double_spend_seen,
+        }
+    }
+}
+
+/// The payments to any account of the wallet now in the daemon's pool,
+/// newest first.
+pub async fn get_pool_payments(rpc: &RpcClient) -> Result<Vec<PoolPayment>> {
+    let resp = rpc
+        .invoke::<GetTransfers>(GetTransfersRequest {
+            pool: true,
+            all_accounts: true,
+            ..Default::default()
+        })
+        .await?;
+    let mut payments: Vec<PoolPayment> = resp.pool.into_iter().map(PoolPayment::from).collect();
+    payments.sort_by_key(|p| std::cmp::Reverse(p.timestamp));
+    Ok(payments)
+}
+
+/// An output (enote) owned by the wallet, from `incoming_transfers`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnedOutput {
+    pub amount: u64,
+    pub tx_hash: String,
+    #[serde(default)]
+    pub spent: bool,
+    #[serde(default)]
+    pub unlocked: bool,
+    #[serde(default)]
+    pub frozen: bool,
+    #[serde(default)]
+    pub subaddr_index: SubaddressIndex,
+}
+
+/// List the outputs owned by `account`. With `available_only`, spent outputs
+/// are excluded.
+pub async fn list_outputs(
+    rpc: &RpcClient,
+    account: u32,
+    available_only: bool,
+) -> Result<Vec<OwnedOutput>> {
+    let transfer_type = if available_only {
+        TransferType::Available
+    } else {
+        TransferType::All
+    };
+    let resp = rpc
+        .invoke::<IncomingTransfers>(IncomingTransfersRequest {
+            transfer_type,
+            account_index: account,
+            ..Default::default()
+        })
+        .await?;
+    Ok(resp.transfers)
+}
+
+/// Validate a Monero address for `network`: its base58, checksum and
+/// network tag. Returns what kind of address it is.
+pub fn validate_address(address: &str, network: Network) -> Result<AddressKind> {
+    Ok(check_address(address, network)?)
+}
+
+fn check_address(
+    address: &str,
+    network: Network,
+) -> std::result::Result<AddressKind, TransactionError> {
+    let (found, kind) = address::decode(address)?;
+    if found != network {
+        return Err(TransactionError::InvalidAddress(format!(
+            "{address}: this is a {found} address, not a {network} one"
+        )));
+    }
+    Ok(kind)
+}
+
+/// Fail with [`TransactionError::WrongNetwork`] if a described tx set pays
+/// or returns change to an address of another network than `network`, as a
+/// set built by a stagenet wallet pasted into a mainnet signer does. The
+/// wallet RPC would only fail deep inside signing.
+pub fn check_network(descriptions: &[TransferDescription], network: Network) -> Result<()> {
+    let addresses = descriptions.iter().flat_map(|d| {
+        d.recipients
+            .iter()
+            .map(|r| r.address.as_str())
+            .chain(std::iter::once(d.change_address.as_str()))
+    });
+    for address in addresses {
+        match Network::of_address(address) {
+            Some(built_for) if built_for != network => {
+                return Err(TransactionError::WrongNetwork {
+                    built_for,
+                    configured: network,
+                }
+                .into());
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::methods::{DescribeTransferResponse, GetTransfersResponse};
+
+    #[test]
+    fn test_format_xmr_whole() {
+        assert_eq!(format_xmr(1_000_000_000_000), "1.000000000000");
+    }
+
+    #[test]
+    fn test_format_xmr_fractional() {
+        assert_eq!(format_xmr(1_500_000_000), "0.001500000000");
+    }
+
+    #[test]
+    fn test_amount_display_pads() {
+        assert_eq!(Amount(1_500_000_000_000).to_string(), "1.500000000000 XMR");
+        assert_eq!(format!("{:>20}", Amount(0)), "  0.000000000000 XMR");
+        assert_eq!(serde_json::to_string(&Amount(7)).unwrap(), "7");
+        assert_eq!(serde_json::from_str::<Amount>("7").unwrap(), Amount(7));
+    }
+
+    /// Deterministic spread of values across the whole u64 range.
+    fn sample_amounts() -> Vec<u64> {
+        let mut values = vec![
+            0,
+            1,
+            PICONERO_PER_XMR - 1,
+            PICONERO_PER_XMR,
+            u64::MAX - 1,
+            u64::MAX,
+        ];
+        let mut x: u64 = 0x9E37_79B9_7F4A_7C15;
+        for _ in 0..500 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            values.push(x >> (x % 64));
+        }
+        values
+    }
+
+    #[test]
+    fn test_amount_format_parse_round_trip() {
+        for v in sample_amounts() {
+            let amount = Amount(v);
+            assert_eq!(amount.to_string().parse::<Amount>().unwrap(), amount);
+            assert_eq!(format_xmr(v).parse::<Amount>().unwrap(), amount);
+        }
+        assert_eq!("1.5".parse::<Amount>().unwrap(), Amount(1_500_000_000_000));
+        assert_eq!(".25".parse::<Amount>().unwrap(), Amount(250_000_000_000));
+        assert_eq!("3".parse::<Amount>().unwrap(), Amount(3 * PICONERO_PER_XMR));
+    }
+
+    #[test]
+    fn test_amount_parse_rejects() {
+        for bad in ["", ".", "-1", "1.2.3", "1,5", "0.0000000000001", "abc XMR"] {
+            assert!(
+                matches!(
+                    bad.parse::<Amount>(),
+                    Err(TransactionError::InvalidAmount(_))
+                ),
+                "{bad:?}"
+            );
+        }
+        assert!(matches!(
+            "18446745".parse::<Amount>(),
+            Err(TransactionError::AmountOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_amount_checked_arithmetic_matches_u128() {
+        let values = sample_amounts();
+        for (a, b) in values.iter().zip(values.iter().rev()) {
+            let wide = *a as u128 + *b as u128;
+            match Amount(*a).checked_add(Amount(*b)) {
+                Ok(sum) => assert_eq!(sum.0 as u128, wide),
+                Err(e) => {
+                    assert!(wide > u64::MAX as u128);
+                    assert!(matches!(e, TransactionError::AmountOverflow));
+                }
+            }
+            match Amount(*a).checked_sub(Amount(*b)) {
+                Ok(diff) => assert_eq!(diff.0, a - b),
+                Err(e) => {
+                    assert!(a < b);
+                    assert!(matches!(e, TransactionError::AmountUnderflow { .. }));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_checked_sum_refuses_wrapping_destinations() {
+        // Two outputs that would wrap around to 1 piconero.
+        let crafted = [Amount(u64::MAX), Amount(2)];
+        assert!(Amount::checked_sum(crafted).is_err());
+        assert_eq!(
+            Amount::checked_sum([Amount(1), Amount(2)]).unwrap(),
+            Amount(3)
+        );
+        assert_eq!(Amount::checked_sum([]).unwrap(), Amount::ZERO);
+    }
+
+    #[test]
+    fn test_grouped_amounts() {
+        assert_eq!(format_xmr_grouped(0), "0.000000000000");
+        assert_eq!(
+            format_xmr_grouped(999 * PICONERO_PER_XMR),
+            "999.000000000000"
+        );
+        assert_eq!(
+            format_xmr_grouped(1_234_567 * PICONERO_PER_XMR + 890_000_000_000),
+            "1,234,567.890000000000"
+        );
+        assert_eq!(
+            format_xmr_grouped(123_456 * PICONERO_PER_XMR),
+            "123,456.000000000000"
+        );
+
+        let xmr = |text: &str| text.parse::<Amount>().map(|a| a.0);
+        let expected = 1_234_567 * PICONERO_PER_XMR + 500_000_000_000;
+        assert_eq!(xmr("1,234,567.5").unwrap(), expected);
+        assert_eq!(xmr("1_234_567.5").unwrap(), expected);
+        assert_eq!(xmr("1,234,567.500000000000 XMR").unwrap(), expected);
+        assert_eq!(xmr("1,234,567").unwrap(), 1_234_567 * PICONERO_PER_XMR);
+        assert_eq!(xmr("1_500").unwrap(), 1_500 * PICONERO_PER_XMR);
+        assert_eq!(xmr("1,500.0").unwrap(), 1_500 * PICONERO_PER_XMR);
+        // Every grouped display parses back to the same amount.
+        for v in [0, 1, 999_999_999_999_999, u64::MAX] {
+            assert_eq!(xmr(&format_xmr_grouped(v)).unwrap(), v);
+        }
+
+        for ambiguous in [
+            "1,500",      // 1.5 with a decimal comma?
+            "1,5",        // a decimal comma
+            "1,50.0",     // not a group of three
+            "1234,567.0", // a leading group too long
+            "1,234_567",  // mixed separators
+            ",123.0",
+            "123,.0",
+            "1,,234.0",
+            "0.000_001", // grouping in the fraction
+        ] {
+            assert!(xmr(ambiguous).is_err(), "{ambiguous} parsed");
+        }
+    }
+
+    #[test]
+    fn test_format_xmr_zero() {
+        assert_eq!(format_xmr(0), "0.000000000000");
+    }
+
+    #[test]
+    fn test_validate_multisig_info() {
+        assert!(validate_multisig_info("4d756c7469736967").is_ok());
+        assert!(validate_multisig_info("").is_err());
+        assert!(validate_multisig_info("abc").is_err());
+        assert!(validate_multisig_info("zz").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_reports_invalid_blobs_without_rpc() {
+        // Nothing valid, so the (unreachable) wallet is never contacted.
+        let rpc = RpcClient::new(&crate::config::DaemonRpc {
+            port: 1,
+            ..Default::default()
+        });
+        let report = import_multisig_info(&rpc, &["".into(), "xyz".into(), "xyz".into()])
+            .await
+            .unwrap();
+        assert!(report.succeeded.is_empty());
+        assert_eq!(report.failed.len(), 3);
+        assert!(report.failed[2].1.to_string().contains("duplicate"));
+    }
+
+    #[test]
+    fn test_submit_rejections_are_typed() {
+        let submit = |message: &str| {
+            submit_error(RpcError::Server {
+                method: "submit_multisig".into(),
+                code: -4,
+                message: message.into(),
+            })
+        };
+        let reason = |err: MultisigError| match err {
+            MultisigError::Transaction(TransactionError::Rejected { reason, .. }) => Some(reason),
+            _ => None,
+        };
+        for (message, expected) in [
+            (
+                "Failed to submit multisig tx: transaction uses an output that is already spent",
+                RejectionReason::KeyImageSpent,
+            ),
+            (
+                "Transaction 5f2a9c was rejected by daemon with status: Failed. Reason: double spend",
+                RejectionReason::DoubleSpend,
+            ),
+            (
+                "Transaction 5f2a9c was rejected by daemon with status: Failed. Reason: fee too low",
+                RejectionReason::FeeTooLow,
+            ),
+            (
+                "Transaction 5f2a9c was rejected by daemon with status: Failed. Reason: bad ring size",
+                RejectionReason::InvalidRing,
+            ),
+            ("transaction was too big", RejectionReason::TooBig),
+            ("Key image already spent in blockchain", RejectionReason::KeyImageSpent),
+        ] {
+            assert_eq!(reason(submit(message)), Some(expected), "{message}");
+        }
+        assert!(matches!(
+            submit("no connection to daemon"),
+            MultisigError::Rpc(_)
+        ));
+        // Mentioning key images doesn't make it a double spend.
+        assert!(matches!(
+            submit("Failed to generate key image helper"),
+            MultisigError::Rpc(_)
+        ));
+    }
+
+    fn dest(address: &str, amount: u64) -> Destination {
+        Destination::new_unchecked(address, Amount(amount))
+    }
+
+    fn numbered(count: usize) -> Vec<Destination> {
+        (0..count).map(|i| dest(&format!("4addr{i}"), 1)).collect()
+    }
+
+    #[test]
+    fn test_merge_duplicates_keeps_order() {
+        let merged = merge_duplicates(&[dest("4a", 1), dest("4b", 2), dest("4a", 3)]).unwrap();
+        assert_eq!(merged.len(), 2);
+        assert_eq!(
+            (merged[0].address.as_str(), merged[0].amount),
+            ("4a", Amount(4))
+        );
+        assert_eq!(
+            (merged[1].address.as_str(), merged[1].amount),
+            ("4b", Amount(2))
+        );
+        assert!(merge_duplicates(&[dest("4a", u64::MAX), dest("4a", 1)]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_rejected_before_rpc() {
+        let (stub, hits) =
+            crate::testing::stub_daemon(r#"{"jsonrpc":"2.0","id":"0","result":{}}"#).await;
+        let rpc = RpcClient::new(&stub);
+        let err = build_unsigned_tx(
+            &rpc,
+            &[dest("4a", 1), dest("4b", 1), dest("4a", 1), dest("4a", 2)],
+            Priority::Default,
+            false,
+            &Source::default(),
+            None,
+        )
+        .await
+        .unwrap_err();
+        match err {
+            MultisigError::Transaction(TransactionError::DuplicateDestinations(d)) => {
+                assert_eq!(d, ["4a"])
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_output_limit_boundary() {
+        let (stub, hits) = crate::testing::stub_daemon(
+            r#"{"jsonrpc":"2.0","id":"0","result":{
+                "tx_hash":"h","fee":5,"multisig_txset":"set","weight":1500,
+                "tx_hash_list":["h1","h2"],"fee_list":[5,6],"weight_list":[1500,2500],
+                "version":65563
+            }}"#,
+        )
+        .await;
+        let rpc = RpcClient::new(&stub);
+
+        let one = build_unsigned_tx(
+            &rpc,
+            &numbered(MAX_DESTINATIONS),
+            Priority::Default,
+            false,
+            &Source::default(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(one.tx_hashes, ["h"]);
+        assert_eq!((one.weight, one.size_bytes), (1500, 1500));
+
+        let err = build_unsigned_tx(
+            &rpc,
+            &numbered(MAX_DESTINATIONS + 1),
+            Priority::Default,
+            false,
+            &Source::default(),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            MultisigError::Transaction(TransactionError::TooManyDestinations {
+                count: 16,
+                max: 15
+            })
+        ));
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let split = build_unsigned_tx(
+            &rpc,
+            &numbered(MAX_DESTINATIONS + 1),
+            Priority::Default,
+            true,
+            &Source::default(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(split.tx_hashes, ["h1", "h2"]);
+        assert_eq!(split.fee, Amount(11));
+        assert_eq!(split.weight, 4000);
+        // This wallet RPC doesn't say which inputs were spent.
+        assert!(split.spent_key_images.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_spent_inputs_frozen_and_thawed() {
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = std::sync::Arc::clone(&calls);
+        let rpc = RpcClient::new(
+            &crate::testing::handler_daemon(move |call| {
+                let method = call["method"].as_str().unwrap_or_default();
+                let key_image = call["params"]["key_image"].as_str().unwrap_or_default();
+                seen.lock().unwrap().push(format!("{method} {key_image}"));
+                match method {
+                    "transfer" => Ok(serde_json::json!({
+                        "tx_hash": "h", "fee": 5, "multisig_txset": "set",
+                        "spent_key_images": { "key_images": ["k1", "k2"] },
+                    })),
+                    "freeze" if key_image == "k2" => Err((-1, "Failed to freeze".into())),
+                    "freeze" | "thaw" => Ok(serde_json::json!({})),
+                    _ => Err((-32601, "Method not found".into())),
+                }
+            })
+            .await,
+        );
+
+        let built = build_unsigned_tx(
+            &rpc,
+            &numbered(1),
+            Priority::Default,
+            false,
+            &Source::default(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(built.spent_key_images, ["k1", "k2"]);
+
+        let mut frozen = Vec::new();
+        assert!(freeze_outputs(&rpc, &built.spent_key_images, &mut frozen)
+            .await
+            .is_err());
+        assert_eq!(frozen, ["k1"]);
+        thaw_outputs(&rpc, &frozen).await.unwrap();
+        assert_eq!(
+            calls.lock().unwrap()[1..],
+            ["freeze k1", "freeze k2", "thaw k1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signing_goes_ahead_without_describe_transfer() {
+        let rpc = RpcClient::new(
+            &crate::testing::method_daemon(&[
+                ("get_version", r#"{"version":65556}"#),
+                (
+                    "sign_multisig",
+                    r#"{"tx_data_hex":"signed","tx_hash_list":["h"]}"#,
+                ),
+            ])
+            .await,
+        );
+        assert!(describe_if_supported(&rpc, "set").await.unwrap().is_none());
+        let signed = sign_multisig_tx(&rpc, "set").await.unwrap();
+        assert_eq!(
+            (signed.tx_data_hex.as_str(), signed.tx_hash.as_str()),
+            ("signed", "h")
+        );
+
+        // Splitting needs transfer_split, which the same server lacks.
+        let err = build_unsigned_tx(
+            &rpc,
+            &numbered(MAX_DESTINATIONS + 1),
+            Priority::Default,
+            true,
+            &Source::default(),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("transfer_split is not available"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_fee_per_byte() {
+        assert_eq!(fee_per_byte(Amount(30_720_000), 1536), Some(20_000));
+        assert_eq!(fee_per_byte(Amount(100), 3), Some(33));
+        assert_eq!(fee_per_byte(Amount(100), 0), None);
+        assert!(!near_weight_limit(1536));
+        assert!(!near_weight_limit(134_459));
+        assert!(near_weight_limit(134_460));
+        assert!(near_weight_limit(MAX_TX_WEIGHT * 2));
+    }
+
+    #[test]
+    fn test_transfer_never_relays_or_fetches_hex() {
+        let params =
+            serde_json::to_value(transfer_params(&[], Priority::Default, &Source::default()))
+                .unwrap();
+        assert_eq!(params["do_not_relay"], true);
+        assert_eq!(params["get_tx_hex"], false);
+        assert_eq!(params["account_index"], 0);
+        assert!(params.get("subaddr_indices").is_none());
+
+        let source = Source {
+            account: 2,
+            subaddresses: vec![1, 3],
+        };
+        let params =
+            serde_json::to_value(transfer_params(&[], Priority::Default, &source)).unwrap();
+        assert_eq!(params["account_index"], 2);
+        assert_eq!(params["subaddr_indices"], serde_json::json!([1, 3]));
+        assert_eq!(source.to_string(), "account 2, subaddresses 1,3");
+    }
+
+    #[tokio::test]
+    async fn test_get_transfer_by_txid() {
+        // The self-transfer is in account 2 only.
+        let known = crate::testing::handler_daemon(|call| {
+            if call["params"]["account_index"] != 2 {
+                return Err((-8, "Transaction not found.".into()));
+            }
+            Ok(serde_json::json!({
+                "transfer": {"txid": "self", "type": "in", "amount": 5},
+                "transfers": [
+                    {"txid": "self", "type": "in", "amount": 5},
+                    {"txid": "self", "type": "out", "amount": 5, "fee": 1,
+                     "destinations": [{"address": "4sub1", "amount": 5}],
+                     "subaddr_indices": [{"major": 2, "minor": 0}]}
+                ]
+            }))
+        })
+        .await;
+        let rpc = RpcClient::new(&known);
+        let found = get_transfer_by_txid(&rpc, "self", 2)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.kind, TransferKind::Out);
+        assert_eq!(found.destinations[0].address, "4sub1");
+        assert!(get_transfer_by_txid(&rpc, "self", 0)
+            .await
+            .unwrap()
+            .is_none());
+
+        let (unknown, _) = crate::testing::stub_daemon(
+            r#"{"jsonrpc":"2.0","id":"0","error":{"code":-8,"message":"Transaction not found."}}"#,
+        )
+        .await;
+        let rpc = RpcClient::new(&unknown);
+        assert!(get_transfer_by_txid(&rpc, "ab", 0).await.unwrap().is_none());
+
+        let (malformed, _) = crate::testing::stub_daemon(
+            r#"{"jsonrpc":"2.0","id":"0","error":{"code":-8,"message":"Transaction ID has invalid format"}}"#,
+        )
+        .await;
+        let rpc = RpcClient::new(&malformed);
+        assert!(get_transfer_by_txid(&rpc, "zz", 0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tx_notes() {
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = std::sync::Arc::clone(&calls);
+        let rpc = RpcClient::new(
+            &crate::testing::handler_daemon(move |call| {
+                seen.lock().unwrap().push(call["params"].clone());
+                match call["method"].as_str() {
+                    // Fewer notes than asked for, as a wallet might answer.
+                    Some("get_tx_notes") => Ok(serde_json::json!({ "notes": ["rent"] })),
+                    Some("set_tx_notes") => Ok(serde_json::json!({})),
+                    _ => Err((-32601, "Method not found".into())),
+                }
+            })
+            .await,
+        );
+        let txids = ["aa".to_string(), "bb".to_string()];
+        assert_eq!(get_tx_notes(&rpc, &txids).await.unwrap(), ["rent", ""]);
+        assert!(get_tx_notes(&rpc, &[]).await.unwrap().is_empty());
+        set_tx_notes(&rpc, &txids, "bills").await.unwrap();
+        assert_eq!(
+            calls.lock().unwrap().last().unwrap(),
+            &serde_json::json!({ "txids": ["aa", "bb"], "notes": ["bills", "bills"] })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_balance_breaks_down_locked_funds() {
+        let rpc = RpcClient::new(
+            &crate::testing::method_daemon(&[(
+                "get_balance",
+                r#"{"balance":10,"unlocked_balance":7,"blocks_to_unlock":8,
+                    "time_to_unlock":120,"multisig_import_needed":false}"#,
+            )])
+            .await,
+        );
+        let balance = get_balance(&rpc, 0).await.unwrap();
+        assert_eq!(balance.unlocked_balance, Amount(7));
+        assert_eq!(balance.locked, Amount(3));
+        assert_eq!(balance.blocks_to_unlock, 8);
+        assert_eq!(balance.time_to_unlock, 120);
+
+        // Wallet RPCs before v0.17 don't say when.
+        let rpc = RpcClient::new(
+            &crate::testing::method_daemon(&[(
+                "get_balance",
+                r#"{"balance":10,"unlocked_balance":10}"#,
+            )])
+            .await,
+        );
+        let balance = total_balance(&rpc).await.unwrap();
+        assert_eq!(balance.locked, Amount::ZERO);
+        assert_eq!(balance.blocks_to_unlock, 0);
+    }
+
+    #[tokio::test]
+    async fn test_locked_funds_are_told_from_too_little() {
+        let (stub, _) = crate::testing::stub_daemon(
+            r#"{"jsonrpc":"2.0","id":"0","result":{"balance":9,"unlocked_balance":4,"blocks_to_unlock":6}}"#,
+        )
+        .await;
+        let rpc = RpcClient::new(&stub);
+        let err = || RpcError::Server {
+            method: "transfer".into(),
+            code: -17,
+            message: "not enough unlocked money".into(),
+        };
+        let source = Source::default();
+
+        let locked = transfer_error(&rpc, err(), &[dest("4a", 7)], &source, Some(Amount(1))).await;
+        assert!(matches!(
+            locked,
+            MultisigError::Transaction(TransactionError::Locked {
+                need: 8,
+                unlocked: 4,
+                blocks_to_unlock: Some(6)
+            })
+        ));
+        assert!(
+            locked.to_string().contains("within about 6 block(s)"),
+            "{locked}"
+        );
+        // More than even the locked funds would cover, once the fee is in.
+        let short = transfer_error(&rpc, err(), &[dest("4a", 7)], &source, Some(Amount(3))).await;
+        assert!(matches!(
+            short,
+            MultisigError::Transaction(TransactionError::InsufficientBalance { need: 10, have: 4 })
+        ));
+
+        // Wallet RPCs before v0.17 don't say when.
+        let (stub, _) = crate::testing::stub_daemon(
+            r#"{"jsonrpc":"2.0","id":"0","result":{"balance":9,"unlocked_balance":4}}"#,
+        )
+        .await;
+        let rpc = RpcClient::new(&stub);
+        let locked = transfer_error(&rpc, err(), &[dest("4a", 7)], &source, None).await;
+        assert!(matches!(
+            locked,
+            MultisigError::Transaction(TransactionError::Locked {
+                blocks_to_unlock: None,
+                ..
+            })
+        ));
+        assert!(!locked.to_string().contains("block(s)"), "{locked}");
+    }
+
+    #[tokio::test]
+    async fn test_transfer_errors_are_typed() {
+        let (stub, _) = crate::testing::stub_daemon(
+            r#"{"jsonrpc":"2.0","id":"0","result":{"balance":4,"unlocked_balance":4}}"#,
+        )
+        .await;
+        let rpc = RpcClient::new(&stub);
+        let server = |code, message: &str| RpcError::Server {
+            method: "transfer".into(),
+            code,
+            message: message.into(),
+        };
+        let dests = [dest("4a", 5), dest("4b", 2)];
+        let source = Source::default();
+
+        let err = transfer_error(
+            &rpc,
+            server(-17, "not enough money"),
+            &dests,
+            &source,
+            Some(Amount(1)),
+        )
+        .await;
+        assert!(matches!(
+            err,
+            MultisigError::Transaction(TransactionError::InsufficientBalance { need: 8, have: 4 })
+        ));
+        let err = transfer_error(
+            &rpc,
+            server(-2, "WALLET_RPC_ERROR_CODE_WRONG_ADDRESS: 4bad"),
+            &dests,
+            &source,
+            None,
+        )
+        .await;
+        assert!(matches!(
+            err,
+            MultisigError::Transaction(TransactionError::InvalidAddress(a)) if a == "4bad"
+        ));
+        let err = transfer_error(&rpc, server(-4, "tx not possible"), &dests, &source, None).await;
+        assert!(matches!(err, MultisigError::Rpc(_)));
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_visibility() {
+        let (stub, hits) = crate::testing::stub_daemon(
+            r#"{"status":"OK","txs":[
+                {"tx_hash":"aa","in_pool":true},
+                {"tx_hash":"bb","in_pool":false,"block_height":90}
+            ],"missed_tx":["cc"]}"#,
+        )
+        .await;
+        let daemon = RpcClient::new(&stub);
+        let hashes = ["aa".to_string(), "bb".into(), "cc".into()];
+        assert_eq!(
+            visibility(&daemon, &hashes).await.unwrap(),
+            [
+                Visibility::InPool,
+                Visibility::Mined,
+                Visibility::NotVisible
+            ]
+        );
+
+        // Seen at once: no second poll.
+        let seen =
+            wait_for_broadcast(&daemon, &hashes[..2], std::time::Duration::from_secs(60)).await;
+        assert_eq!(seen, [Visibility::InPool, Visibility::Mined]);
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        let seen = wait_for_broadcast(&daemon, &hashes[2..], std::time::Duration::ZERO).await;
+        assert_eq!(seen, [Visibility::NotVisible]);
+    }
+
+    #[tokio::test]
+    async fn test_submit_returns_every_hash_of_a_split_set() {
+        let rpc = RpcClient::new(
+            &crate::testing::method_daemon(&[(
+                "submit_multisig",
+                r#"{"tx_hash_list":["h1","h2"]}"#,
+            )])
+            .await,
+        );
+        let result = submit_multisig_tx(&rpc, "set").await.unwrap();
+        assert_eq!(result.tx_hashes, ["h1", "h2"]);
+    }
+
+    #[tokio::test]
+    async fn test_fee_estimate_untrusted_flag() {
+        let (public, _) = crate::testing::stub_daemon(
+            r#"{"jsonrpc":"2.0","id":"0","result":{"fee":20000,"quantization_mask":10000,"untrusted":true}}"#,
+        )
+        .await;
+        let estimate = get_fee_estimate(&RpcClient::new(&public)).await.unwrap();
+        assert!(estimate.untrusted);
+        assert_eq!(estimate.fee, 20000);
+
+        let (own, _) = crate::testing::stub_daemon(
+            r#"{"jsonrpc":"2.0","id":"0","result":{"fee":20000,"untrusted":false}}"#,
+        )
+        .await;
+        assert!(
+            !get_fee_estimate(&RpcClient::new(&own))
+                .await
+                .unwrap()
+                .untrusted
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fee_schedule_follows_the_algorithm() {
+        let daemon = crate::testing::method_daemon(&[
+            (
+                "get_fee_estimate",
+                r#"{"fee":20000,"fees":[20001,80000,320000,4000000],"quantization_mask":10000}"#,
+            ),
+            ("hard_fork_info", r#"{"version":16,"enabled":true}"#),
+        ])
+        .await;
+        let schedule = fee_schedule(&RpcClient::new(&daemon)).await.unwrap();
+        assert_eq!(schedule.algorithm, FeeAlgorithm::PerPriority);
+        assert_eq!(schedule.hard_fork, Some(16));
+        assert_eq!(schedule.rate(Priority::Default), 20001);
+        assert_eq!(schedule.rate(Priority::High), 320000);
+        assert_eq!(schedule.fee(Priority::Medium, 1550), Amount(124_000_000));
+        // 31,001,550 rounded up to a multiple of the mask.
+        assert_eq!(schedule.fee(Priority::Low, 1550), Amount(31_010_000));
+
+        // A daemon without hard_fork_info or per-priority fees.
+        let daemon = crate::testing::method_daemon(&[(
+            "get_fee_estimate",
+            r#"{"fee":20000,"quantization_mask":10000}"#,
+        )])
+        .await;
+        let schedule = fee_schedule(&RpcClient::new(&daemon)).await.unwrap();
+        assert_eq!(schedule.algorithm, FeeAlgorithm::PerByte);
+        assert_eq!(schedule.hard_fork, None);
+        assert_eq!(schedule.rate(Priority::Medium), 100000);
+
+        let estimate = FeeEstimate {
+            fee: 2_000_000,
+            quantization_mask: 0,
+            fees: vec![],
+            untrusted: false,
+        };
+        let schedule = FeeSchedule::new(estimate, Some(7));
+        assert_eq!(schedule.algorithm, FeeAlgorithm::PerKilobyte);
+        // Two started kilobytes at four times the base.
+        assert_eq!(schedule.fee(Priority::Medium, 1025), Amount(16_000_000));
+    }
+
+    #[test]
+    fn test_multisig_set_detection() {
+        assert!(is_multisig_tx_set(
+            "4d6f6e65726f206d756c746973696720756e7369676e6564"
+        ));
+        assert!(is_multisig_tx_set("  4D6F6E65726F206D756C7469736967"));
+        assert!(!is_multisig_tx_set("020001020010"));
+        assert!(!is_multisig_tx_set(""));
+
+        let export = hex::encode(b"Monero multisig export\x02\x91\x07");
+        assert!(is_multisig_export(&export));
+        assert!(!is_multisig_export(&export[..20]));
+        assert!(!is_multisig_export(
+            "4d6f6e65726f206d756c746973696720756e7369676e6564"
+        ));
+    }
+
+    #[test]
+    fn test_monero_file_round_trip() {
+        let mut payload = MULTISIG_FILE_MAGIC.to_vec();
+        payload.extend([0x00, 0xff, 0x10, 0x0a, 0x0d]);
+        let tx_set = hex::encode(&payload);
+        assert!(is_multisig_tx_set(&tx_set));
+
+        let file = to_monero_file(&tx_set).unwrap();
+        assert_eq!(file, payload);
+        assert_eq!(
+            from_monero_file(&file).unwrap().as_deref(),
+            Some(tx_set.as_str())
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("multisig_monero_tx");
+        std::fs::write(&path, &file).unwrap();
+        assert_eq!(read_tx_file(&path, 1024).unwrap(), tx_set);
+        assert!(matches!(
+            read_tx_file(&path, 8),
+            Err(MultisigError::Blob(_))
+        ));
+
+        // Text files are passed through for the usual clean-up.
+        std::fs::write(&path, format!("  {tx_set}\n")).unwrap();
+        assert_eq!(read_tx_file(&path, 1024).unwrap(), tx_set);
+
+        assert!(matches!(
+            to_monero_file("abcd"),
+            Err(MultisigError::Transaction(
+                TransactionError::NotMultisigTxSet(_)
+            ))
+        ));
+    }
+
+    const MULTISIG_FIXTURE: &[u8] = include_bytes!("../tests/fixtures/multisig_monero_tx");
+    const UNSIGNED_FIXTURE: &[u8] = include_bytes!("../tests/fixtures/unsigned_monero_tx");
+    const SIGNED_FIXTURE: &[u8] = include_bytes!("../tests/fixtures/signed_monero_tx");
+
+    #[test]
+    fn test_cold_signing_file_is_refused() {
+        assert!(matches!(
+            from_monero_file(UNSIGNED_FIXTURE),
+            Err(MultisigError::Transaction(
+                TransactionError::ColdSigningFile(ColdSigningKind::Unsigned)
+            ))
+        ));
+        assert!(matches!(
+            from_monero_file(SIGNED_FIXTURE),
+            Err(MultisigError::Transaction(
+                TransactionError::ColdSigningFile(ColdSigningKind::Signed)
+            ))
+        ));
+        assert!(from_monero_file(MULTISIG_FIXTURE).unwrap().is_some());
+        assert_eq!(from_monero_file(b"4d6f6e65").unwrap(), None);
+    }
+
+    #[test]
+    fn test_cold_signing_hex_is_recognized() {
+        let unsigned = hex::encode(UNSIGNED_FIXTURE);
+        let signed = hex::encode(SIGNED_FIXTURE).to_uppercase();
+        let multisig = hex::encode(MULTISIG_FIXTURE);
+        assert_eq!(
+            ColdSigningKind::of_hex(&unsigned),
+            Some(ColdSigningKind::Unsigned)
+        );
+        assert_eq!(
+            ColdSigningKind::of_hex(&signed),
+            Some(ColdSigningKind::Signed)
+        );
+        assert_eq!(ColdSigningKind::of_hex(&multisig), None);
+        assert_eq!(ColdSigningKind::of_hex("4d6f"), None);
+
+        let err = reject_cold_signing(&unsigned).unwrap_err().to_string();
+        assert!(err.contains("unsigned_monero_tx"), "{err}");
+        assert!(err.contains("offline wallet to sign"), "{err}");
+        assert!(reject_cold_signing(&multisig).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_cold_signing_wrappers_check_the_file_kind() {
+        let rpc = RpcClient::new(
+            &crate::testing::method_daemon(&[
+                ("get_version", r#"{"version":65563}"#),
+                (
+                    "sign_transfer",
+                    r#"{"signed_txset":"5369","tx_hash_list":["aa"]}"#,
+                ),
+                ("submit_transfer", r#"{"tx_hash_list":["aa"]}"#),
+            ])
+            .await,
+        );
+        let signed = sign_cold_tx(&rpc, &hex::encode(UNSIGNED_FIXTURE))
+            .await
+            .unwrap();
+        assert_eq!(signed.signed_txset, "5369");
+        assert_eq!(signed.tx_hashes, ["aa"]);
+        assert_eq!(
+            submit_cold_tx(&rpc, &hex::encode(SIGNED_FIXTURE))
+                .await
+                .unwrap(),
+            ["aa"]
+        );
+
+        assert!(matches!(
+            sign_cold_tx(&rpc, &hex::encode(MULTISIG_FIXTURE)).await,
+            Err(MultisigError::Transaction(
+                TransactionError::NotColdSigningFile(ColdSigningKind::Unsigned)
+            ))
+        ));
+        assert!(matches!(
+            submit_cold_tx(&rpc, &hex::encode(UNSIGNED_FIXTURE)).await,
+            Err(MultisigError::Transaction(
+                TransactionError::NotColdSigningFile(ColdSigningKind::Signed)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_raw_tx_result() {
+        let spent: SendRawTransactionResponse =
+            serde_json::from_str(r#"{"status":"Failed","reason":"","double_spend":true}"#).unwrap();
+        assert!(matches!(
+            raw_tx_result(spent),
+            Err(MultisigError::Transaction(TransactionError::Rejected {
+                reason: RejectionReason::DoubleSpend,
+                detail,
+            })) if detail == "double spend"
+        ));
+
+        let low_fee: SendRawTransactionResponse =
+            serde_json::from_str(r#"{"status":"Failed","reason":"fee too low"}"#).unwrap();
+        assert!(matches!(
+            raw_tx_result(low_fee),
+            Err(MultisigError::Transaction(TransactionError::Rejected {
+                reason: RejectionReason::FeeTooLow,
+                detail,
+            })) if detail == "fee too low"
+        ));
+
+        // monerod sets the flag and leaves `reason` empty.
+        let big: SendRawTransactionResponse = serde_json::from_str(
+            r#"{"status":"Failed","reason":"","double_spend":false,"fee_too_low":false,"too_big":true,"invalid_input":false,"low_mixin":false,"not_relayed":false}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            raw_tx_result(big),
+            Err(MultisigError::Transaction(TransactionError::Rejected {
+                reason: RejectionReason::TooBig,
+                ..
+            }))
+        ));
+
+        let ok: SendRawTransactionResponse = serde_json::from_str(r#"{"status":"OK"}"#).unwrap();
+        assert!(raw_tx_result(ok).is_ok());
+    }
+
+    #[test]
+    fn test_get_transfers_response_parses() {
+        let json = r#"{
+            "in": [{"txid": "aa", "type": "in", "amount": 5, "height": 10, "timestamp": 2}],
+            "pool": [{"txid": "bb", "type": "pool", "amount": 7, "timestamp": 3}]
+        }"#;
+        let resp: GetTransfersResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.incoming[0].kind, TransferKind::In);
+        assert_eq!(resp.pool[0].confirmations, 0);
+        assert!(resp.out.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pool_payments_newest_first() {
+        let rpc = RpcClient::new(
+            &crate::testing::method_daemon(&[
+                ("get_version", r#"{"version":65563}"#),
+                (
+                    "get_transfers",
+                    r#"{"pool": [
+                        {"txid": "old", "type": "pool", "amount": 7, "timestamp": 3,
+                         "subaddr_index": {"major": 0, "minor": 2}},
+                        {"txid": "new", "type": "pool", "amount": 9, "timestamp": 8,
+                         "double_spend_seen": true}
+                    ]}"#,
+                ),
+            ])
+            .await,
+        );
+        let payments = get_pool_payments(&rpc).await.unwrap();
+        let txids: Vec<_> = payments.iter().map(|p| p.txid.as_str()).collect();
+        assert_eq!(txids, ["new", "old"]);
+        assert!(payments[0].double_spend_seen);
+        assert!(!payments[1].double_spend_seen);
+        assert_eq!(payments[1].subaddr_index.minor, 2);
+    }
+
+    #[test]
+    fn test_describe_transfer_response_parses() {
+        let json = r#"{"desc": [{
+            "recipients": [{"address": "4abc", "amount": 1000}],
+            "amount_in": 5000, "amount_out": 4900, "change_amount": 3900,
+            "change_address": "4self", "fee": 100, "ring_size": 16, "unlock_time": 0,
+            "dummy_outputs": 0, "extra": "01"
+        }]}"#;
+        let resp: DescribeTransferResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.desc[0].recipients[0].amount, 1000);
+        assert_eq!(resp.desc[0].fee, 100);
+    }
+
+    const DESCRIBE_WITH_CHANGE: &str = r#"{"jsonrpc":"2.0","id":"0","result":{"desc":[{
+        "recipients": [
+            {"address": "4cold", "amount": 1000},
+            {"address": "4sub1", "amount": 400}
+        ],
+        "change_amount": 3500, "change_address": "4self", "fee": 100
+    }]}}"#;
+
+    #[tokio::test]
+    async fn test_change_outputs_labelled_and_excluded() {
+        let (rpc, _) = crate::testing::stub_daemon(DESCRIBE_WITH_CHANGE).await;
+        let desc = describe_transfer(&RpcClient::new(&rpc), "00")
+            .await
+            .unwrap();
+        let own = vec!["4self".to_string(), "4sub1".to_string()];
+
+        let outputs = review_outputs(&desc, &own, &[]);
+        let kinds: Vec<_> = outputs.iter().map(|o| o.kind).collect();
+        assert_eq!(
+            kinds,
+            [OutputKind::Payout, OutputKind::Change, OutputKind::Change]
+        );
+        assert_eq!(outputs[2].amount, 3500);
+        assert_eq!(leaving_amount(&outputs).unwrap(), Amount(1000));
+    }
+
+    #[tokio::test]
+    async fn test_requested_own_address_is_self_transfer() {
+        let (rpc, _) = crate::testing::stub_daemon(DESCRIBE_WITH_CHANGE).await;
+        let desc = describe_transfer(&RpcClient::new(&rpc), "00")
+            .await
+            .unwrap();
+        let own = vec!["4self".to_string(), "4sub1".to_string()];
+
+        let outputs = review_outputs(&desc, &own, &["4sub1".to_string()]);
+        assert_eq!(outputs[1].kind, OutputKind::SelfTransfer);
+        assert_eq!(outputs[1].kind.to_string(), "self-transfer");
+        // Still not leaving the wallet.
+        assert_eq!(leaving_amount(&outputs).unwrap(), Amount(1000));
+    }
+
+    #[tokio::test]
+    async fn test_change_to_a_foreign_address_is_payout() {
+        let (rpc, _) = crate::testing::stub_daemon(DESCRIBE_WITH_CHANGE).await;
+        let desc = describe_transfer(&RpcClient::new(&rpc), "00")
+            .await
+            .unwrap();
+        // "4self" is not among the wallet's addresses.
+        let own = vec!["4sub1".to_string()];
+
+        let outputs = review_outputs(&desc, &own, &[]);
+        assert_eq!(outputs[2].address, "4self");
+        assert_eq!(outputs[2].kind, OutputKind::Payout);
+        assert_eq!(leaving_amount(&outputs).unwrap(), Amount(4500));
+    }
+
+    #[test]
+    fn test_without_own_addresses_everything_is_payout() {
+        let desc: DescribeTransferResponse = serde_json::from_str(
+            r#"{"desc":[{"recipients":[{"address":"4a","amount":5}],"fee":1}]}"#,
+        )
+        .unwrap();
+        let outputs = review_outputs(&desc.desc, &[], &[]);
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(leaving_amount(&outputs).unwrap(), Amount(5));
+    }
+
+    const MAINNET: &str = "4AdUndXHHZ6cfufTMvppY6JwXNouMBzSkbLYfpAV5Usx3skxNgYeYTRj5UzqtReoS44qo9mtmXCqY45DJ852K5Jv2684Rge";
+    const STAGENET: &str = "53QjBsa9cjxesZJQJfXBV5K2KvBmtgw3WXPzqsENUZzVdKR2wY96911DGendcjpn4dXeYYrf3inwHJ7XhKQNpc8jGPHc2EK";
+
+    #[test]
+    fn test_validate_address_mainnet() {
+        assert_eq!(
+            validate_address(MAINNET, Network::Mainnet).unwrap(),
+            AddressKind::Standard
+        );
+    }
+
+    #[test]
+    fn test_validate_address_wrong_prefix() {
+        let invalid = "9".to_string() + &"A".repeat(94);
+        assert!(validate_address(&invalid, Network::Mainnet).is_err());
+    }
+
+    #[test]
+    fn test_validate_address_wrong_length() {
+        let short = "4".to_string() + &"A".repeat(50);
+        assert!(validate_address(&short, Network::Mainnet).is_err());
+    }
+
+    #[test]
+    fn test_validate_address_other_networks() {
+        let subaddress = "888tNkZrPN6JsEgekjMnABU4TBzc2Dt29EPAvkRxbANsAnjyPbb3iQ1YBRk1UXcdRsiKc9dhwMVgN5S9cQUiyoogDavup3H";
+        assert_eq!(
+            validate_address(subaddress, Network::Mainnet).unwrap(),
+            AddressKind::Subaddress
+        );
+        assert!(validate_address(STAGENET, Network::Stagenet).is_ok());
+        let err = validate_address(STAGENET, Network::Mainnet).unwrap_err();
+        assert!(err.to_string().contains("stagenet address"), "{err}");
+    }
+
+    #[test]
+    fn test_destination_checked_at_construction() {
+        let dest = Destination::new(MAINNET, Amount(7), Network::Mainnet).unwrap();
+        assert_eq!(dest.address(), MAINNET);
+        assert_eq!(dest.amount(), Amount(7));
+        assert_eq!(dest.kind(), Some(AddressKind::Standard));
+
+        let typo = MAINNET.replacen("Und", "Unb", 1);
+        let err = Destination::new(&typo, Amount(7), Network::Mainnet).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"), "{err}");
+        assert!(Destination::new(STAGENET, Amount(7), Network::Mainnet).is_err());
+
+        // The tuple form takes any network but still checks the address.
+        let dest = Destination::try_from((STAGENET.to_string(), 7)).unwrap();
+        assert_eq!(dest.amount(), Amount(7));
+        assert!(Destination::try_from((typo, 7)).is_err());
+
+        // Read back from a journal, the kind is decoded again.
+        let json = serde_json::to_string(&dest).unwrap();
+        assert_eq!(json, format!(r#"{{"address":"{STAGENET}","amount":7}}"#));
+        let back: Destination = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.kind(), Some(AddressKind::Standard));
+        let garbage: Destination = serde_json::from_str(r#"{"address":"4x","amount":1}"#).unwrap();
+        assert_eq!(garbage.kind(), None);
+    }
+
+    /// A `describe_transfer` answer for a set built by a stagenet wallet.
+    const STAGENET_DESCRIPTION: &str = r#"{"desc":[{
+        "recipients":[{"address":"5BXAsDboVYEQcxEUsi761WbnJWsFRCwh1PkiGtGnUUcJTGenfCr5WEtdoXezutmPiQMsaM4zJbpdH5PMjkCt7QrXAhV8wDB","amount":1000}],
+        "change_address":"5BXAsDboVYEQcxEUsi761WbnJWsFRCwh1PkiGtGnUUcJTGenfCr5WEtdoXezutmPiQMsaM4zJbpdH5PMjkCt7QrXAhV8wDB",
+        "fee":10
+    }]}"#;
+
+    #[test]
+    fn test_check_network_refuses_cross_network_set() {
+        let resp: DescribeTransferResponse = serde_json::from_str(STAGENET_DESCRIPTION).unwrap();
+        assert!(check_network(&resp.desc, Network::Stagenet).is_ok());
+        let err = check_network(&resp.desc, Network::Mainnet).unwrap_err();
+        assert!(matches!(
+            err,
+            MultisigError::Transaction(TransactionError::WrongNetwork {
+                built_for: Network::Stagenet,
+                configured: Network::Mainnet,
+            })
+        ));
+        assert!(err
+            .to_string()
+            .contains("built for stagenet but you are configured for mainnet"));
+
+        // Nothing to go on: a description without addresses passes.
+        let empty: TransferDescription = serde_json::from_str("{}").unwrap();
+        assert!(check_network(&[empty], Network::Mainnet).is_ok());
+    }
+
+    #[test]
+    fn test_validate_address_typed_error() {
         let short = "4".to_string() + &"A".repeat(50);
-        assert!(validate_address(&short, crate::config::Network::Mainnet).is_err());
+        let err = validate_address(&short, Network::Mainnet).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::error::MultisigError::Transaction(TransactionError::InvalidAddress(_))
+        ));
     }
 }
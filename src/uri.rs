@@ -0,0 +1,250 @@
+//! `monero:` payment request URIs, as wallets and payment pages share them:
+//!
+//! ```text
+//! monero:4AdUndXHHZ6cf...?tx_amount=1.5&tx_description=Invoice%2042
+//! ```
+//!
+//! The address is the path; `tx_amount` is in XMR, and `tx_description` and
+//! `recipient_name` are percent-encoded text. As in BIP 21, which the scheme
+//! follows, unknown parameters are ignored unless they start with `req-`,
+//! which marks them as required.
+
+use thiserror::Error;
+
+use crate::transaction::{format_xmr, Amount};
+
+const SCHEME: &str = "monero:";
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum UriError {
+    #[error("not a monero: URI")]
+    NotMoneroUri,
+
+    #[error("the URI has no address")]
+    MissingAddress,
+
+    #[error("invalid tx_amount {0:?}")]
+    InvalidAmount(String),
+
+    #[error("{0} is not valid percent-encoded UTF-8")]
+    InvalidEncoding(String),
+
+    #[error("the URI requires {0}, which isn't supported")]
+    UnsupportedRequirement(String),
+
+    #[error("{0} is given more than once")]
+    DuplicateParameter(String),
+}
+
+/// What a payment request URI asks for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PaymentRequest {
+    pub address: String,
+    /// `None` leaves the amount to the payer.
+    pub amount: Option<Amount>,
+    pub description: Option<String>,
+    pub recipient_name: Option<String>,
+}
+
+/// The `monero:` URI for `request`. The amount is written in XMR without
+/// trailing zeros.
+pub fn encode(request: &PaymentRequest) -> String {
+    let mut params = Vec::new();
+    if let Some(amount) = request.amount {
+        let xmr = format_xmr(amount.0);
+        let xmr = xmr.trim_end_matches('0').trim_end_matches('.');
+        params.push(format!("tx_amount={xmr}"));
+    }
+    if let Some(description) = &request.description {
+        params.push(format!("tx_description={}", percent_encode(description)));
+    }
+    if let Some(name) = &request.recipient_name {
+        params.push(format!("recipient_name={}", percent_encode(name)));
+    }
+    let mut uri = format!("{SCHEME}{}", request.address);
+    if !params.is_empty() {
+        uri.push('?');
+        uri.push_str(&params.join("&"));
+    }
+    uri
+}
+
+/// Read a `monero:` URI. The scheme is matched in any case. The address
+/// is returned as written; checking it against a network is up to the
+/// caller.
+pub fn parse(uri: &str) -> Result<PaymentRequest, UriError> {
+    let uri = uri.trim();
+    let rest = uri
+        .get(..SCHEME.len())
+        .filter(|scheme| scheme.eq_ignore_ascii_case(SCHEME))
+        .map(|_| &uri[SCHEME.len()..])
+        .ok_or(UriError::NotMoneroUri)?;
+    // Some wallets write `monero://address`.
+    let rest = rest.strip_prefix("//").unwrap_or(rest);
+    let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+    if address.is_empty() {
+        return Err(UriError::MissingAddress);
+    }
+
+    let mut request = PaymentRequest {
+        address: address.to_string(),
+        ..PaymentRequest::default()
+    };
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = percent_decode(value).ok_or_else(|| UriError::InvalidEncoding(key.into()))?;
+        let replaced = match key {
+            "tx_amount" => {
+                let amount = value
+                    .parse::<Amount>()
+                    .map_err(|_| UriError::InvalidAmount(value.clone()))?;
+                request.amount.replace(amount).is_some()
+            }
+            "tx_description" => request.description.replace(value).is_some(),
+            "recipient_name" => request.recipient_name.replace(value).is_some(),
+            key if key.starts_with("req-") => {
+                return Err(UriError::UnsupportedRequirement(key.to_string()));
+            }
+            _ => false,
+        };
+        if replaced {
+            return Err(UriError::DuplicateParameter(key.to_string()));
+        }
+    }
+    Ok(request)
+}
+
+/// Percent-encode everything but RFC 3986's unreserved characters.
+fn percent_encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for b in text.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+/// Undo [`percent_encode`]; `None` for a bad escape or bytes that aren't
+/// UTF-8. `+` stays a plus, as RFC 3986 has it.
+fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = text
+                .get(i + 1..i + 3)
+                .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()))?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "4AdUndXHHZ6cfufTMvppY6JwXNouMBzSkbLYfpAV5Usx3skxNgYeYTRj5UzqtReoS44qo9mtmXCqY45DJ852K5Jv2684Rge";
+
+    #[test]
+    fn test_round_trip() {
+        let request = PaymentRequest {
+            address: ADDRESS.into(),
+            amount: Some(Amount(1_500_000_000_000)),
+            description: Some("Invoice #42: café & co".into()),
+            recipient_name: None,
+        };
+        let uri = encode(&request);
+        assert_eq!(
+            uri,
+            format!(
+                "monero:{ADDRESS}?tx_amount=1.5&tx_description=Invoice%20%2342%3A%20caf%C3%A9%20%26%20co"
+            )
+        );
+        assert_eq!(parse(&uri).unwrap(), request);
+
+        let whole = encode(&PaymentRequest {
+            address: ADDRESS.into(),
+            amount: Some(Amount(2_000_000_000_000)),
+            ..PaymentRequest::default()
+        });
+        assert!(whole.ends_with("?tx_amount=2"), "{whole}");
+    }
+
+    #[test]
+    fn test_scheme_in_any_case() {
+        for uri in [
+            format!("MONERO:{ADDRESS}?tx_amount=1"),
+            format!("Monero:{ADDRESS}?tx_amount=1"),
+            format!("monero://{ADDRESS}?tx_amount=1"),
+        ] {
+            let request = parse(&uri).unwrap();
+            assert_eq!(request.address, ADDRESS);
+            assert_eq!(request.amount, Some(Amount(1_000_000_000_000)));
+        }
+        assert_eq!(parse(ADDRESS), Err(UriError::NotMoneroUri));
+        assert_eq!(parse("bitcoin:1abc"), Err(UriError::NotMoneroUri));
+        assert_eq!(parse("monero:?tx_amount=1"), Err(UriError::MissingAddress));
+    }
+
+    #[test]
+    fn test_missing_amount() {
+        let request = parse(&format!("monero:{ADDRESS}")).unwrap();
+        assert_eq!(request.amount, None);
+        assert_eq!(request.description, None);
+
+        let request = parse(&format!("monero:{ADDRESS}?tx_description=rent")).unwrap();
+        assert_eq!(request.amount, None);
+        assert_eq!(request.description.as_deref(), Some("rent"));
+        assert_eq!(
+            encode(&request),
+            format!("monero:{ADDRESS}?tx_description=rent")
+        );
+    }
+
+    #[test]
+    fn test_unknown_parameters() {
+        let request = parse(&format!(
+            "monero:{ADDRESS}?tx_amount=0.25&label=shop&tx_payment_id=00&recipient_name=Bob%27s"
+        ))
+        .unwrap();
+        assert_eq!(request.amount, Some(Amount(250_000_000_000)));
+        assert_eq!(request.recipient_name.as_deref(), Some("Bob's"));
+
+        assert_eq!(
+            parse(&format!("monero:{ADDRESS}?req-escrow=1")),
+            Err(UriError::UnsupportedRequirement("req-escrow".into()))
+        );
+    }
+
+    #[test]
+    fn test_malformed_parameters() {
+        assert_eq!(
+            parse(&format!("monero:{ADDRESS}?tx_amount=1.2.3")),
+            Err(UriError::InvalidAmount("1.2.3".into()))
+        );
+        assert_eq!(
+            parse(&format!("monero:{ADDRESS}?tx_amount=1&tx_amount=2")),
+            Err(UriError::DuplicateParameter("tx_amount".into()))
+        );
+        assert_eq!(
+            parse(&format!("monero:{ADDRESS}?tx_description=%E2%28")),
+            Err(UriError::InvalidEncoding("tx_description".into()))
+        );
+        assert_eq!(
+            parse(&format!("monero:{ADDRESS}?tx_description=100%")),
+            Err(UriError::InvalidEncoding("tx_description".into()))
+        );
+        // A plus is a plus.
+        let request = parse(&format!("monero:{ADDRESS}?tx_description=a+b")).unwrap();
+        assert_eq!(request.description.as_deref(), Some("a+b"));
+    }
+}
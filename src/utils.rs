@@ -1,25 +1,239 @@
 use std::io::{self, Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-/// Read a multisig data blob from a file or stdin.
-pub fn read_multisig_data(path: Option<&Path>) -> anyhow::Result<String> {
+use thiserror::Error;
+
+use crate::armor::{self, ArmorError, BlobKind};
+use crate::config::{DataDir, FingerprintFormat};
+use crate::error::Result;
+
+/// Problems with a hex blob received from someone else.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum BlobError {
+    #[error(
+        "{len} bytes is over the {max}-byte limit (raise `max_blob_bytes` if this is expected)"
+    )]
+    TooLarge { len: usize, max: usize },
+
+    #[error("blob is empty")]
+    Empty,
+
+    #[error("odd number of hex digits ({0})")]
+    OddLength(usize),
+
+    #[error("unexpected {found:?} at position {position}: only hex digits are allowed")]
+    InvalidChar { found: char, position: usize },
+
+    #[error(
+        "unexpected {found:?} at position {position}: key exchange info is letters and digits only"
+    )]
+    InvalidInfoChar { found: char, position: usize },
+}
+
+/// Invisible characters that mail clients, chat apps and editors slip into
+/// pasted text.
+const ZERO_WIDTH: &[char] = &[
+    '\u{200b}', '\u{200c}', '\u{200d}', '\u{2060}', '\u{feff}', '\u{ad}',
+];
+
+/// What [`normalize_blob`] had to remove from pasted input.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Stripped {
+    pub line_breaks: usize,
+    pub whitespace: usize,
+    pub quote_prefixes: usize,
+    pub zero_width: usize,
+    /// Armor BEGIN/END lines with typographic dashes put right.
+    pub armor_lines: usize,
+}
+
+impl Stripped {
+    pub fn is_empty(&self) -> bool {
+        *self == Stripped::default()
+    }
+}
+
+impl std::fmt::Display for Stripped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = [
+            (self.line_breaks, "line break", "line breaks"),
+            (self.whitespace, "space", "spaces"),
+            (self.quote_prefixes, "quote prefix", "quote prefixes"),
+            (
+                self.zero_width,
+                "invisible character",
+                "invisible characters",
+            ),
+            (
+                self.armor_lines,
+                "mangled armor line",
+                "mangled armor lines",
+            ),
+        ]
+        .into_iter()
+        .filter(|(n, _, _)| *n > 0)
+        .map(|(n, one, many)| format!("{n} {}", if n == 1 { one } else { many }))
+        .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Undo what email clients and chat apps do to a pasted blob: hard wrapping,
+/// `> ` quoting, invisible characters, and typographic dashes in armor lines.
+/// Armor of `kind` is unwrapped first and only its payload is cleaned, so
+/// the armor's own line breaks are not reported. Pass `None` for blobs that
+/// are never armored. Leading and trailing whitespace is not reported either.
+pub fn normalize_blob(
+    text: &str,
+    kind: Option<BlobKind>,
+) -> std::result::Result<(String, Stripped), ArmorError> {
+    let mut stripped = Stripped::default();
+    let cleaned = clean_lines(text, &mut stripped);
+
+    let armored = match kind {
+        Some(expected) => match armor::dearmor(&cleaned)? {
+            Some(a) if a.kind == expected => Some(a.payload),
+            Some(a) => {
+                return Err(ArmorError::WrongKind {
+                    expected,
+                    found: a.kind,
+                })
+            }
+            None => None,
+        },
+        None => None,
+    };
+    let payload = armored.unwrap_or_else(|| cleaned.trim().to_string());
+
+    let mut blob = String::with_capacity(payload.len());
+    for c in payload.chars() {
+        match c {
+            '\n' => stripped.line_breaks += 1,
+            c if c.is_whitespace() => stripped.whitespace += 1,
+            c => blob.push(c),
+        }
+    }
+    Ok((blob, stripped))
+}
+
+/// The armor block in pasted `text` of any kind, cleaned up as
+/// [`normalize_blob`] would, or `None` for unarmored text or armor that
+/// doesn't parse.
+pub fn find_armor(text: &str) -> Option<armor::Armored> {
+    let cleaned = clean_lines(text, &mut Stripped::default());
+    armor::dearmor(&cleaned).ok().flatten()
+}
+
+/// `text` with invisible characters, quote prefixes and mangled armor
+/// lines put right, counting each fix in `stripped`.
+fn clean_lines(text: &str, stripped: &mut Stripped) -> String {
+    let mut lines = Vec::new();
+    for raw in text.lines() {
+        let mut line: String = raw.chars().filter(|c| !ZERO_WIDTH.contains(c)).collect();
+        stripped.zero_width += raw.chars().count() - line.chars().count();
+        let start = line.trim_start();
+        if start.starts_with(['>', '|']) {
+            line = start.trim_start_matches(['>', '|', ' ']).to_string();
+            stripped.quote_prefixes += 1;
+        }
+        if let Some(repaired) = armor::repair_boundary(&line) {
+            line = repaired;
+            stripped.armor_lines += 1;
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// Check that `data` is a plain hex string of at most `max_len` bytes. Upper
+/// and lower case digits are both accepted. Positions in errors count
+/// characters from 1.
+pub fn validate_hex_blob(data: &str, max_len: usize) -> Result<(), BlobError> {
+    if data.len() > max_len {
+        return Err(BlobError::TooLarge {
+            len: data.len(),
+            max: max_len,
+        });
+    }
+    if data.is_empty() {
+        return Err(BlobError::Empty);
+    }
+    if let Some((i, found)) = data
+        .chars()
+        .enumerate()
+        .find(|(_, c)| !c.is_ascii_hexdigit())
+    {
+        return Err(BlobError::InvalidChar {
+            found,
+            position: i + 1,
+        });
+    }
+    if !data.len().is_multiple_of(2) {
+        return Err(BlobError::OddLength(data.len()));
+    }
+    Ok(())
+}
+
+/// Check that `data` looks like key exchange info (`MultisigxV2R1...`): a
+/// non-empty run of letters and digits of at most `max_len` bytes.
+pub fn validate_key_exchange_blob(data: &str, max_len: usize) -> Result<(), BlobError> {
+    if data.len() > max_len {
+        return Err(BlobError::TooLarge {
+            len: data.len(),
+            max: max_len,
+        });
+    }
+    if data.is_empty() {
+        return Err(BlobError::Empty);
+    }
+    match data
+        .chars()
+        .enumerate()
+        .find(|(_, c)| !c.is_ascii_alphanumeric())
+    {
+        Some((i, found)) => Err(BlobError::InvalidInfoChar {
+            found,
+            position: i + 1,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Read all of `reader`, failing once more than `max_len` bytes arrive
+/// instead of buffering whatever the other end sends.
+fn read_capped(reader: impl Read, max_len: usize) -> Result<String> {
+    let mut buf = String::new();
+    reader.take(max_len as u64 + 1).read_to_string(&mut buf)?;
+    if buf.len() > max_len {
+        return Err(BlobError::TooLarge {
+            len: buf.len(),
+            max: max_len,
+        }
+        .into());
+    }
+    Ok(buf)
+}
+
+/// Read a multisig data blob of at most `max_len` bytes from a file or stdin.
+pub fn read_multisig_data(path: Option<&Path>, max_len: usize) -> Result<String> {
     match path {
         Some(p) => {
-            let data = std::fs::read_to_string(p)
-                .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", p.display()))?;
-            Ok(data.trim().to_string())
+            let file = std::fs::File::open(p).map_err(|e| {
+                io::Error::new(e.kind(), format!("failed to read {}: {e}", p.display()))
+            })?;
+            Ok(read_capped(file, max_len)?.trim().to_string())
         }
         None => {
             eprintln!("Reading multisig data from stdin (paste and press Ctrl+D)...");
-            let mut buf = String::new();
-            io::stdin().read_to_string(&mut buf)?;
-            Ok(buf.trim().to_string())
+            Ok(read_capped(io::stdin(), max_len)?.trim().to_string())
         }
     }
 }
 
 /// Write multisig data to a file or stdout.
-pub fn write_multisig_data(path: Option<&Path>, data: &str) -> anyhow::Result<()> {
+pub fn write_multisig_data(path: Option<&Path>, data: &str) -> Result<()> {
     match path {
         Some(p) => {
             if let Some(parent) = p.parent() {
@@ -36,21 +250,435 @@ pub fn write_multisig_data(path: Option<&Path>, data: &str) -> anyhow::Result<()
     Ok(())
 }
 
+/// SHA-256 of a multisig blob as lowercase hex, ignoring surrounding
+/// whitespace. Used to recognize blobs that have been seen before.
+pub fn blob_digest(data: &str) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(data.trim().as_bytes()))
+}
+
+/// Short fingerprint of a blob or address for verbal verification, in the
+/// default word encoding. See [`fingerprint_with`].
+pub fn fingerprint(data: &str) -> String {
+    fingerprint_with(data, FingerprintFormat::Words)
+}
+
+/// Short fingerprint of a blob or address derived from its SHA-256.
+///
+/// Words use the first 8 bytes of the digest (one word per byte); hex uses the
+/// first 16 bytes in 8 groups of 4 digits. Surrounding whitespace is ignored.
+pub fn fingerprint_with(data: &str, format: FingerprintFormat) -> String {
+    use sha2::{Digest, Sha256};
+    digest_fingerprint(&Sha256::digest(data.trim().as_bytes()), format)
+}
+
+/// The fingerprint of a blob known only by its [`blob_digest`], or `None` if
+/// `digest` isn't one.
+pub fn fingerprint_from_digest(digest: &str, format: FingerprintFormat) -> Option<String> {
+    let bytes = hex::decode(digest).ok().filter(|b| b.len() == 32)?;
+    Some(digest_fingerprint(&bytes, format))
+}
+
+fn digest_fingerprint(digest: &[u8], format: FingerprintFormat) -> String {
+    match format {
+        FingerprintFormat::Words => digest[..8]
+            .iter()
+            .map(|b| crate::wordlist::WORDS[*b as usize])
+            .collect::<Vec<_>>()
+            .join("-"),
+        FingerprintFormat::Hex => digest[..16]
+            .chunks(2)
+            .map(hex::encode_upper)
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+/// Check a fingerprint typed in by a user against `data`, accepting either
+/// encoding regardless of case or separator style.
+pub fn fingerprint_matches(data: &str, code: &str) -> bool {
+    let normalize = |s: &str| -> String {
+        s.chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect()
+    };
+    let code = normalize(code);
+    [FingerprintFormat::Words, FingerprintFormat::Hex]
+        .into_iter()
+        .any(|format| normalize(&fingerprint_with(data, format)) == code)
+}
+
+/// Move each of `files` that exists into a new timestamped directory under
+/// `data_dir/trash/`. Returns that directory and the paths moved into it.
+pub fn move_to_trash(data_dir: &DataDir, files: &[PathBuf]) -> io::Result<(PathBuf, Vec<PathBuf>)> {
+    let dir = timestamped_dir(&data_dir.file("trash"), "")?;
+    let moved = move_into(&dir, files)?;
+    Ok((dir, moved))
+}
+
+/// Create a new directory `parent/<prefix><UTC timestamp>`, adding a counter
+/// if one with that name already exists.
+pub fn timestamped_dir(parent: &Path, prefix: &str) -> io::Result<PathBuf> {
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut dir = parent.join(format!("{prefix}{stamp}"));
+    let mut n = 1;
+    while dir.exists() {
+        n += 1;
+        dir = parent.join(format!("{prefix}{stamp}-{n}"));
+    }
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Move each of `files` that exists into `dir`, returning the new paths.
+pub fn move_into(dir: &Path, files: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    let mut moved = Vec::new();
+    for file in files {
+        let Some(name) = file.file_name() else {
+            continue;
+        };
+        if !file.exists() {
+            continue;
+        }
+        let target = dir.join(name);
+        if std::fs::rename(file, &target).is_err() {
+            // Wallet files may live on another filesystem.
+            std::fs::copy(file, &target)?;
+            std::fs::remove_file(file)?;
+        }
+        moved.push(target);
+    }
+    Ok(moved)
+}
+
+/// Write `data` to `path` atomically: write a sibling temp file, fsync it,
+/// then rename it over the target so readers never see a partial file.
+pub fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    std::fs::create_dir_all(dir)?;
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    let tmp = dir.join(format!(".{file_name}.tmp.{}", std::process::id()));
+
+    let result = (|| {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)
+    })();
+    if result.is_err() {
+        std::fs::remove_file(&tmp).ok();
+    }
+    result
+}
+
+/// `user@host` identifying who ran the tool on this machine.
+pub fn identity() -> String {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string());
+    let host = gethostname::gethostname().to_string_lossy().into_owned();
+    format!("{user}@{host}")
+}
+
+/// Parse a duration like `30d`, `12h`, `45m`, `90s`, or `2w`.
+pub fn parse_duration(s: &str) -> std::result::Result<chrono::Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let n: i64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration \"{s}\": expected a number and unit, e.g. 30d"))?;
+    let duration = match unit {
+        "s" => chrono::Duration::try_seconds(n),
+        "m" => chrono::Duration::try_minutes(n),
+        "h" => chrono::Duration::try_hours(n),
+        "d" => chrono::Duration::try_days(n),
+        "w" => chrono::Duration::try_weeks(n),
+        _ => {
+            return Err(format!(
+                "invalid duration unit in \"{s}\": use s, m, h, d, or w"
+            ))
+        }
+    };
+    duration.ok_or_else(|| format!("invalid duration \"{s}\": too long"))
+}
+
 /// Prompt the user for confirmation before a destructive action.
 pub fn confirm(prompt: &str) -> bool {
-    eprint!("{prompt} [y/N] ");
+    let _prompt = crate::interrupt::Prompt::start();
+    let catalog = crate::messages::catalog();
+    eprint!(
+        "{prompt} {} ",
+        catalog.format(crate::messages::Msg::ConfirmYesNo, &[])
+    );
     io::stderr().flush().ok();
     let mut input = String::new();
     io::stdin().read_line(&mut input).ok();
-    matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
+    catalog.is_yes(&input)
+}
+
+/// Prompt the user for a line of text, returned trimmed.
+pub fn ask(prompt: &str) -> String {
+    let _prompt = crate::interrupt::Prompt::start();
+    eprint!("{prompt} ");
+    io::stderr().flush().ok();
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).ok();
+    input.trim().to_string()
+}
+
+/// Like [`ask`], but `None` once stdin is closed, so a question asked again
+/// until the answer is valid doesn't spin forever.
+pub fn ask_line(prompt: &str) -> Option<String> {
+    let _prompt = crate::interrupt::Prompt::start();
+    eprint!("{prompt} ");
+    io::stderr().flush().ok();
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(input.trim().to_string()),
+    }
+}
+
+/// Ask the user to type `expected` back exactly; anything else declines.
+pub fn confirm_typed(prompt: &str, expected: &str) -> bool {
+    let _prompt = crate::interrupt::Prompt::start();
+    eprint!("{prompt}\n> ");
+    io::stderr().flush().ok();
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).ok();
+    input.trim() == expected
+}
+
+/// Characters [`abbreviate_hex`] keeps at each end by default.
+pub const ABBREVIATE_KEEP: usize = 8;
+
+/// Abbreviate a hex string for display, keeping `keep` characters (default
+/// [`ABBREVIATE_KEEP`]) at each end. Armored input is abbreviated by its
+/// payload, so the headers don't crowd out the data.
+pub fn abbreviate_hex(hex: &str, keep: Option<usize>) -> String {
+    let keep = keep.unwrap_or(ABBREVIATE_KEEP);
+    match crate::armor::dearmor(hex) {
+        Ok(Some(armored)) => abbreviate_middle(&armored.payload, keep, keep),
+        _ => abbreviate_middle(hex, keep, keep),
+    }
+}
+
+/// Shorten `s` to its first `head` and last `tail` characters joined by
+/// `...`. Counts characters, not bytes, so any UTF-8 input is safe. Strings
+/// that abbreviating would shorten by less than two characters are returned
+/// whole.
+pub fn abbreviate_middle(s: &str, head: usize, tail: usize) -> String {
+    let count = s.chars().count();
+    if count <= head + tail + 4 {
+        return s.to_string();
+    }
+    let start: String = s.chars().take(head).collect();
+    let end: String = s.chars().skip(count - tail).collect();
+    format!("{start}...{end}")
+}
+
+/// How long an operation may run before a spinner appears.
+pub const PROGRESS_DELAY: Duration = Duration::from_secs(2);
+
+const SPINNER_FRAMES: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+#[derive(Default)]
+struct ProgressState {
+    message: String,
+    drawn: bool,
+    done: bool,
+}
+
+/// A stderr spinner for long-running operations.
+///
+/// Nothing is drawn until [`PROGRESS_DELAY`] has passed, so fast calls stay
+/// silent. The spinner only ever writes to stderr and clears its line when
+/// dropped. Must be created inside a tokio runtime.
+pub struct Progress {
+    state: Arc<Mutex<ProgressState>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl Progress {
+    /// Start a spinner labelled with `label` (typically the RPC method name).
+    pub fn start(label: &str) -> Self {
+        let state = Arc::new(Mutex::new(ProgressState::default()));
+        let label = label.to_string();
+        let started = Instant::now();
+
+        let task = tokio::spawn({
+            let state = Arc::clone(&state);
+            async move {
+                tokio::time::sleep(PROGRESS_DELAY).await;
+                let mut frame = 0;
+                loop {
+                    {
+                        let mut state = state.lock().unwrap();
+                        if state.done {
+                            return;
+                        }
+                        let line = render_progress_line(
+                            SPINNER_FRAMES[frame % SPINNER_FRAMES.len()],
+                            &label,
+                            started.elapsed(),
+                            &state.message,
+                        );
+                        eprint!("\r\x1b[2K{line}");
+                        io::stderr().flush().ok();
+                        state.drawn = true;
+                    }
+                    frame += 1;
+                    tokio::time::sleep(Duration::from_millis(120)).await;
+                }
+            }
+        });
+
+        Self { state, task }
+    }
+
+    /// Replace the detail text shown after the elapsed time.
+    pub fn set_message(&self, message: impl Into<String>) {
+        self.state.lock().unwrap().message = message.into();
+    }
 }
 
-/// Abbreviate a hex string for display (first 8 + last 8 chars).
-pub fn abbreviate_hex(hex: &str) -> String {
-    if hex.len() <= 20 {
-        hex.to_string()
-    } else {
-        format!("{}...{}", &hex[..8], &hex[hex.len() - 8..])
+impl Drop for Progress {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap();
+        state.done = true;
+        if state.drawn {
+            eprint!("\r\x1b[2K");
+            io::stderr().flush().ok();
+        }
+        self.task.abort();
+    }
+}
+
+fn render_progress_line(frame: char, label: &str, elapsed: Duration, message: &str) -> String {
+    let mut line = format!("{frame} {label} ({}s)", elapsed.as_secs());
+    if !message.is_empty() {
+        line.push_str(" — ");
+        line.push_str(message);
+    }
+    line
+}
+
+/// Render an error and all of its sources as a single `a: b: c` line.
+pub fn error_chain(err: &dyn std::error::Error) -> String {
+    let mut out = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        out.push_str(": ");
+        out.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    out
+}
+
+/// JSON object keys whose values must never reach a log sink.
+const SENSITIVE_KEYS: &[&str] = &[
+    "password",
+    "key",
+    "seed",
+    "viewkey",
+    "view_key",
+    "spendkey",
+    "spend_key",
+    "secret",
+];
+
+/// Replace the values of sensitive keys (passwords, keys, seeds) in a JSON
+/// value with a placeholder, recursing through nested objects and arrays.
+pub fn redact_json(value: &serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| {
+                    let v = if SENSITIVE_KEYS.contains(&k.to_lowercase().as_str()) {
+                        Value::String("<redacted>".to_string())
+                    } else {
+                        redact_json(v)
+                    };
+                    (k.clone(), v)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact_json).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Arguments longer than this are recorded by size and fingerprint.
+const LONG_ARG: usize = 100;
+
+/// Short flags whose value is redacted. `-p` is `--password`, except in the
+/// build commands, where it is `--priority` and is redacted all the same.
+const SENSITIVE_SHORT: &[char] = &['p'];
+
+/// Command line arguments fit for a log: values of sensitive flags
+/// (`--password x`, `--password=x`, `-p x`, `-px`) are replaced, and blobs
+/// are shortened to their size and fingerprint.
+pub fn redact_args(args: impl IntoIterator<Item = String>) -> Vec<String> {
+    let sensitive = |flag: &str| {
+        let name = flag
+            .trim_start_matches('-')
+            .replace('-', "_")
+            .to_lowercase();
+        SENSITIVE_KEYS.contains(&name.as_str())
+    };
+    let mut out = Vec::new();
+    let mut redact_next = false;
+    for arg in args {
+        if std::mem::take(&mut redact_next) {
+            out.push("<redacted>".to_string());
+            continue;
+        }
+        if arg.starts_with("--") {
+            match arg.split_once('=') {
+                Some((flag, _)) if sensitive(flag) => {
+                    out.push(format!("{flag}=<redacted>"));
+                    continue;
+                }
+                None => redact_next = sensitive(&arg),
+                _ => {}
+            }
+        } else if let Some(flags) = arg.strip_prefix('-') {
+            // A cluster such as `-yp`: whatever follows the flag is its value.
+            if let Some(at) = flags.find(SENSITIVE_SHORT) {
+                let (flag, value) = arg.split_at(at + 2);
+                if !value.is_empty() {
+                    out.push(format!("{flag}<redacted>"));
+                    continue;
+                }
+                redact_next = true;
+            }
+        }
+        if arg.chars().count() > LONG_ARG {
+            out.push(format!(
+                "<{} chars, {}>",
+                arg.chars().count(),
+                fingerprint(&arg)
+            ));
+        } else {
+            out.push(arg);
+        }
+    }
+    out
+}
+
+/// Redact a raw RPC payload for logging. Payloads that aren't valid JSON are
+/// replaced entirely, since we can't tell which parts are sensitive.
+pub fn redact_payload(payload: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(payload) {
+        Ok(value) => redact_json(&value).to_string(),
+        Err(_) => format!("<unparseable payload, {} bytes>", payload.len()),
     }
 }
 
@@ -58,16 +686,319 @@ pub fn abbreviate_hex(hex: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_validate_hex_blob_accepts_mixed_case() {
+        assert_eq!(validate_hex_blob("deadBEEF0a", 10), Ok(()));
+        assert_eq!(validate_hex_blob("ABCDEF", 64), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_hex_blob_rejects_odd_length() {
+        assert_eq!(validate_hex_blob("abcde", 64), Err(BlobError::OddLength(5)));
+    }
+
+    #[test]
+    fn test_validate_hex_blob_rejects_oversized() {
+        assert_eq!(
+            validate_hex_blob(&"ab".repeat(6), 11),
+            Err(BlobError::TooLarge { len: 12, max: 11 })
+        );
+        assert_eq!(validate_hex_blob("", 10), Err(BlobError::Empty));
+    }
+
+    #[test]
+    fn test_validate_hex_blob_reports_position() {
+        assert_eq!(
+            validate_hex_blob("ab cd", 64),
+            Err(BlobError::InvalidChar {
+                found: ' ',
+                position: 3
+            })
+        );
+        let err = validate_hex_blob("abcd\u{7}f", 64).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unexpected '\\u{7}' at position 5: only hex digits are allowed"
+        );
+        assert_eq!(
+            validate_hex_blob("aé00", 64),
+            Err(BlobError::InvalidChar {
+                found: 'é',
+                position: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_normalize_email_wrapped_and_quoted() {
+        let blob = "ab".repeat(60);
+        let mail = format!(
+            "> {}\r\n> {}\r\n>\u{200b} {}\r\n",
+            &blob[..72],
+            &blob[72..110],
+            &blob[110..]
+        );
+        let (clean, stripped) = normalize_blob(&mail, Some(BlobKind::TxSet)).unwrap();
+        assert_eq!(clean, blob);
+        assert_eq!(stripped.quote_prefixes, 3);
+        assert_eq!(stripped.line_breaks, 2);
+        assert_eq!(stripped.zero_width, 1);
+        assert_eq!(
+            stripped.to_string(),
+            "2 line breaks, 3 quote prefixes, 1 invisible character"
+        );
+        validate_hex_blob(&clean, 1024).unwrap();
+    }
+
+    #[test]
+    fn test_normalize_quoted_armor_with_smart_dashes() {
+        let payload = "cd".repeat(50);
+        let armored = armor::armor(BlobKind::TxSet, &[("Comment", "from bob")], &payload);
+        let mangled: String = armored
+            .replace("-----", "\u{2014}\u{2013}")
+            .lines()
+            .map(|l| format!("> > {l}\n"))
+            .collect();
+        let (clean, stripped) = normalize_blob(&mangled, Some(BlobKind::TxSet)).unwrap();
+        assert_eq!(clean, payload);
+        assert_eq!(stripped.armor_lines, 2);
+        assert_eq!(stripped.quote_prefixes, mangled.lines().count());
+        // The armor's own wrapping is expected and not reported.
+        assert_eq!(stripped.line_breaks, 0);
+
+        assert!(matches!(
+            normalize_blob(&mangled, Some(BlobKind::MultisigInfo)),
+            Err(ArmorError::WrongKind { .. })
+        ));
+    }
+
+    #[test]
+    fn test_normalize_key_exchange_info() {
+        let info = "MultisigxV2R1C9Bd2LDUR3Ab4Hv5bjWEqE8dZ5Kt2ZaLVp";
+        let (clean, stripped) = normalize_blob(info, None).unwrap();
+        assert_eq!(clean, info);
+        assert!(stripped.is_empty());
+
+        let pasted = format!("\u{feff}{} {}\n", &info[..20], &info[20..]);
+        let (clean, stripped) = normalize_blob(&pasted, None).unwrap();
+        assert_eq!(clean, info);
+        assert_eq!(stripped.to_string(), "1 space, 1 invisible character");
+        validate_key_exchange_blob(&clean, 1024).unwrap();
+        assert_eq!(
+            validate_key_exchange_blob("Multisig-x", 1024),
+            Err(BlobError::InvalidInfoChar {
+                found: '-',
+                position: 9
+            })
+        );
+    }
+
+    #[test]
+    fn test_read_capped_stops_at_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big.txset");
+        std::fs::write(&path, "ab".repeat(1000)).unwrap();
+
+        assert_eq!(read_multisig_data(Some(&path), 2000).unwrap().len(), 2000);
+        let err = read_multisig_data(Some(&path), 1999).unwrap_err();
+        assert!(
+            err.to_string().contains("over the 1999-byte limit"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_blob_digest_ignores_whitespace() {
+        assert_eq!(blob_digest("abc"), blob_digest("  abc\n"));
+        assert_eq!(
+            blob_digest("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_words_pinned() {
+        assert_eq!(
+            fingerprint("abc"),
+            fingerprint_with("abc", FingerprintFormat::Words)
+        );
+        assert_eq!(
+            fingerprint("MultisigV1example"),
+            "baker-zenith-planet-hazel-denim-bingo-yogurt-window"
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_hex_pinned() {
+        assert_eq!(
+            fingerprint_with("abc", FingerprintFormat::Hex),
+            "BA78 16BF 8F01 CFEA 4141 40DE 5DAE 2223"
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_matches_either_encoding() {
+        let words = fingerprint("blob");
+        let hex = fingerprint_with("blob", FingerprintFormat::Hex);
+        assert!(fingerprint_matches(
+            "blob",
+            &words.to_uppercase().replace('-', " ")
+        ));
+        assert!(fingerprint_matches("  blob\n", &hex.to_lowercase()));
+        assert!(!fingerprint_matches("other", &words));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30d").unwrap(), chrono::Duration::days(30));
+        assert_eq!(parse_duration("12h").unwrap(), chrono::Duration::hours(12));
+        assert!(parse_duration("30").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("3y").is_err());
+        assert!(parse_duration("99999999999999w").is_err());
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("file.json");
+        write_atomic(&path, b"one").unwrap();
+        write_atomic(&path, b"two").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "two");
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_move_to_trash() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = DataDir::new(tmp.path());
+        let state = dir.file("wallet_state.json");
+        std::fs::write(&state, "{}").unwrap();
+        let missing = dir.file("journal.json");
+
+        let (trash, moved) = move_to_trash(&dir, &[state.clone(), missing]).unwrap();
+        assert!(trash.starts_with(dir.file("trash")));
+        assert_eq!(moved, vec![trash.join("wallet_state.json")]);
+        assert!(!state.exists());
+        assert_eq!(std::fs::read_to_string(&moved[0]).unwrap(), "{}");
+
+        // A second reset in the same second gets its own directory.
+        let (again, moved) = move_to_trash(&dir, &[]).unwrap();
+        assert_ne!(again, trash);
+        assert!(moved.is_empty());
+    }
+
+    #[test]
+    fn test_render_progress_line() {
+        let line = render_progress_line('⠋', "refresh", Duration::from_millis(3500), "");
+        assert_eq!(line, "⠋ refresh (3s)");
+        let line = render_progress_line('⠋', "refresh", Duration::from_secs(12), "scanned block 5");
+        assert_eq!(line, "⠋ refresh (12s) — scanned block 5");
+    }
+
+    #[test]
+    fn test_redact_nested_secrets() {
+        let payload = r#"{"params":{"password":"hunter2","info":["a"]},"result":{"key":"abcd"}}"#;
+        let redacted = redact_payload(payload);
+        assert!(!redacted.contains("hunter2"));
+        assert!(!redacted.contains("abcd"));
+        assert!(redacted.contains(r#""info":["a"]"#));
+    }
+
+    #[test]
+    fn test_redact_unparseable() {
+        assert_eq!(redact_payload("not json"), "<unparseable payload, 8 bytes>");
+    }
+
+    #[test]
+    fn test_redact_args() {
+        let blob = "ab".repeat(80);
+        let args = [
+            "exchange-keys",
+            "--password",
+            "hunter2",
+            "--view-key=abc",
+            "--info",
+            &blob,
+            "--label",
+            "vault",
+        ];
+        let redacted = redact_args(args.map(String::from));
+        assert_eq!(
+            redacted[..4],
+            [
+                "exchange-keys",
+                "--password",
+                "<redacted>",
+                "--view-key=<redacted>"
+            ]
+        );
+        assert_eq!(redacted[5], format!("<160 chars, {}>", fingerprint(&blob)));
+        assert_eq!(redacted[6..], ["--label", "vault"]);
+
+        let short = ["import-wallet", "-p", "x", "-px", "-yp", "y", "-l", "vault"];
+        assert_eq!(
+            redact_args(short.map(String::from)),
+            [
+                "import-wallet",
+                "-p",
+                "<redacted>",
+                "-p<redacted>",
+                "-yp",
+                "<redacted>",
+                "-l",
+                "vault"
+            ]
+        );
+    }
+
     #[test]
     fn test_abbreviate_short() {
-        assert_eq!(abbreviate_hex("abcdef"), "abcdef");
+        assert_eq!(abbreviate_hex("abcdef", None), "abcdef");
+        assert_eq!(abbreviate_hex("", None), "");
+        assert_eq!(abbreviate_middle("", 0, 0), "");
     }
 
     #[test]
     fn test_abbreviate_long() {
         let long = "a]".repeat(20);
-        let result = abbreviate_hex(&long);
+        let result = abbreviate_hex(&long, None);
         assert!(result.contains("..."));
         assert_eq!(result.len(), 8 + 3 + 8);
+        assert_eq!(abbreviate_hex(&long, Some(2)), "a]...a]");
+    }
+
+    #[test]
+    fn test_abbreviate_exactly_20_chars() {
+        let twenty = "0123456789abcdef0123";
+        assert_eq!(abbreviate_hex(twenty, None), twenty);
+        assert_eq!(
+            abbreviate_hex(&format!("{twenty}4"), None),
+            "01234567...def01234"
+        );
+    }
+
+    #[test]
+    fn test_abbreviate_multibyte() {
+        let s = "🦀".repeat(30);
+        assert_eq!(
+            abbreviate_hex(&s, None),
+            format!("{}...{}", "🦀".repeat(8), "🦀".repeat(8))
+        );
+        assert_eq!(abbreviate_middle("ab🦀cdefghij🦀k", 3, 2), "ab🦀...🦀k");
+    }
+
+    #[test]
+    fn test_abbreviate_armored_payload() {
+        let payload = "ab".repeat(100);
+        let text = crate::armor::armor(
+            crate::armor::BlobKind::TxSet,
+            &[("Comment", "from alice")],
+            &payload,
+        );
+        assert_eq!(abbreviate_hex(&text, None), "abababab...abababab");
     }
 }
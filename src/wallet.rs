@@ -1,8 +1,36 @@
-use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
-use crate::config::RpcClient;
+use crate::capabilities::MultisigApi;
+use crate::config::{DataDir, FingerprintFormat, RpcClient};
+use crate::error::{MultisigError, Result, WalletError};
+use crate::rpc::methods::{
+    AddAddressBook, AddAddressBookRequest, CloseWallet, CloseWalletRequest, CreateAccount,
+    CreateAccountRequest, CreateAddress, CreateAddressRequest, EditAddressBook,
+    EditAddressBookRequest, ExchangeMultisigKeys, ExchangeMultisigKeysRequest, FinalizeMultisig,
+    FinalizeMultisigRequest, GenerateFromKeys, GenerateFromKeysRequest, GetAccountTags,
+    GetAccountTagsRequest, GetAccounts, GetAccountsRequest, GetAddress, GetAddressBook,
+    GetAddressBookRequest, GetAddressRequest, GetBlockHeaderByHeight,
+    GetBlockHeaderByHeightRequest, GetHeight, GetHeightRequest, GetInfo, GetInfoRequest,
+    GetInfoResponse, IsMultisig, IsMultisigRequest, KeyType, MakeMultisig, MakeMultisigRequest,
+    PrepareMultisig, PrepareMultisigRequest, QueryKey, QueryKeyRequest, Refresh, RefreshRequest,
+    RescanSpent, RescanSpentRequest, SetAccountTagDescription, SetAccountTagDescriptionRequest,
+    TagAccounts, TagAccountsRequest, UntagAccounts, UntagAccountsRequest,
+};
+use crate::rpc::{batch_call, RpcMethod};
+use crate::secret::Secret;
+use crate::transaction::Amount;
+
+/// Smallest threshold accepted. A 1-of-N wallet isn't meaningful multisig,
+/// and the wallet RPC handles it inconsistently.
+pub const MIN_THRESHOLD: u32 = 2;
+
+/// Largest number of participants the wallet RPC supports in practice.
+pub const MAX_PARTICIPANTS: u32 = 16;
+
+/// Key exchange round count from which `create-wallet` warns.
+pub const MANY_ROUNDS: u32 = 4;
 
 /// Parameters for creating a new multisig wallet.
 #[derive(Debug, Clone)]
@@ -13,22 +41,166 @@ pub struct MultisigParams {
     pub total: u32,
     /// Human-readable label for this wallet.
     pub label: String,
+    /// Names of the other participants; empty if none were given.
+    pub roster: Vec<String>,
 }
 
 impl MultisigParams {
+    /// Check `threshold` and `total` against what the wallet RPC supports:
+    /// `MIN_THRESHOLD <= threshold <= total <= MAX_PARTICIPANTS`.
     pub fn new(threshold: u32, total: u32, label: String) -> Result<Self> {
-        anyhow::ensure!(threshold >= 1, "threshold must be at least 1");
-        anyhow::ensure!(
-            threshold <= total,
-            "threshold ({threshold}) cannot exceed total participants ({total})"
-        );
-        anyhow::ensure!(total >= 2, "multisig requires at least 2 participants");
+        let broken = if total < 2 {
+            Some("participants must be at least 2".to_string())
+        } else if total > MAX_PARTICIPANTS {
+            Some(format!("participants must be at most {MAX_PARTICIPANTS}"))
+        } else if threshold < MIN_THRESHOLD {
+            Some(format!("threshold must be at least {MIN_THRESHOLD}"))
+        } else if threshold > total {
+            Some("threshold cannot exceed participants".to_string())
+        } else {
+            None
+        };
+        if let Some(rule) = broken {
+            return Err(WalletError::InvalidParams(format!(
+                "{rule} (got threshold {threshold}, participants {total})"
+            ))
+            .into());
+        }
         Ok(Self {
             threshold,
             total,
             label,
+            roster: Vec::new(),
         })
     }
+
+    /// Key exchange rounds this wallet needs, see [`key_exchange_rounds`].
+    pub fn exchange_rounds(&self) -> u32 {
+        key_exchange_rounds(self.threshold, self.total)
+    }
+
+    /// Name the other participants, one for each besides ourselves.
+    pub fn with_roster(mut self, roster: Vec<String>) -> Result<Self> {
+        if roster.is_empty() {
+            return Ok(self);
+        }
+        if roster.len() as u32 != self.total - 1 {
+            return Err(WalletError::InvalidParams(format!(
+                "a {}-participant wallet needs {} --participant names (everyone but you), got {}",
+                self.total,
+                self.total - 1,
+                roster.len()
+            ))
+            .into());
+        }
+        for (i, name) in roster.iter().enumerate() {
+            if name.trim().is_empty() || name == ME {
+                return Err(WalletError::InvalidParams(format!(
+                    "invalid participant name \"{name}\""
+                ))
+                .into());
+            }
+            if roster[..i].contains(name) {
+                return Err(WalletError::InvalidParams(format!(
+                    "participant \"{name}\" is named twice"
+                ))
+                .into());
+            }
+        }
+        self.roster = roster;
+        Ok(self)
+    }
+}
+
+/// The roster name for the local participant.
+pub const ME: &str = "me";
+
+/// Why a peer info blob can't be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeerInfoProblem {
+    /// It isn't key exchange info.
+    Format(String),
+    /// It doesn't match the fingerprint given for it.
+    Fingerprint { expected: String },
+    /// The same blob as an earlier one, numbered from 1.
+    Duplicate { of: usize },
+    /// This wallet's own info, sent back.
+    OwnInfo,
+    /// The `--from` name given for it is not in the roster or used twice.
+    Roster(String),
+    /// It was received before from someone else.
+    Mismatch { previous: String, round: u32 },
+    /// The wallet RPC refuses it.
+    Rejected(String),
+}
+
+impl std::fmt::Display for PeerInfoProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PeerInfoProblem::Format(e) => write!(f, "{e}"),
+            PeerInfoProblem::Fingerprint { expected } => write!(
+                f,
+                "fingerprint mismatch: expected {expected}, it may have been altered in transit"
+            ),
+            PeerInfoProblem::Duplicate { of } => write!(f, "same blob as #{of}"),
+            PeerInfoProblem::OwnInfo => write!(f, "this is your own info, not a peer's"),
+            PeerInfoProblem::Roster(e) => write!(f, "{e}"),
+            PeerInfoProblem::Mismatch { previous, round } => {
+                write!(f, "received from \"{previous}\" in round {round}")
+            }
+            PeerInfoProblem::Rejected(e) => write!(f, "the wallet RPC rejects it: {e}"),
+        }
+    }
+}
+
+/// The verdict on one peer info blob, from [`StateFile::check_peer_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerInfoCheck {
+    /// Position among the blobs passed, from 1.
+    pub index: usize,
+    /// Who it came from: the `--from` name, or who it was received from
+    /// before.
+    pub from: Option<String>,
+    pub problem: Option<PeerInfoProblem>,
+}
+
+impl PeerInfoCheck {
+    /// "#2 (bob)", or "#2" when the sender isn't known.
+    pub fn label(&self) -> String {
+        match &self.from {
+            Some(name) => format!("#{} ({name})", self.index),
+            None => format!("#{}", self.index),
+        }
+    }
+}
+
+/// Fail with [`WalletError::BadPeerInfo`], naming the bad blobs and the good
+/// ones, if any check found a problem.
+pub fn peer_info_verdict(checks: &[PeerInfoCheck]) -> Result<()> {
+    let (bad, fine): (Vec<_>, Vec<_>) = checks.iter().partition(|c| c.problem.is_some());
+    if bad.is_empty() {
+        return Ok(());
+    }
+    let bad = bad
+        .iter()
+        .map(|c| {
+            format!(
+                "{}: {}",
+                c.label(),
+                c.problem.as_ref().expect("partitioned")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    let fine = if fine.is_empty() {
+        "none".to_string()
+    } else {
+        fine.iter()
+            .map(|c| c.label())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    Err(WalletError::BadPeerInfo { bad, fine }.into())
 }
 
 /// Represents a single participant's multisig info string produced during key
@@ -47,6 +219,9 @@ pub enum WalletState {
     Created {
         wallet_path: PathBuf,
         params: SerializableParams,
+        /// Block height to start scanning from; `None` scans from genesis.
+        #[serde(default)]
+        restore_height: Option<u64>,
     },
     /// One or more key exchange rounds completed; more rounds may be needed.
     KeyExchangeInProgress {
@@ -54,13 +229,109 @@ pub enum WalletState {
         params: SerializableParams,
         rounds_completed: u32,
         rounds_required: u32,
+        #[serde(default)]
+        restore_height: Option<u64>,
     },
     /// All key exchange rounds finished — wallet is ready for use.
     Ready {
         wallet_path: PathBuf,
         address: String,
         params: SerializableParams,
+        #[serde(default)]
+        restore_height: Option<u64>,
     },
+    /// A view-only copy of a multisig wallet that can see incoming funds but
+    /// never build or sign transactions.
+    WatchOnly {
+        wallet_path: PathBuf,
+        address: String,
+        label: String,
+        #[serde(default)]
+        restore_height: Option<u64>,
+    },
+}
+
+impl WalletState {
+    /// The M-of-N parameters this wallet was created with. Watch-only wallets
+    /// don't know them.
+    pub fn params(&self) -> Option<&SerializableParams> {
+        match self {
+            WalletState::Created { params, .. }
+            | WalletState::KeyExchangeInProgress { params, .. }
+            | WalletState::Ready { params, .. } => Some(params),
+            WalletState::WatchOnly { .. } => None,
+        }
+    }
+
+    fn params_mut(&mut self) -> Option<&mut SerializableParams> {
+        match self {
+            WalletState::Created { params, .. }
+            | WalletState::KeyExchangeInProgress { params, .. }
+            | WalletState::Ready { params, .. } => Some(params),
+            WalletState::WatchOnly { .. } => None,
+        }
+    }
+
+    /// Where the wallet file was recorded at creation.
+    pub fn wallet_path(&self) -> &Path {
+        match self {
+            WalletState::Created { wallet_path, .. }
+            | WalletState::KeyExchangeInProgress { wallet_path, .. }
+            | WalletState::Ready { wallet_path, .. }
+            | WalletState::WatchOnly { wallet_path, .. } => wallet_path,
+        }
+    }
+
+    /// The human-readable label of this wallet.
+    pub fn label(&self) -> &str {
+        match self {
+            WalletState::Created { params, .. }
+            | WalletState::KeyExchangeInProgress { params, .. }
+            | WalletState::Ready { params, .. } => &params.label,
+            WalletState::WatchOnly { label, .. } => label,
+        }
+    }
+
+    /// The height scanning starts from, if one was chosen at creation.
+    pub fn restore_height(&self) -> Option<u64> {
+        match self {
+            WalletState::Created { restore_height, .. }
+            | WalletState::KeyExchangeInProgress { restore_height, .. }
+            | WalletState::Ready { restore_height, .. }
+            | WalletState::WatchOnly { restore_height, .. } => *restore_height,
+        }
+    }
+
+    /// The key exchange round to run next, or `None` once setup is over.
+    pub fn next_round(&self) -> Option<u32> {
+        match self {
+            WalletState::Created { .. } => Some(1),
+            WalletState::KeyExchangeInProgress {
+                rounds_completed, ..
+            } => Some(rounds_completed + 1),
+            WalletState::Ready { .. } | WalletState::WatchOnly { .. } => None,
+        }
+    }
+
+    /// Short description of the setup stage, for messages.
+    pub fn stage(&self) -> String {
+        match self {
+            WalletState::Created { .. } => "no key exchange round done".to_string(),
+            WalletState::KeyExchangeInProgress {
+                rounds_completed, ..
+            } => format!("{rounds_completed} key exchange round(s) done"),
+            WalletState::Ready { .. } => "setup finished".to_string(),
+            WalletState::WatchOnly { .. } => "watch-only".to_string(),
+        }
+    }
+
+    /// Fail with [`WalletError::WatchOnly`] if this wallet can't sign.
+    pub fn ensure_can_sign(&self) -> Result<()> {
+        match self {
+            WalletState::WatchOnly { .. } => Err(WalletError::WatchOnly.into()),
+            _ => Ok(()),
+        }
+    }
 }
 
 /// Serializable copy of [`MultisigParams`] for persisting wallet state.
@@ -69,6 +340,20 @@ pub struct SerializableParams {
     pub threshold: u32,
     pub total: u32,
     pub label: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roster: Vec<String>,
+}
+
+/// `2-of-3 "fund" (alice, bob)`, for messages.
+fn describe_params(params: &SerializableParams) -> String {
+    let mut text = format!(
+        "{}-of-{} \"{}\"",
+        params.threshold, params.total, params.label
+    );
+    if !params.roster.is_empty() {
+        text.push_str(&format!(" ({})", params.roster.join(", ")));
+    }
+    text
 }
 
 impl From<&MultisigParams> for SerializableParams {
@@ -77,33 +362,273 @@ impl From<&MultisigParams> for SerializableParams {
             threshold: p.threshold,
             total: p.total,
             label: p.label.clone(),
+            roster: p.roster.clone(),
         }
     }
 }
 
 // ── Wallet RPC response types ───────────────────────────────────────────────
 
-#[derive(Debug, Deserialize)]
-struct PrepareMultisigResponse {
-    multisig_info: String,
+/// The wallet RPC's view of its multisig setup, from `is_multisig`.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+pub struct MultisigStatus {
+    /// Whether at least one key exchange round has been applied.
+    pub multisig: bool,
+    /// Whether key exchange is finished.
+    pub ready: bool,
+    #[serde(default)]
+    pub threshold: u32,
+    #[serde(default)]
+    pub total: u32,
+}
+
+/// One subaddress from `get_address`. Index 0 is the primary address.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subaddress {
+    #[serde(default)]
+    pub address_index: u32,
+    pub address: String,
+    #[serde(default)]
+    pub label: String,
+    /// Whether it has received anything.
+    #[serde(default)]
+    pub used: bool,
+}
+
+/// An account of the wallet, as listed by `get_accounts`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Account {
+    pub account_index: u32,
+    pub base_address: String,
+    #[serde(default)]
+    pub balance: Amount,
+    #[serde(default)]
+    pub unlocked_balance: Amount,
+    #[serde(default)]
+    pub label: String,
+    /// Its tag, from `get_account_tags`.
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+/// An account tag, as listed by `get_account_tags`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AccountTag {
+    pub tag: String,
+    /// The description set with `set_account_tag_description`.
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub accounts: Vec<u32>,
+}
+
+/// What `--account` names: an index, or a tag standing for the accounts
+/// that have it. Anything that isn't a number is a tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountSelector {
+    Index(u32),
+    Tag(String),
+}
+
+impl std::str::FromStr for AccountSelector {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("expected an account index or tag".into());
+        }
+        if s.bytes().all(|b| b.is_ascii_digit()) {
+            return s
+                .parse()
+                .map(AccountSelector::Index)
+                .map_err(|e| format!("invalid account index: {e}"));
+        }
+        Ok(AccountSelector::Tag(s.to_string()))
+    }
+}
+
+impl AccountSelector {
+    /// The accounts this selects, in index order. A tag no account has is
+    /// an error.
+    pub async fn resolve(&self, rpc: &RpcClient) -> Result<Vec<u32>> {
+        match self {
+            AccountSelector::Index(index) => Ok(vec![*index]),
+            AccountSelector::Tag(tag) => {
+                let mut accounts = account_tags(rpc)
+                    .await?
+                    .into_iter()
+                    .find(|t| t.tag == *tag)
+                    .map(|t| t.accounts)
+                    .unwrap_or_default();
+                if accounts.is_empty() {
+                    return Err(WalletError::NoSuchAccountTag(tag.clone()).into());
+                }
+                accounts.sort_unstable();
+                Ok(accounts)
+            }
+        }
+    }
+}
+
+/// The one account in `accounts`, which `selector` resolved to, for
+/// commands that work on a single account such as spends.
+pub fn single_account(selector: &AccountSelector, accounts: &[u32]) -> Result<u32> {
+    match (accounts, selector) {
+        ([only], _) => Ok(*only),
+        (_, AccountSelector::Tag(tag)) => Err(WalletError::AmbiguousAccountTag {
+            tag: tag.clone(),
+            accounts: accounts.to_vec(),
+        }
+        .into()),
+        (_, AccountSelector::Index(index)) => Ok(*index),
+    }
+}
+
+/// A saved address from the wallet's address book.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AddressBookEntry {
+    /// Position in the address book, for `edit_address_book`.
+    #[serde(default)]
+    pub index: u64,
+    pub address: String,
+    /// The name it was saved under.
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Daemon and wallet chain heights, from [`chain_info`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainInfo {
+    /// Height of the daemon's chain (number of blocks).
+    pub daemon_height: u64,
+    /// Height the wallet has scanned up to.
+    pub wallet_height: u64,
+    /// Height of the network as seen by the daemon; equal to
+    /// `daemon_height` once the daemon is synchronized.
+    pub target_height: u64,
+    /// Current network difficulty.
+    pub difficulty: u64,
+    /// Whether the daemon believes it is synchronized with the network.
+    pub synchronized: bool,
+    /// The daemon flags itself as an untrusted (public remote) node.
+    pub untrusted: bool,
 }
 
-#[derive(Debug, Deserialize)]
-struct MakeMultisigResponse {
-    address: String,
-    #[allow(dead_code)]
-    multisig_info: String,
+impl ChainInfo {
+    fn from_parts(info: GetInfoResponse, wallet_height: u64) -> Self {
+        Self {
+            daemon_height: info.height,
+            wallet_height,
+            // monerod reports a target of 0 once it has caught up.
+            target_height: info.target_height.max(info.height),
+            difficulty: info.difficulty,
+            synchronized: info.synchronized,
+            untrusted: info.untrusted,
+        }
+    }
+
+    /// How many blocks the wallet lags behind the daemon.
+    pub fn wallet_behind(&self) -> u64 {
+        self.daemon_height.saturating_sub(self.wallet_height)
+    }
+
+    /// How many blocks the daemon lags behind the network.
+    pub fn daemon_behind(&self) -> u64 {
+        self.target_height.saturating_sub(self.daemon_height)
+    }
+}
+
+/// Blocks subtracted from a date-derived restore height. Block timestamps are
+/// only loosely ordered, so scanning a day early avoids missing funds.
+pub const RESTORE_DATE_MARGIN: u64 = 720;
+
+/// Return the daemon's chain height (number of blocks).
+pub async fn daemon_height(daemon: &RpcClient) -> Result<u64> {
+    let info = daemon.invoke::<GetInfo>(GetInfoRequest {}).await?;
+    Ok(info.height)
+}
+
+/// Check that `height` is not beyond the daemon's tip.
+pub async fn validate_restore_height(daemon: &RpcClient, height: u64) -> Result<u64> {
+    let tip = daemon_height(daemon).await?;
+    if height > tip {
+        return Err(WalletError::RestoreHeightInFuture { height, tip }.into());
+    }
+    Ok(height)
+}
+
+/// Find an approximate restore height for `date` by binary searching block
+/// header timestamps on the daemon. The result is [`RESTORE_DATE_MARGIN`]
+/// blocks before the last block mined before midnight UTC on `date`.
+pub async fn restore_height_for_date(daemon: &RpcClient, date: NaiveDate) -> Result<u64> {
+    let tip = daemon_height(daemon).await?;
+    let target = date
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is a valid time")
+        .and_utc()
+        .timestamp()
+        .max(0) as u64;
+
+    let height = last_block_before(tip, target, |height| async move {
+        let resp = daemon
+            .invoke::<GetBlockHeaderByHeight>(GetBlockHeaderByHeightRequest { height })
+            .await?;
+        Ok(resp.block_header.timestamp)
+    })
+    .await?;
+
+    match height {
+        Some(h) if h + 1 >= tip => {
+            // Every block is older than the date, so the date is in the future.
+            Err(WalletError::RestoreHeightInFuture { height: h + 1, tip }.into())
+        }
+        Some(h) => Ok(h.saturating_sub(RESTORE_DATE_MARGIN)),
+        None => Ok(0),
+    }
+}
+
+/// The highest height below `tip` whose timestamp is before `target`, or
+/// `None` if even the genesis block isn't.
+async fn last_block_before<F, Fut>(tip: u64, target: u64, mut timestamp: F) -> Result<Option<u64>>
+where
+    F: FnMut(u64) -> Fut,
+    Fut: std::future::Future<Output = Result<u64>>,
+{
+    // Invariant: every height below `lo` is before `target`, and every height
+    // at or above `hi` is not.
+    let (mut lo, mut hi) = (0, tip);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if timestamp(mid).await? < target {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    Ok(lo.checked_sub(1))
 }
 
-#[derive(Debug, Deserialize)]
-struct ExchangeMultisigKeysResponse {
-    address: String,
-    multisig_info: String,
+/// Combine the daemon's `get_info` with the wallet's `get_height`. The two
+/// servers are asked at the same time.
+pub async fn chain_info(daemon: &RpcClient, wallet: &RpcClient) -> Result<ChainInfo> {
+    let (info, wallet_height) = tokio::try_join!(
+        async {
+            let info = daemon.invoke::<GetInfo>(GetInfoRequest {}).await?;
+            Ok::<_, crate::error::MultisigError>(info)
+        },
+        get_height(wallet),
+    )?;
+    Ok(ChainInfo::from_parts(info, wallet_height))
 }
 
-#[derive(Debug, Deserialize)]
-struct FinalizeMultisigResponse {
-    address: String,
+/// Result of a wallet `refresh` call.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RefreshResult {
+    /// Number of new blocks scanned.
+    pub blocks_fetched: u64,
+    /// Whether any incoming funds were found.
+    pub received_money: bool,
 }
 
 // ── Public API ──────────────────────────────────────────────────────────────
@@ -113,126 +638,2786 @@ struct FinalizeMultisigResponse {
 /// Returns the participant's multisig info string that must be shared with all
 /// other participants in the first key exchange round.
 pub async fn prepare_multisig(rpc: &RpcClient) -> Result<String> {
-    let resp: PrepareMultisigResponse = rpc
-        .request("prepare_multisig", &serde_json::json!({}))
-        .await
-        .context("prepare_multisig RPC call failed")?;
+    let resp = rpc
+        .invoke::<PrepareMultisig>(PrepareMultisigRequest {})
+        .await?;
 
     Ok(resp.multisig_info)
 }
 
-/// Perform a round of multisig key exchange with the provided info strings
-/// from other participants.
+/// Perform key exchange `round` with the provided info strings from other
+/// participants, the way `api` runs it.
 ///
-/// For an M-of-N wallet:
+/// With the [current](MultisigApi::Current) API:
 /// - 2-of-N requires a single `make_multisig` call.
-/// - M-of-N where M > 2 requires (M - 1) rounds of `exchange_multisig_keys`,
-///   followed by a final `finalize_multisig`.
+/// - M-of-N where M > 2 requires rounds of `exchange_multisig_keys`.
+///
+/// With the [legacy](MultisigApi::Legacy) API, round 1 is `make_multisig`
+/// and the rest are `exchange_multisig_keys`, except that an N-1/N wallet
+/// ends with `finalize_multisig`. Either way the result looks the same.
 pub async fn exchange_keys(
+    rpc: &RpcClient,
+    peer_info: &[String],
+    params: &SerializableParams,
+    round: u32,
+    api: MultisigApi,
+    password: &Secret<String>,
+) -> Result<KeyExchangeResult> {
+    match api {
+        MultisigApi::Current if params.threshold == 2 && round == 1 => {
+            make_multisig(rpc, peer_info, params.threshold, password).await
+        }
+        MultisigApi::Current => exchange_multisig_keys(rpc, peer_info, password).await,
+        MultisigApi::Legacy if round == 1 => {
+            make_multisig(rpc, peer_info, params.threshold, password).await
+        }
+        MultisigApi::Legacy
+            if params.threshold + 1 == params.total
+                && round >= key_exchange_rounds(params.threshold, params.total) =>
+        {
+            let address = finalize_multisig(rpc, peer_info, password).await?;
+            Ok(KeyExchangeResult::Complete { address })
+        }
+        MultisigApi::Legacy => exchange_multisig_keys(rpc, peer_info, password).await,
+    }
+}
+
+/// Find which of `peer_info` made a key exchange round fail, by sending
+/// halves of it to the wallet RPC until one blob is left. Returns its index,
+/// or `None` if the failure can't be pinned on a single blob.
+///
+/// The probes can't complete a round: they always hold fewer blobs than the
+/// round needs, and `make_multisig` is sent an impossible threshold of 0.
+/// The wallet RPC checks each blob before it looks at the count or the
+/// threshold, so a probe holding the bad blob fails differently from an
+/// empty one. Error messages are compared with digits removed, since the
+/// count ones name the number of blobs.
+pub async fn isolate_bad_blob(
+    rpc: &RpcClient,
+    peer_info: &[String],
+    params: &SerializableParams,
+    round: u32,
+    api: MultisigApi,
+    password: &Secret<String>,
+) -> Result<Option<usize>> {
+    if peer_info.len() < 2 {
+        return Ok(None);
+    }
+    let probe = |subset: Vec<String>| async move {
+        probe_key_exchange(rpc, &subset, params, round, api, password).await
+    };
+    let baseline = probe(Vec::new()).await?;
+    let pick = |indices: &[usize]| indices.iter().map(|&i| peer_info[i].clone()).collect();
+
+    let mut suspects: Vec<usize> = (0..peer_info.len()).collect();
+    while suspects.len() > 1 {
+        let (left, right) = suspects.split_at(suspects.len() / 2);
+        suspects = if probe(pick(left)).await? != baseline {
+            left.to_vec()
+        } else {
+            right.to_vec()
+        };
+    }
+    // The last half was picked by elimination; make sure it fails on its own.
+    let only = suspects[0];
+    Ok((probe(pick(&[only])).await? != baseline).then_some(only))
+}
+
+/// Send `subset` the way [`exchange_keys`] would, in a form that can't
+/// complete the round, and return how the wallet RPC refused it.
+async fn probe_key_exchange(
+    rpc: &RpcClient,
+    subset: &[String],
+    params: &SerializableParams,
+    round: u32,
+    api: MultisigApi,
+    password: &Secret<String>,
+) -> Result<String> {
+    let first = round == 1 && (api == MultisigApi::Legacy || params.threshold == 2);
+    let result = if first {
+        make_multisig(rpc, subset, 0, password).await
+    } else {
+        exchange_multisig_keys(rpc, subset, password).await
+    };
+    match result {
+        Ok(_) => Err(WalletError::ProbeCompleted.into()),
+        Err(MultisigError::Rpc(e)) if e.code().is_some() => {
+            let message = e
+                .message()
+                .unwrap_or_default()
+                .replace(|c: char| c.is_ascii_digit(), "");
+            Ok(format!("{}: {message}", e.code().unwrap_or_default()))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn make_multisig(
     rpc: &RpcClient,
     peer_info: &[String],
     threshold: u32,
-    password: &str,
+    password: &Secret<String>,
 ) -> Result<KeyExchangeResult> {
-    if threshold == 2 {
-        let resp: MakeMultisigResponse = rpc
-            .request(
-                "make_multisig",
-                &serde_json::json!({
-                    "multisig_info": peer_info,
-                    "threshold": threshold,
-                    "password": password,
-                }),
-            )
-            .await
-            .context("make_multisig RPC call failed")?;
+    let resp = rpc
+        .invoke::<MakeMultisig>(MakeMultisigRequest {
+            multisig_info: peer_info.to_vec(),
+            threshold,
+            password: password.clone(),
+        })
+        .await?;
+    Ok(round_result(resp.address, resp.multisig_info))
+}
 
-        Ok(KeyExchangeResult::Complete {
-            address: resp.address,
+async fn exchange_multisig_keys(
+    rpc: &RpcClient,
+    peer_info: &[String],
+    password: &Secret<String>,
+) -> Result<KeyExchangeResult> {
+    let resp = rpc
+        .invoke::<ExchangeMultisigKeys>(ExchangeMultisigKeysRequest {
+            multisig_info: peer_info.to_vec(),
+            password: password.clone(),
         })
+        .await?;
+    Ok(round_result(resp.address, resp.multisig_info))
+}
+
+/// A round is complete once the wallet RPC reports an address.
+fn round_result(address: String, next_info: String) -> KeyExchangeResult {
+    if address.is_empty() {
+        KeyExchangeResult::Partial { next_info }
     } else {
-        let resp: ExchangeMultisigKeysResponse = rpc
-            .request(
-                "exchange_multisig_keys",
-                &serde_json::json!({
-                    "multisig_info": peer_info,
-                    "password": password,
-                }),
-            )
-            .await
-            .context("exchange_multisig_keys RPC call failed")?;
+        KeyExchangeResult::Complete { address }
+    }
+}
 
-        if resp.address.is_empty() {
-            Ok(KeyExchangeResult::Partial {
-                next_info: resp.multisig_info,
-            })
-        } else {
-            Ok(KeyExchangeResult::Complete {
-                address: resp.address,
-            })
+/// Key exchange rounds the wallet RPC runs for an M-of-N wallet: N - M + 1.
+/// A low threshold over many participants takes the most rounds.
+pub fn key_exchange_rounds(threshold: u32, total: u32) -> u32 {
+    (total + 1).saturating_sub(threshold).max(1)
+}
+
+/// Ask the wallet RPC how far its multisig setup has got.
+pub async fn is_multisig(rpc: &RpcClient) -> Result<MultisigStatus> {
+    Ok(rpc.invoke::<IsMultisig>(IsMultisigRequest {}).await?)
+}
+
+/// Bring the state file's setup stage in line with the wallet RPC's, and
+/// settle any interrupted key exchange round. `round_done` records an
+/// undecided round as having gone through. The caller saves the state.
+pub async fn repair(rpc: &RpcClient, state: &mut StateFile, round_done: bool) -> Result<Repair> {
+    let status = is_multisig(rpc).await?;
+    let address = match &state.wallet {
+        WalletState::Created { .. } | WalletState::KeyExchangeInProgress { .. } if status.ready => {
+            Some(get_address(rpc).await?)
+        }
+        _ => None,
+    };
+    Ok(reconcile(state, status, address, round_done))
+}
+
+/// Core of [`repair`]: `address` is the wallet RPC's address, needed only
+/// when it reports setup finished and the state file doesn't.
+fn reconcile(
+    state: &mut StateFile,
+    status: MultisigStatus,
+    address: Option<String>,
+    round_done: bool,
+) -> Repair {
+    let mut repair = Repair::default();
+    let attempt = state.exchange_attempt.take();
+    let stage = state.wallet.stage();
+
+    match (&state.wallet, address) {
+        (WalletState::WatchOnly { .. }, _) => {}
+        (WalletState::Ready { .. }, _) => {
+            if !status.ready {
+                repair.unresolved.push(format!(
+                    "the state file says {stage}, but the wallet RPC's open wallet has {}; \
+                     is the right wallet open?",
+                    if status.multisig {
+                        "not finished key exchange"
+                    } else {
+                        "no multisig setup"
+                    }
+                ));
+            }
+        }
+        (_, Some(address)) if status.ready => {
+            repair.fixed.push(format!(
+                "the state file said {stage}, but the wallet RPC has finished setup: \
+                 marked ready with address {address}"
+            ));
+            let round = attempt.as_ref().map_or(0, |a| a.round);
+            state.complete_round(round, Some(address));
+        }
+        (WalletState::Created { .. }, _) if status.multisig => {
+            repair.fixed.push(format!(
+                "the state file said {stage}, but the wallet RPC has applied round 1: \
+                 recorded it"
+            ));
+            state.complete_round(1, None);
+        }
+        (WalletState::KeyExchangeInProgress { .. }, _) if !status.multisig => {
+            repair.unresolved.push(format!(
+                "the state file says {stage}, but the wallet RPC's open wallet has no \
+                 multisig setup; is the right wallet open?"
+            ));
         }
+        (WalletState::KeyExchangeInProgress { .. }, _) => {
+            // `is_multisig` can't tell intermediate rounds apart.
+            if let Some(round) = attempt
+                .as_ref()
+                .map(|a| a.round)
+                .filter(|&r| Some(r) == state.wallet.next_round())
+            {
+                if round_done {
+                    repair.fixed.push(format!(
+                        "recorded interrupted round {round} as having gone through"
+                    ));
+                    state.complete_round(round, None);
+                } else {
+                    repair.undecided_round = Some(round);
+                    state.exchange_attempt = attempt;
+                    return repair;
+                }
+            }
+        }
+        (WalletState::Created { .. }, _) => {}
+    }
+
+    if let Some(attempt) = attempt {
+        let went_through = state
+            .wallet
+            .next_round()
+            .is_none_or(|next| next > attempt.round);
+        repair.fixed.push(format!(
+            "cleared the record of interrupted round {}, which {}",
+            attempt.round,
+            if went_through {
+                "went through"
+            } else {
+                "never reached the wallet RPC"
+            }
+        ));
     }
+    repair
 }
 
 /// Finalize the multisig wallet after all intermediate exchange rounds.
 pub async fn finalize_multisig(
     rpc: &RpcClient,
     peer_info: &[String],
-    password: &str,
+    password: &Secret<String>,
 ) -> Result<String> {
-    let resp: FinalizeMultisigResponse = rpc
-        .request(
-            "finalize_multisig",
-            &serde_json::json!({
-                "multisig_info": peer_info,
-                "password": password,
-            }),
-        )
-        .await
-        .context("finalize_multisig RPC call failed")?;
+    let resp = rpc
+        .invoke::<FinalizeMultisig>(FinalizeMultisigRequest {
+            multisig_info: peer_info.to_vec(),
+            password: password.clone(),
+        })
+        .await?;
 
     Ok(resp.address)
 }
 
-/// The outcome of a single key exchange round.
-#[derive(Debug)]
-pub enum KeyExchangeResult {
-    /// More rounds are needed; share `next_info` with peers.
-    Partial { next_info: String },
-    /// Key exchange is complete; the wallet address is ready.
-    Complete { address: String },
+/// Save and close the wallet the wallet RPC has open.
+pub async fn close_wallet(rpc: &RpcClient) -> Result<()> {
+    rpc.invoke::<CloseWallet>(CloseWalletRequest {}).await?;
+    Ok(())
 }
 
-/// Persist wallet state to a JSON file inside `data_dir`.
-pub fn save_wallet_state(data_dir: &Path, state: &WalletState) -> Result<()> {
-    std::fs::create_dir_all(data_dir)?;
-    let path = data_dir.join("wallet_state.json");
-    let json = serde_json::to_string_pretty(state)?;
-    std::fs::write(&path, json)?;
-    tracing::info!("Wallet state saved to {}", path.display());
+/// Return the wallet's primary address as reported by the wallet RPC.
+pub async fn get_address(rpc: &RpcClient) -> Result<String> {
+    account_address(rpc, 0).await
+}
+
+/// Fail with [`WalletError::WrongNetwork`] if the wallet open in the wallet
+/// RPC belongs to another network than `network`. Peer key exchange info
+/// doesn't say which network it was made on, so this is the check that
+/// keeps a stagenet wallet RPC from joining a mainnet setup; an address
+/// with no recognizable prefix is let through.
+pub async fn check_network(rpc: &RpcClient, network: crate::config::Network) -> Result<()> {
+    let address = get_address(rpc).await?;
+    match crate::config::Network::of_address(&address) {
+        Some(wallet) if wallet != network => Err(WalletError::WrongNetwork {
+            wallet,
+            configured: network,
+        }
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// The base address of `account`; for account 0 that is the primary
+/// address.
+pub async fn account_address(rpc: &RpcClient, account: u32) -> Result<String> {
+    let resp = rpc
+        .invoke::<GetAddress>(GetAddressRequest {
+            account_index: account,
+            ..Default::default()
+        })
+        .await?;
+    Ok(resp.address)
+}
+
+/// The subaddresses of `account`, its base address first.
+pub async fn subaddresses(rpc: &RpcClient, account: u32) -> Result<Vec<Subaddress>> {
+    let resp = rpc
+        .invoke::<GetAddress>(GetAddressRequest {
+            account_index: account,
+            ..Default::default()
+        })
+        .await?;
+    Ok(resp.addresses)
+}
+
+/// Every entry in the wallet's address book.
+pub async fn address_book(rpc: &RpcClient) -> Result<Vec<AddressBookEntry>> {
+    let resp = rpc
+        .invoke::<GetAddressBook>(GetAddressBookRequest::default())
+        .await?;
+    Ok(resp.entries)
+}
+
+/// Save `address` in the wallet's address book under `name`.
+pub async fn add_address_book(rpc: &RpcClient, address: &str, name: &str) -> Result<u64> {
+    let resp = rpc
+        .invoke::<AddAddressBook>(AddAddressBookRequest {
+            address: address.to_string(),
+            description: name.to_string(),
+        })
+        .await?;
+    Ok(resp.index)
+}
+
+/// Point the address book entry at `index` to `address`, keeping its name.
+pub async fn set_address_book_address(rpc: &RpcClient, index: u64, address: &str) -> Result<()> {
+    rpc.invoke::<EditAddressBook>(EditAddressBookRequest {
+        index,
+        set_address: true,
+        address: Some(address.to_string()),
+        ..Default::default()
+    })
+    .await?;
     Ok(())
 }
 
-/// Load wallet state from a previously saved JSON file.
-pub fn load_wallet_state(data_dir: &Path) -> Result<WalletState> {
-    let path = data_dir.join("wallet_state.json");
-    let contents =
-        std::fs::read_to_string(&path).context("no wallet state found — run create-wallet first")?;
-    let state: WalletState = serde_json::from_str(&contents)?;
-    Ok(state)
+/// The address book entries `name` picks: the one whose description it is,
+/// ignoring case, or else every one whose description starts with it.
+pub fn lookup_address_book<'a>(
+    entries: &'a [AddressBookEntry],
+    name: &str,
+) -> Vec<&'a AddressBookEntry> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return Vec::new();
+    }
+    let exact: Vec<_> = entries
+        .iter()
+        .filter(|e| e.description.to_lowercase() == name)
+        .collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+    entries
+        .iter()
+        .filter(|e| e.description.to_lowercase().starts_with(&name))
+        .collect()
 }
 
-/// Check whether a wallet state file exists in the data directory.
-pub fn wallet_exists(data_dir: &Path) -> bool {
-    data_dir.join("wallet_state.json").exists()
+/// The wallet's accounts, in index order.
+pub async fn accounts(rpc: &RpcClient) -> Result<Vec<Account>> {
+    let resp = rpc
+        .invoke::<GetAccounts>(GetAccountsRequest::default())
+        .await?;
+    Ok(resp.subaddress_accounts)
 }
 
-/// Delete wallet state and associated data.
-pub fn delete_wallet(data_dir: &Path) -> Result<()> {
-    let path = data_dir.join("wallet_state.json");
-    if path.exists() {
-        std::fs::remove_file(&path)?;
-        tracing::info!("Wallet state removed from {}", path.display());
+/// The wallet's accounts with their tags, in index order; with `tag`, only
+/// the accounts that have it.
+pub async fn tagged_accounts(rpc: &RpcClient, tag: Option<&str>) -> Result<Vec<Account>> {
+    let tags = account_tags(rpc).await?;
+    let mut accounts: Vec<Account> = match tag {
+        None => accounts(rpc).await?,
+        // The wallet RPC's own error for an unknown tag is a generic one.
+        Some(tag) if !tags.iter().any(|t| t.tag == tag) => {
+            return Err(WalletError::NoSuchAccountTag(tag.to_string()).into());
+        }
+        Some(tag) => {
+            let resp = rpc
+                .invoke::<GetAccounts>(GetAccountsRequest {
+                    tag: Some(tag.to_string()),
+                })
+                .await?;
+            resp.subaddress_accounts
+        }
+    };
+    for account in &mut accounts {
+        account.tag = tags
+            .iter()
+            .find(|t| t.accounts.contains(&account.account_index))
+            .map(|t| t.tag.clone());
+    }
+    Ok(accounts)
+}
+
+/// The wallet's account tags.
+pub async fn account_tags(rpc: &RpcClient) -> Result<Vec<AccountTag>> {
+    let resp = rpc
+        .invoke::<GetAccountTags>(GetAccountTagsRequest {})
+        .await?;
+    Ok(resp.account_tags)
+}
+
+/// Tag `accounts` with `tag`, replacing any tag they had.
+pub async fn tag_accounts(rpc: &RpcClient, tag: &str, accounts: &[u32]) -> Result<()> {
+    rpc.invoke::<TagAccounts>(TagAccountsRequest {
+        tag: tag.to_string(),
+        accounts: accounts.to_vec(),
+    })
+    .await?;
+    Ok(())
+}
+
+/// Remove the tags of `accounts`.
+pub async fn untag_accounts(rpc: &RpcClient, accounts: &[u32]) -> Result<()> {
+    rpc.invoke::<UntagAccounts>(UntagAccountsRequest {
+        accounts: accounts.to_vec(),
+    })
+    .await?;
+    Ok(())
+}
+
+/// Set the description shown with `tag`.
+pub async fn set_account_tag_description(
+    rpc: &RpcClient,
+    tag: &str,
+    description: &str,
+) -> Result<()> {
+    rpc.invoke::<SetAccountTagDescription>(SetAccountTagDescriptionRequest {
+        tag: tag.to_string(),
+        description: description.to_string(),
+    })
+    .await?;
+    Ok(())
+}
+
+/// Add an account labelled `label`; returns its index and base address.
+pub async fn create_account(rpc: &RpcClient, label: &str) -> Result<(u32, String)> {
+    let resp = rpc
+        .invoke::<CreateAccount>(CreateAccountRequest {
+            label: label.to_string(),
+        })
+        .await?;
+    Ok((resp.account_index, resp.address))
+}
+
+/// Add a subaddress labelled `label` to `account`; returns its index and
+/// address.
+pub async fn create_subaddress(
+    rpc: &RpcClient,
+    account: u32,
+    label: &str,
+) -> Result<(u32, String)> {
+    let resp = rpc
+        .invoke::<CreateAddress>(CreateAddressRequest {
+            account_index: account,
+            label: label.to_string(),
+        })
+        .await?;
+    Ok((resp.address_index, resp.address))
+}
+
+/// Fail with [`WalletError::NoSuchAccount`] or
+/// [`WalletError::NoSuchSubaddress`] unless `account` and each of
+/// `subaddresses` exist. The wallet RPC would otherwise answer an
+/// out-of-range index with a bare "index out of bound" or, for some calls,
+/// quietly use nothing. Account 0 with no subaddresses always exists and is
+/// not queried.
+pub async fn check_account(rpc: &RpcClient, account: u32, subaddresses: &[u32]) -> Result<()> {
+    if account == 0 && subaddresses.is_empty() {
+        return Ok(());
+    }
+    // Both lookups go in one batch; the subaddress one fails when the
+    // account doesn't exist, but then only the account error is reported.
+    let mut calls = vec![batch_call::<GetAccounts>(&GetAccountsRequest::default())];
+    if !subaddresses.is_empty() {
+        calls.push(batch_call::<GetAddress>(&GetAddressRequest {
+            account_index: account,
+            ..Default::default()
+        }));
+    }
+    let mut results = rpc.request_batch(&calls).await?.into_iter();
+    let resp = from_batch::<GetAccounts>(results.next())?;
+    let count = resp.subaddress_accounts.len() as u32;
+    if account >= count {
+        return Err(WalletError::NoSuchAccount {
+            index: account,
+            count,
+        }
+        .into());
+    }
+    if let Some(&max) = subaddresses.iter().max() {
+        let resp = from_batch::<GetAddress>(results.next())?;
+        let count = resp.addresses.len() as u32;
+        if max >= count {
+            return Err(WalletError::NoSuchSubaddress {
+                account,
+                index: max,
+                count,
+            }
+            .into());
+        }
     }
     Ok(())
 }
+
+/// Methods some wallet RPC builds have for listing the wallet files in
+/// their `--wallet-dir`, tried in order. Stock monero-wallet-rpc has none.
+const WALLET_LIST_METHODS: [&str; 2] = ["get_wallet_files", "list_wallets"];
+
+/// A wallet file as the wallet RPC listed it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListedWalletFile {
+    pub filename: String,
+    pub modified: Option<DateTime<Utc>>,
+}
+
+/// The wallet files in the wallet RPC's `--wallet-dir`, or `None` if it has
+/// no method to list them. The answer may be a list of names, or of objects
+/// with a `filename` (or `name`) and an optional `mtime` in Unix seconds;
+/// `.keys` files are folded into their wallet.
+pub async fn rpc_wallet_files(rpc: &RpcClient) -> Result<Option<Vec<ListedWalletFile>>> {
+    for method in WALLET_LIST_METHODS {
+        let result = match rpc.call_raw(method, serde_json::json!({})).await {
+            Ok(result) => result,
+            Err(e) if e.is_method_not_found() => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let entries = match &result {
+            serde_json::Value::Array(entries) => entries.as_slice(),
+            serde_json::Value::Object(fields) => fields
+                .values()
+                .find_map(|v| v.as_array())
+                .map_or(&[][..], Vec::as_slice),
+            _ => &[],
+        };
+        let mut files: Vec<ListedWalletFile> = Vec::new();
+        for entry in entries {
+            let (name, mtime) = match entry {
+                serde_json::Value::String(name) => (name.as_str(), None),
+                entry => (
+                    entry["filename"]
+                        .as_str()
+                        .or_else(|| entry["name"].as_str())
+                        .unwrap_or_default(),
+                    entry["mtime"].as_i64(),
+                ),
+            };
+            let filename = wallet_file_name(Path::new(name));
+            if filename.is_empty() || files.iter().any(|f| f.filename == filename) {
+                continue;
+            }
+            files.push(ListedWalletFile {
+                filename,
+                modified: mtime.and_then(|secs| DateTime::from_timestamp(secs, 0)),
+            });
+        }
+        return Ok(Some(files));
+    }
+    Ok(None)
+}
+
+/// A wallet file this data directory has a record of: the current state's,
+/// or one kept by `delete-wallet --keep-files`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownWalletFile {
+    pub filename: String,
+    pub path: PathBuf,
+    pub label: String,
+    /// `false` for a watch-only copy; `None` for a retired wallet, whose
+    /// record doesn't say.
+    pub multisig: Option<bool>,
+    pub retired: bool,
+}
+
+/// The wallet files recorded in `data_dir`, the current one first.
+pub fn known_wallet_files(data_dir: &DataDir) -> Vec<KnownWalletFile> {
+    let mut known = Vec::new();
+    if let Ok(state) = load_state_unverified(data_dir) {
+        let path = state.wallet.wallet_path().to_path_buf();
+        known.push(KnownWalletFile {
+            filename: wallet_file_name(&path),
+            path,
+            label: state.wallet.label().to_string(),
+            multisig: Some(!matches!(state.wallet, WalletState::WatchOnly { .. })),
+            retired: false,
+        });
+    }
+    let archived = std::fs::read_dir(data_dir.file("archive"))
+        .into_iter()
+        .flatten();
+    for dir in archived.flatten() {
+        let Ok(text) = std::fs::read_to_string(dir.path().join("tombstone.json")) else {
+            continue;
+        };
+        let Ok(tombstone) = serde_json::from_str::<Tombstone>(&text) else {
+            continue;
+        };
+        for path in tombstone.kept_files {
+            if path.extension().is_some_and(|e| e == "keys") {
+                continue;
+            }
+            known.push(KnownWalletFile {
+                filename: wallet_file_name(&path),
+                path,
+                label: tombstone.label.clone(),
+                multisig: None,
+                retired: true,
+            });
+        }
+    }
+    known
+}
+
+/// One row of `list-wallet-files`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WalletFile {
+    pub filename: String,
+    /// The label of the state using the file, or `None` if no state here
+    /// knows it.
+    pub label: Option<String>,
+    pub retired: bool,
+    /// `None` when that can't be told without opening the wallet.
+    pub multisig: Option<bool>,
+    pub modified: Option<DateTime<Utc>>,
+}
+
+impl WalletFile {
+    /// Whether this may be a wallet set up elsewhere, for `adopt`.
+    pub fn adopt_candidate(&self) -> bool {
+        self.label.is_none()
+    }
+}
+
+/// Match the files the wallet RPC listed against the ones `known` here. With
+/// no listing, the known files are all there is to show. The modified time
+/// comes from the listing, else from the file if it is on this machine.
+pub fn match_wallet_files(
+    listed: Option<&[ListedWalletFile]>,
+    known: &[KnownWalletFile],
+) -> Vec<WalletFile> {
+    let local_mtime = |path: &Path| {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+        Some(DateTime::<Utc>::from(modified))
+    };
+    let Some(listed) = listed else {
+        return known
+            .iter()
+            .map(|k| WalletFile {
+                filename: k.filename.clone(),
+                label: Some(k.label.clone()),
+                retired: k.retired,
+                multisig: k.multisig,
+                modified: local_mtime(&k.path),
+            })
+            .collect();
+    };
+    listed
+        .iter()
+        .map(|file| {
+            let known = known.iter().find(|k| k.filename == file.filename);
+            WalletFile {
+                filename: file.filename.clone(),
+                label: known.map(|k| k.label.clone()),
+                retired: known.is_some_and(|k| k.retired),
+                multisig: known.and_then(|k| k.multisig),
+                modified: file
+                    .modified
+                    .or_else(|| known.and_then(|k| local_mtime(&k.path))),
+            }
+        })
+        .collect()
+}
+
+/// The wallet name of `path`: its last component, without `.keys`.
+fn wallet_file_name(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    match name.strip_suffix(".keys") {
+        Some(base) => base.to_string(),
+        None => name,
+    }
+}
+
+/// Fail with [`WalletError::AddressMismatch`] when the wallet RPC has a
+/// different wallet open than the one `state` describes. Only a finished
+/// multisig wallet has an address to compare.
+pub async fn check_open_wallet(rpc: &RpcClient, state: &WalletState) -> Result<()> {
+    let WalletState::Ready { address, .. } = state else {
+        return Ok(());
+    };
+    let rpc_address = get_address(rpc).await?;
+    if &rpc_address != address {
+        return Err(WalletError::AddressMismatch {
+            state: address.clone(),
+            rpc: rpc_address,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Every address the wallet owns: the primary address and all subaddresses
+/// of every account. Used to recognise change in a decoded tx set.
+pub async fn own_addresses(rpc: &RpcClient) -> Result<Vec<String>> {
+    let calls: Vec<_> = accounts(rpc)
+        .await?
+        .iter()
+        .map(|account| {
+            batch_call::<GetAddress>(&GetAddressRequest {
+                account_index: account.account_index,
+                ..Default::default()
+            })
+        })
+        .collect();
+    let mut addresses = Vec::new();
+    for result in rpc.request_batch(&calls).await? {
+        let resp = from_batch::<GetAddress>(Some(result))?;
+        addresses.push(resp.address);
+        addresses.extend(resp.addresses.into_iter().map(|s| s.address));
+    }
+    addresses.sort();
+    addresses.dedup();
+    Ok(addresses)
+}
+
+/// Decode one entry of an [`RpcClient::request_batch`] answer for `M`.
+fn from_batch<M: RpcMethod>(
+    result: Option<std::result::Result<serde_json::Value, crate::config::RpcError>>,
+) -> Result<M::Response> {
+    let value = result.ok_or_else(|| crate::config::RpcError::EmptyResponse {
+        method: M::NAME.to_string(),
+    })??;
+    serde_json::from_value(value).map_err(|source| {
+        crate::config::RpcError::InvalidResponse {
+            method: M::NAME.to_string(),
+            source,
+        }
+        .into()
+    })
+}
+
+/// Fetch the wallet's private view key.
+///
+/// The key grants full visibility into incoming funds; callers should only
+/// display it after explicit user confirmation. RPC logging redacts it.
+pub async fn export_view_key(rpc: &RpcClient) -> Result<Secret<String>> {
+    let resp = rpc
+        .invoke::<QueryKey>(QueryKeyRequest {
+            key_type: KeyType::ViewKey,
+        })
+        .await?;
+    Ok(resp.key)
+}
+
+/// Take over a multisig wallet set up elsewhere, such as with
+/// monero-wallet-cli, that is open in the wallet RPC: its threshold,
+/// participants and address come from the RPC. Refused unless key exchange
+/// has finished. The caller saves the state.
+pub async fn adopt(
+    rpc: &RpcClient,
+    wallet_path: PathBuf,
+    label: String,
+    roster: Vec<String>,
+) -> Result<StateFile> {
+    let status = is_multisig(rpc).await?;
+    if !status.multisig {
+        return Err(WalletError::NotMultisig.into());
+    }
+    if !status.ready {
+        return Err(WalletError::SetupUnfinished.into());
+    }
+    let params = MultisigParams::new(status.threshold, status.total, label)?.with_roster(roster)?;
+    let address = get_address(rpc).await?;
+    let state = WalletState::Ready {
+        wallet_path,
+        address,
+        params: SerializableParams::from(&params),
+        restore_height: None,
+    };
+    Ok(StateFile {
+        created_at: Some(Utc::now()),
+        ..StateFile::new(state)
+    })
+}
+
+/// Create a watch-only wallet from a multisig address and its view key via
+/// `generate_from_keys`, returning the address the wallet RPC reports.
+pub async fn create_watch_wallet(
+    rpc: &RpcClient,
+    filename: &str,
+    address: &str,
+    view_key: &Secret<String>,
+    password: &Secret<String>,
+    restore_height: u64,
+) -> Result<String> {
+    let resp = rpc
+        .invoke::<GenerateFromKeys>(GenerateFromKeysRequest {
+            filename: filename.to_string(),
+            address: address.to_string(),
+            viewkey: view_key.clone(),
+            spendkey: None,
+            password: password.clone(),
+            restore_height,
+        })
+        .await?;
+
+    if resp.address != address {
+        return Err(WalletError::InvalidParams(format!(
+            "wallet RPC created watch-only wallet for {} but {address} was requested",
+            resp.address
+        ))
+        .into());
+    }
+    Ok(resp.address)
+}
+
+/// Comparison of peers' address fingerprints against our own, produced by the
+/// `verify-address` ceremony.
+#[derive(Debug, Clone, Serialize)]
+pub struct AddressVerification {
+    pub address: String,
+    pub fingerprint: String,
+    pub threshold: u32,
+    pub total: u32,
+    pub peers: Vec<PeerFingerprint>,
+}
+
+/// One peer-supplied fingerprint and whether it matched ours.
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerFingerprint {
+    pub provided: String,
+    pub matches: bool,
+}
+
+impl AddressVerification {
+    pub fn new(
+        address: &str,
+        params: &SerializableParams,
+        peer_fingerprints: &[String],
+        format: FingerprintFormat,
+    ) -> Self {
+        Self {
+            address: address.to_string(),
+            fingerprint: crate::utils::fingerprint_with(address, format),
+            threshold: params.threshold,
+            total: params.total,
+            peers: peer_fingerprints
+                .iter()
+                .map(|code| PeerFingerprint {
+                    provided: code.clone(),
+                    matches: crate::utils::fingerprint_matches(address, code),
+                })
+                .collect(),
+        }
+    }
+
+    /// True when at least one peer fingerprint was given and all of them match.
+    pub fn all_match(&self) -> bool {
+        !self.peers.is_empty() && self.peers.iter().all(|p| p.matches)
+    }
+}
+
+/// Return the wallet's current scan height.
+pub async fn get_height(rpc: &RpcClient) -> Result<u64> {
+    let resp = rpc.invoke::<GetHeight>(GetHeightRequest {}).await?;
+    Ok(resp.height)
+}
+
+/// Rescan the blockchain for wallet transactions.
+///
+/// When the client has progress enabled, the wallet height is polled while the
+/// refresh runs so the spinner can show "scanned block X of Y". `target_height`
+/// is Y and is omitted from the message when unknown.
+pub async fn refresh(
+    rpc: &RpcClient,
+    start_height: Option<u64>,
+    target_height: Option<u64>,
+) -> Result<RefreshResult> {
+    let req = RefreshRequest { start_height };
+
+    if !rpc.progress_enabled() {
+        return Ok(rpc.invoke::<Refresh>(req).await?);
+    }
+
+    let quiet = rpc.clone().with_progress(false);
+    let progress = crate::utils::Progress::start(Refresh::NAME);
+    let call = quiet.invoke::<Refresh>(req);
+    tokio::pin!(call);
+
+    loop {
+        tokio::select! {
+            result = &mut call => return Ok(result?),
+            _ = tokio::time::sleep(crate::utils::PROGRESS_DELAY) => {
+                if let Ok(height) = get_height(&quiet).await {
+                    progress.set_message(match target_height {
+                        Some(target) => format!("scanned block {height} of {target}"),
+                        None => format!("scanned block {height}"),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Ask the wallet to re-check which of its outputs are spent, releasing
+/// outputs held by a transaction that was never broadcast. Needs a trusted
+/// daemon.
+pub async fn rescan_spent(rpc: &RpcClient) -> Result<()> {
+    rpc.invoke::<RescanSpent>(RescanSpentRequest {}).await?;
+    Ok(())
+}
+
+/// The outcome of a single key exchange round.
+#[derive(Debug)]
+pub enum KeyExchangeResult {
+    /// More rounds are needed; share `next_info` with peers.
+    Partial { next_info: String },
+    /// Key exchange is complete; the wallet address is ready.
+    Complete { address: String },
+}
+
+// ── State persistence ───────────────────────────────────────────────────────
+
+/// Current version of the on-disk state file layout.
+///
+/// Version 1 was a bare serialized [`WalletState`]; version 2 wraps it in a
+/// [`StateFile`] envelope carrying sync history. Older files are migrated on
+/// load and rewritten in the current layout on the next save.
+pub const STATE_VERSION: u32 = 2;
+
+const STATE_FILE: &str = "wallet_state.json";
+
+/// Machine-local HMAC key for the state checksum, created on first save.
+const STATE_KEY_FILE: &str = "state.key";
+
+/// Envelope field holding the hex HMAC-SHA256 of the rest of the state.
+const CHECKSUM_FIELD: &str = "checksum";
+
+/// The persisted state envelope: the wallet's setup state plus bookkeeping
+/// that applies regardless of setup stage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateFile {
+    pub version: u32,
+    pub wallet: WalletState,
+    /// Peer export blobs already passed to `import_multisig_info`.
+    #[serde(default)]
+    pub imports: Vec<ImportRecord>,
+    /// The most recent blob produced by `export_multisig_info`.
+    #[serde(default)]
+    pub last_export: Option<ExportRecord>,
+    /// Whether all peers confirmed the multisig address via `verify-address`.
+    #[serde(default)]
+    pub verified: bool,
+    /// When the address was last verified.
+    #[serde(default)]
+    pub verified_at: Option<DateTime<Utc>>,
+    /// `build-tx` has warned that the address is unverified, so it doesn't
+    /// again.
+    #[serde(default)]
+    pub unverified_warned: bool,
+    /// Wallet height and balance right after the last successful import.
+    #[serde(default)]
+    pub last_sync: Option<SyncRecord>,
+    /// A key exchange round that was started but whose result was never
+    /// saved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exchange_attempt: Option<ExchangeAttempt>,
+    /// When `create-wallet` or `create-watch-wallet` wrote this file.
+    /// Unknown for files from older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    /// Who each key exchange blob came from, ours included.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contributions: Vec<Contribution>,
+    /// The wallet RPC's multisig API when key exchange began, so later
+    /// rounds run the same way.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multisig_api: Option<MultisigApi>,
+    /// The deal recorded by `escrow init`, for escrow wallets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escrow: Option<crate::escrow::EscrowTerms>,
+    /// Our first-round multisig info from `prepare_multisig`, shown again
+    /// when `create-wallet` is re-run. Not kept by older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prepared_info: Option<String>,
+}
+
+/// A key exchange blob attributed to a roster name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Contribution {
+    pub name: String,
+    /// The key exchange round the blob was input to.
+    pub round: u32,
+    /// See [`blob_digest`](crate::utils::blob_digest).
+    pub digest: String,
+    pub received_at: DateTime<Utc>,
+}
+
+/// One roster entry as listed by `participants`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Participant {
+    pub name: String,
+    /// Rounds this participant has contributed a blob to.
+    pub rounds: Vec<u32>,
+    /// Digest of their most recent blob.
+    pub last_digest: Option<String>,
+}
+
+/// What `delete-wallet` leaves in `data_dir/archive/` once a wallet is
+/// retired.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tombstone {
+    pub label: String,
+    pub address: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub closed_at: DateTime<Utc>,
+    /// Submitted transactions in the journal.
+    pub transactions: usize,
+    /// Total they paid out, in piconero, excluding fees.
+    pub total_sent: u64,
+    /// Wallet files kept with `--keep-files`.
+    #[serde(default)]
+    pub kept_files: Vec<PathBuf>,
+}
+
+/// A snapshot taken after a successful `import_multisig_info`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncRecord {
+    pub synced_at: DateTime<Utc>,
+    pub wallet_height: u64,
+    /// Total balance in atomic units.
+    pub balance: u64,
+}
+
+/// Why the wallet's multisig sync data can't be trusted for building.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StaleSync {
+    /// No import has ever been recorded.
+    NeverSynced,
+    /// The last import is older than the configured maximum age.
+    TooOld { synced_at: DateTime<Utc> },
+    /// The balance moved since the last import, e.g. a co-signer spent.
+    BalanceChanged { then: u64, now: u64 },
+}
+
+impl std::fmt::Display for StaleSync {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StaleSync::NeverSynced => write!(f, "no multisig info has been imported yet"),
+            StaleSync::TooOld { synced_at } => write!(
+                f,
+                "last import was at {}",
+                synced_at.format("%Y-%m-%d %H:%M UTC")
+            ),
+            StaleSync::BalanceChanged { then, now } => write!(
+                f,
+                "balance changed from {} to {} XMR since the last import",
+                crate::transaction::format_xmr(*then),
+                crate::transaction::format_xmr(*now)
+            ),
+        }
+    }
+}
+
+/// A peer export blob that has been imported into this wallet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRecord {
+    /// SHA-256 of the blob, see [`blob_digest`](crate::utils::blob_digest).
+    pub digest: String,
+    pub imported_at: DateTime<Utc>,
+}
+
+/// A blob this wallet exported for its co-signers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportRecord {
+    pub digest: String,
+    pub exported_at: DateTime<Utc>,
+    /// Wallet height the blob was exported at.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u64>,
+    /// The blob itself, kept so `export-info` can print it again while the
+    /// wallet is unchanged. Only kept along with a height.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub info: Option<String>,
+}
+
+/// A key exchange round handed to the wallet RPC, saved before the call and
+/// cleared once the result is saved. One left behind means the process died
+/// in between, so the wallet RPC may be a round ahead of the state file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeAttempt {
+    /// The round being run, counting from 1.
+    pub round: u32,
+    /// Digests of the peer info blobs passed in, in order.
+    pub inputs: Vec<String>,
+    pub started_at: DateTime<Utc>,
+}
+
+impl ExchangeAttempt {
+    /// Whether `peer_info` is the input this attempt was started with.
+    pub fn same_inputs(&self, peer_info: &[String]) -> bool {
+        self.inputs.len() == peer_info.len()
+            && self
+                .inputs
+                .iter()
+                .zip(peer_info)
+                .all(|(digest, blob)| *digest == crate::utils::blob_digest(blob))
+    }
+}
+
+/// What [`repair`] found comparing the state file with the wallet RPC.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Repair {
+    /// Discrepancies corrected in the state file.
+    pub fixed: Vec<String>,
+    /// Discrepancies left for the user, e.g. the wrong wallet being open.
+    pub unresolved: Vec<String>,
+    /// An interrupted round the wallet RPC can't say went through or not.
+    /// Its attempt record is kept.
+    pub undecided_round: Option<u32>,
+}
+
+/// What to do with a peer blob passed to ImportInfo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportDecision {
+    /// Not seen before; pass it to the wallet RPC.
+    Import,
+    /// Already imported at the given time.
+    AlreadyImported(DateTime<Utc>),
+    /// The blob is our own most recent export.
+    OwnExport,
+}
+
+impl StateFile {
+    /// Wrap a freshly created wallet state in a current-version envelope.
+    pub fn new(wallet: WalletState) -> Self {
+        Self {
+            version: STATE_VERSION,
+            wallet,
+            imports: Vec::new(),
+            last_export: None,
+            verified: false,
+            verified_at: None,
+            unverified_warned: false,
+            last_sync: None,
+            exchange_attempt: None,
+            created_at: None,
+            contributions: Vec::new(),
+            multisig_api: None,
+            escrow: None,
+            prepared_info: None,
+        }
+    }
+
+    /// Check that re-running `create-wallet` with `params` asks for the
+    /// wallet this state already describes, so it can resume instead of
+    /// failing. A re-run may leave out the roster. Otherwise fails with
+    /// [`WalletError::AlreadyExists`] naming both parameter sets.
+    pub fn check_rerun(&self, params: &MultisigParams, path: &str) -> Result<()> {
+        let Some(saved) = self.wallet.params() else {
+            return Err(WalletError::AlreadyExists(format!("{path} (a watch-only wallet)")).into());
+        };
+        let same = saved.threshold == params.threshold
+            && saved.total == params.total
+            && saved.label == params.label
+            && (params.roster.is_empty() || saved.roster == params.roster);
+        if same {
+            return Ok(());
+        }
+        Err(WalletError::AlreadyExists(format!(
+            "{path} as {}, not the {} asked for",
+            describe_params(saved),
+            describe_params(&SerializableParams::from(params))
+        ))
+        .into())
+    }
+
+    /// Names of the other participants, empty without a roster.
+    pub fn roster(&self) -> &[String] {
+        self.wallet.params().map_or(&[], |p| &p.roster)
+    }
+
+    /// Replace the roster, checked as [`MultisigParams::with_roster`] checks
+    /// one given at creation. Blobs already received keep the names they
+    /// were recorded under.
+    pub fn set_roster(&mut self, roster: Vec<String>) -> Result<()> {
+        let Some(params) = self.wallet.params_mut() else {
+            return Err(
+                WalletError::InvalidParams("a watch-only wallet has no roster".into()).into(),
+            );
+        };
+        let checked = MultisigParams::new(params.threshold, params.total, params.label.clone())?
+            .with_roster(roster)?;
+        params.roster = checked.roster;
+        Ok(())
+    }
+
+    /// The contribution a blob with this digest was recorded as.
+    pub fn contributor(&self, digest: &str) -> Option<&Contribution> {
+        self.contributions.iter().find(|c| c.digest == digest)
+    }
+
+    /// Check the names given for `peer_info`, one per blob, against the
+    /// roster and against who any blob seen before came from.
+    pub fn check_contributors(&self, names: &[String], peer_info: &[String]) -> Result<()> {
+        for (i, blob) in peer_info.iter().enumerate().take(names.len()) {
+            self.check_contributor(names, i, blob)?;
+        }
+        Ok(())
+    }
+
+    /// [`check_contributors`](Self::check_contributors) for blob `i` alone.
+    fn check_contributor(&self, names: &[String], i: usize, blob: &str) -> Result<()> {
+        let name = &names[i];
+        self.check_member(name)?;
+        if names[..i].contains(name) {
+            return Err(WalletError::DuplicateParticipant(name.clone()).into());
+        }
+        if let Some(previous) = self.contributor(&crate::utils::blob_digest(blob)) {
+            if previous.name != *name {
+                return Err(WalletError::ParticipantMismatch {
+                    claimed: name.clone(),
+                    previous: previous.name.clone(),
+                    round: previous.round,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Check each peer info blob on its own before any is sent to the wallet
+    /// RPC: its format, the `--expect-fingerprint` given for it, that it
+    /// isn't a repeat or this wallet's own info, and the `--from` name given
+    /// for it. Every blob gets a verdict, so one bad blob doesn't hide
+    /// another; see [`peer_info_verdict`].
+    pub fn check_peer_info(
+        &self,
+        blobs: &[String],
+        names: &[String],
+        fingerprints: &[String],
+        max_len: usize,
+    ) -> Vec<PeerInfoCheck> {
+        blobs
+            .iter()
+            .enumerate()
+            .map(|(i, blob)| {
+                let digest = crate::utils::blob_digest(blob);
+                let known = self.contributor(&digest);
+                let from = names
+                    .get(i)
+                    .or(known.map(|c| &c.name))
+                    .filter(|name| *name != ME)
+                    .cloned();
+                let problem =
+                    if let Err(e) = crate::utils::validate_key_exchange_blob(blob, max_len) {
+                        Some(PeerInfoProblem::Format(e.to_string()))
+                    } else if let Some(expected) = fingerprints
+                        .get(i)
+                        .filter(|code| !crate::utils::fingerprint_matches(blob, code))
+                    {
+                        Some(PeerInfoProblem::Fingerprint {
+                            expected: expected.clone(),
+                        })
+                    } else if let Some(j) = blobs[..i].iter().position(|b| b == blob) {
+                        Some(PeerInfoProblem::Duplicate { of: j + 1 })
+                    } else if known.is_some_and(|c| c.name == ME) {
+                        Some(PeerInfoProblem::OwnInfo)
+                    } else if i < names.len() {
+                        match self.check_contributor(names, i, blob) {
+                            Ok(()) => None,
+                            Err(MultisigError::Wallet(WalletError::ParticipantMismatch {
+                                previous,
+                                round,
+                                ..
+                            })) => Some(PeerInfoProblem::Mismatch { previous, round }),
+                            Err(MultisigError::Wallet(e)) => {
+                                Some(PeerInfoProblem::Roster(e.to_string()))
+                            }
+                            Err(e) => Some(PeerInfoProblem::Roster(e.to_string())),
+                        }
+                    } else {
+                        None
+                    };
+                PeerInfoCheck {
+                    index: i + 1,
+                    from,
+                    problem,
+                }
+            })
+            .collect()
+    }
+
+    /// Fail unless `name` is one of the other participants in the roster.
+    pub fn check_member(&self, name: &str) -> Result<()> {
+        let roster = self.roster();
+        if roster.iter().any(|n| n == name) {
+            return Ok(());
+        }
+        let roster = if roster.is_empty() {
+            "none was given to create-wallet".to_string()
+        } else {
+            roster.join(", ")
+        };
+        Err(WalletError::UnknownParticipant {
+            name: name.to_string(),
+            roster,
+        }
+        .into())
+    }
+
+    /// Record that `name` contributed `blob` to key exchange `round`.
+    pub fn record_contribution(&mut self, name: &str, round: u32, blob: &str) {
+        let digest = crate::utils::blob_digest(blob);
+        if self.contributor(&digest).is_none() {
+            self.contributions.push(Contribution {
+                name: name.to_string(),
+                round,
+                digest,
+                received_at: Utc::now(),
+            });
+        }
+    }
+
+    /// The roster with each participant's contributions, us first. Empty
+    /// when the wallet has no roster.
+    pub fn participants(&self) -> Vec<Participant> {
+        let roster = self.roster();
+        if roster.is_empty() {
+            return Vec::new();
+        }
+        std::iter::once(ME)
+            .chain(roster.iter().map(String::as_str))
+            .map(|name| {
+                let mine: Vec<_> = self
+                    .contributions
+                    .iter()
+                    .filter(|c| c.name == name)
+                    .collect();
+                let mut rounds: Vec<u32> = mine.iter().map(|c| c.round).collect();
+                rounds.sort_unstable();
+                rounds.dedup();
+                Participant {
+                    name: name.to_string(),
+                    rounds,
+                    last_digest: mine.last().map(|c| c.digest.clone()),
+                }
+            })
+            .collect()
+    }
+
+    /// Record that key exchange `round` is about to run with `peer_info`.
+    /// Save the state before calling the wallet RPC.
+    pub fn begin_exchange(&mut self, round: u32, peer_info: &[String]) {
+        self.exchange_attempt = Some(ExchangeAttempt {
+            round,
+            inputs: peer_info
+                .iter()
+                .map(|b| crate::utils::blob_digest(b))
+                .collect(),
+            started_at: Utc::now(),
+        });
+    }
+
+    /// Record that key exchange `round` went through, finishing setup when
+    /// the wallet RPC returned the multisig `address`.
+    pub fn complete_round(&mut self, round: u32, address: Option<String>) {
+        let wallet = match &self.wallet {
+            WalletState::Created {
+                wallet_path,
+                params,
+                restore_height,
+            }
+            | WalletState::KeyExchangeInProgress {
+                wallet_path,
+                params,
+                restore_height,
+                ..
+            } => match address {
+                Some(address) => WalletState::Ready {
+                    wallet_path: wallet_path.clone(),
+                    address,
+                    params: params.clone(),
+                    restore_height: *restore_height,
+                },
+                None => WalletState::KeyExchangeInProgress {
+                    wallet_path: wallet_path.clone(),
+                    params: params.clone(),
+                    rounds_completed: round,
+                    rounds_required: key_exchange_rounds(params.threshold, params.total),
+                    restore_height: *restore_height,
+                },
+            },
+            WalletState::Ready { .. } | WalletState::WatchOnly { .. } => return,
+        };
+        self.wallet = wallet;
+        self.exchange_attempt = None;
+    }
+
+    /// Remember the wallet height and balance after a successful import.
+    pub fn record_sync(&mut self, wallet_height: u64, balance: u64) {
+        self.last_sync = Some(SyncRecord {
+            synced_at: Utc::now(),
+            wallet_height,
+            balance,
+        });
+    }
+
+    /// Check whether the last import is recent enough to build on, given the
+    /// wallet's current total balance.
+    pub fn check_sync(
+        &self,
+        now: DateTime<Utc>,
+        max_age: chrono::Duration,
+        balance: u64,
+    ) -> std::result::Result<(), StaleSync> {
+        let Some(sync) = &self.last_sync else {
+            return Err(StaleSync::NeverSynced);
+        };
+        if now - sync.synced_at > max_age {
+            return Err(StaleSync::TooOld {
+                synced_at: sync.synced_at,
+            });
+        }
+        if sync.balance != balance {
+            return Err(StaleSync::BalanceChanged {
+                then: sync.balance,
+                now: balance,
+            });
+        }
+        Ok(())
+    }
+
+    /// Record a successful address verification ceremony.
+    pub fn mark_verified(&mut self) {
+        self.verified = true;
+        self.verified_at = Some(Utc::now());
+    }
+
+    /// Decide whether a peer blob with the given digest should be imported.
+    pub fn classify_import(&self, digest: &str) -> ImportDecision {
+        if self
+            .last_export
+            .as_ref()
+            .is_some_and(|e| e.digest == digest)
+        {
+            return ImportDecision::OwnExport;
+        }
+        match self.imports.iter().find(|r| r.digest == digest) {
+            Some(record) => ImportDecision::AlreadyImported(record.imported_at),
+            None => ImportDecision::Import,
+        }
+    }
+
+    /// Remember that a blob was imported, refreshing the timestamp on reimport.
+    pub fn record_import(&mut self, digest: &str) {
+        let now = Utc::now();
+        match self.imports.iter_mut().find(|r| r.digest == digest) {
+            Some(record) => record.imported_at = now,
+            None => self.imports.push(ImportRecord {
+                digest: digest.to_string(),
+                imported_at: now,
+            }),
+        }
+    }
+
+    /// Remember the blob we just exported. With the wallet `height` it was
+    /// made at, the blob is kept for [`StateFile::reusable_export`].
+    pub fn record_export(&mut self, info: &str, height: Option<u64>) {
+        self.last_export = Some(ExportRecord {
+            digest: crate::utils::blob_digest(info),
+            exported_at: Utc::now(),
+            height,
+            info: height.map(|_| info.trim().to_string()),
+        });
+    }
+
+    /// The last export and its blob, if the wallet is still at the `height`
+    /// it was made at and nothing since could have changed its outputs: no
+    /// import of co-signers' info, which marks outputs spent, and no
+    /// transaction built or signed (`last_tx_activity`), which uses up the
+    /// signing nonces the blob carries.
+    pub fn reusable_export(
+        &self,
+        height: u64,
+        last_tx_activity: Option<DateTime<Utc>>,
+    ) -> Option<(&ExportRecord, &str)> {
+        let record = self.last_export.as_ref()?;
+        let info = record.info.as_deref()?;
+        let unchanged = record.height == Some(height)
+            && self
+                .imports
+                .iter()
+                .all(|i| i.imported_at <= record.exported_at)
+            && last_tx_activity.is_none_or(|at| at <= record.exported_at);
+        unchanged.then_some((record, info))
+    }
+
+    /// Parse a state file of any supported version, migrating it to
+    /// [`STATE_VERSION`]. Returns the stored checksum separately.
+    fn parse(contents: &str) -> Result<(Self, Option<String>)> {
+        let mut value: serde_json::Value = serde_json::from_str(contents)?;
+        let checksum = value
+            .as_object_mut()
+            .and_then(|o| o.remove(CHECKSUM_FIELD))
+            .and_then(|c| c.as_str().map(str::to_string));
+        let version = value
+            .get("version")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as u32)
+            .unwrap_or(1);
+
+        match version {
+            1 => Ok((Self::new(serde_json::from_value(value)?), checksum)),
+            STATE_VERSION => Ok((serde_json::from_value(value)?, checksum)),
+            other => Err(WalletError::UnsupportedStateVersion {
+                found: other,
+                supported: STATE_VERSION,
+            }
+            .into()),
+        }
+    }
+}
+
+/// HMAC-SHA256 of a state file's JSON without its checksum, as stored. It
+/// covers the fields in the file rather than this version's serialization
+/// of them, so a field a later version adds doesn't invalidate older files.
+fn state_checksum(key: &[u8], value: &serde_json::Value) -> Result<String> {
+    Ok(hmac_hex(key, &serde_json::to_vec(value)?))
+}
+
+/// The checksum earlier versions wrote, over `state` as re-serialized.
+/// Still accepted, so their files load; the next save replaces it.
+fn legacy_state_checksum(key: &[u8], state: &StateFile) -> Result<String> {
+    Ok(hmac_hex(key, &serde_json::to_vec(state)?))
+}
+
+fn hmac_hex(key: &[u8], bytes: &[u8]) -> String {
+    use hmac::{Hmac, Mac};
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(bytes);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Read the state key, creating it (readable by the owner only) if asked.
+fn state_key(data_dir: &DataDir, create: bool) -> Result<Option<Vec<u8>>> {
+    let path = data_dir.file(STATE_KEY_FILE);
+    match std::fs::read_to_string(&path) {
+        Ok(text) => {
+            let key = hex::decode(text.trim()).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{}: {e}", path.display()),
+                )
+            })?;
+            return Ok(Some(key));
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && create => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+
+    let mut key = [0u8; 32];
+    getrandom::getrandom(&mut key).map_err(|e| std::io::Error::other(e.to_string()))?;
+    std::fs::create_dir_all(data_dir)?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&path)?;
+    std::io::Write::write_all(&mut file, hex::encode(key).as_bytes())?;
+    file.sync_all()?;
+    tracing::info!("Created state key {}", path.display());
+    Ok(Some(key.to_vec()))
+}
+
+/// Persist the full state envelope to `data_dir`, with its checksum.
+pub fn save_state(data_dir: &DataDir, state: &StateFile) -> Result<()> {
+    let path = data_dir.file(STATE_FILE);
+    let key = state_key(data_dir, true)?.expect("created when missing");
+    let mut value = serde_json::to_value(state)?;
+    let checksum = state_checksum(&key, &value)?;
+    value[CHECKSUM_FIELD] = serde_json::Value::String(checksum);
+    let json = serde_json::to_string_pretty(&value)?;
+    crate::utils::write_atomic(&path, json.as_bytes())?;
+    tracing::info!("Wallet state saved to {}", path.display());
+    Ok(())
+}
+
+/// Load the full state envelope, migrating older layouts, and check its
+/// checksum.
+pub fn load_state(data_dir: &DataDir) -> Result<StateFile> {
+    let path = data_dir.file(STATE_FILE);
+    let contents = read_state_file(data_dir)?;
+    let (state, checksum) = StateFile::parse(&contents)?;
+    let display = path.display().to_string();
+    let Some(checksum) = checksum else {
+        return Err(WalletError::StateUnsigned(display).into());
+    };
+    let Some(key) = state_key(data_dir, false)? else {
+        let key_path = data_dir.file(STATE_KEY_FILE);
+        return Err(WalletError::StateKeyMissing(key_path.display().to_string()).into());
+    };
+    let mut stored: serde_json::Value = serde_json::from_str(&contents)?;
+    if let Some(fields) = stored.as_object_mut() {
+        fields.remove(CHECKSUM_FIELD);
+    }
+    if state_checksum(&key, &stored)? != checksum
+        && legacy_state_checksum(&key, &state)? != checksum
+    {
+        return Err(WalletError::StateTampered(display).into());
+    }
+    Ok(state)
+}
+
+/// Load the state envelope without checking its checksum, for `state
+/// rehash` to show it for review.
+pub fn load_state_unverified(data_dir: &DataDir) -> Result<StateFile> {
+    Ok(read_state(data_dir)?.0)
+}
+
+fn read_state(data_dir: &DataDir) -> Result<(StateFile, Option<String>)> {
+    StateFile::parse(&read_state_file(data_dir)?)
+}
+
+fn read_state_file(data_dir: &DataDir) -> Result<String> {
+    let path = data_dir.file(STATE_FILE);
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => Ok(contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Err(WalletError::NotFound(path.display().to_string()).into())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Persist wallet state to a JSON file inside `data_dir`, keeping any sync
+/// history already recorded there.
+pub fn save_wallet_state(data_dir: &DataDir, state: &WalletState) -> Result<()> {
+    let file = match load_state(data_dir) {
+        Ok(mut existing) => {
+            existing.wallet = state.clone();
+            existing
+        }
+        Err(crate::error::MultisigError::Wallet(WalletError::NotFound(_))) => {
+            StateFile::new(state.clone())
+        }
+        Err(e) => return Err(e),
+    };
+    save_state(data_dir, &file)
+}
+
+/// Load wallet state from a previously saved JSON file.
+pub fn load_wallet_state(data_dir: &DataDir) -> Result<WalletState> {
+    Ok(load_state(data_dir)?.wallet)
+}
+
+/// Where the state file for `data_dir` is kept.
+pub fn state_path(data_dir: &DataDir) -> PathBuf {
+    data_dir.file(STATE_FILE)
+}
+
+/// Check whether a wallet state file exists in the data directory.
+pub fn wallet_exists(data_dir: &DataDir) -> bool {
+    data_dir.file(STATE_FILE).exists()
+}
+
+/// Delete wallet state and associated data.
+pub fn delete_wallet(data_dir: &DataDir) -> Result<()> {
+    let path = data_dir.file(STATE_FILE);
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+        tracing::info!("Wallet state removed from {}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_info_from_synced_daemon() {
+        let info: GetInfoResponse = serde_json::from_value(serde_json::json!({
+            "height": 3100000,
+            "target_height": 0,
+            "difficulty": 250000000000u64,
+            "synchronized": true,
+            "status": "OK"
+        }))
+        .unwrap();
+        let chain = ChainInfo::from_parts(info, 3099990);
+        assert_eq!(chain.target_height, 3100000);
+        assert_eq!(chain.wallet_behind(), 10);
+        assert_eq!(chain.daemon_behind(), 0);
+    }
+
+    fn ready(address: &str) -> WalletState {
+        WalletState::Ready {
+            wallet_path: PathBuf::from("wallet"),
+            address: address.into(),
+            params: SerializableParams::from(&MultisigParams::new(2, 3, "t".into()).unwrap()),
+            restore_height: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_open_wallet() {
+        let (stub, hits) = crate::testing::stub_daemon(
+            r#"{"jsonrpc":"2.0","id":"0","result":{"address":"4ours","addresses":[]}}"#,
+        )
+        .await;
+        let rpc = RpcClient::new(&stub);
+        check_open_wallet(&rpc, &ready("4ours")).await.unwrap();
+
+        let err = check_open_wallet(&rpc, &ready("4other")).await.unwrap_err();
+        assert!(matches!(
+            err,
+            crate::error::MultisigError::Wallet(WalletError::AddressMismatch { .. })
+        ));
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        // Nothing to compare before key exchange finishes: no round trip.
+        let created = WalletState::Created {
+            wallet_path: PathBuf::from("wallet"),
+            params: SerializableParams::from(&MultisigParams::new(2, 3, "t".into()).unwrap()),
+            restore_height: None,
+        };
+        check_open_wallet(&rpc, &created).await.unwrap();
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_adopt_needs_finished_multisig() {
+        let adopt_with = |body: &'static str| async move {
+            let (stub, _) = crate::testing::stub_daemon(body).await;
+            adopt(
+                &RpcClient::new(&stub),
+                PathBuf::from("wallet"),
+                "ops".into(),
+                Vec::new(),
+            )
+            .await
+        };
+
+        assert!(matches!(
+            adopt_with(r#"{"jsonrpc":"2.0","id":"0","result":{"multisig":false,"ready":false}}"#)
+                .await,
+            Err(crate::error::MultisigError::Wallet(
+                WalletError::NotMultisig
+            ))
+        ));
+        assert!(matches!(
+            adopt_with(
+                r#"{"jsonrpc":"2.0","id":"0","result":{"multisig":true,"ready":false,"threshold":2,"total":3}}"#
+            )
+            .await,
+            Err(crate::error::MultisigError::Wallet(
+                WalletError::SetupUnfinished
+            ))
+        ));
+
+        let state = adopt_with(
+            r#"{"jsonrpc":"2.0","id":"0","result":{"multisig":true,"ready":true,"threshold":2,"total":3,"address":"4ours"}}"#,
+        )
+        .await
+        .unwrap();
+        let WalletState::Ready {
+            address, params, ..
+        } = &state.wallet
+        else {
+            panic!("expected a ready wallet");
+        };
+        assert_eq!(address, "4ours");
+        assert_eq!((params.threshold, params.total), (2, 3));
+        assert_eq!(params.label, "ops");
+    }
+
+    #[tokio::test]
+    async fn test_account_tags_resolve() {
+        let daemon = crate::testing::handler_daemon(|call| {
+            let accounts = serde_json::json!([
+                {"account_index": 0, "base_address": "4a"},
+                {"account_index": 1, "base_address": "8b"},
+                {"account_index": 2, "base_address": "8c"},
+                {"account_index": 3, "base_address": "8d"},
+            ]);
+            match call["method"].as_str().unwrap() {
+                "get_account_tags" => Ok(serde_json::json!({"account_tags": [
+                    {"tag": "ops", "label": "day to day", "accounts": [2]},
+                    {"tag": "reserves", "label": "", "accounts": [3, 1]},
+                ]})),
+                "get_accounts" => {
+                    let wanted: &[u64] = match call["params"]["tag"].as_str() {
+                        Some("reserves") => &[1, 3],
+                        Some(_) => return Err((-1, "Tag is unregistered".into())),
+                        None => &[0, 1, 2, 3],
+                    };
+                    let accounts: Vec<_> = accounts
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .filter(|a| wanted.contains(&a["account_index"].as_u64().unwrap()))
+                        .cloned()
+                        .collect();
+                    Ok(serde_json::json!({ "subaddress_accounts": accounts }))
+                }
+                _ => Err((-32601, "Method not found".into())),
+            }
+        })
+        .await;
+        let rpc = RpcClient::new(&daemon);
+        let select = |s: &str| s.parse::<AccountSelector>().unwrap();
+
+        assert_eq!(select("2"), AccountSelector::Index(2));
+        assert_eq!(select("ops").resolve(&rpc).await.unwrap(), [2]);
+        let reserves = select("reserves");
+        let both = reserves.resolve(&rpc).await.unwrap();
+        assert_eq!(both, [1, 3]);
+        assert!(matches!(
+            single_account(&reserves, &both),
+            Err(MultisigError::Wallet(WalletError::AmbiguousAccountTag { ref tag, ref accounts }))
+                if tag == "reserves" && accounts == &[1, 3]
+        ));
+        assert_eq!(single_account(&select("ops"), &[2]).unwrap(), 2);
+        assert!(matches!(
+            select("payroll").resolve(&rpc).await,
+            Err(MultisigError::Wallet(WalletError::NoSuchAccountTag(ref tag))) if tag == "payroll"
+        ));
+
+        let tags: Vec<_> = tagged_accounts(&rpc, None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|a| a.tag)
+            .collect();
+        assert_eq!(
+            tags,
+            [
+                None,
+                Some("reserves".into()),
+                Some("ops".into()),
+                Some("reserves".into())
+            ]
+        );
+        let listed = tagged_accounts(&rpc, Some("reserves")).await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert!(matches!(
+            tagged_accounts(&rpc, Some("payroll")).await,
+            Err(MultisigError::Wallet(WalletError::NoSuchAccountTag(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_check_account_range() {
+        let (stub, hits) = crate::testing::stub_daemon(
+            r#"{"jsonrpc":"2.0","id":"0","result":{
+                "subaddress_accounts":[
+                    {"account_index":0,"base_address":"4a"},
+                    {"account_index":1,"base_address":"8b"}
+                ],
+                "address":"8b",
+                "addresses":[{"address_index":0,"address":"8b"},{"address_index":1,"address":"8c"}]
+            }}"#,
+        )
+        .await;
+        let rpc = RpcClient::new(&stub);
+        let hits = || hits.load(std::sync::atomic::Ordering::SeqCst);
+
+        // The default account is never queried.
+        check_account(&rpc, 0, &[]).await.unwrap();
+        assert_eq!(hits(), 0);
+
+        check_account(&rpc, 1, &[0, 1]).await.unwrap();
+        assert!(matches!(
+            check_account(&rpc, 2, &[]).await,
+            Err(crate::error::MultisigError::Wallet(
+                WalletError::NoSuchAccount { index: 2, count: 2 }
+            ))
+        ));
+        assert!(matches!(
+            check_account(&rpc, 1, &[1, 5]).await,
+            Err(crate::error::MultisigError::Wallet(
+                WalletError::NoSuchSubaddress {
+                    account: 1,
+                    index: 5,
+                    count: 2
+                }
+            ))
+        ));
+        // A wallet RPC reporting no accounts at all doesn't underflow.
+        let none = WalletError::NoSuchAccount { index: 0, count: 0 };
+        assert!(none.to_string().contains("has 0 (0 to 0)"));
+    }
+
+    #[test]
+    fn test_chain_info_untrusted_flag() {
+        let untrusted = |info: serde_json::Value| {
+            let info: GetInfoResponse = serde_json::from_value(info).unwrap();
+            ChainInfo::from_parts(info, 10).untrusted
+        };
+        assert!(untrusted(
+            serde_json::json!({ "height": 10, "untrusted": true })
+        ));
+        assert!(!untrusted(
+            serde_json::json!({ "height": 10, "untrusted": false })
+        ));
+        // Older daemons omit the field.
+        assert!(!untrusted(serde_json::json!({ "height": 10 })));
+    }
+
+    #[tokio::test]
+    async fn test_last_block_before() {
+        // One block every two minutes starting at t=1000.
+        let timestamps: Vec<u64> = (0..1000).map(|h| 1000 + h * 120).collect();
+        let lookup = |h: u64| std::future::ready(Ok(timestamps[h as usize]));
+
+        let found = last_block_before(1000, 1000 + 500 * 120, lookup).await;
+        assert_eq!(found.unwrap(), Some(499));
+        assert_eq!(last_block_before(1000, 0, lookup).await.unwrap(), None);
+        assert_eq!(
+            last_block_before(1000, u64::MAX, lookup).await.unwrap(),
+            Some(999)
+        );
+    }
+
+    #[test]
+    fn test_state_without_restore_height_loads() {
+        let json = r#"{"Created":{"wallet_path":"/tmp/w","params":{"threshold":2,"total":3,"label":"x"}}}"#;
+        let state: WalletState = serde_json::from_str(json).unwrap();
+        assert_eq!(state.restore_height(), None);
+    }
+
+    fn created() -> WalletState {
+        WalletState::Created {
+            wallet_path: PathBuf::from("/tmp/wallet"),
+            params: SerializableParams {
+                threshold: 2,
+                total: 3,
+                label: "test".into(),
+                roster: Vec::new(),
+            },
+            restore_height: None,
+        }
+    }
+
+    #[test]
+    fn test_legacy_state_migrates() {
+        let legacy = serde_json::to_string(&created()).unwrap();
+        let (state, checksum) = StateFile::parse(&legacy).unwrap();
+        assert!(checksum.is_none());
+        assert_eq!(state.version, STATE_VERSION);
+        assert_eq!(state.wallet.label(), "test");
+        assert!(state.imports.is_empty());
+    }
+
+    #[test]
+    fn test_future_state_version_rejected() {
+        let mut value = serde_json::to_value(StateFile::new(created())).unwrap();
+        value["version"] = serde_json::json!(STATE_VERSION + 1);
+        assert!(StateFile::parse(&value.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_state_checksum_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = DataDir::new(tmp.path());
+        save_state(&dir, &StateFile::new(ready("4ours"))).unwrap();
+        let state = load_state(&dir).unwrap();
+        assert!(
+            matches!(state.wallet, WalletState::Ready { ref address, .. } if address == "4ours")
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(dir.file(STATE_KEY_FILE))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_older_state_files_still_load() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = DataDir::new(tmp.path());
+        let key = state_key(&dir, true).unwrap().unwrap();
+        let path = dir.file(STATE_FILE);
+
+        // Written before `verified` and `unverified_warned` existed, which
+        // this version always writes.
+        let mut value = serde_json::to_value(StateFile::new(ready("4ours"))).unwrap();
+        let fields = value.as_object_mut().unwrap();
+        fields.remove("verified");
+        fields.remove("unverified_warned");
+        let checksum = state_checksum(&key, &value).unwrap();
+        value[CHECKSUM_FIELD] = checksum.into();
+        std::fs::write(&path, serde_json::to_string_pretty(&value).unwrap()).unwrap();
+        let state = load_state(&dir).unwrap();
+        assert!(!state.unverified_warned);
+
+        // Checksummed the way earlier versions did.
+        let state = StateFile::new(ready("4ours"));
+        let mut value = serde_json::to_value(&state).unwrap();
+        value[CHECKSUM_FIELD] = legacy_state_checksum(&key, &state).unwrap().into();
+        std::fs::write(&path, serde_json::to_string_pretty(&value).unwrap()).unwrap();
+        load_state(&dir).unwrap();
+
+        // Either way, an edit is still caught.
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, text.replace("4ours", "4evil")).unwrap();
+        assert!(matches!(
+            load_state(&dir).unwrap_err(),
+            crate::error::MultisigError::Wallet(WalletError::StateTampered(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_wallet_files_matched_to_local_states() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = DataDir::new(tmp.path());
+        save_state(&dir, &StateFile::new(ready("4ours"))).unwrap();
+        let archive = dir.file("archive").join("old-1");
+        std::fs::create_dir_all(&archive).unwrap();
+        let tombstone = Tombstone {
+            label: "old".into(),
+            address: "4old".into(),
+            created_at: None,
+            closed_at: Utc::now(),
+            transactions: 0,
+            total_sent: 0,
+            kept_files: vec![archive.join("old-fund"), archive.join("old-fund.keys")],
+        };
+        std::fs::write(
+            archive.join("tombstone.json"),
+            serde_json::to_string(&tombstone).unwrap(),
+        )
+        .unwrap();
+        let known = known_wallet_files(&dir);
+        assert_eq!(known.len(), 2);
+        assert_eq!(
+            (known[0].filename.as_str(), known[0].retired),
+            ("wallet", false)
+        );
+        assert_eq!(
+            (known[1].filename.as_str(), known[1].retired),
+            ("old-fund", true)
+        );
+
+        let rpc = RpcClient::new(
+            &crate::testing::method_daemon(&[(
+                "list_wallets",
+                r#"{"wallets":[{"filename":"/srv/wallets/wallet","mtime":1700000000},
+                    "/srv/wallets/wallet.keys","old-fund","stray"]}"#,
+            )])
+            .await,
+        );
+        let listed = rpc_wallet_files(&rpc).await.unwrap().unwrap();
+        let files = match_wallet_files(Some(&listed), &known);
+        let rows: Vec<_> = files
+            .iter()
+            .map(|f| (f.filename.as_str(), f.label.as_deref(), f.adopt_candidate()))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("wallet", Some("t"), false),
+                ("old-fund", Some("old"), false),
+                ("stray", None, true),
+            ]
+        );
+        assert_eq!(files[0].multisig, Some(true));
+        assert_eq!(files[0].modified.unwrap().timestamp(), 1_700_000_000);
+        assert!(files[1].retired);
+
+        // A wallet RPC without a listing method: only what is known here.
+        let rpc = RpcClient::new(&crate::testing::method_daemon(&[]).await);
+        assert_eq!(rpc_wallet_files(&rpc).await.unwrap(), None);
+        let files = match_wallet_files(None, &known);
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().all(|f| !f.adopt_candidate()));
+    }
+
+    #[test]
+    fn test_replaced_address_detected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = DataDir::new(tmp.path());
+        save_state(&dir, &StateFile::new(ready("4ours"))).unwrap();
+        let path = dir.file(STATE_FILE);
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, text.replace("4ours", "4evil")).unwrap();
+
+        let err = load_state(&dir).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::error::MultisigError::Wallet(WalletError::StateTampered(_))
+        ));
+        // Still readable for review, and a rehash makes it load again.
+        let state = load_state_unverified(&dir).unwrap();
+        save_state(&dir, &state).unwrap();
+        assert!(load_state(&dir).is_ok());
+    }
+
+    #[test]
+    fn test_legacy_unsigned_and_missing_key_are_distinct() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = DataDir::new(tmp.path());
+        let path = dir.file(STATE_FILE);
+        std::fs::write(
+            &path,
+            serde_json::to_string(&StateFile::new(created())).unwrap(),
+        )
+        .unwrap();
+        assert!(matches!(
+            load_state(&dir).unwrap_err(),
+            crate::error::MultisigError::Wallet(WalletError::StateUnsigned(_))
+        ));
+
+        save_state(&dir, &StateFile::new(created())).unwrap();
+        std::fs::remove_file(dir.file(STATE_KEY_FILE)).unwrap();
+        assert!(matches!(
+            load_state(&dir).unwrap_err(),
+            crate::error::MultisigError::Wallet(WalletError::StateKeyMissing(_))
+        ));
+    }
+
+    #[test]
+    fn test_classify_import() {
+        let mut state = StateFile::new(created());
+        assert_eq!(state.classify_import("aa"), ImportDecision::Import);
+
+        state.record_import("aa");
+        assert!(matches!(
+            state.classify_import("aa"),
+            ImportDecision::AlreadyImported(_)
+        ));
+
+        state.record_export("bb", None);
+        assert_eq!(
+            state.classify_import(&crate::utils::blob_digest("bb")),
+            ImportDecision::OwnExport
+        );
+    }
+
+    #[test]
+    fn test_reusable_export() {
+        let mut state = StateFile::new(created());
+        assert!(state.reusable_export(100, None).is_none());
+
+        // Without the height it was made at, an export can't be reused.
+        state.record_export("blob1\n", None);
+        assert!(state.reusable_export(100, None).is_none());
+
+        state.record_export("blob2", Some(100));
+        let exported_at = state.last_export.as_ref().unwrap().exported_at;
+        let (record, info) = state.reusable_export(100, None).unwrap();
+        assert_eq!((record.height, info), (Some(100), "blob2"));
+        assert!(state
+            .reusable_export(100, Some(exported_at - chrono::Duration::hours(1)))
+            .is_some());
+
+        // A new block, a spend or an import since all need a new one.
+        assert!(state.reusable_export(101, None).is_none());
+        assert!(state
+            .reusable_export(100, Some(exported_at + chrono::Duration::seconds(1)))
+            .is_none());
+        state.record_import("peer");
+        state.imports[0].imported_at = exported_at + chrono::Duration::seconds(1);
+        assert!(state.reusable_export(100, None).is_none());
+    }
+
+    #[test]
+    fn test_check_sync() {
+        let mut state = StateFile::new(created());
+        let now = Utc::now();
+        let day = chrono::Duration::hours(24);
+        assert_eq!(state.check_sync(now, day, 0), Err(StaleSync::NeverSynced));
+
+        state.record_sync(100, 5);
+        assert!(state.check_sync(now, day, 5).is_ok());
+        assert_eq!(
+            state.check_sync(now, day, 3),
+            Err(StaleSync::BalanceChanged { then: 5, now: 3 })
+        );
+        assert!(matches!(
+            state.check_sync(now + chrono::Duration::hours(25), day, 5),
+            Err(StaleSync::TooOld { .. })
+        ));
+    }
+
+    #[test]
+    fn test_watch_only_cannot_sign() {
+        let watch = WalletState::WatchOnly {
+            wallet_path: PathBuf::from("/tmp/watch"),
+            address: "4".into(),
+            label: "books".into(),
+            restore_height: Some(3_100_000),
+        };
+        assert_eq!(watch.label(), "books");
+        assert!(watch.params().is_none());
+        assert!(matches!(
+            watch.ensure_can_sign(),
+            Err(crate::error::MultisigError::Wallet(WalletError::WatchOnly))
+        ));
+        assert!(created().ensure_can_sign().is_ok());
+    }
+
+    #[test]
+    fn test_address_verification() {
+        let params = created().params().unwrap().clone();
+        let address = "4".to_string() + &"A".repeat(94);
+        let ours = crate::utils::fingerprint(&address);
+
+        let none = AddressVerification::new(&address, &params, &[], FingerprintFormat::Words);
+        assert!(!none.all_match());
+
+        let good = AddressVerification::new(
+            &address,
+            &params,
+            &[ours.clone(), ours.to_uppercase()],
+            FingerprintFormat::Words,
+        );
+        assert!(good.all_match());
+
+        let bad = AddressVerification::new(
+            &address,
+            &params,
+            &[ours, "acid-acid-acid-acid-acid-acid-acid-acid".into()],
+            FingerprintFormat::Words,
+        );
+        assert!(!bad.all_match());
+        assert!(!bad.peers[1].matches);
+    }
+
+    async fn two_of_three(api: MultisigApi, answers: &'static [(&'static str, &'static str)]) {
+        let rpc = RpcClient::new(&crate::testing::method_daemon(answers).await);
+        let params = SerializableParams::from(&MultisigParams::new(2, 3, "t".into()).unwrap());
+        let password = Secret::new(String::new());
+        let peers = ["MultisigxV2R1a".to_string(), "MultisigxV2R1b".to_string()];
+
+        let KeyExchangeResult::Partial { next_info } =
+            exchange_keys(&rpc, &peers, &params, 1, api, &password)
+                .await
+                .unwrap()
+        else {
+            panic!("round 1 finished setup");
+        };
+        assert_eq!(next_info, "MultisigxV2R2");
+        let result = exchange_keys(&rpc, &peers, &params, 2, api, &password)
+            .await
+            .unwrap();
+        assert!(matches!(result, KeyExchangeResult::Complete { address } if address == "4ready"));
+    }
+
+    #[tokio::test]
+    async fn test_current_api_completes_two_of_three() {
+        // No finalize_multisig: calling it would fail the test.
+        two_of_three(
+            MultisigApi::Current,
+            &[
+                (
+                    "make_multisig",
+                    r#"{"address":"","multisig_info":"MultisigxV2R2"}"#,
+                ),
+                (
+                    "exchange_multisig_keys",
+                    r#"{"address":"4ready","multisig_info":""}"#,
+                ),
+            ],
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_legacy_api_completes_two_of_three() {
+        // No exchange_multisig_keys: a 2-of-3 never needs it here.
+        two_of_three(
+            MultisigApi::Legacy,
+            &[
+                (
+                    "make_multisig",
+                    r#"{"address":"","multisig_info":"MultisigxV2R2"}"#,
+                ),
+                ("finalize_multisig", r#"{"address":"4ready"}"#),
+            ],
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_legacy_api_finalizes_only_n_minus_one_of_n() {
+        // No finalize_multisig: a legacy 2-of-4 ends with exchange_multisig_keys.
+        let rpc = RpcClient::new(
+            &crate::testing::method_daemon(&[(
+                "exchange_multisig_keys",
+                r#"{"address":"4ready","multisig_info":""}"#,
+            )])
+            .await,
+        );
+        let params = SerializableParams::from(&MultisigParams::new(2, 4, "t".into()).unwrap());
+        let peers = ["MultisigxV2Rna".to_string(), "MultisigxV2Rnb".to_string()];
+        let last = key_exchange_rounds(2, 4);
+        let result = exchange_keys(
+            &rpc,
+            &peers,
+            &params,
+            last,
+            MultisigApi::Legacy,
+            &Secret::new(String::new()),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(result, KeyExchangeResult::Complete { address } if address == "4ready"));
+    }
+
+    #[tokio::test]
+    async fn test_key_exchange_refuses_other_network_wallet() {
+        use crate::config::Network;
+        // A stagenet wallet RPC, as behind a mainnet config by mistake.
+        let rpc = RpcClient::new(
+            &crate::testing::method_daemon(&[
+                ("get_version", r#"{"version":65563}"#),
+                (
+                    "get_address",
+                    r#"{"address":"5BXAsDboVYEQcxEUsi761WbnJWsFRCwh1PkiGtGnUUcJTGenfCr5WEtdoXezutmPiQMsaM4zJbpdH5PMjkCt7QrXAhV8wDB","addresses":[]}"#,
+                ),
+            ])
+            .await,
+        );
+        assert!(check_network(&rpc, Network::Stagenet).await.is_ok());
+        let err = check_network(&rpc, Network::Mainnet).await.unwrap_err();
+        assert!(matches!(
+            err,
+            MultisigError::Wallet(WalletError::WrongNetwork {
+                wallet: Network::Stagenet,
+                configured: Network::Mainnet,
+            })
+        ));
+    }
+
+    fn exchanging() -> StateFile {
+        StateFile::new(WalletState::Created {
+            wallet_path: PathBuf::from("wallet"),
+            params: SerializableParams::from(&MultisigParams::new(3, 5, "t".into()).unwrap()),
+            restore_height: Some(5),
+        })
+    }
+
+    #[test]
+    fn test_check_rerun() {
+        let state = exchanging();
+        let same = MultisigParams::new(3, 5, "t".into()).unwrap();
+        state.check_rerun(&same, "data").unwrap();
+        let named = same
+            .clone()
+            .with_roster(["a", "b", "c", "d"].map(String::from).to_vec())
+            .unwrap();
+        // Naming participants the first run didn't is a different wallet.
+        assert!(state.check_rerun(&named, "data").is_err());
+
+        let other = MultisigParams::new(2, 5, "t".into()).unwrap();
+        let err = state.check_rerun(&other, "data").unwrap_err();
+        assert!(matches!(
+            err,
+            MultisigError::Wallet(WalletError::AlreadyExists(_))
+        ));
+        assert_eq!(
+            err.to_string(),
+            "wallet error: wallet already exists at data as 3-of-5 \"t\", not the 2-of-5 \"t\" asked for — \
+             run `reset` to start over"
+        );
+    }
+
+    #[test]
+    fn test_set_roster() {
+        let mut state = exchanging();
+        let roster = ["a", "b", "c", "d"].map(String::from).to_vec();
+        state.set_roster(roster.clone()).unwrap();
+        assert_eq!(state.roster(), roster);
+        // Checked as at creation: one name per other participant.
+        assert!(state.set_roster(vec!["a".into()]).is_err());
+        assert!(state
+            .set_roster(["a", "b", "c", ME].map(String::from).to_vec())
+            .is_err());
+        assert_eq!(state.roster(), roster);
+    }
+
+    fn status(multisig: bool, ready: bool) -> MultisigStatus {
+        MultisigStatus {
+            multisig,
+            ready,
+            threshold: 3,
+            total: 5,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repair_catches_up_with_wallet_rpc() {
+        let (stub, _) = crate::testing::stub_daemon(
+            r#"{"jsonrpc":"2.0","id":"0","result":{"multisig":true,"ready":true,"threshold":3,"total":5,"address":"4multi","addresses":[]}}"#,
+        )
+        .await;
+        let rpc = RpcClient::new(&stub);
+        let mut state = exchanging();
+        state.begin_exchange(1, &["peer".to_string()]);
+
+        let repair = repair(&rpc, &mut state, false).await.unwrap();
+        assert_eq!(repair.fixed.len(), 2, "{:?}", repair.fixed);
+        assert!(repair.fixed[0].contains("marked ready with address 4multi"));
+        assert!(repair.fixed[1].contains("round 1, which went through"));
+        assert!(repair.unresolved.is_empty());
+        assert!(state.exchange_attempt.is_none());
+        match state.wallet {
+            WalletState::Ready {
+                address,
+                restore_height,
+                ..
+            } => {
+                assert_eq!(address, "4multi");
+                assert_eq!(restore_height, Some(5));
+            }
+            other => panic!("expected Ready, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_reconcile_records_missed_first_round() {
+        let mut state = exchanging();
+        let repair = reconcile(&mut state, status(true, false), None, false);
+        assert_eq!(repair.fixed.len(), 1);
+        assert!(matches!(
+            state.wallet,
+            WalletState::KeyExchangeInProgress {
+                rounds_completed: 1,
+                rounds_required: 3,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_reconcile_keeps_undecided_round() {
+        let mut state = exchanging();
+        state.complete_round(1, None);
+        state.begin_exchange(2, &["peer".to_string()]);
+
+        let repair = reconcile(&mut state, status(true, false), None, false);
+        assert_eq!(repair.undecided_round, Some(2));
+        assert!(repair.fixed.is_empty());
+        assert!(state.exchange_attempt.is_some());
+
+        let repair = reconcile(&mut state, status(true, false), None, true);
+        assert_eq!(repair.undecided_round, None);
+        assert_eq!(state.wallet.next_round(), Some(3));
+        assert!(state.exchange_attempt.is_none());
+    }
+
+    #[test]
+    fn test_reconcile_round_never_applied() {
+        let mut state = exchanging();
+        state.begin_exchange(1, &["peer".to_string()]);
+        let repair = reconcile(&mut state, status(false, false), None, false);
+        assert_eq!(state.wallet.next_round(), Some(1));
+        assert!(repair.fixed[0].contains("never reached the wallet RPC"));
+        assert!(state.exchange_attempt.is_none());
+    }
+
+    #[test]
+    fn test_reconcile_reports_wrong_wallet() {
+        let mut state = StateFile::new(ready("4ours"));
+        let repair = reconcile(&mut state, status(false, false), None, false);
+        assert!(repair.fixed.is_empty());
+        assert!(repair.unresolved[0].contains("is the right wallet open?"));
+        assert!(matches!(state.wallet, WalletState::Ready { .. }));
+    }
+
+    #[test]
+    fn test_exchange_attempt_same_inputs() {
+        let mut state = exchanging();
+        let info = vec!["a".to_string(), "b".to_string()];
+        state.begin_exchange(1, &info);
+        let attempt = state.exchange_attempt.unwrap();
+        assert!(attempt.same_inputs(&info));
+        assert!(!attempt.same_inputs(&info[..1]));
+        assert!(!attempt.same_inputs(&["b".to_string(), "a".to_string()]));
+    }
+
+    fn with_roster() -> StateFile {
+        let params = MultisigParams::new(3, 3, "t".into())
+            .unwrap()
+            .with_roster(vec!["alice".into(), "bob".into()])
+            .unwrap();
+        StateFile::new(WalletState::Created {
+            wallet_path: PathBuf::from("wallet"),
+            params: SerializableParams::from(&params),
+            restore_height: None,
+        })
+    }
+
+    fn rule(threshold: u32, total: u32) -> Option<String> {
+        match MultisigParams::new(threshold, total, "t".into()) {
+            Ok(_) => None,
+            Err(crate::error::MultisigError::Wallet(WalletError::InvalidParams(message))) => {
+                Some(message)
+            }
+            Err(other) => panic!("unexpected error {other}"),
+        }
+    }
+
+    #[test]
+    fn test_params_boundaries() {
+        for (threshold, total) in [(2, 2), (2, 3), (3, 3), (2, 16), (16, 16)] {
+            assert_eq!(rule(threshold, total), None, "{threshold}-of-{total}");
+        }
+        let cases = [
+            (0, 3, "threshold must be at least 2"),
+            (1, 3, "threshold must be at least 2"),
+            (4, 3, "threshold cannot exceed participants"),
+            (17, 16, "threshold cannot exceed participants"),
+            (1, 1, "participants must be at least 2"),
+            (2, 1, "participants must be at least 2"),
+            (2, 17, "participants must be at most 16"),
+            (17, 17, "participants must be at most 16"),
+        ];
+        for (threshold, total, expected) in cases {
+            let message = rule(threshold, total).expect("should be rejected");
+            assert!(message.starts_with(expected), "{message}");
+            assert!(
+                message.ends_with(&format!(
+                    "(got threshold {threshold}, participants {total})"
+                )),
+                "{message}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_exchange_round_estimate() {
+        let rounds = |t, n| {
+            MultisigParams::new(t, n, "t".into())
+                .unwrap()
+                .exchange_rounds()
+        };
+        assert_eq!(rounds(2, 2), 1);
+        assert_eq!(rounds(3, 3), 1);
+        assert_eq!(rounds(2, 3), 2);
+        assert_eq!(rounds(3, 5), 3);
+        assert_eq!(rounds(2, 6), 5);
+        assert_eq!(rounds(2, 16), 15);
+    }
+
+    #[test]
+    fn test_roster_validation() {
+        let params = || MultisigParams::new(2, 3, "t".into()).unwrap();
+        assert!(params().with_roster(vec![]).unwrap().roster.is_empty());
+        assert!(params().with_roster(vec!["alice".into()]).is_err());
+        assert!(params()
+            .with_roster(vec!["alice".into(), "alice".into()])
+            .is_err());
+        assert!(params()
+            .with_roster(vec!["me".into(), "bob".into()])
+            .is_err());
+    }
+
+    #[test]
+    fn test_contributors_checked_against_roster() {
+        let mut state = with_roster();
+        state.record_contribution(ME, 1, "ours");
+        let names = |n: &[&str]| n.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let blobs = names(&["a1", "b1"]);
+
+        state
+            .check_contributors(&names(&["alice", "bob"]), &blobs)
+            .unwrap();
+        assert!(matches!(
+            state.check_contributors(&names(&["carol"]), &blobs),
+            Err(crate::error::MultisigError::Wallet(
+                WalletError::UnknownParticipant { .. }
+            ))
+        ));
+        assert!(matches!(
+            state.check_contributors(&names(&["alice", "alice"]), &blobs),
+            Err(crate::error::MultisigError::Wallet(
+                WalletError::DuplicateParticipant(_)
+            ))
+        ));
+
+        state.record_contribution("alice", 1, "a1");
+        state.record_contribution("bob", 1, "b1");
+        let err = state
+            .check_contributors(&names(&["bob"]), &names(&["a1"]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::error::MultisigError::Wallet(WalletError::ParticipantMismatch { ref previous, round: 1, .. })
+                if previous == "alice"
+        ));
+        // Our own blob echoed back under a peer's name is caught too.
+        assert!(state
+            .check_contributors(&names(&["bob"]), &names(&["ours"]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_peer_info_checked_blob_by_blob() {
+        let mut state = with_roster();
+        state.record_contribution(ME, 1, "MultisigxV2R1ours");
+        let strings = |n: &[&str]| n.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let blobs = strings(&[
+            "MultisigxV2R1alice",
+            "MultisigxV2R1alice",
+            "not info!",
+            "MultisigxV2R1ours",
+        ]);
+
+        let checks = state.check_peer_info(&blobs, &[], &[], 1024);
+        let problems: Vec<_> = checks.iter().map(|c| c.problem.clone()).collect();
+        assert_eq!(problems[0], None);
+        assert_eq!(problems[1], Some(PeerInfoProblem::Duplicate { of: 1 }));
+        assert!(matches!(problems[2], Some(PeerInfoProblem::Format(_))));
+        assert_eq!(problems[3], Some(PeerInfoProblem::OwnInfo));
+
+        let err = peer_info_verdict(&checks).unwrap_err().to_string();
+        assert!(err.contains("#2: same blob as #1"), "{err}");
+        assert!(err.contains("fine: #1 —"), "{err}");
+
+        // Names and fingerprints are checked per blob too.
+        let blobs = strings(&["MultisigxV2R1alice", "MultisigxV2R1bob"]);
+        let fingerprints = vec![
+            crate::utils::fingerprint(&blobs[0]),
+            crate::utils::fingerprint("something else"),
+        ];
+        let checks = state.check_peer_info(&blobs, &strings(&["alice", "carol"]), &[], 1024);
+        assert_eq!(checks[0].problem, None);
+        assert!(matches!(
+            checks[1].problem,
+            Some(PeerInfoProblem::Roster(_))
+        ));
+        let checks = state.check_peer_info(&blobs, &[], &fingerprints, 1024);
+        assert_eq!(checks[0].problem, None);
+        assert!(matches!(
+            checks[1].problem,
+            Some(PeerInfoProblem::Fingerprint { .. })
+        ));
+        assert_eq!(checks[1].label(), "#2");
+    }
+
+    /// A wallet RPC expecting `peers` blobs that chokes on any containing
+    /// "bad", whatever else it is sent. Completing a round fails the test.
+    async fn choking_wallet(peers: usize) -> RpcClient {
+        let daemon = crate::testing::handler_daemon(move |call| {
+            let params = &call["params"];
+            let info = params["multisig_info"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default();
+            match call["method"].as_str() {
+                Some("get_version") => return Ok(serde_json::json!({"version": 65563})),
+                Some("make_multisig") => assert_eq!(params["threshold"], 0),
+                Some("exchange_multisig_keys") => assert!(info.len() < peers),
+                _ => return Err((-32601, "Method not found".into())),
+            }
+            if info
+                .iter()
+                .any(|b| b.as_str().unwrap_or_default().contains("bad"))
+            {
+                Err((-1, "Invalid multisig info".into()))
+            } else {
+                Err((-1, format!("Expected {peers} messages, got {}", info.len())))
+            }
+        })
+        .await;
+        RpcClient::new(&daemon)
+    }
+
+    #[tokio::test]
+    async fn test_isolate_bad_blob_names_the_culprit() {
+        let password = Secret::new(String::new());
+        let mut peers: Vec<String> = (0..8).map(|i| format!("MultisigxV2R1peer{i}")).collect();
+        peers[5] = "MultisigxV2R1bad".into();
+        let rpc = choking_wallet(peers.len()).await;
+
+        let params = SerializableParams::from(&MultisigParams::new(3, 9, "t".into()).unwrap());
+        let found = isolate_bad_blob(&rpc, &peers, &params, 2, MultisigApi::Current, &password)
+            .await
+            .unwrap();
+        assert_eq!(found, Some(5));
+
+        // make_multisig probes carry a threshold of 0.
+        let params = SerializableParams::from(&MultisigParams::new(2, 9, "t".into()).unwrap());
+        let found = isolate_bad_blob(&rpc, &peers, &params, 1, MultisigApi::Current, &password)
+            .await
+            .unwrap();
+        assert_eq!(found, Some(5));
+
+        // Nothing to find when every blob is fine.
+        peers[5] = "MultisigxV2R1peer5".into();
+        let found = isolate_bad_blob(&rpc, &peers, &params, 1, MultisigApi::Current, &password)
+            .await
+            .unwrap();
+        assert_eq!(found, None);
+    }
+
+    #[test]
+    fn test_participants_lists_rounds() {
+        let mut state = with_roster();
+        state.record_contribution(ME, 1, "m1");
+        state.record_contribution("alice", 1, "a1");
+        state.record_contribution(ME, 2, "m2");
+        state.record_contribution("alice", 2, "a2");
+
+        let participants = state.participants();
+        let names: Vec<_> = participants.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["me", "alice", "bob"]);
+        assert_eq!(participants[1].rounds, [1, 2]);
+        assert_eq!(
+            participants[1].last_digest.as_deref(),
+            Some(crate::utils::blob_digest("a2").as_str())
+        );
+        assert!(participants[2].rounds.is_empty());
+        assert!(StateFile::new(ready("4a")).participants().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_address_book_lookup() {
+        let rpc = RpcClient::new(
+            &crate::testing::method_daemon(&[(
+                "get_address_book",
+                r#"{"entries":[
+                    {"address":"4a","description":"Alice","index":0,"payment_id":""},
+                    {"address":"4b","description":"Alice (old)","index":1,"payment_id":""},
+                    {"address":"8c","description":"Carol","index":2,"payment_id":""}
+                ]}"#,
+            )])
+            .await,
+        );
+        let entries = address_book(&rpc).await.unwrap();
+        let found = |name| -> Vec<&str> {
+            lookup_address_book(&entries, name)
+                .iter()
+                .map(|e| e.address.as_str())
+                .collect()
+        };
+        assert_eq!(found("alice"), ["4a"]);
+        assert_eq!(found("Al"), ["4a", "4b"]);
+        assert_eq!(found("car"), ["8c"]);
+        assert!(found("bob").is_empty());
+        assert!(found(" ").is_empty());
+    }
+}
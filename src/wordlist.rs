@@ -0,0 +1,35 @@
+//! Word list for spoken fingerprints.
+//!
+//! 256 short, distinct English words, one per byte value. The order is part of
+//! the fingerprint format: never reorder or replace entries, or previously
+//! shared fingerprints stop matching.
+
+pub(crate) const WORDS: [&str; 256] = [
+    "acid", "acorn", "actor", "adobe", "agent", "alarm", "album", "alpha", "amber", "angel",
+    "ankle", "apple", "april", "arena", "arrow", "aspen", "atlas", "attic", "audio", "award",
+    "bacon", "badge", "bagel", "baker", "banjo", "baron", "basil", "basin", "beach", "beard",
+    "berry", "bingo", "bison", "blade", "blaze", "block", "bloom", "board", "bonus", "boost",
+    "brain", "brass", "bread", "brick", "bridge", "broom", "brush", "bugle", "cabin", "cable",
+    "cactus", "camel", "candy", "canoe", "canyon", "cargo", "carpet", "castle", "cedar", "chalk",
+    "chess", "chief", "cider", "citrus", "clock", "cloud", "clover", "cobra", "cocoa", "comet",
+    "coral", "corner", "cotton", "crane", "crayon", "crown", "crystal", "cube", "daisy", "dance",
+    "delta", "denim", "desert", "diesel", "dingo", "disco", "dolphin", "donkey", "dragon", "dream",
+    "drum", "eagle", "echo", "elbow", "ember", "engine", "falcon", "fence", "ferry", "fiber",
+    "fiddle", "flame", "flute", "forest", "fossil", "fox", "frost", "galaxy", "garden", "garlic",
+    "gecko", "geyser", "ginger", "glacier", "globe", "golf", "gopher", "granite", "grape",
+    "gravel", "guitar", "hammer", "harbor", "hazel", "helmet", "hiking", "honey", "hotel", "husky",
+    "igloo", "iris", "island", "ivory", "jacket", "jaguar", "jasmine", "jelly", "jersey", "jigsaw",
+    "juice", "jungle", "kayak", "kernel", "kettle", "kiwi", "koala", "ladder", "lagoon", "lava",
+    "lemon", "lilac", "lizard", "lobster", "locket", "lotus", "lunar", "magnet", "mango", "maple",
+    "marble", "meadow", "melon", "meteor", "mint", "mirror", "monkey", "moose", "motor", "muffin",
+    "nectar", "needle", "nickel", "ninja", "noodle", "oasis", "ocean", "octopus", "ohio", "olive",
+    "onion", "opera", "orbit", "orchid", "otter", "oxygen", "paddle", "panda", "paper", "parade",
+    "parrot", "pastel", "peanut", "pebble", "pepper", "piano", "pickle", "pilot", "planet",
+    "plaza", "pocket", "polar", "potato", "prism", "pumpkin", "puzzle", "quartz", "quiver",
+    "rabbit", "radar", "radio", "raven", "record", "ribbon", "rocket", "rodeo", "ruby", "saddle",
+    "salmon", "silver", "sketch", "sonar", "spider", "spruce", "squid", "stadium", "summit",
+    "sunset", "tango", "tiger", "timber", "toast", "tomato", "topaz", "tractor", "tulip", "tundra",
+    "turtle", "umbrella", "unicorn", "valley", "velvet", "violin", "vortex", "walnut", "walrus",
+    "whale", "willow", "window", "winter", "wizard", "yacht", "yogurt", "zebra", "zenith",
+    "zipper", "zodiac",
+];
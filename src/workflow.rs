@@ -0,0 +1,472 @@
+//! What to do next: the guidance printed after each command.
+//!
+//! [`next_steps`] reads the wallet state and the journal and names the
+//! actions that move the wallet along — the next key exchange round, the
+//! address check, the multisig info sync, and whatever each in-flight
+//! transaction is waiting for. It only looks at what was saved, so the
+//! advice is the same whichever command saved it. Text output prints the
+//! steps under "Next steps:", in the language [`messages`] picked;
+//! `--output json` lists their [`Action`] identifiers as the envelope's
+//! `next_steps`.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::config::DataDir;
+use crate::error::{MultisigError, WalletError};
+use crate::journal::{Journal, JournalEntry, TxStatus};
+use crate::messages::{self, Msg};
+use crate::wallet::{self, StateFile, WalletState};
+
+/// A kind of next step, as listed in `--output json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    CreateWallet,
+    ExchangeKeys,
+    RepairState,
+    VerifyAddress,
+    ExportInfo,
+    ImportInfo,
+    SignTx,
+    /// Hand a partly signed tx set to the co-signers who haven't signed.
+    SendTxSet,
+    SubmitTx,
+    Relay,
+}
+
+impl Action {
+    /// Every action, for the `next_steps` schema.
+    pub const ALL: [Action; 10] = [
+        Action::CreateWallet,
+        Action::ExchangeKeys,
+        Action::RepairState,
+        Action::VerifyAddress,
+        Action::ExportInfo,
+        Action::ImportInfo,
+        Action::SignTx,
+        Action::SendTxSet,
+        Action::SubmitTx,
+        Action::Relay,
+    ];
+}
+
+/// One thing to do next, with the advice for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub action: Action,
+    pub text: String,
+}
+
+impl Step {
+    fn new(action: Action, text: impl Into<String>) -> Self {
+        Self {
+            action,
+            text: text.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// The steps for the wallet in `data_dir`. A missing state file means
+/// creating a wallet; one that can't be read gives no advice, since the
+/// command that read it has already said what's wrong.
+pub fn load(data_dir: &DataDir) -> Vec<Step> {
+    let state = match wallet::load_state(data_dir) {
+        Ok(state) => Some(state),
+        Err(MultisigError::Wallet(WalletError::NotFound(_))) => None,
+        Err(_) => return Vec::new(),
+    };
+    let journal = Journal::load(data_dir).unwrap_or_default();
+    next_steps(state.as_ref(), &journal)
+}
+
+/// The steps for `state` (`None` before `create-wallet`) and `journal`, most
+/// urgent first. Empty when nothing is waiting.
+pub fn next_steps(state: Option<&StateFile>, journal: &Journal) -> Vec<Step> {
+    let Some(state) = state else {
+        return vec![Step::new(
+            Action::CreateWallet,
+            messages::text(Msg::NextCreateWallet),
+        )];
+    };
+
+    let params = match &state.wallet {
+        WalletState::WatchOnly { .. } => return Vec::new(),
+        WalletState::Created { params, .. }
+        | WalletState::KeyExchangeInProgress { params, .. }
+        | WalletState::Ready { params, .. } => params,
+    };
+    let peers = if params.roster.is_empty() {
+        messages::format(Msg::NextPeers, &[("count", &(params.total - 1))])
+    } else {
+        params.roster.join(", ")
+    };
+
+    if let Some(attempt) = &state.exchange_attempt {
+        return vec![Step::new(
+            Action::RepairState,
+            messages::format(Msg::NextRepairState, &[("round", &attempt.round)]),
+        )];
+    }
+    match &state.wallet {
+        WalletState::Created { .. } => {
+            return vec![Step::new(
+                Action::ExchangeKeys,
+                messages::format(Msg::NextExchangeFirst, &[("peers", &peers)]),
+            )];
+        }
+        WalletState::KeyExchangeInProgress {
+            rounds_completed, ..
+        } => {
+            return vec![Step::new(
+                Action::ExchangeKeys,
+                messages::format(
+                    Msg::NextExchangeAgain,
+                    &[("round", rounds_completed), ("peers", &peers)],
+                ),
+            )];
+        }
+        WalletState::Ready { .. } | WalletState::WatchOnly { .. } => {}
+    }
+
+    let mut steps = Vec::new();
+    if !state.verified {
+        steps.push(Step::new(
+            Action::VerifyAddress,
+            messages::format(Msg::NextVerifyAddress, &[("peers", &peers)]),
+        ));
+    }
+    if let Some(step) = sync_step(state, journal, &peers) {
+        steps.push(step);
+    }
+    steps.extend(
+        journal
+            .pending()
+            .into_iter()
+            .filter(|e| e.superseded_by.is_none())
+            .filter_map(|e| tx_step(e, &params.roster)),
+    );
+    steps
+}
+
+/// The export or import the wallet needs before it can sign, if any. A
+/// transaction submitted since the last import spends outputs the peers'
+/// info still counts, so it calls for a fresh round.
+fn sync_step(state: &StateFile, journal: &Journal, peers: &str) -> Option<Step> {
+    let spent_at = journal
+        .entries
+        .iter()
+        .filter(|e| e.status == TxStatus::Submitted)
+        .map(JournalEntry::last_action_at)
+        .max();
+    let current = |at: DateTime<Utc>| spent_at.is_none_or(|spent| at >= spent);
+    if state
+        .last_sync
+        .as_ref()
+        .is_some_and(|s| current(s.synced_at))
+    {
+        return None;
+    }
+    Some(
+        if state
+            .last_export
+            .as_ref()
+            .is_some_and(|e| current(e.exported_at))
+        {
+            Step::new(
+                Action::ImportInfo,
+                messages::format(Msg::NextImportInfo, &[("peers", &peers)]),
+            )
+        } else {
+            Step::new(
+                Action::ExportInfo,
+                messages::format(Msg::NextExportInfo, &[("peers", &peers)]),
+            )
+        },
+    )
+}
+
+/// What the pending `entry` is waiting for.
+fn tx_step(entry: &JournalEntry, roster: &[String]) -> Option<Step> {
+    let id = entry.id;
+    let step = match entry.status {
+        TxStatus::AwaitingSignatures if !entry.signed_by(wallet::ME) => Step::new(
+            Action::SignTx,
+            messages::format(Msg::NextSignTx, &[("id", &id)]),
+        ),
+        TxStatus::AwaitingSignatures => {
+            let needed = entry
+                .signatures_required
+                .saturating_sub(entry.signatures_collected)
+                .max(1);
+            let unsigned: Vec<&str> = roster
+                .iter()
+                .map(String::as_str)
+                .filter(|name| !entry.signed_by(name))
+                .collect();
+            let to = if unsigned.is_empty() {
+                messages::format(Msg::NextMoreCosigners, &[("count", &needed)])
+            } else if unsigned.len() as u32 <= needed {
+                unsigned.join(", ")
+            } else {
+                messages::format(
+                    Msg::NextAnyOf,
+                    &[("count", &needed), ("names", &unsigned.join(", "))],
+                )
+            };
+            Step::new(
+                Action::SendTxSet,
+                messages::format(Msg::NextSendTxSet, &[("id", &id), ("to", &to)]),
+            )
+        }
+        TxStatus::ReadyToSubmit => Step::new(
+            Action::SubmitTx,
+            messages::format(Msg::NextSubmitTx, &[("id", &id)]),
+        ),
+        TxStatus::AwaitingRelay => Step::new(
+            Action::Relay,
+            messages::format(Msg::NextRelay, &[("id", &id)]),
+        ),
+        TxStatus::Submitted | TxStatus::Discarded => return None,
+    };
+    Some(step)
+}
+
+/// The "Next steps:" block for text output; empty when there are none.
+pub fn render(steps: &[Step]) -> String {
+    if steps.is_empty() {
+        return String::new();
+    }
+    let mut out = messages::text(Msg::NextHeader) + "\n";
+    for step in steps {
+        out.push_str(&format!("  - {step}\n"));
+    }
+    out
+}
+
+/// The identifiers of `steps` for `--output json`, each listed once.
+pub fn actions(steps: &[Step]) -> Vec<Action> {
+    let mut actions = Vec::new();
+    for step in steps {
+        if !actions.contains(&step.action) {
+            actions.push(step.action);
+        }
+    }
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{Amount, Destination, Recipient, TransferDescription};
+    use crate::wallet::{ExchangeAttempt, ExportRecord, SerializableParams, SyncRecord};
+
+    fn params(roster: &[&str]) -> SerializableParams {
+        SerializableParams {
+            threshold: 2,
+            total: 3,
+            label: "ops".into(),
+            roster: roster.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn ready(roster: &[&str]) -> StateFile {
+        let mut state = StateFile::new(WalletState::Ready {
+            wallet_path: "w".into(),
+            address: "4addr".into(),
+            params: params(roster),
+            restore_height: None,
+        });
+        state.verified = true;
+        state
+    }
+
+    fn synced(mut state: StateFile, at: DateTime<Utc>) -> StateFile {
+        state.last_sync = Some(SyncRecord {
+            synced_at: at,
+            wallet_height: 100,
+            balance: 5,
+        });
+        state
+    }
+
+    fn actions_for(state: &StateFile, journal: &Journal) -> Vec<Action> {
+        next_steps(Some(state), journal)
+            .iter()
+            .map(|s| s.action)
+            .collect()
+    }
+
+    fn built(journal: &mut Journal, tx_set: &str, signatures_required: u32) {
+        journal.record_built(
+            &[Destination::new_unchecked("4dest", Amount(10))],
+            1,
+            &["hash".into()],
+            tx_set,
+            signatures_required,
+            None,
+        );
+    }
+
+    /// What `describe_transfer` says of the sets [`built`] makes.
+    fn described() -> Vec<TransferDescription> {
+        vec![TransferDescription {
+            recipients: vec![Recipient {
+                address: "4dest".into(),
+                amount: 10,
+            }],
+            amount_in: 0,
+            amount_out: 0,
+            change_amount: 0,
+            change_address: String::new(),
+            fee: 1,
+            ring_size: 16,
+            unlock_time: 0,
+        }]
+    }
+
+    #[test]
+    fn test_no_wallet_suggests_create() {
+        let steps = next_steps(None, &Journal::default());
+        assert_eq!(actions(&steps), [Action::CreateWallet]);
+        assert!(render(&steps).starts_with("Next steps:\n  - run create-wallet"));
+    }
+
+    #[test]
+    fn test_key_exchange_states() {
+        let journal = Journal::default();
+        let created = StateFile::new(WalletState::Created {
+            wallet_path: "w".into(),
+            params: params(&[]),
+            restore_height: None,
+        });
+        let steps = next_steps(Some(&created), &journal);
+        assert_eq!(actions(&steps), [Action::ExchangeKeys]);
+        assert!(steps[0].text.contains("to your 2 peer(s)"), "{}", steps[0]);
+
+        let mut partial = StateFile::new(WalletState::KeyExchangeInProgress {
+            wallet_path: "w".into(),
+            params: params(&["alice", "bob"]),
+            rounds_completed: 1,
+            rounds_required: 2,
+            restore_height: None,
+        });
+        assert_eq!(
+            next_steps(Some(&partial), &journal)[0].text,
+            "send your round 1 blob to alice, bob and wait for theirs, then run exchange-keys \
+             again"
+        );
+
+        partial.exchange_attempt = Some(ExchangeAttempt {
+            round: 2,
+            inputs: Vec::new(),
+            started_at: Utc::now(),
+        });
+        assert_eq!(actions_for(&partial, &journal), [Action::RepairState]);
+    }
+
+    #[test]
+    fn test_ready_wallet_verifies_then_syncs() {
+        let journal = Journal::default();
+        let mut state = ready(&["alice", "bob"]);
+        state.verified = false;
+        assert_eq!(
+            actions_for(&state, &journal),
+            [Action::VerifyAddress, Action::ExportInfo]
+        );
+
+        state.verified = true;
+        state.last_export = Some(ExportRecord {
+            digest: "d".into(),
+            exported_at: Utc::now(),
+            height: None,
+            info: None,
+        });
+        let steps = next_steps(Some(&state), &journal);
+        assert_eq!(
+            steps[0].text,
+            "run import-info with the exports you get back from alice, bob"
+        );
+
+        let state = synced(state, Utc::now());
+        assert!(next_steps(Some(&state), &journal).is_empty());
+        assert_eq!(render(&[]), "");
+    }
+
+    #[test]
+    fn test_pending_transactions() {
+        let state = synced(ready(&["alice", "bob", "carol"]), Utc::now());
+        let mut journal = Journal::default();
+        built(&mut journal, "set-1", 2);
+        let steps = next_steps(Some(&state), &journal);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(
+            steps[0].text,
+            "send tx set #1 to any 1 of: alice, bob, carol"
+        );
+
+        // A co-signer who already signed isn't asked again.
+        let mut journal = Journal::default();
+        built(&mut journal, "set-2", 3);
+        journal.record_signer("set-2b", Some(&described()), "bob", 3);
+        let steps = next_steps(Some(&state), &journal);
+        assert_eq!(steps[0].text, "send tx set #1 to any 1 of: alice, carol");
+
+        // A set a peer sent on unsigned by us is ours to sign.
+        let mut journal = Journal::default();
+        journal.record_signer("incoming", None, "alice", 3);
+        assert_eq!(actions_for(&state, &journal), [Action::SignTx]);
+
+        let mut journal = Journal::default();
+        built(&mut journal, "set-1", 2);
+        journal.record_signer("set-1b", Some(&described()), "alice", 2);
+        assert_eq!(actions_for(&state, &journal), [Action::SubmitTx]);
+
+        journal.record_held("set-1b", &["hash".into()], None, 2);
+        assert_eq!(actions_for(&state, &journal), [Action::Relay]);
+    }
+
+    #[test]
+    fn test_submitted_transaction_needs_new_sync() {
+        let state = synced(
+            ready(&["alice", "bob"]),
+            Utc::now() - chrono::Duration::hours(1),
+        );
+        let mut journal = Journal::default();
+        built(&mut journal, "set-1", 2);
+        journal.record_submitted("set-1b", &["hash".into()], None, 2);
+        let steps = next_steps(Some(&state), &journal);
+        assert_eq!(actions(&steps), [Action::ExportInfo]);
+    }
+
+    #[test]
+    fn test_watch_only_has_no_steps() {
+        let state = StateFile::new(WalletState::WatchOnly {
+            wallet_path: "w".into(),
+            address: "4addr".into(),
+            label: "ops".into(),
+            restore_height: None,
+        });
+        assert!(next_steps(Some(&state), &Journal::default()).is_empty());
+    }
+
+    #[test]
+    fn test_actions_are_listed_once() {
+        let steps = [
+            Step::new(Action::SignTx, "a"),
+            Step::new(Action::SignTx, "b"),
+            Step::new(Action::SubmitTx, "c"),
+        ];
+        assert_eq!(actions(&steps), [Action::SignTx, Action::SubmitTx]);
+        assert_eq!(
+            serde_json::to_value(Action::SendTxSet).unwrap(),
+            "send_tx_set"
+        );
+    }
+}
@@ -0,0 +1,19 @@
+mod common;
+
+use common::{config, run, wallet_rpc};
+
+#[test]
+fn tagging_needs_an_explicit_account() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), wallet_rpc("MultisigxV2R1info"));
+
+    for args in [
+        &["account", "tag", "--tag", "ops"][..],
+        &["account", "untag"],
+    ] {
+        let output = run(&config, args, "");
+        assert!(!output.status.success(), "{args:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("needs --account"), "{args:?}: {stderr}");
+    }
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use common::run;
+
+/// A data directory holding an unsigned state file, plus a config pointing
+/// at it.
+fn setup(dir: &Path) -> (PathBuf, PathBuf) {
+    let data_dir = dir.join("data");
+    std::fs::create_dir_all(&data_dir).unwrap();
+    std::fs::write(
+        data_dir.join("wallet_state.json"),
+        serde_json::json!({
+            "Ready": {
+                "wallet_path": "w",
+                "address": "4AdUndXHHZ6cfufTMvppY6JwXNouMBzSkbLYfpAV5Usx",
+                "params": { "threshold": 2, "total": 3, "label": "test" },
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let config = dir.join("config.json");
+    std::fs::write(
+        &config,
+        serde_json::json!({
+            "network": "mainnet",
+            "daemon": { "host": "127.0.0.1", "port": 1, "tls": false },
+            "data_dir": data_dir,
+        })
+        .to_string(),
+    )
+    .unwrap();
+    (config, data_dir)
+}
+
+fn lines(data_dir: &Path) -> Vec<serde_json::Value> {
+    std::fs::read_to_string(data_dir.join("audit.log"))
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect()
+}
+
+#[test]
+fn writing_commands_are_chained_and_verified() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, data_dir) = setup(dir.path());
+
+    let output = run(&config, &["state", "rehash"], "y\n");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let failed = run(
+        &config,
+        &[
+            "exchange-keys",
+            "--info",
+            "MultisigxV2R1abc",
+            "--password",
+            "hunter2",
+        ],
+        "",
+    );
+    assert!(!failed.status.success());
+    // Read-only commands leave no trace.
+    run(&config, &["journal"], "");
+
+    let log = lines(&data_dir);
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[0]["command"], "state");
+    assert_eq!(log[0]["outcome"], "ok");
+    assert_eq!(log[1]["command"], "exchange-keys");
+    assert_ne!(log[1]["outcome"], "ok");
+    let args = log[1]["args"].to_string();
+    assert!(!args.contains("hunter2"), "{args}");
+    assert!(args.contains("<redacted>"), "{args}");
+
+    let verified = run(&config, &["audit", "verify"], "");
+    assert!(
+        verified.status.success(),
+        "{}",
+        String::from_utf8_lossy(&verified.stderr)
+    );
+    assert!(String::from_utf8_lossy(&verified.stdout).contains("intact: 2 entries"));
+
+    let path = data_dir.join("audit.log");
+    let text = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, text.replacen("\"state\"", "\"reset\"", 1)).unwrap();
+    let broken = run(&config, &["audit", "verify"], "");
+    assert!(!broken.status.success());
+    let stderr = String::from_utf8_lossy(&broken.stderr);
+    assert!(stderr.contains("broken at line 1"), "{stderr}");
+}
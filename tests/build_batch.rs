@@ -0,0 +1,42 @@
+mod common;
+
+#[test]
+fn invalid_rows_are_all_reported_before_building() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.json");
+    std::fs::write(
+        &config,
+        serde_json::json!({
+            "network": "mainnet",
+            "daemon": { "host": "127.0.0.1", "port": 1, "tls": false },
+            "data_dir": dir.path().join("data"),
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let good = "47Y1NtfpLfSSnZrZRcy6BF18VrY5JHibsSkEsbgoTW5z1FU1cKooBSgY92YDdgy8kWDv8bv1YaD8eiC4sysM12M13sAZKHr";
+    let batch = dir.path().join("payouts.csv");
+    std::fs::write(
+        &batch,
+        format!("address,amount,note\n{good},1.5,rent\n9abc,1\n{good},lots\n"),
+    )
+    .unwrap();
+
+    let output = common::command(&config)
+        .args(["build-batch", "--out-dir"])
+        .arg(dir.path().join("sets"))
+        .arg("--file")
+        .arg(&batch)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("2 invalid row(s)"), "{stderr}");
+    assert!(
+        stderr.contains("row 3: invalid destination address"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("row 4: amount"), "{stderr}");
+    assert!(!dir.path().join("sets").exists());
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use std::path::Path;
+
+use common::{config, run, wallet_rpc, REFUND, SELLER};
+
+/// A config for a 2-of-2 wallet, set up through the fake wallet RPC.
+fn ready_wallet(dir: &Path) -> std::path::PathBuf {
+    let config = config(dir, wallet_rpc("MultisigxV2R1info"));
+    for args in [
+        &["create-wallet", "-m", "2", "-n", "2"][..],
+        &["exchange-keys", "--info", "MultisigxV2R1peerinfo"],
+    ] {
+        let output = run(&config, args, "");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    config
+}
+
+#[test]
+fn interactive_destinations_are_asked_again_until_valid() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = ready_wallet(dir.path());
+
+    // A typo, a book entry of another network, then an address book name;
+    // a bad amount, then a good one.
+    let answers = format!("4abc\nfaucet\nsel\nlots\n1.5\nrent\ny\n{REFUND}\n0.25\n\nn\ny\n");
+    let output = run(
+        &config,
+        &["build-tx", "--interactive", "--allow-stale-sync"],
+        &answers,
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stdout}\n{stderr}");
+    assert!(stderr.contains("invalid destination address"), "{stderr}");
+    assert!(
+        stderr.contains("this is a stagenet address, not a mainnet one"),
+        "{stderr}"
+    );
+    assert!(stderr.contains(&format!("Seller: {SELLER}")), "{stderr}");
+    assert!(stderr.contains("Build this transaction?"), "{stderr}");
+    assert!(
+        stdout.contains("Total:         1.750000000000 XMR"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("6d756c74697369675f7478736574"), "{stdout}");
+
+    let journal = std::fs::read_to_string(dir.path().join("data/journal.json")).unwrap();
+    assert!(journal.contains("\"note\": \"rent\""), "{journal}");
+}
+
+#[test]
+fn interactive_build_can_be_declined() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = ready_wallet(dir.path());
+
+    let output = run(
+        &config,
+        &["build-tx", "--interactive", "--allow-stale-sync"],
+        &format!("{SELLER}\n1\n\nn\nn\n"),
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("aborted; nothing was built"), "{stderr}");
+    assert!(!dir.path().join("data/journal.json").exists());
+}
+
+#[test]
+fn unverified_address_warning_is_shown_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = ready_wallet(dir.path());
+    let build = [
+        "build-tx",
+        "--address",
+        REFUND,
+        "--amount",
+        "250000000000",
+        "--allow-stale-sync",
+    ];
+
+    let first = run(&config, &build, "");
+    let stderr = String::from_utf8_lossy(&first.stderr);
+    assert!(first.status.success(), "{stderr}");
+    assert!(stderr.contains("never been verified"), "{stderr}");
+
+    let second = run(&config, &build, "");
+    let stderr = String::from_utf8_lossy(&second.stderr);
+    assert!(second.status.success(), "{stderr}");
+    assert!(!stderr.contains("never been verified"), "{stderr}");
+}
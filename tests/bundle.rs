@@ -0,0 +1,168 @@
+mod common;
+
+use std::path::Path;
+
+use common::{config, run, stdout_of, wallet_rpc, REFUND, SELLER};
+use monero_multisig::bundle::{Bundle, Local};
+use monero_multisig::wallet::AddressBookEntry;
+use serde_json::{json, Value};
+
+/// A machine with a 2-of-3 wallet, `manifest_secret` and `settings` merged
+/// into its config.
+fn machine(dir: &Path, roster: &[&str], settings: Value) -> std::path::PathBuf {
+    std::fs::create_dir_all(dir).unwrap();
+    let path = config(dir, wallet_rpc("MultisigxV2R1info"));
+    let mut file: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    file["manifest_secret"] = json!("s3cret");
+    for (key, value) in settings.as_object().unwrap() {
+        file[key] = value.clone();
+    }
+    std::fs::write(&path, file.to_string()).unwrap();
+    let mut create = vec!["create-wallet", "-m", "2", "-n", "3"];
+    for name in roster {
+        create.extend(["--participant", name]);
+    }
+    stdout_of(&run(&path, &create, ""));
+    path
+}
+
+fn read_config(path: &Path) -> Value {
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn export_then_import_on_a_new_machine() {
+    let dir = tempfile::tempdir().unwrap();
+    let alice = machine(
+        &dir.path().join("alice"),
+        &["bob", "carol"],
+        json!({
+            "sweep_warning_percent": 50,
+            "policy": { "allowed_destinations": [SELLER] },
+            "notifications": { "webhook_url": "https://hooks.example", "secret": "alice-only" },
+        }),
+    );
+    let file = dir.path().join("group.bundle");
+    let out = file.to_str().unwrap();
+
+    let refused = run(&alice, &["bundle", "export", "--out", out], "");
+    assert!(!refused.status.success());
+    let stderr = String::from_utf8_lossy(&refused.stderr);
+    assert!(stderr.contains("name yourself with --from"), "{stderr}");
+
+    let exported = stdout_of(&run(
+        &alice,
+        &["bundle", "export", "--out", out, "--from", "alice"],
+        "",
+    ));
+    assert!(
+        exported.contains("Roster:        alice, bob, carol"),
+        "{exported}"
+    );
+    let text = std::fs::read_to_string(&file).unwrap();
+    assert!(
+        !text.contains("alice-only") && !text.contains("s3cret"),
+        "{text}"
+    );
+    assert!(!text.contains("hooks.example"), "{text}");
+
+    let bob = machine(
+        &dir.path().join("bob"),
+        &[],
+        json!({ "notifications": { "secret": "bob-only" } }),
+    );
+    let imported = stdout_of(&run(
+        &bob,
+        &["bundle", "import", out, "--as", "bob", "--yes"],
+        "",
+    ));
+    assert!(imported.contains("Bundle from alice"), "{imported}");
+    assert!(
+        imported.contains("\n+ roster: alice, carol\n"),
+        "{imported}"
+    );
+    assert!(
+        imported.contains(&format!("+ allowed destinations: {SELLER}")),
+        "{imported}"
+    );
+    assert!(
+        imported.contains("- sweep_warning_percent: 90\n+ sweep_warning_percent: 50"),
+        "{imported}"
+    );
+    // The wallet RPC's address book already has what the bundle carries.
+    assert!(!imported.contains("address book"), "{imported}");
+
+    let config = read_config(&bob);
+    assert_eq!(config["sweep_warning_percent"], 50);
+    assert_eq!(config["policy"]["allowed_destinations"], json!([SELLER]));
+    assert_eq!(config["notifications"], json!({ "secret": "bob-only" }));
+    assert_eq!(config["manifest_secret"], "s3cret");
+    let participants = stdout_of(&run(&bob, &["participants"], ""));
+    assert!(
+        participants.contains("alice") && participants.contains("carol"),
+        "{participants}"
+    );
+
+    let again = stdout_of(&run(&bob, &["bundle", "import", out], ""));
+    assert!(
+        again.contains("Nothing to import: this machine already matches the bundle."),
+        "{again}"
+    );
+}
+
+#[test]
+fn tampered_bundles_are_rejected_and_conflicts_asked_about() {
+    let dir = tempfile::tempdir().unwrap();
+    let carol = machine(dir.path(), &["alice", "bob"], json!({}));
+    let local = Local {
+        address_book: vec![AddressBookEntry {
+            index: 0,
+            address: REFUND.into(),
+            description: "Seller".into(),
+        }],
+        ..Local::default()
+    };
+    let bundle = Bundle::new(
+        vec!["alice".into(), "dave".into(), "carol".into()],
+        &local,
+        "s3cret",
+    );
+    let file = dir.path().join("group.bundle");
+    let out = file.to_str().unwrap();
+
+    let mut tampered = serde_json::to_value(&bundle).unwrap();
+    tampered["address_book"][0]["address"] = json!("4attacker");
+    std::fs::write(&file, tampered.to_string()).unwrap();
+    let rejected = run(&carol, &["bundle", "import", out, "--yes"], "");
+    assert!(!rejected.status.success());
+    let stderr = String::from_utf8_lossy(&rejected.stderr);
+    assert!(
+        stderr.contains("the bundle's HMAC doesn't match"),
+        "{stderr}"
+    );
+    assert!(String::from_utf8_lossy(&rejected.stdout).is_empty());
+
+    std::fs::write(&file, serde_json::to_string(&bundle).unwrap()).unwrap();
+    // Taking the bundle's Seller, keeping our roster, then confirming.
+    let output = run(
+        &carol,
+        &["bundle", "import", out, "--as", "carol"],
+        "r\nk\ny\n",
+    );
+    let imported = stdout_of(&output);
+    assert!(
+        imported.contains(&format!(
+            "Conflict:\n- address book: Seller = {SELLER}\n+ address book: Seller = {REFUND}"
+        )),
+        "{imported}"
+    );
+    assert!(
+        imported.contains("Conflict:\n- roster: alice, bob\n+ roster: alice, dave"),
+        "{imported}"
+    );
+    assert!(imported.contains("Applied 1 change(s)."), "{imported}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("take the bundle's [r]?"), "{stderr}");
+    let participants = stdout_of(&run(&carol, &["participants"], ""));
+    assert!(!participants.contains("dave"), "{participants}");
+}
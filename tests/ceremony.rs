@@ -0,0 +1,314 @@
+//! A whole 2-of-3 setup and spend, run through the binary by three
+//! participants, each with their own data directory and their own wallet
+//! RPC. The wallet RPCs answer from the scripts in `tests/fixtures/ceremony`,
+//! so when the RPC surface changes, update them there. Read top to bottom,
+//! the test is also the intended order of operations.
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+
+const DESTINATION: &str = "4AdUndXHHZ6cfufTMvppY6JwXNouMBzSkbLYfpAV5Usx3skxNgYeYTRj5UzqtReoS44qo9mtmXCqY45DJ852K5Jv2684Rge";
+const TX_HASH: &str = "c3f5a1b2d4e6f8091a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f7081";
+
+fn fixture(name: &str) -> Value {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/ceremony")
+        .join(format!("{name}.json"));
+    serde_json::from_str(&std::fs::read_to_string(&path).unwrap())
+        .unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+}
+
+/// Where a scripted wallet RPC is in its script.
+struct Script {
+    stages: Vec<Value>,
+    stage: usize,
+    shared: Value,
+    /// Every method called, in order, for failure messages.
+    calls: Vec<String>,
+}
+
+impl Script {
+    /// The scripted result for `method`: from the current stage, else an
+    /// earlier one, else `shared.json`. Calling a stage's `until` method
+    /// moves on to the next stage.
+    fn answer(&mut self, method: &str) -> Option<Value> {
+        self.calls.push(method.to_string());
+        let result = self.stages[..=self.stage]
+            .iter()
+            .rev()
+            .find_map(|stage| stage["results"].get(method))
+            .or_else(|| self.shared.get(method))
+            .cloned();
+        if self.stages[self.stage]["until"] == method {
+            self.stage += 1;
+        }
+        result
+    }
+}
+
+struct Participant {
+    name: &'static str,
+    dir: tempfile::TempDir,
+    config: PathBuf,
+    script: Arc<Mutex<Script>>,
+}
+
+impl Participant {
+    fn new(name: &'static str) -> Self {
+        let script = Arc::new(Mutex::new(Script {
+            stages: fixture(name)["stages"].as_array().unwrap().clone(),
+            stage: 0,
+            shared: fixture("shared"),
+            calls: Vec::new(),
+        }));
+        let port = scripted_rpc(Arc::clone(&script));
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("config.json");
+        std::fs::write(
+            &config,
+            json!({
+                "network": "mainnet",
+                "daemon": { "host": "127.0.0.1", "port": port, "tls": false },
+                "data_dir": dir.path().join("data"),
+                "manifest_secret": "group secret",
+            })
+            .to_string(),
+        )
+        .unwrap();
+        Participant {
+            name,
+            dir,
+            config,
+            script,
+        }
+    }
+
+    fn output(&self, args: &[&str], stdin: &str) -> Output {
+        common::run(&self.config, args, stdin)
+    }
+
+    /// Run a command that must succeed and return its stdout.
+    fn run(&self, args: &[&str]) -> String {
+        self.answer(args, "")
+    }
+
+    /// Like [`Participant::run`], answering its prompts with `stdin`.
+    fn answer(&self, args: &[&str], stdin: &str) -> String {
+        let output = self.output(args, stdin);
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        assert!(
+            output.status.success(),
+            "{} {args:?} failed\n{stdout}\n{}\nwallet RPC calls: {:?}",
+            self.name,
+            String::from_utf8_lossy(&output.stderr),
+            self.script.lock().unwrap().calls,
+        );
+        stdout
+    }
+
+    fn run_json(&self, args: &[&str]) -> Value {
+        let stdout = self.run(&[&["--output", "json"], args].concat());
+        serde_json::from_str::<Value>(&stdout).unwrap()["result"].clone()
+    }
+
+    fn journal(&self) -> Value {
+        let path = self.dir.path().join("data/journal.json");
+        match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).unwrap(),
+            Err(_) => Value::Null,
+        }
+    }
+}
+
+/// A wallet RPC (and daemon) answering from `script`, with "Method not
+/// found" for anything it doesn't script.
+fn scripted_rpc(script: Arc<Mutex<Script>>) -> u16 {
+    common::serve(move |path, call| {
+        // Daemon endpoints other than `/json_rpc` are scripted by path and
+        // answered without the JSON-RPC envelope.
+        let method = match path {
+            "/json_rpc" => call["method"].as_str().unwrap_or_default(),
+            other => other,
+        };
+        let result = script.lock().unwrap().answer(method);
+        match result {
+            Some(result) if path != "/json_rpc" => result,
+            Some(result) => json!({ "jsonrpc": "2.0", "id": call["id"], "result": result }),
+            None => json!({
+                "jsonrpc": "2.0",
+                "id": call["id"],
+                "error": { "code": -32601, "message": "Method not found" },
+            }),
+        }
+    })
+}
+
+/// The line after `label` in `stdout`, where blobs are printed.
+fn blob_after(stdout: &str, label: &str) -> String {
+    let mut lines = stdout.lines().skip_while(|l| !l.contains(label)).skip(1);
+    lines
+        .find(|l| !l.trim().is_empty())
+        .unwrap_or_else(|| panic!("nothing after {label:?} in {stdout}"))
+        .trim()
+        .to_string()
+}
+
+fn fingerprint_after(stdout: &str, label: &str) -> String {
+    stdout
+        .lines()
+        .find_map(|l| l.strip_prefix(label))
+        .unwrap_or_else(|| panic!("no {label:?} in {stdout}"))
+        .trim()
+        .to_string()
+}
+
+#[test]
+fn two_of_three_ceremony() {
+    let people = ["alice", "bob", "carol"].map(Participant::new);
+    let [alice, bob, carol] = &people;
+    let others = |me: &Participant| -> Vec<&Participant> {
+        people.iter().filter(|p| p.name != me.name).collect()
+    };
+
+    // Each creates their wallet and shares their first round info.
+    let mut round1 = Vec::new();
+    for p in &people {
+        let out = p.run(&["create-wallet", "-m", "2", "-n", "3", "--label", "fund"]);
+        round1.push(blob_after(&out, "Your multisig info"));
+    }
+    assert_eq!(round1[0], "MultisigxV2R1alice");
+
+    // Round 1 with the others' info, which yields the round 2 info.
+    let mut round2 = Vec::new();
+    for (i, p) in people.iter().enumerate() {
+        let mut args = vec!["exchange-keys", "--info"];
+        args.extend(
+            round1
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, b)| b.as_str()),
+        );
+        let out = p.run(&args);
+        assert!(out.contains("More rounds needed"), "{out}");
+        round2.push(blob_after(&out, "Share this info with peers"));
+    }
+
+    // Round 2 finishes the wallet; everyone gets the same address.
+    let mut fingerprints = Vec::new();
+    for (i, p) in people.iter().enumerate() {
+        let mut args = vec!["exchange-keys", "--info"];
+        args.extend(
+            round2
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, b)| b.as_str()),
+        );
+        let out = p.run(&args);
+        assert!(out.contains("Multisig wallet is ready!"), "{out}");
+        fingerprints.push(fingerprint_after(&out, "Address fingerprint:"));
+    }
+    assert!(fingerprints.iter().all(|f| *f == fingerprints[0]));
+
+    // Each reads out the fingerprint to the others and records theirs.
+    for p in &people {
+        let mut args = vec!["verify-address"];
+        for _ in others(p) {
+            args.extend(["--peer-fingerprint", fingerprints[0].as_str()]);
+        }
+        let result = p.run_json(&args);
+        assert_eq!(result["verified"], true, "{}", p.name);
+    }
+
+    // Everyone exports, then imports the others' info.
+    let exports: Vec<String> = people
+        .iter()
+        .map(|p| blob_after(&p.run(&["export-info"]), "Multisig info (share"))
+        .collect();
+    for (i, p) in people.iter().enumerate() {
+        let mut args = vec!["import-info", "--info"];
+        args.extend(
+            exports
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, b)| b.as_str()),
+        );
+        let out = p.run(&args);
+        assert!(out.contains("Imported 2 of 2"), "{out}");
+    }
+
+    // Alice builds and sends the set with its manifest; Bob checks them
+    // against each other before signing, and Alice submits.
+    let manifest = alice.dir.path().join("rent.manifest");
+    let built = alice.run(&[
+        "build-tx",
+        "--manifest-out",
+        manifest.to_str().unwrap(),
+        "--address",
+        DESTINATION,
+        "--amount",
+        "1500000000000",
+        "--note",
+        "rent",
+    ]);
+    let unsigned = blob_after(&built, "Multisig tx set");
+    let set = alice.dir.path().join("rent.txset");
+    std::fs::write(&set, &unsigned).unwrap();
+    let manifest = manifest.to_str().unwrap();
+    let checked = bob.run_json(&[
+        "verify-tx-set",
+        "--tx-in",
+        set.to_str().unwrap(),
+        "--manifest",
+        manifest,
+    ]);
+    assert_eq!(checked["matches"], true, "{checked}");
+
+    // A manifest edited in transit fails its HMAC, and nothing is signed.
+    let edited = bob.dir.path().join("edited.manifest");
+    let text = std::fs::read_to_string(manifest).unwrap();
+    std::fs::write(&edited, text.replace("\"rent\"", "\"bonus\"")).unwrap();
+    let refused = bob.output(
+        &[
+            "sign-tx",
+            "--tx-data",
+            &unsigned,
+            "--manifest",
+            edited.to_str().unwrap(),
+        ],
+        "",
+    );
+    assert!(!refused.status.success());
+    let stderr = String::from_utf8_lossy(&refused.stderr);
+    assert!(stderr.contains("HMAC doesn't match"), "{stderr}");
+    assert_eq!(bob.journal(), Value::Null);
+
+    let signed = bob.run(&["sign-tx", "--tx-data", &unsigned, "--manifest", manifest]);
+    assert!(signed.contains("matches the manifest"), "{signed}");
+    let signed = blob_after(&signed, "Updated tx set");
+    let submitted = alice.answer(&["submit-tx", "--tx-data", &signed], "y\n");
+    assert!(submitted.contains(TX_HASH), "{submitted}");
+
+    let entries = alice.journal();
+    let entry = &entries["entries"][0];
+    assert_eq!(entry["status"], "submitted", "{entries}");
+    assert_eq!(entry["note"], "rent");
+    assert_eq!(entry["tx_hashes"], json!([TX_HASH]));
+    assert_eq!(entry["destinations"][0]["address"], DESTINATION);
+
+    // Bob's copy stops at his signature, which completed the set.
+    let entries = bob.journal();
+    let entry = &entries["entries"][0];
+    assert_eq!(entry["status"], "ready_to_submit", "{entries}");
+    assert_eq!(entry["signatures_collected"], 2);
+    assert_eq!(entry["tx_hashes"], json!([TX_HASH]));
+    // Carol took no part in the spend.
+    assert_eq!(carol.journal(), Value::Null);
+}
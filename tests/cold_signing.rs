@@ -0,0 +1,95 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use common::run;
+
+/// A config whose wallet RPC can't be reached: the cold-signing files must be
+/// refused before anything is sent to it.
+fn setup(dir: &Path) -> PathBuf {
+    let config = dir.join("config.json");
+    std::fs::write(
+        &config,
+        serde_json::json!({
+            "network": "mainnet",
+            "daemon": { "host": "127.0.0.1", "port": 1, "tls": false },
+            "data_dir": dir.join("data"),
+        })
+        .to_string(),
+    )
+    .unwrap();
+    config
+}
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+#[test]
+fn cold_signing_files_are_explained() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = setup(dir.path());
+
+    for (command, name) in [
+        ("sign-tx", "unsigned_monero_tx"),
+        ("sign-tx", "signed_monero_tx"),
+        ("submit-tx", "signed_monero_tx"),
+    ] {
+        let path = fixture(name);
+        let hex = hex::encode(std::fs::read(&path).unwrap());
+        for args in [
+            [command, "--tx-file", path.to_str().unwrap()],
+            [command, "--tx-data", &hex],
+        ] {
+            let output = run(&config, &args, "");
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert!(!output.status.success(), "{args:?}");
+            assert!(
+                stderr.contains(&format!("this is {name}")),
+                "{args:?}: {stderr}"
+            );
+            assert!(stderr.contains("not a multisig tx set"), "{stderr}");
+        }
+    }
+}
+
+#[test]
+fn multisig_tx_set_is_not_mistaken_for_cold_signing() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = setup(dir.path());
+
+    let path = fixture("multisig_monero_tx");
+    let output = run(
+        &config,
+        &["sign-tx", "--tx-file", path.to_str().unwrap()],
+        "",
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // It gets as far as the (unreachable) wallet RPC.
+    assert!(!output.status.success());
+    assert!(!stderr.contains("cold-signing"), "{stderr}");
+}
+
+#[cfg(feature = "cold-sign")]
+#[test]
+fn cold_sign_runs_offline() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = common::config(dir.path(), common::wallet_rpc("MultisigxV2R1info"));
+
+    let path = fixture("unsigned_monero_tx");
+    let output = run(
+        &config,
+        &[
+            "--offline",
+            "cold-sign",
+            "--tx-file",
+            path.to_str().unwrap(),
+        ],
+        "",
+    );
+    let stdout = common::stdout_of(&output);
+    assert!(stdout.contains("Signed 1 transaction(s):"), "{stdout}");
+    assert!(stdout.contains(common::COLD_TX), "{stdout}");
+}
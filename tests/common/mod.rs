@@ -0,0 +1,282 @@
+//! Running the binary, and a fake wallet RPC, for the tests that run it end
+//! to end.
+
+// Each test binary uses its own share of these.
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+/// The binary with `--config config`, without the environment a test run
+/// shouldn't pick up: the log filter and the message language. The fake
+/// wallets have an empty password, given through the environment since
+/// there's no terminal to ask on.
+pub fn command(config: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_monero-multisig"));
+    command
+        .arg("--config")
+        .arg(config)
+        .env_remove("RUST_LOG")
+        .env_remove("MONERO_MULTISIG_LANG")
+        .env("MONERO_MULTISIG_WALLET_PASSWORD", "");
+    command
+}
+
+/// Run the binary with `args`, answering its prompts with `stdin`.
+pub fn run(config: &Path, args: &[&str], stdin: &str) -> Output {
+    let mut child = command(config)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// The stdout of a run that must have succeeded.
+pub fn stdout_of(output: &Output) -> String {
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    assert!(
+        output.status.success(),
+        "{stdout}\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    stdout
+}
+
+/// Serve HTTP on a free local port, answering each request with the JSON
+/// `reply` makes of its path and body. Returns the port.
+pub fn serve<F>(reply: F) -> u16
+where
+    F: Fn(&str, &Value) -> Value + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let reply = Arc::new(reply);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let reply = Arc::clone(&reply);
+            std::thread::spawn(move || {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+                    return;
+                }
+                let path = request_line.split(' ').nth(1).unwrap_or_default();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                        return;
+                    }
+                    let line = line.trim().to_ascii_lowercase();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(value) = line.strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                if reader.read_exact(&mut body).is_err() {
+                    return;
+                }
+                let body: Value = serde_json::from_slice(&body).unwrap_or_default();
+                let reply = reply(path, &body).to_string();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{reply}",
+                    reply.len()
+                );
+            });
+        }
+    });
+    port
+}
+
+pub const REFUND: &str = "4AdUndXHHZ6cfufTMvppY6JwXNouMBzSkbLYfpAV5Usx3skxNgYeYTRj5UzqtReoS44qo9mtmXCqY45DJ852K5Jv2684Rge";
+pub const SELLER: &str = "888tNkZrPN6JsEgekjMnABU4TBzc2Dt29EPAvkRxbANsAnjyPbb3iQ1YBRk1UXcdRsiKc9dhwMVgN5S9cQUiyoogDavup3H";
+pub const ESCROW: &str = "47Y1NtfpLfSSnZrZRcy6BF18VrY5JHibsSkEsbgoTW5z1FU1cKooBSgY92YDdgy8kWDv8bv1YaD8eiC4sysM12M13sAZKHr";
+/// An address of another network, in the fake wallet's address book.
+pub const STAGENET: &str = "53QjBsa9cjxesZJQJfXBV5K2KvBmtgw3WXPzqsENUZzVdKR2wY96911DGendcjpn4dXeYYrf3inwHJ7XhKQNpc8jGPHc2EK";
+/// The transaction that paid the wallet's 10 XMR.
+pub const FUNDING_TX: &str = "c5d9e5a2b1f0e7d6c3b4a5968778695a4b3c2d1e0f1a2b3c4d5e6f708192a3b4";
+/// The transaction `sign_transfer` signs from a cold-signing file.
+pub const COLD_TX: &str = "0f1e2d3c4b5a69788796a5b4c3d2e1f00f1e2d3c4b5a69788796a5b4c3d2e1f0";
+/// This wallet's info for the second round of a setup past 2-of-2.
+pub const NEXT_INFO: &str = "MultisigxV2Rnmyroundtwoinfo";
+
+/// A wallet RPC whose `make_multisig` makes a 2-of-2 multisig wallet at
+/// [`ESCROW`] holding 10 XMR. Answers the methods setting up, syncing and
+/// building call, and anything else with "Method not found". As the daemon,
+/// it has every transaction asked about in its pool.
+pub fn wallet_rpc(info: &'static str) -> u16 {
+    fake_rpc(info, 2, FUNDS)
+}
+
+/// Like [`wallet_rpc`], but for a 2-of-`total` wallet. Past 2-of-2,
+/// `make_multisig` returns [`NEXT_INFO`] and `exchange_multisig_keys`
+/// finishes the setup.
+pub fn group_wallet_rpc(info: &'static str, total: u32) -> u16 {
+    fake_rpc(info, total, FUNDS)
+}
+
+/// Like [`wallet_rpc`], but the wallet has spent everything.
+pub fn empty_wallet_rpc(info: &'static str) -> u16 {
+    fake_rpc(info, 2, 0)
+}
+
+/// The 10 XMR the fake wallet holds, in piconero.
+const FUNDS: u64 = 10_000_000_000_000;
+
+fn fake_rpc(info: &'static str, total: u32, balance: u64) -> u16 {
+    let ready = AtomicBool::new(false);
+    serve(move |path, call| {
+        if path == "/get_transactions" {
+            let txs: Vec<Value> = call["txs_hashes"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|hash| json!({ "tx_hash": hash, "in_pool": true }))
+                .collect();
+            return json!({ "txs": txs, "status": "OK" });
+        }
+        match answer(call, info, total, balance, &ready) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": call["id"], "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": call["id"],
+                "error": { "code": code, "message": message },
+            }),
+        }
+    })
+}
+
+const METHOD_NOT_FOUND: (i64, &str) = (-32601, "Method not found");
+
+fn answer(
+    call: &Value,
+    info: &str,
+    total: u32,
+    balance: u64,
+    ready: &AtomicBool,
+) -> Result<Value, (i64, &'static str)> {
+    let ready_now = ready.load(Ordering::SeqCst);
+    Ok(match call["method"].as_str().ok_or(METHOD_NOT_FOUND)? {
+        "get_version" => json!({ "version": 65562, "release": true }),
+        "sign_transfer" => json!({ "signed_txset": "5369676e6564", "tx_hash_list": [COLD_TX] }),
+        "prepare_multisig" => json!({ "multisig_info": info }),
+        "make_multisig" if total > 2 => json!({ "address": "", "multisig_info": NEXT_INFO }),
+        "make_multisig" | "exchange_multisig_keys" => {
+            ready.store(true, Ordering::SeqCst);
+            json!({ "address": ESCROW, "multisig_info": "" })
+        }
+        "is_multisig" => json!({
+            "multisig": ready_now,
+            "ready": ready_now,
+            "threshold": if ready_now { 2 } else { 0 },
+            "total": if ready_now { total } else { 0 },
+        }),
+        "get_address" => json!({
+            "address": ESCROW,
+            "addresses": [{ "address": ESCROW, "address_index": 0, "label": "", "used": true }],
+        }),
+        "get_address_book" => json!({
+            "entries": [
+                { "address": SELLER, "description": "Seller", "index": 0, "payment_id": "" },
+                { "address": STAGENET, "description": "Faucet", "index": 1, "payment_id": "" },
+            ],
+        }),
+        "add_address_book" => json!({ "index": 1 }),
+        "edit_address_book" => json!({}),
+        "get_height" => json!({ "height": 3_000_000 }),
+        "refresh" => json!({ "blocks_fetched": 0, "received_money": false }),
+        "close_wallet" => json!({}),
+        "get_accounts" => json!({
+            "subaddress_accounts": [{
+                "account_index": 0,
+                "base_address": ESCROW,
+                "balance": balance,
+                "unlocked_balance": balance,
+                "label": "Primary account",
+            }],
+            "total_balance": balance,
+            "total_unlocked_balance": balance,
+        }),
+        "incoming_transfers" => json!({
+            "transfers": [{
+                "amount": 10_000_000_000_000u64,
+                "spent": false,
+                "frozen": false,
+                "unlocked": true,
+                "global_index": 91_234_567,
+                "key_image": "",
+                "subaddr_index": { "major": 0, "minor": 0 },
+                "tx_hash": FUNDING_TX,
+            }],
+        }),
+        "export_multisig_info" => json!({ "info": hex::encode(b"Monero multisig export\x01mine") }),
+        "import_multisig_info" => json!({ "n_outputs": 1 }),
+        "get_transfer_by_txid" if call["params"]["txid"] != FUNDING_TX => {
+            return Err((-8, "Transaction not found."))
+        }
+        "get_transfer_by_txid" => json!({
+            "transfer": {
+                "txid": FUNDING_TX,
+                "type": "in",
+                "amount": 10_000_000_000_000u64,
+                "height": 2_999_990,
+                "confirmations": 10,
+                "unlock_time": 0,
+                "address": ESCROW,
+                "subaddr_index": { "major": 0, "minor": 0 },
+                "note": "funding",
+            },
+        }),
+        "get_balance" => json!({
+            "balance": balance,
+            "unlocked_balance": balance,
+            "multisig_import_needed": false,
+            "per_subaddress": [],
+        }),
+        "transfer" => json!({
+            "amount": 1_000_000_000_000u64,
+            "fee": 30_000_000,
+            "multisig_txset": "6d756c74697369675f7478736574",
+            "tx_hash": "",
+            "tx_key": "",
+            "unsigned_txset": "",
+        }),
+        _ => return Err(METHOD_NOT_FOUND),
+    })
+}
+
+/// A config using the wallet RPC on `port` and a data directory in `dir`.
+pub fn config(dir: &Path, port: u16) -> PathBuf {
+    let path = dir.join("config.json");
+    std::fs::write(
+        &path,
+        json!({
+            "network": "mainnet",
+            "daemon": { "host": "127.0.0.1", "port": port, "tls": false },
+            "data_dir": dir.join("data"),
+        })
+        .to_string(),
+    )
+    .unwrap();
+    path
+}
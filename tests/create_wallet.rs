@@ -0,0 +1,75 @@
+mod common;
+
+use common::{config, run, stdout_of, wallet_rpc};
+
+#[test]
+fn rerun_with_the_same_params_resumes() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = config(dir.path(), wallet_rpc("MultisigxV2R1first"));
+    let created = stdout_of(&run(&first, &["create-wallet", "-m", "2", "-n", "3"], ""));
+    assert!(created.contains("Creating 2-of-3"), "{created}");
+    assert!(created.contains("MultisigxV2R1first"), "{created}");
+
+    // A wallet RPC that would prepare different info isn't asked: the info
+    // from the first run is shown again.
+    let second = config(dir.path(), wallet_rpc("MultisigxV2R1second"));
+    let resumed = stdout_of(&run(&second, &["create-wallet", "-m", "2", "-n", "3"], ""));
+    assert!(
+        resumed.contains("already exists with these parameters (no key exchange round done)"),
+        "{resumed}"
+    );
+    assert!(resumed.contains("MultisigxV2R1first"), "{resumed}");
+    assert!(!resumed.contains("MultisigxV2R1second"), "{resumed}");
+}
+
+#[test]
+fn rerun_with_other_params_is_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), wallet_rpc("MultisigxV2R1info"));
+    stdout_of(&run(&config, &["create-wallet", "-m", "2", "-n", "3"], ""));
+    let state = std::fs::read_to_string(dir.path().join("data/wallet_state.json")).unwrap();
+
+    let refused = run(&config, &["create-wallet", "-m", "2", "-n", "4"], "");
+    assert!(!refused.status.success());
+    let stderr = String::from_utf8_lossy(&refused.stderr);
+    assert!(
+        stderr
+            .contains("as 2-of-3 \"default\", not the 2-of-4 \"default\" asked for — run `reset`"),
+        "{stderr}"
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("data/wallet_state.json")).unwrap(),
+        state
+    );
+}
+
+#[test]
+fn next_steps_follow_the_language() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), wallet_rpc("MultisigxV2R1info"));
+    let created = run(
+        &config,
+        &["--lang", "es", "create-wallet", "-m", "2", "-n", "2"],
+        "",
+    );
+    stdout_of(&created);
+    let stderr = String::from_utf8_lossy(&created.stderr);
+    assert!(
+        stderr.contains(
+            "Siguientes pasos:\n  - envíe su información multisig a sus 1 participante(s)"
+        ),
+        "{stderr}"
+    );
+
+    // An unknown language falls back to English, from the environment too.
+    let status = common::command(&config)
+        .arg("status")
+        .env("MONERO_MULTISIG_LANG", "tlh")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&status.stderr);
+    assert!(
+        stderr.contains("Next steps:\n  - send your multisig info"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,81 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn config(dir: &Path, data_dir: &str) -> std::path::PathBuf {
+    let config = dir.join("config.json");
+    std::fs::write(
+        &config,
+        serde_json::json!({
+            "network": "mainnet",
+            "daemon": { "host": "127.0.0.1", "port": 1, "tls": false },
+            "data_dir": data_dir,
+        })
+        .to_string(),
+    )
+    .unwrap();
+    config
+}
+
+fn journal(config: &Path, args: &[&str], env: &[(&str, &Path)]) -> Output {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_monero-multisig"));
+    cmd.arg("--config")
+        .arg(config)
+        .args(args)
+        .arg("journal")
+        .env_remove("RUST_LOG");
+    for (key, value) in env {
+        cmd.env(key, value);
+    }
+    cmd.output().unwrap()
+}
+
+#[test]
+fn flag_overrides_configured_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let configured = dir.path().join("configured");
+    let config = config(dir.path(), configured.to_str().unwrap());
+    let chosen = dir.path().join("backup/data");
+
+    let output = journal(&config, &["--data-dir", chosen.to_str().unwrap()], &[]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(chosen.join(".lock").exists());
+    assert!(!configured.exists());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&chosen).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+    }
+}
+
+#[test]
+fn configured_dir_expands_variables() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), "${MULTISIG_TEST_ROOT}/data");
+
+    let output = journal(&config, &[], &[("MULTISIG_TEST_ROOT", dir.path())]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(dir.path().join("data/.lock").exists());
+}
+
+#[test]
+fn unusable_dir_is_named() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("not-a-dir");
+    std::fs::write(&file, "").unwrap();
+    let config = config(dir.path(), file.to_str().unwrap());
+
+    let output = journal(&config, &[], &[]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&file.display().to_string()), "{stderr}");
+    assert!(stderr.contains("not a directory"), "{stderr}");
+}
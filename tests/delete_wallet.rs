@@ -0,0 +1,63 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use common::{config, empty_wallet_rpc, run, wallet_rpc, ESCROW};
+use serde_json::Value;
+
+/// A finished 2-of-2 wallet labelled "vault" on the wallet RPC on `port`.
+fn ready_wallet(dir: &Path, port: u16) -> PathBuf {
+    let config = config(dir, port);
+    for args in [
+        &["create-wallet", "-m", "2", "-n", "2", "--label", "vault"][..],
+        &["exchange-keys", "--info", "MultisigxV2R1peerinfo"],
+    ] {
+        let output = run(&config, args, "");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    config
+}
+
+#[test]
+fn delete_wallet_archives_a_record_and_removes_the_state() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = ready_wallet(dir.path(), empty_wallet_rpc("MultisigxV2R1info"));
+    let data_dir = dir.path().join("data");
+    assert!(data_dir.join("wallet_state.json").exists());
+
+    let output = run(&config, &["delete-wallet", "--label", "vault"], "vault\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(!data_dir.join("wallet_state.json").exists());
+    assert!(!data_dir.join("journal.json").exists());
+
+    let archived: Vec<_> = std::fs::read_dir(data_dir.join("archive"))
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    assert_eq!(archived.len(), 1);
+    let name = archived[0].file_name().unwrap().to_string_lossy();
+    assert!(name.starts_with("vault-"), "{name}");
+    let tombstone: Value =
+        serde_json::from_slice(&std::fs::read(archived[0].join("tombstone.json")).unwrap())
+            .unwrap();
+    assert_eq!(tombstone["label"], "vault");
+    assert_eq!(tombstone["address"], ESCROW);
+    assert_eq!(tombstone["transactions"], 0);
+}
+
+#[test]
+fn delete_wallet_refuses_a_wallet_holding_funds() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = ready_wallet(dir.path(), wallet_rpc("MultisigxV2R1info"));
+    let data_dir = dir.path().join("data");
+
+    let output = run(&config, &["delete-wallet", "--label", "vault"], "vault\n");
+    assert!(!output.status.success());
+    assert!(data_dir.join("wallet_state.json").exists());
+    assert!(!data_dir.join("archive").exists());
+}
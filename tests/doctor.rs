@@ -0,0 +1,61 @@
+mod common;
+
+use common::{config, run, wallet_rpc};
+use serde_json::Value;
+
+#[test]
+fn latency_times_every_method() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), wallet_rpc("MultisigxV2R1info"));
+    let output = run(&config, &["--output", "json", "doctor", "--latency"], "");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let envelope: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let report = &envelope["result"];
+    assert_eq!(report["iterations"], 10);
+    let methods: Vec<&str> = report["methods"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["method"].as_str().unwrap())
+        .collect();
+    assert_eq!(methods, ["get_version", "get_height", "get_balance"]);
+    assert_eq!(report["methods"][2]["budget_ms"], 2000);
+    // The fake wallet RPC answers no daemon calls.
+    assert_eq!(report["daemon_synchronized"], Value::Null);
+    assert_eq!(report["causes"], serde_json::json!([]));
+
+    let text = run(&config, &["doctor"], "");
+    let stdout = String::from_utf8_lossy(&text.stdout);
+    assert!(
+        stdout.contains("Every call is within its budget."),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn slow_commands_point_at_doctor() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), wallet_rpc("MultisigxV2R1info"));
+    let quiet = run(&config, &["get-version"], "");
+    let stderr = String::from_utf8_lossy(&quiet.stderr);
+    assert!(!stderr.contains("doctor --latency"), "{stderr}");
+
+    // With no time allowed at all, any RPC call is too slow.
+    let mut file: Value = serde_json::from_str(&std::fs::read_to_string(&config).unwrap()).unwrap();
+    file["latency"] = serde_json::json!({ "command_total_ms": 0 });
+    std::fs::write(&config, file.to_string()).unwrap();
+    let slow = run(&config, &["get-version"], "");
+    assert!(slow.status.success());
+    let stderr = String::from_utf8_lossy(&slow.stderr);
+    assert!(
+        stderr.contains("in all; run doctor --latency to find out why"),
+        "{stderr}"
+    );
+    let doctor = run(&config, &["doctor"], "");
+    let stderr = String::from_utf8_lossy(&doctor.stderr);
+    assert!(!stderr.contains("run doctor --latency"), "{stderr}");
+}
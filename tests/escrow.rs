@@ -0,0 +1,78 @@
+mod common;
+
+use common::{config, run, stdout_of, wallet_rpc, REFUND, SELLER};
+use serde_json::{json, Value};
+
+fn invite(stdout: &str) -> String {
+    stdout
+        .lines()
+        .find(|line| line.starts_with("MoneroEscrowV1:"))
+        .unwrap_or_else(|| panic!("no invite in {stdout}"))
+        .to_string()
+}
+
+#[test]
+fn escrow_happy_path() {
+    let buyer_dir = tempfile::tempdir().unwrap();
+    let seller_dir = tempfile::tempdir().unwrap();
+    let buyer = config(buyer_dir.path(), wallet_rpc("MultisigxV2R1buyerinfo"));
+    let seller = config(seller_dir.path(), wallet_rpc("MultisigxV2R1sellerinfo"));
+
+    let init = ["escrow", "init", "--refund-address", REFUND, "--role"];
+    let buyer_invite = invite(&stdout_of(&run(
+        &buyer,
+        &[&init[..], &["buyer"]].concat(),
+        "",
+    )));
+    let seller_invite = invite(&stdout_of(&run(
+        &seller,
+        &[&init[..], &["seller"]].concat(),
+        "",
+    )));
+    assert!(buyer_invite.starts_with(&format!("MoneroEscrowV1:buyer:{REFUND}:")));
+
+    // Joining with your own invite mixes up the roles.
+    let output = run(
+        &buyer,
+        &["escrow", "join", "--peer-info", &buyer_invite],
+        "",
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("also from the buyer"), "{stderr}");
+
+    let joined = stdout_of(&run(
+        &buyer,
+        &["escrow", "join", "--peer-info", &seller_invite],
+        "",
+    ));
+    assert!(joined.contains("Multisig wallet is ready!"), "{joined}");
+    let state = std::fs::read_to_string(buyer_dir.path().join("data/wallet_state.json")).unwrap();
+    let state: Value = serde_json::from_str(&state).unwrap();
+    assert_eq!(
+        state["escrow"],
+        json!({ "role": "buyer", "refund_address": REFUND })
+    );
+
+    let release = [
+        "escrow",
+        "release",
+        "--to",
+        SELLER,
+        "--amount",
+        "1",
+        "--allow-stale-sync",
+    ];
+    let built = stdout_of(&run(&buyer, &release, ""));
+    assert!(built.contains("6d756c74697369675f7478736574"), "{built}");
+
+    let refund = ["escrow", "refund", "--amount", "1", "--allow-stale-sync"];
+    let built = stdout_of(&run(&buyer, &refund, ""));
+    assert!(built.contains("6d756c74697369675f7478736574"), "{built}");
+
+    // Anywhere but the registered address takes --override.
+    let output = run(&buyer, &[&refund[..], &["--to", SELLER]].concat(), "");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("needs --override"), "{stderr}");
+}
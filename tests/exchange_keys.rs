@@ -0,0 +1,113 @@
+mod common;
+
+use std::path::Path;
+
+use common::{config, group_wallet_rpc, run, NEXT_INFO};
+
+/// A config for a 2-of-3 wallet before any key exchange round.
+fn new_wallet(dir: &Path) -> std::path::PathBuf {
+    let config = config(dir, group_wallet_rpc("MultisigxV2R1myinfo", 3));
+    let output = run(&config, &["create-wallet", "-m", "2", "-n", "3"], "");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    config
+}
+
+#[test]
+fn interactive_rounds_run_to_completion() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = new_wallet(dir.path());
+
+    // A mangled blob and our own info sent back are both asked for again.
+    let answers = format!(
+        "MultisigxV2R1bob!\nMultisigxV2R1bob\nMultisigxV2R1carol\n\
+         {NEXT_INFO}\nMultisigxV2Rnbob\nMultisigxV2Rncarol\n"
+    );
+    let output = run(
+        &config,
+        &["exchange-keys", "--interactive-rounds"],
+        &answers,
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stdout}\n{stderr}");
+    assert!(
+        stderr.contains("Round 1, peer info #1 (2 still expected):"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("Round 2, peer info #2 (1 still expected):"),
+        "{stderr}"
+    );
+    assert_eq!(stderr.matches("Rejected:").count(), 2, "{stderr}");
+    assert!(stdout.contains(NEXT_INFO), "{stdout}");
+    assert!(stdout.contains("Multisig wallet is ready!"), "{stdout}");
+}
+
+#[test]
+fn interactive_rounds_can_be_resumed_per_round() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = new_wallet(dir.path());
+
+    // Input ends after the first round.
+    let output = run(
+        &config,
+        &["exchange-keys", "--interactive-rounds"],
+        "MultisigxV2R1bob\nMultisigxV2R1carol\nMultisigxV2Rnbob\n",
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("input ended before round 2's peer info was complete"),
+        "{stderr}"
+    );
+
+    let output = run(
+        &config,
+        &[
+            "exchange-keys",
+            "--info",
+            "MultisigxV2Rnbob",
+            "MultisigxV2Rncarol",
+        ],
+        "",
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{stdout}\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        stdout.contains("Performing key exchange round 2"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("Multisig wallet is ready!"), "{stdout}");
+}
+
+#[test]
+fn no_password_and_no_terminal_is_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = new_wallet(dir.path());
+
+    let output = common::command(&config)
+        .env_remove("MONERO_MULTISIG_WALLET_PASSWORD")
+        .args([
+            "exchange-keys",
+            "--info",
+            "MultisigxV2R1bob",
+            "--info",
+            "MultisigxV2R1carol",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("no wallet password for \"") && stderr.contains("no terminal to ask on"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,34 @@
+//! The core API without default features: run with
+//! `cargo test --no-default-features --test feature_matrix`. Only items
+//! that no feature gates may be used here.
+
+use monero_multisig::config::{Config, DaemonRpc, RpcClient};
+use monero_multisig::error::MultisigError;
+use monero_multisig::transaction::{self, Amount, Destination};
+use monero_multisig::wallet::{self, MultisigParams};
+
+#[test]
+fn core_api_builds_without_default_features() {
+    let config = Config::default();
+    let rpc = RpcClient::new(&DaemonRpc {
+        port: 1,
+        ..DaemonRpc::default()
+    });
+    assert!(rpc.url().starts_with("http://"));
+    assert!(!config.data_dir.as_os_str().is_empty());
+
+    let params = MultisigParams::new(2, 3, "fund".into()).unwrap();
+    assert_eq!(
+        wallet::key_exchange_rounds(params.threshold, params.total),
+        2
+    );
+
+    let destination = Destination::new_unchecked("4dest", Amount(1));
+    assert_eq!(
+        transaction::format_xmr(destination.amount().0),
+        "0.000000000001"
+    );
+
+    let _: fn(MultisigError) -> String = |e| e.to_string();
+    assert!(!monero_multisig::metrics::render().is_empty());
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use std::path::Path;
+
+use common::{config, run, stdout_of, wallet_rpc};
+use serde_json::Value;
+
+fn inspect(config: &Path, file: &Path) -> Value {
+    let stdout = stdout_of(&run(
+        config,
+        &[
+            "--output",
+            "json",
+            "inspect-info",
+            "--in",
+            file.to_str().unwrap(),
+        ],
+        "",
+    ));
+    let envelope: Value = serde_json::from_str(&stdout).unwrap();
+    envelope["result"].clone()
+}
+
+#[test]
+fn export_header_shows_in_inspect_and_import() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), wallet_rpc("MultisigxV2R1info"));
+    for args in [
+        &["create-wallet", "-m", "2", "-n", "2"][..],
+        &["exchange-keys", "--info", "MultisigxV2R1peerinfo"],
+    ] {
+        stdout_of(&run(&config, args, ""));
+    }
+
+    let exported = stdout_of(&run(
+        &config,
+        &["--armor", "export-info", "--from", "alice"],
+        "",
+    ));
+    assert!(
+        exported.contains("Height: 3000000\nFrom: alice\n"),
+        "{exported}"
+    );
+    let ours = dir.path().join("ours.txt");
+    std::fs::write(&ours, &exported).unwrap();
+    let result = inspect(&config, &ours);
+    assert_eq!(result["kind"], "multisig_info");
+    assert_eq!(result["armored"], true);
+    assert_eq!(result["export_height"], 3_000_000);
+    assert_eq!(result["from"], "alice");
+    assert_eq!(result["status"], "own_export");
+
+    // A peer's export from well before this wallet's height.
+    let blob = hex::encode(b"Monero multisig export\x01bob's");
+    let armored = format!(
+        "-----BEGIN MONERO MULTISIG INFO-----\nCreated: 2026-10-01T08:00:00Z\n\
+         Height: 2990000\nFrom: bob\n\n{blob}\n-----END MONERO MULTISIG INFO-----\n"
+    );
+    let theirs = dir.path().join("theirs.txt");
+    std::fs::write(&theirs, &armored).unwrap();
+    let result = inspect(&config, &theirs);
+    assert_eq!(result["status"], "new");
+    assert_eq!(result["created_at"], "2026-10-01T08:00:00Z");
+    assert_eq!(result["wallet_height"], Value::Null);
+
+    let imported = stdout_of(&run(
+        &config,
+        &["import-info", &format!("--info={armored}")],
+        "",
+    ));
+    assert!(
+        imported.contains("exported at height 2990000, 10000 blocks behind"),
+        "{imported}"
+    );
+    assert!(imported.contains("ask bob to export again"), "{imported}");
+    let result = inspect(&config, &theirs);
+    assert_eq!(result["status"], "imported");
+    assert_eq!(result["wallet_height"], 3_000_000);
+    assert_eq!(result["blocks_behind"], 10_000);
+    assert_eq!(result["stale"], true);
+
+    // Raw blobs from other tools still import, with nothing to warn about.
+    let raw = hex::encode(b"Monero multisig export\x01carol's");
+    let imported = stdout_of(&run(&config, &["import-info", "--info", &raw], ""));
+    assert!(!imported.contains("warning"), "{imported}");
+    let raw_file = dir.path().join("raw.txt");
+    std::fs::write(&raw_file, &raw).unwrap();
+    let text = stdout_of(&run(
+        &config,
+        &["inspect-info", "--in", raw_file.to_str().unwrap()],
+        "",
+    ));
+    assert!(
+        text.contains("Kind:         multisig info (for import-info) (raw)"),
+        "{text}"
+    );
+    assert!(text.contains("Exported:     unknown"), "{text}");
+    assert!(text.contains("Imported:     yes"), "{text}");
+}
+
+#[test]
+fn unchanged_wallet_reprints_the_last_export() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), wallet_rpc("MultisigxV2R1info"));
+    for args in [
+        &["create-wallet", "-m", "2", "-n", "2"][..],
+        &["exchange-keys", "--info", "MultisigxV2R1peerinfo"],
+    ] {
+        stdout_of(&run(&config, args, ""));
+    }
+
+    let export = ["--armor", "export-info", "--from", "alice"];
+    let output = run(&config, &export, "");
+    let first = stdout_of(&output);
+    assert!(!first.contains("unchanged"), "{first}");
+    // The address was never verified, and the peer's export is still due.
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "Next steps:\n  - compare the wallet address with your 1 peer(s) using \
+             verify-address\n  - run import-info with the exports you get back"
+        ),
+        "{stderr}"
+    );
+    let again = stdout_of(&run(&config, &export, ""));
+    assert!(
+        again.contains("(unchanged since height 3000000; --force exports again)"),
+        "{again}"
+    );
+    // The armor, export time included, is the same, so peers who already
+    // imported it skip it.
+    let armor = |out: &str| out.split("\nInfo fingerprint").next().unwrap().to_string();
+    assert_eq!(armor(&again), armor(&first));
+
+    let forced = stdout_of(&run(&config, &["export-info", "--force"], ""));
+    assert!(!forced.contains("unchanged"), "{forced}");
+}
+
+#[test]
+fn export_header_names_no_one_unless_asked() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), wallet_rpc("MultisigxV2R1info"));
+    for args in [
+        &["create-wallet", "-m", "2", "-n", "2"][..],
+        &["exchange-keys", "--info", "MultisigxV2R1peerinfo"],
+    ] {
+        stdout_of(&run(&config, args, ""));
+    }
+
+    let exported = stdout_of(&run(&config, &["--armor", "export-info"], ""));
+    assert!(exported.contains("Height: 3000000\n"), "{exported}");
+    assert!(!exported.contains("From:"), "{exported}");
+}
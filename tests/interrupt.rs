@@ -0,0 +1,46 @@
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+#[test]
+fn ctrl_c_stops_monitor_between_polls() {
+    // Nothing listens on port 1, so each poll fails quickly and the monitor
+    // spends its time in the hour-long sleep.
+    let mut child = Command::new(env!("CARGO_BIN_EXE_monero-multisig"))
+        .args([
+            "--daemon-port",
+            "1",
+            "monitor",
+            "--include-existing",
+            "--interval",
+            "1h",
+        ])
+        .env_remove("RUST_LOG")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(500));
+
+    let sent = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(sent.success());
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        assert!(
+            started.elapsed() < Duration::from_secs(10),
+            "monitor did not stop"
+        );
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    assert_eq!(status.code(), Some(130));
+
+    let output = child.wait_with_output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("press Ctrl+C again"), "{stderr}");
+}
@@ -0,0 +1,135 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Child, Command, Output, Stdio};
+use std::time::{Duration, Instant};
+
+/// A data directory holding an unsigned state file, plus a config pointing
+/// at it, so `state rehash` stops at its confirmation prompt.
+fn setup(dir: &Path) -> std::path::PathBuf {
+    let data_dir = dir.join("data");
+    std::fs::create_dir_all(&data_dir).unwrap();
+    std::fs::write(
+        data_dir.join("wallet_state.json"),
+        serde_json::json!({
+            "Ready": {
+                "wallet_path": "w",
+                "address": "4AdUndXHHZ6cfufTMvppY6JwXNouMBzSkbLYfpAV5Usx",
+                "params": { "threshold": 2, "total": 3, "label": "test" },
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let config = dir.join("config.json");
+    std::fs::write(
+        &config,
+        serde_json::json!({
+            "network": "mainnet",
+            "daemon": { "host": "127.0.0.1", "port": 1, "tls": false },
+            "data_dir": data_dir,
+        })
+        .to_string(),
+    )
+    .unwrap();
+    config
+}
+
+fn bin(config: &Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_monero-multisig"));
+    cmd.arg("--config").arg(config).env_remove("RUST_LOG");
+    cmd
+}
+
+/// Start `state rehash` and wait until it holds the exclusive lock.
+fn hold_lock(dir: &Path, config: &Path) -> Child {
+    let child = bin(config)
+        .args(["state", "rehash"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let owner = format!("pid {} ", child.id());
+    let started = Instant::now();
+    while !std::fs::read_to_string(dir.join("data/.lock"))
+        .unwrap_or_default()
+        .starts_with(&owner)
+    {
+        assert!(
+            started.elapsed() < Duration::from_secs(10),
+            "lock never taken"
+        );
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    child
+}
+
+fn journal(config: &Path) -> Output {
+    bin(config)
+        .args(["--lock-timeout", "1", "journal"])
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn second_invocation_waits_for_the_first() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = setup(dir.path());
+    let mut holder = hold_lock(dir.path(), &config);
+
+    let blocked = journal(&config);
+    assert!(!blocked.status.success());
+    let stderr = String::from_utf8_lossy(&blocked.stderr);
+    assert!(stderr.contains(&format!("pid {}", holder.id())), "{stderr}");
+    assert!(stderr.contains("(state)"), "{stderr}");
+
+    holder.stdin.take().unwrap().write_all(b"n\n").unwrap();
+    holder.wait().unwrap();
+
+    let output = journal(&config);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn lock_released_when_holder_is_killed() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = setup(dir.path());
+    let mut holder = hold_lock(dir.path(), &config);
+
+    holder.kill().unwrap();
+    holder.wait().unwrap();
+
+    let output = journal(&config);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn incoming_watch_takes_no_lock() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = setup(dir.path());
+    let mut holder = hold_lock(dir.path(), &config);
+
+    // It gets past the lock to the (unreachable) wallet RPC.
+    let output = bin(&config)
+        .args(["--lock-timeout", "1", "incoming", "--watch"])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        !stderr.contains(&format!("pid {}", holder.id())),
+        "{stderr}"
+    );
+
+    holder.kill().unwrap();
+    holder.wait().unwrap();
+}
@@ -0,0 +1,47 @@
+use std::process::Command;
+
+#[test]
+fn log_file_receives_json_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    let log_path = dir.path().join("audit.log");
+
+    // Nothing listens on port 1, so the RPC call fails, but the command and
+    // the failed call must still be recorded.
+    let status = Command::new(env!("CARGO_BIN_EXE_monero-multisig"))
+        .args(["--daemon-port", "1", "--log-file"])
+        .arg(&log_path)
+        .arg("balance")
+        .env_remove("RUST_LOG")
+        .output()
+        .unwrap()
+        .status;
+    assert!(!status.success());
+
+    let lines: Vec<serde_json::Value> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|p| {
+            p.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("audit.log")
+        })
+        .flat_map(|p| {
+            std::fs::read_to_string(p)
+                .unwrap()
+                .lines()
+                .map(|l| serde_json::from_str(l).expect("log line is valid JSON"))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    assert!(!lines.is_empty(), "no log lines written");
+    let rpc_line = lines
+        .iter()
+        .find(|l| l["fields"]["rpc_method"] == "get_balance")
+        .expect("rpc call was logged");
+    assert!(rpc_line["timestamp"].is_string());
+    assert!(rpc_line["level"].is_string());
+    assert!(rpc_line["fields"]["duration_ms"].is_u64());
+    assert_eq!(rpc_line["span"]["command"], "balance");
+}
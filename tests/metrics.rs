@@ -0,0 +1,76 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Fetch `/metrics` over a plain socket, retrying until the server is up.
+fn scrape(port: u16) -> String {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        if let Ok(mut stream) = TcpStream::connect(("127.0.0.1", port)) {
+            stream
+                .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            return response;
+        }
+        assert!(Instant::now() < deadline, "metrics endpoint never came up");
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[test]
+fn monitor_serves_metrics() {
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    // Nothing listens on port 1, so every poll fails; the monitor keeps
+    // running and counts the failed calls.
+    let mut child = Command::new(env!("CARGO_BIN_EXE_monero-multisig"))
+        .args([
+            "--daemon-port",
+            "1",
+            "monitor",
+            "--include-existing",
+            "--interval",
+            "1s",
+        ])
+        .args(["--metrics-addr", &format!("127.0.0.1:{port}")])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let mut response = scrape(port);
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !response.contains("method=\"get_transfers\"") && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(200));
+        response = scrape(port);
+    }
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+    for name in [
+        "multisig_rpc_requests_total",
+        "multisig_rpc_duration_seconds",
+        "multisig_key_exchange_rounds_total",
+        "multisig_tx_sets_total",
+        "multisig_webhook_failures_total",
+        "multisig_wallet_height_gap_blocks",
+    ] {
+        assert!(
+            response.contains(&format!("# TYPE {name} ")),
+            "missing {name}"
+        );
+    }
+    assert!(
+        response
+            .contains("multisig_rpc_requests_total{method=\"get_transfers\",outcome=\"error\"}"),
+        "{response}"
+    );
+}
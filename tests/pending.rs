@@ -0,0 +1,41 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn prune_refuses_an_age_older_than_any_date() {
+    let dir = tempfile::tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    std::fs::create_dir_all(&data_dir).unwrap();
+    std::fs::write(data_dir.join("journal.json"), r#"{"entries":[]}"#).unwrap();
+    let config = dir.path().join("config.json");
+    std::fs::write(
+        &config,
+        serde_json::json!({
+            "network": "mainnet",
+            "daemon": { "host": "127.0.0.1", "port": 1, "tls": false },
+            "data_dir": data_dir,
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let output = run(
+        &config,
+        &["pending", "prune", "--older-than", "100000000w"],
+        "",
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    assert!(
+        stderr.contains("--older-than reaches back further than any date"),
+        "{stderr}"
+    );
+
+    let output = run(&config, &["pending", "prune", "--older-than", "30d"], "");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
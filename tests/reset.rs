@@ -0,0 +1,85 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use common::run;
+
+/// A data directory part-way through setup, with an empty journal, plus a
+/// config pointing at it.
+fn setup(dir: &Path) -> (PathBuf, PathBuf) {
+    let data_dir = dir.join("data");
+    std::fs::create_dir_all(&data_dir).unwrap();
+    std::fs::write(
+        data_dir.join("wallet_state.json"),
+        serde_json::json!({
+            "KeyExchangeInProgress": {
+                "wallet_path": "w",
+                "params": { "threshold": 3, "total": 3, "label": "vault" },
+                "rounds_completed": 1,
+                "rounds_required": 2,
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    std::fs::write(data_dir.join("journal.json"), r#"{"entries":[]}"#).unwrap();
+
+    let config = dir.join("config.json");
+    std::fs::write(
+        &config,
+        serde_json::json!({
+            "network": "mainnet",
+            "daemon": { "host": "127.0.0.1", "port": 1, "tls": false },
+            "data_dir": data_dir,
+        })
+        .to_string(),
+    )
+    .unwrap();
+    (config, data_dir)
+}
+
+#[test]
+fn reset_moves_state_to_trash() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, data_dir) = setup(dir.path());
+
+    let output = run(&config, &["reset"], "vault\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(
+        stderr.contains("\"vault\" (1 key exchange round(s) done)"),
+        "{stderr}"
+    );
+    assert!(!data_dir.join("wallet_state.json").exists());
+    assert!(!data_dir.join("journal.json").exists());
+
+    let backups: Vec<_> = std::fs::read_dir(data_dir.join("trash"))
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    assert_eq!(backups.len(), 1);
+    assert!(backups[0].join("wallet_state.json").exists());
+    assert!(backups[0].join("journal.json").exists());
+}
+
+#[test]
+fn reset_needs_the_label_typed() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, data_dir) = setup(dir.path());
+
+    let output = run(&config, &["reset"], "yes\n");
+    assert!(!output.status.success());
+    assert!(data_dir.join("wallet_state.json").exists());
+    assert!(!data_dir.join("trash").exists());
+}
+
+#[test]
+fn create_wallet_refuses_leftover_state() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, _) = setup(dir.path());
+
+    let output = run(&config, &["create-wallet", "-m", "2", "-n", "3"], "");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("run `reset` to start over"), "{stderr}");
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::{config, run, wallet_rpc, FUNDING_TX};
+use serde_json::Value;
+
+#[test]
+fn shows_a_transfer_the_wallet_knows() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), wallet_rpc("MultisigxV2R1info"));
+
+    let output = run(
+        &config,
+        &["--output", "json", "show-tx", "--tx-hash", FUNDING_TX],
+        "",
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let envelope: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let result = &envelope["result"];
+    assert_eq!(envelope["command"], "show-tx");
+    assert_eq!(result["transfer"]["type"], "in");
+    assert_eq!(result["transfer"]["confirmations"], 10);
+    assert_eq!(result["note"], "funding");
+    // Only a transaction the wallet doesn't know is looked up on the daemon.
+    assert_eq!(result["visibility"], Value::Null);
+
+    let text = run(&config, &["show-tx", "--tx-hash", FUNDING_TX], "");
+    let stdout = String::from_utf8_lossy(&text.stdout);
+    assert!(stdout.contains("Direction:     in"), "{stdout}");
+    assert!(
+        stdout.contains("Height:        2999990 (10 confirmation(s))"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("Subaddress:    0/0"), "{stdout}");
+    assert!(stdout.contains("Note:          funding"), "{stdout}");
+}
+
+#[test]
+fn asks_the_daemon_about_a_transaction_the_wallet_doesnt_know() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), wallet_rpc("MultisigxV2R1info"));
+    let unknown = "ab".repeat(32);
+
+    let output = run(
+        &config,
+        &["--output", "json", "show-tx", "--tx-hash", &unknown],
+        "",
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let envelope: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let result = &envelope["result"];
+    assert_eq!(result["transfer"], Value::Null);
+    assert_eq!(result["note"], Value::Null);
+    assert_eq!(result["visibility"], "in_pool");
+
+    let text = run(&config, &["show-tx", "--tx-hash", &unknown], "");
+    let stdout = String::from_utf8_lossy(&text.stdout);
+    assert!(stdout.contains("Not in this wallet."), "{stdout}");
+    assert!(
+        stdout.contains("Daemon:        confirmed in pool"),
+        "{stdout}"
+    );
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use std::path::Path;
+
+use common::{run, serve, ESCROW, SELLER};
+use serde_json::{json, Value};
+
+const TX_HASH: &str = "c3f5a1b2d4e6f8091a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f7081";
+
+/// A multisig wallet RPC that decodes every set as 1 XMR to the seller and
+/// signs it.
+fn signing_rpc() -> u16 {
+    fn result(call: &Value) -> Value {
+        match call["method"].as_str().unwrap_or_default() {
+            "get_accounts" => json!({
+                "subaddress_accounts": [{
+                    "account_index": 0,
+                    "base_address": ESCROW,
+                    "balance": 0,
+                    "unlocked_balance": 0,
+                    "label": "Primary account",
+                }],
+                "total_balance": 0,
+                "total_unlocked_balance": 0,
+            }),
+            "get_address" => json!({
+                "address": ESCROW,
+                "addresses": [{ "address": ESCROW, "address_index": 0, "label": "", "used": true }],
+            }),
+            "describe_transfer" => json!({
+                "desc": [{
+                    "recipients": [{ "address": SELLER, "amount": 1_000_000_000_000u64 }],
+                    "amount_in": 2_000_000_000_000u64,
+                    "amount_out": 1_999_970_000_000u64,
+                    "change_amount": 999_970_000_000u64,
+                    "change_address": ESCROW,
+                    "fee": 30_000_000,
+                    "ring_size": 16,
+                    "unlock_time": 0,
+                }],
+            }),
+            "sign_multisig" => json!({ "tx_data_hex": "7369676e6564", "tx_hash_list": [TX_HASH] }),
+            _ => Value::Null,
+        }
+    }
+    let reply = |call: &Value| {
+        let result = result(call);
+        if result.is_null() {
+            json!({ "jsonrpc": "2.0", "id": call["id"], "error": { "code": -32601, "message": "Method not found" } })
+        } else {
+            json!({ "jsonrpc": "2.0", "id": call["id"], "result": result })
+        }
+    };
+    serve(move |_, body| match body.as_array() {
+        Some(calls) => calls.iter().map(reply).collect(),
+        None => reply(body),
+    })
+}
+
+fn setup(dir: &Path) -> std::path::PathBuf {
+    let config = common::config(dir, signing_rpc());
+    let sets = dir.join("sets");
+    std::fs::create_dir_all(&sets).unwrap();
+    std::fs::write(sets.join("rent.txset"), "72656e74").unwrap();
+    std::fs::write(sets.join("payroll.txset"), "706179726f6c6c").unwrap();
+    config
+}
+
+#[test]
+fn json_output_is_only_the_result() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = setup(dir.path());
+    let sets = dir.path().join("sets");
+    let sets = sets.to_str().unwrap();
+
+    // Nothing can be asked with JSON output, so it needs --yes.
+    let refused = run(
+        &config,
+        &[
+            "--output",
+            "json",
+            "sign-batch",
+            "--in-dir",
+            sets,
+            "--allow-sweep",
+        ],
+        "y\n",
+    );
+    assert!(!refused.status.success());
+    let stderr = String::from_utf8_lossy(&refused.stderr);
+    assert!(
+        stderr.contains("sign-batch with --output json needs --yes"),
+        "{stderr}"
+    );
+    assert!(refused.stdout.is_empty());
+
+    let output = run(
+        &config,
+        &[
+            "--output",
+            "json",
+            "sign-batch",
+            "--in-dir",
+            sets,
+            "--allow-sweep",
+            "--yes",
+        ],
+        "",
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{stdout}\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: Value = serde_json::from_str(&stdout).unwrap_or_else(|e| panic!("{e}: {stdout}"));
+    assert_eq!(
+        result["result"]["signed"].as_array().map(Vec::len),
+        Some(2),
+        "{stdout}"
+    );
+}